@@ -0,0 +1,153 @@
+//! Helpers shared by the ERC-8004 registry programs
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+/// Commit to a set of registry accounts as a single SHA-256 hash
+///
+/// Canonical form, reproducible off-chain from the raw accounts:
+/// - accounts are sorted by address (ascending bytes), so input order is irrelevant
+/// - leaf = sha256(address || account data), data as stored (discriminator included)
+/// - commitment = sha256(domain || agent_id (u64 LE)
+///   || account count (u32 LE) || leaf_0 || ... || leaf_n-1)
+///
+/// Each registry passes its own `domain`, so commitments never collide across
+/// registries.
+pub fn state_commitment(domain: &[u8], agent_id: u64, mut accounts: Vec<(Pubkey, Vec<u8>)>) -> [u8; 32] {
+    accounts.sort_by_key(|a| a.0);
+
+    let leaves: Vec<[u8; 32]> = accounts
+        .iter()
+        .map(|(address, data)| hashv(&[address.as_ref(), data]).to_bytes())
+        .collect();
+
+    let agent_id = agent_id.to_le_bytes();
+    let count = (leaves.len() as u32).to_le_bytes();
+    let mut parts: Vec<&[u8]> = vec![domain, &agent_id, &count];
+    parts.extend(leaves.iter().map(|leaf| leaf.as_ref()));
+
+    hashv(&parts).to_bytes()
+}
+
+/// Create a PDA account of `space` bytes owned by `owner`, paid by `payer`
+///
+/// Like Anchor's `init`, an address that was pre-funded (e.g. to block
+/// `create_account`) is topped up to rent exemption, then allocated and assigned.
+pub fn create_pda_account<'info>(
+    payer: &AccountInfo<'info>,
+    target: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    owner: &Pubkey,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let rent_exempt = Rent::get()?.minimum_balance(space);
+    let current = target.lamports();
+
+    if current == 0 {
+        return anchor_lang::system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                anchor_lang::system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: target.clone(),
+                },
+                &[signer_seeds],
+            ),
+            rent_exempt,
+            space as u64,
+            owner,
+        );
+    }
+
+    let shortfall = rent_exempt.saturating_sub(current);
+    if shortfall > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.clone(),
+                    to: target.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    anchor_lang::system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            anchor_lang::system_program::Allocate {
+                account_to_allocate: target.clone(),
+            },
+            &[signer_seeds],
+        ),
+        space as u64,
+    )?;
+    anchor_lang::system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            anchor_lang::system_program::Assign {
+                account_to_assign: target.clone(),
+            },
+            &[signer_seeds],
+        ),
+        owner,
+    )
+}
+
+/// Grow `account` to `new_len` bytes, `payer` topping it up to rent exemption
+pub fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    let shortfall = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+    if shortfall > 0 {
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                anchor_lang::system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    account.resize(new_len)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOMAIN: &[u8] = b"erc8004:test:state:v1";
+
+    #[test]
+    fn test_state_commitment_ignores_account_order() {
+        let a = (Pubkey::new_from_array([1; 32]), vec![1, 2, 3]);
+        let b = (Pubkey::new_from_array([2; 32]), vec![4, 5]);
+        let c = (Pubkey::new_from_array([3; 32]), vec![6]);
+
+        let forward = state_commitment(DOMAIN, 7, vec![a.clone(), b.clone(), c.clone()]);
+        assert_eq!(forward, state_commitment(DOMAIN, 7, vec![c.clone(), a.clone(), b.clone()]));
+        assert_ne!(forward, state_commitment(DOMAIN, 8, vec![a.clone(), b.clone(), c.clone()]));
+        assert_ne!(forward, state_commitment(b"other", 7, vec![a.clone(), b.clone(), c]));
+        assert_ne!(forward, state_commitment(DOMAIN, 7, vec![a, b]));
+    }
+
+    #[test]
+    fn test_state_commitment_canonical_serialization() {
+        let address = Pubkey::new_from_array([9; 32]);
+        let data = vec![42u8; 10];
+
+        let leaf = hashv(&[address.as_ref(), &data]).to_bytes();
+        let expected = hashv(&[DOMAIN, &5u64.to_le_bytes(), &1u32.to_le_bytes(), &leaf]).to_bytes();
+
+        assert_eq!(state_commitment(DOMAIN, 5, vec![(address, data)]), expected);
+    }
+}
@@ -0,0 +1,193 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    ed25519_program,
+    sysvar::instructions::{load_current_index_checked, load_instruction_at_checked},
+};
+
+use crate::error::IdentityError;
+use crate::state::MetadataEntry;
+
+/// Domain prefix for signed metadata import manifests
+/// Signed message: IMPORT_METADATA_DOMAIN || agent_mint || nonce (u64 LE) || borsh(manifest)
+pub const IMPORT_METADATA_DOMAIN: &[u8] = b"erc8004:import_metadata:v2";
+
+/// Ed25519 program instruction layout (see solana ed25519_program)
+/// 1 (num_signatures) + 1 (padding), then 14 bytes of offsets per signature
+const SIGNATURE_OFFSETS_START: usize = 2;
+const SIGNATURE_OFFSETS_SIZE: usize = 14;
+const PUBKEY_SIZE: usize = 32;
+const SIGNATURE_SIZE: usize = 64;
+
+/// Instruction index meaning "data lives in the Ed25519 instruction itself"
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Build the message an agent owner signs to authorize `import_metadata`
+///
+/// `nonce` is the agent's `import_nonce`, consumed on import so a signed
+/// manifest can only be applied once.
+pub fn import_metadata_message(
+    agent_mint: &Pubkey,
+    nonce: u64,
+    manifest: &[MetadataEntry],
+) -> Result<Vec<u8>> {
+    let mut message = Vec::with_capacity(IMPORT_METADATA_DOMAIN.len() + PUBKEY_SIZE + 8);
+    message.extend_from_slice(IMPORT_METADATA_DOMAIN);
+    message.extend_from_slice(agent_mint.as_ref());
+    message.extend_from_slice(&nonce.to_le_bytes());
+    manifest.serialize(&mut message)?;
+    Ok(message)
+}
+
+/// Check that Ed25519 program instruction data verifies exactly one signature,
+/// by `signer` over `message`, with all data inline in the instruction
+pub fn ed25519_ix_matches(data: &[u8], signer: &Pubkey, message: &[u8], signature: &[u8; 64]) -> bool {
+    if data.len() < SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_SIZE || data[0] != 1 {
+        return false;
+    }
+
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let offsets = SIGNATURE_OFFSETS_START;
+    let signature_offset = read_u16(offsets) as usize;
+    let signature_ix = read_u16(offsets + 2);
+    let pubkey_offset = read_u16(offsets + 4) as usize;
+    let pubkey_ix = read_u16(offsets + 6);
+    let message_offset = read_u16(offsets + 8) as usize;
+    let message_size = read_u16(offsets + 10) as usize;
+    let message_ix = read_u16(offsets + 12);
+
+    // Offsets must point into this instruction, not another one in the tx
+    if signature_ix != CURRENT_INSTRUCTION
+        || pubkey_ix != CURRENT_INSTRUCTION
+        || message_ix != CURRENT_INSTRUCTION
+    {
+        return false;
+    }
+
+    let slice = |offset: usize, len: usize| data.get(offset..offset.checked_add(len)?);
+
+    slice(pubkey_offset, PUBKEY_SIZE) == Some(signer.as_ref())
+        && slice(signature_offset, SIGNATURE_SIZE) == Some(&signature[..])
+        && slice(message_offset, message_size) == Some(message)
+}
+
+/// Verify the instruction right before the current one is an Ed25519 program
+/// check of `signature` by `signer` over `message`
+///
+/// The Ed25519 program fails the whole transaction if the signature is invalid,
+/// so a matching instruction proves the signer authorized `message`.
+pub fn verify_preceding_ed25519(
+    sysvar_instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+    signature: &[u8; 64],
+) -> Result<()> {
+    let current = load_current_index_checked(sysvar_instructions)?;
+    require!(current > 0, IdentityError::InvalidSignature);
+
+    let ix = load_instruction_at_checked((current - 1) as usize, sysvar_instructions)?;
+    require!(
+        ix.program_id == ed25519_program::ID
+            && ix.accounts.is_empty()
+            && ed25519_ix_matches(&ix.data, signer, message, signature),
+        IdentityError::InvalidSignature
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lay out Ed25519 instruction data the way @solana/web3.js does:
+    /// header, offsets, pubkey, signature, message
+    fn ed25519_ix_data(signer: &Pubkey, signature: &[u8; 64], message: &[u8]) -> Vec<u8> {
+        let pubkey_offset = (SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_SIZE) as u16;
+        let signature_offset = pubkey_offset + PUBKEY_SIZE as u16;
+        let message_offset = signature_offset + SIGNATURE_SIZE as u16;
+
+        let mut data = vec![1u8, 0u8];
+        for value in [
+            signature_offset,
+            CURRENT_INSTRUCTION,
+            pubkey_offset,
+            CURRENT_INSTRUCTION,
+            message_offset,
+            message.len() as u16,
+            CURRENT_INSTRUCTION,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(signature);
+        data.extend_from_slice(message);
+        data
+    }
+
+    fn manifest() -> Vec<MetadataEntry> {
+        vec![MetadataEntry {
+            key: "model".to_string(),
+            value: b"gpt".to_vec(),
+        }]
+    }
+
+    #[test]
+    fn test_matching_ed25519_ix() {
+        let signer = Pubkey::new_unique();
+        let message = import_metadata_message(&Pubkey::new_unique(), 0, &manifest()).unwrap();
+        let signature = [7u8; 64];
+
+        let data = ed25519_ix_data(&signer, &signature, &message);
+        assert!(ed25519_ix_matches(&data, &signer, &message, &signature));
+    }
+
+    #[test]
+    fn test_rejects_wrong_signer_message_or_signature() {
+        let signer = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let message = import_metadata_message(&mint, 0, &manifest()).unwrap();
+        let signature = [7u8; 64];
+        let data = ed25519_ix_data(&signer, &signature, &message);
+
+        assert!(!ed25519_ix_matches(&data, &Pubkey::new_unique(), &message, &signature));
+        assert!(!ed25519_ix_matches(&data, &signer, &message, &[8u8; 64]));
+
+        // Same manifest signed for another agent
+        let other = import_metadata_message(&Pubkey::new_unique(), 0, &manifest()).unwrap();
+        assert!(!ed25519_ix_matches(&data, &signer, &other, &signature));
+
+        // Same manifest signed for another nonce (replay after import)
+        let replayed = import_metadata_message(&mint, 1, &manifest()).unwrap();
+        assert!(!ed25519_ix_matches(&data, &signer, &replayed, &signature));
+    }
+
+    #[test]
+    fn test_rejects_offsets_into_other_instructions() {
+        let signer = Pubkey::new_unique();
+        let message = b"hello".to_vec();
+        let signature = [7u8; 64];
+
+        let mut data = ed25519_ix_data(&signer, &signature, &message);
+        // message_instruction_index -> instruction 0
+        data[SIGNATURE_OFFSETS_START + 12..SIGNATURE_OFFSETS_START + 14].copy_from_slice(&0u16.to_le_bytes());
+        assert!(!ed25519_ix_matches(&data, &signer, &message, &signature));
+    }
+
+    #[test]
+    fn test_rejects_malformed_data() {
+        let signer = Pubkey::new_unique();
+        let signature = [7u8; 64];
+
+        assert!(!ed25519_ix_matches(&[], &signer, b"m", &signature));
+
+        // Two signatures
+        let mut data = ed25519_ix_data(&signer, &signature, b"m");
+        data[0] = 2;
+        assert!(!ed25519_ix_matches(&data, &signer, b"m", &signature));
+
+        // Truncated message
+        let mut data = ed25519_ix_data(&signer, &signature, b"message");
+        data.truncate(data.len() - 1);
+        assert!(!ed25519_ix_matches(&data, &signer, b"message", &signature));
+    }
+}
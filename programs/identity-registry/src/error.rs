@@ -2,16 +2,16 @@ use anchor_lang::prelude::*;
 
 #[error_code]
 pub enum IdentityError {
-    #[msg("Token URI exceeds maximum length of 200 bytes")]
+    #[msg("Token URI exceeds the registry's maximum URI length")]
     UriTooLong,
 
     #[msg("Metadata key exceeds maximum length of 32 bytes")]
     KeyTooLong,
 
-    #[msg("Metadata value exceeds maximum length of 256 bytes")]
+    #[msg("Metadata value exceeds the registry's maximum value length")]
     ValueTooLong,
 
-    #[msg("Maximum of 10 metadata entries reached")]
+    #[msg("Agent metadata capacity reached (10 entries unless resized)")]
     MetadataLimitReached,
 
     #[msg("Only agent owner can perform this action")]
@@ -29,7 +29,7 @@ pub enum IdentityError {
     #[msg("Metadata extension not found for this agent")]
     ExtensionNotFound,
 
-    #[msg("Invalid extension index")]
+    #[msg("Extension index out of range (max 16 extensions per agent)")]
     InvalidExtensionIndex,
 
     #[msg("Collection mint does not match registry config")]
@@ -43,4 +43,91 @@ pub enum IdentityError {
 
     #[msg("Transfer destination is same as source")]
     TransferToSelf,
+
+    #[msg("Cached owner no longer holds the agent NFT, call sync_owner first")]
+    StaleOwner,
+
+    #[msg("Batch accounts must be passed in complete groups")]
+    InvalidBatchAccounts,
+
+    #[msg("Too many agents in batch")]
+    BatchTooLarge,
+
+    #[msg("Unknown agent category")]
+    InvalidCategory,
+
+    #[msg("Missing or invalid owner Ed25519 signature over the metadata manifest")]
+    InvalidSignature,
+
+    #[msg("Duplicate metadata key")]
+    DuplicateMetadataKey,
+
+    #[msg("Unknown operator type")]
+    InvalidOperatorType,
+
+    #[msg("Max value length must be between 1 and MetadataEntry::MAX_VALUE_LENGTH bytes")]
+    InvalidMaxValueLength,
+
+    #[msg("Successor must be another registered agent")]
+    InvalidSuccessor,
+
+    #[msg("Collection metadata account does not match the registry collection")]
+    InvalidCollectionMetadata,
+
+    #[msg("New authority must be a program-derived address different from the current one")]
+    InvalidPdaAuthority,
+
+    #[msg("Reputation or validation account does not belong to this agent, or was passed twice")]
+    InvalidCountSource,
+
+    #[msg("No transfer is pending for this agent")]
+    NoPendingTransfer,
+
+    #[msg("A two-step transfer or transfer approval is pending: complete or cancel it first")]
+    TransferPending,
+
+    #[msg("Registry is paused: new registrations are halted")]
+    RegistryPaused,
+
+    #[msg("Token URI must start with ipfs://, ar://, https:// or http://")]
+    InvalidUriScheme,
+
+    #[msg("Agent account does not hold the requested agent_id")]
+    AgentIdMismatch,
+
+    #[msg("No transfer approval is set for this agent")]
+    NoTransferApproval,
+
+    #[msg("Seller fee must be at most 10000 basis points")]
+    InvalidSellerFee,
+
+    #[msg("Name exceeds maximum length of 32 bytes")]
+    NameTooLong,
+
+    #[msg("Metadata capacity must be between 10 and 28 entries")]
+    InvalidMetadataCapacity,
+
+    #[msg("Metadata capacity is below the number of existing entries")]
+    MetadataCapacityTooSmall,
+
+    #[msg("Ownership history account must be passed exactly when record_history is set")]
+    OwnershipHistoryMismatch,
+
+    #[msg("Token program does not match the registration path (SPL Token or Token-2022)")]
+    InvalidTokenProgram,
+
+    #[msg("Agent is frozen: thaw it with thaw_agent first")]
+    AgentFrozen,
+
+    #[msg("Agent is not frozen")]
+    AgentNotFrozen,
+
+    #[msg("Max URI length must be between 1 and 512 bytes")]
+    InvalidMaxUriLength,
+
+    #[msg("Import nonce does not match the agent's current import_nonce")]
+    InvalidImportNonce,
+
+    #[msg("Owner agent list missing or not the PDA of its owner")]
+    InvalidOwnerAgentList,
 }
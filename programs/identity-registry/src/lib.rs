@@ -1,11 +1,22 @@
+// Anchor's generated IDL handlers still call the deprecated `AccountInfo::realloc`
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
     metadata::Metadata,
-    token::{self, Mint, MintTo, Token, TokenAccount},
+    token::{self, Approve, Burn, Mint, MintTo, Revoke, Token, TokenAccount},
+    token_2022,
+    token_interface::{self, TokenInterface},
 };
 use mpl_token_metadata::{
-    instructions::{CreateV1CpiBuilder, SetAndVerifyCollectionCpiBuilder, UpdateAsUpdateAuthorityV2CpiBuilder, UpdateV1CpiBuilder},
+    accounts::Metadata as MetaplexMetadata,
+    instructions::{
+        ApproveCollectionAuthorityCpiBuilder, CreateV1CpiBuilder, RevokeCollectionAuthorityCpiBuilder,
+        FreezeDelegatedAccountCpiBuilder, SetAndVerifyCollectionCpiBuilder,
+        ThawDelegatedAccountCpiBuilder, UpdateAsUpdateAuthorityV2CpiBuilder, UpdateV1CpiBuilder,
+        VerifyCollectionCpiBuilder,
+    },
     types::{Collection, Data, PrintSupply, TokenStandard},
     ID as TOKEN_METADATA_PROGRAM_ID,
 };
@@ -14,9 +25,15 @@ declare_id!("5euA2SjKFduF6FvXJuJdyqEo6ViAHMrw54CJB5PLaEJn");
 
 mod state;
 mod error;
+mod ed25519;
+#[cfg(feature = "views")]
+mod views;
 
 use state::*;
 use error::*;
+#[cfg(feature = "views")]
+use views::*;
+use ed25519::{import_metadata_message, verify_preceding_ed25519};
 
 #[program]
 pub mod identity_registry {
@@ -35,6 +52,12 @@ pub mod identity_registry {
         config.next_agent_id = 0;
         config.total_agents = 0;
         config.collection_mint = ctx.accounts.collection_mint.key();
+        config.max_value_length = MetadataEntry::DEFAULT_VALUE_LENGTH;
+        config.paused = false;
+        config.default_seller_fee_bps = 0;
+        config.verify_collection = true;
+        config.max_uri_length = AgentAccount::DEFAULT_URI_LENGTH;
+        config.validation_registry = DEFAULT_VALIDATION_REGISTRY_ID;
         config.bump = ctx.bumps.config;
 
         // Mint 1 collection NFT to authority
@@ -76,18 +99,439 @@ pub mod identity_registry {
         Ok(())
     }
 
+    /// Set the maximum metadata value length accepted by this registry
+    ///
+    /// Applies to register_with_metadata, set_metadata, set_metadata_extended and
+    /// import_metadata. Accounts only reserve 256 value bytes per metadata slot:
+    /// above that, longer values use the slack of other slots, and an agent
+    /// that runs out of room (`MetadataLimitReached`) grows its account with
+    /// `resize_agent_metadata`. Lowering the limit does not touch values that
+    /// are already stored.
+    ///
+    /// # Arguments
+    /// * `max_value_length` - New limit in bytes (1 to `MetadataEntry::MAX_VALUE_LENGTH`)
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the registry authority
+    /// * `InvalidMaxValueLength` - If the limit is 0 or above `MetadataEntry::MAX_VALUE_LENGTH`
+    pub fn set_max_value_length(ctx: Context<UpdateConfig>, max_value_length: u16) -> Result<()> {
+        if !RegistryConfig::is_valid_max_value_length(max_value_length) {
+            msg!(
+                "Max value length must be between 1 and {} bytes",
+                MetadataEntry::MAX_VALUE_LENGTH
+            );
+            return err!(IdentityError::InvalidMaxValueLength);
+        }
+
+        ctx.accounts.config.max_value_length = max_value_length;
+
+        msg!("Max metadata value length set to {} bytes", max_value_length);
+
+        Ok(())
+    }
+
+    /// Set the maximum token URI length accepted by this registry
+    ///
+    /// Applies to every register variant and set_agent_uri, for deployments
+    /// using long signed gateway URLs, up to `AgentAccount::MAX_URI_LENGTH`.
+    /// Agent accounts reserve 200 URI bytes; a longer URI grows the account by
+    /// the excess when it is stored, paid by the registering owner or the
+    /// `set_agent_uri` signer. Metaplex caps NFT metadata URIs at
+    /// 200 bytes: a longer URI is kept on the AgentAccount only and the NFT
+    /// metadata URI is left empty.
+    ///
+    /// # Arguments
+    /// * `max_uri_length` - New limit in bytes (1-512)
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the registry authority
+    /// * `InvalidMaxUriLength` - If the limit is 0 or above 512
+    pub fn set_max_uri_length(ctx: Context<UpdateConfig>, max_uri_length: u16) -> Result<()> {
+        require!(
+            RegistryConfig::is_valid_max_uri_length(max_uri_length),
+            IdentityError::InvalidMaxUriLength
+        );
+
+        ctx.accounts.config.max_uri_length = max_uri_length;
+
+        msg!("Max token URI length set to {} bytes", max_uri_length);
+
+        Ok(())
+    }
+
+    /// Pause or resume new registrations
+    ///
+    /// Incident switch: while paused, the register instructions fail with
+    /// `RegistryPaused`. Existing agents stay fully mutable (metadata, URI,
+    /// transfers).
+    ///
+    /// # Arguments
+    /// * `paused` - Whether new registrations are halted
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the registry authority
+    pub fn set_paused(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
+        ctx.accounts.config.paused = paused;
+
+        msg!("Registry paused: {}", paused);
+
+        Ok(())
+    }
+
+    /// Turn collection verification of new agents on or off
+    ///
+    /// While off, the register instructions still point new agent NFTs at the
+    /// registry collection but leave them unverified (e.g. during a collection
+    /// migration); `verify_agent_collection` verifies them afterwards.
+    ///
+    /// # Arguments
+    /// * `verify_collection` - Whether registration verifies collection membership
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the registry authority
+    pub fn set_verify_collection(ctx: Context<UpdateConfig>, verify_collection: bool) -> Result<()> {
+        ctx.accounts.config.verify_collection = verify_collection;
+
+        msg!("Collection verification on registration: {}", verify_collection);
+
+        Ok(())
+    }
+
+    /// Set the Validation Registry program `sync_agent_counts` reads from
+    ///
+    /// Defaults to the devnet deployment; clusters where the Validation
+    /// Registry lives at another address (e.g. localnet) point it here.
+    ///
+    /// # Arguments
+    /// * `validation_registry` - Validation Registry program ID
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the registry authority
+    pub fn set_validation_registry(
+        ctx: Context<UpdateConfig>,
+        validation_registry: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.config.validation_registry = validation_registry;
+
+        msg!("Validation Registry set to {}", validation_registry);
+
+        Ok(())
+    }
+
+    /// Set the royalty written into new agent NFTs
+    ///
+    /// Applies to agents registered afterwards; existing NFTs keep the fee they
+    /// were minted with.
+    ///
+    /// # Arguments
+    /// * `seller_fee_bps` - Royalty in basis points (0-10000, 500 = 5%)
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the registry authority
+    /// * `InvalidSellerFee` - If the fee is above 10000
+    pub fn set_default_seller_fee_bps(ctx: Context<UpdateConfig>, seller_fee_bps: u16) -> Result<()> {
+        require!(
+            RegistryConfig::is_valid_seller_fee_bps(seller_fee_bps),
+            IdentityError::InvalidSellerFee
+        );
+
+        ctx.accounts.config.default_seller_fee_bps = seller_fee_bps;
+
+        msg!("Default seller fee set to {} bps", seller_fee_bps);
+
+        Ok(())
+    }
+
+    /// Approve a Metaplex collection authority delegate
+    ///
+    /// Lets `delegate` verify agents into the registry collection (i.e. act as the
+    /// `authority` in the register instructions, together with its collection
+    /// authority record) without being the registry admin.
+    ///
+    /// # Arguments
+    /// * `delegate` - Wallet allowed to verify agents into the collection
+    ///
+    /// # Events
+    /// * `CollectionAuthorityApproved` - Emitted when the delegate is approved
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the registry authority
+    pub fn approve_collection_authority(
+        ctx: Context<ApproveCollectionAuthority>,
+        delegate: Pubkey,
+    ) -> Result<()> {
+        ApproveCollectionAuthorityCpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+            .collection_authority_record(&ctx.accounts.collection_authority_record)
+            .new_collection_authority(&ctx.accounts.delegate_account)
+            .update_authority(&ctx.accounts.authority.to_account_info())
+            .payer(&ctx.accounts.authority.to_account_info())
+            .metadata(&ctx.accounts.collection_metadata)
+            .mint(&ctx.accounts.collection_mint.to_account_info())
+            .system_program(&ctx.accounts.system_program.to_account_info())
+            .invoke()?;
+
+        emit!(CollectionAuthorityApproved {
+            collection_mint: ctx.accounts.collection_mint.key(),
+            delegate,
+        });
+
+        msg!("Collection authority delegate approved: {}", delegate);
+
+        Ok(())
+    }
+
+    /// Revoke a Metaplex collection authority delegate
+    ///
+    /// # Arguments
+    /// * `delegate` - Previously approved delegate
+    ///
+    /// # Events
+    /// * `CollectionAuthorityRevoked` - Emitted when the delegate is revoked
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the registry authority
+    pub fn revoke_collection_authority(
+        ctx: Context<RevokeCollectionAuthority>,
+        delegate: Pubkey,
+    ) -> Result<()> {
+        RevokeCollectionAuthorityCpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+            .collection_authority_record(&ctx.accounts.collection_authority_record)
+            .delegate_authority(&ctx.accounts.delegate_account)
+            .revoke_authority(&ctx.accounts.authority.to_account_info())
+            .metadata(&ctx.accounts.collection_metadata)
+            .mint(&ctx.accounts.collection_mint.to_account_info())
+            .invoke()?;
+
+        emit!(CollectionAuthorityRevoked {
+            collection_mint: ctx.accounts.collection_mint.key(),
+            delegate,
+        });
+
+        msg!("Collection authority delegate revoked: {}", delegate);
+
+        Ok(())
+    }
+
+    /// Verify an agent NFT into the registry collection after the fact
+    ///
+    /// For agents registered while `verify_collection` was off. Signed by the
+    /// registry authority, or an approved delegate passing its collection
+    /// authority record (same as registration).
+    ///
+    /// # Arguments
+    /// * `agent_mint` - Mint of the agent NFT to verify
+    ///
+    /// # Events
+    /// * `AgentCollectionVerified` - Emitted when the agent is verified
+    ///
+    /// # Errors
+    /// * `InvalidCollectionMint` - If the collection mint is not the registry's
+    pub fn verify_agent_collection(
+        ctx: Context<VerifyAgentCollection>,
+        agent_mint: Pubkey,
+    ) -> Result<()> {
+        let collection_authority_record = ctx
+            .accounts
+            .collection_authority_record
+            .as_ref()
+            .map(|record| record.to_account_info());
+
+        VerifyCollectionCpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+            .metadata(&ctx.accounts.agent_metadata)
+            .collection_authority(&ctx.accounts.authority.to_account_info())
+            .payer(&ctx.accounts.authority.to_account_info())
+            .collection_mint(&ctx.accounts.collection_mint.to_account_info())
+            .collection(&ctx.accounts.collection_metadata)
+            .collection_master_edition_account(&ctx.accounts.collection_master_edition)
+            .collection_authority_record(collection_authority_record.as_ref())
+            .invoke()?;
+
+        emit!(AgentCollectionVerified {
+            agent_id: ctx.accounts.agent_account.agent_id,
+            agent_mint,
+            collection_mint: ctx.accounts.collection_mint.key(),
+            verified_by: ctx.accounts.authority.key(),
+        });
+
+        msg!("Agent {} verified into the collection", ctx.accounts.agent_account.agent_id);
+
+        Ok(())
+    }
+
+    /// Hand the registry authority to a new key
+    ///
+    /// Only moves `config.authority`. Collection verification also needs the
+    /// collection's Metaplex update authority, which the old authority must then
+    /// hand over with `update_collection_authority` (both can go in the same
+    /// transaction). For a governance program's PDA, use `set_pda_authority`.
+    ///
+    /// # Arguments
+    /// * `new_authority` - New registry authority
+    ///
+    /// # Events
+    /// * `AuthorityTransferred` - Emitted with the old and new authority
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the registry authority
+    pub fn transfer_authority(ctx: Context<UpdateConfig>, new_authority: Pubkey) -> Result<()> {
+        move_registry_authority(&mut ctx.accounts.config, new_authority);
+
+        Ok(())
+    }
+
+    /// Move the collection's Metaplex update authority to `config.authority`
+    ///
+    /// Second half of `transfer_authority`: signed by the current collection
+    /// update authority (the previous registry authority), so that
+    /// `SetAndVerifyCollection` in future registrations works under the new key.
+    /// The destination is always `config.authority`, so the signer cannot hand
+    /// the collection anywhere else.
+    ///
+    /// # Errors
+    /// * Metaplex rejects the CPI if the signer isn't the collection update authority
+    pub fn update_collection_authority(ctx: Context<UpdateCollectionAuthority>) -> Result<()> {
+        move_collection_update_authority(
+            &ctx.accounts.token_metadata_program,
+            &ctx.accounts.collection_authority,
+            &ctx.accounts.collection_mint,
+            &ctx.accounts.collection_metadata,
+            &ctx.accounts.system_program,
+            &ctx.accounts.sysvar_instructions,
+            ctx.accounts.config.authority,
+        )
+    }
+
+    /// Rename the collection or point it at a new URI
+    ///
+    /// Lets operators rebrand after deployment instead of keeping the name and
+    /// URI hardcoded in `initialize`. Fields left as `None` keep their current
+    /// value; symbol, royalty and creators are carried over unchanged.
+    ///
+    /// # Arguments
+    /// * `name` - New collection name (max 32 bytes)
+    /// * `uri` - New collection URI (max 200 bytes)
+    ///
+    /// # Events
+    /// * `CollectionMetadataUpdated` - Emitted with the resulting name and URI
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the registry authority
+    /// * `NameTooLong` - If name exceeds 32 bytes
+    /// * `UriTooLong` - If uri exceeds 200 bytes
+    /// * `InvalidUriScheme` - If uri is not ipfs://, ar://, https:// or http://
+    /// * `InvalidCollectionMetadata` - If the collection metadata can't be read
+    pub fn update_collection_metadata(
+        ctx: Context<UpdateCollectionMetadata>,
+        name: Option<String>,
+        uri: Option<String>,
+    ) -> Result<()> {
+        if let Some(name) = &name {
+            require!(
+                name.len() <= mpl_token_metadata::MAX_NAME_LENGTH,
+                IdentityError::NameTooLong
+            );
+        }
+        if let Some(uri) = &uri {
+            require!(
+                uri.len() <= mpl_token_metadata::MAX_URI_LENGTH,
+                IdentityError::UriTooLong
+            );
+            validate_uri(uri)?;
+        }
+
+        let current =
+            MetaplexMetadata::safe_deserialize(&ctx.accounts.collection_metadata.try_borrow_data()?)
+                .map_err(|_| error!(IdentityError::InvalidCollectionMetadata))?;
+
+        let name = name.unwrap_or_else(|| current.name.trim_end_matches('\0').to_string());
+        let uri = uri.unwrap_or_else(|| current.uri.trim_end_matches('\0').to_string());
+
+        UpdateV1CpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+            .authority(&ctx.accounts.authority.to_account_info())
+            .mint(&ctx.accounts.collection_mint.to_account_info())
+            .metadata(&ctx.accounts.collection_metadata.to_account_info())
+            .payer(&ctx.accounts.authority.to_account_info())
+            .system_program(&ctx.accounts.system_program.to_account_info())
+            .sysvar_instructions(&ctx.accounts.sysvar_instructions.to_account_info())
+            .data(Data {
+                name: name.clone(),
+                symbol: current.symbol.trim_end_matches('\0').to_string(),
+                uri: uri.clone(),
+                seller_fee_basis_points: current.seller_fee_basis_points,
+                creators: current.creators,
+            })
+            .invoke()?;
+
+        emit!(CollectionMetadataUpdated {
+            collection_mint: ctx.accounts.collection_mint.key(),
+            name: name.clone(),
+            uri: uri.clone(),
+        });
+
+        msg!("Collection metadata updated: {} ({})", name, uri);
+
+        Ok(())
+    }
+
+    /// Hand the registry over to a governance program's PDA
+    ///
+    /// `transfer_authority` and `update_collection_authority` in one step:
+    /// sets `config.authority` to `new_authority` and moves the collection's
+    /// Metaplex update authority with it, so the governance program can later
+    /// sign every authority-gated instruction (config updates, collection
+    /// delegates, operator attestations) via `invoke_signed`. Authority checks
+    /// only compare keys against a `Signer`, which a CPI-signed PDA satisfies.
+    ///
+    /// This is one-way from the wallet's point of view: afterwards only the
+    /// governance program can act as the authority.
+    ///
+    /// # Arguments
+    /// * `new_authority` - Off-curve PDA owned by the governance program
+    ///
+    /// # Events
+    /// * `AuthorityTransferred` - Emitted with the old and new authority
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the registry authority
+    /// * `InvalidPdaAuthority` - If `new_authority` is on-curve or unchanged
+    pub fn set_pda_authority(ctx: Context<SetPdaAuthority>, new_authority: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.config.is_valid_pda_authority(&new_authority),
+            IdentityError::InvalidPdaAuthority
+        );
+
+        // Collection verification needs the new authority to be the collection's
+        // update authority too
+        move_collection_update_authority(
+            &ctx.accounts.token_metadata_program,
+            &ctx.accounts.authority,
+            &ctx.accounts.collection_mint,
+            &ctx.accounts.collection_metadata,
+            &ctx.accounts.system_program,
+            &ctx.accounts.sysvar_instructions,
+            new_authority,
+        )?;
+        move_registry_authority(&mut ctx.accounts.config, new_authority);
+
+        Ok(())
+    }
+
     /// Register a new agent with empty URI (ERC-8004 spec: register())
     ///
     /// Creates an agent with empty token URI and assigns a sequential agent ID.
     /// The contract creates and mints the NFT to the caller as part of the collection.
     ///
+    /// # Returns
+    /// * The new agent ID (also set as return data)
+    ///
     /// # Events
     /// * `AgentRegistered` - Emitted when agent is successfully registered
     ///
     /// # Errors
     /// * `Overflow` - If agent ID counter overflows
-    pub fn register_empty(ctx: Context<Register>) -> Result<()> {
-        register_internal(ctx, String::new(), vec![])
+    /// * `RegistryPaused` - If new registrations are halted
+    pub fn register_empty(ctx: Context<Register>) -> Result<u64> {
+        register_internal(ctx, String::new(), vec![], OperatorType::Autonomous as u8, String::new(), token::ID)
     }
 
     /// Register a new agent with URI (ERC-8004 spec: register(tokenURI))
@@ -96,16 +540,30 @@ pub mod identity_registry {
     /// The contract creates and mints the NFT to the caller as part of the collection.
     ///
     /// # Arguments
-    /// * `token_uri` - IPFS/Arweave/HTTP URI (max 200 bytes, can be empty string)
+    /// * `token_uri` - IPFS/Arweave/HTTP URI (max `max_uri_length` bytes, can be empty string)
+    ///
+    /// # Returns
+    /// * The new agent ID (also set as return data, readable by CPI callers via
+    ///   `get_return_data`)
+    ///
+    /// # Register and validate in one transaction
+    /// The agent ID is `config.next_agent_id` at execution time, so a client can
+    /// read it beforehand and append `validation_registry::request_validation`
+    /// for that ID (and the new agent account) after this instruction. If another
+    /// registration lands first, the ID no longer matches the agent account and
+    /// the whole transaction fails atomically, so no half-onboarded agent exists.
     ///
     /// # Events
     /// * `AgentRegistered` - Emitted when agent is successfully registered
     ///
     /// # Errors
-    /// * `UriTooLong` - If token_uri exceeds 200 bytes
+    /// * `UriTooLong` - If token_uri exceeds the registry's max_uri_length
+    /// * `InvalidUriScheme` - If token_uri is not ipfs://, ar://, https:// or http://
     /// * `Overflow` - If agent ID counter overflows
-    pub fn register(ctx: Context<Register>, token_uri: String) -> Result<()> {
-        register_internal(ctx, token_uri, vec![])
+    /// * `RegistryPaused` - If new registrations are halted
+    /// * `InvalidTokenProgram` - If `token_program` is not SPL Token (see `register_token2022`)
+    pub fn register(ctx: Context<Register>, token_uri: String) -> Result<u64> {
+        register_internal(ctx, token_uri, vec![], OperatorType::Autonomous as u8, String::new(), token::ID)
     }
 
     /// Register a new agent with URI and initial metadata (ERC-8004 spec: register(tokenURI, metadata[]))
@@ -115,25 +573,120 @@ pub mod identity_registry {
     /// The contract creates and mints the NFT to the caller as part of the collection.
     ///
     /// # Arguments
-    /// * `token_uri` - IPFS/Arweave/HTTP URI (max 200 bytes, can be empty string)
+    /// * `token_uri` - IPFS/Arweave/HTTP URI (max `max_uri_length` bytes, can be empty string)
     /// * `metadata` - Initial metadata entries (max 10 entries)
     ///
+    /// # Returns
+    /// * The new agent ID (also set as return data)
+    ///
     /// # Events
     /// * `AgentRegistered` - Emitted when agent is successfully registered
     /// * `MetadataSet` - Emitted for each metadata entry
     ///
     /// # Errors
-    /// * `UriTooLong` - If token_uri exceeds 200 bytes
+    /// * `UriTooLong` - If token_uri exceeds the registry's max_uri_length
+    /// * `InvalidUriScheme` - If token_uri is not ipfs://, ar://, https:// or http://
     /// * `KeyTooLong` - If any key exceeds 32 bytes
-    /// * `ValueTooLong` - If any value exceeds 256 bytes
+    /// * `ValueTooLong` - If any value exceeds the registry's max_value_length
     /// * `MetadataLimitReached` - If more than 10 entries provided
+    /// * `DuplicateMetadataKey` - If two entries share a key
     /// * `Overflow` - If agent ID counter overflows
+    /// * `RegistryPaused` - If new registrations are halted
     pub fn register_with_metadata(
         ctx: Context<Register>,
         token_uri: String,
         metadata: Vec<MetadataEntry>,
-    ) -> Result<()> {
-        register_internal(ctx, token_uri, metadata)
+    ) -> Result<u64> {
+        register_internal(ctx, token_uri, metadata, OperatorType::Autonomous as u8, String::new(), token::ID)
+    }
+
+    /// Register a new agent with URI, initial metadata and declared operator type
+    ///
+    /// Same as `register_with_metadata`, but declares up front whether the agent is
+    /// autonomous, human-operated or hybrid instead of defaulting to autonomous.
+    ///
+    /// # Arguments
+    /// * `token_uri` - IPFS/Arweave/HTTP URI (max `max_uri_length` bytes, can be empty string)
+    /// * `metadata` - Initial metadata entries (max 10 entries)
+    /// * `operator_type` - 0 = Autonomous, 1 = HumanOperated, 2 = Hybrid
+    ///
+    /// # Returns
+    /// * The new agent ID (also set as return data)
+    ///
+    /// # Events
+    /// * `AgentRegistered` - Emitted when agent is successfully registered
+    /// * `MetadataSet` - Emitted for each metadata entry
+    /// * `OperatorTypeSet` - Emitted if a non-autonomous type is declared
+    ///
+    /// # Errors
+    /// * `InvalidOperatorType` - If operator_type is not a known value
+    /// * Same as `register_with_metadata`
+    pub fn register_with_operator_type(
+        ctx: Context<Register>,
+        token_uri: String,
+        metadata: Vec<MetadataEntry>,
+        operator_type: u8,
+    ) -> Result<u64> {
+        register_internal(ctx, token_uri, metadata, operator_type, String::new(), token::ID)
+    }
+
+    /// Register a new agent with URI and a custom NFT display name
+    ///
+    /// Same as `register`, but names the NFT `name` instead of `Agent #{id}` so
+    /// agents are distinguishable in wallets. The name is also stored in
+    /// `AgentAccount::nft_name`. An empty name falls back to `Agent #{id}`.
+    ///
+    /// # Arguments
+    /// * `token_uri` - IPFS/Arweave/HTTP URI (max `max_uri_length` bytes, can be empty string)
+    /// * `name` - NFT display name (max 32 bytes, Metaplex limit)
+    ///
+    /// # Returns
+    /// * The new agent ID (also set as return data)
+    ///
+    /// # Events
+    /// * `AgentRegistered` - Emitted when agent is successfully registered
+    ///
+    /// # Errors
+    /// * `NameTooLong` - If name exceeds 32 bytes
+    /// * Same as `register`
+    pub fn register_named(ctx: Context<Register>, token_uri: String, name: String) -> Result<u64> {
+        register_internal(ctx, token_uri, vec![], OperatorType::Autonomous as u8, name, token::ID)
+    }
+
+    /// Register a new agent whose NFT is a Token-2022 mint
+    ///
+    /// Same as `register`, but the agent mint and token account are created
+    /// under the Token-2022 program (pass it as `token_program`), so the agent
+    /// can later use Token-2022 tooling. The Metaplex metadata, master edition
+    /// and collection work as for legacy agents; the collection itself stays a
+    /// legacy SPL Token mint.
+    ///
+    /// `transfer_agent` and `sync_owner` accept either token program. The
+    /// other token-account based instructions (two-step transfers, approvals,
+    /// freezing, deregistration, token account reopening) still require
+    /// legacy agents.
+    ///
+    /// # Arguments
+    /// * `token_uri` - IPFS/Arweave/HTTP URI (max `max_uri_length` bytes, can be empty string)
+    ///
+    /// # Returns
+    /// * The new agent ID (also set as return data)
+    ///
+    /// # Events
+    /// * `AgentRegistered` - Emitted when agent is successfully registered
+    ///
+    /// # Errors
+    /// * `InvalidTokenProgram` - If `token_program` is not Token-2022
+    /// * Same as `register`
+    pub fn register_token2022(ctx: Context<Register>, token_uri: String) -> Result<u64> {
+        register_internal(
+            ctx,
+            token_uri,
+            vec![],
+            OperatorType::Autonomous as u8,
+            String::new(),
+            token_2022::ID,
+        )
     }
 
     /// Internal registration logic shared by all register functions
@@ -143,33 +696,71 @@ pub mod identity_registry {
     /// or register_with_metadata() instead.
     #[doc(hidden)]
     pub fn register_internal(
-        mut ctx: Context<Register>,
+        ctx: Context<Register>,
         token_uri: String,
         metadata: Vec<MetadataEntry>,
-    ) -> Result<()> {
-        // Validate token URI length (ERC-8004 spec: max 200 bytes)
+        operator_type: u8,
+        name: String,
+        token_program_id: Pubkey,
+    ) -> Result<u64> {
+        require!(!ctx.accounts.config.paused, IdentityError::RegistryPaused);
+
+        // Legacy and Token-2022 agents are registered through separate entry points
+        require_keys_eq!(
+            ctx.accounts.token_program.key(),
+            token_program_id,
+            IdentityError::InvalidTokenProgram
+        );
+
+        // NFT display name (Metaplex limit: 32 bytes)
+        require!(
+            name.len() <= mpl_token_metadata::MAX_NAME_LENGTH,
+            IdentityError::NameTooLong
+        );
+
+        // The Collection struct below is built from config, verification uses the account
+        require!(
+            ctx.accounts.collection_mint.key() == ctx.accounts.config.collection_mint,
+            IdentityError::InvalidCollectionMint
+        );
+
+        // Validate token URI length (registry-configured max, 200 bytes by default)
         require!(
-            token_uri.len() <= AgentAccount::MAX_URI_LENGTH,
+            ctx.accounts.config.accepts_uri_length(token_uri.len()),
             IdentityError::UriTooLong
         );
+        validate_uri(&token_uri)?;
 
-        // Validate metadata
         require!(
-            metadata.len() <= AgentAccount::MAX_METADATA_ENTRIES,
+            OperatorType::from_u8(operator_type).is_some(),
+            IdentityError::InvalidOperatorType
+        );
+
+        // Validate metadata (must fit the base account's slots)
+        require!(
+            MetadataEntry::fit_in(&metadata, AgentAccount::MAX_METADATA_ENTRIES),
             IdentityError::MetadataLimitReached
         );
 
-        for entry in &metadata {
+        for (i, entry) in metadata.iter().enumerate() {
             require!(
                 entry.key.len() <= MetadataEntry::MAX_KEY_LENGTH,
                 IdentityError::KeyTooLong
             );
             require!(
-                entry.value.len() <= MetadataEntry::MAX_VALUE_LENGTH,
+                ctx.accounts.config.accepts_value_length(entry.value.len()),
                 IdentityError::ValueTooLong
             );
+            // Keys must be unique for get_metadata/set_metadata to be well-defined
+            require!(
+                !metadata[..i].iter().any(|other| other.key == entry.key),
+                IdentityError::DuplicateMetadataKey
+            );
         }
 
+        // Defense in depth: the mint must be a fresh NFT mint, not a pre-existing one
+        assert_new_agent_mint(ctx.accounts.agent_mint.supply, ctx.accounts.agent_mint.decimals)?;
+
         let config = &mut ctx.accounts.config;
         let agent_id = config.next_agent_id;
 
@@ -185,10 +776,10 @@ pub mod identity_registry {
             .ok_or(IdentityError::Overflow)?;
 
         // Mint 1 agent NFT to owner
-        token::mint_to(
+        token_interface::mint_to(
             CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
-                MintTo {
+                token_interface::MintTo {
                     mint: ctx.accounts.agent_mint.to_account_info(),
                     to: ctx.accounts.agent_token_account.to_account_info(),
                     authority: ctx.accounts.owner.to_account_info(),
@@ -198,12 +789,12 @@ pub mod identity_registry {
         )?;
 
         // Create Metaplex NFT metadata + master edition WITH collection reference
-        let agent_name = format!("Agent #{}", agent_id);
-        let metadata_uri = if token_uri.is_empty() {
-            String::new()
+        let agent_name = if name.is_empty() {
+            format!("Agent #{}", agent_id)
         } else {
-            token_uri.clone()
+            name
         };
+        let metadata_uri = nft_uri(&token_uri);
 
         CreateV1CpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
             .metadata(&ctx.accounts.agent_metadata)
@@ -217,7 +808,7 @@ pub mod identity_registry {
             .spl_token_program(Some(&ctx.accounts.token_program.to_account_info()))
             .name(agent_name.clone())
             .uri(metadata_uri)
-            .seller_fee_basis_points(0)
+            .seller_fee_basis_points(config.default_seller_fee_bps)
             .token_standard(TokenStandard::NonFungible)
             .print_supply(PrintSupply::Zero)
             .collection(Collection {
@@ -226,31 +817,79 @@ pub mod identity_registry {
             })
             .invoke()?;
 
-        // Verify collection membership (requires collection authority)
-        SetAndVerifyCollectionCpiBuilder::new(
-            &ctx.accounts.token_metadata_program.to_account_info(),
-        )
-        .metadata(&ctx.accounts.agent_metadata)
-        .collection_authority(&ctx.accounts.authority.to_account_info())
-        .payer(&ctx.accounts.owner.to_account_info())
-        .update_authority(&ctx.accounts.owner.to_account_info())
-        .collection_mint(&ctx.accounts.collection_mint.to_account_info())
-        .collection(&ctx.accounts.collection_metadata)
-        .collection_master_edition_account(&ctx.accounts.collection_master_edition)
-        .invoke()?;
+        // Verify collection membership (requires collection authority or an
+        // approved delegate, whose record Metaplex checks). Skipped while the
+        // authority has verification off: the NFT keeps `verified: false`.
+        if config.verify_collection {
+            let collection_authority_record = ctx
+                .accounts
+                .collection_authority_record
+                .as_ref()
+                .map(|record| record.to_account_info());
+
+            SetAndVerifyCollectionCpiBuilder::new(
+                &ctx.accounts.token_metadata_program.to_account_info(),
+            )
+            .metadata(&ctx.accounts.agent_metadata)
+            .collection_authority(&ctx.accounts.authority.to_account_info())
+            .payer(&ctx.accounts.owner.to_account_info())
+            .update_authority(&ctx.accounts.owner.to_account_info())
+            .collection_mint(&ctx.accounts.collection_mint.to_account_info())
+            .collection(&ctx.accounts.collection_metadata)
+            .collection_master_edition_account(&ctx.accounts.collection_master_edition)
+            .collection_authority_record(collection_authority_record.as_ref())
+            .invoke()?;
+        }
+
+        // The account is created for a URI up to RESERVED_URI_LENGTH; grow it
+        // by the excess of a longer one
+        if token_uri.len() > AgentAccount::RESERVED_URI_LENGTH {
+            erc8004_common::grow_account(
+                &ctx.accounts.agent_account.to_account_info(),
+                &ctx.accounts.owner.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+                AgentAccount::space_for(token_uri.len(), AgentAccount::MAX_METADATA_ENTRIES),
+            )?;
+        }
 
         // Initialize agent account
         let agent = &mut ctx.accounts.agent_account;
         agent.agent_id = agent_id;
         agent.owner = ctx.accounts.owner.key();
         agent.agent_mint = ctx.accounts.agent_mint.key();
+        agent.category = AgentCategory::UNCATEGORIZED;
+        agent.operator_type = operator_type;
+        agent.status = AgentStatus::Active as u8;
+        agent.successor_agent = None;
         agent.token_uri = token_uri.clone();
         agent.nft_name = agent_name.clone();
         agent.nft_symbol = String::new(); // Empty symbol for now
         agent.metadata = metadata.clone();
+        agent.extension_count = 0;
+        agent.cached_feedback_count = 0;
+        agent.cached_validation_count = 0;
+        agent.pending_owner = None;
+        agent.metadata_delegate = None;
+        agent.approved = None;
         agent.created_at = Clock::get()?.unix_timestamp;
+        agent.updated_at = agent.created_at;
         agent.bump = ctx.bumps.agent_account;
 
+        let index = &mut ctx.accounts.agent_id_index;
+        index.agent_id = agent_id;
+        index.agent_mint = agent.agent_mint;
+        index.bump = ctx.bumps.agent_id_index;
+
+        let owner_agents = &mut ctx.accounts.owner_agents;
+        owner_agents.init_if_new(ctx.accounts.owner.key(), ctx.bumps.owner_agents);
+        if !owner_agents.add(agent_id) {
+            msg!("Owner agent list full: agent {} not indexed", agent_id);
+        }
+
+        let registry_stats = &mut ctx.accounts.registry_stats;
+        registry_stats.bump = ctx.bumps.registry_stats;
+        registry_stats.record_registration(agent.created_at);
+
         // Emit registration event (ERC-8004 spec: Registered event)
         emit!(Registered {
             agent_id,
@@ -259,6 +898,14 @@ pub mod identity_registry {
             agent_mint: ctx.accounts.agent_mint.key(),
         });
 
+        if operator_type != OperatorType::Autonomous as u8 {
+            emit!(OperatorTypeSet {
+                agent_id,
+                operator_type,
+                set_by: ctx.accounts.owner.key(),
+            });
+        }
+
         // Emit metadata events if any
         for entry in &metadata {
             emit!(MetadataSet {
@@ -280,7 +927,7 @@ pub mod identity_registry {
         // by Metaplex when creating the master edition. This makes the NFT truly immutable
         // with supply = 1 forever. No additional action needed.
 
-        Ok(())
+        Ok(agent_id)
     }
 
     /// Get agent metadata value by key (ERC-8004 spec: getMetadata(agentId, key))
@@ -304,6 +951,23 @@ pub mod identity_registry {
         }
     }
 
+    /// Get all metadata entries of an agent (view)
+    ///
+    /// Lets an indexer read a whole profile in one simulate call instead of one
+    /// `get_metadata` per key. Return data is capped at 1024 bytes by the runtime,
+    /// so agents with large values must still be read key by key (or by fetching
+    /// the account).
+    ///
+    /// Entries carry no timestamp, so the canonical created_at tiebreak of
+    /// the other list views doesn't apply: storage (insertion) order is the
+    /// same for every reader.
+    ///
+    /// # Returns
+    /// * All metadata entries, in storage order
+    pub fn get_all_metadata(ctx: Context<GetMetadata>) -> Result<Vec<MetadataEntry>> {
+        Ok(ctx.accounts.agent_account.metadata.clone())
+    }
+
     /// Set agent metadata (ERC-8004 spec: setMetadata(agentId, key, value))
     ///
     /// Updates or adds a metadata entry for the agent. Only the agent owner can call this.
@@ -319,26 +983,57 @@ pub mod identity_registry {
     ///
     /// # Errors
     /// * `KeyTooLong` - If key exceeds 32 bytes
-    /// * `ValueTooLong` - If value exceeds 256 bytes
-    /// * `MetadataLimitReached` - If adding new entry would exceed 10 entries
-    /// * `Unauthorized` - If caller is not the agent owner
+    /// * `ValueTooLong` - If value exceeds the registry's max_value_length
+    /// * `MetadataLimitReached` - If the entries would no longer fit the account's
+    ///   slots (10 unless grown with `resize_agent_metadata`, 256 value bytes each)
+    /// * `Unauthorized` - If caller is neither the agent owner nor its metadata delegate
+    /// * `StaleOwner` - Strict mode only: the cached owner no longer holds the NFT
+    ///
+    /// # Strict mode
+    /// Passing the optional `owner_token_account` makes the instruction also verify
+    /// that the cached owner still holds the agent NFT in that account. If the NFT
+    /// was moved by a plain SPL transfer or the owner closed their token account,
+    /// the call fails with `StaleOwner` instead of writing on behalf of a wallet
+    /// that no longer owns the agent. Call `sync_owner` (or
+    /// `reopen_agent_token_account`) to restore a consistent state.
     pub fn set_metadata(
         ctx: Context<SetMetadata>,
         key: String,
         value: Vec<u8>,
     ) -> Result<()> {
+        require!(
+            ctx.accounts.agent_account.can_edit_metadata(&ctx.accounts.owner.key()),
+            IdentityError::Unauthorized
+        );
+
+        // Strict mode: cached owner must still hold the agent NFT
+        if let Some(token_account) = &ctx.accounts.owner_token_account {
+            require!(
+                ctx.accounts.agent_account.is_held_by(
+                    &token_account.mint,
+                    &token_account.owner,
+                    token_account.amount,
+                ),
+                IdentityError::StaleOwner
+            );
+        }
+
         // Validate key length (ERC-8004 adaptation: max 32 bytes)
         require!(
             key.len() <= MetadataEntry::MAX_KEY_LENGTH,
             IdentityError::KeyTooLong
         );
 
-        // Validate value length (ERC-8004 adaptation: max 256 bytes)
+        // Validate value length (ERC-8004 adaptation: registry-configured max, 256 bytes by default)
         require!(
-            value.len() <= MetadataEntry::MAX_VALUE_LENGTH,
+            ctx.accounts.config.accepts_value_length(value.len()),
             IdentityError::ValueTooLong
         );
 
+        let capacity = AgentAccount::metadata_capacity(
+            ctx.accounts.agent_account.to_account_info().data_len(),
+            ctx.accounts.agent_account.token_uri.len(),
+        );
         let agent = &mut ctx.accounts.agent_account;
 
         // Find existing entry or add new one
@@ -346,18 +1041,20 @@ pub mod identity_registry {
             // Update existing entry
             entry.value = value.clone();
         } else {
-            // Add new entry (max 10 entries)
-            require!(
-                agent.metadata.len() < AgentAccount::MAX_METADATA_ENTRIES,
-                IdentityError::MetadataLimitReached
-            );
-
             agent.metadata.push(MetadataEntry {
                 key: key.clone(),
                 value: value.clone(),
             });
         }
 
+        // 10 slots unless the account was resized; a value longer than the
+        // 256 bytes reserved per slot uses the slack of the others
+        require!(
+            MetadataEntry::fit_in(&agent.metadata, capacity),
+            IdentityError::MetadataLimitReached
+        );
+        agent.updated_at = Clock::get()?.unix_timestamp;
+
         // Emit event (ERC-8004 spec: MetadataSet event)
         emit!(MetadataSet {
             agent_id: agent.agent_id,
@@ -375,38 +1072,322 @@ pub mod identity_registry {
         Ok(())
     }
 
+    /// Let another key edit the agent's metadata (owner only)
+    ///
+    /// The delegate may call `set_metadata`, nothing else: it cannot set the
+    /// URI (which also rewrites the NFT metadata, signed by the owner),
+    /// transfer the agent or change the delegate. Ownership changes clear it.
+    ///
+    /// # Arguments
+    /// * `delegate` - New delegate, or `None` to revoke
+    ///
+    /// # Events
+    /// * `MetadataDelegateSet` - Emitted when the delegate is set or revoked
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the agent owner
+    pub fn set_metadata_delegate(
+        ctx: Context<SetMetadataDelegate>,
+        delegate: Option<Pubkey>,
+    ) -> Result<()> {
+        let agent = &mut ctx.accounts.agent_account;
+        agent.metadata_delegate = delegate;
+        agent.updated_at = Clock::get()?.unix_timestamp;
+
+        emit!(MetadataDelegateSet {
+            agent_id: agent.agent_id,
+            delegate,
+        });
+
+        msg!("Agent {} metadata delegate set to {:?}", agent.agent_id, delegate);
+
+        Ok(())
+    }
+
+    /// Grow (or shrink) the agent account's metadata capacity in place (owner only)
+    ///
+    /// Reallocates `AgentAccount` to hold `new_max_entries` entries instead of
+    /// the base 10, so `set_metadata` can store more keys without extension
+    /// PDAs. The owner pays the extra rent on growth and is refunded on shrink.
+    /// Shrinking never drops entries: the new capacity must still fit them.
+    ///
+    /// # Arguments
+    /// * `new_max_entries` - New capacity (10 to 28 entries)
+    ///
+    /// # Events
+    /// * `AgentMetadataResized` - Emitted with the new capacity
+    ///
+    /// # Errors
+    /// * `InvalidMetadataCapacity` - Capacity outside 10..=28
+    /// * `MetadataCapacityTooSmall` - Capacity below the number of existing entries
+    /// * `Unauthorized` - If caller is not the agent owner
+    pub fn resize_agent_metadata(
+        ctx: Context<ResizeAgentMetadata>,
+        new_max_entries: u16,
+    ) -> Result<()> {
+        let new_max_entries = new_max_entries as usize;
+        require!(
+            (AgentAccount::MAX_METADATA_ENTRIES..=AgentAccount::MAX_RESIZED_METADATA_ENTRIES)
+                .contains(&new_max_entries),
+            IdentityError::InvalidMetadataCapacity
+        );
+
+        let agent = &mut ctx.accounts.agent_account;
+        require!(
+            MetadataEntry::fit_in(&agent.metadata, new_max_entries),
+            IdentityError::MetadataCapacityTooSmall
+        );
+        agent.updated_at = Clock::get()?.unix_timestamp;
+
+        emit!(AgentMetadataResized {
+            agent_id: agent.agent_id,
+            max_entries: new_max_entries as u16,
+        });
+
+        msg!(
+            "Agent {} metadata capacity set to {} entries",
+            agent.agent_id,
+            new_max_entries
+        );
+
+        Ok(())
+    }
+
+    /// Remove a metadata entry from the agent account
+    ///
+    /// Frees a slot under the 10-entry cap for a new key.
+    ///
+    /// # Arguments
+    /// * `key` - Metadata key to remove
+    ///
+    /// # Events
+    /// * `MetadataRemoved` - Emitted when the entry is removed
+    ///
+    /// # Errors
+    /// * `MetadataNotFound` - If no entry has this key
+    /// * `Unauthorized` - If caller is neither the agent owner nor its metadata delegate
+    /// * `StaleOwner` - Strict mode only, see `set_metadata`
+    pub fn remove_metadata(ctx: Context<SetMetadata>, key: String) -> Result<()> {
+        require!(
+            ctx.accounts.agent_account.can_edit_metadata(&ctx.accounts.owner.key()),
+            IdentityError::Unauthorized
+        );
+
+        // Strict mode: cached owner must still hold the agent NFT
+        if let Some(token_account) = &ctx.accounts.owner_token_account {
+            require!(
+                ctx.accounts.agent_account.is_held_by(
+                    &token_account.mint,
+                    &token_account.owner,
+                    token_account.amount,
+                ),
+                IdentityError::StaleOwner
+            );
+        }
+
+        let agent = &mut ctx.accounts.agent_account;
+        require!(agent.remove_metadata(&key), IdentityError::MetadataNotFound);
+        agent.updated_at = Clock::get()?.unix_timestamp;
+
+        emit!(MetadataRemoved {
+            agent_id: agent.agent_id,
+            key: key.clone(),
+            extension_index: None,
+        });
+
+        msg!("Metadata '{}' removed from agent {}", key, agent.agent_id);
+
+        Ok(())
+    }
+
+    /// Set the agent's directory category
+    ///
+    /// Categories are a fixed-offset `u16` on AgentAccount, so marketplaces can
+    /// filter agents by vertical with a getProgramAccounts memcmp at
+    /// `AgentAccount::CATEGORY_OFFSET`. See `AgentCategory` for the registry.
+    ///
+    /// # Arguments
+    /// * `category` - Standard category (0-8) or deployment-specific (>= 1000)
+    ///
+    /// # Events
+    /// * `CategorySet` - Emitted when category is successfully set
+    ///
+    /// # Errors
+    /// * `InvalidCategory` - If category is in the reserved range
+    /// * `Unauthorized` - If caller is not the agent owner
+    pub fn set_category(ctx: Context<SetCategory>, category: u16) -> Result<()> {
+        require!(AgentCategory::is_valid(category), IdentityError::InvalidCategory);
+
+        let agent = &mut ctx.accounts.agent_account;
+        agent.category = category;
+
+        emit!(CategorySet {
+            agent_id: agent.agent_id,
+            category,
+            set_by: ctx.accounts.owner.key(),
+        });
+
+        msg!("Agent {} category set to {}", agent.agent_id, category);
+
+        Ok(())
+    }
+
+    /// Declare whether the agent is autonomous, human-operated or hybrid
+    ///
+    /// The type sits at a fixed offset (`AgentAccount::OPERATOR_TYPE_OFFSET`) so it
+    /// can be filtered with getProgramAccounts memcmp. Changing it invalidates any
+    /// existing `OperatorAttestation` until the registry authority re-attests.
+    ///
+    /// # Arguments
+    /// * `operator_type` - 0 = Autonomous, 1 = HumanOperated, 2 = Hybrid
+    ///
+    /// # Events
+    /// * `OperatorTypeSet` - Emitted when operator type is successfully set
+    ///
+    /// # Errors
+    /// * `InvalidOperatorType` - If operator_type is not a known value
+    /// * `Unauthorized` - If caller is not the agent owner
+    pub fn set_operator_type(ctx: Context<SetOperatorType>, operator_type: u8) -> Result<()> {
+        require!(
+            OperatorType::from_u8(operator_type).is_some(),
+            IdentityError::InvalidOperatorType
+        );
+
+        let agent = &mut ctx.accounts.agent_account;
+        agent.operator_type = operator_type;
+
+        emit!(OperatorTypeSet {
+            agent_id: agent.agent_id,
+            operator_type,
+            set_by: ctx.accounts.owner.key(),
+        });
+
+        msg!("Agent {} operator type set to {}", agent.agent_id, operator_type);
+
+        Ok(())
+    }
+
+    /// Registry authority vouches for an agent's declared operator type
+    ///
+    /// Creates or refreshes the agent's `OperatorAttestation` PDA with the type
+    /// currently declared on the agent account. Consumers should treat the
+    /// attestation as valid only while its `operator_type` matches the agent's.
+    ///
+    /// # Events
+    /// * `OperatorTypeAttested` - Emitted when the attestation is written
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the registry authority
+    pub fn attest_operator_type(ctx: Context<AttestOperatorType>) -> Result<()> {
+        let agent = &ctx.accounts.agent_account;
+        let attestation = &mut ctx.accounts.operator_attestation;
+
+        attestation.agent_id = agent.agent_id;
+        attestation.operator_type = agent.operator_type;
+        attestation.attester = ctx.accounts.authority.key();
+        attestation.attested_at = Clock::get()?.unix_timestamp;
+        attestation.bump = ctx.bumps.operator_attestation;
+
+        emit!(OperatorTypeAttested {
+            agent_id: agent.agent_id,
+            operator_type: agent.operator_type,
+            attester: attestation.attester,
+        });
+
+        msg!("Agent {} operator type {} attested", agent.agent_id, agent.operator_type);
+
+        Ok(())
+    }
+
+    /// Deprecate the agent, optionally pointing clients to a successor
+    ///
+    /// Sets `status` to Deprecated (fixed offset `AgentAccount::STATUS_OFFSET`) and
+    /// records `successor_agent`, so views and the other registries can redirect
+    /// consumers to the new version while references to this agent keep working.
+    /// Can be called again to change the successor.
+    ///
+    /// # Arguments
+    /// * `successor_id` - Agent ID of the successor, or None for a plain deprecation
+    ///
+    /// # Events
+    /// * `AgentDeprecated` - Emitted when the agent is deprecated
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the agent owner
+    /// * `InvalidSuccessor` - If the successor account is missing, does not match
+    ///   `successor_id`, or is the agent itself
+    pub fn deprecate_agent(ctx: Context<DeprecateAgent>, successor_id: Option<u64>) -> Result<()> {
+        let agent = &mut ctx.accounts.agent_account;
+
+        if let Some(successor_id) = successor_id {
+            let successor = ctx
+                .accounts
+                .successor_account
+                .as_ref()
+                .ok_or(IdentityError::InvalidSuccessor)?;
+            require!(
+                successor.agent_id == successor_id && successor_id != agent.agent_id,
+                IdentityError::InvalidSuccessor
+            );
+        }
+
+        agent.status = AgentStatus::Deprecated as u8;
+        agent.successor_agent = successor_id;
+
+        emit!(AgentDeprecated {
+            agent_id: agent.agent_id,
+            successor_agent: successor_id,
+            deprecated_by: ctx.accounts.owner.key(),
+        });
+
+        msg!("Agent {} deprecated, successor {:?}", agent.agent_id, successor_id);
+
+        Ok(())
+    }
+
     /// Set agent URI (ERC-8004 spec: setAgentUri(agentId, newUri))
     ///
-    /// Updates the token URI for an agent. Only the agent owner can call this.
+    /// Updates the token URI for an agent and mirrors it into the NFT metadata.
+    /// Owner only: the owner holds the Metaplex update authority the NFT update
+    /// is signed with, so a metadata delegate can't keep both URIs in sync and
+    /// is rejected.
     ///
     /// # Arguments
-    /// * `new_uri` - New IPFS/Arweave/HTTP URI (max 200 bytes, can be empty string)
+    /// * `new_uri` - New IPFS/Arweave/HTTP URI (max `max_uri_length` bytes, can be empty string)
     ///
     /// # Events
     /// * `AgentUriSet` - Emitted when URI is successfully updated
     ///
     /// # Errors
-    /// * `UriTooLong` - If new_uri exceeds 200 bytes
+    /// * `UriTooLong` - If new_uri exceeds the registry's max_uri_length
+    /// * `InvalidUriScheme` - If new_uri is not ipfs://, ar://, https:// or http://
     /// * `Unauthorized` - If caller is not the agent owner
     pub fn set_agent_uri(ctx: Context<SetAgentUri>, new_uri: String) -> Result<()> {
-        // Validate URI length (ERC-8004 spec: max 200 bytes)
+        // Validate URI length (registry-configured max, 200 bytes by default)
         require!(
-            new_uri.len() <= AgentAccount::MAX_URI_LENGTH,
+            ctx.accounts.config.accepts_uri_length(new_uri.len()),
             IdentityError::UriTooLong
         );
+        validate_uri(&new_uri)?;
 
         let agent = &mut ctx.accounts.agent_account;
 
         // Update AgentAccount URI
         agent.token_uri = new_uri.clone();
+        agent.updated_at = Clock::get()?.unix_timestamp;
 
         // Sync URI to Metaplex NFT metadata using UpdateAsUpdateAuthorityV2
         // This ensures wallets and marketplaces display the updated URI
+        // Keep the royalty the NFT was minted with
+        let seller_fee_basis_points =
+            MetaplexMetadata::safe_deserialize(&ctx.accounts.agent_metadata.try_borrow_data()?)?
+                .seller_fee_basis_points;
+
         let metadata_data = Data {
             name: agent.nft_name.clone(),
             symbol: agent.nft_symbol.clone(),
-            uri: new_uri.clone(),
-            seller_fee_basis_points: 0,
+            uri: nft_uri(&new_uri),
+            seller_fee_basis_points,
             creators: None,
         };
 
@@ -449,12 +1430,28 @@ pub mod identity_registry {
     /// # Arguments
     /// None - new owner is derived from SPL Token account
     ///
+    /// A pending two-step transfer (see `initiate_transfer`) is cleared, since
+    /// the NFT no longer sits in the account it was delegated from.
+    ///
+    /// # Arguments
+    /// * `record_history` - Append an `OwnershipHistory` entry (pass `ownership_history`)
+    ///
     /// # Events
     /// * `AgentOwnerSynced` - Emitted when owner is successfully synced
+    /// * `OwnershipRecorded` - Emitted when a history entry is appended
     ///
     /// # Errors
     /// * `InvalidTokenAccount` - If token account doesn't hold the NFT
-    pub fn sync_owner(ctx: Context<SyncOwner>) -> Result<()> {
+    /// * `InvalidNftSupply` / `InvalidNftDecimals` - If the mint isn't a valid agent NFT
+    /// * `OwnershipHistoryMismatch` - `ownership_history` passed without
+    ///   `record_history`, or missing with it
+    pub fn sync_owner(ctx: Context<SyncOwner>, record_history: bool) -> Result<()> {
+        assert_agent_nft_invariants(ctx.accounts.agent_mint.supply, ctx.accounts.agent_mint.decimals)?;
+        require!(
+            ctx.accounts.ownership_history.is_some() == record_history,
+            IdentityError::OwnershipHistoryMismatch
+        );
+
         let agent = &mut ctx.accounts.agent_account;
         let token_account = &ctx.accounts.token_account;
 
@@ -467,32 +1464,63 @@ pub mod identity_registry {
         let old_owner = agent.owner;
         let new_owner = token_account.owner;
 
-        // Update cached owner
-        agent.owner = new_owner;
-
         // Transfer Metaplex update_authority to new owner (ERC-8004 compliance)
         // This allows the new owner to modify tokenURI via set_agent_uri()
+        // If the NFT moved by a plain SPL transfer during a two-step transfer or
+        // while approved, the PDA holds the update_authority and the pending
+        // transfer or approval is void
+        let agent_mint = agent.agent_mint;
+        let bump = [agent.bump];
+        let seeds: &[&[u8]] = &[b"agent", agent_mint.as_ref(), &bump];
+        let (authority, signer_seeds) = if agent.pda_holds_update_authority() {
+            (agent.to_account_info(), vec![seeds])
+        } else {
+            (ctx.accounts.old_owner_signer.to_account_info(), vec![])
+        };
+        agent.pending_owner = None;
+
         UpdateV1CpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
-            .authority(&ctx.accounts.old_owner_signer.to_account_info())
+            .authority(&authority)
             .mint(&ctx.accounts.agent_mint.to_account_info())
             .metadata(&ctx.accounts.agent_metadata.to_account_info())
             .payer(&ctx.accounts.old_owner_signer.to_account_info())
             .system_program(&ctx.accounts.system_program.to_account_info())
             .sysvar_instructions(&ctx.accounts.sysvar_instructions.to_account_info())
             .new_update_authority(new_owner)
-            .invoke()?;
+            .invoke_signed(&signer_seeds)?;
+
+        // Update cached owner
+        agent.set_owner(new_owner);
+        agent.updated_at = Clock::get()?.unix_timestamp;
+        let agent_id = agent.agent_id;
 
         // Emit event
         emit!(AgentOwnerSynced {
-            agent_id: agent.agent_id,
+            agent_id,
             old_owner,
             new_owner,
             agent_mint: agent.agent_mint,
         });
 
+        if let Some(history) = ctx.accounts.ownership_history.as_mut() {
+            record_ownership(history, ctx.bumps.ownership_history, agent, old_owner, new_owner);
+        }
+
+        if new_owner != old_owner {
+            move_owner_agent(
+                &mut ctx.accounts.old_owner_agents,
+                ctx.bumps.old_owner_agents,
+                old_owner,
+                &mut ctx.accounts.new_owner_agents,
+                ctx.bumps.new_owner_agents,
+                new_owner,
+                agent_id,
+            );
+        }
+
         msg!(
             "Agent {} owner synced: {} -> {} (update_authority transferred)",
-            agent.agent_id,
+            agent_id,
             old_owner,
             new_owner
         );
@@ -500,46 +1528,409 @@ pub mod identity_registry {
         Ok(())
     }
 
-    /// Get agent owner by agent mint (ERC-721: ownerOf)
-    ///
-    /// Query the current owner of an agent NFT. This is a view function
-    /// that doesn't modify state.
+    /// Refresh the feedback and validation counts cached on the agent account
     ///
-    /// # Arguments
-    /// None - agent_mint is derived from context
+    /// Denormalizes downstream registry state so a single AgentAccount read is
+    /// enough for a summary card. Counts are synced lazily by the owner rather
+    /// than pushed on every feedback/validation, so they are only as fresh as
+    /// the last call: readers needing exact figures should query the Reputation
+    /// and Validation registries directly.
     ///
-    /// # Returns
-    /// The owner's public key
+    /// The feedback count is read from the agent's `AgentReputationMetadata`
+    /// (`agent_reputation`, may not exist yet if no feedback was given), the
+    /// validation count (`total_requests`, requests ever created) from its
+    /// `AgentValidationMetadata` (`agent_validation`, may not exist yet if no
+    /// validation was requested). Both PDAs are checked for seeds, owner and
+    /// layout, so the counts can't be spoofed.
     ///
     /// # Events
-    /// None (view function)
-    pub fn owner_of(ctx: Context<OwnerOf>) -> Result<Pubkey> {
-        Ok(ctx.accounts.agent_account.owner)
+    /// * `AgentCountsSynced` - Emitted with the new cached counts
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the agent owner
+    /// * `InvalidCountSource` - An account isn't this agent's reputation/validation
+    ///   account
+    pub fn sync_agent_counts(ctx: Context<SyncAgentCounts>) -> Result<()> {
+        let agent_id = ctx.accounts.agent_account.agent_id;
+
+        // Feedback count: no reputation account yet means no feedback yet
+        let reputation = &ctx.accounts.agent_reputation;
+        let feedback_count = if reputation.data_is_empty() {
+            0
+        } else {
+            require!(
+                reputation.owner == &REPUTATION_REGISTRY_ID,
+                IdentityError::InvalidCountSource
+            );
+            AgentAccount::feedback_count_from_data(&reputation.try_borrow_data()?, agent_id)
+                .ok_or(IdentityError::InvalidCountSource)?
+        };
+
+        // Validation count: no validation account yet means no request yet
+        let validation = &ctx.accounts.agent_validation;
+        let validation_count = if validation.data_is_empty() {
+            0
+        } else {
+            require!(
+                validation.owner == &ctx.accounts.config.validation_registry,
+                IdentityError::InvalidCountSource
+            );
+            AgentAccount::validation_count_from_data(&validation.try_borrow_data()?, agent_id)
+                .ok_or(IdentityError::InvalidCountSource)?
+        };
+
+        let agent = &mut ctx.accounts.agent_account;
+        agent.cached_feedback_count = feedback_count;
+        agent.cached_validation_count = validation_count;
+
+        emit!(AgentCountsSynced {
+            agent_id,
+            feedback_count,
+            validation_count,
+        });
+
+        msg!(
+            "Agent {} counts synced: {} feedbacks, {} validations",
+            agent_id,
+            feedback_count,
+            validation_count
+        );
+
+        Ok(())
     }
 
-    /// Create a metadata extension PDA for additional metadata storage
-    ///
-    /// Allows storing more than 10 metadata entries by creating extension accounts.
-    /// Each extension can hold 10 additional entries.
+    /// Sync the cached owner of many agents at once (e.g. after an airdrop)
     ///
-    /// # Arguments
-    /// * `extension_index` - Index of the extension (0, 1, 2, ...)
+    /// Operational counterpart of `sync_owner` for large-scale distribution events.
+    /// Agents are passed via `remaining_accounts` in groups of five:
+    /// `[agent_account (mut), token_account, agent_mint, agent_metadata (mut),
+    /// new_owner_agents (mut)]`, the last being the `owner_agents` list of the
+    /// token account's owner.
+    /// Each agent whose token account holds the NFT (amount = 1) for a wallet other
+    /// than the cached owner gets its owner and Metaplex update_authority moved to
+    /// that wallet, and moves from the signer's agent list to the new owner's
+    /// (lists are created as needed, paid by the signer). Agents whose token
+    /// account no longer holds the NFT, or that are already in sync, are skipped
+    /// rather than failing the batch.
     ///
     /// # Events
-    /// * None (creation only)
+    /// * `AgentOwnerSynced` - Emitted once per updated agent
     ///
     /// # Errors
-    /// * `InvalidExtensionIndex` - If extension index > 255
-    pub fn create_metadata_extension(
-        ctx: Context<CreateMetadataExtension>,
-        extension_index: u8,
+    /// * `InvalidBatchAccounts` - If remaining accounts are not groups of five
+    /// * `BatchTooLarge` - If more than `AgentAccount::MAX_SYNC_BATCH` agents are passed
+    /// * `InvalidTokenAccount` - If a token account or mint doesn't match its agent
+    /// * `Unauthorized` - If the signer isn't the cached owner of an agent to update
+    /// * `InvalidOwnerAgentList` - If an agent list isn't the PDA of its owner
+    pub fn sync_owners_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SyncOwnersBatch<'info>>,
     ) -> Result<()> {
-        let extension = &mut ctx.accounts.metadata_extension;
+        let groups = ctx
+            .remaining_accounts
+            .chunks_exact(AgentAccount::SYNC_BATCH_ACCOUNTS_PER_AGENT);
+
+        require!(groups.remainder().is_empty(), IdentityError::InvalidBatchAccounts);
+        require!(
+            groups.len() <= AgentAccount::MAX_SYNC_BATCH,
+            IdentityError::BatchTooLarge
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let mut synced: u32 = 0;
+
+        for group in groups {
+            let mut agent: Account<AgentAccount> = Account::try_from(&group[0])?;
+            let token_account: Account<TokenAccount> = Account::try_from(&group[1])?;
+            let agent_mint = &group[2];
+            let agent_metadata = &group[3];
+            let new_owner_agents = &group[4];
+
+            require!(
+                token_account.mint == agent.agent_mint && agent_mint.key() == agent.agent_mint,
+                IdentityError::InvalidTokenAccount
+            );
+
+            // Skip agents whose token account no longer holds the NFT
+            if token_account.amount != 1 {
+                msg!("Agent {} skipped: token account does not hold the NFT", agent.agent_id);
+                continue;
+            }
+
+            let old_owner = agent.owner;
+            let new_owner = token_account.owner;
+
+            // Skip agents already in sync
+            if new_owner == old_owner {
+                continue;
+            }
+
+            // Skip agents in a two-step transfer or approved for transfer
+            // (use `sync_owner`, which clears both)
+            if agent.pda_holds_update_authority() {
+                msg!("Agent {} skipped: transfer pending", agent.agent_id);
+                continue;
+            }
+
+            // Old owner (current update_authority) must sign
+            require!(
+                old_owner == ctx.accounts.old_owner_signer.key(),
+                IdentityError::Unauthorized
+            );
+
+            UpdateV1CpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+                .authority(&ctx.accounts.old_owner_signer.to_account_info())
+                .mint(agent_mint)
+                .metadata(agent_metadata)
+                .payer(&ctx.accounts.old_owner_signer.to_account_info())
+                .system_program(&ctx.accounts.system_program.to_account_info())
+                .sysvar_instructions(&ctx.accounts.sysvar_instructions.to_account_info())
+                .new_update_authority(new_owner)
+                .invoke()?;
+
+            agent.set_owner(new_owner);
+            agent.updated_at = now;
+            agent.exit(&crate::ID)?;
+
+            move_owner_agent_accounts(
+                &ctx.accounts.old_owner_agents.to_account_info(),
+                old_owner,
+                new_owner_agents,
+                new_owner,
+                agent.agent_id,
+                &ctx.accounts.old_owner_signer.to_account_info(),
+                &ctx.accounts.system_program.to_account_info(),
+            )?;
+
+            emit!(AgentOwnerSynced {
+                agent_id: agent.agent_id,
+                old_owner,
+                new_owner,
+                agent_mint: agent.agent_mint,
+            });
+
+            synced += 1;
+        }
+
+        msg!("Batch owner sync: {} agent(s) updated", synced);
+
+        Ok(())
+    }
+
+    /// Recreate the agent owner's token account after it was closed
+    ///
+    /// If the cached owner closed their associated token account (e.g. to recover
+    /// rent after moving the NFT elsewhere), this instruction recreates the ATA for
+    /// the cached owner. When the NFT now sits in a different holding account,
+    /// pass it as `holder_token_account` and the cached owner and Metaplex
+    /// update_authority are re-synced to its owner, like `sync_owner`. Re-syncing
+    /// also moves the agent between the `old_owner_agents` and `new_owner_agents`
+    /// lists, which must then be passed (created as needed, paid by the owner).
+    ///
+    /// # Events
+    /// * `AgentOwnerSynced` - Emitted if the owner was re-synced to the holder
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the cached agent owner
+    /// * `InvalidTokenAccount` - If the holder token account doesn't hold the NFT
+    /// * `InvalidNftSupply` / `InvalidNftDecimals` - If the mint isn't a valid agent NFT
+    /// * `TransferPending` - If re-syncing during a two-step transfer or while
+    ///   approved for transfer (use `sync_owner`)
+    /// * `InvalidOwnerAgentList` - If re-syncing without both agent lists, or
+    ///   one isn't the PDA of its owner
+    pub fn reopen_agent_token_account(ctx: Context<ReopenAgentTokenAccount>) -> Result<()> {
+        assert_agent_nft_invariants(ctx.accounts.agent_mint.supply, ctx.accounts.agent_mint.decimals)?;
+
+        let agent = &mut ctx.accounts.agent_account;
+
+        let Some(holder) = &ctx.accounts.holder_token_account else {
+            msg!(
+                "Agent {} token account reopened for owner {}",
+                agent.agent_id,
+                agent.owner
+            );
+            return Ok(());
+        };
+
+        // Holder must actually hold the agent NFT
+        require!(holder.amount == 1, IdentityError::InvalidTokenAccount);
+
+        let old_owner = agent.owner;
+        let new_owner = holder.owner;
+
+        if new_owner == old_owner {
+            msg!("Agent {} owner already in sync", agent.agent_id);
+            return Ok(());
+        }
+
+        require!(!agent.pda_holds_update_authority(), IdentityError::TransferPending);
+
+        let (Some(old_owner_agents), Some(new_owner_agents)) =
+            (&ctx.accounts.old_owner_agents, &ctx.accounts.new_owner_agents)
+        else {
+            return err!(IdentityError::InvalidOwnerAgentList);
+        };
+        move_owner_agent_accounts(
+            &old_owner_agents.to_account_info(),
+            old_owner,
+            &new_owner_agents.to_account_info(),
+            new_owner,
+            agent.agent_id,
+            &ctx.accounts.owner.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+
+        // Update cached owner
+        agent.set_owner(new_owner);
+        agent.updated_at = Clock::get()?.unix_timestamp;
+
+        // Transfer Metaplex update_authority to the actual holder
+        UpdateV1CpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+            .authority(&ctx.accounts.owner.to_account_info())
+            .mint(&ctx.accounts.agent_mint.to_account_info())
+            .metadata(&ctx.accounts.agent_metadata.to_account_info())
+            .payer(&ctx.accounts.owner.to_account_info())
+            .system_program(&ctx.accounts.system_program.to_account_info())
+            .sysvar_instructions(&ctx.accounts.sysvar_instructions.to_account_info())
+            .new_update_authority(new_owner)
+            .invoke()?;
+
+        emit!(AgentOwnerSynced {
+            agent_id: agent.agent_id,
+            old_owner,
+            new_owner,
+            agent_mint: agent.agent_mint,
+        });
+
+        msg!(
+            "Agent {} token account reopened, owner re-synced: {} -> {}",
+            agent.agent_id,
+            old_owner,
+            new_owner
+        );
+
+        Ok(())
+    }
+
+    /// Get agent owner by agent mint (ERC-721: ownerOf)
+    ///
+    /// Query the current owner of an agent NFT. This is a view function
+    /// that doesn't modify state.
+    ///
+    /// # Arguments
+    /// None - agent_mint is derived from context
+    ///
+    /// # Returns
+    /// The owner's public key
+    ///
+    /// # Events
+    /// None (view function)
+    pub fn owner_of(ctx: Context<OwnerOf>) -> Result<Pubkey> {
+        Ok(ctx.accounts.agent_account.owner)
+    }
+
+    /// Get agent owner for a given agent_id (CPI view)
+    ///
+    /// Entry point for downstream registries: they CPI into this instead of
+    /// parsing `AgentAccount` bytes by offset, so the account layout can
+    /// change without breaking them. The result is returned via
+    /// `set_return_data`.
+    ///
+    /// # Arguments
+    /// * `agent_id` - Agent the caller expects `agent_account` to hold
+    ///
+    /// # Returns
+    /// The owner's public key
+    ///
+    /// # Events
+    /// None (view function)
+    pub fn get_owner(ctx: Context<GetOwner>, _agent_id: u64) -> Result<Pubkey> {
+        Ok(ctx.accounts.agent_account.owner)
+    }
+
+    /// Agents registered on a given UTC day (view)
+    ///
+    /// Cheap growth metric read from `RegistryStats`, without scanning agents.
+    ///
+    /// # Arguments
+    /// * `day_epoch` - Days since the Unix epoch (`unix_timestamp / 86400`)
+    ///
+    /// # Returns
+    /// Registrations that day; 0 for days older than the last 30
+    ///
+    /// # Events
+    /// None (view function)
+    pub fn get_daily_registrations(ctx: Context<GetRegistryStats>, day_epoch: i64) -> Result<u64> {
+        Ok(ctx.accounts.registry_stats.daily_registrations(day_epoch))
+    }
+
+    /// Get the registry's collection NFT details (view)
+    ///
+    /// Combines the config's collection mint with the name and URI read from
+    /// its Metaplex metadata account, so clients don't need to parse the
+    /// Metaplex layout themselves.
+    ///
+    /// # Returns
+    /// `CollectionView` with the collection mint, name and URI
+    ///
+    /// # Events
+    /// None (view function)
+    ///
+    /// # Errors
+    /// * `InvalidCollectionMetadata` - Account is not the collection's Metaplex metadata
+    pub fn get_collection(ctx: Context<GetCollection>) -> Result<CollectionView> {
+        let collection_mint = ctx.accounts.config.collection_mint;
+        let info = &ctx.accounts.collection_metadata;
+
+        require!(
+            info.owner == &TOKEN_METADATA_PROGRAM_ID
+                && info.key() == MetaplexMetadata::find_pda(&collection_mint).0,
+            IdentityError::InvalidCollectionMetadata
+        );
+
+        let metadata = MetaplexMetadata::safe_deserialize(&info.try_borrow_data()?)
+            .map_err(|_| error!(IdentityError::InvalidCollectionMetadata))?;
+
+        Ok(CollectionView::new(collection_mint, &metadata.name, &metadata.uri))
+    }
+
+    /// Create a metadata extension PDA for additional metadata storage
+    ///
+    /// Allows storing more than 10 metadata entries by creating extension accounts.
+    /// Each extension can hold 10 additional entries, up to
+    /// `AgentAccount::MAX_EXTENSIONS` extensions per agent.
+    ///
+    /// # Arguments
+    /// * `extension_index` - Index of the extension (0 to MAX_EXTENSIONS - 1)
+    ///
+    /// # Events
+    /// * `MetadataExtensionCreated` - Emitted when the extension is created
+    ///
+    /// # Errors
+    /// * `InvalidExtensionIndex` - If the index is out of range or the agent
+    ///   already has MAX_EXTENSIONS extensions
+    pub fn create_metadata_extension(
+        ctx: Context<CreateMetadataExtension>,
+        extension_index: u8,
+    ) -> Result<()> {
+        let agent = &mut ctx.accounts.agent_account;
+        require!(
+            agent.can_create_extension(extension_index),
+            IdentityError::InvalidExtensionIndex
+        );
+        agent.extension_count += 1;
+
+        let extension = &mut ctx.accounts.metadata_extension;
         extension.agent_mint = ctx.accounts.agent_mint.key();
+        extension.agent_id = agent.agent_id;
         extension.extension_index = extension_index;
         extension.metadata = Vec::new();
         extension.bump = ctx.bumps.metadata_extension;
 
+        emit!(MetadataExtensionCreated {
+            agent_mint: extension.agent_mint,
+            extension_index,
+        });
+
         msg!(
             "Created metadata extension {} for agent mint {}",
             extension_index,
@@ -549,6 +1940,40 @@ pub mod identity_registry {
         Ok(())
     }
 
+    /// Close a metadata extension PDA and return its rent to the owner
+    ///
+    /// Its entries are dropped. Extensions are independent PDAs, so other indices
+    /// are unaffected, and the same index can be created again afterwards.
+    ///
+    /// # Arguments
+    /// * `extension_index` - Index of the extension to close
+    ///
+    /// # Events
+    /// * `MetadataExtensionClosed` - Emitted when the extension is closed
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the agent owner
+    pub fn close_metadata_extension(
+        ctx: Context<CloseMetadataExtension>,
+        extension_index: u8,
+    ) -> Result<()> {
+        let agent = &mut ctx.accounts.agent_account;
+        agent.extension_count = agent.extension_count.saturating_sub(1);
+
+        emit!(MetadataExtensionClosed {
+            agent_mint: agent.agent_mint,
+            extension_index,
+        });
+
+        msg!(
+            "Closed metadata extension {} for agent mint {}",
+            extension_index,
+            agent.agent_mint
+        );
+
+        Ok(())
+    }
+
     /// Set metadata in an extension PDA
     ///
     /// # Arguments
@@ -561,8 +1986,9 @@ pub mod identity_registry {
     ///
     /// # Errors
     /// * `KeyTooLong` - If key exceeds 32 bytes
-    /// * `ValueTooLong` - If value exceeds 256 bytes
-    /// * `MetadataLimitReached` - If extension already has 10 entries
+    /// * `ValueTooLong` - If value exceeds the registry's max_value_length
+    /// * `MetadataLimitReached` - If the entries would no longer fit the extension's
+    ///   10 slots (256 value bytes each)
     pub fn set_metadata_extended(
         ctx: Context<SetMetadataExtended>,
         _extension_index: u8,
@@ -575,7 +2001,7 @@ pub mod identity_registry {
             IdentityError::KeyTooLong
         );
         require!(
-            value.len() <= MetadataEntry::MAX_VALUE_LENGTH,
+            ctx.accounts.config.accepts_value_length(value.len()),
             IdentityError::ValueTooLong
         );
 
@@ -585,13 +2011,12 @@ pub mod identity_registry {
         if let Some(entry) = extension.find_metadata_mut(&key) {
             entry.value = value.clone();
         } else {
-            // Add new entry if under limit
-            require!(
-                extension.metadata.len() < MetadataExtension::MAX_METADATA_ENTRIES,
-                IdentityError::MetadataLimitReached
-            );
             extension.metadata.push(MetadataEntry { key: key.clone(), value: value.clone() });
         }
+        require!(
+            MetadataEntry::fit_in(&extension.metadata, MetadataExtension::MAX_METADATA_ENTRIES),
+            IdentityError::MetadataLimitReached
+        );
 
         // Emit event
         emit!(MetadataSet {
@@ -604,6 +2029,37 @@ pub mod identity_registry {
         Ok(())
     }
 
+    /// Remove a metadata entry from an extension PDA
+    ///
+    /// # Arguments
+    /// * `extension_index` - Which extension to remove from
+    /// * `key` - Metadata key to remove
+    ///
+    /// # Events
+    /// * `MetadataRemoved` - Emitted when the entry is removed
+    ///
+    /// # Errors
+    /// * `MetadataNotFound` - If no entry has this key
+    /// * `Unauthorized` - If caller is not the agent owner
+    pub fn remove_metadata_extended(
+        ctx: Context<SetMetadataExtended>,
+        extension_index: u8,
+        key: String,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.metadata_extension.remove_metadata(&key),
+            IdentityError::MetadataNotFound
+        );
+
+        emit!(MetadataRemoved {
+            agent_id: ctx.accounts.agent_account.agent_id,
+            key,
+            extension_index: Some(extension_index),
+        });
+
+        Ok(())
+    }
+
     /// Get metadata from an extension PDA
     ///
     /// # Arguments
@@ -625,245 +2081,2372 @@ pub mod identity_registry {
         }
     }
 
-    /// Transfer agent NFT to new owner with automatic owner sync
+    /// Get all metadata entries of an extension PDA (view)
     ///
-    /// This is a convenience function that combines SPL Token transfer + sync_owner
-    /// in a single instruction.
+    /// Same 1024-byte return data cap and storage order as `get_all_metadata`.
+    ///
+    /// # Arguments
+    /// * `extension_index` - Which extension to read from
+    ///
+    /// # Returns
+    /// * All metadata entries of the extension, in storage order
+    pub fn get_all_metadata_extended(
+        ctx: Context<GetMetadataExtended>,
+        _extension_index: u8,
+    ) -> Result<Vec<MetadataEntry>> {
+        Ok(ctx.accounts.metadata_extension.metadata.clone())
+    }
+
+    /// Import an owner-signed metadata manifest submitted by a relayer
+    ///
+    /// Lets a backend populate many attributes in one transaction without the owner
+    /// paying or signing the transaction. The owner signs
+    /// `IMPORT_METADATA_DOMAIN || agent_mint || nonce || borsh(manifest)` off-chain
+    /// and the relayer places an Ed25519 program instruction verifying that
+    /// signature immediately before this one.
+    ///
+    /// Entries update existing keys in place (base account first, then extensions),
+    /// otherwise fill the base account and then extensions in order. Pass extension
+    /// PDAs 0, 1, 2, ... as remaining_accounts; missing ones are created (paid by
+    /// the relayer) only if entries land in them.
+    ///
+    /// The signature is bound to the agent mint and its current owner, so it stops
+    /// being valid once the agent changes hands, and to the agent's `import_nonce`,
+    /// which is incremented on import so each manifest applies only once.
+    ///
+    /// # Arguments
+    /// * `manifest` - Metadata entries to set
+    /// * `nonce` - The agent's current `import_nonce`, as signed by the owner
+    /// * `signature` - Owner's Ed25519 signature over the manifest message
     ///
     /// # Events
-    /// * `AgentOwnerSynced` - Emitted after successful transfer
+    /// * `MetadataSet` - Emitted for each entry
+    /// * `MetadataExtensionCreated` - Emitted for each extension created
     ///
     /// # Errors
-    /// * `TransferToSelf` - If destination is same as source
-    pub fn transfer_agent(ctx: Context<TransferAgent>) -> Result<()> {
-        // Prevent self-transfer
+    /// * `KeyTooLong` - If any key exceeds 32 bytes
+    /// * `ValueTooLong` - If any value exceeds the registry's max_value_length
+    /// * `DuplicateMetadataKey` - If the manifest sets the same key twice
+    /// * `InvalidImportNonce` - If `nonce` isn't the agent's current `import_nonce`
+    /// * `InvalidSignature` - If no matching owner Ed25519 check precedes this instruction
+    /// * `InvalidExtensionIndex` - If remaining_accounts aren't extension PDAs 0, 1, 2, ...
+    ///   or exceed `AgentAccount::MAX_EXTENSIONS`
+    /// * `MetadataLimitReached` - If the base account and extensions run out of space
+    pub fn import_metadata<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ImportMetadata<'info>>,
+        manifest: Vec<MetadataEntry>,
+        nonce: u64,
+        signature: [u8; 64],
+    ) -> Result<()> {
+        // Validate all entries up front
+        for (i, entry) in manifest.iter().enumerate() {
+            require!(
+                entry.key.len() <= MetadataEntry::MAX_KEY_LENGTH,
+                IdentityError::KeyTooLong
+            );
+            require!(
+                ctx.accounts.config.accepts_value_length(entry.value.len()),
+                IdentityError::ValueTooLong
+            );
+            require!(
+                !manifest[..i].iter().any(|other| other.key == entry.key),
+                IdentityError::DuplicateMetadataKey
+            );
+        }
+
+        let agent_mint = ctx.accounts.agent_mint.key();
+
         require!(
-            ctx.accounts.from_token_account.key() != ctx.accounts.to_token_account.key(),
-            IdentityError::TransferToSelf
+            nonce == ctx.accounts.agent_account.import_nonce,
+            IdentityError::InvalidImportNonce
         );
 
-        // Step 1: SPL Token transfer via CPI
-        let cpi_accounts = token::Transfer {
-            from: ctx.accounts.from_token_account.to_account_info(),
-            to: ctx.accounts.to_token_account.to_account_info(),
-            authority: ctx.accounts.owner.to_account_info(),
-        };
-        token::transfer(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                cpi_accounts,
-            ),
-            1, // NFT amount
+        // Owner must have signed this exact manifest for this agent and nonce
+        let message = import_metadata_message(&agent_mint, nonce, &manifest)?;
+        verify_preceding_ed25519(
+            &ctx.accounts.sysvar_instructions,
+            &ctx.accounts.agent_account.owner,
+            &message,
+            &signature,
         )?;
 
-        // Step 2: Transfer Metaplex update_authority to new owner (ERC-8004 compliance)
-        // This allows the new owner to modify tokenURI via set_agent_uri()
-        let new_owner = ctx.accounts.to_token_account.owner;
-        UpdateV1CpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
-            .authority(&ctx.accounts.owner.to_account_info())
-            .mint(&ctx.accounts.agent_mint.to_account_info())
-            .metadata(&ctx.accounts.agent_metadata.to_account_info())
-            .payer(&ctx.accounts.owner.to_account_info())
-            .system_program(&ctx.accounts.system_program.to_account_info())
-            .sysvar_instructions(&ctx.accounts.sysvar_instructions.to_account_info())
-            .new_update_authority(new_owner)
+        // Load extensions in index order, keeping missing ones in memory until needed
+        require!(
+            ctx.remaining_accounts.len() <= AgentAccount::MAX_EXTENSIONS as usize,
+            IdentityError::InvalidExtensionIndex
+        );
+
+        let mut extensions: Vec<(MetadataExtension, bool)> = Vec::new();
+        for (index, info) in ctx.remaining_accounts.iter().enumerate() {
+            let index = index as u8;
+            let (expected, bump) = Pubkey::find_program_address(
+                &[b"metadata_ext", agent_mint.as_ref(), &[index]],
+                &crate::ID,
+            );
+            require_keys_eq!(info.key(), expected, IdentityError::InvalidExtensionIndex);
+
+            if info.owner == &crate::ID {
+                let extension: Account<MetadataExtension> = Account::try_from(info)?;
+                extensions.push((extension.into_inner(), true));
+            } else {
+                extensions.push((
+                    MetadataExtension {
+                        agent_mint,
+                        agent_id: ctx.accounts.agent_account.agent_id,
+                        extension_index: index,
+                        metadata: Vec::new(),
+                        bump,
+                    },
+                    false,
+                ));
+            }
+        }
+
+        // Place entries: update in place, else base account, else first extension with room
+        let capacity = AgentAccount::metadata_capacity(
+            ctx.accounts.agent_account.to_account_info().data_len(),
+            ctx.accounts.agent_account.token_uri.len(),
+        );
+        let agent = &mut ctx.accounts.agent_account;
+        for entry in &manifest {
+            if let Some(existing) = agent.find_metadata_mut(&entry.key) {
+                existing.value = entry.value.clone();
+            } else if let Some(existing) = extensions
+                .iter_mut()
+                .find_map(|(extension, _)| extension.find_metadata_mut(&entry.key))
+            {
+                existing.value = entry.value.clone();
+            } else if MetadataEntry::fit_in(agent.metadata.iter().chain([entry]), capacity) {
+                agent.metadata.push(entry.clone());
+            } else {
+                let (extension, _) = extensions
+                    .iter_mut()
+                    .find(|(extension, _)| {
+                        MetadataEntry::fit_in(
+                            extension.metadata.iter().chain([entry]),
+                            MetadataExtension::MAX_METADATA_ENTRIES,
+                        )
+                    })
+                    .ok_or(IdentityError::MetadataLimitReached)?;
+                extension.metadata.push(entry.clone());
+            }
+        }
+
+        // Values updated in place may have outgrown their account
+        require!(
+            MetadataEntry::fit_in(&agent.metadata, capacity)
+                && extensions.iter().all(|(extension, _)| {
+                    MetadataEntry::fit_in(&extension.metadata, MetadataExtension::MAX_METADATA_ENTRIES)
+                }),
+            IdentityError::MetadataLimitReached
+        );
+
+        // Persist extensions, creating the ones that received entries
+        for ((extension, exists), info) in extensions.iter().zip(ctx.remaining_accounts) {
+            if !exists {
+                if extension.metadata.is_empty() {
+                    continue;
+                }
+
+                erc8004_common::create_pda_account(
+                    &ctx.accounts.payer.to_account_info(),
+                    info,
+                    &ctx.accounts.system_program.to_account_info(),
+                    8 + MetadataExtension::MAX_SIZE,
+                    &crate::ID,
+                    &[
+                        b"metadata_ext",
+                        agent_mint.as_ref(),
+                        &[extension.extension_index],
+                        &[extension.bump],
+                    ],
+                )?;
+                agent.extension_count += 1;
+
+                emit!(MetadataExtensionCreated {
+                    agent_mint,
+                    extension_index: extension.extension_index,
+                });
+            }
+
+            let mut data = info.try_borrow_mut_data()?;
+            extension.try_serialize(&mut &mut data[..])?;
+        }
+
+        agent.import_nonce += 1;
+
+        for entry in manifest {
+            emit!(MetadataSet {
+                agent_id: agent.agent_id,
+                indexed_key: entry.key.clone(),
+                key: entry.key,
+                value: entry.value,
+            });
+        }
+
+        msg!("Imported signed metadata manifest for agent {}", agent.agent_id);
+
+        Ok(())
+    }
+
+    /// Transfer agent NFT to new owner with automatic owner sync
+    ///
+    /// This is a convenience function that combines SPL Token transfer + sync_owner
+    /// in a single instruction.
+    ///
+    /// # Arguments
+    /// * `record_history` - Append an `OwnershipHistory` entry (pass
+    ///   `ownership_history`, paid by the sender)
+    ///
+    /// # Events
+    /// * `AgentOwnerSynced` - Emitted after successful transfer
+    /// * `OwnershipRecorded` - Emitted when a history entry is appended
+    ///
+    /// # Errors
+    /// * `TransferToSelf` - If destination is same as source
+    /// * `InvalidNftSupply` / `InvalidNftDecimals` - If the mint isn't a valid agent NFT
+    /// * `TransferPending` - If a two-step transfer or transfer approval is pending
+    /// * `AgentFrozen` - If the owner froze the agent (see `freeze_agent`)
+    /// * `OwnershipHistoryMismatch` - `ownership_history` passed without
+    ///   `record_history`, or missing with it
+    pub fn transfer_agent(ctx: Context<TransferAgent>, record_history: bool) -> Result<()> {
+        assert_agent_nft_invariants(ctx.accounts.agent_mint.supply, ctx.accounts.agent_mint.decimals)?;
+        require!(!ctx.accounts.agent_account.frozen, IdentityError::AgentFrozen);
+        require!(
+            ctx.accounts.ownership_history.is_some() == record_history,
+            IdentityError::OwnershipHistoryMismatch
+        );
+
+        // The PDA holds the update_authority during a two-step transfer or approval
+        require!(
+            !ctx.accounts.agent_account.pda_holds_update_authority(),
+            IdentityError::TransferPending
+        );
+
+        // Prevent self-transfer
+        require!(
+            ctx.accounts.from_token_account.key() != ctx.accounts.to_token_account.key(),
+            IdentityError::TransferToSelf
+        );
+
+        // Step 1: SPL Token / Token-2022 transfer via CPI
+        let cpi_accounts = token_interface::TransferChecked {
+            from: ctx.accounts.from_token_account.to_account_info(),
+            mint: ctx.accounts.agent_mint.to_account_info(),
+            to: ctx.accounts.to_token_account.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        };
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                cpi_accounts,
+            ),
+            1, // NFT amount
+            0, // NFT decimals
+        )?;
+
+        // Step 2: Transfer Metaplex update_authority to new owner (ERC-8004 compliance)
+        // This allows the new owner to modify tokenURI via set_agent_uri()
+        let new_owner = ctx.accounts.to_token_account.owner;
+        UpdateV1CpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+            .authority(&ctx.accounts.owner.to_account_info())
+            .mint(&ctx.accounts.agent_mint.to_account_info())
+            .metadata(&ctx.accounts.agent_metadata.to_account_info())
+            .payer(&ctx.accounts.owner.to_account_info())
+            .system_program(&ctx.accounts.system_program.to_account_info())
+            .sysvar_instructions(&ctx.accounts.sysvar_instructions.to_account_info())
+            .new_update_authority(new_owner)
             .invoke()?;
 
         // Step 3: Automatic sync_owner
         let agent = &mut ctx.accounts.agent_account;
         let old_owner = agent.owner;
-        agent.owner = new_owner;
+        agent.set_owner(new_owner);
+        agent.updated_at = Clock::get()?.unix_timestamp;
+        let agent_id = agent.agent_id;
 
         emit!(AgentOwnerSynced {
-            agent_id: agent.agent_id,
+            agent_id,
             old_owner,
             new_owner,
             agent_mint: agent.agent_mint,
         });
 
+        if let Some(history) = ctx.accounts.ownership_history.as_mut() {
+            record_ownership(history, ctx.bumps.ownership_history, agent, old_owner, new_owner);
+        }
+
+        if new_owner != old_owner {
+            move_owner_agent(
+                &mut ctx.accounts.old_owner_agents,
+                ctx.bumps.old_owner_agents,
+                old_owner,
+                &mut ctx.accounts.new_owner_agents,
+                ctx.bumps.new_owner_agents,
+                new_owner,
+                agent_id,
+            );
+        }
+
         msg!(
             "Agent {} transferred: {} -> {}",
-            agent.agent_id,
+            agent_id,
             old_owner,
             new_owner
         );
 
         Ok(())
     }
-}
 
-// ============================================================================
-// Account Contexts
-// ============================================================================
+    /// Start a two-step transfer to `new_owner` (see `accept_transfer`)
+    ///
+    /// Safer than `transfer_agent` for high-value agents: nothing moves until the
+    /// recipient accepts, so a mistyped destination can't lose the agent. The NFT
+    /// stays in the owner's token account, delegated to the AgentAccount PDA, and
+    /// the Metaplex update_authority is handed to the PDA so the transfer can
+    /// complete without the owner's signature. URI updates are therefore
+    /// unavailable until the transfer is accepted or cancelled. Calling again
+    /// replaces the pending recipient.
+    ///
+    /// If the NFT is moved by a plain SPL transfer in the meantime, `sync_owner`
+    /// clears the pending transfer.
+    ///
+    /// # Arguments
+    /// * `new_owner` - Wallet allowed to accept the transfer
+    ///
+    /// # Events
+    /// * `TransferInitiated` - Emitted with the pending owner
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the agent owner
+    /// * `TransferToSelf` - If `new_owner` is the current owner
+    /// * `InvalidTokenAccount` - If the owner's token account doesn't hold the NFT
+    /// * `TransferPending` - If a transfer approval is outstanding
+    /// * `AgentFrozen` - If the owner froze the agent
+    pub fn initiate_transfer(ctx: Context<InitiateTransfer>, new_owner: Pubkey) -> Result<()> {
+        assert_agent_nft_invariants(ctx.accounts.agent_mint.supply, ctx.accounts.agent_mint.decimals)?;
+
+        let agent = &mut ctx.accounts.agent_account;
+        require!(new_owner != agent.owner, IdentityError::TransferToSelf);
+        require!(agent.approved.is_none(), IdentityError::TransferPending);
+        require!(!agent.frozen, IdentityError::AgentFrozen);
+
+        token::approve(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Approve {
+                    to: ctx.accounts.owner_token_account.to_account_info(),
+                    delegate: agent.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            1, // NFT amount
+        )?;
+
+        // First initiation: hand the update_authority to the PDA
+        if agent.pending_owner.is_none() {
+            UpdateV1CpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+                .authority(&ctx.accounts.owner.to_account_info())
+                .mint(&ctx.accounts.agent_mint.to_account_info())
+                .metadata(&ctx.accounts.agent_metadata.to_account_info())
+                .payer(&ctx.accounts.owner.to_account_info())
+                .system_program(&ctx.accounts.system_program.to_account_info())
+                .sysvar_instructions(&ctx.accounts.sysvar_instructions.to_account_info())
+                .new_update_authority(agent.key())
+                .invoke()?;
+        }
+
+        agent.pending_owner = Some(new_owner);
+
+        emit!(TransferInitiated {
+            agent_id: agent.agent_id,
+            owner: agent.owner,
+            pending_owner: new_owner,
+        });
+
+        msg!("Agent {} transfer initiated: {} -> {}", agent.agent_id, agent.owner, new_owner);
+
+        Ok(())
+    }
+
+    /// Complete a two-step transfer as the pending owner
+    ///
+    /// Moves the NFT from the owner's token account (as the PDA delegate) to the
+    /// pending owner's associated token account, created if needed, hands the
+    /// Metaplex update_authority to the pending owner and syncs `owner`.
+    ///
+    /// # Events
+    /// * `TransferAccepted` - Emitted after the transfer completes
+    ///
+    /// # Errors
+    /// * `NoPendingTransfer` - If no transfer is pending
+    /// * `Unauthorized` - If caller is not the pending owner
+    /// * `StaleOwner` - If the owner's token account no longer holds the NFT
+    pub fn accept_transfer(ctx: Context<AcceptTransfer>) -> Result<()> {
+        assert_agent_nft_invariants(ctx.accounts.agent_mint.supply, ctx.accounts.agent_mint.decimals)?;
+
+        let agent = &mut ctx.accounts.agent_account;
+        let pending_owner = agent.pending_owner.ok_or(IdentityError::NoPendingTransfer)?;
+        require!(
+            ctx.accounts.new_owner.key() == pending_owner,
+            IdentityError::Unauthorized
+        );
+
+        let from = &ctx.accounts.owner_token_account;
+        require!(
+            agent.is_held_by(&from.mint, &from.owner, from.amount),
+            IdentityError::StaleOwner
+        );
+
+        let agent_info = agent.to_account_info();
+        let agent_mint = agent.agent_mint;
+        let seeds: &[&[u8]] = &[b"agent", agent_mint.as_ref(), &[agent.bump]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: from.to_account_info(),
+                    to: ctx.accounts.new_owner_token_account.to_account_info(),
+                    authority: agent_info.clone(),
+                },
+                &[seeds],
+            ),
+            1, // NFT amount
+        )?;
+
+        UpdateV1CpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+            .authority(&agent_info)
+            .mint(&ctx.accounts.agent_mint.to_account_info())
+            .metadata(&ctx.accounts.agent_metadata.to_account_info())
+            .payer(&ctx.accounts.new_owner.to_account_info())
+            .system_program(&ctx.accounts.system_program.to_account_info())
+            .sysvar_instructions(&ctx.accounts.sysvar_instructions.to_account_info())
+            .new_update_authority(pending_owner)
+            .invoke_signed(&[seeds])?;
+
+        let old_owner = agent.owner;
+        agent.set_owner(pending_owner);
+        agent.pending_owner = None;
+        agent.updated_at = Clock::get()?.unix_timestamp;
+        let agent_id = agent.agent_id;
+
+        emit!(TransferAccepted {
+            agent_id,
+            old_owner,
+            new_owner: pending_owner,
+        });
+
+        move_owner_agent(
+            &mut ctx.accounts.old_owner_agents,
+            ctx.bumps.old_owner_agents,
+            old_owner,
+            &mut ctx.accounts.new_owner_agents,
+            ctx.bumps.new_owner_agents,
+            pending_owner,
+            agent_id,
+        );
+
+        msg!("Agent {} transfer accepted: {} -> {}", agent_id, old_owner, pending_owner);
+
+        Ok(())
+    }
+
+    /// Cancel a pending two-step transfer as the current owner
+    ///
+    /// Revokes the PDA's delegation and returns the Metaplex update_authority
+    /// to the owner.
+    ///
+    /// # Events
+    /// * `TransferCancelled` - Emitted with the discarded pending owner
+    ///
+    /// # Errors
+    /// * `NoPendingTransfer` - If no transfer is pending
+    /// * `Unauthorized` - If caller is not the agent owner
+    pub fn cancel_transfer(ctx: Context<CancelTransfer>) -> Result<()> {
+        let agent = &mut ctx.accounts.agent_account;
+        let pending_owner = agent.pending_owner.ok_or(IdentityError::NoPendingTransfer)?;
+
+        token::revoke(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Revoke {
+                source: ctx.accounts.owner_token_account.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ))?;
+
+        let agent_info = agent.to_account_info();
+        let agent_mint = agent.agent_mint;
+        let seeds: &[&[u8]] = &[b"agent", agent_mint.as_ref(), &[agent.bump]];
+
+        UpdateV1CpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+            .authority(&agent_info)
+            .mint(&ctx.accounts.agent_mint.to_account_info())
+            .metadata(&ctx.accounts.agent_metadata.to_account_info())
+            .payer(&ctx.accounts.owner.to_account_info())
+            .system_program(&ctx.accounts.system_program.to_account_info())
+            .sysvar_instructions(&ctx.accounts.sysvar_instructions.to_account_info())
+            .new_update_authority(agent.owner)
+            .invoke_signed(&[seeds])?;
+
+        agent.pending_owner = None;
+
+        emit!(TransferCancelled {
+            agent_id: agent.agent_id,
+            owner: agent.owner,
+            pending_owner,
+        });
+
+        msg!("Agent {} transfer to {} cancelled", agent.agent_id, pending_owner);
+
+        Ok(())
+    }
+
+    /// Approve `delegate` to move the agent with `transfer_agent_from` (ERC-721: approve)
+    ///
+    /// For escrow and marketplace flows where a program, not the owner, moves
+    /// the NFT. The owner's token account is delegated to `delegate` for the
+    /// NFT, and the Metaplex update_authority is handed to the AgentAccount PDA
+    /// so the transfer can hand it on without the owner's signature. URI updates
+    /// are therefore unavailable until the approval is used or revoked. Calling
+    /// again replaces the approved key.
+    ///
+    /// # Arguments
+    /// * `delegate` - Key allowed to call `transfer_agent_from`
+    ///
+    /// # Events
+    /// * `TransferApproved` - Emitted with the approved key
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the agent owner
+    /// * `TransferToSelf` - If `delegate` is the current owner
+    /// * `TransferPending` - If a two-step transfer is pending
+    /// * `InvalidTokenAccount` - If the owner's token account doesn't hold the NFT
+    /// * `AgentFrozen` - If the owner froze the agent
+    pub fn approve_transfer(ctx: Context<ApproveTransfer>, delegate: Pubkey) -> Result<()> {
+        assert_agent_nft_invariants(ctx.accounts.agent_mint.supply, ctx.accounts.agent_mint.decimals)?;
+
+        let agent = &mut ctx.accounts.agent_account;
+        require!(delegate != agent.owner, IdentityError::TransferToSelf);
+        require!(agent.pending_owner.is_none(), IdentityError::TransferPending);
+        require!(!agent.frozen, IdentityError::AgentFrozen);
+
+        token::approve(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Approve {
+                    to: ctx.accounts.owner_token_account.to_account_info(),
+                    delegate: ctx.accounts.delegate_account.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            1, // NFT amount
+        )?;
+
+        // First approval: hand the update_authority to the PDA
+        if agent.approved.is_none() {
+            UpdateV1CpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+                .authority(&ctx.accounts.owner.to_account_info())
+                .mint(&ctx.accounts.agent_mint.to_account_info())
+                .metadata(&ctx.accounts.agent_metadata.to_account_info())
+                .payer(&ctx.accounts.owner.to_account_info())
+                .system_program(&ctx.accounts.system_program.to_account_info())
+                .sysvar_instructions(&ctx.accounts.sysvar_instructions.to_account_info())
+                .new_update_authority(agent.key())
+                .invoke()?;
+        }
+
+        agent.approved = Some(delegate);
+
+        emit!(TransferApproved {
+            agent_id: agent.agent_id,
+            owner: agent.owner,
+            approved: delegate,
+        });
+
+        msg!("Agent {} approved for transfer by {}", agent.agent_id, delegate);
+
+        Ok(())
+    }
+
+    /// Move an approved agent as its delegate (ERC-721: transferFrom)
+    ///
+    /// The approved key signs instead of the owner: the NFT moves from the
+    /// owner's token account to `to_token_account` with the delegate as SPL
+    /// authority, the PDA hands the Metaplex update_authority to the new holder,
+    /// and `owner` is synced as in `transfer_agent`. The approval is consumed.
+    ///
+    /// # Events
+    /// * `AgentOwnerSynced` - Emitted after the transfer completes
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the approved key
+    /// * `StaleOwner` - If the owner's token account no longer holds the NFT
+    /// * `TransferToSelf` - If the destination is the source token account
+    /// * `InvalidNftSupply` / `InvalidNftDecimals` - If the mint isn't a valid agent NFT
+    pub fn transfer_agent_from(ctx: Context<TransferAgentFrom>) -> Result<()> {
+        assert_agent_nft_invariants(ctx.accounts.agent_mint.supply, ctx.accounts.agent_mint.decimals)?;
+
+        let agent = &mut ctx.accounts.agent_account;
+        require!(
+            agent.approved == Some(ctx.accounts.delegate.key()),
+            IdentityError::Unauthorized
+        );
+
+        let from = &ctx.accounts.from_token_account;
+        require!(
+            agent.is_held_by(&from.mint, &from.owner, from.amount),
+            IdentityError::StaleOwner
+        );
+        require!(
+            from.key() != ctx.accounts.to_token_account.key(),
+            IdentityError::TransferToSelf
+        );
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: from.to_account_info(),
+                    to: ctx.accounts.to_token_account.to_account_info(),
+                    authority: ctx.accounts.delegate.to_account_info(),
+                },
+            ),
+            1, // NFT amount
+        )?;
+
+        let agent_info = agent.to_account_info();
+        let agent_mint = agent.agent_mint;
+        let seeds: &[&[u8]] = &[b"agent", agent_mint.as_ref(), &[agent.bump]];
+        let new_owner = ctx.accounts.to_token_account.owner;
+
+        UpdateV1CpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+            .authority(&agent_info)
+            .mint(&ctx.accounts.agent_mint.to_account_info())
+            .metadata(&ctx.accounts.agent_metadata.to_account_info())
+            .payer(&ctx.accounts.delegate.to_account_info())
+            .system_program(&ctx.accounts.system_program.to_account_info())
+            .sysvar_instructions(&ctx.accounts.sysvar_instructions.to_account_info())
+            .new_update_authority(new_owner)
+            .invoke_signed(&[seeds])?;
+
+        // Automatic sync_owner (also consumes the approval)
+        let old_owner = agent.owner;
+        agent.set_owner(new_owner);
+        agent.updated_at = Clock::get()?.unix_timestamp;
+        let agent_id = agent.agent_id;
+
+        emit!(AgentOwnerSynced {
+            agent_id,
+            old_owner,
+            new_owner,
+            agent_mint,
+        });
+
+        if new_owner != old_owner {
+            move_owner_agent(
+                &mut ctx.accounts.old_owner_agents,
+                ctx.bumps.old_owner_agents,
+                old_owner,
+                &mut ctx.accounts.new_owner_agents,
+                ctx.bumps.new_owner_agents,
+                new_owner,
+                agent_id,
+            );
+        }
+
+        msg!(
+            "Agent {} transferred by {}: {} -> {}",
+            agent_id,
+            ctx.accounts.delegate.key(),
+            old_owner,
+            new_owner
+        );
+
+        Ok(())
+    }
+
+    /// Revoke a transfer approval as the current owner (ERC-721: approve(0))
+    ///
+    /// Revokes the SPL delegation and returns the Metaplex update_authority to
+    /// the owner.
+    ///
+    /// # Events
+    /// * `TransferApprovalRevoked` - Emitted with the revoked key
+    ///
+    /// # Errors
+    /// * `NoTransferApproval` - If no approval is set
+    /// * `Unauthorized` - If caller is not the agent owner
+    pub fn revoke_transfer_approval(ctx: Context<CancelTransfer>) -> Result<()> {
+        let agent = &mut ctx.accounts.agent_account;
+        let approved = agent.approved.ok_or(IdentityError::NoTransferApproval)?;
+
+        token::revoke(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Revoke {
+                source: ctx.accounts.owner_token_account.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ))?;
+
+        let agent_info = agent.to_account_info();
+        let agent_mint = agent.agent_mint;
+        let seeds: &[&[u8]] = &[b"agent", agent_mint.as_ref(), &[agent.bump]];
+
+        UpdateV1CpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+            .authority(&agent_info)
+            .mint(&ctx.accounts.agent_mint.to_account_info())
+            .metadata(&ctx.accounts.agent_metadata.to_account_info())
+            .payer(&ctx.accounts.owner.to_account_info())
+            .system_program(&ctx.accounts.system_program.to_account_info())
+            .sysvar_instructions(&ctx.accounts.sysvar_instructions.to_account_info())
+            .new_update_authority(agent.owner)
+            .invoke_signed(&[seeds])?;
+
+        agent.approved = None;
+
+        emit!(TransferApprovalRevoked {
+            agent_id: agent.agent_id,
+            owner: agent.owner,
+            approved,
+        });
+
+        msg!("Agent {} transfer approval for {} revoked", agent.agent_id, approved);
+
+        Ok(())
+    }
+
+    /// Freeze the owner's token account holding the agent NFT
+    ///
+    /// Guards the agent while it is listed or disputed: a frozen token account
+    /// can't be transferred, delegated or burned by anyone, including the
+    /// owner, until `thaw_agent`. Metaplex moved the mint's freeze authority to
+    /// the master edition at registration, so the freeze goes through Token
+    /// Metadata's `FreezeDelegatedAccount`: the NFT is delegated to this
+    /// AgentAccount PDA, which then freezes the account. `frozen` records the
+    /// state for readers.
+    ///
+    /// # Events
+    /// * `AgentFreezeChanged` - Emitted with `frozen = true`
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the agent owner
+    /// * `InvalidTokenAccount` - If the owner's token account doesn't hold the NFT
+    /// * `AgentFrozen` - If the agent is already frozen
+    /// * `TransferPending` - If a two-step transfer or transfer approval is pending
+    ///   (they use the same token delegation)
+    pub fn freeze_agent(ctx: Context<FreezeAgent>) -> Result<()> {
+        assert_agent_nft_invariants(ctx.accounts.agent_mint.supply, ctx.accounts.agent_mint.decimals)?;
+
+        let agent = &mut ctx.accounts.agent_account;
+        require!(!agent.frozen, IdentityError::AgentFrozen);
+        require!(
+            !agent.pda_holds_update_authority(),
+            IdentityError::TransferPending
+        );
+
+        token::approve(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Approve {
+                    to: ctx.accounts.owner_token_account.to_account_info(),
+                    delegate: agent.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            1, // NFT amount
+        )?;
+
+        let agent_info = agent.to_account_info();
+        let agent_mint = agent.agent_mint;
+        let seeds: &[&[u8]] = &[b"agent", agent_mint.as_ref(), &[agent.bump]];
+
+        FreezeDelegatedAccountCpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+            .delegate(&agent_info)
+            .token_account(&ctx.accounts.owner_token_account.to_account_info())
+            .edition(&ctx.accounts.agent_master_edition)
+            .mint(&ctx.accounts.agent_mint.to_account_info())
+            .token_program(&ctx.accounts.token_program.to_account_info())
+            .invoke_signed(&[seeds])?;
+
+        agent.frozen = true;
+        agent.updated_at = Clock::get()?.unix_timestamp;
+
+        emit!(AgentFreezeChanged {
+            agent_id: agent.agent_id,
+            owner: agent.owner,
+            frozen: true,
+        });
+
+        msg!("Agent {} frozen", agent.agent_id);
+
+        Ok(())
+    }
+
+    /// Thaw the owner's token account frozen by `freeze_agent`
+    ///
+    /// The AgentAccount PDA thaws the account through Token Metadata's
+    /// `ThawDelegatedAccount`, then the owner revokes its delegation, so the
+    /// agent can be transferred again.
+    ///
+    /// # Events
+    /// * `AgentFreezeChanged` - Emitted with `frozen = false`
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the agent owner
+    /// * `InvalidTokenAccount` - If the owner's token account doesn't hold the NFT
+    /// * `AgentNotFrozen` - If the agent isn't frozen
+    pub fn thaw_agent(ctx: Context<FreezeAgent>) -> Result<()> {
+        let agent = &mut ctx.accounts.agent_account;
+        require!(agent.frozen, IdentityError::AgentNotFrozen);
+
+        let agent_info = agent.to_account_info();
+        let agent_mint = agent.agent_mint;
+        let seeds: &[&[u8]] = &[b"agent", agent_mint.as_ref(), &[agent.bump]];
+
+        ThawDelegatedAccountCpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+            .delegate(&agent_info)
+            .token_account(&ctx.accounts.owner_token_account.to_account_info())
+            .edition(&ctx.accounts.agent_master_edition)
+            .mint(&ctx.accounts.agent_mint.to_account_info())
+            .token_program(&ctx.accounts.token_program.to_account_info())
+            .invoke_signed(&[seeds])?;
+
+        token::revoke(CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Revoke {
+                source: ctx.accounts.owner_token_account.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        ))?;
+
+        agent.frozen = false;
+        agent.updated_at = Clock::get()?.unix_timestamp;
+
+        emit!(AgentFreezeChanged {
+            agent_id: agent.agent_id,
+            owner: agent.owner,
+            frozen: false,
+        });
+
+        msg!("Agent {} thawed", agent.agent_id);
+
+        Ok(())
+    }
+
+    /// Permanently retire an agent: burn its NFT and close its AgentAccount
+    ///
+    /// Burns the agent NFT (SPL Token `burn` of amount 1), closes the AgentAccount
+    /// and its `AgentIdIndex` PDAs returning their rent to the owner, and
+    /// decrements `config.total_agents`.
+    /// `next_agent_id` is untouched, so agent IDs are never reused.
+    ///
+    /// Master edition: since `register` moved the mint authority to the master
+    /// edition, the mint can never be re-minted. An SPL burn only needs the
+    /// holder's signature, not the mint authority, so the master edition takes no
+    /// part in the burn. It is still passed (PDA-checked) so the accounts mirror
+    /// Token Metadata's own burn; the metadata and master edition accounts are
+    /// left on-chain and describe a zero-supply mint.
+    ///
+    /// Metadata extensions are not closed and become orphaned.
+    ///
+    /// Pass the owner's `owner_agents` list to drop the agent from it.
+    ///
+    /// # Events
+    /// * `AgentDeregistered` - Emitted after the agent is burned and closed
+    ///
+    /// # Errors
+    /// * `Unauthorized` - If caller is not the agent owner
+    /// * `InvalidTokenAccount` - If the token account doesn't hold the agent NFT
+    /// * `InvalidNftSupply` / `InvalidNftDecimals` - If the mint isn't a valid agent NFT
+    /// * `AgentFrozen` - If the owner froze the agent
+    pub fn deregister(ctx: Context<Deregister>) -> Result<()> {
+        assert_agent_nft_invariants(ctx.accounts.agent_mint.supply, ctx.accounts.agent_mint.decimals)?;
+        require!(!ctx.accounts.agent_account.frozen, IdentityError::AgentFrozen);
+
+        token::burn(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Burn {
+                    mint: ctx.accounts.agent_mint.to_account_info(),
+                    from: ctx.accounts.owner_token_account.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ),
+            1, // NFT amount
+        )?;
+
+        let config = &mut ctx.accounts.config;
+        config.total_agents = config
+            .total_agents
+            .checked_sub(1)
+            .ok_or(IdentityError::Overflow)?;
+
+        let agent = &ctx.accounts.agent_account;
+
+        if let Some(owner_agents) = ctx.accounts.owner_agents.as_mut() {
+            owner_agents.remove(agent.agent_id);
+        }
+
+        emit!(AgentDeregistered {
+            agent_id: agent.agent_id,
+            owner: agent.owner,
+            agent_mint: agent.agent_mint,
+        });
+
+        msg!("Agent {} deregistered by {}", agent.agent_id, agent.owner);
+
+        Ok(())
+    }
+
+    /// Read an agent (view, `views` feature)
+    ///
+    /// Stable, versioned return data for CPI callers; see `views::AgentView`.
+    ///
+    /// # Returns
+    /// `AgentView` (metadata values excluded, use `get_metadata`)
+    ///
+    /// # Events
+    /// None (view function)
+    #[cfg(feature = "views")]
+    pub fn get_agent(ctx: Context<GetAgent>) -> Result<AgentView> {
+        Ok(AgentView::from(&*ctx.accounts.agent_account))
+    }
+
+    /// Read the registry configuration (view, `views` feature)
+    ///
+    /// # Returns
+    /// `RegistryConfigView`
+    ///
+    /// # Events
+    /// None (view function)
+    #[cfg(feature = "views")]
+    pub fn get_config(ctx: Context<GetConfig>) -> Result<RegistryConfigView> {
+        Ok(RegistryConfigView::from(&*ctx.accounts.config))
+    }
+}
+
+// ============================================================================
+// Guards
+// ============================================================================
+
+/// Check an agent NFT mint satisfies the registry's NFT invariants
+/// (supply = 1, decimals = 0) before moving or re-syncing the agent
+fn assert_agent_nft_invariants(supply: u64, decimals: u8) -> Result<()> {
+    require!(supply == 1, IdentityError::InvalidNftSupply);
+    require!(decimals == 0, IdentityError::InvalidNftDecimals);
+    Ok(())
+}
+
+/// Check a token URI is empty or uses a scheme marketplaces and indexers resolve
+fn validate_uri(uri: &str) -> Result<()> {
+    require!(
+        uri.is_empty() || AgentAccount::URI_SCHEMES.iter().any(|scheme| uri.starts_with(scheme)),
+        IdentityError::InvalidUriScheme
+    );
+    Ok(())
+}
+
+/// URI written into the agent NFT's Metaplex metadata: the token URI, or empty
+/// if it is longer than Metaplex accepts (the AgentAccount keeps the full URI)
+fn nft_uri(token_uri: &str) -> String {
+    if token_uri.len() <= mpl_token_metadata::MAX_URI_LENGTH {
+        token_uri.to_string()
+    } else {
+        String::new()
+    }
+}
+
+/// Check a mint about to become an agent NFT has nothing minted yet and 0 decimals
+fn assert_new_agent_mint(supply: u64, decimals: u8) -> Result<()> {
+    require!(supply == 0, IdentityError::InvalidNftSupply);
+    require!(decimals == 0, IdentityError::InvalidNftDecimals);
+    Ok(())
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Move an agent between owner lists after an ownership change
+///
+/// Lists created by `init_if_needed` in the same instruction are initialized here.
+fn move_owner_agent(
+    old_list: &mut OwnerAgentList,
+    old_bump: u8,
+    old_owner: Pubkey,
+    new_list: &mut OwnerAgentList,
+    new_bump: u8,
+    new_owner: Pubkey,
+    agent_id: u64,
+) {
+    old_list.init_if_new(old_owner, old_bump);
+    old_list.remove(agent_id);
+
+    new_list.init_if_new(new_owner, new_bump);
+    if !new_list.add(agent_id) {
+        msg!("Owner agent list full: agent {} not indexed", agent_id);
+    }
+}
+
+/// Move an agent between owner lists passed as plain accounts
+/// (`sync_owners_batch`, `reopen_agent_token_account`), creating lists that
+/// don't exist yet, paid by `payer`
+fn move_owner_agent_accounts<'info>(
+    old_info: &AccountInfo<'info>,
+    old_owner: Pubkey,
+    new_info: &AccountInfo<'info>,
+    new_owner: Pubkey,
+    agent_id: u64,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let mut old_list = load_owner_agent_list(old_info, old_owner, payer, system_program)?;
+    let mut new_list = load_owner_agent_list(new_info, new_owner, payer, system_program)?;
+
+    let (old_bump, new_bump) = (old_list.bump, new_list.bump);
+    move_owner_agent(&mut old_list, old_bump, old_owner, &mut new_list, new_bump, new_owner, agent_id);
+
+    old_list.try_serialize(&mut &mut old_info.try_borrow_mut_data()?[..])?;
+    new_list.try_serialize(&mut &mut new_info.try_borrow_mut_data()?[..])?;
+    Ok(())
+}
+
+/// Read `owner`'s agent list from `info`, creating the account if it doesn't exist yet
+fn load_owner_agent_list<'info>(
+    info: &AccountInfo<'info>,
+    owner: Pubkey,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<OwnerAgentList> {
+    let (address, bump) =
+        Pubkey::find_program_address(&[b"owner_agents", owner.as_ref()], &crate::ID);
+    require_keys_eq!(info.key(), address, IdentityError::InvalidOwnerAgentList);
+
+    if info.owner == &crate::ID {
+        return OwnerAgentList::try_deserialize(&mut &info.try_borrow_data()?[..]);
+    }
+
+    erc8004_common::create_pda_account(
+        payer,
+        info,
+        system_program,
+        8 + OwnerAgentList::SIZE,
+        &crate::ID,
+        &[b"owner_agents", owner.as_ref(), &[bump]],
+    )?;
+    Ok(OwnerAgentList {
+        owner,
+        agent_ids: Vec::new(),
+        truncated: false,
+        bump,
+    })
+}
+
+/// Point `config.authority` at `new_authority` (`transfer_authority`, `set_pda_authority`)
+fn move_registry_authority(config: &mut RegistryConfig, new_authority: Pubkey) {
+    let old_authority = config.authority;
+    config.authority = new_authority;
+
+    emit!(AuthorityTransferred {
+        old_authority,
+        new_authority,
+    });
+
+    msg!("Registry authority transferred: {} -> {}", old_authority, new_authority);
+}
+
+/// Hand the collection's Metaplex update authority from `authority` (the
+/// current update authority, also paying) to `new_authority`
+fn move_collection_update_authority<'info>(
+    token_metadata_program: &Program<'info, Metadata>,
+    authority: &Signer<'info>,
+    collection_mint: &Account<'info, Mint>,
+    collection_metadata: &UncheckedAccount<'info>,
+    system_program: &Program<'info, System>,
+    sysvar_instructions: &UncheckedAccount<'info>,
+    new_authority: Pubkey,
+) -> Result<()> {
+    UpdateV1CpiBuilder::new(&token_metadata_program.to_account_info())
+        .authority(&authority.to_account_info())
+        .mint(&collection_mint.to_account_info())
+        .metadata(&collection_metadata.to_account_info())
+        .payer(&authority.to_account_info())
+        .system_program(&system_program.to_account_info())
+        .sysvar_instructions(&sysvar_instructions.to_account_info())
+        .new_update_authority(new_authority)
+        .invoke()?;
+
+    msg!("Collection update authority moved to {}", new_authority);
+
+    Ok(())
+}
+
+/// Fill a freshly created `OwnershipHistory` entry with an ownership change
+///
+/// The entry's PDA is seeded with `agent.history_len`, so the counter is only
+/// advanced once the entry exists.
+fn record_ownership(
+    history: &mut OwnershipHistory,
+    bump: Option<u8>,
+    agent: &mut AgentAccount,
+    from: Pubkey,
+    to: Pubkey,
+) {
+    let seq = agent.take_history_seq();
+    history.agent_id = agent.agent_id;
+    history.agent_mint = agent.agent_mint;
+    history.seq = seq;
+    history.from = from;
+    history.to = to;
+    history.ts = agent.updated_at;
+    history.bump = bump.unwrap_or_default();
+
+    emit!(OwnershipRecorded {
+        agent_id: agent.agent_id,
+        agent_mint: agent.agent_mint,
+        seq,
+        from,
+        to,
+    });
+}
+
+// ============================================================================
+// Account Contexts
+// ============================================================================
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + RegistryConfig::SIZE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, RegistryConfig>,
+
+    /// Collection NFT mint (created during initialization)
+    #[account(
+        init,
+        payer = authority,
+        mint::decimals = 0,
+        mint::authority = authority.key(),
+        mint::freeze_authority = authority.key(),
+    )]
+    pub collection_mint: Account<'info, Mint>,
+
+    /// Metaplex Collection metadata account
+    /// CHECK: Created by Metaplex CPI
+    #[account(mut)]
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    /// Metaplex Collection master edition account
+    /// CHECK: Created by Metaplex CPI
+    #[account(mut)]
+    pub collection_master_edition: UncheckedAccount<'info>,
+
+    /// Token account to hold the collection NFT
+    #[account(
+        init,
+        payer = authority,
+        associated_token::mint = collection_mint,
+        associated_token::authority = authority,
+    )]
+    pub collection_token_account: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+
+    /// Metaplex Token Metadata program
+    pub token_metadata_program: Program<'info, Metadata>,
+
+    /// Sysvar Instructions
+    /// CHECK: Sysvar account
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Register<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump
+    )]
+    pub config: Account<'info, RegistryConfig>,
+
+    /// Collection authority record of an approved delegate (see `approve_collection_authority`)
+    /// CHECK: Validated by Metaplex against `authority` during collection verification
+    pub collection_authority_record: Option<UncheckedAccount<'info>>,
+
+    /// Registry authority, or an approved delegate when `collection_authority_record` is passed
+    /// (needed to verify collection)
+    /// CHECK: Must match config.authority unless a delegate record is provided
+    #[account(constraint = authority.key() == config.authority || collection_authority_record.is_some())]
+    pub authority: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + AgentAccount::MAX_SIZE,
+        seeds = [b"agent", agent_mint.key().as_ref()],
+        bump
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+
+    /// Reverse index agent_id -> agent_mint for the ID being assigned
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + AgentIdIndex::SIZE,
+        seeds = [b"agent_id", config.next_agent_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agent_id_index: Account<'info, AgentIdIndex>,
+
+    /// Owner's agent list (created on the owner's first registration)
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + OwnerAgentList::SIZE,
+        seeds = [b"owner_agents", owner.key().as_ref()],
+        bump
+    )]
+    pub owner_agents: Account<'info, OwnerAgentList>,
+
+    /// Registry growth metrics (created with the first registration after upgrade),
+    /// boxed to keep the 30-day ring off this context's stack frame
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + RegistryStats::SIZE,
+        seeds = [b"registry_stats"],
+        bump
+    )]
+    pub registry_stats: Box<Account<'info, RegistryStats>>,
+
+    /// Agent NFT mint (created by this instruction under `token_program`, part of collection)
+    #[account(
+        init,
+        payer = owner,
+        mint::decimals = 0,
+        mint::authority = owner.key(),
+        mint::freeze_authority = owner.key(),
+        mint::token_program = token_program,
+    )]
+    pub agent_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    /// Metaplex metadata account for the agent NFT
+    /// CHECK: Created by Metaplex CPI
+    #[account(mut)]
+    pub agent_metadata: UncheckedAccount<'info>,
+
+    /// Metaplex master edition account for the agent NFT
+    /// CHECK: Created by Metaplex CPI
+    #[account(mut)]
+    pub agent_master_edition: UncheckedAccount<'info>,
+
+    /// Token account to receive the agent NFT
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = agent_mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program,
+    )]
+    pub agent_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    // Collection accounts (for verification)
+    #[account(constraint = collection_mint.key() == config.collection_mint @ IdentityError::InvalidCollectionMint)]
+    pub collection_mint: Account<'info, Mint>,
+
+    /// CHECK: Checked by Metaplex
+    #[account(mut)]
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Checked by Metaplex
+    pub collection_master_edition: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// SPL Token (`register*`) or Token-2022 (`register_token2022`)
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub rent: Sysvar<'info, Rent>,
+
+    /// Metaplex Token Metadata program
+    pub token_metadata_program: Program<'info, Metadata>,
+
+    /// Sysvar Instructions
+    /// CHECK: Sysvar account
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = authority.key() == config.authority @ IdentityError::Unauthorized
+    )]
+    pub config: Account<'info, RegistryConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCollectionAuthority<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RegistryConfig>,
+
+    /// Current collection update authority (checked by Metaplex)
+    #[account(mut)]
+    pub collection_authority: Signer<'info>,
+
+    #[account(constraint = collection_mint.key() == config.collection_mint @ IdentityError::InvalidCollectionMint)]
+    pub collection_mint: Account<'info, Mint>,
+
+    /// CHECK: Metaplex metadata PDA verified via seeds constraint
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            collection_mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = TOKEN_METADATA_PROGRAM_ID
+    )]
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Metaplex requires this for authorization
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCollectionMetadata<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = authority.key() == config.authority @ IdentityError::Unauthorized
+    )]
+    pub config: Account<'info, RegistryConfig>,
+
+    /// Registry authority (collection update authority)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(constraint = collection_mint.key() == config.collection_mint @ IdentityError::InvalidCollectionMint)]
+    pub collection_mint: Account<'info, Mint>,
+
+    /// CHECK: Metaplex metadata PDA verified via seeds constraint
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            collection_mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = TOKEN_METADATA_PROGRAM_ID
+    )]
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Metaplex requires this for authorization
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPdaAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = authority.key() == config.authority @ IdentityError::Unauthorized
+    )]
+    pub config: Account<'info, RegistryConfig>,
+
+    /// Current registry authority (collection update authority)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(constraint = collection_mint.key() == config.collection_mint @ IdentityError::InvalidCollectionMint)]
+    pub collection_mint: Account<'info, Mint>,
+
+    /// CHECK: Metaplex metadata PDA verified via seeds constraint
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            collection_mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = TOKEN_METADATA_PROGRAM_ID
+    )]
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Metaplex requires this for authorization
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(delegate: Pubkey)]
+pub struct ApproveCollectionAuthority<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = authority.key() == config.authority @ IdentityError::Unauthorized
+    )]
+    pub config: Account<'info, RegistryConfig>,
+
+    /// Registry authority (collection update authority)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Collection authority record PDA, created by Metaplex
+    #[account(mut)]
+    pub collection_authority_record: UncheckedAccount<'info>,
+
+    /// CHECK: Delegate being approved, must match the instruction argument
+    #[account(constraint = delegate_account.key() == delegate)]
+    pub delegate_account: UncheckedAccount<'info>,
+
+    #[account(constraint = collection_mint.key() == config.collection_mint @ IdentityError::InvalidCollectionMint)]
+    pub collection_mint: Account<'info, Mint>,
+
+    /// CHECK: Checked by Metaplex
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(delegate: Pubkey)]
+pub struct RevokeCollectionAuthority<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = authority.key() == config.authority @ IdentityError::Unauthorized
+    )]
+    pub config: Account<'info, RegistryConfig>,
+
+    /// Registry authority (collection update authority)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Collection authority record PDA, closed by Metaplex
+    #[account(mut)]
+    pub collection_authority_record: UncheckedAccount<'info>,
+
+    /// CHECK: Delegate being revoked, must match the instruction argument
+    #[account(constraint = delegate_account.key() == delegate)]
+    pub delegate_account: UncheckedAccount<'info>,
+
+    #[account(constraint = collection_mint.key() == config.collection_mint @ IdentityError::InvalidCollectionMint)]
+    pub collection_mint: Account<'info, Mint>,
+
+    /// CHECK: Checked by Metaplex
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+}
+
+#[derive(Accounts)]
+#[instruction(agent_mint: Pubkey)]
+pub struct VerifyAgentCollection<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RegistryConfig>,
+
+    /// Collection authority record of an approved delegate (see `approve_collection_authority`)
+    /// CHECK: Validated by Metaplex against `authority` during collection verification
+    pub collection_authority_record: Option<UncheckedAccount<'info>>,
+
+    /// Registry authority, or an approved delegate when `collection_authority_record` is passed
+    #[account(
+        mut,
+        constraint = authority.key() == config.authority || collection_authority_record.is_some()
+            @ IdentityError::Unauthorized
+    )]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"agent", agent_mint.as_ref()],
+        bump = agent_account.bump
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+
+    /// CHECK: Metaplex metadata PDA verified via seeds constraint
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            agent_mint.as_ref(),
+        ],
+        bump,
+        seeds::program = TOKEN_METADATA_PROGRAM_ID
+    )]
+    pub agent_metadata: UncheckedAccount<'info>,
+
+    #[account(constraint = collection_mint.key() == config.collection_mint @ IdentityError::InvalidCollectionMint)]
+    pub collection_mint: Account<'info, Mint>,
+
+    /// CHECK: Checked by Metaplex
+    #[account(mut)]
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Checked by Metaplex
+    pub collection_master_edition: UncheckedAccount<'info>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+}
+
+#[derive(Accounts)]
+pub struct GetMetadata<'info> {
+    #[account(
+        seeds = [b"agent", agent_account.agent_mint.as_ref()],
+        bump = agent_account.bump
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+}
+
+#[derive(Accounts)]
+pub struct SetMetadata<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RegistryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent_account.agent_mint.as_ref()],
+        bump = agent_account.bump
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+
+    /// Agent owner or metadata delegate (checked in handler)
+    pub owner: Signer<'info>,
+
+    /// Optional token account of the cached owner (enables strict mode)
+    #[account(
+        constraint = owner_token_account.mint == agent_account.agent_mint @ IdentityError::InvalidTokenAccount
+    )]
+    pub owner_token_account: Option<Account<'info, TokenAccount>>,
+}
+
+#[derive(Accounts)]
+pub struct SetCategory<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_account.agent_mint.as_ref()],
+        bump = agent_account.bump,
+        constraint = owner.key() == agent_account.owner @ IdentityError::Unauthorized
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetOperatorType<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_account.agent_mint.as_ref()],
+        bump = agent_account.bump,
+        constraint = owner.key() == agent_account.owner @ IdentityError::Unauthorized
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AttestOperatorType<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = authority.key() == config.authority @ IdentityError::Unauthorized
+    )]
+    pub config: Account<'info, RegistryConfig>,
+
+    #[account(
+        seeds = [b"agent", agent_account.agent_mint.as_ref()],
+        bump = agent_account.bump
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + OperatorAttestation::SIZE,
+        seeds = [b"operator_attestation", agent_account.agent_mint.as_ref()],
+        bump
+    )]
+    pub operator_attestation: Account<'info, OperatorAttestation>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DeprecateAgent<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_account.agent_mint.as_ref()],
+        bump = agent_account.bump,
+        constraint = owner.key() == agent_account.owner @ IdentityError::Unauthorized
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+
+    /// Successor agent (required when a successor_id is given)
+    #[account(
+        seeds = [b"agent", successor_account.agent_mint.as_ref()],
+        bump = successor_account.bump
+    )]
+    pub successor_account: Option<Account<'info, AgentAccount>>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMetadataDelegate<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_account.agent_mint.as_ref()],
+        bump = agent_account.bump,
+        constraint = owner.key() == agent_account.owner @ IdentityError::Unauthorized
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_max_entries: u16)]
+pub struct ResizeAgentMetadata<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_account.agent_mint.as_ref()],
+        bump = agent_account.bump,
+        constraint = owner.key() == agent_account.owner @ IdentityError::Unauthorized,
+        realloc = AgentAccount::space_for(agent_account.token_uri.len(), new_max_entries as usize),
+        realloc::payer = owner,
+        realloc::zero = false
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_uri: String)]
+pub struct SetAgentUri<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RegistryConfig>,
+
+    /// Resized to the new URI, keeping its metadata capacity
+    #[account(
+        mut,
+        seeds = [b"agent", agent_account.agent_mint.as_ref()],
+        bump = agent_account.bump,
+        constraint = agent_account.owner == owner.key() @ IdentityError::Unauthorized,
+        realloc = AgentAccount::space_for(
+            new_uri.len(),
+            AgentAccount::metadata_capacity(
+                agent_account.to_account_info().data_len(),
+                agent_account.token_uri.len()
+            )
+        ),
+        realloc::payer = owner,
+        realloc::zero = false
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+
+    /// CHECK: Metaplex metadata PDA verified via seeds constraint
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            agent_mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = TOKEN_METADATA_PROGRAM_ID
+    )]
+    pub agent_metadata: UncheckedAccount<'info>,
+
+    pub agent_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    /// Agent owner (Metaplex update authority)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Metaplex requires this for authorization
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SyncOwner<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_account.agent_mint.as_ref()],
+        bump = agent_account.bump
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+
+    /// Token account holding the agent NFT (must have amount = 1),
+    /// SPL Token or Token-2022
+    #[account(
+        constraint = token_account.mint == agent_account.agent_mint @ IdentityError::InvalidTokenAccount
+    )]
+    pub token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// CHECK: Metaplex metadata PDA verified via seeds constraint
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            agent_mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = TOKEN_METADATA_PROGRAM_ID
+    )]
+    pub agent_metadata: UncheckedAccount<'info>,
+
+    #[account(constraint = agent_mint.key() == agent_account.agent_mint @ IdentityError::InvalidTokenAccount)]
+    pub agent_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    /// Previous owner's agent list
+    #[account(
+        init_if_needed,
+        payer = old_owner_signer,
+        space = 8 + OwnerAgentList::SIZE,
+        seeds = [b"owner_agents", agent_account.owner.as_ref()],
+        bump
+    )]
+    pub old_owner_agents: Account<'info, OwnerAgentList>,
+
+    /// New owner's agent list
+    #[account(
+        init_if_needed,
+        payer = old_owner_signer,
+        space = 8 + OwnerAgentList::SIZE,
+        seeds = [b"owner_agents", token_account.owner.as_ref()],
+        bump
+    )]
+    pub new_owner_agents: Account<'info, OwnerAgentList>,
+
+    /// Next ownership history entry (only with `record_history`)
+    #[account(
+        init,
+        payer = old_owner_signer,
+        space = 8 + OwnershipHistory::SIZE,
+        seeds = [
+            b"owner_history",
+            agent_account.agent_mint.as_ref(),
+            &agent_account.history_len.to_le_bytes(),
+        ],
+        bump
+    )]
+    pub ownership_history: Option<Account<'info, OwnershipHistory>>,
+
+    /// Old owner (current update_authority) must sign to transfer authority
+    #[account(
+        mut,
+        constraint = old_owner_signer.key() == agent_account.owner @ IdentityError::Unauthorized
+    )]
+    pub old_owner_signer: Signer<'info>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Metaplex requires this for authorization
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SyncAgentCounts<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RegistryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"agent", agent_account.agent_mint.as_ref()],
+        bump = agent_account.bump,
+        constraint = agent_account.owner == owner.key() @ IdentityError::Unauthorized
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+
+    /// CHECK: Reputation Registry AgentReputationMetadata PDA, verified via seeds;
+    /// owner and layout checked in the handler
+    #[account(
+        seeds = [b"agent_reputation", agent_account.agent_id.to_le_bytes().as_ref()],
+        bump,
+        seeds::program = REPUTATION_REGISTRY_ID
+    )]
+    pub agent_reputation: UncheckedAccount<'info>,
+
+    /// CHECK: Validation Registry AgentValidationMetadata PDA, verified via seeds;
+    /// owner and layout checked in the handler
+    #[account(
+        seeds = [b"agent_validation", agent_account.agent_id.to_le_bytes().as_ref()],
+        bump,
+        seeds::program = config.validation_registry
+    )]
+    pub agent_validation: UncheckedAccount<'info>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SyncOwnersBatch<'info> {
+    /// Cached owner of the agents being synced (current update_authority)
+    #[account(mut)]
+    pub old_owner_signer: Signer<'info>,
+
+    /// CHECK: Signer's `owner_agents` list, created if needed (address checked in handler)
+    #[account(mut)]
+    pub old_owner_agents: UncheckedAccount<'info>,
+
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Metaplex requires this for authorization
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReopenAgentTokenAccount<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_account.agent_mint.as_ref()],
+        bump = agent_account.bump,
+        constraint = owner.key() == agent_account.owner @ IdentityError::Unauthorized
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+
+    #[account(constraint = agent_mint.key() == agent_account.agent_mint @ IdentityError::InvalidTokenAccount)]
+    pub agent_mint: Account<'info, Mint>,
+
+    /// Associated token account of the cached owner (recreated if closed)
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = agent_mint,
+        associated_token::authority = owner,
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    /// Token account currently holding the NFT, if it moved elsewhere
+    #[account(
+        constraint = holder_token_account.mint == agent_account.agent_mint @ IdentityError::InvalidTokenAccount
+    )]
+    pub holder_token_account: Option<Account<'info, TokenAccount>>,
+
+    /// CHECK: Metaplex metadata PDA verified via seeds constraint
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            agent_mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = TOKEN_METADATA_PROGRAM_ID
+    )]
+    pub agent_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Cached owner's `owner_agents` list, required when re-syncing to
+    /// `holder_token_account` (created if needed, address checked in handler)
+    #[account(mut)]
+    pub old_owner_agents: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Holder's `owner_agents` list, same as `old_owner_agents`
+    #[account(mut)]
+    pub new_owner_agents: Option<UncheckedAccount<'info>>,
+
+    /// Cached owner (current update_authority), pays for the recreated account
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Metaplex requires this for authorization
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct OwnerOf<'info> {
+    /// Agent account PDA
+    #[account(
+        seeds = [b"agent", agent_account.agent_mint.as_ref()],
+        bump = agent_account.bump
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+}
+
+#[derive(Accounts)]
+#[instruction(agent_id: u64)]
+pub struct GetOwner<'info> {
+    /// Agent account PDA, must hold `agent_id`
+    #[account(
+        seeds = [b"agent", agent_account.agent_mint.as_ref()],
+        bump = agent_account.bump,
+        constraint = agent_account.agent_id == agent_id @ IdentityError::AgentIdMismatch
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+}
+
+#[derive(Accounts)]
+pub struct GetRegistryStats<'info> {
+    #[account(seeds = [b"registry_stats"], bump = registry_stats.bump)]
+    pub registry_stats: Account<'info, RegistryStats>,
+}
+
+#[derive(Accounts)]
+pub struct GetCollection<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RegistryConfig>,
+
+    /// CHECK: Owner and PDA verified against config.collection_mint in the handler
+    pub collection_metadata: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(extension_index: u8)]
+pub struct CreateMetadataExtension<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + MetadataExtension::MAX_SIZE,
+        seeds = [b"metadata_ext", agent_mint.key().as_ref(), &[extension_index]],
+        bump
+    )]
+    pub metadata_extension: Account<'info, MetadataExtension>,
+
+    /// Agent NFT mint (for PDA derivation)
+    pub agent_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    /// Agent account (to verify ownership and count extensions)
+    #[account(
+        mut,
+        seeds = [b"agent", agent_mint.key().as_ref()],
+        bump = agent_account.bump,
+        constraint = agent_account.owner == owner.key() @ IdentityError::Unauthorized
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(extension_index: u8)]
+pub struct CloseMetadataExtension<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"metadata_ext", agent_mint.key().as_ref(), &[extension_index]],
+        bump = metadata_extension.bump
+    )]
+    pub metadata_extension: Account<'info, MetadataExtension>,
+
+    /// Agent NFT mint (for PDA derivation)
+    pub agent_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    /// Agent account (to verify ownership and count extensions)
+    #[account(
+        mut,
+        seeds = [b"agent", agent_mint.key().as_ref()],
+        bump = agent_account.bump,
+        constraint = agent_account.owner == owner.key() @ IdentityError::Unauthorized
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+
+    /// Agent owner, receives the extension rent
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(extension_index: u8)]
+pub struct SetMetadataExtended<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RegistryConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"metadata_ext", agent_mint.key().as_ref(), &[extension_index]],
+        bump = metadata_extension.bump
+    )]
+    pub metadata_extension: Account<'info, MetadataExtension>,
+
+    /// Agent NFT mint (for PDA derivation)
+    pub agent_mint: InterfaceAccount<'info, token_interface::Mint>,
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
+    /// Agent account (to verify ownership)
     #[account(
-        init,
-        payer = authority,
-        space = 8 + RegistryConfig::SIZE,
-        seeds = [b"config"],
-        bump
+        seeds = [b"agent", agent_mint.key().as_ref()],
+        bump = agent_account.bump,
+        constraint = agent_account.owner == owner.key() @ IdentityError::Unauthorized
     )]
-    pub config: Account<'info, RegistryConfig>,
+    pub agent_account: Account<'info, AgentAccount>,
 
-    /// Collection NFT mint (created during initialization)
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(extension_index: u8)]
+pub struct GetMetadataExtended<'info> {
     #[account(
-        init,
-        payer = authority,
-        mint::decimals = 0,
-        mint::authority = authority.key(),
-        mint::freeze_authority = authority.key(),
+        seeds = [b"metadata_ext", agent_mint.key().as_ref(), &[extension_index]],
+        bump = metadata_extension.bump
     )]
-    pub collection_mint: Account<'info, Mint>,
+    pub metadata_extension: Account<'info, MetadataExtension>,
 
-    /// Metaplex Collection metadata account
-    /// CHECK: Created by Metaplex CPI
-    #[account(mut)]
-    pub collection_metadata: UncheckedAccount<'info>,
+    /// Agent NFT mint (for PDA derivation)
+    pub agent_mint: InterfaceAccount<'info, token_interface::Mint>,
+}
 
-    /// Metaplex Collection master edition account
-    /// CHECK: Created by Metaplex CPI
-    #[account(mut)]
-    pub collection_master_edition: UncheckedAccount<'info>,
+#[derive(Accounts)]
+pub struct ImportMetadata<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RegistryConfig>,
 
-    /// Token account to hold the collection NFT
     #[account(
-        init,
-        payer = authority,
-        associated_token::mint = collection_mint,
-        associated_token::authority = authority,
+        mut,
+        seeds = [b"agent", agent_mint.key().as_ref()],
+        bump = agent_account.bump
     )]
-    pub collection_token_account: Account<'info, TokenAccount>,
+    pub agent_account: Account<'info, AgentAccount>,
 
+    /// Agent NFT mint (for PDA derivation)
+    pub agent_mint: InterfaceAccount<'info, token_interface::Mint>,
+
+    /// Relayer submitting the owner-signed manifest (pays for new extensions)
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub payer: Signer<'info>,
 
     pub system_program: Program<'info, System>,
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub rent: Sysvar<'info, Rent>,
-
-    /// Metaplex Token Metadata program
-    pub token_metadata_program: Program<'info, Metadata>,
 
-    /// Sysvar Instructions
-    /// CHECK: Sysvar account
+    /// CHECK: Instructions sysvar, read to find the owner's Ed25519 check
     #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
     pub sysvar_instructions: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct Register<'info> {
+pub struct TransferAgent<'info> {
     #[account(
         mut,
-        seeds = [b"config"],
-        bump = config.bump
+        seeds = [b"agent", agent_account.agent_mint.as_ref()],
+        bump = agent_account.bump
     )]
-    pub config: Account<'info, RegistryConfig>,
+    pub agent_account: Account<'info, AgentAccount>,
 
-    /// Registry authority (needed to verify collection)
-    /// CHECK: Must match config.authority
-    #[account(constraint = authority.key() == config.authority)]
-    pub authority: UncheckedAccount<'info>,
+    /// Source token account
+    #[account(
+        mut,
+        constraint = from_token_account.mint == agent_account.agent_mint @ IdentityError::InvalidTokenAccount,
+        constraint = from_token_account.owner == owner.key() @ IdentityError::Unauthorized,
+        constraint = from_token_account.amount == 1 @ IdentityError::InvalidTokenAccount
+    )]
+    pub from_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// Destination token account
+    #[account(
+        mut,
+        constraint = to_token_account.mint == agent_account.agent_mint @ IdentityError::InvalidTokenAccount
+    )]
+    pub to_token_account: InterfaceAccount<'info, token_interface::TokenAccount>,
+
+    /// Agent NFT mint (for the checked transfer and Metaplex authority transfer)
+    #[account(constraint = agent_mint.key() == agent_account.agent_mint @ IdentityError::InvalidTokenAccount)]
+    pub agent_mint: InterfaceAccount<'info, token_interface::Mint>,
 
+    /// CHECK: Metaplex metadata PDA verified via seeds constraint
     #[account(
-        init,
+        mut,
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            agent_mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = TOKEN_METADATA_PROGRAM_ID
+    )]
+    pub agent_metadata: UncheckedAccount<'info>,
+
+    /// Previous owner's agent list
+    #[account(
+        init_if_needed,
         payer = owner,
-        space = 8 + AgentAccount::MAX_SIZE,
-        seeds = [b"agent", agent_mint.key().as_ref()],
+        space = 8 + OwnerAgentList::SIZE,
+        seeds = [b"owner_agents", agent_account.owner.as_ref()],
         bump
     )]
-    pub agent_account: Account<'info, AgentAccount>,
+    pub old_owner_agents: Account<'info, OwnerAgentList>,
+
+    /// New owner's agent list
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + OwnerAgentList::SIZE,
+        seeds = [b"owner_agents", to_token_account.owner.as_ref()],
+        bump
+    )]
+    pub new_owner_agents: Account<'info, OwnerAgentList>,
 
-    /// Agent NFT mint (created by this instruction, part of collection)
+    /// Next ownership history entry (only with `record_history`)
     #[account(
         init,
         payer = owner,
-        mint::decimals = 0,
-        mint::authority = owner.key(),
-        mint::freeze_authority = owner.key(),
+        space = 8 + OwnershipHistory::SIZE,
+        seeds = [
+            b"owner_history",
+            agent_account.agent_mint.as_ref(),
+            &agent_account.history_len.to_le_bytes(),
+        ],
+        bump
     )]
-    pub agent_mint: Account<'info, Mint>,
+    pub ownership_history: Option<Account<'info, OwnershipHistory>>,
 
-    /// Metaplex metadata account for the agent NFT
-    /// CHECK: Created by Metaplex CPI
     #[account(mut)]
-    pub agent_metadata: UncheckedAccount<'info>,
+    pub owner: Signer<'info>,
 
-    /// Metaplex master edition account for the agent NFT
-    /// CHECK: Created by Metaplex CPI
-    #[account(mut)]
-    pub agent_master_edition: UncheckedAccount<'info>,
+    /// SPL Token or Token-2022, whichever owns the agent mint
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
 
-    /// Token account to receive the agent NFT
+    /// CHECK: Metaplex requires this for authorization
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitiateTransfer<'info> {
     #[account(
-        init,
-        payer = owner,
-        associated_token::mint = agent_mint,
-        associated_token::authority = owner,
+        mut,
+        seeds = [b"agent", agent_account.agent_mint.as_ref()],
+        bump = agent_account.bump,
+        constraint = owner.key() == agent_account.owner @ IdentityError::Unauthorized
     )]
-    pub agent_token_account: Account<'info, TokenAccount>,
+    pub agent_account: Account<'info, AgentAccount>,
 
-    // Collection accounts (for verification)
-    #[account(constraint = collection_mint.key() == config.collection_mint)]
-    pub collection_mint: Account<'info, Mint>,
+    #[account(constraint = agent_mint.key() == agent_account.agent_mint @ IdentityError::InvalidTokenAccount)]
+    pub agent_mint: Account<'info, Mint>,
 
-    /// CHECK: Checked by Metaplex
-    #[account(mut)]
-    pub collection_metadata: UncheckedAccount<'info>,
+    /// Owner's token account holding the agent NFT (delegated to the PDA)
+    #[account(
+        mut,
+        constraint = owner_token_account.mint == agent_account.agent_mint @ IdentityError::InvalidTokenAccount,
+        constraint = owner_token_account.owner == owner.key() @ IdentityError::Unauthorized,
+        constraint = owner_token_account.amount == 1 @ IdentityError::InvalidTokenAccount
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
 
-    /// CHECK: Checked by Metaplex
-    pub collection_master_edition: UncheckedAccount<'info>,
+    /// CHECK: Metaplex metadata PDA verified via seeds constraint
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            agent_mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = TOKEN_METADATA_PROGRAM_ID
+    )]
+    pub agent_metadata: UncheckedAccount<'info>,
 
     #[account(mut)]
     pub owner: Signer<'info>,
 
-    pub system_program: Program<'info, System>,
     pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub rent: Sysvar<'info, Rent>,
-
-    /// Metaplex Token Metadata program
     pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
 
-    /// Sysvar Instructions
-    /// CHECK: Sysvar account
+    /// CHECK: Metaplex requires this for authorization
     #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
     pub sysvar_instructions: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct GetMetadata<'info> {
+pub struct FreezeAgent<'info> {
     #[account(
+        mut,
         seeds = [b"agent", agent_account.agent_mint.as_ref()],
-        bump = agent_account.bump
+        bump = agent_account.bump,
+        constraint = owner.key() == agent_account.owner @ IdentityError::Unauthorized
     )]
     pub agent_account: Account<'info, AgentAccount>,
+
+    #[account(constraint = agent_mint.key() == agent_account.agent_mint @ IdentityError::InvalidTokenAccount)]
+    pub agent_mint: Account<'info, Mint>,
+
+    /// Owner's token account holding the agent NFT (delegated to the PDA while frozen)
+    #[account(
+        mut,
+        constraint = owner_token_account.mint == agent_account.agent_mint @ IdentityError::InvalidTokenAccount,
+        constraint = owner_token_account.owner == owner.key() @ IdentityError::Unauthorized,
+        constraint = owner_token_account.amount == 1 @ IdentityError::InvalidTokenAccount
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    /// Master edition, the mint's freeze authority
+    /// CHECK: Metaplex master edition PDA verified via seeds constraint
+    #[account(
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            agent_mint.key().as_ref(),
+            b"edition",
+        ],
+        bump,
+        seeds::program = TOKEN_METADATA_PROGRAM_ID
+    )]
+    pub agent_master_edition: UncheckedAccount<'info>,
+
+    pub owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_metadata_program: Program<'info, Metadata>,
 }
 
 #[derive(Accounts)]
-pub struct SetMetadata<'info> {
+pub struct AcceptTransfer<'info> {
     #[account(
         mut,
         seeds = [b"agent", agent_account.agent_mint.as_ref()],
-        bump = agent_account.bump,
-        constraint = owner.key() == agent_account.owner @ IdentityError::Unauthorized
+        bump = agent_account.bump
     )]
     pub agent_account: Account<'info, AgentAccount>,
 
-    pub owner: Signer<'info>,
+    #[account(constraint = agent_mint.key() == agent_account.agent_mint @ IdentityError::InvalidTokenAccount)]
+    pub agent_mint: Account<'info, Mint>,
+
+    /// Current owner's token account, delegated to the PDA by `initiate_transfer`
+    #[account(
+        mut,
+        constraint = owner_token_account.mint == agent_account.agent_mint @ IdentityError::InvalidTokenAccount
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    /// Pending owner's associated token account (created if needed)
+    #[account(
+        init_if_needed,
+        payer = new_owner,
+        associated_token::mint = agent_mint,
+        associated_token::authority = new_owner,
+    )]
+    pub new_owner_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Metaplex metadata PDA verified via seeds constraint
+    #[account(
+        mut,
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            agent_mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = TOKEN_METADATA_PROGRAM_ID
+    )]
+    pub agent_metadata: UncheckedAccount<'info>,
+
+    /// Previous owner's agent list
+    #[account(
+        init_if_needed,
+        payer = new_owner,
+        space = 8 + OwnerAgentList::SIZE,
+        seeds = [b"owner_agents", agent_account.owner.as_ref()],
+        bump
+    )]
+    pub old_owner_agents: Account<'info, OwnerAgentList>,
+
+    /// New owner's agent list
+    #[account(
+        init_if_needed,
+        payer = new_owner,
+        space = 8 + OwnerAgentList::SIZE,
+        seeds = [b"owner_agents", new_owner.key().as_ref()],
+        bump
+    )]
+    pub new_owner_agents: Account<'info, OwnerAgentList>,
+
+    /// Pending owner, pays for their token account and agent list if they don't exist
+    #[account(mut)]
+    pub new_owner: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Metaplex requires this for authorization
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct SetAgentUri<'info> {
+pub struct CancelTransfer<'info> {
+    #[account(
+        mut,
+        seeds = [b"agent", agent_account.agent_mint.as_ref()],
+        bump = agent_account.bump,
+        constraint = owner.key() == agent_account.owner @ IdentityError::Unauthorized
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+
+    #[account(constraint = agent_mint.key() == agent_account.agent_mint @ IdentityError::InvalidTokenAccount)]
+    pub agent_mint: Account<'info, Mint>,
+
+    /// Owner's token account delegated to the PDA
     #[account(
         mut,
-        seeds = [b"agent", agent_account.agent_mint.as_ref()],
-        bump = agent_account.bump,
-        constraint = owner.key() == agent_account.owner @ IdentityError::Unauthorized
+        constraint = owner_token_account.mint == agent_account.agent_mint @ IdentityError::InvalidTokenAccount,
+        constraint = owner_token_account.owner == owner.key() @ IdentityError::Unauthorized
     )]
-    pub agent_account: Account<'info, AgentAccount>,
+    pub owner_token_account: Account<'info, TokenAccount>,
 
     /// CHECK: Metaplex metadata PDA verified via seeds constraint
     #[account(
@@ -878,32 +4461,44 @@ pub struct SetAgentUri<'info> {
     )]
     pub agent_metadata: UncheckedAccount<'info>,
 
-    pub agent_mint: Account<'info, Mint>,
-
     #[account(mut)]
     pub owner: Signer<'info>,
 
+    pub token_program: Program<'info, Token>,
     pub token_metadata_program: Program<'info, Metadata>,
     pub system_program: Program<'info, System>,
 
     /// CHECK: Metaplex requires this for authorization
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
     pub sysvar_instructions: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-pub struct SyncOwner<'info> {
+#[instruction(delegate: Pubkey)]
+pub struct ApproveTransfer<'info> {
     #[account(
         mut,
         seeds = [b"agent", agent_account.agent_mint.as_ref()],
-        bump = agent_account.bump
+        bump = agent_account.bump,
+        constraint = owner.key() == agent_account.owner @ IdentityError::Unauthorized
     )]
     pub agent_account: Account<'info, AgentAccount>,
 
-    /// Token account holding the agent NFT (must have amount = 1)
+    #[account(constraint = agent_mint.key() == agent_account.agent_mint @ IdentityError::InvalidTokenAccount)]
+    pub agent_mint: Account<'info, Mint>,
+
+    /// Owner's token account holding the agent NFT (delegated to `delegate`)
     #[account(
-        constraint = token_account.mint == agent_account.agent_mint @ IdentityError::InvalidTokenAccount
+        mut,
+        constraint = owner_token_account.mint == agent_account.agent_mint @ IdentityError::InvalidTokenAccount,
+        constraint = owner_token_account.owner == owner.key() @ IdentityError::Unauthorized,
+        constraint = owner_token_account.amount == 1 @ IdentityError::InvalidTokenAccount
     )]
-    pub token_account: Account<'info, TokenAccount>,
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Approved key, only set as SPL delegate
+    #[account(address = delegate)]
+    pub delegate_account: UncheckedAccount<'info>,
 
     /// CHECK: Metaplex metadata PDA verified via seeds constraint
     #[account(
@@ -918,15 +4513,10 @@ pub struct SyncOwner<'info> {
     )]
     pub agent_metadata: UncheckedAccount<'info>,
 
-    pub agent_mint: Account<'info, Mint>,
-
-    /// Old owner (current update_authority) must sign to transfer authority
-    #[account(
-        mut,
-        constraint = old_owner_signer.key() == agent_account.owner @ IdentityError::Unauthorized
-    )]
-    pub old_owner_signer: Signer<'info>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
 
+    pub token_program: Program<'info, Token>,
     pub token_metadata_program: Program<'info, Metadata>,
     pub system_program: Program<'info, System>,
 
@@ -936,130 +4526,146 @@ pub struct SyncOwner<'info> {
 }
 
 #[derive(Accounts)]
-pub struct OwnerOf<'info> {
-    /// Agent account PDA
+pub struct TransferAgentFrom<'info> {
     #[account(
+        mut,
         seeds = [b"agent", agent_account.agent_mint.as_ref()],
         bump = agent_account.bump
     )]
     pub agent_account: Account<'info, AgentAccount>,
-}
-
-#[derive(Accounts)]
-#[instruction(extension_index: u8)]
-pub struct CreateMetadataExtension<'info> {
-    #[account(
-        init,
-        payer = owner,
-        space = 8 + MetadataExtension::MAX_SIZE,
-        seeds = [b"metadata_ext", agent_mint.key().as_ref(), &[extension_index]],
-        bump
-    )]
-    pub metadata_extension: Account<'info, MetadataExtension>,
 
-    /// Agent NFT mint (for PDA derivation)
+    #[account(constraint = agent_mint.key() == agent_account.agent_mint @ IdentityError::InvalidTokenAccount)]
     pub agent_mint: Account<'info, Mint>,
 
-    /// Agent account (to verify ownership)
+    /// Owner's token account, delegated to `delegate` by `approve_transfer`
     #[account(
-        seeds = [b"agent", agent_mint.key().as_ref()],
-        bump = agent_account.bump,
-        constraint = agent_account.owner == owner.key() @ IdentityError::Unauthorized
+        mut,
+        constraint = from_token_account.mint == agent_account.agent_mint @ IdentityError::InvalidTokenAccount
     )]
-    pub agent_account: Account<'info, AgentAccount>,
-
-    #[account(mut)]
-    pub owner: Signer<'info>,
+    pub from_token_account: Account<'info, TokenAccount>,
 
-    pub system_program: Program<'info, System>,
-}
+    /// Destination token account
+    #[account(
+        mut,
+        constraint = to_token_account.mint == agent_account.agent_mint @ IdentityError::InvalidTokenAccount
+    )]
+    pub to_token_account: Account<'info, TokenAccount>,
 
-#[derive(Accounts)]
-#[instruction(extension_index: u8)]
-pub struct SetMetadataExtended<'info> {
+    /// CHECK: Metaplex metadata PDA verified via seeds constraint
     #[account(
         mut,
-        seeds = [b"metadata_ext", agent_mint.key().as_ref(), &[extension_index]],
-        bump = metadata_extension.bump
+        seeds = [
+            b"metadata",
+            TOKEN_METADATA_PROGRAM_ID.as_ref(),
+            agent_mint.key().as_ref(),
+        ],
+        bump,
+        seeds::program = TOKEN_METADATA_PROGRAM_ID
     )]
-    pub metadata_extension: Account<'info, MetadataExtension>,
+    pub agent_metadata: UncheckedAccount<'info>,
 
-    /// Agent NFT mint (for PDA derivation)
-    pub agent_mint: Account<'info, Mint>,
+    /// Previous owner's agent list
+    #[account(
+        init_if_needed,
+        payer = delegate,
+        space = 8 + OwnerAgentList::SIZE,
+        seeds = [b"owner_agents", agent_account.owner.as_ref()],
+        bump
+    )]
+    pub old_owner_agents: Account<'info, OwnerAgentList>,
 
-    /// Agent account (to verify ownership)
+    /// New owner's agent list
     #[account(
-        seeds = [b"agent", agent_mint.key().as_ref()],
-        bump = agent_account.bump,
-        constraint = agent_account.owner == owner.key() @ IdentityError::Unauthorized
+        init_if_needed,
+        payer = delegate,
+        space = 8 + OwnerAgentList::SIZE,
+        seeds = [b"owner_agents", to_token_account.owner.as_ref()],
+        bump
     )]
-    pub agent_account: Account<'info, AgentAccount>,
+    pub new_owner_agents: Account<'info, OwnerAgentList>,
 
-    pub owner: Signer<'info>,
+    /// Approved key (checked in handler), pays for agent lists if they don't exist
+    #[account(mut)]
+    pub delegate: Signer<'info>,
+
+    pub token_program: Program<'info, Token>,
+    pub token_metadata_program: Program<'info, Metadata>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Metaplex requires this for authorization
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
-#[instruction(extension_index: u8)]
-pub struct GetMetadataExtended<'info> {
+pub struct Deregister<'info> {
     #[account(
-        seeds = [b"metadata_ext", agent_mint.key().as_ref(), &[extension_index]],
-        bump = metadata_extension.bump
+        mut,
+        seeds = [b"config"],
+        bump = config.bump
     )]
-    pub metadata_extension: Account<'info, MetadataExtension>,
-
-    /// Agent NFT mint (for PDA derivation)
-    pub agent_mint: Account<'info, Mint>,
-}
+    pub config: Account<'info, RegistryConfig>,
 
-#[derive(Accounts)]
-pub struct TransferAgent<'info> {
     #[account(
         mut,
+        close = owner,
         seeds = [b"agent", agent_account.agent_mint.as_ref()],
-        bump = agent_account.bump
+        bump = agent_account.bump,
+        constraint = owner.key() == agent_account.owner @ IdentityError::Unauthorized
     )]
     pub agent_account: Account<'info, AgentAccount>,
 
-    /// Source token account
+    /// Reverse index agent_id -> agent_mint, closed with the agent
     #[account(
         mut,
-        constraint = from_token_account.mint == agent_account.agent_mint @ IdentityError::InvalidTokenAccount,
-        constraint = from_token_account.owner == owner.key() @ IdentityError::Unauthorized,
-        constraint = from_token_account.amount == 1 @ IdentityError::InvalidTokenAccount
+        close = owner,
+        seeds = [b"agent_id", agent_account.agent_id.to_le_bytes().as_ref()],
+        bump = agent_id_index.bump
     )]
-    pub from_token_account: Account<'info, TokenAccount>,
+    pub agent_id_index: Account<'info, AgentIdIndex>,
 
-    /// Destination token account
+    /// Agent NFT mint (supply drops to 0)
     #[account(
         mut,
-        constraint = to_token_account.mint == agent_account.agent_mint @ IdentityError::InvalidTokenAccount
+        constraint = agent_mint.key() == agent_account.agent_mint @ IdentityError::InvalidTokenAccount
     )]
-    pub to_token_account: Account<'info, TokenAccount>,
-
-    /// Agent NFT mint (for Metaplex authority transfer)
     pub agent_mint: Account<'info, Mint>,
 
-    /// CHECK: Metaplex metadata PDA verified via seeds constraint
+    /// Owner's token account holding the agent NFT
     #[account(
         mut,
+        constraint = owner_token_account.mint == agent_account.agent_mint @ IdentityError::InvalidTokenAccount,
+        constraint = owner_token_account.owner == owner.key() @ IdentityError::Unauthorized,
+        constraint = owner_token_account.amount == 1 @ IdentityError::InvalidTokenAccount
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    /// CHECK: Metaplex master edition PDA verified via seeds constraint (not
+    /// needed by the SPL burn, see `deregister`)
+    #[account(
         seeds = [
             b"metadata",
             TOKEN_METADATA_PROGRAM_ID.as_ref(),
             agent_mint.key().as_ref(),
+            b"edition",
         ],
         bump,
         seeds::program = TOKEN_METADATA_PROGRAM_ID
     )]
-    pub agent_metadata: UncheckedAccount<'info>,
+    pub agent_master_edition: UncheckedAccount<'info>,
+
+    /// Owner's agent list (optional, the agent is removed from it)
+    #[account(
+        mut,
+        seeds = [b"owner_agents", owner.key().as_ref()],
+        bump = owner_agents.bump
+    )]
+    pub owner_agents: Option<Account<'info, OwnerAgentList>>,
 
+    #[account(mut)]
     pub owner: Signer<'info>,
-    pub token_program: Program<'info, Token>,
-    pub token_metadata_program: Program<'info, Metadata>,
-    pub system_program: Program<'info, System>,
 
-    /// CHECK: Metaplex requires this for authorization
-    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
-    pub sysvar_instructions: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
 // ============================================================================
@@ -1084,6 +4690,29 @@ pub struct MetadataSet {
     pub value: Vec<u8>,
 }
 
+/// Event emitted when a metadata extension PDA is created
+#[event]
+pub struct MetadataExtensionCreated {
+    pub agent_mint: Pubkey,
+    pub extension_index: u8,
+}
+
+/// Event emitted when a metadata extension PDA is closed
+#[event]
+pub struct MetadataExtensionClosed {
+    pub agent_mint: Pubkey,
+    pub extension_index: u8,
+}
+
+/// Event emitted when an agent metadata entry is removed
+#[event]
+pub struct MetadataRemoved {
+    pub agent_id: u64,
+    pub key: String,
+    /// Extension PDA the entry was removed from (`None` for the agent account)
+    pub extension_index: Option<u8>,
+}
+
 /// Event emitted when agent URI is updated (ERC-8004 spec: UriUpdated)
 #[event]
 pub struct UriUpdated {
@@ -1092,6 +4721,74 @@ pub struct UriUpdated {
     pub updated_by: Pubkey, // Who performed the update
 }
 
+/// Event emitted when an agent's metadata delegate is set or revoked
+#[event]
+pub struct MetadataDelegateSet {
+    pub agent_id: u64,
+    pub delegate: Option<Pubkey>,
+}
+
+/// Event emitted when an agent account's metadata capacity is resized
+#[event]
+pub struct AgentMetadataResized {
+    pub agent_id: u64,
+    pub max_entries: u16,
+}
+
+/// Event emitted when an agent's directory category is set
+#[event]
+pub struct CategorySet {
+    pub agent_id: u64,
+    pub category: u16,
+    pub set_by: Pubkey,
+}
+
+/// Event emitted when an agent's operator type is declared
+#[event]
+pub struct OperatorTypeSet {
+    pub agent_id: u64,
+    pub operator_type: u8,
+    pub set_by: Pubkey,
+}
+
+/// Event emitted when the registry authority attests an agent's operator type
+#[event]
+pub struct OperatorTypeAttested {
+    pub agent_id: u64,
+    pub operator_type: u8,
+    pub attester: Pubkey,
+}
+
+/// Event emitted when a collection authority delegate is approved
+#[event]
+pub struct CollectionAuthorityApproved {
+    pub collection_mint: Pubkey,
+    pub delegate: Pubkey,
+}
+
+/// Event emitted when an agent registered unverified is verified into the collection
+#[event]
+pub struct AgentCollectionVerified {
+    pub agent_id: u64,
+    pub agent_mint: Pubkey,
+    pub collection_mint: Pubkey,
+    pub verified_by: Pubkey,
+}
+
+/// Event emitted when the registry authority is transferred to a new key
+#[event]
+pub struct AuthorityTransferred {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+/// Event emitted when a collection authority delegate is revoked
+#[event]
+pub struct CollectionAuthorityRevoked {
+    pub collection_mint: Pubkey,
+    pub delegate: Pubkey,
+}
+
 /// Event emitted when agent owner is synced after transfer
 #[event]
 pub struct AgentOwnerSynced {
@@ -1100,3 +4797,128 @@ pub struct AgentOwnerSynced {
     pub new_owner: Pubkey,
     pub agent_mint: Pubkey,
 }
+
+/// Event emitted when an ownership change is appended to the agent's history
+#[event]
+pub struct OwnershipRecorded {
+    pub agent_id: u64,
+    pub agent_mint: Pubkey,
+    pub seq: u32,
+    pub from: Pubkey,
+    pub to: Pubkey,
+}
+
+/// Event emitted when a two-step transfer is initiated
+#[event]
+pub struct TransferInitiated {
+    pub agent_id: u64,
+    pub owner: Pubkey,
+    pub pending_owner: Pubkey,
+}
+
+/// Event emitted when the collection's name or URI is changed
+#[event]
+pub struct CollectionMetadataUpdated {
+    pub collection_mint: Pubkey,
+    pub name: String,
+    pub uri: String,
+}
+
+/// Event emitted when the owner approves a key to move the agent
+#[event]
+pub struct TransferApproved {
+    pub agent_id: u64,
+    pub owner: Pubkey,
+    pub approved: Pubkey,
+}
+
+/// Event emitted when the owner revokes a transfer approval
+#[event]
+pub struct TransferApprovalRevoked {
+    pub agent_id: u64,
+    pub owner: Pubkey,
+    pub approved: Pubkey,
+}
+
+/// Event emitted when the pending owner accepts a two-step transfer
+#[event]
+pub struct TransferAccepted {
+    pub agent_id: u64,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+/// Event emitted when the owner freezes or thaws an agent
+#[event]
+pub struct AgentFreezeChanged {
+    pub agent_id: u64,
+    pub owner: Pubkey,
+    pub frozen: bool,
+}
+
+/// Event emitted when the owner cancels a two-step transfer
+#[event]
+pub struct TransferCancelled {
+    pub agent_id: u64,
+    pub owner: Pubkey,
+    pub pending_owner: Pubkey,
+}
+
+/// Event emitted when an agent's cached feedback/validation counts are synced
+#[event]
+pub struct AgentCountsSynced {
+    pub agent_id: u64,
+    pub feedback_count: u64,
+    pub validation_count: u64,
+}
+
+/// Event emitted when an agent is deprecated
+#[event]
+pub struct AgentDeprecated {
+    pub agent_id: u64,
+    pub successor_agent: Option<u64>,
+    pub deprecated_by: Pubkey,
+}
+
+/// Event emitted when an agent is burned and its account closed
+#[event]
+pub struct AgentDeregistered {
+    pub agent_id: u64,
+    pub owner: Pubkey,
+    pub agent_mint: Pubkey,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_uri() {
+        assert!(validate_uri("").is_ok());
+        assert!(validate_uri("ipfs://QmAgent").is_ok());
+        assert!(validate_uri("ar://tx").is_ok());
+        assert!(validate_uri("https://example.com/agent.json").is_ok());
+        assert!(validate_uri("http://localhost/agent.json").is_ok());
+        assert_eq!(
+            validate_uri("ftp://example.com/agent.json").unwrap_err(),
+            IdentityError::InvalidUriScheme.into()
+        );
+        assert_eq!(
+            validate_uri("example.com").unwrap_err(),
+            IdentityError::InvalidUriScheme.into()
+        );
+    }
+
+    #[test]
+    fn test_assert_new_agent_mint() {
+        assert!(assert_new_agent_mint(0, 0).is_ok());
+        assert_eq!(
+            assert_new_agent_mint(1, 0).unwrap_err(),
+            IdentityError::InvalidNftSupply.into()
+        );
+        assert_eq!(
+            assert_new_agent_mint(0, 6).unwrap_err(),
+            IdentityError::InvalidNftDecimals.into()
+        );
+    }
+}
@@ -1,4 +1,11 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::pubkey;
+
+/// Reputation Registry program (source of `cached_feedback_count`)
+pub const REPUTATION_REGISTRY_ID: Pubkey = pubkey!("9WcFLL3Fsqs96JxuewEt9iqRwULtCZEsPT717hPbsQAa");
+
+/// Devnet Validation Registry program, default `RegistryConfig::validation_registry`
+pub const DEFAULT_VALIDATION_REGISTRY_ID: Pubkey = pubkey!("CXvuHNGWTHNqXmWr95wSpNGKR3kpcJUhzKofTF3zsoxW");
 
 /// Global registry configuration
 #[account]
@@ -15,14 +22,97 @@ pub struct RegistryConfig {
     /// Metaplex Collection NFT mint (all agents are part of this collection)
     pub collection_mint: Pubkey,
 
+    /// Maximum metadata value length accepted by this deployment
+    /// (1..=MetadataEntry::MAX_VALUE_LENGTH, defaults to DEFAULT_VALUE_LENGTH)
+    pub max_value_length: u16,
+
+    /// New registrations are halted (existing agents stay fully mutable)
+    pub paused: bool,
+
+    /// Royalty written into new agent NFTs' Metaplex metadata, in basis
+    /// points (0..=MAX_SELLER_FEE_BPS)
+    pub default_seller_fee_bps: u16,
+
+    /// Whether registration verifies new agents into the collection
+    /// (off lets a migration mint them unverified, see `verify_agent_collection`)
+    pub verify_collection: bool,
+
+    /// Maximum token URI length accepted by this deployment
+    /// (1..=AgentAccount::MAX_URI_LENGTH, defaults to DEFAULT_URI_LENGTH)
+    pub max_uri_length: u16,
+
+    /// Validation Registry program (source of `cached_validation_count`);
+    /// `AgentValidationMetadata` accounts must be owned by it
+    pub validation_registry: Pubkey,
+
     /// PDA bump seed
     pub bump: u8,
 }
 
 impl RegistryConfig {
     /// Space required for RegistryConfig account
-    /// 32 (authority) + 8 (next_agent_id) + 8 (total_agents) + 32 (collection_mint) + 1 (bump)
-    pub const SIZE: usize = 32 + 8 + 8 + 32 + 1;
+    /// 32 (authority) + 8 (next_agent_id) + 8 (total_agents) + 32 (collection_mint)
+    /// + 2 (max_value_length) + 1 (paused) + 2 (default_seller_fee_bps)
+    /// + 1 (verify_collection) + 2 (max_uri_length) + 32 (validation_registry) + 1 (bump)
+    pub const SIZE: usize = 32 + 8 + 8 + 32 + 2 + 1 + 2 + 1 + 2 + 32 + 1;
+
+    /// Highest seller fee Metaplex accepts (100%)
+    pub const MAX_SELLER_FEE_BPS: u16 = 10_000;
+
+    /// Whether `bps` is a seller fee Metaplex accepts
+    pub fn is_valid_seller_fee_bps(bps: u16) -> bool {
+        bps <= Self::MAX_SELLER_FEE_BPS
+    }
+
+    /// Whether `max_value_length` is a limit the account layout can hold
+    pub fn is_valid_max_value_length(max_value_length: u16) -> bool {
+        max_value_length > 0 && max_value_length as usize <= MetadataEntry::MAX_VALUE_LENGTH
+    }
+
+    /// Whether `max_uri_length` is a limit the account layout can hold
+    pub fn is_valid_max_uri_length(max_uri_length: u16) -> bool {
+        max_uri_length > 0 && max_uri_length as usize <= AgentAccount::MAX_URI_LENGTH
+    }
+
+    /// Whether `new_authority` can take over as a governance PDA authority
+    /// (off-curve, so only a program can sign for it via invoke_signed)
+    pub fn is_valid_pda_authority(&self, new_authority: &Pubkey) -> bool {
+        !new_authority.is_on_curve() && *new_authority != self.authority
+    }
+
+    /// Whether a metadata value of `len` bytes is accepted by this deployment
+    pub fn accepts_value_length(&self, len: usize) -> bool {
+        len <= self.max_value_length as usize
+    }
+
+    /// Whether a token URI of `len` bytes is accepted by this deployment
+    pub fn accepts_uri_length(&self, len: usize) -> bool {
+        len <= self.max_uri_length as usize
+    }
+}
+
+/// Registry collection identity returned by `get_collection`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct CollectionView {
+    /// Metaplex Collection NFT mint
+    pub collection_mint: Pubkey,
+
+    /// Collection name from the Metaplex metadata (padding stripped)
+    pub name: String,
+
+    /// Collection URI from the Metaplex metadata (padding stripped)
+    pub uri: String,
+}
+
+impl CollectionView {
+    /// Metaplex pads name/symbol/uri with NUL bytes up to their max length
+    pub fn new(collection_mint: Pubkey, name: &str, uri: &str) -> Self {
+        Self {
+            collection_mint,
+            name: name.trim_end_matches('\0').to_string(),
+            uri: uri.trim_end_matches('\0').to_string(),
+        }
+    }
 }
 
 /// Agent account (equivalent to ERC-721 token)
@@ -37,8 +127,22 @@ pub struct AgentAccount {
     /// Agent NFT mint (SPL Token with supply=1, decimals=0)
     pub agent_mint: Pubkey,
 
+    /// Directory category (see `AgentCategory`), 0 = uncategorized
+    /// Kept before variable-length fields so it sits at a fixed offset
+    /// (`AgentAccount::CATEGORY_OFFSET`) for getProgramAccounts memcmp filters
+    pub category: u16,
+
+    /// Declared operator type (see `OperatorType`), fixed offset `OPERATOR_TYPE_OFFSET`
+    pub operator_type: u8,
+
+    /// Lifecycle status (see `AgentStatus`), fixed offset `STATUS_OFFSET`
+    pub status: u8,
+
+    /// Agent that supersedes this one, set by `deprecate_agent`
+    pub successor_agent: Option<u64>,
+
     /// Token URI (IPFS/Arweave/HTTP link)
-    /// Max 200 bytes per ERC-8004 spec
+    /// Max `RegistryConfig::max_uri_length` bytes (200 by default, per ERC-8004)
     pub token_uri: String,
 
     /// NFT name stored for UpdateMetadata CPI (e.g., "Agent #123")
@@ -52,36 +156,519 @@ pub struct AgentAccount {
     /// Key-value metadata (max 10 entries)
     pub metadata: Vec<MetadataEntry>,
 
+    /// Metadata extension PDAs created for this agent (max MAX_EXTENSIONS)
+    pub extension_count: u8,
+
+    /// Non-revoked feedbacks in the Reputation Registry as of the last
+    /// `sync_agent_counts` (lazily synced, may be stale)
+    pub cached_feedback_count: u64,
+
+    /// Validation requests in the Validation Registry as of the last
+    /// `sync_agent_counts` (lazily synced, may be stale)
+    pub cached_validation_count: u64,
+
+    /// Recipient of a two-step transfer awaiting `accept_transfer`. While set,
+    /// the NFT is delegated to and the Metaplex update authority held by this
+    /// AgentAccount PDA. Cleared on accept, cancel, or `sync_owner`.
+    pub pending_owner: Option<Pubkey>,
+
+    /// Key allowed to call `set_metadata` on the owner's behalf. Cannot set
+    /// the URI, transfer the agent or change the delegate; cleared whenever
+    /// ownership changes.
+    pub metadata_delegate: Option<Pubkey>,
+
+    /// Key allowed to move the NFT with `transfer_agent_from` (ERC-721
+    /// `getApproved`). While set, the NFT is delegated to this key and the
+    /// Metaplex update authority held by this AgentAccount PDA. Cleared on
+    /// transfer, revocation, or `sync_owner`.
+    pub approved: Option<Pubkey>,
+
     /// Creation timestamp
     pub created_at: i64,
 
+    /// Last mutation timestamp (metadata, URI, ownership); equals `created_at`
+    /// until the first change
+    pub updated_at: i64,
+
+    /// `OwnershipHistory` entries recorded for this agent (next entry's `seq`)
+    pub history_len: u32,
+
+    /// Owner's token account is frozen by `freeze_agent` (NFT delegated to
+    /// this AgentAccount PDA until `thaw_agent`)
+    pub frozen: bool,
+
+    /// Nonce the owner signs into the next `import_metadata` manifest,
+    /// incremented on each import so a signed manifest can't be replayed
+    pub import_nonce: u64,
+
     /// PDA bump seed
     pub bump: u8,
 }
 
 impl AgentAccount {
     /// Maximum size for AgentAccount
-    /// 8 (discriminator) + 8 (agent_id) + 32 (owner) + 32 (agent_mint)
+    /// 8 (discriminator) + 8 (agent_id) + 32 (owner) + 32 (agent_mint) + 2 (category)
+    /// + 1 (operator_type) + 1 (status) + 1 + 8 (successor_agent)
     /// + 4 + 200 (token_uri) + 4 + 32 (nft_name) + 4 + 10 (nft_symbol)
-    /// + 4 + (10 * MetadataEntry::MAX_SIZE) (metadata)
-    /// + 8 (created_at) + 1 (bump)
-    pub const MAX_SIZE: usize = 8 + 8 + 32 + 32 + 4 + 200 + 4 + 32 + 4 + 10 + 4 + (10 * MetadataEntry::MAX_SIZE) + 8 + 1;
+    /// + 4 + (10 * MetadataEntry::SLOT_SIZE) (metadata) + 1 (extension_count)
+    /// + 8 (cached_feedback_count) + 8 (cached_validation_count)
+    /// + 1 + 32 (pending_owner) + 1 + 32 (metadata_delegate) + 1 + 32 (approved)
+    /// + 8 (created_at) + 8 (updated_at) + 4 (history_len) + 1 (frozen)
+    /// + 8 (import_nonce) + 1 (bump)
+    ///
+    /// Token URIs up to RESERVED_URI_LENGTH fit; see `space_for` for longer ones.
+    pub const MAX_SIZE: usize = 8 + 8 + 32 + 32 + 2 + 1 + 1 + 9 + 4 + Self::RESERVED_URI_LENGTH + 4 + 32 + 4 + 10 + 4 + (10 * MetadataEntry::SLOT_SIZE) + 1 + 8 + 8 + 33 + 33 + 33 + 8 + 8 + 4 + 1 + 8 + 1;
+
+    /// Byte offset of `category` in the account data (including discriminator)
+    /// 8 (discriminator) + 8 (agent_id) + 32 (owner) + 32 (agent_mint)
+    pub const CATEGORY_OFFSET: usize = 8 + 8 + 32 + 32;
+
+    /// Byte offset of `operator_type` in the account data (including discriminator)
+    pub const OPERATOR_TYPE_OFFSET: usize = Self::CATEGORY_OFFSET + 2;
+
+    /// Byte offset of `status` in the account data (including discriminator)
+    pub const STATUS_OFFSET: usize = Self::OPERATOR_TYPE_OFFSET + 1;
 
     /// Maximum number of metadata entries allowed
     pub const MAX_METADATA_ENTRIES: usize = 10;
 
+    /// Upper bound for `resize_agent_metadata`
+    ///
+    /// Growing from the base 10 entries to 28 adds 18 * 296 = 5328 bytes, which
+    /// keeps any resize within the runtime's 10 KiB per-instruction realloc limit.
+    pub const MAX_RESIZED_METADATA_ENTRIES: usize = 28;
+
+    /// Account space (including discriminator) for a `uri_len`-byte token URI
+    /// and `max_entries` metadata entries
+    ///
+    /// Never less than the base `8 + MAX_SIZE`, which already fits 10 entries
+    /// and a RESERVED_URI_LENGTH URI; a longer URI grows the account by the
+    /// excess only.
+    pub fn space_for(uri_len: usize, max_entries: usize) -> usize {
+        8 + Self::MAX_SIZE
+            + uri_len.saturating_sub(Self::RESERVED_URI_LENGTH)
+            + max_entries.saturating_sub(Self::MAX_METADATA_ENTRIES) * MetadataEntry::SLOT_SIZE
+    }
+
+    /// Number of metadata entries an account of `data_len` bytes storing a
+    /// `uri_len`-byte token URI can hold
+    pub fn metadata_capacity(data_len: usize, uri_len: usize) -> usize {
+        Self::MAX_METADATA_ENTRIES
+            + data_len
+                .saturating_sub(8 + Self::MAX_SIZE + uri_len.saturating_sub(Self::RESERVED_URI_LENGTH))
+                / MetadataEntry::SLOT_SIZE
+    }
+
+    /// Maximum metadata extensions per agent (indices 0..MAX_EXTENSIONS)
+    pub const MAX_EXTENSIONS: u8 = 16;
+
     /// Maximum token URI length in bytes
-    pub const MAX_URI_LENGTH: usize = 200;
+    ///
+    /// Agent accounts only reserve RESERVED_URI_LENGTH bytes: registration and
+    /// `set_agent_uri` grow (or shrink) them to the URI actually stored, so
+    /// only agents with long URIs pay rent for them.
+    pub const MAX_URI_LENGTH: usize = 512;
+
+    /// Token URI bytes included in MAX_SIZE (the default `max_uri_length`)
+    pub const RESERVED_URI_LENGTH: usize = 200;
+
+    /// Default `RegistryConfig::max_uri_length` (ERC-8004 spec)
+    pub const DEFAULT_URI_LENGTH: u16 = 200;
+
+    /// Schemes accepted for a non-empty token URI
+    pub const URI_SCHEMES: [&'static str; 4] = ["ipfs://", "ar://", "https://", "http://"];
+
+    /// Maximum agents per `sync_owners_batch` call (compute and account limits)
+    pub const MAX_SYNC_BATCH: usize = 8;
+
+    /// Remaining accounts per agent in `sync_owners_batch`
+    /// agent_account, token_account, agent_mint, agent_metadata, new_owner_agents
+    pub const SYNC_BATCH_ACCOUNTS_PER_AGENT: usize = 5;
+
+    /// Anchor discriminator of the Reputation Registry's `AgentReputationMetadata`
+    pub const AGENT_REPUTATION_DISCRIMINATOR: [u8; 8] = [36, 5, 84, 173, 138, 224, 67, 147];
+
+    /// Anchor discriminator of the Validation Registry's `AgentValidationMetadata`
+    pub const AGENT_VALIDATION_DISCRIMINATOR: [u8; 8] = [165, 146, 118, 34, 242, 101, 143, 180];
+
+    /// Read `total_feedbacks` for `agent_id` from raw `AgentReputationMetadata` data
+    /// Layout: 8 (discriminator) + 8 (agent_id) + 8 (total_feedbacks)
+    pub fn feedback_count_from_data(data: &[u8], agent_id: u64) -> Option<u64> {
+        if data.len() < 24 || data[..8] != Self::AGENT_REPUTATION_DISCRIMINATOR {
+            return None;
+        }
+        let id = u64::from_le_bytes(data[8..16].try_into().ok()?);
+        let count = u64::from_le_bytes(data[16..24].try_into().ok()?);
+        (id == agent_id).then_some(count)
+    }
+
+    /// Read `total_requests` for `agent_id` from raw `AgentValidationMetadata` data
+    /// Layout: 8 (discriminator) + 8 (agent_id) + 8 (total_requests)
+    pub fn validation_count_from_data(data: &[u8], agent_id: u64) -> Option<u64> {
+        if data.len() < 24 || data[..8] != Self::AGENT_VALIDATION_DISCRIMINATOR {
+            return None;
+        }
+        let id = u64::from_le_bytes(data[8..16].try_into().ok()?);
+        let count = u64::from_le_bytes(data[16..24].try_into().ok()?);
+        (id == agent_id).then_some(count)
+    }
 
     /// Find metadata entry by key
+    ///
+    /// Linear scan on purpose: entries stay in insertion order (what
+    /// `get_all_metadata` returns) and at most `MAX_RESIZED_METADATA_ENTRIES`
+    /// keys of up to 32 bytes are compared, each rejected on length or first
+    /// differing byte. See docs/PERFORMANCE_BENCHMARKS.md (Metadata Key Lookup).
     pub fn find_metadata(&self, key: &str) -> Option<&MetadataEntry> {
         self.metadata.iter().find(|entry| entry.key == key)
     }
 
-    /// Find mutable metadata entry by key
+    /// Find mutable metadata entry by key (linear scan, see `find_metadata`)
     pub fn find_metadata_mut(&mut self, key: &str) -> Option<&mut MetadataEntry> {
         self.metadata.iter_mut().find(|entry| entry.key == key)
     }
+
+    /// Remove the metadata entry with `key`, keeping the order of the others.
+    /// Returns false if no entry had that key.
+    pub fn remove_metadata(&mut self, key: &str) -> bool {
+        let len = self.metadata.len();
+        self.metadata.retain(|entry| entry.key != key);
+        self.metadata.len() != len
+    }
+
+    /// Check that a token account (mint, owner, amount) holds this agent's NFT
+    /// on behalf of the cached owner
+    pub fn is_held_by(&self, mint: &Pubkey, holder: &Pubkey, amount: u64) -> bool {
+        *mint == self.agent_mint && *holder == self.owner && amount == 1
+    }
+
+    /// Whether `signer` may edit metadata: the owner or the current delegate
+    pub fn can_edit_metadata(&self, signer: &Pubkey) -> bool {
+        *signer == self.owner || self.metadata_delegate == Some(*signer)
+    }
+
+    /// Record a new owner, dropping the delegate and transfer approval granted
+    /// by the previous one
+    pub fn set_owner(&mut self, new_owner: Pubkey) {
+        self.owner = new_owner;
+        self.metadata_delegate = None;
+        self.approved = None;
+    }
+
+    /// Whether this PDA holds the Metaplex update_authority, i.e. a two-step
+    /// transfer or a transfer approval is outstanding
+    pub fn pda_holds_update_authority(&self) -> bool {
+        self.pending_owner.is_some() || self.approved.is_some()
+    }
+
+    /// Whether another extension may be created at `extension_index`
+    pub fn can_create_extension(&self, extension_index: u8) -> bool {
+        extension_index < Self::MAX_EXTENSIONS && self.extension_count < Self::MAX_EXTENSIONS
+    }
+
+    /// Whether the owner has deprecated this agent
+    pub fn is_deprecated(&self) -> bool {
+        self.status == AgentStatus::Deprecated as u8
+    }
+
+    /// Reserve the `seq` of the next `OwnershipHistory` entry
+    pub fn take_history_seq(&mut self) -> u32 {
+        let seq = self.history_len;
+        self.history_len += 1;
+        seq
+    }
+}
+
+/// Agent directory categories, stored as `AgentAccount.category: u16`
+///
+/// Category registry:
+/// | Value       | Category                         |
+/// |-------------|----------------------------------|
+/// | 0           | Uncategorized (default)          |
+/// | 1           | Trading                          |
+/// | 2           | Research                         |
+/// | 3           | Gaming                           |
+/// | 4           | Assistant                        |
+/// | 5           | Developer tools                  |
+/// | 6           | Data & analytics                 |
+/// | 7           | Social                           |
+/// | 8           | Infrastructure                   |
+/// | 9..=999     | Reserved for future categories   |
+/// | 1000..      | Deployment-specific categories   |
+pub struct AgentCategory;
+
+impl AgentCategory {
+    pub const UNCATEGORIZED: u16 = 0;
+
+    /// Highest standard category currently registered (Infrastructure)
+    pub const MAX_STANDARD: u16 = 8;
+
+    /// First value available for deployment-specific categories
+    pub const CUSTOM_START: u16 = 1000;
+
+    /// Check a category is a registered standard value or in the custom range
+    pub fn is_valid(category: u16) -> bool {
+        category <= Self::MAX_STANDARD || category >= Self::CUSTOM_START
+    }
+}
+
+/// Who operates an agent, stored as `AgentAccount.operator_type: u8`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum OperatorType {
+    /// Acts without a human in the loop (default)
+    Autonomous = 0,
+    /// A human operates or approves every action
+    HumanOperated = 1,
+    /// Autonomous with human oversight
+    Hybrid = 2,
+}
+
+impl OperatorType {
+    /// Parse a stored operator type
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Autonomous),
+            1 => Some(Self::HumanOperated),
+            2 => Some(Self::Hybrid),
+            _ => None,
+        }
+    }
+}
+
+/// Agent lifecycle status, stored as `AgentAccount.status: u8`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum AgentStatus {
+    /// Registered and current (default)
+    Active = 0,
+    /// Superseded, clients should migrate to `successor_agent` when set
+    Deprecated = 1,
+}
+
+/// Registry authority attestation of an agent's declared operator type
+/// Seeds: [b"operator_attestation", agent_mint]
+#[account]
+pub struct OperatorAttestation {
+    /// Agent ID being vouched for
+    pub agent_id: u64,
+
+    /// Operator type the attester vouched for
+    pub operator_type: u8,
+
+    /// Registry authority that signed the attestation
+    pub attester: Pubkey,
+
+    /// Attestation timestamp
+    pub attested_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl OperatorAttestation {
+    /// Space required for OperatorAttestation account
+    /// 8 (agent_id) + 1 (operator_type) + 32 (attester) + 8 (attested_at) + 1 (bump)
+    pub const SIZE: usize = 8 + 1 + 32 + 8 + 1;
+
+    /// Check the attestation still matches the agent's declared type
+    /// (the owner may change the type after it was attested)
+    pub fn vouches_for(&self, agent: &AgentAccount) -> bool {
+        self.agent_id == agent.agent_id && self.operator_type == agent.operator_type
+    }
+}
+
+/// Reverse index from agent ID to agent mint, created at registration
+/// Seeds: [b"agent_id", agent_id.to_le_bytes()]
+///
+/// Clients that only know the numeric ID (e.g. from events) resolve
+/// id -> mint -> AgentAccount PDA deterministically.
+#[account]
+pub struct AgentIdIndex {
+    /// Agent ID (sequential)
+    pub agent_id: u64,
+
+    /// Agent NFT mint (AgentAccount seed)
+    pub agent_mint: Pubkey,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl AgentIdIndex {
+    /// Space required for AgentIdIndex account
+    /// 8 (agent_id) + 32 (agent_mint) + 1 (bump)
+    pub const SIZE: usize = 8 + 32 + 1;
+}
+
+/// Agents held by one owner, for "my agents" enumeration without a scan
+/// Seeds: [b"owner_agents", owner]
+///
+/// Maintained by registration, `transfer_agent`, `accept_transfer`,
+/// `sync_owner`, `sync_owners_batch`, the re-sync path of
+/// `reopen_agent_token_account` and `deregister` (when passed). Agents
+/// registered before the index existed are only added when they next move.
+///
+/// The list is capped at `MAX_AGENTS`: an index must never block a
+/// registration or transfer, so agents acquired while it is full are left out
+/// and `truncated` is set.
+#[account]
+pub struct OwnerAgentList {
+    /// Wallet owning the listed agents
+    pub owner: Pubkey,
+
+    /// Agent IDs, in the order they were acquired
+    pub agent_ids: Vec<u64>,
+
+    /// Set once an agent could not be listed because the list was full
+    pub truncated: bool,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl OwnerAgentList {
+    /// Maximum agents tracked per owner
+    pub const MAX_AGENTS: usize = 64;
+
+    /// Space required for OwnerAgentList account
+    /// 32 (owner) + 4 + 64 * 8 (agent_ids) + 1 (truncated) + 1 (bump)
+    pub const SIZE: usize = 32 + 4 + Self::MAX_AGENTS * 8 + 1 + 1;
+
+    /// Set owner and bump on a freshly created list
+    pub fn init_if_new(&mut self, owner: Pubkey, bump: u8) {
+        if self.owner == Pubkey::default() {
+            self.owner = owner;
+            self.bump = bump;
+        }
+    }
+
+    /// Append an agent; returns false (and marks the list truncated) when full
+    pub fn add(&mut self, agent_id: u64) -> bool {
+        if self.agent_ids.contains(&agent_id) {
+            return true;
+        }
+        if self.agent_ids.len() >= Self::MAX_AGENTS {
+            self.truncated = true;
+            return false;
+        }
+        self.agent_ids.push(agent_id);
+        true
+    }
+
+    /// Remove an agent; returns false if it wasn't listed
+    pub fn remove(&mut self, agent_id: u64) -> bool {
+        let len = self.agent_ids.len();
+        self.agent_ids.retain(|id| *id != agent_id);
+        self.agent_ids.len() != len
+    }
+}
+
+/// Registrations counted for one UTC day
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct DailyRegistrations {
+    /// Days since the Unix epoch (`unix_timestamp / 86400`)
+    pub day_epoch: i64,
+
+    /// Agents registered that day
+    pub count: u64,
+}
+
+/// Registry growth metrics, updated on every registration
+/// Seeds: [b"registry_stats"]
+///
+/// Per-day counts live in a ring of `TRACKED_DAYS` slots indexed by
+/// `day_epoch % TRACKED_DAYS`: a slot still holding an older day is reset
+/// when that day's first registration arrives, so only the last 30 days
+/// are answerable.
+#[account]
+pub struct RegistryStats {
+    /// Timestamp of the latest registration
+    pub last_registration_ts: i64,
+
+    /// Per-day counts, see `DailyRegistrations`
+    pub days: [DailyRegistrations; 30],
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl RegistryStats {
+    /// Days kept in the ring
+    pub const TRACKED_DAYS: usize = 30;
+
+    /// Seconds per UTC day
+    pub const SECONDS_PER_DAY: i64 = 86_400;
+
+    /// Space required for RegistryStats account
+    /// 8 (last_registration_ts) + 30 * (8 + 8) (days) + 1 (bump)
+    pub const SIZE: usize = 8 + Self::TRACKED_DAYS * (8 + 8) + 1;
+
+    /// Day (since the Unix epoch) a timestamp falls on
+    pub fn day_epoch(unix_timestamp: i64) -> i64 {
+        unix_timestamp.div_euclid(Self::SECONDS_PER_DAY)
+    }
+
+    fn slot(day_epoch: i64) -> usize {
+        day_epoch.rem_euclid(Self::TRACKED_DAYS as i64) as usize
+    }
+
+    /// Count a registration made at `now` (saturating: metrics never block a registration)
+    pub fn record_registration(&mut self, now: i64) {
+        let day_epoch = Self::day_epoch(now);
+        let day = &mut self.days[Self::slot(day_epoch)];
+        if day.day_epoch != day_epoch {
+            *day = DailyRegistrations { day_epoch, count: 0 };
+        }
+        day.count = day.count.saturating_add(1);
+        self.last_registration_ts = now;
+    }
+
+    /// Registrations on `day_epoch` (0 if none, or older than the ring)
+    pub fn daily_registrations(&self, day_epoch: i64) -> u64 {
+        let day = &self.days[Self::slot(day_epoch)];
+        if day.day_epoch == day_epoch {
+            day.count
+        } else {
+            0
+        }
+    }
+}
+
+/// One ownership change of an agent, recorded on request
+/// Seeds: [b"owner_history", agent_mint, seq (u32 LE)]
+///
+/// Opt-in via `record_history` on `transfer_agent` / `sync_owner`, so only
+/// owners who want an on-chain chain of custody pay its rent. Entries are
+/// numbered from 0 in transfer order; `AgentAccount.history_len` is the count.
+#[account]
+pub struct OwnershipHistory {
+    /// Agent that changed hands
+    pub agent_id: u64,
+
+    /// Agent NFT mint
+    pub agent_mint: Pubkey,
+
+    /// Position in the agent's history (0 = first recorded transfer)
+    pub seq: u32,
+
+    /// Owner before the change
+    pub from: Pubkey,
+
+    /// Owner after the change
+    pub to: Pubkey,
+
+    /// Time of the change
+    pub ts: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl OwnershipHistory {
+    /// Space required for OwnershipHistory account
+    /// 8 (agent_id) + 32 (agent_mint) + 4 (seq) + 32 (from) + 32 (to) + 8 (ts) + 1 (bump)
+    pub const SIZE: usize = 8 + 32 + 4 + 32 + 32 + 8 + 1;
 }
 
 /// Metadata extension PDA for additional entries beyond the base 10
@@ -91,6 +678,9 @@ pub struct MetadataExtension {
     /// Agent NFT mint reference
     pub agent_mint: Pubkey,
 
+    /// Agent ID (saves indexers a lookup through the mint)
+    pub agent_id: u64,
+
     /// Extension index (0, 1, 2, ...) for sequential extensions
     pub extension_index: u8,
 
@@ -103,9 +693,9 @@ pub struct MetadataExtension {
 
 impl MetadataExtension {
     /// Maximum size for MetadataExtension
-    /// 8 (discriminator) + 32 (agent_mint) + 1 (extension_index)
-    /// + 4 + (10 * MetadataEntry::MAX_SIZE) (metadata) + 1 (bump)
-    pub const MAX_SIZE: usize = 8 + 32 + 1 + 4 + (10 * MetadataEntry::MAX_SIZE) + 1;
+    /// 8 (discriminator) + 32 (agent_mint) + 8 (agent_id) + 1 (extension_index)
+    /// + 4 + (10 * MetadataEntry::SLOT_SIZE) (metadata) + 1 (bump)
+    pub const MAX_SIZE: usize = 8 + 32 + 8 + 1 + 4 + (10 * MetadataEntry::SLOT_SIZE) + 1;
 
     /// Maximum number of metadata entries per extension
     pub const MAX_METADATA_ENTRIES: usize = 10;
@@ -119,6 +709,14 @@ impl MetadataExtension {
     pub fn find_metadata_mut(&mut self, key: &str) -> Option<&mut MetadataEntry> {
         self.metadata.iter_mut().find(|entry| entry.key == key)
     }
+
+    /// Remove the metadata entry with `key`, keeping the order of the others.
+    /// Returns false if no entry had that key.
+    pub fn remove_metadata(&mut self, key: &str) -> bool {
+        let len = self.metadata.len();
+        self.metadata.retain(|entry| entry.key != key);
+        self.metadata.len() != len
+    }
 }
 
 /// Metadata entry (key-value pair)
@@ -127,49 +725,588 @@ pub struct MetadataEntry {
     /// Metadata key (max 32 bytes)
     pub key: String,
 
-    /// Metadata value (arbitrary bytes, max 256 bytes)
+    /// Metadata value (arbitrary bytes, max `RegistryConfig::max_value_length`)
     pub value: Vec<u8>,
 }
 
 impl MetadataEntry {
-    /// Maximum size per metadata entry
+    /// Space reserved per metadata slot
     /// 4 (key length) + 32 (key) + 4 (value length) + 256 (value)
-    pub const MAX_SIZE: usize = 4 + 32 + 4 + 256;
+    ///
+    /// Accounts reserve RESERVED_VALUE_LENGTH value bytes per slot whatever
+    /// limit the deployment configures. Longer values (up to MAX_VALUE_LENGTH)
+    /// use the slack of other slots; an agent that runs out of room grows its
+    /// account with `resize_agent_metadata`.
+    pub const SLOT_SIZE: usize = 4 + 32 + 4 + Self::RESERVED_VALUE_LENGTH;
+
+    /// Value bytes reserved per slot (the default `max_value_length`)
+    pub const RESERVED_VALUE_LENGTH: usize = 256;
 
     /// Maximum key length in bytes
     pub const MAX_KEY_LENGTH: usize = 32;
 
-    /// Maximum value length in bytes
-    pub const MAX_VALUE_LENGTH: usize = 256;
+    /// Hard cap on value length in bytes (what accounts are sized for)
+    /// Deployments choose their limit up to this with `set_max_value_length`
+    pub const MAX_VALUE_LENGTH: usize = 512;
+
+    /// Default value length limit for new registries (ERC-8004 adaptation)
+    pub const DEFAULT_VALUE_LENGTH: u16 = 256;
+
+    /// Serialized size of this entry
+    pub fn size(&self) -> usize {
+        4 + self.key.len() + 4 + self.value.len()
+    }
+
+    /// Whether `entries` fit in `slots` metadata slots: at most one entry per
+    /// slot, and no more bytes than the slots reserve together
+    pub fn fit_in<'a>(entries: impl IntoIterator<Item = &'a MetadataEntry>, slots: usize) -> bool {
+        let (count, bytes) = entries
+            .into_iter()
+            .fold((0, 0), |(count, bytes), entry| (count + 1, bytes + entry.size()));
+        count <= slots && bytes <= slots * Self::SLOT_SIZE
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_count_source_discriminators() {
+        use anchor_lang::solana_program::hash::hash;
+
+        assert_eq!(
+            hash(b"account:AgentReputationMetadata").to_bytes()[..8],
+            AgentAccount::AGENT_REPUTATION_DISCRIMINATOR
+        );
+        assert_eq!(
+            hash(b"account:AgentValidationMetadata").to_bytes()[..8],
+            AgentAccount::AGENT_VALIDATION_DISCRIMINATOR
+        );
+    }
+
+    #[test]
+    fn test_count_source_parsing() {
+        let mut reputation = AgentAccount::AGENT_REPUTATION_DISCRIMINATOR.to_vec();
+        reputation.extend_from_slice(&7u64.to_le_bytes());
+        reputation.extend_from_slice(&42u64.to_le_bytes());
+        assert_eq!(AgentAccount::feedback_count_from_data(&reputation, 7), Some(42));
+        assert_eq!(AgentAccount::feedback_count_from_data(&reputation, 8), None);
+        assert_eq!(AgentAccount::feedback_count_from_data(&reputation[..23], 7), None);
+
+        let mut validation = AgentAccount::AGENT_VALIDATION_DISCRIMINATOR.to_vec();
+        validation.extend_from_slice(&7u64.to_le_bytes());
+        validation.extend_from_slice(&3u64.to_le_bytes());
+        assert_eq!(AgentAccount::validation_count_from_data(&validation, 7), Some(3));
+        assert_eq!(AgentAccount::validation_count_from_data(&validation, 8), None);
+        assert_eq!(AgentAccount::validation_count_from_data(&validation[..23], 7), None);
+        // Right agent id, wrong account type
+        assert_eq!(AgentAccount::validation_count_from_data(&reputation, 7), None);
+    }
+
+    #[test]
+    fn test_pda_authority_must_be_off_curve() {
+        let config = RegistryConfig {
+            authority: Pubkey::new_unique(),
+            next_agent_id: 0,
+            total_agents: 0,
+            collection_mint: Pubkey::new_unique(),
+            max_value_length: MetadataEntry::DEFAULT_VALUE_LENGTH,
+            paused: false,
+            default_seller_fee_bps: 0,
+            verify_collection: true,
+            max_uri_length: AgentAccount::DEFAULT_URI_LENGTH,
+            validation_registry: DEFAULT_VALIDATION_REGISTRY_ID,
+            bump: 255,
+        };
+
+        let (governance_pda, _) =
+            Pubkey::find_program_address(&[b"governance"], &Pubkey::new_unique());
+        assert!(config.is_valid_pda_authority(&governance_pda));
+
+        // Ed25519 base point: a regular (on-curve) wallet key
+        let mut wallet = [0x66u8; 32];
+        wallet[0] = 0x58;
+        assert!(!config.is_valid_pda_authority(&Pubkey::new_from_array(wallet)));
+    }
+
+    #[test]
+    fn test_collection_view_strips_padding() {
+        let mint = Pubkey::new_unique();
+        let mut name = String::from("ERC-8004 Agents");
+        name.extend(std::iter::repeat_n('\0', 17));
+
+        let view = CollectionView::new(mint, &name, "https://example.com/c.json\0\0");
+        assert_eq!(view.collection_mint, mint);
+        assert_eq!(view.name, "ERC-8004 Agents");
+        assert_eq!(view.uri, "https://example.com/c.json");
+    }
+
     #[test]
     fn test_registry_config_size() {
-        assert_eq!(RegistryConfig::SIZE, 81);
+        assert_eq!(RegistryConfig::SIZE, 121);
+    }
+
+    #[test]
+    fn test_seller_fee_bounds() {
+        assert!(RegistryConfig::is_valid_seller_fee_bps(0));
+        assert!(RegistryConfig::is_valid_seller_fee_bps(500));
+        assert!(RegistryConfig::is_valid_seller_fee_bps(10_000));
+        assert!(!RegistryConfig::is_valid_seller_fee_bps(10_001));
     }
 
     #[test]
     fn test_metadata_entry_size() {
-        assert_eq!(MetadataEntry::MAX_SIZE, 296);
+        assert_eq!(MetadataEntry::SLOT_SIZE, 296);
+    }
+
+    #[test]
+    fn test_max_value_length_bounds() {
+        assert!(!RegistryConfig::is_valid_max_value_length(0));
+        assert!(RegistryConfig::is_valid_max_value_length(MetadataEntry::DEFAULT_VALUE_LENGTH));
+        assert!(RegistryConfig::is_valid_max_value_length(512));
+        assert!(!RegistryConfig::is_valid_max_value_length(513));
+
+        let config = RegistryConfig {
+            authority: Pubkey::default(),
+            next_agent_id: 0,
+            total_agents: 0,
+            collection_mint: Pubkey::default(),
+            max_value_length: 300,
+            paused: false,
+            default_seller_fee_bps: 0,
+            verify_collection: true,
+            max_uri_length: AgentAccount::DEFAULT_URI_LENGTH,
+            validation_registry: DEFAULT_VALIDATION_REGISTRY_ID,
+            bump: 0,
+        };
+        assert!(config.accepts_value_length(300));
+        assert!(!config.accepts_value_length(301));
+    }
+
+    #[test]
+    fn test_max_uri_length_bounds() {
+        assert!(!RegistryConfig::is_valid_max_uri_length(0));
+        assert!(RegistryConfig::is_valid_max_uri_length(AgentAccount::DEFAULT_URI_LENGTH));
+        assert!(RegistryConfig::is_valid_max_uri_length(512));
+        assert!(!RegistryConfig::is_valid_max_uri_length(513));
+
+        let config = RegistryConfig {
+            authority: Pubkey::default(),
+            next_agent_id: 0,
+            total_agents: 0,
+            collection_mint: Pubkey::default(),
+            max_value_length: MetadataEntry::DEFAULT_VALUE_LENGTH,
+            paused: false,
+            default_seller_fee_bps: 0,
+            verify_collection: true,
+            max_uri_length: 300,
+            validation_registry: DEFAULT_VALIDATION_REGISTRY_ID,
+            bump: 0,
+        };
+        assert!(config.accepts_uri_length(250));
+        assert!(config.accepts_uri_length(300));
+        assert!(!config.accepts_uri_length(301));
     }
 
     #[test]
     fn test_agent_account_max_size() {
         // Should be under 10KB for reasonable rent costs
-        assert!(AgentAccount::MAX_SIZE < 10240);
+        const { assert!(AgentAccount::MAX_SIZE < 10240) };
         // Actual expected size
-        assert_eq!(AgentAccount::MAX_SIZE, 3257);
+        // 10 slots of 256 value bytes, 200-byte URI
+        assert_eq!(AgentAccount::MAX_SIZE, 3457);
+    }
+
+    #[test]
+    fn test_agent_metadata_capacity() {
+        // Base account holds the default 10 entries
+        assert_eq!(AgentAccount::space_for(0, 10), 8 + AgentAccount::MAX_SIZE);
+        assert_eq!(AgentAccount::space_for(200, 0), 8 + AgentAccount::MAX_SIZE);
+        assert_eq!(AgentAccount::metadata_capacity(8 + AgentAccount::MAX_SIZE, 0), 10);
+
+        // Capacity round-trips through the account size, whatever the URI
+        for uri_len in [0, 200, 201, AgentAccount::MAX_URI_LENGTH] {
+            for entries in 10..=AgentAccount::MAX_RESIZED_METADATA_ENTRIES {
+                assert_eq!(
+                    AgentAccount::metadata_capacity(AgentAccount::space_for(uri_len, entries), uri_len),
+                    entries
+                );
+            }
+        }
+        assert_eq!(AgentAccount::space_for(0, 20), 8 + 3457 + 10 * 296);
+
+        // Only the URI excess over the reserve is paid for
+        assert_eq!(AgentAccount::space_for(512, 10), 8 + AgentAccount::MAX_SIZE + 312);
+        // Accounts sized under the former 512-byte URI and value reserves turn
+        // their extra room into slots
+        assert_eq!(AgentAccount::metadata_capacity(8 + 6329, 0), 19);
+
+        // Largest resize fits a single realloc
+        const {
+            assert!(
+                (AgentAccount::MAX_RESIZED_METADATA_ENTRIES - AgentAccount::MAX_METADATA_ENTRIES)
+                    * MetadataEntry::SLOT_SIZE
+                    <= 10240
+            )
+        };
+    }
+
+    fn test_agent(owner: Pubkey, agent_mint: Pubkey) -> AgentAccount {
+        AgentAccount {
+            agent_id: 0,
+            owner,
+            agent_mint,
+            category: AgentCategory::UNCATEGORIZED,
+            operator_type: OperatorType::Autonomous as u8,
+            status: AgentStatus::Active as u8,
+            successor_agent: None,
+            token_uri: String::new(),
+            nft_name: String::new(),
+            nft_symbol: String::new(),
+            metadata: Vec::new(),
+            extension_count: 0,
+            cached_feedback_count: 0,
+            cached_validation_count: 0,
+            pending_owner: None,
+            metadata_delegate: None,
+            approved: None,
+            created_at: 0,
+            updated_at: 0,
+            history_len: 0,
+            frozen: false,
+            import_nonce: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn test_metadata_fit_in_slots() {
+        let entry = |value_len: usize| MetadataEntry {
+            key: "k".repeat(MetadataEntry::MAX_KEY_LENGTH),
+            value: vec![0; value_len],
+        };
+        assert_eq!(entry(MetadataEntry::RESERVED_VALUE_LENGTH).size(), MetadataEntry::SLOT_SIZE);
+
+        // Full-size entries fill one slot each
+        let full = vec![entry(MetadataEntry::RESERVED_VALUE_LENGTH); 10];
+        assert!(MetadataEntry::fit_in(&full, 10));
+        assert!(!MetadataEntry::fit_in(&full, 9));
+
+        // A long value borrows the slack of short ones...
+        let mut mixed = vec![entry(0); 9];
+        mixed.push(entry(MetadataEntry::MAX_VALUE_LENGTH));
+        assert!(MetadataEntry::fit_in(&mixed, 10));
+
+        // ...but not beyond the bytes the slots reserve together
+        let long = vec![entry(MetadataEntry::MAX_VALUE_LENGTH); 10];
+        assert!(!MetadataEntry::fit_in(&long, 10));
+        assert!(MetadataEntry::fit_in(&long, 19));
+    }
+
+    #[test]
+    fn test_category_fixed_offset() {
+        let mut agent = test_agent(Pubkey::new_unique(), Pubkey::new_unique());
+        agent.category = 3; // Gaming
+        agent.token_uri = "ipfs://QmVariableLength".to_string();
+
+        let mut data = Vec::new();
+        agent.try_serialize(&mut data).unwrap();
+
+        let offset = AgentAccount::CATEGORY_OFFSET;
+        assert_eq!(&data[offset..offset + 2], &3u16.to_le_bytes());
+    }
+
+    #[test]
+    fn test_agent_long_uri_fits_its_space() {
+        let mut agent = test_agent(Pubkey::new_unique(), Pubkey::new_unique());
+        agent.token_uri = "x".repeat(AgentAccount::MAX_URI_LENGTH);
+        agent.nft_name = "x".repeat(32);
+        agent.nft_symbol = "x".repeat(10);
+        agent.successor_agent = Some(1);
+        agent.pending_owner = Some(Pubkey::new_unique());
+        agent.metadata_delegate = Some(Pubkey::new_unique());
+        agent.approved = Some(Pubkey::new_unique());
+        agent.metadata = (0..AgentAccount::MAX_METADATA_ENTRIES)
+            .map(|i| MetadataEntry {
+                key: format!("{:0>32}", i),
+                value: vec![0; MetadataEntry::RESERVED_VALUE_LENGTH],
+            })
+            .collect();
+
+        let mut data = Vec::new();
+        agent.try_serialize(&mut data).unwrap();
+        assert!(
+            data.len()
+                <= AgentAccount::space_for(agent.token_uri.len(), AgentAccount::MAX_METADATA_ENTRIES)
+        );
+    }
+
+    #[test]
+    fn test_category_validation() {
+        assert!(AgentCategory::is_valid(AgentCategory::UNCATEGORIZED));
+        assert!(AgentCategory::is_valid(AgentCategory::MAX_STANDARD));
+        assert!(!AgentCategory::is_valid(AgentCategory::MAX_STANDARD + 1));
+        assert!(!AgentCategory::is_valid(999));
+        assert!(AgentCategory::is_valid(AgentCategory::CUSTOM_START));
+        assert!(AgentCategory::is_valid(u16::MAX));
+    }
+
+    #[test]
+    fn test_operator_type() {
+        assert_eq!(OperatorType::from_u8(0), Some(OperatorType::Autonomous));
+        assert_eq!(OperatorType::from_u8(1), Some(OperatorType::HumanOperated));
+        assert_eq!(OperatorType::from_u8(2), Some(OperatorType::Hybrid));
+        assert_eq!(OperatorType::from_u8(3), None);
+
+        let mut agent = test_agent(Pubkey::new_unique(), Pubkey::new_unique());
+        agent.category = u16::MAX;
+        agent.operator_type = OperatorType::Hybrid as u8;
+
+        let mut data = Vec::new();
+        agent.try_serialize(&mut data).unwrap();
+        assert_eq!(data[AgentAccount::OPERATOR_TYPE_OFFSET], 2);
+    }
+
+    #[test]
+    fn test_extension_cap() {
+        let mut agent = test_agent(Pubkey::new_unique(), Pubkey::new_unique());
+        assert!(agent.can_create_extension(0));
+        assert!(agent.can_create_extension(AgentAccount::MAX_EXTENSIONS - 1));
+        assert!(!agent.can_create_extension(AgentAccount::MAX_EXTENSIONS));
+        assert!(!agent.can_create_extension(u8::MAX));
+
+        agent.extension_count = AgentAccount::MAX_EXTENSIONS;
+        assert!(!agent.can_create_extension(0));
+    }
+
+    #[test]
+    fn test_deprecated_status() {
+        let mut agent = test_agent(Pubkey::new_unique(), Pubkey::new_unique());
+        assert!(!agent.is_deprecated());
+
+        agent.status = AgentStatus::Deprecated as u8;
+        agent.successor_agent = Some(42);
+        assert!(agent.is_deprecated());
+
+        let mut data = Vec::new();
+        agent.try_serialize(&mut data).unwrap();
+        let offset = AgentAccount::STATUS_OFFSET;
+        assert_eq!(data[offset], AgentStatus::Deprecated as u8);
+        // Option<u64> right after status: tag 1 + agent id
+        assert_eq!(data[offset + 1], 1);
+        assert_eq!(&data[offset + 2..offset + 10], &42u64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_operator_attestation_goes_stale_on_type_change() {
+        assert_eq!(OperatorAttestation::SIZE, 50);
+
+        let mut agent = test_agent(Pubkey::new_unique(), Pubkey::new_unique());
+        agent.operator_type = OperatorType::HumanOperated as u8;
+
+        let attestation = OperatorAttestation {
+            agent_id: agent.agent_id,
+            operator_type: agent.operator_type,
+            attester: Pubkey::new_unique(),
+            attested_at: 0,
+            bump: 0,
+        };
+        assert!(attestation.vouches_for(&agent));
+
+        agent.operator_type = OperatorType::Autonomous as u8;
+        assert!(!attestation.vouches_for(&agent));
+    }
+
+    #[test]
+    fn test_is_held_by_cached_owner() {
+        let owner = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let agent = test_agent(owner, mint);
+
+        assert!(agent.is_held_by(&mint, &owner, 1));
+    }
+
+    #[test]
+    fn test_is_held_by_detects_stale_owner() {
+        let owner = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let agent = test_agent(owner, mint);
+
+        // NFT moved out of the cached owner's token account
+        assert!(!agent.is_held_by(&mint, &owner, 0));
+        // NFT held by someone else (plain SPL transfer without sync_owner)
+        assert!(!agent.is_held_by(&mint, &Pubkey::new_unique(), 1));
+        // Token account for a different mint
+        assert!(!agent.is_held_by(&Pubkey::new_unique(), &owner, 1));
+    }
+
+    #[test]
+    fn test_metadata_delegate() {
+        let owner = Pubkey::new_unique();
+        let delegate = Pubkey::new_unique();
+        let mut agent = test_agent(owner, Pubkey::new_unique());
+
+        assert!(agent.can_edit_metadata(&owner));
+        assert!(!agent.can_edit_metadata(&delegate));
+
+        agent.metadata_delegate = Some(delegate);
+        assert!(agent.can_edit_metadata(&delegate));
+        assert!(!agent.can_edit_metadata(&Pubkey::new_unique()));
+
+        // New owner does not inherit the previous owner's delegate
+        let new_owner = Pubkey::new_unique();
+        agent.set_owner(new_owner);
+        assert!(agent.can_edit_metadata(&new_owner));
+        assert!(!agent.can_edit_metadata(&delegate));
+        assert_eq!(agent.metadata_delegate, None);
+    }
+
+    #[test]
+    fn test_transfer_approval() {
+        let mut agent = test_agent(Pubkey::new_unique(), Pubkey::new_unique());
+        assert!(!agent.pda_holds_update_authority());
+
+        agent.approved = Some(Pubkey::new_unique());
+        assert!(agent.pda_holds_update_authority());
+
+        // Approval does not survive a change of owner (ERC-721)
+        agent.set_owner(Pubkey::new_unique());
+        assert_eq!(agent.approved, None);
+        assert!(!agent.pda_holds_update_authority());
+
+        agent.pending_owner = Some(Pubkey::new_unique());
+        assert!(agent.pda_holds_update_authority());
+    }
+
+    #[test]
+    fn test_remove_metadata() {
+        let mut agent = test_agent(Pubkey::new_unique(), Pubkey::new_unique());
+        for key in ["a", "b", "c"] {
+            agent.metadata.push(MetadataEntry { key: key.to_string(), value: vec![1] });
+        }
+
+        assert!(agent.remove_metadata("b"));
+        assert!(!agent.remove_metadata("b"));
+        let keys: Vec<&str> = agent.metadata.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(keys, ["a", "c"]);
+    }
+
+    #[test]
+    fn test_metadata_lookup_at_capacity() {
+        let mut agent = test_agent(Pubkey::new_unique(), Pubkey::new_unique());
+        let capacity = AgentAccount::MAX_RESIZED_METADATA_ENTRIES;
+        // Shared prefixes and equal (max) lengths: the worst case for key comparison
+        let key = |i: usize| format!("agent.profile.field.{:012}", i);
+        for i in 0..capacity {
+            agent.metadata.push(MetadataEntry { key: key(i), value: vec![i as u8] });
+        }
+        assert!(MetadataEntry::fit_in(&agent.metadata, capacity));
+
+        for i in 0..capacity {
+            assert_eq!(agent.find_metadata(&key(i)).unwrap().value, vec![i as u8]);
+        }
+        assert!(agent.find_metadata(&key(capacity)).is_none());
+        assert!(agent.find_metadata("agent.profile.field").is_none());
+
+        let last = key(capacity - 1);
+        agent.find_metadata_mut(&last).unwrap().value = vec![0xff];
+        assert_eq!(agent.find_metadata(&last).unwrap().value, vec![0xff]);
+
+        // Insertion order is what get_all_metadata returns
+        assert!(agent.remove_metadata(&key(0)));
+        assert_eq!(agent.metadata[0].key, key(1));
+        assert!(agent.find_metadata(&key(0)).is_none());
+    }
+
+    #[test]
+    fn test_agent_id_index_size() {
+        assert_eq!(AgentIdIndex::SIZE, 41);
+    }
+
+    #[test]
+    fn test_registry_stats_daily_counts() {
+        assert_eq!(RegistryStats::SIZE, 489);
+
+        let mut stats = RegistryStats {
+            last_registration_ts: 0,
+            days: [DailyRegistrations::default(); RegistryStats::TRACKED_DAYS],
+            bump: 0,
+        };
+        let day = 20_000;
+        let midnight = day * RegistryStats::SECONDS_PER_DAY;
+
+        // Two registrations on one day, one right after midnight
+        stats.record_registration(midnight + 10);
+        stats.record_registration(midnight + RegistryStats::SECONDS_PER_DAY - 1);
+        stats.record_registration(midnight + RegistryStats::SECONDS_PER_DAY);
+        assert_eq!(stats.daily_registrations(day), 2);
+        assert_eq!(stats.daily_registrations(day + 1), 1);
+        assert_eq!(stats.daily_registrations(day - 1), 0);
+        assert_eq!(stats.last_registration_ts, midnight + RegistryStats::SECONDS_PER_DAY);
+
+        // 30 days later the slot is reused and the old day is forgotten
+        let later = day + RegistryStats::TRACKED_DAYS as i64;
+        stats.record_registration(later * RegistryStats::SECONDS_PER_DAY);
+        assert_eq!(stats.daily_registrations(later), 1);
+        assert_eq!(stats.daily_registrations(day), 0);
+        assert_eq!(stats.daily_registrations(day + 1), 1);
+    }
+
+    #[test]
+    fn test_ownership_history_seq() {
+        assert_eq!(OwnershipHistory::SIZE, 117);
+
+        let mut agent = test_agent(Pubkey::new_unique(), Pubkey::new_unique());
+        assert_eq!(agent.take_history_seq(), 0);
+        assert_eq!(agent.take_history_seq(), 1);
+        assert_eq!(agent.history_len, 2);
+    }
+
+    #[test]
+    fn test_owner_agent_list() {
+        assert_eq!(OwnerAgentList::SIZE, 550);
+
+        let owner = Pubkey::new_unique();
+        let mut list = OwnerAgentList {
+            owner: Pubkey::default(),
+            agent_ids: vec![],
+            truncated: false,
+            bump: 0,
+        };
+        list.init_if_new(owner, 253);
+        list.init_if_new(Pubkey::new_unique(), 1);
+        assert_eq!(list.owner, owner);
+        assert_eq!(list.bump, 253);
+
+        assert!(list.add(1));
+        assert!(list.add(2));
+        assert!(list.add(1));
+        assert_eq!(list.agent_ids, [1, 2]);
+
+        assert!(list.remove(1));
+        assert!(!list.remove(1));
+        assert_eq!(list.agent_ids, [2]);
+
+        for id in 10..10 + OwnerAgentList::MAX_AGENTS as u64 - 1 {
+            assert!(list.add(id));
+        }
+        assert!(!list.truncated);
+        assert!(!list.add(1_000));
+        assert!(list.truncated);
+        assert_eq!(list.agent_ids.len(), OwnerAgentList::MAX_AGENTS);
+
+        // A full list still fits the allocated space
+        let mut data = Vec::new();
+        list.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), 8 + OwnerAgentList::SIZE);
     }
 
     #[test]
     fn test_metadata_extension_max_size() {
         // Should be under 10KB for reasonable rent costs
-        assert!(MetadataExtension::MAX_SIZE < 10240);
-        // Actual expected size: 8 + 32 + 1 + 4 + (10 * 296) + 1 = 3006
-        assert_eq!(MetadataExtension::MAX_SIZE, 3006);
+        const { assert!(MetadataExtension::MAX_SIZE < 10240) };
+        // Actual expected size: 8 + 32 + 8 + 1 + 4 + (10 * SLOT_SIZE) + 1 = 3014
+        assert_eq!(MetadataExtension::MAX_SIZE, 3014);
     }
 }
@@ -0,0 +1,139 @@
+//! Read-only view surface (`views` feature)
+//!
+//! Stable, versioned return-data encodings for CPI callers and simulating
+//! clients. Each view is Borsh-encoded via `set_return_data` by Anchor and
+//! starts with `version`; fields are only ever appended, so a decoder for
+//! version N can read any later version's prefix.
+
+use anchor_lang::prelude::*;
+
+use crate::state::{AgentAccount, RegistryConfig};
+
+/// Encoding version of every view in this module
+pub const VIEWS_VERSION: u8 = 1;
+
+/// `get_agent` return data
+///
+/// Metadata values are left out to stay within the 1024-byte return data
+/// limit; read them with `get_metadata` / `get_metadata_extended`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct AgentView {
+    pub version: u8,
+    pub agent_id: u64,
+    pub owner: Pubkey,
+    pub agent_mint: Pubkey,
+    pub category: u16,
+    pub operator_type: u8,
+    pub status: u8,
+    pub successor_agent: Option<u64>,
+    pub token_uri: String,
+    pub metadata_count: u8,
+    pub extension_count: u8,
+    pub cached_feedback_count: u64,
+    pub cached_validation_count: u64,
+    pub created_at: i64,
+}
+
+impl From<&AgentAccount> for AgentView {
+    fn from(agent: &AgentAccount) -> Self {
+        Self {
+            version: VIEWS_VERSION,
+            agent_id: agent.agent_id,
+            owner: agent.owner,
+            agent_mint: agent.agent_mint,
+            category: agent.category,
+            operator_type: agent.operator_type,
+            status: agent.status,
+            successor_agent: agent.successor_agent,
+            token_uri: agent.token_uri.clone(),
+            metadata_count: agent.metadata.len() as u8,
+            extension_count: agent.extension_count,
+            cached_feedback_count: agent.cached_feedback_count,
+            cached_validation_count: agent.cached_validation_count,
+            created_at: agent.created_at,
+        }
+    }
+}
+
+/// `get_config` return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct RegistryConfigView {
+    pub version: u8,
+    pub authority: Pubkey,
+    pub next_agent_id: u64,
+    pub total_agents: u64,
+    pub collection_mint: Pubkey,
+    pub max_value_length: u16,
+    pub max_uri_length: u16,
+    pub validation_registry: Pubkey,
+}
+
+impl From<&RegistryConfig> for RegistryConfigView {
+    fn from(config: &RegistryConfig) -> Self {
+        Self {
+            version: VIEWS_VERSION,
+            authority: config.authority,
+            next_agent_id: config.next_agent_id,
+            total_agents: config.total_agents,
+            collection_mint: config.collection_mint,
+            max_value_length: config.max_value_length,
+            max_uri_length: config.max_uri_length,
+            validation_registry: config.validation_registry,
+        }
+    }
+}
+
+#[derive(Accounts)]
+pub struct GetAgent<'info> {
+    #[account(
+        seeds = [b"agent", agent_account.agent_mint.as_ref()],
+        bump = agent_account.bump
+    )]
+    pub agent_account: Account<'info, AgentAccount>,
+}
+
+#[derive(Accounts)]
+pub struct GetConfig<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, RegistryConfig>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_agent_view_fits_return_data() {
+        let agent = AgentAccount {
+            agent_id: 1,
+            owner: Pubkey::new_unique(),
+            agent_mint: Pubkey::new_unique(),
+            category: 0,
+            operator_type: 0,
+            status: 0,
+            successor_agent: Some(2),
+            token_uri: "x".repeat(AgentAccount::MAX_URI_LENGTH),
+            nft_name: String::new(),
+            nft_symbol: String::new(),
+            metadata: Vec::new(),
+            extension_count: AgentAccount::MAX_EXTENSIONS,
+            cached_feedback_count: 0,
+            cached_validation_count: 0,
+            pending_owner: Some(Pubkey::new_unique()),
+            metadata_delegate: Some(Pubkey::new_unique()),
+            approved: Some(Pubkey::new_unique()),
+            created_at: 0,
+            updated_at: 0,
+            history_len: 0,
+            frozen: false,
+            import_nonce: 0,
+            bump: 255,
+        };
+
+        // Largest encoding stays under the 1024-byte return data limit
+        let encoded = AgentView::from(&agent).try_to_vec().unwrap();
+        assert_eq!(encoded[0], VIEWS_VERSION);
+        assert!(encoded.len() <= 1024);
+        assert_eq!(AgentView::try_from_slice(&encoded).unwrap(), AgentView::from(&agent));
+    }
+}
@@ -0,0 +1,111 @@
+//! Mock governance program (tests only)
+//!
+//! Owns the `[b"governance"]` PDA that `set_pda_authority` hands the Identity
+//! Registry to, and signs the registry's authority-gated instructions with it
+//! via CPI, like a real governance program would after a passed proposal.
+
+// Anchor's generated IDL handlers still call the deprecated `AccountInfo::realloc`
+#![allow(deprecated)]
+
+use anchor_lang::prelude::*;
+use erc8004_solana::cpi::accounts::{UpdateCollectionAuthority, UpdateConfig};
+
+declare_id!("7auQ1fZZzmfKTR7KnvKnPABWqCTYzubeCgDJYdJAXNqJ");
+
+/// Seed of the PDA acting as registry authority
+pub const GOVERNANCE_SEED: &[u8] = b"governance";
+
+#[program]
+pub mod mock_governance {
+    use super::*;
+
+    /// Pause or resume registrations, signed by the governance PDA
+    pub fn set_paused(ctx: Context<GovernedConfig>, paused: bool) -> Result<()> {
+        let seeds: &[&[u8]] = &[GOVERNANCE_SEED, &[ctx.bumps.governance]];
+        erc8004_solana::cpi::set_paused(
+            CpiContext::new_with_signer(
+                ctx.accounts.identity_program.to_account_info(),
+                UpdateConfig {
+                    config: ctx.accounts.config.to_account_info(),
+                    authority: ctx.accounts.governance.to_account_info(),
+                },
+                &[seeds],
+            ),
+            paused,
+        )
+    }
+
+    /// Hand the registry (config and collection update authority) to `new_authority`
+    pub fn hand_back(ctx: Context<HandBack>, new_authority: Pubkey) -> Result<()> {
+        let seeds: &[&[u8]] = &[GOVERNANCE_SEED, &[ctx.bumps.governance]];
+        erc8004_solana::cpi::transfer_authority(
+            CpiContext::new_with_signer(
+                ctx.accounts.identity_program.to_account_info(),
+                UpdateConfig {
+                    config: ctx.accounts.config.to_account_info(),
+                    authority: ctx.accounts.governance.to_account_info(),
+                },
+                &[seeds],
+            ),
+            new_authority,
+        )?;
+        erc8004_solana::cpi::update_collection_authority(CpiContext::new_with_signer(
+            ctx.accounts.identity_program.to_account_info(),
+            UpdateCollectionAuthority {
+                config: ctx.accounts.config.to_account_info(),
+                collection_authority: ctx.accounts.governance.to_account_info(),
+                collection_mint: ctx.accounts.collection_mint.to_account_info(),
+                collection_metadata: ctx.accounts.collection_metadata.to_account_info(),
+                token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                sysvar_instructions: ctx.accounts.sysvar_instructions.to_account_info(),
+            },
+            &[seeds],
+        ))
+    }
+}
+
+#[derive(Accounts)]
+pub struct GovernedConfig<'info> {
+    /// CHECK: Identity Registry config, checked by the registry
+    #[account(mut)]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: Signs via invoke_signed only
+    #[account(seeds = [GOVERNANCE_SEED], bump)]
+    pub governance: UncheckedAccount<'info>,
+
+    /// CHECK: Identity Registry program (address differs per cluster)
+    #[account(executable)]
+    pub identity_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct HandBack<'info> {
+    /// CHECK: Identity Registry config, checked by the registry
+    #[account(mut)]
+    pub config: UncheckedAccount<'info>,
+
+    /// CHECK: Signs via invoke_signed only; current collection update authority
+    #[account(mut, seeds = [GOVERNANCE_SEED], bump)]
+    pub governance: UncheckedAccount<'info>,
+
+    /// CHECK: Collection mint, checked by the registry
+    pub collection_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Collection metadata PDA, checked by the registry
+    #[account(mut)]
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Metaplex Token Metadata program, checked by the registry
+    pub token_metadata_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Instructions sysvar, checked by the registry
+    pub sysvar_instructions: UncheckedAccount<'info>,
+
+    /// CHECK: Identity Registry program (address differs per cluster)
+    #[account(executable)]
+    pub identity_program: UncheckedAccount<'info>,
+}
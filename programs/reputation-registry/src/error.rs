@@ -5,10 +5,10 @@ pub enum ReputationError {
     #[msg("Score must be between 0 and 100")]
     InvalidScore,
 
-    #[msg("File URI exceeds maximum length of 200 bytes")]
+    #[msg("File URI exceeds the registry's maximum URI length")]
     UriTooLong,
 
-    #[msg("Response URI exceeds maximum length of 200 bytes")]
+    #[msg("Response URI exceeds the registry's maximum URI length")]
     ResponseUriTooLong,
 
     #[msg("Only feedback author can revoke")]
@@ -47,4 +47,91 @@ pub enum ReputationError {
 
     #[msg("FeedbackAuth signer is not agent owner")]
     UnauthorizedSigner,
+
+    #[msg("Score scale must be greater than 0")]
+    InvalidScoreScale,
+
+    #[msg("Only the registry authority can perform this action")]
+    UnauthorizedAuthority,
+
+    #[msg("Too many accounts passed to a batch instruction")]
+    BatchTooLarge,
+
+    #[msg("Feedback account does not belong to this agent or was passed twice")]
+    InvalidFeedbackAccount,
+
+    #[msg("Reputation account passed twice")]
+    InvalidReputationAccount,
+
+    #[msg("A reputation attestation was already minted for this agent recently")]
+    AttestationRateLimited,
+
+    #[msg("Feedback edit window has closed")]
+    EditWindowClosed,
+
+    #[msg("Edit window must not be negative")]
+    InvalidEditWindow,
+
+    #[msg("Reputation policy is not supported by this registry")]
+    UnsupportedReputationPolicy,
+
+    #[msg("Policy mask must enable the mean policy and only known policies")]
+    InvalidPolicyMask,
+
+    #[msg("Client wallet balance is below the registry minimum for feedback")]
+    InsufficientClientBalance,
+
+    #[msg("Feedback must be revoked before it can be closed")]
+    FeedbackNotRevoked,
+
+    #[msg("Identity Registry program does not match the configured one")]
+    InvalidIdentityRegistry,
+
+    #[msg("Tag reputation account required for a feedback with tag2 set")]
+    MissingTagReputation,
+
+    #[msg("Half-life must be greater than 0")]
+    InvalidHalfLife,
+
+    #[msg("Feedback already disputed")]
+    AlreadyDisputed,
+
+    #[msg("Too many tags (max 8 per feedback)")]
+    TooManyTags,
+
+    #[msg("Response already revoked")]
+    ResponseAlreadyRevoked,
+
+    #[msg("Feedback weight must be at most 10000 basis points")]
+    InvalidTrustWeight,
+
+    #[msg("Account is not this agent's Validation Registry metadata")]
+    InvalidValidationMetadata,
+
+    #[msg("Agent owner cannot give feedback to their own agent")]
+    SelfFeedbackNotAllowed,
+
+    #[msg("Client is blocked from giving feedback to this agent")]
+    ClientBlocked,
+
+    #[msg("Treasury account required to collect the feedback fee")]
+    MissingTreasury,
+
+    #[msg("Treasury does not match the configured one")]
+    InvalidTreasury,
+
+    #[msg("Tag reputation account does not belong to the feedback's tag")]
+    InvalidTagReputation,
+
+    #[msg("Max URI length must be between 1 and 512 bytes")]
+    InvalidMaxUriLength,
+
+    #[msg("Every live feedback account of the agent must be passed")]
+    IncompleteFeedbackSet,
+
+    #[msg("Feedback fee must be at least the rent-exempt minimum of an empty account")]
+    FeedbackFeeBelowRent,
+
+    #[msg("Score histogram account required for feedback counted in it")]
+    MissingScoreHistogram,
 }
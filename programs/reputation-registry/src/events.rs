@@ -11,6 +11,17 @@ pub struct NewFeedback {
     pub tag2: [u8; 32],
     pub file_uri: String,
     pub file_hash: [u8; 32],
+    pub file_size: u64,
+}
+
+/// Event emitted alongside `NewFeedback` when a feedback carries extra tags
+#[event]
+pub struct NewFeedbackTags {
+    pub agent_id: u64,
+    pub client_address: Pubkey,
+    pub feedback_index: u64,
+    /// tag1, tag2, then the extra tags
+    pub tags: Vec<[u8; 32]>,
 }
 
 /// Event emitted when feedback is revoked
@@ -21,6 +32,67 @@ pub struct FeedbackRevoked {
     pub feedback_index: u64,
 }
 
+/// Event emitted when a revocation leaves an agent without non-revoked
+/// feedback and its score aggregates are reset to zero
+#[event]
+pub struct ReputationEmpty {
+    pub agent_id: u64,
+}
+
+/// Event emitted when feedback is updated in place by its author
+#[event]
+pub struct FeedbackUpdated {
+    pub agent_id: u64,
+    pub client_address: Pubkey,
+    pub feedback_index: u64,
+    pub old_score: u8,
+    pub new_score: u8,
+    pub file_uri: String,
+    pub file_hash: [u8; 32],
+}
+
+/// Event emitted when a revoked feedback account is closed
+#[event]
+pub struct FeedbackClosed {
+    pub agent_id: u64,
+    pub client_address: Pubkey,
+    pub feedback_index: u64,
+}
+
+/// Event emitted when the agent owner disputes a feedback
+#[event]
+pub struct FeedbackDisputed {
+    pub agent_id: u64,
+    pub client_address: Pubkey,
+    pub feedback_index: u64,
+    pub disputer: Pubkey,
+}
+
+/// Event emitted when the agent owner blocks a client from giving feedback
+#[event]
+pub struct ClientBlockAdded {
+    pub agent_id: u64,
+    pub client: Pubkey,
+    pub blocked_by: Pubkey,
+}
+
+/// Event emitted when the agent owner lifts a client block
+#[event]
+pub struct ClientBlockRemoved {
+    pub agent_id: u64,
+    pub client: Pubkey,
+    pub unblocked_by: Pubkey,
+}
+
+/// Event emitted when an agent owner selects a reputation policy
+#[event]
+pub struct ReputationPolicySet {
+    pub agent_id: u64,
+    pub policy: u8,
+    pub average_score: u8,
+    pub set_by: Pubkey,
+}
+
 /// Event emitted when response is appended to feedback
 #[event]
 pub struct ResponseAppended {
@@ -30,4 +102,55 @@ pub struct ResponseAppended {
     pub response_index: u64,
     pub responder: Pubkey,
     pub response_uri: String,
+    pub file_size: u64,
+}
+
+/// Event emitted when a response is revoked by its responder
+#[event]
+pub struct ResponseRevoked {
+    pub agent_id: u64,
+    pub client_address: Pubkey,
+    pub feedback_index: u64,
+    pub response_index: u64,
+    pub responder: Pubkey,
+}
+
+/// Event emitted when a response account is closed by its responder
+#[event]
+pub struct ResponseClosed {
+    pub agent_id: u64,
+    pub client_address: Pubkey,
+    pub feedback_index: u64,
+    pub response_index: u64,
+    pub responder: Pubkey,
+}
+
+/// Event emitted when a leaderboard snapshot is taken
+#[event]
+pub struct LeaderboardSnapshotted {
+    pub epoch: u64,
+    /// Ranked agent ids, best first
+    pub agent_ids: Vec<u64>,
+    pub agents_considered: u16,
+}
+
+/// Event emitted when an agent's reputation aggregate is recomputed from its feedback
+#[event]
+pub struct ReputationRecomputed {
+    pub agent_id: u64,
+    pub total_feedbacks: u64,
+    pub average_score: u8,
+    pub recomputed_by: Pubkey,
+}
+
+/// Event emitted when a reputation attestation NFT is minted
+#[event]
+pub struct ReputationAttestationMinted {
+    pub agent_id: u64,
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub average_score: u8,
+    pub total_feedbacks: u64,
+    pub snapshot_at: i64,
+    pub uri: String,
 }
@@ -1,55 +1,277 @@
+// Anchor's generated IDL handlers still call the deprecated `AccountInfo::realloc`
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    metadata::Metadata,
+    token::{self, FreezeAccount, Mint, MintTo, Token, TokenAccount},
+};
+use mpl_token_metadata::{
+    instructions::CreateV1CpiBuilder,
+    types::{PrintSupply, TokenStandard},
+};
 
 declare_id!("9WcFLL3Fsqs96JxuewEt9iqRwULtCZEsPT717hPbsQAa");
 
 pub mod error;
 pub mod events;
 pub mod state;
+#[cfg(feature = "views")]
+pub mod views;
 
 use error::*;
 use events::*;
 use state::*;
+#[cfg(feature = "views")]
+use views::*;
 
 #[program]
+#[allow(clippy::too_many_arguments)]
 pub mod reputation_registry {
     use super::*;
 
-    /// Initialize placeholder - reputation registry doesn't require initialization
-    /// (relies on Identity Registry for agent validation)
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+    /// Initialize the reputation registry configuration
+    ///
+    /// Creates the global ReputationConfig account with the default 0-100
+    /// score scale. Agent validation still relies on the Identity Registry,
+    /// whose program ID is recorded here: agent accounts owned by any other
+    /// program are rejected.
+    pub fn initialize(ctx: Context<Initialize>, identity_registry: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        config.authority = ctx.accounts.authority.key();
+        config.score_scale = ReputationConfig::DEFAULT_SCORE_SCALE;
+        config.require_live_agent = false;
+        config.edit_window = 0;
+        config.supported_policies = ReputationPolicy::ALL;
+        config.min_client_balance = 0;
+        config.identity_registry = identity_registry;
+        config.feedback_fee_lamports = 0;
+        config.treasury = Pubkey::default();
+        config.max_uri_length = ReputationConfig::DEFAULT_URI_LENGTH;
+        config.max_tracked_tags = ReputationConfig::DEFAULT_MAX_TRACKED_TAGS;
+        config.validation_registry = DEFAULT_VALIDATION_REGISTRY_ID;
+        config.bump = ctx.bumps.config;
+
         msg!("Reputation Registry: {:?}", ctx.program_id);
+        msg!("Identity Registry: {}", identity_registry);
+        Ok(())
+    }
+
+    /// Set the score scale accepted by give_feedback (e.g. 5 for star ratings)
+    ///
+    /// Only the registry authority can change the scale. Existing feedback keeps
+    /// the scale it was submitted under; aggregates are always normalized to 0-100,
+    /// so averages stay comparable across a scale change.
+    ///
+    /// # Errors
+    /// * `InvalidScoreScale` - Scale is zero
+    pub fn set_score_scale(ctx: Context<UpdateConfig>, score_scale: u8) -> Result<()> {
+        require!(score_scale > 0, ReputationError::InvalidScoreScale);
+
+        ctx.accounts.config.score_scale = score_scale;
+
+        msg!("Score scale set to 0-{}", score_scale);
+
+        Ok(())
+    }
+
+    /// Require append_response to prove the agent is still registered
+    ///
+    /// When enabled, append_response must be passed the agent's Identity Registry
+    /// account (with its mint and the Identity Registry program) and fails if the
+    /// agent no longer exists. Off by default so responses stay possible on
+    /// feedback for deregistered agents (historical audit).
+    pub fn set_require_live_agent(ctx: Context<UpdateConfig>, require_live_agent: bool) -> Result<()> {
+        ctx.accounts.config.require_live_agent = require_live_agent;
+
+        msg!("Require live agent for responses: {}", require_live_agent);
+
+        Ok(())
+    }
+
+    /// Set how long feedback stays mutable after it is given
+    ///
+    /// Past the window, revoke_feedback fails and the feedback is immutable,
+    /// so a client cannot rewrite history at the last minute of a dispute.
+    /// The window is measured from `feedback.created_at`; 0 means unlimited.
+    ///
+    /// # Errors
+    /// * `InvalidEditWindow` - Window is negative
+    pub fn set_edit_window(ctx: Context<UpdateConfig>, edit_window: i64) -> Result<()> {
+        require!(edit_window >= 0, ReputationError::InvalidEditWindow);
+
+        ctx.accounts.config.edit_window = edit_window;
+
+        msg!("Feedback edit window set to {}s", edit_window);
+
+        Ok(())
+    }
+
+    /// Set the minimum SOL balance a client wallet must hold to give feedback
+    ///
+    /// Anti-Sybil measure: raises the cost of spinning up throwaway reviewers.
+    /// Only the balance is checked, nothing is staked or locked. 0 disables it.
+    pub fn set_min_client_balance(ctx: Context<UpdateConfig>, min_client_balance: u64) -> Result<()> {
+        ctx.accounts.config.min_client_balance = min_client_balance;
+
+        msg!("Minimum client balance set to {} lamports", min_client_balance);
+
+        Ok(())
+    }
+
+    /// Set the fee charged per feedback and the treasury receiving it
+    ///
+    /// Anti-Sybil measure: every give_feedback transfers `feedback_fee_lamports`
+    /// from the payer to `treasury` (the agent owner's wallet or a registry
+    /// treasury). 0 keeps feedback free and the treasury account optional.
+    ///
+    /// The fee is a plain system transfer, so a non-zero fee must reach the
+    /// rent-exempt minimum of an empty account; otherwise the first transfer
+    /// to a treasury that doesn't exist yet would fail.
+    ///
+    /// # Errors
+    /// * `InvalidTreasury` - Non-zero fee without a treasury
+    /// * `FeedbackFeeBelowRent` - Non-zero fee below the rent-exempt minimum
+    pub fn set_feedback_fee(
+        ctx: Context<UpdateConfig>,
+        feedback_fee_lamports: u64,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        require!(
+            feedback_fee_lamports == 0 || treasury != Pubkey::default(),
+            ReputationError::InvalidTreasury
+        );
+        require!(
+            feedback_fee_lamports == 0
+                || feedback_fee_lamports >= Rent::get()?.minimum_balance(0),
+            ReputationError::FeedbackFeeBelowRent
+        );
+
+        let config = &mut ctx.accounts.config;
+        config.feedback_fee_lamports = feedback_fee_lamports;
+        config.treasury = treasury;
+
+        msg!("Feedback fee set to {} lamports (treasury {})", feedback_fee_lamports, treasury);
+
+        Ok(())
+    }
+
+    /// Set the maximum feedback and response URI length (e.g. for long signed
+    /// gateway URLs)
+    ///
+    /// Feedback and response accounts reserve 200 URI bytes; a longer URI
+    /// grows its account by the excess, paid by whoever creates or updates it.
+    /// Lowering the limit does not touch URIs that are already stored.
+    ///
+    /// # Errors
+    /// * `InvalidMaxUriLength` - Limit is 0 or above 512
+    pub fn set_max_uri_length(ctx: Context<UpdateConfig>, max_uri_length: u16) -> Result<()> {
+        require!(
+            ReputationConfig::is_valid_max_uri_length(max_uri_length),
+            ReputationError::InvalidMaxUriLength
+        );
+
+        ctx.accounts.config.max_uri_length = max_uri_length;
+
+        msg!("Max URI length set to {} bytes", max_uri_length);
+
+        Ok(())
+    }
+
+    /// Set how many distinct tags per agent get a `TagReputation` counter
+    ///
+    /// Past the cap, feedback with a new tag still counts toward the agent
+    /// aggregate and the histogram but creates no counter for that tag. Agents
+    /// already above a lowered cap keep their counters.
+    pub fn set_max_tracked_tags(ctx: Context<UpdateConfig>, max_tracked_tags: u16) -> Result<()> {
+        ctx.accounts.config.max_tracked_tags = max_tracked_tags;
+
+        msg!("Max tracked tags per agent set to {}", max_tracked_tags);
+
+        Ok(())
+    }
+
+    /// Set the Validation Registry program `get_trust_score` reads from
+    ///
+    /// Defaults to the devnet deployment; clusters where the Validation
+    /// Registry lives at another address (e.g. localnet) point it here.
+    pub fn set_validation_registry(
+        ctx: Context<UpdateConfig>,
+        validation_registry: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.config.validation_registry = validation_registry;
+
+        msg!("Validation Registry set to {}", validation_registry);
+
+        Ok(())
+    }
+
+    /// Set which reputation policies agents may select (bitmask, bit = 1 << policy)
+    ///
+    /// The mean policy must stay enabled. Agents that already selected a policy
+    /// being disabled keep it until they change it.
+    ///
+    /// # Errors
+    /// * `InvalidPolicyMask` - Mean disabled or unknown bits set
+    pub fn set_supported_policies(ctx: Context<UpdateConfig>, supported_policies: u8) -> Result<()> {
+        require!(
+            supported_policies & !ReputationPolicy::ALL == 0
+                && supported_policies & (1 << ReputationPolicy::Mean as u8) != 0,
+            ReputationError::InvalidPolicyMask
+        );
+
+        ctx.accounts.config.supported_policies = supported_policies;
+
+        msg!("Supported reputation policies: {:#05b}", supported_policies);
+
         Ok(())
     }
 
     /// Give feedback to an agent (ERC-8004 spec: giveFeedback)
     ///
-    /// Creates a new feedback entry for the specified agent with score 0-100,
-    /// tags, and file metadata. Uses client_index account to determine the
-    /// sequential feedback_index per client-agent pair and updates cached reputation stats.
+    /// Creates a new feedback entry for the specified agent with a score on the
+    /// registry's configured scale (0-100 by default), tags, and file metadata. Uses client_index account to determine the
+    /// sequential feedback_index per client-agent pair and updates cached reputation stats,
+    /// including the per-tag counters of tag1 (and of tag2 when set). A tag new to an
+    /// agent that already tracks `config.max_tracked_tags` tags gets no counter: its
+    /// freshly created account is closed again and the rent refunded to `payer`.
+    /// Account creation is paid by `payer`, which may be the client itself (self-paid)
+    /// or a sponsor that co-signs.
     ///
     /// # Arguments
     /// * `agent_id` - Agent ID from Identity Registry
-    /// * `score` - Rating 0-100 (validated on-chain)
+    /// * `score` - Rating 0..=config.score_scale (validated on-chain, normalized to 0-100 in aggregates)
     /// * `tag1` - Full bytes32 tag (ERC-8004 spec requirement)
     /// * `tag2` - Full bytes32 tag (ERC-8004 spec requirement)
-    /// * `file_uri` - IPFS/Arweave link (max 200 bytes)
+    /// * `file_uri` - IPFS/Arweave link (max `config.max_uri_length` bytes)
     /// * `file_hash` - SHA-256 hash of feedback file
     /// * `feedback_index` - Expected index (must match client_index.last_index)
     /// * `feedback_auth` - Signature-based authorization from agent owner (ERC-8004 spam prevention)
+    /// * `file_size` - Size of the feedback file in bytes (advisory, 0 if unknown)
     ///
     /// # Events
     /// * `NewFeedback` - Emitted when feedback is successfully created
     ///
     /// # Errors
-    /// * `InvalidScore` - Score not in range 0-100
-    /// * `UriTooLong` - URI exceeds 200 bytes
+    /// * `InvalidScore` - Score not in range 0..=score_scale
+    /// * `UriTooLong` - URI exceeds the configured max_uri_length
     /// * `AgentNotFound` - Agent doesn't exist in Identity Registry
+    /// * `InvalidIdentityRegistry` - Program passed is not the configured Identity Registry
     /// * `InvalidFeedbackIndex` - Provided index doesn't match expected
     /// * `Overflow` - Arithmetic overflow in index or stats
     /// * `FeedbackAuthClientMismatch` - feedbackAuth.client_address doesn't match signer
     /// * `FeedbackAuthExpired` - feedbackAuth expired
     /// * `FeedbackAuthIndexLimitExceeded` - Client exceeded authorized feedback limit
     /// * `UnauthorizedSigner` - feedbackAuth signer is not agent owner
+    /// * `SelfFeedbackNotAllowed` - Client is the agent owner
+    /// * `ClientBlocked` - The agent owner blocked this client (see `block_client`)
+    /// * `MissingTreasury` - A feedback fee is set but `treasury` wasn't passed
+    /// * `InvalidTreasury` - `treasury` is not the configured one
+    /// * `InsufficientClientBalance` - Client holds less than config.min_client_balance
+    ///   (checked after account rent is paid, so self-paid clients need the minimum plus rent)
+    /// * `MissingTagReputation` - tag2 is set but its tag reputation account wasn't passed
     pub fn give_feedback(
         ctx: Context<GiveFeedback>,
         agent_id: u64,
@@ -60,142 +282,72 @@ pub mod reputation_registry {
         file_hash: [u8; 32],
         feedback_index: u64,
         feedback_auth: FeedbackAuth,
+        file_size: u64,
     ) -> Result<()> {
-        // Validate score (0-100)
-        require!(score <= 100, ReputationError::InvalidScore);
-
-        // Validate URI length
-        require!(
-            file_uri.len() <= FeedbackAccount::MAX_URI_LENGTH,
-            ReputationError::UriTooLong
-        );
-
-        // Validate agent exists in Identity Registry via manual deserialization
-        // Required because agent_account discriminator differs across programs
-        let agent_data = ctx.accounts.agent_account.try_borrow_data()?;
-
-        // Verify minimum size (discriminator + agent_id + owner)
-        require!(agent_data.len() >= 8 + 8 + 32, ReputationError::AgentNotFound);
-
-        // Skip 8-byte discriminator, read agent_id (next 8 bytes)
-        let stored_agent_id = u64::from_le_bytes(
-            agent_data[8..16]
-                .try_into()
-                .map_err(|_| ReputationError::AgentNotFound)?
-        );
-
-        // Verify agent_id matches
-        require!(stored_agent_id == agent_id, ReputationError::AgentNotFound);
-
-        // Read agent owner (bytes 16-48) for feedbackAuth verification
-        let agent_owner_bytes: [u8; 32] = agent_data[16..48]
-            .try_into()
-            .map_err(|_| ReputationError::AgentNotFound)?;
-        let agent_owner = Pubkey::new_from_array(agent_owner_bytes);
+        record_feedback(
+            ctx,
+            agent_id,
+            score,
+            tag1,
+            tag2,
+            file_uri,
+            file_hash,
+            feedback_index,
+            feedback_auth,
+            file_size,
+            Vec::new(),
+        )
+    }
 
-        // Verify feedbackAuth signer is agent owner (ERC-8004 requirement)
+    /// Give feedback with more than two tags
+    ///
+    /// Same as `give_feedback`, with up to `FeedbackAccount::MAX_EXTRA_TAGS`
+    /// additional tags stored on the feedback account (at most
+    /// `FeedbackAccount::MAX_TAGS` in total). Per-tag counters are only kept
+    /// for tag1 and tag2.
+    ///
+    /// # Arguments
+    /// * Same as `give_feedback`, plus:
+    /// * `extra_tags` - Tags beyond tag1/tag2 (max 6)
+    ///
+    /// # Events
+    /// * `NewFeedback` - Emitted when feedback is successfully created
+    /// * `NewFeedbackTags` - Full tag list (tag1, tag2, extra tags), when extra tags are given
+    ///
+    /// # Errors
+    /// * `TooManyTags` - More than `MAX_EXTRA_TAGS` extra tags
+    /// * Same as `give_feedback`
+    pub fn give_feedback_tags(
+        ctx: Context<GiveFeedback>,
+        agent_id: u64,
+        score: u8,
+        tag1: [u8; 32],
+        tag2: [u8; 32],
+        file_uri: String,
+        file_hash: [u8; 32],
+        feedback_index: u64,
+        feedback_auth: FeedbackAuth,
+        file_size: u64,
+        extra_tags: Vec<[u8; 32]>,
+    ) -> Result<()> {
         require!(
-            feedback_auth.signer_address == agent_owner,
-            ReputationError::UnauthorizedSigner
+            extra_tags.len() <= FeedbackAccount::MAX_EXTRA_TAGS,
+            ReputationError::TooManyTags
         );
 
-        // Get or initialize client index account
-        let client_index = &mut ctx.accounts.client_index;
-
-        // Determine current index for feedbackAuth verification
-        let current_index = if client_index.last_index == 0 && client_index.agent_id == 0 {
-            0u64 // First feedback from this client to this agent
-        } else {
-            client_index.last_index // Next feedback index
-        };
-
-        // Verify feedbackAuth (checks client, expiry, index_limit, signature)
-        let current_time = Clock::get()?.unix_timestamp;
-        feedback_auth.verify(&ctx.accounts.client.key(), current_index, current_time)?;
-
-        // Validate feedback_index matches expected
-        if client_index.last_index == 0 && client_index.agent_id == 0 {
-            // First feedback from this client to this agent
-            require!(feedback_index == 0, ReputationError::InvalidFeedbackIndex);
-            client_index.agent_id = agent_id;
-            client_index.client_address = ctx.accounts.client.key();
-            client_index.bump = ctx.bumps.client_index;
-        } else {
-            // Subsequent feedback - validate index matches
-            require!(
-                feedback_index == client_index.last_index,
-                ReputationError::InvalidFeedbackIndex
-            );
-        }
-
-        // Increment index for next feedback
-        client_index.last_index = client_index
-            .last_index
-            .checked_add(1)
-            .ok_or(ReputationError::Overflow)?;
-
-        // Initialize feedback account
-        let feedback = &mut ctx.accounts.feedback_account;
-        feedback.agent_id = agent_id;
-        feedback.client_address = ctx.accounts.client.key();
-        feedback.feedback_index = feedback_index;
-        feedback.score = score;
-        feedback.tag1 = tag1;
-        feedback.tag2 = tag2;
-        feedback.file_uri = file_uri.clone();
-        feedback.file_hash = file_hash;
-        feedback.is_revoked = false;
-        feedback.created_at = Clock::get()?.unix_timestamp;
-        feedback.bump = ctx.bumps.feedback_account;
-
-        // Update agent reputation metadata (cached stats)
-        let metadata = &mut ctx.accounts.agent_reputation;
-
-        if metadata.agent_id == 0 {
-            // First feedback for this agent - initialize
-            metadata.agent_id = agent_id;
-            metadata.total_feedbacks = 1;
-            metadata.total_score_sum = score as u64;
-            metadata.average_score = score;
-            metadata.bump = ctx.bumps.agent_reputation;
-        } else {
-            // Update existing stats
-            metadata.total_feedbacks = metadata
-                .total_feedbacks
-                .checked_add(1)
-                .ok_or(ReputationError::Overflow)?;
-
-            metadata.total_score_sum = metadata
-                .total_score_sum
-                .checked_add(score as u64)
-                .ok_or(ReputationError::Overflow)?;
-
-            metadata.average_score = (metadata.total_score_sum / metadata.total_feedbacks) as u8;
-        }
-
-        metadata.last_updated = Clock::get()?.unix_timestamp;
-
-        // Emit event
-        emit!(NewFeedback {
+        record_feedback(
+            ctx,
             agent_id,
-            client_address: ctx.accounts.client.key(),
-            feedback_index,
             score,
             tag1,
             tag2,
             file_uri,
             file_hash,
-        });
-
-        msg!(
-            "Feedback created: agent_id={}, client={}, index={}, score={}",
-            agent_id,
-            ctx.accounts.client.key(),
             feedback_index,
-            score
-        );
-
-        Ok(())
+            feedback_auth,
+            file_size,
+            extra_tags,
+        )
     }
 
     /// Revoke feedback (ERC-8004 spec: revokeFeedback)
@@ -210,17 +362,28 @@ pub mod reputation_registry {
     ///
     /// # Events
     /// * `FeedbackRevoked` - Emitted when feedback is successfully revoked
+    /// * `ReputationEmpty` - Emitted when it was the agent's last non-revoked feedback
     ///
     /// # Errors
     /// * `Unauthorized` - Caller is not the original feedback author
     /// * `AlreadyRevoked` - Feedback was already revoked
+    /// * `EditWindowClosed` - The configured edit window has elapsed
     /// * `FeedbackNotFound` - Feedback doesn't exist
+    /// * `MissingTagReputation` - The feedback was counted under a tag whose tag reputation
+    ///   account wasn't passed
+    /// * `InvalidTagReputation` - A tag reputation account isn't the one of the feedback's tag
+    /// * `MissingScoreHistogram` - The feedback was counted in the score histogram
+    ///   and it wasn't passed
     pub fn revoke_feedback(
         ctx: Context<RevokeFeedback>,
         agent_id: u64,
         feedback_index: u64,
     ) -> Result<()> {
-        let feedback = &mut ctx.accounts.feedback_account;
+        let feedback_info = ctx.accounts.feedback_account.to_account_info();
+        let feedback = &mut load_existing::<FeedbackAccount>(
+            &feedback_info,
+            ReputationError::FeedbackNotFound,
+        )?;
 
         // Validate caller is the original feedback author
         require!(
@@ -231,30 +394,62 @@ pub mod reputation_registry {
         // Validate feedback is not already revoked
         require!(!feedback.is_revoked, ReputationError::AlreadyRevoked);
 
+        // Feedback becomes immutable once the edit window has elapsed
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            ctx.accounts.config.is_editable(feedback.created_at, now),
+            ReputationError::EditWindowClosed
+        );
+
         // Mark as revoked
         feedback.is_revoked = true;
 
-        // Update agent reputation metadata (subtract from aggregates)
+        // Update agent reputation metadata (subtract the same normalized
+        // contribution that was added in give_feedback)
         let metadata = &mut ctx.accounts.agent_reputation;
+        metadata.remove_score(feedback.normalized_score(), feedback.created_at)?;
 
-        metadata.total_feedbacks = metadata
-            .total_feedbacks
-            .checked_sub(1)
-            .ok_or(ReputationError::Overflow)?;
+        // Last feedback gone: no ghost sums or min/max left behind
+        if metadata.reset_if_empty() {
+            emit!(ReputationEmpty { agent_id });
+        }
 
-        metadata.total_score_sum = metadata
-            .total_score_sum
-            .checked_sub(feedback.score as u64)
-            .ok_or(ReputationError::Overflow)?;
+        metadata.last_updated = now;
 
-        // Recalculate average (avoid division by zero)
-        metadata.average_score = if metadata.total_feedbacks == 0 {
-            0
-        } else {
-            (metadata.total_score_sum / metadata.total_feedbacks) as u8
-        };
+        // Same for the per-tag counters it was added to
+        if feedback.tracks_tag(FeedbackAccount::TRACKED_TAG1) {
+            let tag1_reputation = ctx
+                .accounts
+                .tag1_reputation
+                .as_mut()
+                .ok_or(ReputationError::MissingTagReputation)?;
+            require!(
+                tag1_reputation.agent_id == agent_id && tag1_reputation.tag == feedback.tag1,
+                ReputationError::InvalidTagReputation
+            );
+            tag1_reputation.remove_score(feedback.normalized_score())?;
+        }
+        if feedback.tracks_tag(FeedbackAccount::TRACKED_TAG2) {
+            let tag2_reputation = ctx
+                .accounts
+                .tag2_reputation
+                .as_mut()
+                .ok_or(ReputationError::MissingTagReputation)?;
+            require!(
+                tag2_reputation.agent_id == agent_id && tag2_reputation.tag == feedback.tag2,
+                ReputationError::InvalidTagReputation
+            );
+            tag2_reputation.remove_score(feedback.normalized_score())?;
+        }
+        if feedback.in_histogram() {
+            ctx.accounts
+                .score_histogram
+                .as_mut()
+                .ok_or(ReputationError::MissingScoreHistogram)?
+                .remove_score(feedback.normalized_score())?;
+        }
 
-        metadata.last_updated = Clock::get()?.unix_timestamp;
+        feedback.try_serialize(&mut &mut feedback_info.try_borrow_mut_data()?[..])?;
 
         // Emit event
         emit!(FeedbackRevoked {
@@ -273,200 +468,1999 @@ pub mod reputation_registry {
         Ok(())
     }
 
-    /// Append response to feedback (ERC-8004 spec: appendResponse)
+    /// Update feedback in place
     ///
-    /// Allows anyone (agent, third-party aggregator, etc.) to append a response
-    /// to existing feedback. Responses are stored in separate PDA accounts,
-    /// enabling unlimited responses per feedback. Common use cases:
-    /// - Agent showing refund/resolution
-    /// - Data aggregator flagging spam
-    /// - Community providing additional context
+    /// Lets the original author revise a rating without revoking it, so the
+    /// responses attached to the feedback stay with it. The new score uses the
+    /// scale the feedback was given on, and the declared file size is reset to
+    /// 0 (unknown) since it described the previous file.
     ///
     /// # Arguments
     /// * `agent_id` - Agent ID from Identity Registry
-    /// * `client_address` - Original feedback author address
-    /// * `feedback_index` - Index of feedback being responded to
-    /// * `response_uri` - IPFS/Arweave link to response content (max 200 bytes)
-    /// * `response_hash` - SHA-256 hash of response file
+    /// * `feedback_index` - Index of feedback to update
+    /// * `new_score` - New rating 0..=feedback.score_scale
+    /// * `new_file_uri` - New IPFS/Arweave link (max `config.max_uri_length` bytes;
+    ///   the account is resized to fit, the client paying or being refunded)
+    /// * `new_file_hash` - SHA-256 hash of the new feedback file
     ///
     /// # Events
-    /// * `ResponseAppended` - Emitted when response is successfully added
+    /// * `FeedbackUpdated` - Emitted with the old and new score
     ///
     /// # Errors
-    /// * `ResponseUriTooLong` - URI exceeds 200 bytes
-    /// * `FeedbackNotFound` - Referenced feedback doesn't exist
-    pub fn append_response(
-        ctx: Context<AppendResponse>,
+    /// * `Unauthorized` - Caller is not the original feedback author
+    /// * `AlreadyRevoked` - Feedback was revoked
+    /// * `InvalidScore` - Score not in range 0..=feedback.score_scale
+    /// * `UriTooLong` - URI exceeds the configured max_uri_length
+    /// * `EditWindowClosed` - The configured edit window has elapsed
+    /// * `MissingTagReputation` - The feedback was counted under a tag whose tag reputation
+    ///   account wasn't passed
+    /// * `MissingScoreHistogram` - The feedback was counted in the score histogram
+    ///   and it wasn't passed
+    pub fn update_feedback(
+        ctx: Context<UpdateFeedback>,
         agent_id: u64,
-        client_address: Pubkey,
         feedback_index: u64,
-        response_uri: String,
-        response_hash: [u8; 32],
+        new_score: u8,
+        new_file_uri: String,
+        new_file_hash: [u8; 32],
     ) -> Result<()> {
-        // Validate URI length
+        let feedback = &mut ctx.accounts.feedback_account;
+
         require!(
-            response_uri.len() <= ResponseAccount::MAX_URI_LENGTH,
-            ReputationError::ResponseUriTooLong
+            feedback.client_address == ctx.accounts.client.key(),
+            ReputationError::Unauthorized
+        );
+        require!(!feedback.is_revoked, ReputationError::AlreadyRevoked);
+        require!(new_score <= feedback.score_scale, ReputationError::InvalidScore);
+        require!(
+            ctx.accounts.config.accepts_uri_length(new_file_uri.len()),
+            ReputationError::UriTooLong
         );
 
-        // Get or initialize response index account
-        let response_index_account = &mut ctx.accounts.response_index;
-        let response_index = if response_index_account.agent_id == 0 {
-            // First response to this feedback
-            response_index_account.agent_id = agent_id;
-            response_index_account.client_address = client_address;
-            response_index_account.feedback_index = feedback_index;
-            response_index_account.next_index = 1; // Next response will be index 1
-            response_index_account.bump = ctx.bumps.response_index;
-            0u64
-        } else {
-            let current_index = response_index_account.next_index;
-            response_index_account.next_index = current_index
-                .checked_add(1)
-                .ok_or(ReputationError::Overflow)?;
-            current_index
-        };
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            ctx.accounts.config.is_editable(feedback.created_at, now),
+            ReputationError::EditWindowClosed
+        );
 
-        // Initialize response account
-        let response = &mut ctx.accounts.response_account;
-        response.agent_id = agent_id;
-        response.client_address = client_address;
-        response.feedback_index = feedback_index;
-        response.response_index = response_index;
-        response.responder = ctx.accounts.responder.key();
-        response.response_uri = response_uri.clone();
-        response.response_hash = response_hash;
-        response.created_at = Clock::get()?.unix_timestamp;
-        response.bump = ctx.bumps.response_account;
+        let old_score = feedback.score;
+        let old_normalized = feedback.normalized_score();
 
-        // Emit event
-        emit!(ResponseAppended {
+        feedback.score = new_score;
+        feedback.file_uri = new_file_uri.clone();
+        feedback.file_hash = new_file_hash;
+        feedback.file_size = 0;
+
+        // Swap the normalized contribution in the cached aggregates
+        let new_normalized = feedback.normalized_score();
+        let metadata = &mut ctx.accounts.agent_reputation;
+        metadata.replace_score(old_normalized, new_normalized, feedback.created_at)?;
+
+        metadata.last_updated = now;
+
+        if feedback.tracks_tag(FeedbackAccount::TRACKED_TAG1) {
+            ctx.accounts
+                .tag1_reputation
+                .as_mut()
+                .ok_or(ReputationError::MissingTagReputation)?
+                .replace_score(old_normalized, new_normalized)?;
+        }
+        if feedback.tracks_tag(FeedbackAccount::TRACKED_TAG2) {
+            ctx.accounts
+                .tag2_reputation
+                .as_mut()
+                .ok_or(ReputationError::MissingTagReputation)?
+                .replace_score(old_normalized, new_normalized)?;
+        }
+        if feedback.in_histogram() {
+            ctx.accounts
+                .score_histogram
+                .as_mut()
+                .ok_or(ReputationError::MissingScoreHistogram)?
+                .replace_score(old_normalized, new_normalized)?;
+        }
+
+        emit!(FeedbackUpdated {
             agent_id,
-            client_address,
+            client_address: ctx.accounts.client.key(),
             feedback_index,
-            response_index,
-            responder: ctx.accounts.responder.key(),
-            response_uri,
+            old_score,
+            new_score,
+            file_uri: new_file_uri,
+            file_hash: new_file_hash,
         });
 
         msg!(
-            "Response appended: agent_id={}, feedback_index={}, response_index={}, responder={}",
+            "Feedback updated: agent_id={}, client={}, index={}, score={}->{}",
             agent_id,
+            ctx.accounts.client.key(),
             feedback_index,
-            response_index,
-            ctx.accounts.responder.key()
+            old_score,
+            new_score
         );
 
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-pub struct Initialize {}
+    /// Close a revoked feedback account and return its rent to the author
+    ///
+    /// Revocation already removed the feedback from the aggregates, so only
+    /// `feedback_account_count` is decremented here. Responses to the feedback
+    /// are separate accounts and are not affected.
+    ///
+    /// # Arguments
+    /// * `agent_id` - Agent ID from Identity Registry
+    /// * `feedback_index` - Index of feedback to close
+    ///
+    /// # Events
+    /// * `FeedbackClosed` - Emitted when the account is closed
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the original feedback author
+    /// * `FeedbackNotRevoked` - Feedback must be revoked first
+    pub fn close_feedback(
+        ctx: Context<CloseFeedback>,
+        agent_id: u64,
+        feedback_index: u64,
+    ) -> Result<()> {
+        let feedback = &ctx.accounts.feedback_account;
+
+        require!(
+            feedback.client_address == ctx.accounts.client.key(),
+            ReputationError::Unauthorized
+        );
+
+        // Closing a live feedback would leave its score in the aggregates
+        require!(feedback.is_revoked, ReputationError::FeedbackNotRevoked);
+
+        let metadata = &mut ctx.accounts.agent_reputation;
+        metadata.feedback_account_count = metadata
+            .feedback_account_count
+            .checked_sub(1)
+            .ok_or(ReputationError::Overflow)?;
+
+        metadata.last_updated = Clock::get()?.unix_timestamp;
+
+        emit!(FeedbackClosed {
+            agent_id,
+            client_address: ctx.accounts.client.key(),
+            feedback_index,
+        });
+
+        msg!(
+            "Feedback closed: agent_id={}, client={}, index={}",
+            agent_id,
+            ctx.accounts.client.key(),
+            feedback_index
+        );
+
+        Ok(())
+    }
+
+    /// Select the aggregation policy for an agent's average score
+    ///
+    /// Only the agent owner (per the Identity Registry) can choose the policy.
+    /// Running sums for every policy are kept on each give/revoke, so the new
+    /// average takes effect immediately over the agent's existing feedback.
+    ///
+    /// # Arguments
+    /// * `agent_id` - Agent ID from Identity Registry
+    /// * `policy` - `ReputationPolicy` value (0 = mean, 1 = trimmed, 2 = decayed)
+    ///
+    /// # Events
+    /// * `ReputationPolicySet` - Emitted with the recomputed average
+    ///
+    /// # Errors
+    /// * `UnsupportedReputationPolicy` - Unknown policy or disabled in config
+    /// * `AgentNotFound` - Agent doesn't exist in Identity Registry
+    /// * `Unauthorized` - Signer is not the agent owner
+    pub fn set_reputation_policy(
+        ctx: Context<SetReputationPolicy>,
+        agent_id: u64,
+        policy: u8,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.config.supports_policy(policy),
+            ReputationError::UnsupportedReputationPolicy
+        );
+
+        let agent_owner =
+            agent_owner_from_data(&ctx.accounts.agent_account.try_borrow_data()?, agent_id)?;
+        require!(
+            agent_owner == ctx.accounts.owner.key(),
+            ReputationError::Unauthorized
+        );
+
+        let metadata = &mut ctx.accounts.agent_reputation;
+        if metadata.agent_id == 0 {
+            // No feedback yet for this agent - initialize
+            metadata.agent_id = agent_id;
+            metadata.bump = ctx.bumps.agent_reputation;
+        }
+
+        metadata.set_policy(policy);
+        metadata.last_updated = Clock::get()?.unix_timestamp;
+
+        emit!(ReputationPolicySet {
+            agent_id,
+            policy,
+            average_score: metadata.average_score,
+            set_by: agent_owner,
+        });
+
+        msg!("Reputation policy for agent {} set to {}", agent_id, policy);
+
+        Ok(())
+    }
+
+    /// Flag a feedback as disputed (agent owner)
+    ///
+    /// Gives agents a way to contest abusive reviews they can't revoke. The
+    /// flag is informational for off-chain UIs: the feedback stays counted in
+    /// every aggregate.
+    ///
+    /// # Arguments
+    /// * `agent_id` - Agent ID from Identity Registry
+    /// * `client_address` - Author of the feedback
+    /// * `feedback_index` - Index of the feedback
+    ///
+    /// # Events
+    /// * `FeedbackDisputed` - Emitted when the flag is set
+    ///
+    /// # Errors
+    /// * `AgentNotFound` - Agent doesn't exist in Identity Registry
+    /// * `Unauthorized` - Signer is not the agent owner
+    /// * `AlreadyDisputed` - Feedback was already disputed
+    pub fn dispute_feedback(
+        ctx: Context<DisputeFeedback>,
+        agent_id: u64,
+        client_address: Pubkey,
+        feedback_index: u64,
+    ) -> Result<()> {
+        let agent_owner =
+            agent_owner_from_data(&ctx.accounts.agent_account.try_borrow_data()?, agent_id)?;
+        require!(
+            agent_owner == ctx.accounts.owner.key(),
+            ReputationError::Unauthorized
+        );
+
+        let feedback = &mut ctx.accounts.feedback_account;
+        require!(!feedback.is_disputed, ReputationError::AlreadyDisputed);
+        feedback.is_disputed = true;
+
+        emit!(FeedbackDisputed {
+            agent_id,
+            client_address,
+            feedback_index,
+            disputer: agent_owner,
+        });
+
+        msg!(
+            "Feedback disputed: agent_id={}, client={}, index={}",
+            agent_id,
+            client_address,
+            feedback_index
+        );
+
+        Ok(())
+    }
+
+    /// Block a client from giving new feedback to the agent (agent owner)
+    ///
+    /// Stops a harasser from leaving endless feedback. Blocking is
+    /// forward-only: feedback the client already gave stays counted and can
+    /// only be contested with `dispute_feedback`.
+    ///
+    /// # Arguments
+    /// * `agent_id` - Agent ID from Identity Registry
+    /// * `client` - Client address to block
+    ///
+    /// # Events
+    /// * `ClientBlockAdded` - Emitted when the block is created
+    ///
+    /// # Errors
+    /// * `AgentNotFound` - Agent doesn't exist in Identity Registry
+    /// * `Unauthorized` - Signer is not the agent owner
+    pub fn block_client(ctx: Context<BlockClient>, agent_id: u64, client: Pubkey) -> Result<()> {
+        let agent_owner =
+            agent_owner_from_data(&ctx.accounts.agent_account.try_borrow_data()?, agent_id)?;
+        require!(
+            agent_owner == ctx.accounts.owner.key(),
+            ReputationError::Unauthorized
+        );
+
+        let block = &mut ctx.accounts.client_block;
+        block.agent_id = agent_id;
+        block.client = client;
+        block.blocked_at = Clock::get()?.unix_timestamp;
+        block.bump = ctx.bumps.client_block;
+
+        emit!(ClientBlockAdded {
+            agent_id,
+            client,
+            blocked_by: agent_owner,
+        });
+
+        msg!("Client {} blocked for agent {}", client, agent_id);
+
+        Ok(())
+    }
+
+    /// Lift a client block and return its rent to the agent owner
+    ///
+    /// # Arguments
+    /// * `agent_id` - Agent ID from Identity Registry
+    /// * `client` - Blocked client address
+    ///
+    /// # Events
+    /// * `ClientBlockRemoved` - Emitted when the block is closed
+    ///
+    /// # Errors
+    /// * `AgentNotFound` - Agent doesn't exist in Identity Registry
+    /// * `Unauthorized` - Signer is not the agent owner
+    pub fn unblock_client(ctx: Context<UnblockClient>, agent_id: u64, client: Pubkey) -> Result<()> {
+        let agent_owner =
+            agent_owner_from_data(&ctx.accounts.agent_account.try_borrow_data()?, agent_id)?;
+        require!(
+            agent_owner == ctx.accounts.owner.key(),
+            ReputationError::Unauthorized
+        );
+
+        emit!(ClientBlockRemoved {
+            agent_id,
+            client,
+            unblocked_by: agent_owner,
+        });
+
+        msg!("Client {} unblocked for agent {}", client, agent_id);
+
+        Ok(())
+    }
+
+    /// Check the cached reputation aggregate against the feedback accounts (view)
+    ///
+    /// Read-only integrity auditor for monitoring bots: pass every live feedback
+    /// account of the agent as remaining_accounts and simulate the transaction.
+    /// Aggregates are recomputed from scratch and compared with the cached
+    /// `AgentReputationMetadata`; a mismatch means the cache drifted (e.g. a
+    /// feedback account was closed without being revoked) and should be recomputed.
+    ///
+    /// # Arguments
+    /// * `agent_id` - Agent ID from Identity Registry
+    ///
+    /// # Returns
+    /// * `ReputationConsistency` with the recomputed values and `is_consistent`
+    ///
+    /// # Errors
+    /// * `BatchTooLarge` - More than `AgentReputationMetadata::MAX_CONSISTENCY_BATCH` accounts
+    /// * `InvalidFeedbackAccount` - An account belongs to another agent or is passed twice
+    /// * `Overflow` - The recomputed sums overflow
+    pub fn verify_reputation_consistency<'info>(
+        ctx: Context<'_, '_, 'info, 'info, VerifyReputationConsistency<'info>>,
+        agent_id: u64,
+    ) -> Result<ReputationConsistency> {
+        require!(
+            ctx.remaining_accounts.len() <= AgentReputationMetadata::MAX_CONSISTENCY_BATCH,
+            ReputationError::BatchTooLarge
+        );
+
+        let mut feedbacks = Vec::with_capacity(ctx.remaining_accounts.len());
+        for (i, info) in ctx.remaining_accounts.iter().enumerate() {
+            require!(
+                !ctx.remaining_accounts[..i].iter().any(|other| other.key == info.key),
+                ReputationError::InvalidFeedbackAccount
+            );
+
+            let feedback: Account<FeedbackAccount> = Account::try_from(info)?;
+            require!(feedback.agent_id == agent_id, ReputationError::InvalidFeedbackAccount);
+            feedbacks.push(feedback.into_inner());
+        }
+
+        ctx.accounts.agent_reputation.check_consistency(&feedbacks)
+    }
+
+    /// Repair an agent's cached reputation aggregate from its feedback accounts
+    ///
+    /// Permissionless fix for a cache that drifted from the feedback it
+    /// summarizes (see `verify_reputation_consistency`). Pass every live
+    /// feedback account of the agent as remaining_accounts: the aggregates are
+    /// recomputed from scratch over the non-revoked ones and overwrite the
+    /// cached values. Anyone may call it, since it can only converge to the
+    /// truth: each account must be the agent's feedback PDA and their number
+    /// must match `feedback_account_count`, so a partial set is rejected.
+    /// Per-tag counters and the score histogram are not touched.
+    ///
+    /// Limit: the whole set goes in one transaction, so only agents with at
+    /// most `AgentReputationMetadata::MAX_CONSISTENCY_BATCH` (60) feedback
+    /// accounts, revoked ones included, can be repaired. There is no chunked
+    /// variant: a cursor spread over several transactions could mix feedback
+    /// given or revoked in between. Authors closing revoked feedback
+    /// (`close_feedback`) can bring an agent back under the limit.
+    ///
+    /// # Arguments
+    /// * `agent_id` - Agent ID from Identity Registry
+    ///
+    /// # Events
+    /// * `ReputationRecomputed` - Emitted with the repaired aggregate
+    ///
+    /// # Errors
+    /// * `BatchTooLarge` - More than `AgentReputationMetadata::MAX_CONSISTENCY_BATCH` accounts
+    ///   passed, or the agent has more feedback accounts than that
+    /// * `IncompleteFeedbackSet` - Account count differs from `feedback_account_count`
+    /// * `InvalidFeedbackAccount` - An account isn't this agent's feedback PDA or is passed twice
+    /// * `Overflow` - The recomputed sums overflow
+    pub fn recompute_reputation<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RecomputeReputation<'info>>,
+        agent_id: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.remaining_accounts.len() <= AgentReputationMetadata::MAX_CONSISTENCY_BATCH,
+            ReputationError::BatchTooLarge
+        );
+        require!(
+            ctx.accounts.agent_reputation.is_recomputable(),
+            ReputationError::BatchTooLarge
+        );
+        require!(
+            ctx.remaining_accounts.len() as u64 == ctx.accounts.agent_reputation.feedback_account_count,
+            ReputationError::IncompleteFeedbackSet
+        );
+
+        let mut feedbacks = Vec::with_capacity(ctx.remaining_accounts.len());
+        for (i, info) in ctx.remaining_accounts.iter().enumerate() {
+            require!(
+                !ctx.remaining_accounts[..i].iter().any(|other| other.key == info.key),
+                ReputationError::InvalidFeedbackAccount
+            );
+
+            let feedback: Account<FeedbackAccount> = Account::try_from(info)?;
+            require!(feedback.agent_id == agent_id, ReputationError::InvalidFeedbackAccount);
+            let expected = Pubkey::create_program_address(
+                &[
+                    b"feedback",
+                    agent_id.to_le_bytes().as_ref(),
+                    feedback.client_address.as_ref(),
+                    feedback.feedback_index.to_le_bytes().as_ref(),
+                    &[feedback.bump],
+                ],
+                &crate::ID,
+            )
+            .map_err(|_| error!(ReputationError::InvalidFeedbackAccount))?;
+            require_keys_eq!(expected, info.key(), ReputationError::InvalidFeedbackAccount);
+            feedbacks.push(feedback.into_inner());
+        }
+
+        let reputation = &mut ctx.accounts.agent_reputation;
+        reputation.recompute_from(&feedbacks)?;
+
+        emit!(ReputationRecomputed {
+            agent_id,
+            total_feedbacks: reputation.total_feedbacks,
+            average_score: reputation.average_score,
+            recomputed_by: ctx.accounts.caller.key(),
+        });
+
+        msg!(
+            "Agent {} reputation recomputed: {} feedbacks, average {}",
+            agent_id,
+            reputation.total_feedbacks,
+            reputation.average_score
+        );
+
+        Ok(())
+    }
+
+    /// Commit to an agent's complete feedback history (view)
+    ///
+    /// Pass every feedback account of the agent as remaining_accounts and
+    /// simulate the transaction. Returns a SHA-256 commitment over their raw
+    /// data in canonical order (see `state::state_commitment`), so audits and
+    /// bridges can compare a single hash with the one reproduced off-chain.
+    ///
+    /// # Arguments
+    /// * `agent_id` - Agent ID from Identity Registry
+    ///
+    /// # Returns
+    /// * 32-byte commitment, independent of the order accounts are passed in
+    ///
+    /// # Errors
+    /// * `BatchTooLarge` - More than `AgentReputationMetadata::MAX_CONSISTENCY_BATCH` accounts
+    /// * `InvalidFeedbackAccount` - An account belongs to another agent or is passed twice
+    pub fn state_commitment<'info>(
+        ctx: Context<'_, '_, 'info, 'info, StateCommitment<'info>>,
+        agent_id: u64,
+    ) -> Result<[u8; 32]> {
+        require!(
+            ctx.remaining_accounts.len() <= AgentReputationMetadata::MAX_CONSISTENCY_BATCH,
+            ReputationError::BatchTooLarge
+        );
+
+        let mut accounts = Vec::with_capacity(ctx.remaining_accounts.len());
+        for (i, info) in ctx.remaining_accounts.iter().enumerate() {
+            require!(
+                !ctx.remaining_accounts[..i].iter().any(|other| other.key == info.key),
+                ReputationError::InvalidFeedbackAccount
+            );
+
+            let feedback: Account<FeedbackAccount> = Account::try_from(info)?;
+            require!(feedback.agent_id == agent_id, ReputationError::InvalidFeedbackAccount);
+            accounts.push((info.key(), info.try_borrow_data()?.to_vec()));
+        }
+
+        Ok(state::state_commitment(agent_id, accounts))
+    }
+
+    /// Snapshot the top agents by reputation for an epoch
+    ///
+    /// Ranks the AgentReputationMetadata accounts passed in remaining_accounts
+    /// and stores the top entries in a LeaderboardSnapshot PDA for `epoch`.
+    /// The PDA can only be created once, so a snapshot cannot be rewritten.
+    /// Only the registry authority can take snapshots.
+    ///
+    /// # Arguments
+    /// * `epoch` - Caller-defined epoch (e.g. week number)
+    ///
+    /// # Events
+    /// * `LeaderboardSnapshotted` - Emitted with the ranked agent ids
+    ///
+    /// # Errors
+    /// * `BatchTooLarge` - More than MAX_INPUT_ACCOUNTS reputation accounts
+    /// * `InvalidReputationAccount` - Same agent passed twice
+    pub fn snapshot_leaderboard<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SnapshotLeaderboard<'info>>,
+        epoch: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.remaining_accounts.len() <= LeaderboardSnapshot::MAX_INPUT_ACCOUNTS,
+            ReputationError::BatchTooLarge
+        );
+
+        let mut entries: Vec<LeaderboardEntry> = Vec::with_capacity(ctx.remaining_accounts.len());
+        for info in ctx.remaining_accounts.iter() {
+            let reputation: Account<AgentReputationMetadata> = Account::try_from(info)?;
+            require!(
+                !entries.iter().any(|e| e.agent_id == reputation.agent_id),
+                ReputationError::InvalidReputationAccount
+            );
+            entries.push(LeaderboardEntry::from_reputation(&reputation));
+        }
+
+        let snapshot = &mut ctx.accounts.snapshot;
+        snapshot.epoch = epoch;
+        snapshot.entries = LeaderboardSnapshot::rank(entries);
+        snapshot.created_at = Clock::get()?.unix_timestamp;
+        snapshot.bump = ctx.bumps.snapshot;
+
+        emit!(LeaderboardSnapshotted {
+            epoch,
+            agent_ids: snapshot.entries.iter().map(|e| e.agent_id).collect(),
+            agents_considered: ctx.remaining_accounts.len() as u16,
+        });
+
+        Ok(())
+    }
+
+    /// Mint a soulbound reputation attestation NFT to the agent owner
+    ///
+    /// Point-in-time credential the agent can present outside the registry.
+    /// The current average score, feedback count and snapshot timestamp are
+    /// encoded in the NFT URI and mirrored in the ReputationAttestationRecord PDA.
+    /// The token account is frozen before the master edition takes over the
+    /// freeze authority, so the attestation can never be transferred.
+    ///
+    /// # Arguments
+    /// * `agent_id` - Agent ID from Identity Registry
+    /// * `base_uri` - Renderer URI the snapshot query parameters are appended to
+    ///
+    /// # Events
+    /// * `ReputationAttestationMinted` - Emitted with the encoded snapshot
+    ///
+    /// # Errors
+    /// * `AgentNotFound` - Agent doesn't exist in Identity Registry
+    /// * `Unauthorized` - Signer is not the agent owner
+    /// * `AttestationRateLimited` - Previous attestation is less than a day old
+    /// * `UriTooLong` - Encoded URI exceeds 200 bytes
+    pub fn mint_reputation_attestation(
+        ctx: Context<MintReputationAttestation>,
+        agent_id: u64,
+        base_uri: String,
+    ) -> Result<()> {
+        // Read agent_id and owner from the Identity Registry agent account
+        let agent_owner =
+            agent_owner_from_data(&ctx.accounts.agent_account.try_borrow_data()?, agent_id)?;
+        require!(
+            agent_owner == ctx.accounts.owner.key(),
+            ReputationError::Unauthorized
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let record = &mut ctx.accounts.attestation_record;
+        require!(record.can_issue(now), ReputationError::AttestationRateLimited);
+
+        let reputation = &ctx.accounts.agent_reputation;
+        let uri = ReputationAttestationRecord::uri(&base_uri, reputation, now);
+        require!(
+            uri.len() <= ReputationAttestationRecord::MAX_URI_LENGTH,
+            ReputationError::UriTooLong
+        );
+
+        let authority_seeds: &[&[u8]] = &[b"attestation_authority", &[ctx.bumps.attestation_authority]];
+
+        // Mint the single attestation token and freeze it in the owner's wallet
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.attestation_mint.to_account_info(),
+                    to: ctx.accounts.attestation_token_account.to_account_info(),
+                    authority: ctx.accounts.attestation_authority.to_account_info(),
+                },
+                &[authority_seeds],
+            ),
+            1,
+        )?;
+
+        token::freeze_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            FreezeAccount {
+                account: ctx.accounts.attestation_token_account.to_account_info(),
+                mint: ctx.accounts.attestation_mint.to_account_info(),
+                authority: ctx.accounts.attestation_authority.to_account_info(),
+            },
+            &[authority_seeds],
+        ))?;
+
+        // Metadata + master edition (mint and freeze authority move to the edition)
+        CreateV1CpiBuilder::new(&ctx.accounts.token_metadata_program.to_account_info())
+            .metadata(&ctx.accounts.attestation_metadata)
+            .master_edition(Some(&ctx.accounts.attestation_master_edition))
+            .mint(&ctx.accounts.attestation_mint.to_account_info(), true)
+            .authority(&ctx.accounts.attestation_authority)
+            .payer(&ctx.accounts.owner.to_account_info())
+            .update_authority(&ctx.accounts.attestation_authority, true)
+            .system_program(&ctx.accounts.system_program.to_account_info())
+            .sysvar_instructions(&ctx.accounts.sysvar_instructions)
+            .spl_token_program(Some(&ctx.accounts.token_program.to_account_info()))
+            .name(ReputationAttestationRecord::nft_name(agent_id))
+            .uri(uri.clone())
+            .seller_fee_basis_points(0)
+            .token_standard(TokenStandard::NonFungible)
+            .print_supply(PrintSupply::Zero)
+            .invoke_signed(&[authority_seeds])?;
+
+        record.agent_id = agent_id;
+        record.last_mint = ctx.accounts.attestation_mint.key();
+        record.average_score = reputation.average_score;
+        record.total_feedbacks = reputation.total_feedbacks;
+        record.snapshot_at = now;
+        record.issued_count = record
+            .issued_count
+            .checked_add(1)
+            .ok_or(ReputationError::Overflow)?;
+        record.bump = ctx.bumps.attestation_record;
+
+        emit!(ReputationAttestationMinted {
+            agent_id,
+            owner: ctx.accounts.owner.key(),
+            mint: record.last_mint,
+            average_score: record.average_score,
+            total_feedbacks: record.total_feedbacks,
+            snapshot_at: now,
+            uri,
+        });
+
+        Ok(())
+    }
+
+    /// Append response to feedback (ERC-8004 spec: appendResponse)
+    ///
+    /// Allows anyone (agent, third-party aggregator, etc.) to append a response
+    /// to existing feedback. Responses are stored in separate PDA accounts,
+    /// enabling unlimited responses per feedback. Common use cases:
+    /// - Agent showing refund/resolution
+    /// - Data aggregator flagging spam
+    /// - Community providing additional context
+    ///
+    /// # Arguments
+    /// * `agent_id` - Agent ID from Identity Registry
+    /// * `client_address` - Original feedback author address
+    /// * `feedback_index` - Index of feedback being responded to
+    /// * `response_uri` - IPFS/Arweave link to response content (max `config.max_uri_length` bytes)
+    /// * `response_hash` - SHA-256 hash of response file
+    /// * `file_size` - Size of the response file in bytes (advisory, 0 if unknown)
+    ///
+    /// # Events
+    /// * `ResponseAppended` - Emitted when response is successfully added
+    ///
+    /// # Errors
+    /// * `ResponseUriTooLong` - URI exceeds the configured max_uri_length
+    /// * `FeedbackNotFound` - Referenced feedback doesn't exist
+    /// * `AgentNotFound` - `require_live_agent` is on and the agent account is
+    ///   missing, not the agent's PDA, or no longer registered
+    pub fn append_response(
+        ctx: Context<AppendResponse>,
+        agent_id: u64,
+        client_address: Pubkey,
+        feedback_index: u64,
+        response_uri: String,
+        response_hash: [u8; 32],
+        file_size: u64,
+    ) -> Result<()> {
+        // Validate URI length
+        require!(
+            ctx.accounts.config.accepts_uri_length(response_uri.len()),
+            ReputationError::ResponseUriTooLong
+        );
+
+        // Responses attach to an existing feedback (PDA checked by seeds)
+        load_existing::<FeedbackAccount>(
+            &ctx.accounts.feedback_account,
+            ReputationError::FeedbackNotFound,
+        )?;
+
+        // Strict mode: agent must still exist in the Identity Registry
+        if ctx.accounts.config.require_live_agent {
+            let (Some(agent_mint), Some(agent_account), Some(identity_registry_program)) = (
+                &ctx.accounts.agent_mint,
+                &ctx.accounts.agent_account,
+                &ctx.accounts.identity_registry_program,
+            ) else {
+                return err!(ReputationError::AgentNotFound);
+            };
+
+            let (expected_agent_account, _) = Pubkey::find_program_address(
+                &[b"agent", agent_mint.key().as_ref()],
+                &identity_registry_program.key(),
+            );
+            require_keys_eq!(
+                identity_registry_program.key(),
+                ctx.accounts.config.identity_registry,
+                ReputationError::InvalidIdentityRegistry
+            );
+            require!(
+                agent_account.key() == expected_agent_account
+                    && agent_account.owner == &ctx.accounts.config.identity_registry,
+                ReputationError::AgentNotFound
+            );
+            agent_owner_from_data(&agent_account.try_borrow_data()?, agent_id)?;
+        }
+
+        // Get or initialize response index account
+        let response_index_account = &mut ctx.accounts.response_index;
+        let response_index = if response_index_account.agent_id == 0 {
+            // First response to this feedback
+            response_index_account.agent_id = agent_id;
+            response_index_account.client_address = client_address;
+            response_index_account.feedback_index = feedback_index;
+            response_index_account.next_index = 1; // Next response will be index 1
+            response_index_account.bump = ctx.bumps.response_index;
+            0u64
+        } else {
+            let current_index = response_index_account.next_index;
+            response_index_account.next_index = current_index
+                .checked_add(1)
+                .ok_or(ReputationError::Overflow)?;
+            current_index
+        };
+
+        // Initialize response account
+        let response = &mut ctx.accounts.response_account;
+        response.agent_id = agent_id;
+        response.client_address = client_address;
+        response.feedback_index = feedback_index;
+        response.response_index = response_index;
+        response.responder = ctx.accounts.responder.key();
+        response.response_uri = response_uri.clone();
+        response.response_hash = response_hash;
+        response.file_size = file_size;
+        response.created_at = Clock::get()?.unix_timestamp;
+        response.is_revoked = false;
+        response.bump = ctx.bumps.response_account;
+
+        // Emit event
+        emit!(ResponseAppended {
+            agent_id,
+            client_address,
+            feedback_index,
+            response_index,
+            responder: ctx.accounts.responder.key(),
+            response_uri,
+            file_size,
+        });
+
+        msg!(
+            "Response appended: agent_id={}, feedback_index={}, response_index={}, responder={}",
+            agent_id,
+            feedback_index,
+            response_index,
+            ctx.accounts.responder.key()
+        );
+
+        Ok(())
+    }
+
+    /// Revoke a response (retract it while keeping it on-chain)
+    ///
+    /// Only the original responder can revoke. The account stays for audit;
+    /// indexers can hide revoked responses.
+    ///
+    /// # Arguments
+    /// * `agent_id`, `client_address`, `feedback_index` - Feedback PDA seeds
+    /// * `response_index` - Index of the response to revoke
+    ///
+    /// # Events
+    /// * `ResponseRevoked` - Emitted when the response is revoked
+    ///
+    /// # Errors
+    /// * `ResponseNotFound` - Response doesn't exist
+    /// * `Unauthorized` - Caller is not the responder
+    /// * `ResponseAlreadyRevoked` - Response was already revoked
+    pub fn revoke_response(
+        ctx: Context<RevokeResponse>,
+        agent_id: u64,
+        client_address: Pubkey,
+        feedback_index: u64,
+        response_index: u64,
+    ) -> Result<()> {
+        let response_info = ctx.accounts.response_account.to_account_info();
+        let response = &mut load_existing::<ResponseAccount>(
+            &response_info,
+            ReputationError::ResponseNotFound,
+        )?;
+        require!(
+            response.responder == ctx.accounts.responder.key(),
+            ReputationError::Unauthorized
+        );
+        require!(!response.is_revoked, ReputationError::ResponseAlreadyRevoked);
+
+        response.is_revoked = true;
+        response.try_serialize(&mut &mut response_info.try_borrow_mut_data()?[..])?;
+
+        emit!(ResponseRevoked {
+            agent_id,
+            client_address,
+            feedback_index,
+            response_index,
+            responder: ctx.accounts.responder.key(),
+        });
+
+        msg!(
+            "Response revoked: agent_id={}, feedback_index={}, response_index={}, responder={}",
+            agent_id,
+            feedback_index,
+            response_index,
+            ctx.accounts.responder.key()
+        );
+
+        Ok(())
+    }
+
+    /// Close a response account and return its rent to the responder
+    ///
+    /// The response index is not rewound, so later responses keep unique indices.
+    ///
+    /// # Arguments
+    /// * `agent_id`, `client_address`, `feedback_index` - Feedback PDA seeds
+    /// * `response_index` - Index of the response to close
+    ///
+    /// # Events
+    /// * `ResponseClosed` - Emitted when the account is closed
+    ///
+    /// # Errors
+    /// * `Unauthorized` - Caller is not the responder
+    pub fn close_response(
+        ctx: Context<CloseResponse>,
+        agent_id: u64,
+        client_address: Pubkey,
+        feedback_index: u64,
+        response_index: u64,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.response_account.responder == ctx.accounts.responder.key(),
+            ReputationError::Unauthorized
+        );
+
+        emit!(ResponseClosed {
+            agent_id,
+            client_address,
+            feedback_index,
+            response_index,
+            responder: ctx.accounts.responder.key(),
+        });
+
+        msg!(
+            "Response closed: agent_id={}, feedback_index={}, response_index={}, responder={}",
+            agent_id,
+            feedback_index,
+            response_index,
+            ctx.accounts.responder.key()
+        );
+
+        Ok(())
+    }
+
+    /// Read a feedback entry (view, `views` feature)
+    ///
+    /// Stable, versioned return data for CPI callers; see `views::FeedbackView`.
+    ///
+    /// # Arguments
+    /// * `agent_id`, `client_address`, `feedback_index` - Feedback PDA seeds
+    #[cfg(feature = "views")]
+    pub fn get_feedback(
+        ctx: Context<GetFeedback>,
+        _agent_id: u64,
+        _client_address: Pubkey,
+        _feedback_index: u64,
+    ) -> Result<FeedbackView> {
+        Ok(FeedbackView::from(&*ctx.accounts.feedback_account))
+    }
+
+    /// Read an agent's cached reputation aggregate (view, `views` feature)
+    ///
+    /// IDL-typed summary (count, average, min/max score, last update) so
+    /// clients don't decode the `AgentReputationMetadata` layout themselves.
+    ///
+    /// # Arguments
+    /// * `agent_id` - Agent ID from Identity Registry
+    #[cfg(feature = "views")]
+    pub fn get_reputation(ctx: Context<GetReputation>, _agent_id: u64) -> Result<ReputationView> {
+        Ok(ReputationView::from(&*ctx.accounts.agent_reputation))
+    }
+
+    /// Read several agents' reputation summaries at once (view, `views` feature)
+    ///
+    /// One simulated call instead of one account read per agent, for
+    /// leaderboards and discovery services. Pass `AgentReputationMetadata`
+    /// PDAs as remaining_accounts; summaries are returned in the same order,
+    /// not in a canonical one: callers match them to the agents they asked
+    /// for by position, and a summary has no timestamp to sort on.
+    /// Accounts that don't exist yet (agent without feedback) or aren't
+    /// reputation accounts come back with `found = false` instead of failing
+    /// the call.
+    ///
+    /// At most `ReputationSummary::MAX_BATCH` (32) accounts per call: the
+    /// return data limit (1024 bytes) binds before the transaction account
+    /// lock limit does. A legacy transaction fits about 30 of them next to the
+    /// config account; send a full batch as a v0 transaction with an address
+    /// lookup table. Compare 50 agents with two calls.
+    ///
+    /// # Errors
+    /// * `BatchTooLarge` - More than `ReputationSummary::MAX_BATCH` accounts
+    #[cfg(feature = "views")]
+    pub fn get_reputation_batch(ctx: Context<GetReputationBatch>) -> Result<Vec<ReputationSummary>> {
+        require!(
+            ctx.remaining_accounts.len() <= ReputationSummary::MAX_BATCH,
+            ReputationError::BatchTooLarge
+        );
+
+        Ok(ctx
+            .remaining_accounts
+            .iter()
+            .map(ReputationSummary::from_account_info)
+            .collect())
+    }
+
+    /// Read an agent's average score decayed by inactivity (view, `views` feature)
+    ///
+    /// Freshness-adjusted score without scanning feedback: the cached average
+    /// halves every `half_life_secs` since the agent's reputation was last
+    /// updated (see `AgentReputationMetadata::decayed_average_score`). This is
+    /// an approximation of per-feedback age decay; nothing is mutated.
+    ///
+    /// # Arguments
+    /// * `agent_id` - Agent ID from Identity Registry
+    /// * `half_life_secs` - Seconds for the score to halve
+    ///
+    /// # Errors
+    /// * `InvalidHalfLife` - Half-life is not positive
+    #[cfg(feature = "views")]
+    pub fn get_decayed_reputation(
+        ctx: Context<GetReputation>,
+        _agent_id: u64,
+        half_life_secs: i64,
+    ) -> Result<u8> {
+        require!(half_life_secs > 0, ReputationError::InvalidHalfLife);
+
+        let now = Clock::get()?.unix_timestamp;
+        Ok(ctx
+            .accounts
+            .agent_reputation
+            .decayed_average_score(now, half_life_secs))
+    }
+
+    /// Read an agent's feedback score distribution (view, `views` feature)
+    ///
+    /// Ten non-revoked feedback counts by normalized score (0-9, ..., 90-100)
+    /// for rating-distribution charts without scanning every feedback.
+    ///
+    /// # Arguments
+    /// * `agent_id` - Agent ID from Identity Registry
+    #[cfg(feature = "views")]
+    pub fn get_histogram(ctx: Context<GetHistogram>, _agent_id: u64) -> Result<HistogramView> {
+        Ok(HistogramView::from(&*ctx.accounts.score_histogram))
+    }
+
+    /// Read an agent's trust score blending feedback and validation (view, `views` feature)
+    ///
+    /// Weighted blend of the cached feedback average and the Validation
+    /// Registry's average response (see `state::blend_trust_score`). If one
+    /// side has no data yet the other side's average is returned unweighted.
+    ///
+    /// # Arguments
+    /// * `agent_id` - Agent ID from Identity Registry
+    /// * `feedback_weight_bps` - Weight of the feedback average; the validation
+    ///   average gets the remaining `10000 - feedback_weight_bps`
+    ///
+    /// # Errors
+    /// * `InvalidTrustWeight` - Feedback weight above 10000 basis points
+    /// * `InvalidValidationMetadata` - Validation account is not the agent's metadata
+    #[cfg(feature = "views")]
+    pub fn get_trust_score(
+        ctx: Context<GetTrustScore>,
+        agent_id: u64,
+        feedback_weight_bps: u16,
+    ) -> Result<u8> {
+        require!(
+            feedback_weight_bps <= TRUST_WEIGHT_TOTAL_BPS,
+            ReputationError::InvalidTrustWeight
+        );
+
+        let (feedback_count, feedback_average) = {
+            let info = ctx.accounts.agent_reputation.to_account_info();
+            if info.data_is_empty() {
+                (0, 0)
+            } else {
+                let reputation =
+                    AgentReputationMetadata::try_deserialize(&mut &info.data.borrow()[..])?;
+                (reputation.total_feedbacks, reputation.average_score)
+            }
+        };
+
+        let (validation_count, validation_average) = {
+            let info = ctx.accounts.agent_validation.to_account_info();
+            if info.data_is_empty() {
+                (0, 0)
+            } else {
+                require!(
+                    info.owner == &ctx.accounts.config.validation_registry,
+                    ReputationError::InvalidValidationMetadata
+                );
+                validation_summary_from_data(&info.data.borrow(), agent_id)?
+            }
+        };
+
+        Ok(blend_trust_score(
+            feedback_average,
+            feedback_count,
+            validation_average,
+            validation_count,
+            feedback_weight_bps,
+        ))
+    }
+
+    /// Read the registry configuration (view, `views` feature)
+    #[cfg(feature = "views")]
+    pub fn get_config(ctx: Context<GetConfig>) -> Result<ReputationConfigView> {
+        Ok(ReputationConfigView::from(&*ctx.accounts.config))
+    }
+}
+
+/// Deserialize a program account the seeds constraint already located,
+/// failing with `not_found` (instead of Anchor's generic "account not
+/// initialized") when it was never created or has been closed
+fn load_existing<T: AccountDeserialize>(info: &AccountInfo, not_found: ReputationError) -> Result<T> {
+    if info.data_is_empty() || info.owner != &crate::ID {
+        return Err(not_found.into());
+    }
+    T::try_deserialize(&mut &info.try_borrow_data()?[..])
+}
+
+/// Add a feedback's normalized score to the counter of `tag`, returning whether
+/// it was counted
+///
+/// A tag the agent doesn't track yet (account just created by
+/// `init_if_needed`) only gets a counter while the agent tracks fewer than
+/// `config.max_tracked_tags` tags; otherwise the account is closed again, refunding
+/// `payer`.
+fn count_tag<'info>(
+    tag_reputation: &mut Account<'info, TagReputation>,
+    bump: u8,
+    tag: [u8; 32],
+    normalized_score: u8,
+    config: &ReputationConfig,
+    metadata: &mut AgentReputationMetadata,
+    payer: &AccountInfo<'info>,
+) -> Result<bool> {
+    if tag_reputation.is_new() {
+        if !config.can_track_new_tag(metadata.tracked_tag_count) {
+            tag_reputation.close(payer.clone())?;
+            return Ok(false);
+        }
+        metadata.tracked_tag_count = metadata
+            .tracked_tag_count
+            .checked_add(1)
+            .ok_or(ReputationError::Overflow)?;
+    }
+    tag_reputation.add_score(metadata.agent_id, tag, bump, normalized_score)?;
+    Ok(true)
+}
+
+/// Shared body of `give_feedback` and `give_feedback_tags`
+#[allow(clippy::too_many_arguments)]
+fn record_feedback(
+    ctx: Context<GiveFeedback>,
+    agent_id: u64,
+    score: u8,
+    tag1: [u8; 32],
+    tag2: [u8; 32],
+    file_uri: String,
+    file_hash: [u8; 32],
+    feedback_index: u64,
+    feedback_auth: FeedbackAuth,
+    file_size: u64,
+    extra_tags: Vec<[u8; 32]>,
+) -> Result<()> {
+    // Validate score against the configured scale
+    let score_scale = ctx.accounts.config.score_scale;
+    require!(score <= score_scale, ReputationError::InvalidScore);
+
+    // Anti-Sybil: client must hold the configured minimum balance (not locked)
+    require!(
+        ctx.accounts
+            .config
+            .meets_min_client_balance(ctx.accounts.client.lamports()),
+        ReputationError::InsufficientClientBalance
+    );
+
+    // Validate URI length
+    require!(
+        ctx.accounts.config.accepts_uri_length(file_uri.len()),
+        ReputationError::UriTooLong
+    );
+
+    // Validate agent exists in Identity Registry via manual deserialization
+    // Required because agent_account discriminator differs across programs
+    // Agent owner is needed for feedbackAuth verification
+    let agent_owner =
+        agent_owner_from_data(&ctx.accounts.agent_account.try_borrow_data()?, agent_id)?;
+
+    // First-line defense against self-reviews: the owner's own wallet can't rate its agent
+    require!(
+        ctx.accounts.client.key() != agent_owner,
+        ReputationError::SelfFeedbackNotAllowed
+    );
+
+    // Agent owner blocked this client (existing feedback is unaffected)
+    require!(
+        ctx.accounts.client_block.data_is_empty(),
+        ReputationError::ClientBlocked
+    );
+
+    // Verify feedbackAuth signer is agent owner (ERC-8004 requirement)
+    require!(
+        feedback_auth.signer_address == agent_owner,
+        ReputationError::UnauthorizedSigner
+    );
+
+    // Get or initialize client index account
+    let client_index = &mut ctx.accounts.client_index;
+
+    // Determine current index for feedbackAuth verification
+    let current_index = if !client_index.is_initialized {
+        0u64 // First feedback from this client to this agent
+    } else {
+        client_index.last_index // Next feedback index
+    };
+
+    // Verify feedbackAuth (checks client, expiry, index_limit, signature)
+    let current_time = Clock::get()?.unix_timestamp;
+    feedback_auth.verify(&ctx.accounts.client.key(), current_index, current_time)?;
+
+    // Validate feedback_index matches expected
+    if !client_index.is_initialized {
+        // First feedback from this client to this agent
+        require!(feedback_index == 0, ReputationError::InvalidFeedbackIndex);
+        client_index.is_initialized = true;
+        client_index.agent_id = agent_id;
+        client_index.client_address = ctx.accounts.client.key();
+        client_index.bump = ctx.bumps.client_index;
+    } else {
+        // Subsequent feedback - validate index matches
+        require!(
+            feedback_index == client_index.last_index,
+            ReputationError::InvalidFeedbackIndex
+        );
+    }
+
+    // Increment index for next feedback
+    client_index.last_index = client_index
+        .last_index
+        .checked_add(1)
+        .ok_or(ReputationError::Overflow)?;
+
+    // Anti-Sybil fee, paid by the payer (sponsor or client)
+    let feedback_fee = ctx.accounts.config.feedback_fee_lamports;
+    if feedback_fee > 0 {
+        let treasury = ctx
+            .accounts
+            .treasury
+            .as_ref()
+            .ok_or(ReputationError::MissingTreasury)?;
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: treasury.to_account_info(),
+                },
+            ),
+            feedback_fee,
+        )?;
+    }
+
+    // The account is created without extra tags (GiveFeedback can't see them);
+    // grow it by exactly the tags stored
+    if !extra_tags.is_empty() {
+        erc8004_common::grow_account(
+            &ctx.accounts.feedback_account.to_account_info(),
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            FeedbackAccount::space_for(file_uri.len(), extra_tags.len()),
+        )?;
+    }
+
+    // Initialize feedback account
+    let feedback = &mut ctx.accounts.feedback_account;
+    feedback.agent_id = agent_id;
+    feedback.client_address = ctx.accounts.client.key();
+    feedback.feedback_index = feedback_index;
+    feedback.score = score;
+    feedback.score_scale = score_scale;
+    feedback.tag1 = tag1;
+    feedback.tag2 = tag2;
+    feedback.file_uri = file_uri.clone();
+    feedback.file_hash = file_hash;
+    feedback.file_size = file_size;
+    feedback.is_revoked = false;
+    feedback.created_at = Clock::get()?.unix_timestamp;
+    feedback.is_disputed = false;
+    feedback.extra_tags = extra_tags.clone();
+    feedback.bump = ctx.bumps.feedback_account;
+
+    // Normalize to the canonical 0-100 scale so aggregates stay comparable
+    let normalized_score = feedback.normalized_score();
+
+    // Update agent reputation metadata (cached stats)
+    let metadata = &mut ctx.accounts.agent_reputation;
+
+    if metadata.agent_id == 0 {
+        // First feedback for this agent - initialize
+        metadata.agent_id = agent_id;
+        metadata.bump = ctx.bumps.agent_reputation;
+    }
+
+    metadata.add_score(normalized_score, feedback.created_at)?;
+
+    // Per-tag counters (tag2 only when set and distinct from tag1), recorded
+    // on the feedback so revoke/update touch exactly the same ones
+    let payer = ctx.accounts.payer.to_account_info();
+    let mut tracked_tags = 0;
+    if count_tag(
+        &mut ctx.accounts.tag1_reputation,
+        ctx.bumps.tag1_reputation,
+        tag1,
+        normalized_score,
+        &ctx.accounts.config,
+        &mut ctx.accounts.agent_reputation,
+        &payer,
+    )? {
+        tracked_tags |= FeedbackAccount::TRACKED_TAG1;
+    }
+    if TagReputation::counts_tag2(&tag1, &tag2)
+        && count_tag(
+            ctx.accounts
+                .tag2_reputation
+                .as_mut()
+                .ok_or(ReputationError::MissingTagReputation)?,
+            ctx.bumps.tag2_reputation.unwrap_or_default(),
+            tag2,
+            normalized_score,
+            &ctx.accounts.config,
+            &mut ctx.accounts.agent_reputation,
+            &payer,
+        )?
+    {
+        tracked_tags |= FeedbackAccount::TRACKED_TAG2;
+    }
+    ctx.accounts.score_histogram.add_score(
+        agent_id,
+        ctx.bumps.score_histogram,
+        normalized_score,
+    )?;
+    ctx.accounts.feedback_account.tracked_tags = tracked_tags | FeedbackAccount::TRACKED_HISTOGRAM;
+
+    let metadata = &mut ctx.accounts.agent_reputation;
+    metadata.feedback_account_count = metadata
+        .feedback_account_count
+        .checked_add(1)
+        .ok_or(ReputationError::Overflow)?;
+
+    metadata.last_updated = Clock::get()?.unix_timestamp;
+
+    // Emit event
+    emit!(NewFeedback {
+        agent_id,
+        client_address: ctx.accounts.client.key(),
+        feedback_index,
+        score,
+        tag1,
+        tag2,
+        file_uri,
+        file_hash,
+        file_size,
+    });
+
+    if !extra_tags.is_empty() {
+        let mut tags = vec![tag1, tag2];
+        tags.extend(extra_tags);
+        emit!(NewFeedbackTags {
+            agent_id,
+            client_address: ctx.accounts.client.key(),
+            feedback_index,
+            tags,
+        });
+    }
+
+    msg!(
+        "Feedback created: agent_id={}, client={}, index={}, score={}",
+        agent_id,
+        ctx.accounts.client.key(),
+        feedback_index,
+        score
+    );
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = ReputationConfig::SIZE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, ReputationConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for authority-only configuration updates
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = authority.key() == config.authority @ ReputationError::UnauthorizedAuthority
+    )]
+    pub config: Account<'info, ReputationConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+/// Accounts for give_feedback instruction
+#[derive(Accounts)]
+#[instruction(agent_id: u64, _score: u8, tag1: [u8; 32], tag2: [u8; 32], file_uri: String, _file_hash: [u8; 32], feedback_index: u64, _feedback_auth: FeedbackAuth)]
+pub struct GiveFeedback<'info> {
+    /// Registry configuration (score scale)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ReputationConfig>,
+
+    /// Client giving the feedback (signer & author)
+    #[account(mut)]
+    pub client: Signer<'info>,
+
+    /// Payer for sponsorship (pays for account creation)
+    /// Can be a different wallet for sponsored feedback. For self-paid feedback
+    /// pass the client's pubkey here too: the runtime merges duplicate account
+    /// metas, so the transaction needs only the client's signature.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Agent NFT mint (required to derive agent PDA correctly)
+    /// This must be passed by the client who knows the agent's mint address
+    /// CHECK: Will be validated via agent_account PDA derivation
+    pub agent_mint: UncheckedAccount<'info>,
+
+    /// Agent account from Identity Registry (validation)
+    /// PDA derivation uses agent_mint to match Identity Registry's scheme
+    /// CHECK: Validated via PDA seeds, program ownership, and manual deserialization
+    #[account(
+        seeds = [b"agent", agent_mint.key().as_ref()],
+        bump,
+        seeds::program = identity_registry_program.key(),
+        constraint = agent_account.owner == &config.identity_registry @ ReputationError::AgentNotFound
+    )]
+    pub agent_account: UncheckedAccount<'info>,
+
+    /// Client index account (tracks next feedback index for this client-agent pair)
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = ClientIndexAccount::SIZE,
+        seeds = [b"client_index", agent_id.to_le_bytes().as_ref(), client.key().as_ref()],
+        bump
+    )]
+    pub client_index: Account<'info, ClientIndexAccount>,
+
+    /// Feedback account (one per feedback)
+    #[account(
+        init,
+        payer = payer,
+        space = FeedbackAccount::space_for(file_uri.len(), 0),
+        seeds = [
+            b"feedback",
+            agent_id.to_le_bytes().as_ref(),
+            client.key().as_ref(),
+            feedback_index.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub feedback_account: Account<'info, FeedbackAccount>,
+
+    /// Agent reputation metadata (cached stats)
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = AgentReputationMetadata::SIZE,
+        seeds = [b"agent_reputation", agent_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agent_reputation: Account<'info, AgentReputationMetadata>,
+
+    /// Per-tag counters for tag1 (closed again when it would exceed
+    /// `config.max_tracked_tags`)
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = TagReputation::SIZE,
+        seeds = [b"tag_reputation", agent_id.to_le_bytes().as_ref(), tag1.as_ref()],
+        bump
+    )]
+    pub tag1_reputation: Account<'info, TagReputation>,
+
+    /// Per-tag counters for tag2 (required when tag2 is set and differs from tag1)
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = TagReputation::SIZE,
+        seeds = [b"tag_reputation", agent_id.to_le_bytes().as_ref(), tag2.as_ref()],
+        bump
+    )]
+    pub tag2_reputation: Option<Account<'info, TagReputation>>,
+
+    /// Score distribution of the agent's feedback
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = ScoreHistogram::SIZE,
+        seeds = [b"histogram", agent_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub score_histogram: Account<'info, ScoreHistogram>,
+
+    /// Agent owner's block on this client (must not exist)
+    /// CHECK: PDA derived from seeds, only checked for being empty
+    #[account(
+        seeds = [b"client_block", agent_id.to_le_bytes().as_ref(), client.key().as_ref()],
+        bump
+    )]
+    pub client_block: UncheckedAccount<'info>,
+
+    /// Feedback fee recipient, required when `config.feedback_fee_lamports` is set
+    /// CHECK: Must be the treasury recorded in config, only receives lamports
+    #[account(mut, address = config.treasury @ ReputationError::InvalidTreasury)]
+    pub treasury: Option<UncheckedAccount<'info>>,
+
+    /// Identity Registry program (for CPI validation)
+    /// CHECK: Must be the Identity Registry recorded in config
+    #[account(address = config.identity_registry @ ReputationError::InvalidIdentityRegistry)]
+    pub identity_registry_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for revoke_feedback instruction
+#[derive(Accounts)]
+#[instruction(agent_id: u64, feedback_index: u64)]
+pub struct RevokeFeedback<'info> {
+    /// Registry configuration (edit window)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ReputationConfig>,
+
+    /// Client revoking their feedback (must be original author)
+    pub client: Signer<'info>,
+
+    /// Feedback account to revoke
+    /// CHECK: PDA derived from seeds; existence checked in the handler (`FeedbackNotFound`)
+    #[account(
+        mut,
+        seeds = [
+            b"feedback",
+            agent_id.to_le_bytes().as_ref(),
+            client.key().as_ref(),
+            feedback_index.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub feedback_account: UncheckedAccount<'info>,
+
+    /// Agent reputation metadata (update aggregates)
+    #[account(
+        mut,
+        seeds = [b"agent_reputation", agent_id.to_le_bytes().as_ref()],
+        bump = agent_reputation.bump
+    )]
+    pub agent_reputation: Account<'info, AgentReputationMetadata>,
+
+    /// Per-tag counters for the feedback's tag1 (required when it has its own
+    /// counter; matched to the feedback in the handler)
+    #[account(mut)]
+    pub tag1_reputation: Option<Account<'info, TagReputation>>,
+
+    /// Per-tag counters for the feedback's tag2 (required when it has its own counter)
+    #[account(mut)]
+    pub tag2_reputation: Option<Account<'info, TagReputation>>,
+
+    /// Score distribution of the agent's feedback (required when the
+    /// feedback was counted in it)
+    #[account(
+        mut,
+        seeds = [b"histogram", agent_id.to_le_bytes().as_ref()],
+        bump = score_histogram.bump
+    )]
+    pub score_histogram: Option<Account<'info, ScoreHistogram>>,
+}
+
+/// Accounts for update_feedback instruction
+#[derive(Accounts)]
+#[instruction(agent_id: u64, feedback_index: u64, _new_score: u8, new_file_uri: String)]
+pub struct UpdateFeedback<'info> {
+    /// Registry configuration (edit window, max URI length)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ReputationConfig>,
+
+    /// Client updating their feedback (must be original author)
+    /// Pays for (or is refunded) the resize when the URI length crosses 200 bytes
+    #[account(mut)]
+    pub client: Signer<'info>,
+
+    /// Feedback account to update
+    #[account(
+        mut,
+        seeds = [
+            b"feedback",
+            agent_id.to_le_bytes().as_ref(),
+            client.key().as_ref(),
+            feedback_index.to_le_bytes().as_ref()
+        ],
+        bump = feedback_account.bump,
+        realloc = FeedbackAccount::space_for(
+            new_file_uri.len(),
+            feedback_account.extra_tags.len()
+        ),
+        realloc::payer = client,
+        realloc::zero = false
+    )]
+    pub feedback_account: Account<'info, FeedbackAccount>,
+
+    /// Agent reputation metadata (update aggregates)
+    #[account(
+        mut,
+        seeds = [b"agent_reputation", agent_id.to_le_bytes().as_ref()],
+        bump = agent_reputation.bump
+    )]
+    pub agent_reputation: Account<'info, AgentReputationMetadata>,
 
-/// Accounts for give_feedback instruction
+    /// Per-tag counters for the feedback's tag1 (required when it has its own counter)
+    #[account(
+        mut,
+        seeds = [b"tag_reputation", agent_id.to_le_bytes().as_ref(), feedback_account.tag1.as_ref()],
+        bump = tag1_reputation.bump
+    )]
+    pub tag1_reputation: Option<Account<'info, TagReputation>>,
+
+    /// Per-tag counters for the feedback's tag2 (required when it has its own counter)
+    #[account(
+        mut,
+        seeds = [b"tag_reputation", agent_id.to_le_bytes().as_ref(), feedback_account.tag2.as_ref()],
+        bump = tag2_reputation.bump
+    )]
+    pub tag2_reputation: Option<Account<'info, TagReputation>>,
+
+    /// Score distribution of the agent's feedback (required when the
+    /// feedback was counted in it)
+    #[account(
+        mut,
+        seeds = [b"histogram", agent_id.to_le_bytes().as_ref()],
+        bump = score_histogram.bump
+    )]
+    pub score_histogram: Option<Account<'info, ScoreHistogram>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for close_feedback instruction
 #[derive(Accounts)]
-#[instruction(agent_id: u64, _score: u8, _tag1: [u8; 32], _tag2: [u8; 32], _file_uri: String, _file_hash: [u8; 32], feedback_index: u64, _feedback_auth: FeedbackAuth)]
-pub struct GiveFeedback<'info> {
-    /// Client giving the feedback (signer & author)
+#[instruction(agent_id: u64, feedback_index: u64)]
+pub struct CloseFeedback<'info> {
+    /// Feedback author (receives the rent)
     #[account(mut)]
     pub client: Signer<'info>,
 
-    /// Payer for sponsorship (pays for account creation)
-    /// Can be same as client or different wallet for sponsored feedback
-    #[account(mut)]
-    pub payer: Signer<'info>,
+    /// Revoked feedback account to close
+    #[account(
+        mut,
+        close = client,
+        seeds = [
+            b"feedback",
+            agent_id.to_le_bytes().as_ref(),
+            client.key().as_ref(),
+            feedback_index.to_le_bytes().as_ref()
+        ],
+        bump = feedback_account.bump
+    )]
+    pub feedback_account: Account<'info, FeedbackAccount>,
+
+    /// Agent reputation metadata (feedback_account_count)
+    #[account(
+        mut,
+        seeds = [b"agent_reputation", agent_id.to_le_bytes().as_ref()],
+        bump = agent_reputation.bump
+    )]
+    pub agent_reputation: Account<'info, AgentReputationMetadata>,
+}
+
+/// Accounts for dispute_feedback instruction
+#[derive(Accounts)]
+#[instruction(agent_id: u64, client_address: Pubkey, feedback_index: u64)]
+pub struct DisputeFeedback<'info> {
+    /// Registry configuration (Identity Registry program ID)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ReputationConfig>,
+
+    /// Agent owner
+    pub owner: Signer<'info>,
 
     /// Agent NFT mint (required to derive agent PDA correctly)
-    /// This must be passed by the client who knows the agent's mint address
     /// CHECK: Will be validated via agent_account PDA derivation
     pub agent_mint: UncheckedAccount<'info>,
 
-    /// Agent account from Identity Registry (validation)
-    /// PDA derivation uses agent_mint to match Identity Registry's scheme
+    /// Agent account from Identity Registry (ownership check)
     /// CHECK: Validated via PDA seeds, program ownership, and manual deserialization
     #[account(
         seeds = [b"agent", agent_mint.key().as_ref()],
         bump,
-        seeds::program = identity_registry_program.key()
+        seeds::program = identity_registry_program.key(),
+        constraint = agent_account.owner == &config.identity_registry @ ReputationError::AgentNotFound
     )]
     pub agent_account: UncheckedAccount<'info>,
 
-    /// Client index account (tracks next feedback index for this client-agent pair)
-    #[account(
-        init_if_needed,
-        payer = payer,
-        space = ClientIndexAccount::SIZE,
-        seeds = [b"client_index", agent_id.to_le_bytes().as_ref(), client.key().as_ref()],
-        bump
-    )]
-    pub client_index: Account<'info, ClientIndexAccount>,
-
-    /// Feedback account (one per feedback)
+    /// Feedback account to flag
     #[account(
-        init,
-        payer = payer,
-        space = FeedbackAccount::MAX_SIZE,
+        mut,
         seeds = [
             b"feedback",
             agent_id.to_le_bytes().as_ref(),
-            client.key().as_ref(),
+            client_address.as_ref(),
             feedback_index.to_le_bytes().as_ref()
         ],
-        bump
+        bump = feedback_account.bump
     )]
     pub feedback_account: Account<'info, FeedbackAccount>,
 
-    /// Agent reputation metadata (cached stats)
+    /// Identity Registry program
+    /// CHECK: Must be the Identity Registry recorded in config
+    #[account(address = config.identity_registry @ ReputationError::InvalidIdentityRegistry)]
+    pub identity_registry_program: UncheckedAccount<'info>,
+}
+
+/// Accounts for block_client instruction
+#[derive(Accounts)]
+#[instruction(agent_id: u64, client: Pubkey)]
+pub struct BlockClient<'info> {
+    /// Registry configuration (Identity Registry program ID)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ReputationConfig>,
+
+    /// Agent owner (pays for the block account)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Agent NFT mint (required to derive agent PDA correctly)
+    /// CHECK: Will be validated via agent_account PDA derivation
+    pub agent_mint: UncheckedAccount<'info>,
+
+    /// Agent account from Identity Registry (ownership check)
+    /// CHECK: Validated via PDA seeds, program ownership, and manual deserialization
+    #[account(
+        seeds = [b"agent", agent_mint.key().as_ref()],
+        bump,
+        seeds::program = identity_registry_program.key(),
+        constraint = agent_account.owner == &config.identity_registry @ ReputationError::AgentNotFound
+    )]
+    pub agent_account: UncheckedAccount<'info>,
+
+    /// Block record for this client
+    #[account(
+        init,
+        payer = owner,
+        space = ClientBlock::SIZE,
+        seeds = [b"client_block", agent_id.to_le_bytes().as_ref(), client.as_ref()],
+        bump
+    )]
+    pub client_block: Account<'info, ClientBlock>,
+
+    /// Identity Registry program
+    /// CHECK: Must be the Identity Registry recorded in config
+    #[account(address = config.identity_registry @ ReputationError::InvalidIdentityRegistry)]
+    pub identity_registry_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for unblock_client instruction
+#[derive(Accounts)]
+#[instruction(agent_id: u64, client: Pubkey)]
+pub struct UnblockClient<'info> {
+    /// Registry configuration (Identity Registry program ID)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ReputationConfig>,
+
+    /// Agent owner (receives the rent)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Agent NFT mint (required to derive agent PDA correctly)
+    /// CHECK: Will be validated via agent_account PDA derivation
+    pub agent_mint: UncheckedAccount<'info>,
+
+    /// Agent account from Identity Registry (ownership check)
+    /// CHECK: Validated via PDA seeds, program ownership, and manual deserialization
+    #[account(
+        seeds = [b"agent", agent_mint.key().as_ref()],
+        bump,
+        seeds::program = identity_registry_program.key(),
+        constraint = agent_account.owner == &config.identity_registry @ ReputationError::AgentNotFound
+    )]
+    pub agent_account: UncheckedAccount<'info>,
+
+    /// Block record to close
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"client_block", agent_id.to_le_bytes().as_ref(), client.as_ref()],
+        bump = client_block.bump
+    )]
+    pub client_block: Account<'info, ClientBlock>,
+
+    /// Identity Registry program
+    /// CHECK: Must be the Identity Registry recorded in config
+    #[account(address = config.identity_registry @ ReputationError::InvalidIdentityRegistry)]
+    pub identity_registry_program: UncheckedAccount<'info>,
+}
+
+/// Accounts for set_reputation_policy instruction
+#[derive(Accounts)]
+#[instruction(agent_id: u64)]
+pub struct SetReputationPolicy<'info> {
+    /// Registry configuration (supported policies)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ReputationConfig>,
+
+    /// Agent owner (pays for the reputation account if no feedback exists yet)
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Agent NFT mint (required to derive agent PDA correctly)
+    /// CHECK: Will be validated via agent_account PDA derivation
+    pub agent_mint: UncheckedAccount<'info>,
+
+    /// Agent account from Identity Registry (ownership check)
+    /// CHECK: Validated via PDA seeds, program ownership, and manual deserialization
+    #[account(
+        seeds = [b"agent", agent_mint.key().as_ref()],
+        bump,
+        seeds::program = identity_registry_program.key(),
+        constraint = agent_account.owner == &config.identity_registry @ ReputationError::AgentNotFound
+    )]
+    pub agent_account: UncheckedAccount<'info>,
+
+    /// Agent reputation metadata (policy and cached stats)
     #[account(
         init_if_needed,
-        payer = payer,
+        payer = owner,
         space = AgentReputationMetadata::SIZE,
         seeds = [b"agent_reputation", agent_id.to_le_bytes().as_ref()],
         bump
     )]
     pub agent_reputation: Account<'info, AgentReputationMetadata>,
 
-    /// Identity Registry program (for CPI validation)
-    /// CHECK: Program ID verified via seeds::program constraint
+    /// Identity Registry program
+    /// CHECK: Must be the Identity Registry recorded in config
+    #[account(address = config.identity_registry @ ReputationError::InvalidIdentityRegistry)]
     pub identity_registry_program: UncheckedAccount<'info>,
 
     pub system_program: Program<'info, System>,
 }
 
-/// Accounts for revoke_feedback instruction
+/// Accounts for verify_reputation_consistency view
 #[derive(Accounts)]
-#[instruction(agent_id: u64, feedback_index: u64)]
-pub struct RevokeFeedback<'info> {
-    /// Client revoking their feedback (must be original author)
-    pub client: Signer<'info>,
+#[instruction(agent_id: u64)]
+pub struct VerifyReputationConsistency<'info> {
+    /// Agent reputation metadata (cached stats to audit)
+    #[account(
+        seeds = [b"agent_reputation", agent_id.to_le_bytes().as_ref()],
+        bump = agent_reputation.bump
+    )]
+    pub agent_reputation: Account<'info, AgentReputationMetadata>,
+}
 
-    /// Feedback account to revoke
+/// Accounts for recompute_reputation (feedback accounts via remaining_accounts)
+#[derive(Accounts)]
+#[instruction(agent_id: u64)]
+pub struct RecomputeReputation<'info> {
+    /// Agent reputation metadata (cached stats to overwrite)
     #[account(
         mut,
-        seeds = [
-            b"feedback",
-            agent_id.to_le_bytes().as_ref(),
-            client.key().as_ref(),
-            feedback_index.to_le_bytes().as_ref()
-        ],
-        bump = feedback_account.bump
+        seeds = [b"agent_reputation", agent_id.to_le_bytes().as_ref()],
+        bump = agent_reputation.bump
     )]
-    pub feedback_account: Account<'info, FeedbackAccount>,
+    pub agent_reputation: Account<'info, AgentReputationMetadata>,
 
-    /// Agent reputation metadata (update aggregates)
+    /// Anyone (the repair can only converge to the feedback accounts' truth)
+    pub caller: Signer<'info>,
+}
+
+/// Accounts for state_commitment view (feedback accounts via remaining_accounts)
+#[derive(Accounts)]
+pub struct StateCommitment<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ReputationConfig>,
+}
+
+/// Accounts for snapshot_leaderboard instruction
+#[derive(Accounts)]
+#[instruction(epoch: u64)]
+pub struct SnapshotLeaderboard<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = authority.key() == config.authority @ ReputationError::UnauthorizedAuthority
+    )]
+    pub config: Account<'info, ReputationConfig>,
+
+    /// Snapshot PDA for this epoch (init fails if it already exists)
+    #[account(
+        init,
+        payer = authority,
+        space = LeaderboardSnapshot::SIZE,
+        seeds = [b"leaderboard", epoch.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub snapshot: Account<'info, LeaderboardSnapshot>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Accounts for mint_reputation_attestation instruction
+#[derive(Accounts)]
+#[instruction(agent_id: u64)]
+pub struct MintReputationAttestation<'info> {
+    /// Registry configuration (Identity Registry program ID)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ReputationConfig>,
+
+    /// CHECK: Will be validated via agent_account PDA derivation
+    pub agent_mint: UncheckedAccount<'info>,
+
+    /// Agent account from Identity Registry (owner check)
+    /// CHECK: Validated via PDA seeds and manual deserialization
+    #[account(
+        seeds = [b"agent", agent_mint.key().as_ref()],
+        bump,
+        seeds::program = identity_registry_program.key(),
+        constraint = agent_account.owner == &config.identity_registry @ ReputationError::AgentNotFound
+    )]
+    pub agent_account: UncheckedAccount<'info>,
+
+    /// Agent reputation metadata (stats being attested)
     #[account(
-        mut,
         seeds = [b"agent_reputation", agent_id.to_le_bytes().as_ref()],
         bump = agent_reputation.bump
     )]
     pub agent_reputation: Account<'info, AgentReputationMetadata>,
+
+    /// Latest attestation for this agent (rate limit)
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = ReputationAttestationRecord::SIZE,
+        seeds = [b"reputation_attestation", agent_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub attestation_record: Account<'info, ReputationAttestationRecord>,
+
+    /// Program PDA holding mint, freeze and update authority over attestations
+    /// CHECK: PDA signer only, holds no data
+    #[account(seeds = [b"attestation_authority"], bump)]
+    pub attestation_authority: UncheckedAccount<'info>,
+
+    /// Attestation NFT mint (created by this instruction)
+    #[account(
+        init,
+        payer = owner,
+        mint::decimals = 0,
+        mint::authority = attestation_authority,
+        mint::freeze_authority = attestation_authority,
+    )]
+    pub attestation_mint: Account<'info, Mint>,
+
+    /// CHECK: Created by Metaplex CPI
+    #[account(mut)]
+    pub attestation_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Created by Metaplex CPI
+    #[account(mut)]
+    pub attestation_master_edition: UncheckedAccount<'info>,
+
+    /// Owner's token account for the attestation (frozen after minting)
+    #[account(
+        init,
+        payer = owner,
+        associated_token::mint = attestation_mint,
+        associated_token::authority = owner,
+    )]
+    pub attestation_token_account: Account<'info, TokenAccount>,
+
+    /// Agent owner
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// Identity Registry program
+    /// CHECK: Must be the Identity Registry recorded in config
+    #[account(address = config.identity_registry @ ReputationError::InvalidIdentityRegistry)]
+    pub identity_registry_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Metaplex Token Metadata program
+    pub token_metadata_program: Program<'info, Metadata>,
+
+    /// Sysvar Instructions
+    /// CHECK: Sysvar account
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub sysvar_instructions: UncheckedAccount<'info>,
 }
 
 /// Accounts for append_response instruction
 #[derive(Accounts)]
-#[instruction(agent_id: u64, client_address: Pubkey, feedback_index: u64, _response_uri: String, _response_hash: [u8; 32])]
+#[instruction(agent_id: u64, client_address: Pubkey, feedback_index: u64, response_uri: String, _response_hash: [u8; 32])]
 pub struct AppendResponse<'info> {
+    /// Registry configuration (require_live_agent)
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ReputationConfig>,
+
     /// Responder (can be anyone - agent, aggregator, etc.)
     pub responder: Signer<'info>,
 
@@ -475,7 +2469,8 @@ pub struct AppendResponse<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
-    /// Feedback account being responded to (validation)
+    /// Feedback account being responded to
+    /// CHECK: PDA derived from seeds; existence checked in the handler (`FeedbackNotFound`)
     #[account(
         seeds = [
             b"feedback",
@@ -483,9 +2478,9 @@ pub struct AppendResponse<'info> {
             client_address.as_ref(),
             feedback_index.to_le_bytes().as_ref()
         ],
-        bump = feedback_account.bump
+        bump
     )]
-    pub feedback_account: Account<'info, FeedbackAccount>,
+    pub feedback_account: UncheckedAccount<'info>,
 
     /// Response index account (tracks next response index for this feedback)
     #[account(
@@ -506,7 +2501,7 @@ pub struct AppendResponse<'info> {
     #[account(
         init,
         payer = payer,
-        space = ResponseAccount::MAX_SIZE,
+        space = ResponseAccount::space_for(response_uri.len()),
         seeds = [
             b"response",
             agent_id.to_le_bytes().as_ref(),
@@ -518,6 +2513,64 @@ pub struct AppendResponse<'info> {
     )]
     pub response_account: Account<'info, ResponseAccount>,
 
+    /// Agent NFT mint (only needed when config.require_live_agent is set)
+    /// CHECK: Used to derive the expected agent_account PDA
+    pub agent_mint: Option<UncheckedAccount<'info>>,
+
+    /// Agent account from Identity Registry (only needed when config.require_live_agent is set)
+    /// CHECK: PDA derivation and agent_id checked in the handler
+    pub agent_account: Option<UncheckedAccount<'info>>,
+
+    /// Identity Registry program (only needed when config.require_live_agent is set)
+    /// CHECK: Checked against config.identity_registry in the handler
+    pub identity_registry_program: Option<UncheckedAccount<'info>>,
+
     pub system_program: Program<'info, System>,
 }
 
+/// Accounts for revoke_response instruction
+#[derive(Accounts)]
+#[instruction(agent_id: u64, client_address: Pubkey, feedback_index: u64, response_index: u64)]
+pub struct RevokeResponse<'info> {
+    /// Original responder
+    pub responder: Signer<'info>,
+
+    /// Response account to revoke
+    /// CHECK: PDA derived from seeds; existence checked in the handler (`ResponseNotFound`)
+    #[account(
+        mut,
+        seeds = [
+            b"response",
+            agent_id.to_le_bytes().as_ref(),
+            client_address.as_ref(),
+            feedback_index.to_le_bytes().as_ref(),
+            response_index.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub response_account: UncheckedAccount<'info>,
+}
+
+/// Accounts for close_response instruction
+#[derive(Accounts)]
+#[instruction(agent_id: u64, client_address: Pubkey, feedback_index: u64, response_index: u64)]
+pub struct CloseResponse<'info> {
+    /// Original responder (receives the rent)
+    #[account(mut)]
+    pub responder: Signer<'info>,
+
+    /// Response account to close
+    #[account(
+        mut,
+        close = responder,
+        seeds = [
+            b"response",
+            agent_id.to_le_bytes().as_ref(),
+            client_address.as_ref(),
+            feedback_index.to_le_bytes().as_ref(),
+            response_index.to_le_bytes().as_ref()
+        ],
+        bump = response_account.bump
+    )]
+    pub response_account: Account<'info, ResponseAccount>,
+}
@@ -1,5 +1,274 @@
 use anchor_lang::prelude::*;
 
+use crate::error::ReputationError;
+
+/// Global reputation registry configuration
+/// Seeds: [b"config"]
+#[account]
+pub struct ReputationConfig {
+    /// Registry authority (admin)
+    pub authority: Pubkey,
+
+    /// Maximum score accepted in give_feedback (scores range 0..=score_scale)
+    /// Aggregates always use the canonical 0-100 scale
+    pub score_scale: u8,
+
+    /// Require append_response to prove the agent is still registered in the
+    /// Identity Registry (off by default so historical feedback stays answerable)
+    pub require_live_agent: bool,
+
+    /// Seconds after creation during which a client may still revoke its
+    /// feedback (0 = unlimited, feedback stays mutable forever)
+    pub edit_window: i64,
+
+    /// Bitmask of `ReputationPolicy` values agents may select (bit = 1 << policy)
+    pub supported_policies: u8,
+
+    /// Minimum lamports the client wallet must hold to give feedback
+    /// (anti-Sybil balance check, nothing is locked; 0 = disabled)
+    pub min_client_balance: u64,
+
+    /// Identity Registry program ID, set once at initialize
+    /// Agent accounts must be owned by this program
+    pub identity_registry: Pubkey,
+
+    /// Lamports transferred from the payer to `treasury` per feedback
+    /// (anti-Sybil fee; 0 = free)
+    pub feedback_fee_lamports: u64,
+
+    /// Recipient of feedback fees
+    pub treasury: Pubkey,
+
+    /// Maximum feedback and response URI length accepted by this deployment
+    /// (1..=MAX_URI_LENGTH, defaults to DEFAULT_URI_LENGTH)
+    pub max_uri_length: u16,
+
+    /// Distinct tags per agent that get a `TagReputation` counter; feedback
+    /// with further new tags only counts toward the agent aggregate
+    pub max_tracked_tags: u16,
+
+    /// Validation Registry program ID (owner of the `AgentValidationMetadata`
+    /// accounts `get_trust_score` reads), settable by the authority
+    pub validation_registry: Pubkey,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ReputationConfig {
+    /// Size calculation
+    /// 8 (discriminator) + 32 (authority) + 1 (score_scale) + 1 (require_live_agent)
+    /// + 8 (edit_window) + 1 (supported_policies) + 8 (min_client_balance)
+    /// + 32 (identity_registry) + 8 (feedback_fee_lamports) + 32 (treasury)
+    /// + 2 (max_uri_length) + 2 (max_tracked_tags) + 32 (validation_registry) + 1 (bump)
+    pub const SIZE: usize = 8 + 32 + 1 + 1 + 8 + 1 + 8 + 32 + 8 + 32 + 2 + 2 + 32 + 1;
+
+    /// Default `max_tracked_tags`
+    pub const DEFAULT_MAX_TRACKED_TAGS: u16 = 64;
+
+    /// Whether an agent already tracking `tracked_tag_count` tags may start
+    /// tracking another one
+    pub fn can_track_new_tag(&self, tracked_tag_count: u16) -> bool {
+        tracked_tag_count < self.max_tracked_tags
+    }
+
+    /// Default score scale (ERC-8004 spec: 0-100)
+    pub const DEFAULT_SCORE_SCALE: u8 = 100;
+
+    /// Default `max_uri_length` (ERC-8004 spec)
+    pub const DEFAULT_URI_LENGTH: u16 = 200;
+
+    /// Highest `max_uri_length` a deployment may configure
+    pub const MAX_URI_LENGTH: u16 = 512;
+
+    /// Whether `max_uri_length` is a limit this registry supports
+    pub fn is_valid_max_uri_length(max_uri_length: u16) -> bool {
+        max_uri_length > 0 && max_uri_length <= Self::MAX_URI_LENGTH
+    }
+
+    /// Whether a feedback or response URI of `len` bytes is accepted
+    pub fn accepts_uri_length(&self, len: usize) -> bool {
+        len <= self.max_uri_length as usize
+    }
+
+    /// Whether agents of this registry may select `policy`
+    pub fn supports_policy(&self, policy: u8) -> bool {
+        ReputationPolicy::from_u8(policy).is_some() && self.supported_policies & (1 << policy) != 0
+    }
+
+    /// Whether a client holding `lamports` may give feedback
+    pub fn meets_min_client_balance(&self, lamports: u64) -> bool {
+        lamports >= self.min_client_balance
+    }
+
+    /// Whether feedback created at `created_at` may still be modified at `now`
+    pub fn is_editable(&self, created_at: i64, now: i64) -> bool {
+        self.edit_window == 0 || now <= created_at.saturating_add(self.edit_window)
+    }
+}
+
+/// Aggregation strategy used for an agent's `average_score`
+///
+/// The registry maintains the running sums of every strategy, so an agent can
+/// switch policy at any time without replaying its feedback history.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ReputationPolicy {
+    /// Plain mean of all non-revoked scores (default)
+    Mean = 0,
+    /// Mean of scores within TRIM_MIN_SCORE..=TRIM_MAX_SCORE, so drive-by 0s
+    /// and 100s do not dominate (falls back to the mean if all are extreme)
+    Trimmed = 1,
+    /// Recency-weighted mean: each feedback weighs 1 + DECAY_PERIOD periods
+    /// elapsed since DECAY_ORIGIN when it was given, de-emphasizing old feedback
+    Decayed = 2,
+}
+
+impl ReputationPolicy {
+    /// Bitmask with every policy enabled
+    pub const ALL: u8 = 0b111;
+
+    /// Lowest normalized score kept by the trimmed policy
+    pub const TRIM_MIN_SCORE: u8 = 10;
+
+    /// Highest normalized score kept by the trimmed policy
+    pub const TRIM_MAX_SCORE: u8 = 90;
+
+    /// Start of recency weighting (2025-01-01T00:00:00Z)
+    pub const DECAY_ORIGIN: i64 = 1_735_689_600;
+
+    /// Seconds per recency weight step (30 days)
+    pub const DECAY_PERIOD: i64 = 30 * 24 * 60 * 60;
+
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Mean),
+            1 => Some(Self::Trimmed),
+            2 => Some(Self::Decayed),
+            _ => None,
+        }
+    }
+
+    /// Whether a normalized score counts toward the trimmed mean
+    pub fn is_within_trim(score: u8) -> bool {
+        (Self::TRIM_MIN_SCORE..=Self::TRIM_MAX_SCORE).contains(&score)
+    }
+
+    /// Recency weight of feedback created at `created_at` (always >= 1)
+    pub fn decay_weight(created_at: i64) -> u64 {
+        1 + (created_at.saturating_sub(Self::DECAY_ORIGIN).max(0) / Self::DECAY_PERIOD) as u64
+    }
+}
+
+/// Normalize a score submitted on a `scale` (0..=scale) to the canonical 0-100 scale
+///
+/// Rounds half up so e.g. 2/3 on a 3-point scale becomes 67.
+pub fn normalize_score(score: u8, scale: u8) -> u8 {
+    if scale == 0 || scale == ReputationConfig::DEFAULT_SCORE_SCALE {
+        return score;
+    }
+    ((score as u32 * 100 + scale as u32 / 2) / scale as u32) as u8
+}
+
+/// Read the owner of `agent_id` from raw Identity Registry AgentAccount data
+///
+/// Identity accounts are deserialized by hand because their discriminator
+/// belongs to another program: 8 (discriminator) + 8 (agent_id) + 32 (owner).
+pub fn agent_owner_from_data(agent_data: &[u8], agent_id: u64) -> Result<Pubkey> {
+    require!(agent_data.len() >= 8 + 8 + 32, ReputationError::AgentNotFound);
+
+    let stored_agent_id = u64::from_le_bytes(
+        agent_data[8..16]
+            .try_into()
+            .map_err(|_| ReputationError::AgentNotFound)?
+    );
+    require!(stored_agent_id == agent_id, ReputationError::AgentNotFound);
+
+    let owner_bytes: [u8; 32] = agent_data[16..48]
+        .try_into()
+        .map_err(|_| ReputationError::AgentNotFound)?;
+    Ok(Pubkey::new_from_array(owner_bytes))
+}
+
+/// Devnet Validation Registry program, default `ReputationConfig::validation_registry`
+pub const DEFAULT_VALIDATION_REGISTRY_ID: Pubkey = pubkey!("CXvuHNGWTHNqXmWr95wSpNGKR3kpcJUhzKofTF3zsoxW");
+
+/// Anchor discriminator of the Validation Registry's `AgentValidationMetadata`
+/// (sha256("account:AgentValidationMetadata")[..8])
+pub const AGENT_VALIDATION_DISCRIMINATOR: [u8; 8] = [165, 146, 118, 34, 242, 101, 143, 180];
+
+/// Read `(total_responses, average_response)` of `agent_id` from raw
+/// Validation Registry `AgentValidationMetadata` data
+///
+/// Layout after the 8-byte discriminator: agent_id (8 bytes),
+/// total_requests (8), total_responses (8), sum_responses (8). The average
+/// rounds half up like `AgentValidationMetadata::average_response`.
+pub fn validation_summary_from_data(data: &[u8], agent_id: u64) -> Result<(u64, u8)> {
+    require!(data.len() >= 8 + 8 * 4, ReputationError::InvalidValidationMetadata);
+    require!(
+        data[..8] == AGENT_VALIDATION_DISCRIMINATOR,
+        ReputationError::InvalidValidationMetadata
+    );
+
+    let read_u64 = |offset: usize| -> Result<u64> {
+        Ok(u64::from_le_bytes(
+            data[offset..offset + 8]
+                .try_into()
+                .map_err(|_| ReputationError::InvalidValidationMetadata)?,
+        ))
+    };
+    require!(read_u64(8)? == agent_id, ReputationError::InvalidValidationMetadata);
+
+    let total_responses = read_u64(24)?;
+    let sum_responses = read_u64(32)?;
+    if total_responses == 0 {
+        return Ok((0, 0));
+    }
+    let average = (sum_responses + total_responses / 2) / total_responses;
+    Ok((total_responses, average.min(100) as u8))
+}
+
+/// Basis points of a full trust score weight (feedback + validation)
+pub const TRUST_WEIGHT_TOTAL_BPS: u16 = 10_000;
+
+/// Blend the feedback and validation averages into one 0-100 trust score
+///
+/// `feedback_weight_bps` weighs the feedback average; the validation average
+/// gets the remaining `10_000 - feedback_weight_bps`. A side without any data
+/// (zero count) is ignored and the other side's average is returned as is;
+/// with no data at all the score is 0. Rounds half up.
+pub fn blend_trust_score(
+    feedback_average: u8,
+    feedback_count: u64,
+    validation_average: u8,
+    validation_count: u64,
+    feedback_weight_bps: u16,
+) -> u8 {
+    match (feedback_count, validation_count) {
+        (0, 0) => 0,
+        (_, 0) => feedback_average,
+        (0, _) => validation_average,
+        _ => {
+            let total = TRUST_WEIGHT_TOTAL_BPS as u32;
+            let feedback_weight = feedback_weight_bps.min(TRUST_WEIGHT_TOTAL_BPS) as u32;
+            let weighted = feedback_average as u32 * feedback_weight
+                + validation_average as u32 * (total - feedback_weight);
+            ((weighted + total / 2) / total) as u8
+        }
+    }
+}
+
+/// Domain prefix of reputation state commitments
+pub const STATE_COMMITMENT_DOMAIN: &[u8] = b"erc8004:reputation:state:v1";
+
+/// Commit to an agent's feedback accounts as a single SHA-256 hash
+///
+/// See `erc8004_common::state_commitment` for the canonical form, with
+/// `STATE_COMMITMENT_DOMAIN` as the domain.
+pub fn state_commitment(agent_id: u64, accounts: Vec<(Pubkey, Vec<u8>)>) -> [u8; 32] {
+    erc8004_common::state_commitment(STATE_COMMITMENT_DOMAIN, agent_id, accounts)
+}
+
 /// Feedback account - One per feedback (per client-agent pair)
 /// Seeds: [b"feedback", agent_id, client_address, feedback_index]
 #[account]
@@ -15,9 +284,12 @@ pub struct FeedbackAccount {
     /// Client B: indices 0, 1, 2, 3... (independent)
     pub feedback_index: u64,
 
-    /// Score (0-100, validated on-chain)
+    /// Score as submitted (0..=score_scale, validated on-chain)
     pub score: u8,
 
+    /// Score scale in effect when the feedback was submitted
+    pub score_scale: u8,
+
     /// Tag1 - Full bytes32 (ERC-8004 spec requirement)
     pub tag1: [u8; 32],
 
@@ -30,12 +302,27 @@ pub struct FeedbackAccount {
     /// File hash (SHA-256, 32 bytes)
     pub file_hash: [u8; 32],
 
+    /// File size in bytes as declared by the client (advisory, not verified; 0 = unknown)
+    pub file_size: u64,
+
     /// Revocation status (preserves audit trail)
     pub is_revoked: bool,
 
     /// Creation timestamp
     pub created_at: i64,
 
+    /// Flagged by the agent owner as abusive (informational, aggregates unchanged)
+    pub is_disputed: bool,
+
+    /// Tags beyond tag1/tag2 (max 6, no per-tag counters)
+    pub extra_tags: Vec<[u8; 32]>,
+
+    /// `TagReputation` counters this feedback was added to
+    /// (`TRACKED_TAG1` | `TRACKED_TAG2`); tags past the agent's
+    /// `max_tracked_tags` have none. `TRACKED_HISTOGRAM` is set when it was
+    /// counted in the `ScoreHistogram` (unset for older feedback)
+    pub tracked_tags: u8,
+
     /// PDA bump seed
     pub bump: u8,
 }
@@ -43,12 +330,61 @@ pub struct FeedbackAccount {
 impl FeedbackAccount {
     /// Maximum size calculation
     /// 8 (discriminator) + 8 (agent_id) + 32 (client_address) + 8 (feedback_index)
-    /// + 1 (score) + 32 (tag1) + 32 (tag2) + 4 + 200 (file_uri)
-    /// + 32 (file_hash) + 1 (is_revoked) + 8 (created_at) + 1 (bump)
-    pub const MAX_SIZE: usize = 8 + 8 + 32 + 8 + 1 + 32 + 32 + 4 + 200 + 32 + 1 + 8 + 1;
+    /// + 1 (score) + 1 (score_scale) + 32 (tag1) + 32 (tag2) + 4 + 200 (file_uri)
+    /// + 32 (file_hash) + 8 (file_size) + 1 (is_revoked) + 8 (created_at)
+    /// + 1 (is_disputed) + 4 + 6 * 32 (extra_tags) + 1 (tracked_tags) + 1 (bump)
+    pub const MAX_SIZE: usize = Self::BASE_SIZE + Self::MAX_EXTRA_TAGS * 32;
 
-    /// Maximum URI length (ERC-8004 spec)
-    pub const MAX_URI_LENGTH: usize = 200;
+    /// Size without extra tags (empty extra_tags vector, 200-byte URI)
+    pub const BASE_SIZE: usize =
+        8 + 8 + 32 + 8 + 1 + 1 + 32 + 32 + 4 + 200 + 32 + 8 + 1 + 8 + 1 + 4 + 1 + 1;
+
+    /// `tracked_tags` bit: counted in tag1's `TagReputation`
+    pub const TRACKED_TAG1: u8 = 1 << 0;
+
+    /// `tracked_tags` bit: counted in tag2's `TagReputation`
+    pub const TRACKED_TAG2: u8 = 1 << 1;
+
+    /// `tracked_tags` bit: counted in the agent's `ScoreHistogram`
+    pub const TRACKED_HISTOGRAM: u8 = 1 << 2;
+
+    /// Maximum number of tags beyond tag1/tag2
+    pub const MAX_EXTRA_TAGS: usize = 6;
+
+    /// Maximum number of tags per feedback (tag1 + tag2 + extra tags)
+    pub const MAX_TAGS: usize = 2 + Self::MAX_EXTRA_TAGS;
+
+    /// URI bytes included in MAX_SIZE (the default `max_uri_length`)
+    pub const RESERVED_URI_LENGTH: usize = 200;
+
+    /// Account space for a feedback whose URI is `uri_len` bytes and that
+    /// stores `extra_tag_count` extra tags
+    ///
+    /// URIs up to RESERVED_URI_LENGTH fit BASE_SIZE; a longer one (allowed by a
+    /// raised `max_uri_length`) grows the account by the excess only, and each
+    /// extra tag adds its 32 bytes, so ordinary feedback doesn't pay rent for
+    /// either ceiling.
+    pub fn space_for(uri_len: usize, extra_tag_count: usize) -> usize {
+        Self::BASE_SIZE
+            + extra_tag_count * 32
+            + uri_len.saturating_sub(Self::RESERVED_URI_LENGTH)
+    }
+
+    /// Score contribution to the aggregates (canonical 0-100 scale)
+    pub fn normalized_score(&self) -> u8 {
+        normalize_score(self.score, self.score_scale)
+    }
+
+    /// Whether the feedback was counted in the `TagReputation` of `flag`
+    /// (`TRACKED_TAG1` or `TRACKED_TAG2`)
+    pub fn tracks_tag(&self, flag: u8) -> bool {
+        self.tracked_tags & flag != 0
+    }
+
+    /// Whether the feedback was counted in the agent's `ScoreHistogram`
+    pub fn in_histogram(&self) -> bool {
+        self.tracks_tag(Self::TRACKED_HISTOGRAM)
+    }
 }
 
 /// Response account - Separate account per response (unlimited responses)
@@ -76,9 +412,15 @@ pub struct ResponseAccount {
     /// Response hash (SHA-256, 32 bytes)
     pub response_hash: [u8; 32],
 
+    /// Response file size in bytes as declared by the responder (advisory, not verified; 0 = unknown)
+    pub file_size: u64,
+
     /// Creation timestamp
     pub created_at: i64,
 
+    /// Retracted by the responder (kept on-chain for audit)
+    pub is_revoked: bool,
+
     /// PDA bump seed
     pub bump: u8,
 }
@@ -87,11 +429,146 @@ impl ResponseAccount {
     /// Maximum size calculation
     /// 8 (discriminator) + 8 (agent_id) + 32 (client_address) + 8 (feedback_index)
     /// + 8 (response_index) + 32 (responder) + 4 + 200 (response_uri)
-    /// + 32 (response_hash) + 8 (created_at) + 1 (bump)
-    pub const MAX_SIZE: usize = 8 + 8 + 32 + 8 + 8 + 32 + 4 + 200 + 32 + 8 + 1;
+    /// + 32 (response_hash) + 8 (file_size) + 8 (created_at) + 1 (is_revoked) + 1 (bump)
+    pub const MAX_SIZE: usize = 8 + 8 + 32 + 8 + 8 + 32 + 4 + 200 + 32 + 8 + 8 + 1 + 1;
 
-    /// Maximum URI length
-    pub const MAX_URI_LENGTH: usize = 200;
+    /// URI bytes included in MAX_SIZE (the default `max_uri_length`)
+    pub const RESERVED_URI_LENGTH: usize = 200;
+
+    /// Account space for a response whose URI is `uri_len` bytes
+    /// (see `FeedbackAccount::space_for`)
+    pub fn space_for(uri_len: usize) -> usize {
+        Self::MAX_SIZE + uri_len.saturating_sub(Self::RESERVED_URI_LENGTH)
+    }
+}
+
+/// Per-tag reputation counters - Feedback aggregates for one capability
+/// Seeds: [b"tag_reputation", agent_id, tag]
+#[account]
+pub struct TagReputation {
+    /// Agent ID
+    pub agent_id: u64,
+
+    /// Tag (tag1 or tag2 of the counted feedbacks)
+    pub tag: [u8; 32],
+
+    /// Non-revoked feedbacks carrying this tag
+    pub count: u64,
+
+    /// Sum of their normalized (0-100) scores
+    pub score_sum: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl TagReputation {
+    /// Size calculation
+    /// 8 (discriminator) + 8 (agent_id) + 32 (tag) + 8 (count) + 8 (score_sum) + 1 (bump)
+    pub const SIZE: usize = 8 + 8 + 32 + 8 + 8 + 1;
+
+    /// Whether a feedback's tag2 gets its own counter: it must be set and
+    /// differ from tag1, so a feedback is never counted twice under one tag
+    pub fn counts_tag2(tag1: &[u8; 32], tag2: &[u8; 32]) -> bool {
+        *tag2 != [0u8; 32] && tag2 != tag1
+    }
+
+    /// Whether the account was just created by `init_if_needed` (bump still
+    /// unset; canonical bumps are never 0 in practice)
+    pub fn is_new(&self) -> bool {
+        self.bump == 0
+    }
+
+    /// Add a normalized score, initializing the account on first use
+    pub fn add_score(&mut self, agent_id: u64, tag: [u8; 32], bump: u8, score: u8) -> Result<()> {
+        if self.agent_id == 0 {
+            self.agent_id = agent_id;
+            self.tag = tag;
+            self.bump = bump;
+        }
+
+        self.count = self.count.checked_add(1).ok_or(ReputationError::Overflow)?;
+        self.score_sum = self
+            .score_sum
+            .checked_add(score as u64)
+            .ok_or(ReputationError::Overflow)?;
+        Ok(())
+    }
+
+    /// Remove a normalized score previously added with add_score
+    pub fn remove_score(&mut self, score: u8) -> Result<()> {
+        self.count = self.count.checked_sub(1).ok_or(ReputationError::Overflow)?;
+        self.score_sum = self
+            .score_sum
+            .checked_sub(score as u64)
+            .ok_or(ReputationError::Overflow)?;
+        Ok(())
+    }
+
+    /// Swap a counted normalized score for `new_score` (count unchanged)
+    pub fn replace_score(&mut self, old_score: u8, new_score: u8) -> Result<()> {
+        self.score_sum = self
+            .score_sum
+            .checked_sub(old_score as u64)
+            .and_then(|sum| sum.checked_add(new_score as u64))
+            .ok_or(ReputationError::Overflow)?;
+        Ok(())
+    }
+}
+
+/// Score distribution - Non-revoked feedback counts per normalized score bucket
+/// Seeds: [b"histogram", agent_id]
+#[account]
+pub struct ScoreHistogram {
+    /// Agent ID
+    pub agent_id: u64,
+
+    /// Feedback counts for normalized scores 0-9, 10-19, ..., 90-100
+    pub buckets: [u64; 10],
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ScoreHistogram {
+    /// Size calculation
+    /// 8 (discriminator) + 8 (agent_id) + 10 * 8 (buckets) + 1 (bump)
+    pub const SIZE: usize = 8 + 8 + 10 * 8 + 1;
+
+    /// Bucket of a normalized (0-100) score; 100 falls in the last bucket
+    pub fn bucket(score: u8) -> usize {
+        (score as usize / 10).min(9)
+    }
+
+    /// Count a normalized score, initializing the account on first use
+    pub fn add_score(&mut self, agent_id: u64, bump: u8, score: u8) -> Result<()> {
+        if self.agent_id == 0 {
+            self.agent_id = agent_id;
+            self.bump = bump;
+        }
+
+        let bucket = &mut self.buckets[Self::bucket(score)];
+        *bucket = bucket.checked_add(1).ok_or(ReputationError::Overflow)?;
+        Ok(())
+    }
+
+    /// Uncount a normalized score previously added with add_score
+    ///
+    /// Only call this for feedback counted in the histogram
+    /// (`FeedbackAccount::in_histogram`).
+    pub fn remove_score(&mut self, score: u8) -> Result<()> {
+        let bucket = &mut self.buckets[Self::bucket(score)];
+        *bucket = bucket.checked_sub(1).ok_or(ReputationError::Overflow)?;
+        Ok(())
+    }
+
+    /// Move a counted normalized score to the bucket of `new_score`
+    pub fn replace_score(&mut self, old_score: u8, new_score: u8) -> Result<()> {
+        self.remove_score(old_score)?;
+        let bucket = &mut self.buckets[Self::bucket(new_score)];
+        *bucket = bucket.checked_add(1).ok_or(ReputationError::Overflow)?;
+        Ok(())
+    }
 }
 
 /// Client index account - Tracks next feedback index for client-agent pair
@@ -107,13 +584,44 @@ pub struct ClientIndexAccount {
     /// Last used index (next feedback will use this value)
     pub last_index: u64,
 
+    /// Set by the client's first feedback to this agent. An account fresh
+    /// from `init_if_needed` is all zeroes, which for agent_id 0 is otherwise
+    /// indistinguishable from a used one.
+    pub is_initialized: bool,
+
     /// PDA bump seed
     pub bump: u8,
 }
 
 impl ClientIndexAccount {
     /// Size calculation
-    /// 8 (discriminator) + 8 (agent_id) + 32 (client_address) + 8 (last_index) + 1 (bump)
+    /// 8 (discriminator) + 8 (agent_id) + 32 (client_address) + 8 (last_index)
+    /// + 1 (is_initialized) + 1 (bump)
+    pub const SIZE: usize = 8 + 8 + 32 + 8 + 1 + 1;
+}
+
+/// Client block - The agent owner refuses new feedback from this client
+/// Seeds: [b"client_block", agent_id, client]
+///
+/// Forward-only: feedback the client already gave stays counted.
+#[account]
+pub struct ClientBlock {
+    /// Agent ID
+    pub agent_id: u64,
+
+    /// Blocked client address
+    pub client: Pubkey,
+
+    /// Block timestamp
+    pub blocked_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ClientBlock {
+    /// Size calculation
+    /// 8 (discriminator) + 8 (agent_id) + 32 (client) + 8 (blocked_at) + 1 (bump)
     pub const SIZE: usize = 8 + 8 + 32 + 8 + 1;
 }
 
@@ -133,18 +641,512 @@ pub struct AgentReputationMetadata {
     /// Average score (0-100, precalculated)
     pub average_score: u8,
 
+    /// Confidence in the average (0-100), grows with total_feedbacks
+    /// See `AgentReputationMetadata::confidence_for`
+    pub confidence: u8,
+
     /// Last update timestamp
     pub last_updated: i64,
 
     /// PDA bump seed
     pub bump: u8,
+
+    /// Live feedback accounts for this agent, revoked ones included
+    /// (incremented on give, decremented only when an account is closed)
+    pub feedback_account_count: u64,
+
+    /// `ReputationPolicy` used for average_score (set by the agent owner)
+    pub reputation_policy: u8,
+
+    /// Non-revoked feedbacks within the trimmed policy's score band
+    pub trimmed_feedbacks: u64,
+
+    /// Sum of their normalized scores
+    pub trimmed_score_sum: u64,
+
+    /// Sum of recency weights of non-revoked feedbacks (decayed policy)
+    pub decay_weight_sum: u64,
+
+    /// Sum of weight * normalized score of non-revoked feedbacks (decayed policy)
+    pub decay_weighted_score_sum: u64,
+
+    /// Lowest normalized score given (high-water mark, see `add_score`)
+    pub min_score: u8,
+
+    /// Highest normalized score given (high-water mark, see `add_score`)
+    pub max_score: u8,
+
+    /// Average score in basis points of the 0-100 scale (0-10000, truncated),
+    /// same policy as average_score which stays the rounded integer
+    pub average_score_bps: u16,
+
+    /// Distinct tags with a `TagReputation` counter (capped by
+    /// `ReputationConfig::max_tracked_tags`)
+    pub tracked_tag_count: u16,
 }
 
 impl AgentReputationMetadata {
     /// Size calculation
     /// 8 (discriminator) + 8 (agent_id) + 8 (total_feedbacks) + 8 (total_score_sum)
-    /// + 1 (average_score) + 8 (last_updated) + 1 (bump)
-    pub const SIZE: usize = 8 + 8 + 8 + 8 + 1 + 8 + 1;
+    /// + 1 (average_score) + 1 (confidence) + 8 (last_updated) + 1 (bump)
+    /// + 8 (feedback_account_count) + 1 (reputation_policy) + 8 (trimmed_feedbacks)
+    /// + 8 (trimmed_score_sum) + 8 (decay_weight_sum) + 8 (decay_weighted_score_sum)
+    /// + 1 (min_score) + 1 (max_score) + 2 (average_score_bps) + 2 (tracked_tag_count)
+    pub const SIZE: usize = 8 + 8 + 8 + 8 + 1 + 1 + 8 + 1 + 8 + 1 + 8 + 8 + 8 + 8 + 1 + 1 + 2 + 2;
+
+    /// Feedback count at which confidence reaches 50
+    pub const CONFIDENCE_HALF_COUNT: u64 = 10;
+
+    /// Maximum feedback accounts per consistency check or state commitment
+    ///
+    /// Leaves room for the fee payer, the program and the instruction's own
+    /// accounts under the 64-account transaction lock limit. A legacy
+    /// transaction only fits about 30 account keys, so larger batches must be
+    /// sent as a v0 transaction with an address lookup table.
+    ///
+    /// These instructions take an agent's whole feedback set at once, so
+    /// agents with more feedback accounts can't be checked or repaired on
+    /// chain (see `is_recomputable`).
+    pub const MAX_CONSISTENCY_BATCH: usize = 60;
+
+    /// Whether `recompute_reputation` can repair this agent: its whole
+    /// feedback set (revoked included) fits one `MAX_CONSISTENCY_BATCH`
+    pub fn is_recomputable(&self) -> bool {
+        self.feedback_account_count <= Self::MAX_CONSISTENCY_BATCH as u64
+    }
+
+    /// Add a normalized (0-100) score given at `created_at` to the cached aggregates
+    ///
+    /// `min_score`/`max_score` are high-water marks: revoking a feedback can't
+    /// narrow them without scanning every feedback, so they only widen, and
+    /// are reset to the next score once no non-revoked feedback remains.
+    pub fn add_score(&mut self, score: u8, created_at: i64) -> Result<()> {
+        if self.total_feedbacks == 0 {
+            self.min_score = score;
+            self.max_score = score;
+        } else {
+            self.min_score = self.min_score.min(score);
+            self.max_score = self.max_score.max(score);
+        }
+
+        self.total_feedbacks = self
+            .total_feedbacks
+            .checked_add(1)
+            .ok_or(ReputationError::Overflow)?;
+
+        self.total_score_sum = self
+            .total_score_sum
+            .checked_add(score as u64)
+            .ok_or(ReputationError::Overflow)?;
+
+        if ReputationPolicy::is_within_trim(score) {
+            self.trimmed_feedbacks = self
+                .trimmed_feedbacks
+                .checked_add(1)
+                .ok_or(ReputationError::Overflow)?;
+            self.trimmed_score_sum = self
+                .trimmed_score_sum
+                .checked_add(score as u64)
+                .ok_or(ReputationError::Overflow)?;
+        }
+
+        let weight = ReputationPolicy::decay_weight(created_at);
+        self.decay_weight_sum = self
+            .decay_weight_sum
+            .checked_add(weight)
+            .ok_or(ReputationError::Overflow)?;
+        self.decay_weighted_score_sum = weight
+            .checked_mul(score as u64)
+            .and_then(|w| self.decay_weighted_score_sum.checked_add(w))
+            .ok_or(ReputationError::Overflow)?;
+
+        self.recompute_average();
+        Ok(())
+    }
+
+    /// Remove a normalized (0-100) score given at `created_at` from the cached
+    /// aggregates (same contribution as the matching add_score)
+    pub fn remove_score(&mut self, score: u8, created_at: i64) -> Result<()> {
+        self.total_feedbacks = self
+            .total_feedbacks
+            .checked_sub(1)
+            .ok_or(ReputationError::Overflow)?;
+
+        self.total_score_sum = self
+            .total_score_sum
+            .checked_sub(score as u64)
+            .ok_or(ReputationError::Overflow)?;
+
+        if ReputationPolicy::is_within_trim(score) {
+            self.trimmed_feedbacks = self
+                .trimmed_feedbacks
+                .checked_sub(1)
+                .ok_or(ReputationError::Overflow)?;
+            self.trimmed_score_sum = self
+                .trimmed_score_sum
+                .checked_sub(score as u64)
+                .ok_or(ReputationError::Overflow)?;
+        }
+
+        let weight = ReputationPolicy::decay_weight(created_at);
+        self.decay_weight_sum = self
+            .decay_weight_sum
+            .checked_sub(weight)
+            .ok_or(ReputationError::Overflow)?;
+        self.decay_weighted_score_sum = weight
+            .checked_mul(score as u64)
+            .and_then(|w| self.decay_weighted_score_sum.checked_sub(w))
+            .ok_or(ReputationError::Overflow)?;
+
+        self.recompute_average();
+        Ok(())
+    }
+
+    /// Replace a normalized score given at `created_at` with `new_score`
+    /// (the feedback keeps its position in time, so its decay weight is unchanged)
+    pub fn replace_score(&mut self, old_score: u8, new_score: u8, created_at: i64) -> Result<()> {
+        self.remove_score(old_score, created_at)?;
+        self.add_score(new_score, created_at)
+    }
+
+    /// Zero every score aggregate once no non-revoked feedback remains
+    ///
+    /// Sums, averages and min/max would otherwise keep whatever the last
+    /// revocation left (e.g. stale high-water marks). Returns whether the
+    /// agent is empty, i.e. whether anything was reset.
+    pub fn reset_if_empty(&mut self) -> bool {
+        if self.total_feedbacks != 0 {
+            return false;
+        }
+
+        self.total_score_sum = 0;
+        self.trimmed_feedbacks = 0;
+        self.trimmed_score_sum = 0;
+        self.decay_weight_sum = 0;
+        self.decay_weighted_score_sum = 0;
+        self.min_score = 0;
+        self.max_score = 0;
+        self.recompute_average();
+        true
+    }
+
+    /// Switch the aggregation policy and recompute average_score from the
+    /// running sums (the caller validates `policy` against the config)
+    pub fn set_policy(&mut self, policy: u8) {
+        self.reputation_policy = policy;
+        self.recompute_average();
+    }
+
+    /// Average score rounded half up: (sum * 2 + count) / (count * 2)
+    /// Plain integer division truncates, so 89.9 would be reported as 89
+    /// Returns 0 when there is no feedback
+    pub fn rounded_average_score(&self) -> u8 {
+        let count = self.total_feedbacks as u128;
+        (self.total_score_sum as u128 * 2 + count)
+            .checked_div(count * 2)
+            .unwrap_or(0) as u8
+    }
+
+    /// Average score (0-100) under the agent's reputation policy, rounded half up
+    pub fn policy_average_score(&self) -> u8 {
+        let (sum, count) = self.policy_sums();
+        let count = count as u128;
+        (sum as u128 * 2 + count).checked_div(count * 2).unwrap_or(0) as u8
+    }
+
+    /// Average score under the agent's reputation policy in basis points of
+    /// the 0-100 scale: sum * 100 / count (0-10000, 0 when there is no feedback)
+    pub fn policy_average_score_bps(&self) -> u16 {
+        let (sum, count) = self.policy_sums();
+        (sum as u128 * 100).checked_div(count as u128).unwrap_or(0) as u16
+    }
+
+    /// (score sum, count) averaged by the agent's reputation policy
+    /// (weights stand in for the count under the decayed policy)
+    fn policy_sums(&self) -> (u64, u64) {
+        match ReputationPolicy::from_u8(self.reputation_policy) {
+            Some(ReputationPolicy::Trimmed) if self.trimmed_feedbacks > 0 => {
+                (self.trimmed_score_sum, self.trimmed_feedbacks)
+            }
+            Some(ReputationPolicy::Decayed) => {
+                (self.decay_weighted_score_sum, self.decay_weight_sum)
+            }
+            _ => (self.total_score_sum, self.total_feedbacks),
+        }
+    }
+
+    /// Average score (0-100) decayed by the time since `last_updated`, rounded half up
+    ///
+    /// Approximation: per-feedback decay would need every feedback account, so
+    /// the whole cached average (policy average in bps) is decayed by the time
+    /// since the agent's last reputation activity. It halves every
+    /// `half_life_secs`, interpolated linearly between halvings.
+    /// `half_life_secs` must be positive (checked by the caller).
+    pub fn decayed_average_score(&self, now: i64, half_life_secs: i64) -> u8 {
+        let elapsed = now.saturating_sub(self.last_updated).max(0) as u128;
+        let half_life = half_life_secs.max(1) as u128;
+
+        let halvings = u32::try_from(elapsed / half_life).unwrap_or(u32::MAX);
+        let at_halving = (self.average_score_bps as u128).checked_shr(halvings).unwrap_or(0);
+        let bps = at_halving - at_halving * (elapsed % half_life) / (2 * half_life);
+
+        ((bps + 50) / 100) as u8
+    }
+
+    /// Recompute aggregates from scratch over `feedbacks` and compare them with
+    /// the cached values
+    ///
+    /// `feedbacks` must be every live feedback account of this agent: the result
+    /// is only consistent if their number also matches `feedback_account_count`.
+    pub fn check_consistency<'a>(
+        &self,
+        feedbacks: impl IntoIterator<Item = &'a FeedbackAccount>,
+    ) -> Result<ReputationConsistency> {
+        let recomputed = self.recomputed(feedbacks)?;
+
+        Ok(ReputationConsistency {
+            is_consistent: recomputed.feedback_account_count == self.feedback_account_count
+                && recomputed.total_feedbacks == self.total_feedbacks
+                && recomputed.total_score_sum == self.total_score_sum
+                && recomputed.trimmed_score_sum == self.trimmed_score_sum
+                && recomputed.decay_weighted_score_sum == self.decay_weighted_score_sum
+                && recomputed.average_score == self.average_score
+                && recomputed.average_score_bps == self.average_score_bps,
+            feedback_accounts_checked: recomputed.feedback_account_count,
+            recomputed_feedbacks: recomputed.total_feedbacks,
+            recomputed_score_sum: recomputed.total_score_sum,
+            recomputed_average_score: recomputed.average_score,
+        })
+    }
+
+    /// Overwrite the cached aggregates with ones recomputed from scratch over
+    /// `feedbacks` (non-revoked entries only)
+    ///
+    /// `feedbacks` must be every live feedback account of this agent (the
+    /// caller checks their number against `feedback_account_count`). The
+    /// min/max high-water marks become the exact range of the counted scores.
+    /// `last_updated` is kept: a repair isn't reputation activity.
+    pub fn recompute_from<'a>(
+        &mut self,
+        feedbacks: impl IntoIterator<Item = &'a FeedbackAccount>,
+    ) -> Result<()> {
+        *self = self.recomputed(feedbacks)?;
+        Ok(())
+    }
+
+    /// Fresh aggregates over `feedbacks`, keeping identity, policy and timestamps
+    fn recomputed<'a>(
+        &self,
+        feedbacks: impl IntoIterator<Item = &'a FeedbackAccount>,
+    ) -> Result<AgentReputationMetadata> {
+        let mut recomputed = AgentReputationMetadata {
+            agent_id: self.agent_id,
+            total_feedbacks: 0,
+            total_score_sum: 0,
+            average_score: 0,
+            confidence: 0,
+            last_updated: self.last_updated,
+            bump: self.bump,
+            feedback_account_count: 0,
+            reputation_policy: self.reputation_policy,
+            trimmed_feedbacks: 0,
+            trimmed_score_sum: 0,
+            decay_weight_sum: 0,
+            decay_weighted_score_sum: 0,
+            min_score: 0,
+            max_score: 0,
+            average_score_bps: 0,
+            tracked_tag_count: self.tracked_tag_count,
+        };
+
+        for feedback in feedbacks {
+            recomputed.feedback_account_count += 1;
+            if !feedback.is_revoked {
+                recomputed.add_score(feedback.normalized_score(), feedback.created_at)?;
+            }
+        }
+        recomputed.recompute_average();
+        Ok(recomputed)
+    }
+
+    /// Confidence for a given feedback count: 100 * n / (n + CONFIDENCE_HALF_COUNT)
+    ///
+    /// 0 with no feedback, 50 at 10 feedbacks, 90 at 100, 99 from 1000 on.
+    /// Non-decreasing in n and saturates toward (never above) 100.
+    pub fn confidence_for(total_feedbacks: u64) -> u8 {
+        let n = total_feedbacks as u128;
+        (n * 100 / (n + Self::CONFIDENCE_HALF_COUNT as u128)) as u8
+    }
+
+    /// Recalculate cached average and confidence after an add/remove
+    fn recompute_average(&mut self) {
+        self.average_score = self.policy_average_score();
+        self.average_score_bps = self.policy_average_score_bps();
+        self.confidence = Self::confidence_for(self.total_feedbacks);
+    }
+}
+
+/// Result of `verify_reputation_consistency`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct ReputationConsistency {
+    /// Cached aggregates match the recomputed ones and every live account was checked
+    pub is_consistent: bool,
+
+    /// Number of feedback accounts passed in
+    pub feedback_accounts_checked: u64,
+
+    /// Non-revoked feedbacks among them
+    pub recomputed_feedbacks: u64,
+
+    /// Sum of their normalized scores
+    pub recomputed_score_sum: u64,
+
+    /// Recomputed average (0-100)
+    pub recomputed_average_score: u8,
+}
+
+/// One ranked agent in a leaderboard snapshot
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct LeaderboardEntry {
+    pub agent_id: u64,
+
+    /// Average score (0-100) at snapshot time
+    pub average_score: u8,
+
+    /// Non-revoked feedbacks at snapshot time
+    pub total_feedbacks: u64,
+}
+
+impl LeaderboardEntry {
+    /// 8 (agent_id) + 1 (average_score) + 8 (total_feedbacks)
+    pub const SIZE: usize = 8 + 1 + 8;
+
+    pub fn from_reputation(reputation: &AgentReputationMetadata) -> Self {
+        Self {
+            agent_id: reputation.agent_id,
+            average_score: reputation.average_score,
+            total_feedbacks: reputation.total_feedbacks,
+        }
+    }
+}
+
+/// Leaderboard snapshot - Top agents frozen at an epoch
+/// Seeds: [b"leaderboard", epoch]
+///
+/// Created once per epoch and never modified, so consumers can rely on
+/// the ranking not changing after the fact.
+#[account]
+pub struct LeaderboardSnapshot {
+    /// Epoch this snapshot was taken for (caller-defined)
+    pub epoch: u64,
+
+    /// Ranked entries, best first (at most MAX_ENTRIES)
+    pub entries: Vec<LeaderboardEntry>,
+
+    /// Snapshot timestamp
+    pub created_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl LeaderboardSnapshot {
+    /// Maximum ranked agents stored per snapshot
+    pub const MAX_ENTRIES: usize = 10;
+
+    /// Maximum reputation accounts considered per snapshot
+    /// Bounded by the transaction account limit (with address lookup tables)
+    pub const MAX_INPUT_ACCOUNTS: usize = 64;
+
+    /// Size calculation
+    /// 8 (discriminator) + 8 (epoch) + 4 + MAX_ENTRIES * LeaderboardEntry::SIZE (entries)
+    /// + 8 (created_at) + 1 (bump)
+    pub const SIZE: usize = 8 + 8 + 4 + Self::MAX_ENTRIES * LeaderboardEntry::SIZE + 8 + 1;
+
+    /// Rank entries best first and keep the top MAX_ENTRIES
+    ///
+    /// Order: higher average_score, then more feedbacks, then lower agent_id,
+    /// so the result does not depend on the order accounts were passed in.
+    pub fn rank(mut entries: Vec<LeaderboardEntry>) -> Vec<LeaderboardEntry> {
+        entries.sort_by(|a, b| {
+            b.average_score
+                .cmp(&a.average_score)
+                .then(b.total_feedbacks.cmp(&a.total_feedbacks))
+                .then(a.agent_id.cmp(&b.agent_id))
+        });
+        entries.truncate(Self::MAX_ENTRIES);
+        entries
+    }
+}
+
+/// Reputation attestation record - Latest soulbound attestation minted for an agent
+/// Seeds: [b"reputation_attestation", agent_id]
+///
+/// Mirrors the stats encoded in the most recent attestation NFT and
+/// rate-limits issuance to one per MIN_ISSUE_INTERVAL.
+#[account]
+pub struct ReputationAttestationRecord {
+    /// Agent ID
+    pub agent_id: u64,
+
+    /// Mint of the most recent attestation NFT
+    pub last_mint: Pubkey,
+
+    /// Average score (0-100) at snapshot time
+    pub average_score: u8,
+
+    /// Non-revoked feedbacks at snapshot time
+    pub total_feedbacks: u64,
+
+    /// Snapshot timestamp of the most recent attestation
+    pub snapshot_at: i64,
+
+    /// Attestations minted so far
+    pub issued_count: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ReputationAttestationRecord {
+    /// Size calculation
+    /// 8 (discriminator) + 8 (agent_id) + 32 (last_mint) + 1 (average_score)
+    /// + 8 (total_feedbacks) + 8 (snapshot_at) + 8 (issued_count) + 1 (bump)
+    pub const SIZE: usize = 8 + 8 + 32 + 1 + 8 + 8 + 8 + 1;
+
+    /// Minimum time between two attestations for the same agent (1 day)
+    pub const MIN_ISSUE_INTERVAL: i64 = 24 * 60 * 60;
+
+    /// Maximum attestation URI length (Metaplex limit)
+    pub const MAX_URI_LENGTH: usize = 200;
+
+    /// Whether a new attestation may be minted at `now`
+    pub fn can_issue(&self, now: i64) -> bool {
+        self.issued_count == 0 || now >= self.snapshot_at.saturating_add(Self::MIN_ISSUE_INTERVAL)
+    }
+
+    /// Attestation NFT name, e.g. "Reputation #42"
+    pub fn nft_name(agent_id: u64) -> String {
+        format!("Reputation #{}", agent_id)
+    }
+
+    /// Attestation NFT URI: `base_uri` with the snapshot encoded as query parameters,
+    /// e.g. "https://x/att?agent_id=42&score=87&feedbacks=15&at=1700000000"
+    ///
+    /// Off-chain renderers read the stats from the URI; the same values are
+    /// kept on-chain in this record for verification.
+    pub fn uri(base_uri: &str, reputation: &AgentReputationMetadata, snapshot_at: i64) -> String {
+        let separator = if base_uri.contains('?') { '&' } else { '?' };
+        format!(
+            "{}{}agent_id={}&score={}&feedbacks={}&at={}",
+            base_uri,
+            separator,
+            reputation.agent_id,
+            reputation.average_score,
+            reputation.total_feedbacks,
+            snapshot_at
+        )
+    }
 }
 
 /// Response index account - Tracks next response index for a feedback
@@ -220,8 +1222,6 @@ impl FeedbackAuth {
         current_index: u64,
         current_time: i64,
     ) -> Result<()> {
-        use crate::error::ReputationError;
-
         // 1. Verify client_address matches
         require!(
             self.client_address == *client,
@@ -272,3 +1272,800 @@ impl FeedbackAuth {
         .to_vec()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_reputation() -> AgentReputationMetadata {
+        AgentReputationMetadata {
+            agent_id: 1,
+            total_feedbacks: 0,
+            total_score_sum: 0,
+            average_score: 0,
+            confidence: 0,
+            last_updated: 0,
+            bump: 0,
+            feedback_account_count: 0,
+            reputation_policy: ReputationPolicy::Mean as u8,
+            trimmed_feedbacks: 0,
+            trimmed_score_sum: 0,
+            decay_weight_sum: 0,
+            decay_weighted_score_sum: 0,
+            min_score: 0,
+            max_score: 0,
+            average_score_bps: 0,
+            tracked_tag_count: 0,
+        }
+    }
+
+    fn feedback(score: u8, score_scale: u8, is_revoked: bool) -> FeedbackAccount {
+        FeedbackAccount {
+            agent_id: 1,
+            client_address: Pubkey::new_unique(),
+            feedback_index: 0,
+            score,
+            score_scale,
+            tag1: [0; 32],
+            tag2: [0; 32],
+            file_uri: String::new(),
+            file_hash: [0; 32],
+            file_size: 0,
+            is_revoked,
+            created_at: 0,
+            is_disputed: false,
+            extra_tags: Vec::new(),
+            tracked_tags: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn test_feedback_account_max_size() {
+        assert_eq!(FeedbackAccount::MAX_SIZE, 574);
+    }
+
+    #[test]
+    fn test_feedback_account_extra_tags_round_trip() {
+        let mut fb = feedback(80, 100, false);
+        fb.tag1 = [1; 32];
+        fb.tag2 = [2; 32];
+        fb.extra_tags = vec![[3; 32], [4; 32], [5; 32]];
+        fb.file_uri = "x".repeat(FeedbackAccount::RESERVED_URI_LENGTH);
+
+        let mut data = Vec::new();
+        fb.try_serialize(&mut data).unwrap();
+        assert!(data.len() <= FeedbackAccount::MAX_SIZE);
+
+        assert_eq!(data.len(), FeedbackAccount::space_for(fb.file_uri.len(), 3));
+
+        let decoded = FeedbackAccount::try_deserialize(&mut data.as_slice()).unwrap();
+        assert_eq!(decoded.tag1, [1; 32]);
+        assert_eq!(decoded.tag2, [2; 32]);
+        assert_eq!(decoded.extra_tags, vec![[3; 32], [4; 32], [5; 32]]);
+
+        // A full tag list still fits the allocated space
+        fb.extra_tags = vec![[9; 32]; FeedbackAccount::MAX_EXTRA_TAGS];
+        let mut data = Vec::new();
+        fb.try_serialize(&mut data).unwrap();
+        assert_eq!(data.len(), FeedbackAccount::MAX_SIZE);
+    }
+
+    #[test]
+    fn test_feedback_space_for_long_uri() {
+        assert_eq!(FeedbackAccount::space_for(0, 0), FeedbackAccount::BASE_SIZE);
+        assert_eq!(FeedbackAccount::space_for(200, 0), FeedbackAccount::BASE_SIZE);
+        assert_eq!(FeedbackAccount::space_for(250, 0), FeedbackAccount::BASE_SIZE + 50);
+        assert_eq!(
+            FeedbackAccount::space_for(200, FeedbackAccount::MAX_EXTRA_TAGS),
+            FeedbackAccount::MAX_SIZE
+        );
+        assert_eq!(ResponseAccount::space_for(512), ResponseAccount::MAX_SIZE + 312);
+
+        // A ceiling-length URI with a full tag list fits its computed space
+        let mut fb = feedback(80, 100, false);
+        fb.file_uri = "x".repeat(ReputationConfig::MAX_URI_LENGTH as usize);
+        fb.extra_tags = vec![[9; 32]; FeedbackAccount::MAX_EXTRA_TAGS];
+        let mut data = Vec::new();
+        fb.try_serialize(&mut data).unwrap();
+        assert_eq!(
+            data.len(),
+            FeedbackAccount::space_for(fb.file_uri.len(), fb.extra_tags.len())
+        );
+    }
+
+    #[test]
+    fn test_response_account_max_size() {
+        assert_eq!(ResponseAccount::MAX_SIZE, 350);
+    }
+
+    #[test]
+    fn test_normalize_score() {
+        assert_eq!(normalize_score(85, 100), 85);
+        assert_eq!(normalize_score(5, 5), 100);
+        assert_eq!(normalize_score(0, 5), 0);
+        assert_eq!(normalize_score(4, 5), 80);
+        assert_eq!(normalize_score(2, 3), 67);
+        assert_eq!(normalize_score(255, 255), 100);
+    }
+
+    #[test]
+    fn test_aggregate_is_scale_consistent() {
+        let mut reputation = empty_reputation();
+
+        // 4/5 stars before a scale change, 80/100 after: both are 80
+        reputation.add_score(normalize_score(4, 5), 0).unwrap();
+        reputation.add_score(normalize_score(80, 100), 0).unwrap();
+
+        assert_eq!(reputation.total_feedbacks, 2);
+        assert_eq!(reputation.total_score_sum, 160);
+        assert_eq!(reputation.average_score, 80);
+    }
+
+    #[test]
+    fn test_min_max_scores() {
+        let mut reputation = empty_reputation();
+
+        for score in [10, 90, 50] {
+            reputation.add_score(score, 0).unwrap();
+        }
+        assert_eq!((reputation.min_score, reputation.max_score), (10, 90));
+
+        // High-water marks: revoking the extremes doesn't narrow the range
+        reputation.remove_score(10, 0).unwrap();
+        reputation.remove_score(90, 0).unwrap();
+        assert_eq!((reputation.min_score, reputation.max_score), (10, 90));
+
+        // Reset by the next score once every feedback is revoked
+        reputation.remove_score(50, 0).unwrap();
+        assert!(reputation.reset_if_empty());
+        assert_eq!((reputation.min_score, reputation.max_score), (0, 0));
+        reputation.add_score(70, 0).unwrap();
+        assert!(!reputation.reset_if_empty());
+        assert_eq!((reputation.min_score, reputation.max_score), (70, 70));
+    }
+
+    #[test]
+    fn test_replace_score_moves_average() {
+        let mut reputation = empty_reputation();
+
+        reputation.add_score(40, 0).unwrap();
+        reputation.add_score(60, 0).unwrap();
+        assert_eq!(reputation.average_score, 50);
+
+        reputation.replace_score(40, 80, 0).unwrap();
+        assert_eq!(reputation.total_feedbacks, 2);
+        assert_eq!(reputation.total_score_sum, 140);
+        assert_eq!(reputation.average_score, 70);
+    }
+
+    #[test]
+    fn test_revoke_subtracts_normalized_contribution() {
+        let mut reputation = empty_reputation();
+
+        reputation.add_score(normalize_score(1, 5), 0).unwrap();
+        reputation.add_score(normalize_score(90, 100), 0).unwrap();
+        assert_eq!(reputation.average_score, 55);
+
+        reputation.remove_score(normalize_score(1, 5), 0).unwrap();
+        assert_eq!(reputation.total_feedbacks, 1);
+        assert_eq!(reputation.total_score_sum, 90);
+        assert_eq!(reputation.average_score, 90);
+
+        reputation.remove_score(normalize_score(90, 100), 0).unwrap();
+        assert_eq!(reputation.total_score_sum, 0);
+        assert_eq!(reputation.average_score, 0);
+    }
+
+    #[test]
+    fn test_average_rounds_half_up_on_give() {
+        let mut reputation = empty_reputation();
+
+        // 90 + 90 + 89 = 269 / 3 = 89.67 -> 90 (truncation would give 89)
+        reputation.add_score(90, 0).unwrap();
+        reputation.add_score(90, 0).unwrap();
+        reputation.add_score(89, 0).unwrap();
+        assert_eq!(reputation.average_score, 90);
+
+        // 269 + 0 = 269 / 4 = 67.25 -> 67
+        reputation.add_score(0, 0).unwrap();
+        assert_eq!(reputation.average_score, 67);
+
+        // Exactly .5 rounds up: 1 / 2 = 0.5 -> 1
+        let mut half = empty_reputation();
+        half.add_score(1, 0).unwrap();
+        half.add_score(0, 0).unwrap();
+        assert_eq!(half.average_score, 1);
+    }
+
+    #[test]
+    fn test_tag_reputation_counters() {
+        assert_eq!(TagReputation::SIZE, 65);
+
+        let translation = [1u8; 32];
+        let coding = [2u8; 32];
+        assert!(!TagReputation::counts_tag2(&translation, &[0u8; 32]));
+        assert!(!TagReputation::counts_tag2(&translation, &translation));
+        assert!(TagReputation::counts_tag2(&translation, &coding));
+
+        let mut tag = TagReputation {
+            agent_id: 0,
+            tag: [0u8; 32],
+            count: 0,
+            score_sum: 0,
+            bump: 0,
+        };
+        tag.add_score(7, translation, 254, 90).unwrap();
+        tag.add_score(7, translation, 254, 80).unwrap();
+        assert_eq!((tag.agent_id, tag.tag, tag.bump), (7, translation, 254));
+        assert_eq!((tag.count, tag.score_sum), (2, 170));
+
+        tag.replace_score(90, 40).unwrap();
+        assert_eq!((tag.count, tag.score_sum), (2, 120));
+
+        tag.remove_score(40).unwrap();
+        assert_eq!((tag.count, tag.score_sum), (1, 80));
+        tag.remove_score(80).unwrap();
+        assert!(tag.remove_score(0).is_err());
+    }
+
+    #[test]
+    fn test_score_histogram_buckets() {
+        assert_eq!(ScoreHistogram::SIZE, 97);
+
+        assert_eq!(ScoreHistogram::bucket(0), 0);
+        assert_eq!(ScoreHistogram::bucket(9), 0);
+        assert_eq!(ScoreHistogram::bucket(10), 1);
+        assert_eq!(ScoreHistogram::bucket(89), 8);
+        assert_eq!(ScoreHistogram::bucket(90), 9);
+        // Right edge is inclusive
+        assert_eq!(ScoreHistogram::bucket(100), 9);
+
+        let mut histogram = ScoreHistogram {
+            agent_id: 0,
+            buckets: [0; 10],
+            bump: 0,
+        };
+        for score in [5, 15, 15, 55, 95, 100] {
+            histogram.add_score(7, 254, score).unwrap();
+        }
+        assert_eq!((histogram.agent_id, histogram.bump), (7, 254));
+        assert_eq!(histogram.buckets, [1, 2, 0, 0, 0, 1, 0, 0, 0, 2]);
+
+        histogram.replace_score(15, 42).unwrap();
+        assert_eq!(histogram.buckets, [1, 1, 0, 0, 1, 1, 0, 0, 0, 2]);
+
+        histogram.remove_score(100).unwrap();
+        assert_eq!(histogram.buckets, [1, 1, 0, 0, 1, 1, 0, 0, 0, 1]);
+
+        // A score that was never counted can't be uncounted
+        assert!(histogram.remove_score(30).is_err());
+        assert!(histogram.replace_score(30, 40).is_err());
+    }
+
+    #[test]
+    fn test_decayed_average_score() {
+        let mut reputation = empty_reputation();
+        reputation.add_score(80, 0).unwrap();
+        reputation.last_updated = 1_000;
+        let day = 86_400;
+
+        // No time elapsed (or clock behind last_updated): undecayed
+        assert_eq!(reputation.decayed_average_score(1_000, day), 80);
+        assert_eq!(reputation.decayed_average_score(0, day), 80);
+
+        // One half-life halves, half a half-life interpolates to 3/4
+        assert_eq!(reputation.decayed_average_score(1_000 + day, day), 40);
+        assert_eq!(reputation.decayed_average_score(1_000 + day / 2, day), 60);
+        assert_eq!(reputation.decayed_average_score(1_000 + 2 * day, day), 20);
+
+        // Non-increasing over time, reaching 0
+        let mut previous = 80;
+        for t in (1_000..1_000 + 20 * day).step_by(3_600) {
+            let score = reputation.decayed_average_score(t, day);
+            assert!(score <= previous);
+            previous = score;
+        }
+        assert_eq!(reputation.decayed_average_score(i64::MAX, 1), 0);
+    }
+
+    #[test]
+    fn test_average_score_bps() {
+        let mut reputation = empty_reputation();
+
+        // 0 and 1 average to 0.5 points = 50 bps
+        reputation.add_score(0, 0).unwrap();
+        reputation.add_score(1, 0).unwrap();
+        assert_eq!(reputation.average_score_bps, 50);
+
+        // 0 and 100 average to 50 points = 5000 bps
+        reputation.remove_score(1, 0).unwrap();
+        reputation.add_score(100, 0).unwrap();
+        assert_eq!(reputation.average_score_bps, 5000);
+        assert_eq!(reputation.average_score, 50);
+
+        // Recomputed on revoke too
+        reputation.remove_score(0, 0).unwrap();
+        assert_eq!(reputation.average_score_bps, 10000);
+
+        reputation.remove_score(100, 0).unwrap();
+        assert_eq!(reputation.average_score_bps, 0);
+    }
+
+    #[test]
+    fn test_average_rounds_half_up_on_revoke() {
+        let mut reputation = empty_reputation();
+
+        reputation.add_score(100, 0).unwrap();
+        reputation.add_score(80, 0).unwrap();
+        reputation.add_score(79, 0).unwrap();
+        reputation.add_score(0, 0).unwrap();
+
+        // 259 / 3 = 86.33 -> 86
+        reputation.remove_score(0, 0).unwrap();
+        assert_eq!(reputation.average_score, 86);
+
+        // 179 / 2 = 89.5 -> 90 (truncation would give 89)
+        reputation.remove_score(80, 0).unwrap();
+        assert_eq!(reputation.average_score, 90);
+        assert_eq!(reputation.average_score, reputation.rounded_average_score());
+    }
+
+    #[test]
+    fn test_rounded_average_bounds() {
+        let mut reputation = empty_reputation();
+        assert_eq!(reputation.rounded_average_score(), 0);
+
+        reputation.total_feedbacks = 3;
+        reputation.total_score_sum = 300;
+        assert_eq!(reputation.rounded_average_score(), 100);
+
+        // Large sums never overflow the intermediate
+        reputation.total_feedbacks = u64::MAX / 100;
+        reputation.total_score_sum = (u64::MAX / 100) * 100;
+        assert_eq!(reputation.rounded_average_score(), 100);
+    }
+
+    #[test]
+    fn test_reputation_metadata_size() {
+        assert_eq!(AgentReputationMetadata::SIZE, 90);
+    }
+
+    #[test]
+    fn test_policies_aggregate_identical_feedback_differently() {
+        const DAY: i64 = 24 * 60 * 60;
+        let origin = ReputationPolicy::DECAY_ORIGIN;
+        // An old 0, then two recent 80s and a recent 100
+        let history = [
+            (0, origin),
+            (80, origin + 300 * DAY),
+            (80, origin + 300 * DAY),
+            (100, origin + 300 * DAY),
+        ];
+
+        let mut mean = empty_reputation();
+        let mut trimmed = empty_reputation();
+        trimmed.set_policy(ReputationPolicy::Trimmed as u8);
+        let mut decayed = empty_reputation();
+        decayed.set_policy(ReputationPolicy::Decayed as u8);
+
+        for (score, at) in history {
+            mean.add_score(score, at).unwrap();
+            trimmed.add_score(score, at).unwrap();
+            decayed.add_score(score, at).unwrap();
+        }
+
+        // 260 / 4 = 65
+        assert_eq!(mean.average_score, 65);
+        // Only the two 80s are within the band
+        assert_eq!(trimmed.average_score, 80);
+        // Weights 1, 11, 11, 11: (0 + 880 + 880 + 1100) / 34 = 84.1
+        assert_eq!(decayed.average_score, 84);
+
+        // Revoking removes the same weighted contribution
+        decayed.remove_score(0, origin).unwrap();
+        assert_eq!(decayed.average_score, 87);
+        assert_eq!(decayed.decay_weight_sum, 33);
+
+        // Switching policy recomputes from the running sums
+        mean.set_policy(ReputationPolicy::Trimmed as u8);
+        assert_eq!(mean.average_score, 80);
+    }
+
+    #[test]
+    fn test_trimmed_falls_back_to_mean() {
+        let mut reputation = empty_reputation();
+        reputation.set_policy(ReputationPolicy::Trimmed as u8);
+        reputation.add_score(100, 0).unwrap();
+        reputation.add_score(0, 0).unwrap();
+        assert_eq!(reputation.trimmed_feedbacks, 0);
+        assert_eq!(reputation.average_score, 50);
+    }
+
+    #[test]
+    fn test_supported_policies() {
+        let mut config = ReputationConfig {
+            authority: Pubkey::new_unique(),
+            score_scale: ReputationConfig::DEFAULT_SCORE_SCALE,
+            require_live_agent: false,
+            edit_window: 0,
+            supported_policies: ReputationPolicy::ALL,
+            min_client_balance: 0,
+            identity_registry: Pubkey::new_unique(),
+            feedback_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            max_uri_length: ReputationConfig::DEFAULT_URI_LENGTH,
+            max_tracked_tags: ReputationConfig::DEFAULT_MAX_TRACKED_TAGS,
+            validation_registry: DEFAULT_VALIDATION_REGISTRY_ID,
+            bump: 255,
+        };
+        assert!(config.supports_policy(ReputationPolicy::Mean as u8));
+        assert!(config.supports_policy(ReputationPolicy::Decayed as u8));
+        assert!(!config.supports_policy(3));
+        assert!(!config.supports_policy(7));
+
+        config.supported_policies = 1 << ReputationPolicy::Mean as u8;
+        assert!(!config.supports_policy(ReputationPolicy::Trimmed as u8));
+    }
+
+    #[test]
+    fn test_consistency_matches_incremental_updates() {
+        let feedbacks = [feedback(80, 100, false), feedback(4, 5, false), feedback(10, 100, true)];
+
+        let mut reputation = empty_reputation();
+        for fb in &feedbacks {
+            reputation.add_score(fb.normalized_score(), fb.created_at).unwrap();
+            reputation.feedback_account_count += 1;
+        }
+        reputation.remove_score(feedbacks[2].normalized_score(), feedbacks[2].created_at).unwrap();
+
+        let result = reputation.check_consistency(&feedbacks).unwrap();
+        assert!(result.is_consistent);
+        assert_eq!(result.feedback_accounts_checked, 3);
+        assert_eq!(result.recomputed_feedbacks, 2);
+        assert_eq!(result.recomputed_score_sum, 160);
+        assert_eq!(result.recomputed_average_score, 80);
+    }
+
+    #[test]
+    fn test_recompute_limited_to_one_batch() {
+        let mut reputation = empty_reputation();
+        reputation.feedback_account_count = AgentReputationMetadata::MAX_CONSISTENCY_BATCH as u64;
+        assert!(reputation.is_recomputable());
+
+        // One more feedback account (even a revoked one) and the set no
+        // longer fits a transaction: the cache can't be repaired on chain
+        reputation.feedback_account_count += 1;
+        assert!(!reputation.is_recomputable());
+    }
+
+    #[test]
+    fn test_recompute_repairs_drifted_cache() {
+        let feedbacks = [feedback(80, 100, false), feedback(4, 5, false), feedback(10, 100, true)];
+
+        let mut reputation = empty_reputation();
+        reputation.feedback_account_count = 3;
+        reputation.last_updated = 1_700_000_000;
+        // Drifted: a revoked score still counted, plus a phantom one
+        for score in [80, 80, 10, 0] {
+            reputation.add_score(score, 0).unwrap();
+        }
+        assert!(!reputation.check_consistency(&feedbacks).unwrap().is_consistent);
+
+        reputation.recompute_from(&feedbacks).unwrap();
+        assert!(reputation.check_consistency(&feedbacks).unwrap().is_consistent);
+        assert_eq!(reputation.total_feedbacks, 2);
+        assert_eq!(reputation.total_score_sum, 160);
+        assert_eq!(reputation.average_score, 80);
+        assert_eq!((reputation.min_score, reputation.max_score), (80, 80));
+        assert_eq!(reputation.feedback_account_count, 3);
+        assert_eq!(reputation.last_updated, 1_700_000_000);
+    }
+
+    #[test]
+    fn test_consistency_detects_drift() {
+        let feedbacks = [feedback(80, 100, false), feedback(60, 100, false)];
+
+        let mut reputation = empty_reputation();
+        for fb in &feedbacks {
+            reputation.add_score(fb.normalized_score(), fb.created_at).unwrap();
+            reputation.feedback_account_count += 1;
+        }
+
+        // Second account closed without going through revoke: aggregate drifted
+        let result = reputation.check_consistency(&feedbacks[..1]).unwrap();
+        assert!(!result.is_consistent);
+        assert_eq!(result.recomputed_feedbacks, 1);
+        assert_eq!(result.recomputed_average_score, 80);
+
+        // Cached sum corrupted while all accounts are present
+        reputation.total_score_sum += 1;
+        assert!(!reputation.check_consistency(&feedbacks).unwrap().is_consistent);
+    }
+
+    #[test]
+    fn test_confidence_grows_with_feedback_count() {
+        assert_eq!(AgentReputationMetadata::confidence_for(0), 0);
+        assert_eq!(AgentReputationMetadata::confidence_for(10), 50);
+        assert_eq!(AgentReputationMetadata::confidence_for(100), 90);
+        assert_eq!(AgentReputationMetadata::confidence_for(u64::MAX), 99);
+
+        // Strictly increasing while few feedbacks exist, never decreasing after
+        let mut previous = 0;
+        for n in 1..=10_000u64 {
+            let confidence = AgentReputationMetadata::confidence_for(n);
+            if n <= 20 {
+                assert!(confidence > previous);
+            }
+            assert!(confidence >= previous);
+            assert!(confidence < 100);
+            previous = confidence;
+        }
+    }
+
+    #[test]
+    fn test_confidence_recomputed_on_give_and_revoke() {
+        let mut reputation = empty_reputation();
+
+        reputation.add_score(100, 0).unwrap();
+        reputation.add_score(100, 0).unwrap();
+        assert_eq!(reputation.average_score, 100);
+        assert_eq!(reputation.confidence, 16); // 2 feedbacks: 200 / 12
+
+        reputation.remove_score(100, 0).unwrap();
+        assert_eq!(reputation.confidence, 9); // 1 feedback: 100 / 11
+
+        reputation.remove_score(100, 0).unwrap();
+        assert_eq!(reputation.confidence, 0);
+    }
+
+    fn entry(agent_id: u64, average_score: u8, total_feedbacks: u64) -> LeaderboardEntry {
+        LeaderboardEntry {
+            agent_id,
+            average_score,
+            total_feedbacks,
+        }
+    }
+
+    #[test]
+    fn test_leaderboard_snapshot_size() {
+        assert_eq!(LeaderboardSnapshot::SIZE, 199);
+    }
+
+    #[test]
+    fn test_leaderboard_ranking_order() {
+        let ranked = LeaderboardSnapshot::rank(vec![
+            entry(1, 70, 5),
+            entry(2, 95, 3),
+            entry(3, 95, 10),
+            entry(4, 70, 5),
+            entry(5, 40, 100),
+        ]);
+
+        let ids: Vec<u64> = ranked.iter().map(|e| e.agent_id).collect();
+        // Score first, then feedback count, then lowest agent_id
+        assert_eq!(ids, vec![3, 2, 1, 4, 5]);
+    }
+
+    #[test]
+    fn test_leaderboard_keeps_top_n() {
+        let entries = (0..20).map(|id| entry(id, id as u8, 1)).collect();
+        let ranked = LeaderboardSnapshot::rank(entries);
+
+        assert_eq!(ranked.len(), LeaderboardSnapshot::MAX_ENTRIES);
+        assert_eq!(ranked[0].agent_id, 19);
+        assert_eq!(ranked[LeaderboardSnapshot::MAX_ENTRIES - 1].agent_id, 10);
+    }
+
+    #[test]
+    fn test_attestation_uri_encodes_current_stats() {
+        let mut reputation = empty_reputation();
+        reputation.agent_id = 42;
+        reputation.add_score(80, 0).unwrap();
+        reputation.add_score(95, 0).unwrap();
+
+        assert_eq!(
+            ReputationAttestationRecord::uri("https://example.com/att", &reputation, 1_700_000_000),
+            "https://example.com/att?agent_id=42&score=88&feedbacks=2&at=1700000000"
+        );
+        assert_eq!(
+            ReputationAttestationRecord::uri("https://example.com/att?v=1", &reputation, 5),
+            "https://example.com/att?v=1&agent_id=42&score=88&feedbacks=2&at=5"
+        );
+    }
+
+    #[test]
+    fn test_attestation_rate_limit() {
+        let mut record = ReputationAttestationRecord {
+            agent_id: 1,
+            last_mint: Pubkey::default(),
+            average_score: 0,
+            total_feedbacks: 0,
+            snapshot_at: 0,
+            issued_count: 0,
+            bump: 0,
+        };
+        // First attestation is always allowed
+        assert!(record.can_issue(0));
+
+        record.issued_count = 1;
+        record.snapshot_at = 1_000;
+        assert!(!record.can_issue(1_000));
+        assert!(!record.can_issue(1_000 + ReputationAttestationRecord::MIN_ISSUE_INTERVAL - 1));
+        assert!(record.can_issue(1_000 + ReputationAttestationRecord::MIN_ISSUE_INTERVAL));
+    }
+
+    #[test]
+    fn test_attestation_record_size() {
+        assert_eq!(ReputationAttestationRecord::SIZE, 74);
+    }
+
+    #[test]
+    fn test_client_index_size() {
+        assert_eq!(ClientIndexAccount::SIZE, 58);
+    }
+
+    #[test]
+    fn test_client_block_size() {
+        assert_eq!(ClientBlock::SIZE, 57);
+    }
+
+    #[test]
+    fn test_reputation_config_size() {
+        assert_eq!(ReputationConfig::SIZE, 168);
+    }
+
+    #[test]
+    fn test_max_tracked_tags() {
+        let config = ReputationConfig {
+            authority: Pubkey::default(),
+            score_scale: ReputationConfig::DEFAULT_SCORE_SCALE,
+            require_live_agent: false,
+            edit_window: 0,
+            supported_policies: ReputationPolicy::ALL,
+            min_client_balance: 0,
+            identity_registry: Pubkey::default(),
+            feedback_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            max_uri_length: ReputationConfig::DEFAULT_URI_LENGTH,
+            max_tracked_tags: 2,
+            validation_registry: DEFAULT_VALIDATION_REGISTRY_ID,
+            bump: 255,
+        };
+        assert!(config.can_track_new_tag(0));
+        assert!(config.can_track_new_tag(1));
+        assert!(!config.can_track_new_tag(2));
+
+        let mut fb = feedback(80, 100, false);
+        assert!(!fb.tracks_tag(FeedbackAccount::TRACKED_TAG1));
+        fb.tracked_tags = FeedbackAccount::TRACKED_TAG2;
+        assert!(!fb.tracks_tag(FeedbackAccount::TRACKED_TAG1));
+        assert!(fb.tracks_tag(FeedbackAccount::TRACKED_TAG2));
+        assert!(!fb.in_histogram());
+        fb.tracked_tags |= FeedbackAccount::TRACKED_HISTOGRAM;
+        assert!(fb.in_histogram());
+        assert!(fb.tracks_tag(FeedbackAccount::TRACKED_TAG2));
+    }
+
+    #[test]
+    fn test_min_client_balance() {
+        let mut config = ReputationConfig {
+            authority: Pubkey::new_unique(),
+            score_scale: ReputationConfig::DEFAULT_SCORE_SCALE,
+            require_live_agent: false,
+            edit_window: 0,
+            supported_policies: ReputationPolicy::ALL,
+            min_client_balance: 0,
+            identity_registry: Pubkey::new_unique(),
+            feedback_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            max_uri_length: ReputationConfig::DEFAULT_URI_LENGTH,
+            max_tracked_tags: ReputationConfig::DEFAULT_MAX_TRACKED_TAGS,
+            validation_registry: DEFAULT_VALIDATION_REGISTRY_ID,
+            bump: 255,
+        };
+        // Disabled by default: even an empty wallet passes
+        assert!(config.meets_min_client_balance(0));
+
+        config.min_client_balance = 1_000_000_000;
+        assert!(!config.meets_min_client_balance(999_999_999));
+        assert!(config.meets_min_client_balance(1_000_000_000));
+    }
+
+    #[test]
+    fn test_edit_window() {
+        let mut config = ReputationConfig {
+            authority: Pubkey::new_unique(),
+            score_scale: ReputationConfig::DEFAULT_SCORE_SCALE,
+            require_live_agent: false,
+            edit_window: 0,
+            supported_policies: ReputationPolicy::ALL,
+            min_client_balance: 0,
+            identity_registry: Pubkey::new_unique(),
+            feedback_fee_lamports: 0,
+            treasury: Pubkey::default(),
+            max_uri_length: ReputationConfig::DEFAULT_URI_LENGTH,
+            max_tracked_tags: ReputationConfig::DEFAULT_MAX_TRACKED_TAGS,
+            validation_registry: DEFAULT_VALIDATION_REGISTRY_ID,
+            bump: 255,
+        };
+        // Unlimited by default
+        assert!(config.is_editable(0, i64::MAX));
+
+        config.edit_window = 3600;
+        assert!(config.is_editable(1_000, 1_000));
+        assert!(config.is_editable(1_000, 4_600));
+        assert!(!config.is_editable(1_000, 4_601));
+        // No overflow near the end of time
+        assert!(config.is_editable(i64::MAX - 10, i64::MAX));
+    }
+
+    #[test]
+    fn test_agent_owner_from_data() {
+        let owner = Pubkey::new_unique();
+        let mut data = vec![0u8; 8];
+        data.extend_from_slice(&7u64.to_le_bytes());
+        data.extend_from_slice(owner.as_ref());
+
+        assert_eq!(agent_owner_from_data(&data, 7).unwrap(), owner);
+        // Wrong agent id, truncated or closed account
+        assert!(agent_owner_from_data(&data, 8).is_err());
+        assert!(agent_owner_from_data(&data[..47], 7).is_err());
+        assert!(agent_owner_from_data(&[], 7).is_err());
+    }
+
+    #[test]
+    fn test_validation_summary_from_data() {
+        use anchor_lang::solana_program::hash::hash;
+
+        assert_eq!(
+            hash(b"account:AgentValidationMetadata").to_bytes()[..8],
+            AGENT_VALIDATION_DISCRIMINATOR
+        );
+
+        let mut data = AGENT_VALIDATION_DISCRIMINATOR.to_vec();
+        for value in [7u64, 4, 3, 121] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&[0; 17]);
+
+        // 121 / 3 = 40.33 -> 40
+        assert_eq!(validation_summary_from_data(&data, 7).unwrap(), (3, 40));
+        // Wrong agent id, truncated or foreign account
+        assert!(validation_summary_from_data(&data, 8).is_err());
+        assert!(validation_summary_from_data(&data[..39], 7).is_err());
+        data[0] ^= 1;
+        assert!(validation_summary_from_data(&data, 7).is_err());
+    }
+
+    #[test]
+    fn test_blend_trust_score() {
+        // 80 feedback + 40 validation at 60/40
+        assert_eq!(blend_trust_score(80, 5, 40, 3, 6_000), 64);
+        // Full weight on either side
+        assert_eq!(blend_trust_score(80, 5, 40, 3, 10_000), 80);
+        assert_eq!(blend_trust_score(80, 5, 40, 3, 0), 40);
+        // Rounds half up: 0.5 * 81 + 0.5 * 40 = 60.5
+        assert_eq!(blend_trust_score(81, 5, 40, 3, 5_000), 61);
+        // A side without data falls back to the other
+        assert_eq!(blend_trust_score(80, 5, 0, 0, 6_000), 80);
+        assert_eq!(blend_trust_score(0, 0, 40, 3, 6_000), 40);
+        assert_eq!(blend_trust_score(0, 0, 0, 0, 6_000), 0);
+    }
+
+    #[test]
+    fn test_state_commitment_uses_registry_domain() {
+        let accounts = vec![(Pubkey::new_from_array([9; 32]), vec![42u8; 10])];
+
+        let commitment = state_commitment(5, accounts.clone());
+        assert_eq!(
+            commitment,
+            erc8004_common::state_commitment(STATE_COMMITMENT_DOMAIN, 5, accounts.clone())
+        );
+        // The Validation Registry commits to the same accounts differently
+        assert_ne!(
+            commitment,
+            erc8004_common::state_commitment(b"erc8004:validation:state:v1", 5, accounts)
+        );
+    }
+}
@@ -0,0 +1,381 @@
+//! Read-only view surface (`views` feature)
+//!
+//! Stable, versioned return-data encodings for CPI callers and simulating
+//! clients. Each view is Borsh-encoded via `set_return_data` by Anchor and
+//! starts with `version`; fields are only ever appended, so a decoder for
+//! version N can read any later version's prefix.
+
+use anchor_lang::prelude::*;
+
+use crate::state::{
+    AgentReputationMetadata, FeedbackAccount, ReputationConfig, ScoreHistogram,
+};
+
+/// Encoding version of every view in this module
+pub const VIEWS_VERSION: u8 = 1;
+
+/// `get_feedback` return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct FeedbackView {
+    pub version: u8,
+    pub agent_id: u64,
+    pub client_address: Pubkey,
+    pub feedback_index: u64,
+    pub score: u8,
+    pub score_scale: u8,
+    /// Score on the canonical 0-100 scale
+    pub normalized_score: u8,
+    pub tag1: [u8; 32],
+    pub tag2: [u8; 32],
+    pub file_uri: String,
+    pub file_hash: [u8; 32],
+    pub file_size: u64,
+    pub is_revoked: bool,
+    pub created_at: i64,
+}
+
+impl From<&FeedbackAccount> for FeedbackView {
+    fn from(feedback: &FeedbackAccount) -> Self {
+        Self {
+            version: VIEWS_VERSION,
+            agent_id: feedback.agent_id,
+            client_address: feedback.client_address,
+            feedback_index: feedback.feedback_index,
+            score: feedback.score,
+            score_scale: feedback.score_scale,
+            normalized_score: feedback.normalized_score(),
+            tag1: feedback.tag1,
+            tag2: feedback.tag2,
+            file_uri: feedback.file_uri.clone(),
+            file_hash: feedback.file_hash,
+            file_size: feedback.file_size,
+            is_revoked: feedback.is_revoked,
+            created_at: feedback.created_at,
+        }
+    }
+}
+
+/// `get_reputation` return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct ReputationView {
+    pub version: u8,
+    pub agent_id: u64,
+    pub total_feedbacks: u64,
+    pub average_score: u8,
+    pub confidence: u8,
+    pub reputation_policy: u8,
+    pub last_updated: i64,
+    /// Lowest and highest normalized score given (high-water marks)
+    pub min_score: u8,
+    pub max_score: u8,
+}
+
+impl From<&AgentReputationMetadata> for ReputationView {
+    fn from(reputation: &AgentReputationMetadata) -> Self {
+        Self {
+            version: VIEWS_VERSION,
+            agent_id: reputation.agent_id,
+            total_feedbacks: reputation.total_feedbacks,
+            average_score: reputation.average_score,
+            confidence: reputation.confidence,
+            reputation_policy: reputation.reputation_policy,
+            last_updated: reputation.last_updated,
+            min_score: reputation.min_score,
+            max_score: reputation.max_score,
+        }
+    }
+}
+
+/// `get_reputation_batch` return data entry
+///
+/// Deliberately smaller than `ReputationView` so a full batch fits in the
+/// 1024-byte return data limit.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct ReputationSummary {
+    pub version: u8,
+    /// False if the account at this position doesn't exist (no feedback yet)
+    /// or isn't an `AgentReputationMetadata`; every other field is zero then
+    pub found: bool,
+    pub agent_id: u64,
+    pub total_feedbacks: u64,
+    pub average_score: u8,
+    pub confidence: u8,
+    pub last_updated: i64,
+}
+
+impl ReputationSummary {
+    /// Maximum reputation accounts per `get_reputation_batch` call
+    /// Bounded by the return data limit: 4 + 32 * 28 bytes fits in 1024
+    pub const MAX_BATCH: usize = 32;
+
+    /// Entry for an account that couldn't be read
+    pub fn missing() -> Self {
+        Self {
+            version: VIEWS_VERSION,
+            found: false,
+            agent_id: 0,
+            total_feedbacks: 0,
+            average_score: 0,
+            confidence: 0,
+            last_updated: 0,
+        }
+    }
+
+    /// Summary of `info` if it holds an `AgentReputationMetadata`, else `missing()`
+    pub fn from_account_info(info: &AccountInfo) -> Self {
+        if info.data_is_empty() || info.owner != &crate::ID {
+            return Self::missing();
+        }
+        let Ok(data) = info.try_borrow_data() else {
+            return Self::missing();
+        };
+        match AgentReputationMetadata::try_deserialize(&mut &data[..]) {
+            Ok(reputation) => Self::from(&reputation),
+            Err(_) => Self::missing(),
+        }
+    }
+}
+
+impl From<&AgentReputationMetadata> for ReputationSummary {
+    fn from(reputation: &AgentReputationMetadata) -> Self {
+        Self {
+            version: VIEWS_VERSION,
+            found: true,
+            agent_id: reputation.agent_id,
+            total_feedbacks: reputation.total_feedbacks,
+            average_score: reputation.average_score,
+            confidence: reputation.confidence,
+            last_updated: reputation.last_updated,
+        }
+    }
+}
+
+/// `get_histogram` return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct HistogramView {
+    pub version: u8,
+    pub agent_id: u64,
+    /// Non-revoked feedback counts for normalized scores 0-9, 10-19, ..., 90-100
+    pub buckets: [u64; 10],
+}
+
+impl From<&ScoreHistogram> for HistogramView {
+    fn from(histogram: &ScoreHistogram) -> Self {
+        Self {
+            version: VIEWS_VERSION,
+            agent_id: histogram.agent_id,
+            buckets: histogram.buckets,
+        }
+    }
+}
+
+/// `get_config` return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct ReputationConfigView {
+    pub version: u8,
+    pub authority: Pubkey,
+    pub score_scale: u8,
+    pub require_live_agent: bool,
+    pub edit_window: i64,
+    pub supported_policies: u8,
+    pub min_client_balance: u64,
+    pub feedback_fee_lamports: u64,
+    pub treasury: Pubkey,
+    pub max_uri_length: u16,
+    pub max_tracked_tags: u16,
+    pub validation_registry: Pubkey,
+}
+
+impl From<&ReputationConfig> for ReputationConfigView {
+    fn from(config: &ReputationConfig) -> Self {
+        Self {
+            version: VIEWS_VERSION,
+            authority: config.authority,
+            score_scale: config.score_scale,
+            require_live_agent: config.require_live_agent,
+            edit_window: config.edit_window,
+            supported_policies: config.supported_policies,
+            min_client_balance: config.min_client_balance,
+            feedback_fee_lamports: config.feedback_fee_lamports,
+            treasury: config.treasury,
+            max_uri_length: config.max_uri_length,
+            max_tracked_tags: config.max_tracked_tags,
+            validation_registry: config.validation_registry,
+        }
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(agent_id: u64, client_address: Pubkey, feedback_index: u64)]
+pub struct GetFeedback<'info> {
+    #[account(
+        seeds = [
+            b"feedback",
+            agent_id.to_le_bytes().as_ref(),
+            client_address.as_ref(),
+            feedback_index.to_le_bytes().as_ref()
+        ],
+        bump = feedback_account.bump
+    )]
+    pub feedback_account: Account<'info, FeedbackAccount>,
+}
+
+#[derive(Accounts)]
+#[instruction(agent_id: u64)]
+pub struct GetReputation<'info> {
+    #[account(
+        seeds = [b"agent_reputation", agent_id.to_le_bytes().as_ref()],
+        bump = agent_reputation.bump
+    )]
+    pub agent_reputation: Account<'info, AgentReputationMetadata>,
+}
+
+#[derive(Accounts)]
+#[instruction(agent_id: u64)]
+pub struct GetHistogram<'info> {
+    #[account(
+        seeds = [b"histogram", agent_id.to_le_bytes().as_ref()],
+        bump = score_histogram.bump
+    )]
+    pub score_histogram: Account<'info, ScoreHistogram>,
+}
+
+#[derive(Accounts)]
+#[instruction(agent_id: u64)]
+pub struct GetTrustScore<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ReputationConfig>,
+
+    /// CHECK: Empty until the agent's first feedback; deserialized in the handler
+    #[account(seeds = [b"agent_reputation", agent_id.to_le_bytes().as_ref()], bump)]
+    pub agent_reputation: UncheckedAccount<'info>,
+
+    /// CHECK: Validation Registry `AgentValidationMetadata` PDA, empty until the
+    /// agent's first validation request; decoded by hand in the handler
+    #[account(
+        seeds = [b"agent_validation", agent_id.to_le_bytes().as_ref()],
+        bump,
+        seeds::program = config.validation_registry
+    )]
+    pub agent_validation: UncheckedAccount<'info>,
+}
+
+/// Reputation accounts are passed as remaining_accounts
+#[derive(Accounts)]
+pub struct GetReputationBatch<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ReputationConfig>,
+}
+
+#[derive(Accounts)]
+pub struct GetConfig<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ReputationConfig>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feedback_view_fits_return_data() {
+        let feedback = FeedbackAccount {
+            agent_id: 1,
+            client_address: Pubkey::new_unique(),
+            feedback_index: 0,
+            score: 4,
+            score_scale: 5,
+            tag1: [0; 32],
+            tag2: [0; 32],
+            file_uri: "x".repeat(ReputationConfig::MAX_URI_LENGTH as usize),
+            file_hash: [0; 32],
+            file_size: 0,
+            is_revoked: false,
+            created_at: 0,
+            is_disputed: false,
+            extra_tags: Vec::new(),
+            tracked_tags: 0,
+            bump: 255,
+        };
+
+        let view = FeedbackView::from(&feedback);
+        assert_eq!(view.normalized_score, 80);
+
+        // Largest encoding stays under the 1024-byte return data limit
+        let encoded = view.try_to_vec().unwrap();
+        assert_eq!(encoded[0], VIEWS_VERSION);
+        assert!(encoded.len() <= 1024);
+        assert_eq!(FeedbackView::try_from_slice(&encoded).unwrap(), view);
+    }
+
+    #[test]
+    fn test_reputation_view_summary() {
+        let reputation = AgentReputationMetadata {
+            agent_id: 7,
+            total_feedbacks: 2,
+            total_score_sum: 140,
+            average_score: 70,
+            confidence: 10,
+            last_updated: 1_700_000_000,
+            bump: 255,
+            feedback_account_count: 2,
+            reputation_policy: 0,
+            trimmed_feedbacks: 2,
+            trimmed_score_sum: 140,
+            decay_weight_sum: 0,
+            decay_weighted_score_sum: 0,
+            min_score: 60,
+            max_score: 80,
+            average_score_bps: 7000,
+            tracked_tag_count: 0,
+        };
+
+        let view = ReputationView::from(&reputation);
+        assert_eq!(view.agent_id, 7);
+        assert_eq!(view.total_feedbacks, 2);
+        assert_eq!(view.average_score, 70);
+        assert_eq!((view.min_score, view.max_score), (60, 80));
+        assert_eq!(view.last_updated, 1_700_000_000);
+
+        // Appended fields: the version 1 prefix still decodes as before
+        let encoded = view.try_to_vec().unwrap();
+        assert_eq!(encoded[0], VIEWS_VERSION);
+        assert_eq!(encoded.len(), 1 + 8 + 8 + 1 + 1 + 1 + 8 + 1 + 1);
+    }
+
+    #[test]
+    fn test_reputation_batch_fits_return_data() {
+        let reputation = AgentReputationMetadata {
+            agent_id: 3,
+            total_feedbacks: 4,
+            total_score_sum: 360,
+            average_score: 90,
+            confidence: 28,
+            last_updated: 1_700_000_000,
+            bump: 255,
+            feedback_account_count: 4,
+            reputation_policy: 0,
+            trimmed_feedbacks: 4,
+            trimmed_score_sum: 360,
+            decay_weight_sum: 0,
+            decay_weighted_score_sum: 0,
+            min_score: 80,
+            max_score: 100,
+            average_score_bps: 9000,
+            tracked_tag_count: 0,
+        };
+
+        let summary = ReputationSummary::from(&reputation);
+        assert!(summary.found);
+        assert_eq!((summary.agent_id, summary.average_score), (3, 90));
+
+        let missing = ReputationSummary::missing();
+        assert!(!missing.found);
+        assert_eq!(missing.version, VIEWS_VERSION);
+
+        let batch = vec![summary; ReputationSummary::MAX_BATCH];
+        let encoded = batch.try_to_vec().unwrap();
+        assert_eq!(encoded.len(), 4 + ReputationSummary::MAX_BATCH * 28);
+        assert!(encoded.len() <= 1024);
+    }
+}
@@ -2,10 +2,10 @@ use anchor_lang::prelude::*;
 
 #[error_code]
 pub enum ValidationError {
-    #[msg("Request URI exceeds maximum length of 200 bytes")]
+    #[msg("Request URI exceeds the registry's maximum URI length")]
     RequestUriTooLong,
 
-    #[msg("Response URI exceeds maximum length of 200 bytes")]
+    #[msg("Response URI exceeds the registry's maximum URI length")]
     ResponseUriTooLong,
 
     #[msg("Response must be between 0 and 100")]
@@ -31,4 +31,79 @@ pub enum ValidationError {
 
     #[msg("Request hash mismatch")]
     RequestHashMismatch,
+
+    #[msg("Response tag is required by the registry")]
+    MissingResponseTag,
+
+    #[msg("Only the registry authority can perform this action")]
+    UnauthorizedAuthority,
+
+    #[msg("Too many accounts passed to a batch instruction")]
+    BatchTooLarge,
+
+    #[msg("Validation request does not belong to this agent")]
+    AgentMismatch,
+
+    #[msg("A pending validation request with this hash already exists for the agent")]
+    DuplicateRequest,
+
+    #[msg("Request hash index account is required when dedup_by_hash is enabled")]
+    MissingRequestHashIndex,
+
+    #[msg("Maximum number of responses for this validation request reached")]
+    ResponseHistoryLimitReached,
+
+    #[msg("Response limit must be greater than 0")]
+    InvalidResponseLimit,
+
+    #[msg("Funding amount must be greater than 0")]
+    InvalidFundingAmount,
+
+    #[msg("Account passed more than once")]
+    DuplicateAccount,
+
+    #[msg("Validation request deadline has passed")]
+    ValidationExpired,
+
+    #[msg("Only the agent owner or registry authority can close this validation request")]
+    UnauthorizedClose,
+
+    #[msg("Only the original requester can cancel this validation request")]
+    UnauthorizedCancel,
+
+    #[msg("Validation request already has a response")]
+    ValidationNotPending,
+
+    #[msg("Validator is not registered")]
+    ValidatorNotRegistered,
+
+    #[msg("Validator metadata URI exceeds 200 bytes")]
+    ValidatorUriTooLong,
+
+    #[msg("Too many validators in one request (max 8)")]
+    TooManyValidators,
+
+    #[msg("Validation request account does not match the expected PDA")]
+    RequestAccountMismatch,
+
+    #[msg("Identity Registry program does not match the configured one")]
+    InvalidIdentityRegistry,
+
+    #[msg("Note URI exceeds 200 bytes")]
+    NoteUriTooLong,
+
+    #[msg("Passing threshold must be between 0 and 100")]
+    InvalidPassingThreshold,
+
+    #[msg("Reputation attestation is not enabled for this registry")]
+    AttestationDisabled,
+
+    #[msg("Reputation Registry program or accounts don't match the configured attestation")]
+    InvalidReputationRegistry,
+
+    #[msg("Max URI length must be between 1 and 512 bytes")]
+    InvalidMaxUriLength,
+
+    #[msg("Response entries must be the request's response_count entries in index order")]
+    ResponseEntriesMismatch,
 }
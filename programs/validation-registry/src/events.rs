@@ -25,4 +25,65 @@ pub struct ValidationResponded {
     pub response_hash: [u8; 32],
     pub tag: [u8; 32],
     pub responded_at: i64,
+    /// responded_at of the replaced response (0 for the first response)
+    pub supersedes_responded_at: i64,
+}
+
+/// Event emitted when the requester cancels a pending validation request
+#[event]
+pub struct ValidationCancelled {
+    pub agent_id: u64,
+    pub validator_address: Pubkey,
+    pub nonce: u32,
+    pub requester: Pubkey,
+}
+
+/// Event emitted when a pending validation request past its deadline is
+/// marked expired, or closed once past the grace period
+#[event]
+pub struct ValidationExpired {
+    pub agent_id: u64,
+    pub validator_address: Pubkey,
+    pub nonce: u32,
+    pub deadline: i64,
+    pub closed: bool,
+}
+
+/// Event emitted when a validator is paid from the reward pool
+#[event]
+pub struct ValidatorRewarded {
+    pub agent_id: u64,
+    pub validator_address: Pubkey,
+    pub nonce: u32,
+    pub amount: u64,
+    pub pool_balance: u64,
+}
+
+/// Event emitted when a passing response is attested as Reputation Registry feedback
+#[event]
+pub struct ValidationAttested {
+    pub agent_id: u64,
+    pub validator_address: Pubkey,
+    pub nonce: u32,
+    pub response: u8,
+    pub feedback_index: u64,
+}
+
+/// Event emitted when a note is attached to a validation request
+#[event]
+pub struct ValidationNoteAppended {
+    pub agent_id: u64,
+    pub validator_address: Pubkey,
+    pub nonce: u32,
+    pub note_index: u32,
+    pub author: Pubkey,
+    pub note_uri: String,
+    pub note_hash: [u8; 32],
+}
+
+/// Event emitted when the registry authority registers a validator
+#[event]
+pub struct ValidatorRegistered {
+    pub validator: Pubkey,
+    pub metadata_uri: String,
 }
@@ -1,23 +1,57 @@
+// Anchor's generated IDL handlers still call the deprecated `AccountInfo::realloc`
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::{get_return_data, invoke};
 
 mod error;
 mod events;
 mod state;
+#[cfg(feature = "views")]
+mod views;
 
 use error::ValidationError;
-use events::{ValidationRequested, ValidationResponded};
-use state::{ValidationConfig, ValidationRequest};
+use events::{
+    ValidationAttested, ValidationCancelled, ValidationExpired, ValidationNoteAppended,
+    ValidationRequested, ValidationResponded, ValidatorRegistered, ValidatorRewarded,
+};
+use state::{
+    agent_owner_from_data, give_feedback_data, is_agent_account, AgentValidationMetadata, FeedbackAuth, PendingValidation, RequestHashIndex, RewardPool, ValidationConfig,
+    ValidationNote, ValidationNoteIndex, ValidationRequest, ValidationResponseEntry,
+    ValidationStatus, ValidatorAccount, ValidatorNonceCounter, ATTEST_FEEDBACK_ACCOUNTS,
+    GET_OWNER_DISCRIMINATOR,
+};
+#[cfg(feature = "views")]
+use views::*;
 
 declare_id!("CXvuHNGWTHNqXmWr95wSpNGKR3kpcJUhzKofTF3zsoxW");
 
 #[program]
+#[allow(clippy::too_many_arguments)]
 pub mod validation_registry {
     use super::*;
 
     /// Initialize the Validation Registry with Identity Registry reference
     ///
     /// ERC-8004: Required setup to enable cross-program validation
-    pub fn initialize(ctx: Context<Initialize>, identity_registry: Pubkey) -> Result<()> {
+    ///
+    /// Args:
+    /// - identity_registry: Identity Registry program ID
+    /// - passing_threshold: Minimum response counted as passing (0-100, default 50).
+    ///   Fixed for the registry's lifetime, so rollups stay consistent
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        identity_registry: Pubkey,
+        passing_threshold: Option<u8>,
+    ) -> Result<()> {
+        let passing_threshold =
+            passing_threshold.unwrap_or(ValidationConfig::DEFAULT_PASSING_THRESHOLD);
+        require!(
+            ValidationConfig::is_valid_passing_threshold(passing_threshold),
+            ValidationError::InvalidPassingThreshold
+        );
+
         let config = &mut ctx.accounts.config;
 
         config.authority = ctx.accounts.authority.key();
@@ -25,6 +59,14 @@ pub mod validation_registry {
         config.total_requests = 0;
         config.total_responses = 0;
         config.bump = ctx.bumps.config;
+        config.require_response_tag = false;
+        config.dedup_by_hash = false;
+        config.max_responses_per_request = ValidationConfig::DEFAULT_MAX_RESPONSES_PER_REQUEST;
+        config.per_response_reward = 0;
+        config.require_registered_validators = false;
+        config.passing_threshold = passing_threshold;
+        config.reputation_registry = None;
+        config.max_uri_length = ValidationConfig::DEFAULT_URI_LENGTH;
 
         msg!("Validation Registry initialized");
         msg!("Identity Registry: {}", identity_registry);
@@ -32,6 +74,170 @@ pub mod validation_registry {
         Ok(())
     }
 
+    /// Require validators to categorize responses with a non-zero tag
+    ///
+    /// Only the registry authority can change this setting. Default is off.
+    pub fn set_require_response_tag(ctx: Context<UpdateConfig>, required: bool) -> Result<()> {
+        ctx.accounts.config.require_response_tag = required;
+
+        msg!("Response tag required: {}", required);
+
+        Ok(())
+    }
+
+    /// Reject requests duplicating the hash of a pending request for the same agent
+    ///
+    /// Only the registry authority can change this setting. Default is off.
+    pub fn set_dedup_by_hash(ctx: Context<UpdateConfig>, enabled: bool) -> Result<()> {
+        ctx.accounts.config.dedup_by_hash = enabled;
+
+        msg!("Dedup by request hash: {}", enabled);
+
+        Ok(())
+    }
+
+    /// Set the maximum number of responses a validator can record per request
+    ///
+    /// Only the registry authority can change this setting.
+    /// Default is `ValidationConfig::DEFAULT_MAX_RESPONSES_PER_REQUEST`.
+    pub fn set_max_responses_per_request(
+        ctx: Context<UpdateConfig>,
+        max_responses: u16,
+    ) -> Result<()> {
+        require!(max_responses > 0, ValidationError::InvalidResponseLimit);
+
+        ctx.accounts.config.max_responses_per_request = max_responses;
+
+        msg!("Max responses per request: {}", max_responses);
+
+        Ok(())
+    }
+
+    /// Set the lamports paid from the reward pool for each first response
+    ///
+    /// Only the registry authority can change this setting. Default is 0 (disabled).
+    pub fn set_per_response_reward(ctx: Context<UpdateConfig>, lamports: u64) -> Result<()> {
+        ctx.accounts.config.per_response_reward = lamports;
+
+        msg!("Per-response reward: {} lamports", lamports);
+
+        Ok(())
+    }
+
+    /// Only accept requests naming a registered validator
+    ///
+    /// Only the registry authority can change this setting. Default is off.
+    pub fn set_require_registered_validators(
+        ctx: Context<UpdateConfig>,
+        required: bool,
+    ) -> Result<()> {
+        ctx.accounts.config.require_registered_validators = required;
+
+        msg!("Registered validators required: {}", required);
+
+        Ok(())
+    }
+
+    /// Enable `respond_and_attest` with the given Reputation Registry program
+    ///
+    /// Only the registry authority can change this setting. Default is None
+    /// (attestation disabled); passing None disables it again.
+    pub fn set_reputation_registry(
+        ctx: Context<UpdateConfig>,
+        reputation_registry: Option<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts.config.reputation_registry = reputation_registry;
+
+        msg!("Reputation attestation: {:?}", reputation_registry);
+
+        Ok(())
+    }
+
+    /// Set the maximum request and response URI length (e.g. for long signed
+    /// gateway URLs)
+    ///
+    /// Only the registry authority can change this setting. Default is 200
+    /// bytes. These URIs are only emitted in events, so no account is resized;
+    /// validator metadata and note URIs keep their fixed 200-byte cap.
+    ///
+    /// Args:
+    /// - max_uri_length: New limit in bytes (1-512)
+    pub fn set_max_uri_length(ctx: Context<UpdateConfig>, max_uri_length: u16) -> Result<()> {
+        require!(
+            ValidationConfig::is_valid_max_uri_length(max_uri_length),
+            ValidationError::InvalidMaxUriLength
+        );
+
+        ctx.accounts.config.max_uri_length = max_uri_length;
+
+        msg!("Max URI length: {} bytes", max_uri_length);
+
+        Ok(())
+    }
+
+    /// Register a validator (curated validator set)
+    ///
+    /// Only the registry authority can register validators; it pays for the
+    /// `ValidatorAccount`.
+    ///
+    /// Args:
+    /// - metadata_uri: IPFS/Arweave link to validator metadata (max 200 bytes)
+    pub fn register_validator(ctx: Context<RegisterValidator>, metadata_uri: String) -> Result<()> {
+        require!(
+            metadata_uri.len() <= ValidatorAccount::MAX_URI_LENGTH,
+            ValidationError::ValidatorUriTooLong
+        );
+
+        let validator_account = &mut ctx.accounts.validator_account;
+        validator_account.validator = ctx.accounts.validator.key();
+        validator_account.metadata_uri = metadata_uri.clone();
+        validator_account.registered_at = Clock::get()?.unix_timestamp;
+        validator_account.bump = ctx.bumps.validator_account;
+
+        emit!(ValidatorRegistered {
+            validator: ctx.accounts.validator.key(),
+            metadata_uri,
+        });
+
+        msg!("Validator registered: {}", ctx.accounts.validator.key());
+
+        Ok(())
+    }
+
+    /// Add lamports to the shared validator reward pool
+    ///
+    /// Anyone can fund the pool; the pool account is created on first funding.
+    ///
+    /// Args:
+    /// - amount: Lamports to transfer from the funder into the pool
+    pub fn fund_reward_pool(ctx: Context<FundRewardPool>, amount: u64) -> Result<()> {
+        require!(amount > 0, ValidationError::InvalidFundingAmount);
+
+        anchor_lang::system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.funder.to_account_info(),
+                    to: ctx.accounts.reward_pool.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let pool = &mut ctx.accounts.reward_pool;
+        pool.bump = ctx.bumps.reward_pool;
+        pool.pool_balance = pool.pool_balance
+            .checked_add(amount)
+            .ok_or(ValidationError::Overflow)?;
+        pool.total_funded = pool.total_funded
+            .checked_add(amount)
+            .ok_or(ValidationError::Overflow)?;
+
+        msg!("Reward pool funded with {} lamports (balance {})", amount, pool.pool_balance);
+
+        Ok(())
+    }
+
     /// Request validation for an agent (ERC-8004: validationRequest)
     ///
     /// Only the agent owner can request validation.
@@ -41,8 +247,16 @@ pub mod validation_registry {
     /// - agent_id: Agent to validate
     /// - validator_address: Who can respond to this validation
     /// - nonce: Sequence number for multiple validations from same validator
-    /// - request_uri: IPFS/Arweave link to validation request (max 200 bytes)
+    /// - request_uri: IPFS/Arweave link to validation request (max `config.max_uri_length` bytes)
     /// - request_hash: SHA-256 hash of request content for integrity
+    /// - deadline: Unix timestamp after which responses are rejected (0 = no expiry)
+    ///
+    /// When `config.require_registered_validators` is set, the validator's
+    /// `validator_account` must be passed.
+    ///
+    /// When `config.dedup_by_hash` is set, `request_hash_index` must be passed.
+    /// If it already points to a request, that request must be passed as
+    /// `previous_request` and must have been responded to or closed.
     pub fn request_validation(
         ctx: Context<RequestValidation>,
         agent_id: u64,
@@ -50,30 +264,20 @@ pub mod validation_registry {
         nonce: u32,
         request_uri: String,
         request_hash: [u8; 32],
+        deadline: i64,
     ) -> Result<()> {
         // Validate URI length (ERC-8004 spec)
         require!(
-            request_uri.len() <= ValidationRequest::MAX_URI_LENGTH,
+            ctx.accounts.config.accepts_uri_length(request_uri.len()),
             ValidationError::RequestUriTooLong
         );
 
-        // Manually deserialize and verify agent account
-        let agent_data = ctx.accounts.agent_account.try_borrow_data()?;
-
-        // Skip 8-byte discriminator, then read fields:
-        // agent_id (8 bytes), owner (32 bytes), agent_mint (32 bytes)
-        require!(agent_data.len() >= 8 + 8 + 32, ValidationError::AgentNotFound);
-
-        let stored_agent_id = u64::from_le_bytes(
-            agent_data[8..16]
-                .try_into()
-                .map_err(|_| ValidationError::AgentNotFound)?
-        );
-        let stored_owner = Pubkey::try_from(&agent_data[16..48])
-            .map_err(|_| ValidationError::AgentNotFound)?;
-
-        // Verify agent_id matches
-        require!(stored_agent_id == agent_id, ValidationError::AgentNotFound);
+        // Ask the Identity Registry who owns the agent
+        let stored_owner = identity_owner_of(
+            &ctx.accounts.identity_registry_program,
+            &ctx.accounts.agent_account,
+            agent_id,
+        )?;
 
         // Verify requester is the owner
         require!(
@@ -81,6 +285,29 @@ pub mod validation_registry {
             ValidationError::UnauthorizedRequester
         );
 
+        // Curated registries only accept registered validators
+        if ctx.accounts.config.require_registered_validators {
+            require!(
+                ctx.accounts.validator_account.is_some(),
+                ValidationError::ValidatorNotRegistered
+            );
+        }
+
+        // Reject identical-content requests while an earlier one is pending
+        if ctx.accounts.config.dedup_by_hash {
+            claim_request_hash(
+                ctx.accounts
+                    .request_hash_index
+                    .as_mut()
+                    .ok_or(ValidationError::MissingRequestHashIndex)?,
+                ctx.accounts.previous_request.as_ref(),
+                agent_id,
+                request_hash,
+                ctx.accounts.validation_request.key(),
+                ctx.bumps.request_hash_index.unwrap_or_default(),
+            )?;
+        }
+
         let config = &mut ctx.accounts.config;
         let validation_request = &mut ctx.accounts.validation_request;
         let clock = Clock::get()?;
@@ -94,6 +321,11 @@ pub mod validation_registry {
         validation_request.response = 0; // 0 = pending
         validation_request.created_at = clock.unix_timestamp;
         validation_request.responded_at = 0; // No response yet
+        validation_request.supersedes_responded_at = 0;
+        validation_request.response_count = 0;
+        validation_request.deadline = deadline;
+        validation_request.requester = ctx.accounts.requester.key();
+        validation_request.status = ValidationStatus::Pending as u8;
         validation_request.bump = ctx.bumps.validation_request;
 
         // Increment total requests counter
@@ -101,6 +333,12 @@ pub mod validation_registry {
             .checked_add(1)
             .ok_or(ValidationError::Overflow)?;
 
+        ctx.accounts.agent_validation.record_request(
+            agent_id,
+            ctx.bumps.agent_validation,
+            clock.unix_timestamp,
+        )?;
+
         // Emit event with full metadata (URI stored in event, not on-chain)
         emit!(ValidationRequested {
             agent_id,
@@ -117,6 +355,277 @@ pub mod validation_registry {
         Ok(())
     }
 
+    /// Request validation with the next nonce for this (agent, validator) pair
+    ///
+    /// Same as `request_validation` without a `deadline`, except the nonce is
+    /// read from the pair's `ValidatorNonceCounter` (created on first use) and
+    /// the counter advanced, so callers never pick one. Nonces start at 0.
+    /// The manual `request_validation` remains for callers that manage nonces
+    /// themselves; mixing both for the same pair can hit a nonce already used.
+    ///
+    /// Args:
+    /// - agent_id: Agent to validate
+    /// - validator_address: Who can respond to this validation
+    /// - request_uri: IPFS/Arweave link to validation request (max `config.max_uri_length` bytes)
+    /// - request_hash: SHA-256 hash of request content for integrity
+    ///
+    /// `validator_account`, `request_hash_index` and `previous_request` are
+    /// required under the same config flags as in `request_validation`.
+    pub fn request_validation_auto(
+        ctx: Context<RequestValidationAuto>,
+        agent_id: u64,
+        validator_address: Pubkey,
+        request_uri: String,
+        request_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.config.accepts_uri_length(request_uri.len()),
+            ValidationError::RequestUriTooLong
+        );
+
+        let stored_owner = identity_owner_of(
+            &ctx.accounts.identity_registry_program,
+            &ctx.accounts.agent_account,
+            agent_id,
+        )?;
+        require!(
+            stored_owner == ctx.accounts.requester.key(),
+            ValidationError::UnauthorizedRequester
+        );
+
+        if ctx.accounts.config.require_registered_validators {
+            require!(
+                ctx.accounts.validator_account.is_some(),
+                ValidationError::ValidatorNotRegistered
+            );
+        }
+
+        if ctx.accounts.config.dedup_by_hash {
+            claim_request_hash(
+                ctx.accounts
+                    .request_hash_index
+                    .as_mut()
+                    .ok_or(ValidationError::MissingRequestHashIndex)?,
+                ctx.accounts.previous_request.as_ref(),
+                agent_id,
+                request_hash,
+                ctx.accounts.validation_request.key(),
+                ctx.bumps.request_hash_index.unwrap_or_default(),
+            )?;
+        }
+
+        // The request PDA was derived from the current counter value
+        let counter = &mut ctx.accounts.nonce_counter;
+        counter.agent_id = agent_id;
+        counter.validator_address = validator_address;
+        counter.bump = ctx.bumps.nonce_counter;
+        let nonce = counter.take_nonce()?;
+
+        let config = &mut ctx.accounts.config;
+        let validation_request = &mut ctx.accounts.validation_request;
+        let clock = Clock::get()?;
+
+        validation_request.agent_id = agent_id;
+        validation_request.validator_address = validator_address;
+        validation_request.nonce = nonce;
+        validation_request.request_hash = request_hash;
+        validation_request.response_hash = [0; 32];
+        validation_request.response = 0;
+        validation_request.created_at = clock.unix_timestamp;
+        validation_request.responded_at = 0;
+        validation_request.supersedes_responded_at = 0;
+        validation_request.response_count = 0;
+        validation_request.deadline = 0;
+        validation_request.requester = ctx.accounts.requester.key();
+        validation_request.status = ValidationStatus::Pending as u8;
+        validation_request.bump = ctx.bumps.validation_request;
+
+        config.total_requests = config.total_requests
+            .checked_add(1)
+            .ok_or(ValidationError::Overflow)?;
+
+        ctx.accounts.agent_validation.record_request(
+            agent_id,
+            ctx.bumps.agent_validation,
+            clock.unix_timestamp,
+        )?;
+
+        emit!(ValidationRequested {
+            agent_id,
+            validator_address,
+            nonce,
+            request_uri,
+            request_hash,
+            requester: ctx.accounts.requester.key(),
+            created_at: clock.unix_timestamp,
+        });
+
+        msg!(
+            "Validation requested for agent #{} by validator {} (nonce {})",
+            agent_id,
+            validator_address,
+            nonce
+        );
+
+        Ok(())
+    }
+
+    /// Request validation of the same content from several validators at once
+    ///
+    /// Creates one `ValidationRequest` per validator with `nonce = base_nonce + i`,
+    /// exactly as `request_validation` would. Only the agent owner can request.
+    ///
+    /// Args:
+    /// - agent_id: Agent to validate
+    /// - validators: Who can respond, one request each (max `MAX_MULTI_VALIDATORS`)
+    /// - base_nonce: Nonce of the first request
+    /// - request_uri: IPFS/Arweave link to validation request (max `config.max_uri_length` bytes)
+    /// - request_hash: SHA-256 hash of request content for integrity
+    /// - deadline: Unix timestamp after which responses are rejected (0 = no expiry)
+    ///
+    /// Remaining accounts: the N writable validation request PDAs in `validators`
+    /// order, followed by the N `ValidatorAccount`s when
+    /// `config.require_registered_validators` is set.
+    ///
+    /// Not available while `config.dedup_by_hash` is set, since every request
+    /// in the batch shares the same hash.
+    pub fn request_validation_multi<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RequestValidationMulti<'info>>,
+        agent_id: u64,
+        validators: Vec<Pubkey>,
+        base_nonce: u32,
+        request_uri: String,
+        request_hash: [u8; 32],
+        deadline: i64,
+    ) -> Result<()> {
+        require!(
+            validators.len() <= ValidationRequest::MAX_MULTI_VALIDATORS,
+            ValidationError::TooManyValidators
+        );
+
+        require!(
+            ctx.accounts.config.accepts_uri_length(request_uri.len()),
+            ValidationError::RequestUriTooLong
+        );
+
+        let stored_owner = identity_owner_of(
+            &ctx.accounts.identity_registry_program,
+            &ctx.accounts.agent_account,
+            agent_id,
+        )?;
+        require!(
+            stored_owner == ctx.accounts.requester.key(),
+            ValidationError::UnauthorizedRequester
+        );
+
+        require!(!ctx.accounts.config.dedup_by_hash, ValidationError::DuplicateRequest);
+
+        let registered = ctx.accounts.config.require_registered_validators;
+        let expected_accounts = if registered {
+            validators.len() * 2
+        } else {
+            validators.len()
+        };
+        require!(
+            ctx.remaining_accounts.len() == expected_accounts,
+            ValidationError::RequestAccountMismatch
+        );
+
+        let (request_infos, validator_infos) = ctx.remaining_accounts.split_at(validators.len());
+        let requester = ctx.accounts.requester.key();
+        let clock = Clock::get()?;
+        let space = 8 + ValidationRequest::SIZE;
+
+        for (i, (validator_address, info)) in validators.iter().zip(request_infos).enumerate() {
+            // Curated registries only accept registered validators
+            if registered {
+                let validator_account: Account<ValidatorAccount> =
+                    Account::try_from(&validator_infos[i])
+                        .map_err(|_| ValidationError::ValidatorNotRegistered)?;
+                require_keys_eq!(
+                    validator_account.validator,
+                    *validator_address,
+                    ValidationError::ValidatorNotRegistered
+                );
+            }
+
+            let nonce = base_nonce
+                .checked_add(i as u32)
+                .ok_or(ValidationError::Overflow)?;
+            let agent_id_bytes = agent_id.to_le_bytes();
+            let nonce_bytes = nonce.to_le_bytes();
+            let (expected, bump) = Pubkey::find_program_address(
+                &[b"validation", &agent_id_bytes, validator_address.as_ref(), &nonce_bytes],
+                &crate::ID,
+            );
+            require_keys_eq!(info.key(), expected, ValidationError::RequestAccountMismatch);
+
+            // Tops up a pre-funded address instead of failing like create_account
+            erc8004_common::create_pda_account(
+                &ctx.accounts.payer.to_account_info(),
+                info,
+                &ctx.accounts.system_program.to_account_info(),
+                space,
+                &crate::ID,
+                &[
+                    b"validation",
+                    &agent_id_bytes,
+                    validator_address.as_ref(),
+                    &nonce_bytes,
+                    &[bump],
+                ],
+            )?;
+
+            let validation_request = ValidationRequest {
+                agent_id,
+                validator_address: *validator_address,
+                nonce,
+                request_hash,
+                response_hash: [0; 32],
+                response: 0,
+                created_at: clock.unix_timestamp,
+                responded_at: 0,
+                supersedes_responded_at: 0,
+                response_count: 0,
+                deadline,
+                requester,
+                status: ValidationStatus::Pending as u8,
+                bump,
+            };
+            validation_request.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+
+            ctx.accounts.config.total_requests = ctx
+                .accounts
+                .config
+                .total_requests
+                .checked_add(1)
+                .ok_or(ValidationError::Overflow)?;
+            ctx.accounts.agent_validation.record_request(
+                agent_id,
+                ctx.bumps.agent_validation,
+                clock.unix_timestamp,
+            )?;
+
+            emit!(ValidationRequested {
+                agent_id,
+                validator_address: *validator_address,
+                nonce,
+                request_uri: request_uri.clone(),
+                request_hash,
+                requester,
+                created_at: clock.unix_timestamp,
+            });
+        }
+
+        msg!(
+            "Validation requested for agent #{} from {} validators",
+            agent_id,
+            validators.len()
+        );
+
+        Ok(())
+    }
+
     /// Validator responds to a validation request (ERC-8004: validationResponse)
     ///
     /// Only the designated validator can respond.
@@ -124,36 +633,107 @@ pub mod validation_registry {
     ///
     /// Args:
     /// - response: Validation score 0-100 (0=failed, 100=passed)
-    /// - response_uri: IPFS/Arweave link to validation report (max 200 bytes)
+    /// - response_uri: IPFS/Arweave link to validation report (max `config.max_uri_length` bytes)
     /// - response_hash: SHA-256 hash of response content
-    /// - tag: Tag for categorization (e.g., "oasf-v0.8.0", "zkml-verified")
+    /// - tag: Tag for categorization (e.g., "oasf-v0.8.0", "zkml-verified"),
+    ///   must be non-zero when `config.require_response_tag` is set
+    /// - expected_request_hash: Request hash the validator assessed; when non-zero
+    ///   it must match the request's hash (all zeros skips the check)
+    ///
+    /// Responses are rejected once the request's deadline (if any) has passed.
+    ///
+    /// Every response is also appended as a `ValidationResponseEntry` (paid by
+    /// the validator) at index `response_count`; the request keeps the latest.
+    /// The agent's `AgentValidationMetadata` rollup counts the latest response
+    /// of each request (an update replaces the previous one).
+    ///
+    /// When `reward_pool` is passed and `config.per_response_reward` is set, the
+    /// validator is paid that amount for the first response to a request, as
+    /// long as the pool still holds the full reward. Updates are never rewarded.
     pub fn respond_to_validation(
         ctx: Context<RespondToValidation>,
         response: u8,
         response_uri: String,
         response_hash: [u8; 32],
         tag: [u8; 32],
+        expected_request_hash: [u8; 32],
     ) -> Result<()> {
         // Validate response range (ERC-8004 spec: 0-100)
         require!(response <= 100, ValidationError::InvalidResponse);
 
+        // Bind the response to the request content the validator actually read
+        require!(
+            expected_request_hash == [0; 32]
+                || expected_request_hash == ctx.accounts.validation_request.request_hash,
+            ValidationError::RequestHashMismatch
+        );
+
         // Validate URI length
         require!(
-            response_uri.len() <= ValidationRequest::MAX_URI_LENGTH,
+            ctx.accounts.config.accepts_uri_length(response_uri.len()),
             ValidationError::ResponseUriTooLong
         );
 
+        // Validate tag if the registry requires one
+        require!(
+            ctx.accounts.config.accepts_response_tag(&tag),
+            ValidationError::MissingResponseTag
+        );
+
+        // Bound response history growth from update spam
+        require!(
+            ctx.accounts
+                .validation_request
+                .can_record_response(ctx.accounts.config.max_responses_per_request),
+            ValidationError::ResponseHistoryLimitReached
+        );
+
         let config = &mut ctx.accounts.config;
         let validation_request = &mut ctx.accounts.validation_request;
         let clock = Clock::get()?;
 
+        // Stale requests can't be answered (or updated) past their deadline
+        require!(
+            !validation_request.is_expired(clock.unix_timestamp),
+            ValidationError::ValidationExpired
+        );
+
+        // Append the response to the request's immutable history
+        let entry = &mut ctx.accounts.validation_response;
+        entry.agent_id = validation_request.agent_id;
+        entry.validator_address = validation_request.validator_address;
+        entry.nonce = validation_request.nonce;
+        entry.response_index = validation_request.response_count;
+        entry.response = response;
+        entry.response_hash = response_hash;
+        entry.tag = tag;
+        entry.responded_at = clock.unix_timestamp;
+        entry.bump = ctx.bumps.validation_response;
+
         // Check if this is the first response
-        let is_first_response = validation_request.responded_at == 0;
+        let is_first_response = validation_request.is_pending();
+
+        // Roll the response into the agent's validation stats
+        ctx.accounts.agent_validation.record_response(
+            config,
+            validation_request.agent_id,
+            ctx.bumps.agent_validation,
+            (!is_first_response).then_some(validation_request.response),
+            response,
+            clock.unix_timestamp,
+        )?;
 
         // Update validation request
         validation_request.response = response;
         validation_request.response_hash = response_hash;
+        // Link the new assessment to the one it replaces (0 on first response)
+        validation_request.supersedes_responded_at = validation_request.responded_at;
         validation_request.responded_at = clock.unix_timestamp;
+        validation_request.status = ValidationStatus::Responded as u8;
+        validation_request.response_count = validation_request
+            .response_count
+            .checked_add(1)
+            .ok_or(ValidationError::Overflow)?;
 
         // Increment total responses counter (only on first response)
         if is_first_response {
@@ -162,6 +742,30 @@ pub mod validation_registry {
                 .ok_or(ValidationError::Overflow)?;
         }
 
+        // Pay the validator from the shared pool (first response only)
+        if let Some(pool) = ctx.accounts.reward_pool.as_mut() {
+            if let Some(reward) = pool
+                .reward_due(config.per_response_reward)
+                .filter(|_| is_first_response)
+            {
+                pool.pool_balance -= reward;
+                pool.total_paid = pool.total_paid
+                    .checked_add(reward)
+                    .ok_or(ValidationError::Overflow)?;
+
+                **pool.to_account_info().try_borrow_mut_lamports()? -= reward;
+                **ctx.accounts.validator.to_account_info().try_borrow_mut_lamports()? += reward;
+
+                emit!(ValidatorRewarded {
+                    agent_id: validation_request.agent_id,
+                    validator_address: validation_request.validator_address,
+                    nonce: validation_request.nonce,
+                    amount: reward,
+                    pool_balance: pool.pool_balance,
+                });
+            }
+        }
+
         // Emit event with full metadata
         emit!(ValidationResponded {
             agent_id: validation_request.agent_id,
@@ -172,6 +776,7 @@ pub mod validation_registry {
             response_hash,
             tag,
             responded_at: clock.unix_timestamp,
+            supersedes_responded_at: validation_request.supersedes_responded_at,
         });
 
         msg!(
@@ -194,30 +799,518 @@ pub mod validation_registry {
         response_uri: String,
         response_hash: [u8; 32],
         tag: [u8; 32],
+        expected_request_hash: [u8; 32],
     ) -> Result<()> {
         // Same logic as respond_to_validation
         // ERC-8004 allows multiple responses (progressive validation)
-        respond_to_validation(ctx, response, response_uri, response_hash, tag)
+        respond_to_validation(
+            ctx,
+            response,
+            response_uri,
+            response_hash,
+            tag,
+            expected_request_hash,
+        )
     }
 
-    /// Close a validation request to recover rent (optional)
+    /// Respond to a validation and attest a passing response as reputation feedback
     ///
-    /// Only the agent owner or program authority can close validations.
-    /// Rent is returned to the specified receiver.
-    pub fn close_validation(
-        _ctx: Context<CloseValidation>,
+    /// Records the response exactly like `respond_to_validation`. When it
+    /// reaches `config.passing_threshold`, the validator then gives the agent
+    /// feedback in the Reputation Registry through a `give_feedback` CPI: the
+    /// validator is the client (and payer), the response is the score, `tag` is
+    /// tag1 and the response report is the feedback file. Failing responses
+    /// are recorded without feedback.
+    ///
+    /// Opt-in: the registry authority enables it with `set_reputation_registry`.
+    /// The Reputation Registry's rules apply unchanged, so the validator needs a
+    /// `feedback_auth` from the agent owner and the response must be within the
+    /// Reputation Registry's score scale (0-100 by default).
+    ///
+    /// Args:
+    /// - response, response_uri, response_hash, tag, expected_request_hash:
+    ///   as in `respond_to_validation`
+    /// - feedback_index: Validator's next feedback index for the agent
+    /// - feedback_auth: Agent owner's authorization for the validator as client
+    ///
+    /// Remaining accounts: the Reputation Registry program, then the
+    /// `ATTEST_FEEDBACK_ACCOUNTS` `give_feedback` accounts in order. Optional
+    /// ones (tag2_reputation, treasury) are passed as the Reputation Registry
+    /// program ID when left out.
+    pub fn respond_and_attest<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RespondToValidation<'info>>,
+        response: u8,
+        response_uri: String,
+        response_hash: [u8; 32],
+        tag: [u8; 32],
+        expected_request_hash: [u8; 32],
+        feedback_index: u64,
+        feedback_auth: FeedbackAuth,
     ) -> Result<()> {
-        // Account closure is handled automatically by Anchor's `close` constraint
-        msg!("Validation request closed, rent recovered");
-        Ok(())
-    }
-}
-
-// ============================================================================
-// Instruction Contexts
-// ============================================================================
+        let reputation_registry = ctx
+            .accounts
+            .config
+            .reputation_registry
+            .ok_or(ValidationError::AttestationDisabled)?;
+        let feedback_accounts = ctx.remaining_accounts;
+        require!(
+            feedback_accounts.len() == 1 + ATTEST_FEEDBACK_ACCOUNTS
+                && feedback_accounts[0].key() == reputation_registry,
+            ValidationError::InvalidReputationRegistry
+        );
 
-#[derive(Accounts)]
+        let passing = ctx.accounts.config.is_passing(response);
+        let agent_id = ctx.accounts.validation_request.agent_id;
+        let nonce = ctx.accounts.validation_request.nonce;
+        let validator = ctx.accounts.validator.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let data = give_feedback_data(
+            agent_id,
+            response,
+            tag,
+            &response_uri,
+            response_hash,
+            feedback_index,
+            &feedback_auth,
+        )?;
+
+        respond_to_validation(
+            ctx,
+            response,
+            response_uri,
+            response_hash,
+            tag,
+            expected_request_hash,
+        )?;
+
+        if passing {
+            give_feedback_cpi(feedback_accounts, &validator, &system_program, data)?;
+
+            emit!(ValidationAttested {
+                agent_id,
+                validator_address: validator.key(),
+                nonce,
+                response,
+                feedback_index,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Get all pending validation requests for an agent (view)
+    ///
+    /// Takes the agent's `ValidationRequest` accounts via `remaining_accounts`
+    /// and returns those still awaiting a response, in canonical order
+    /// (`PendingValidation::canonical_cmp`: created_at, then validator, then nonce)
+    /// whatever order they were passed in.
+    /// Up to `ValidationRequest::MAX_VIEW_BATCH` accounts per call.
+    ///
+    /// Args:
+    /// - agent_id: Agent whose requests are passed
+    pub fn get_pending_validations<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GetPendingValidations<'info>>,
+        agent_id: u64,
+    ) -> Result<Vec<PendingValidation>> {
+        require!(
+            ctx.remaining_accounts.len() <= ValidationRequest::MAX_VIEW_BATCH,
+            ValidationError::BatchTooLarge
+        );
+
+        let mut pending = Vec::new();
+
+        for info in ctx.remaining_accounts {
+            let request: Account<ValidationRequest> = Account::try_from(info)?;
+            require!(request.agent_id == agent_id, ValidationError::AgentMismatch);
+
+            if request.is_pending() {
+                pending.push(PendingValidation {
+                    validator_address: request.validator_address,
+                    nonce: request.nonce,
+                    created_at: request.created_at,
+                });
+            }
+        }
+
+        pending.sort_by(PendingValidation::canonical_cmp);
+
+        Ok(pending)
+    }
+
+    /// Commit to an agent's complete validation history (view)
+    ///
+    /// Takes every `ValidationRequest` account of the agent via `remaining_accounts`
+    /// and returns a SHA-256 commitment over their raw data in canonical order
+    /// (see `state::state_commitment`), independent of the order passed in.
+    /// Up to `ValidationRequest::MAX_COMMITMENT_BATCH` accounts per call.
+    ///
+    /// Args:
+    /// - agent_id: Agent whose requests are passed
+    pub fn state_commitment<'info>(
+        ctx: Context<'_, '_, 'info, 'info, StateCommitment<'info>>,
+        agent_id: u64,
+    ) -> Result<[u8; 32]> {
+        require!(
+            ctx.remaining_accounts.len() <= ValidationRequest::MAX_COMMITMENT_BATCH,
+            ValidationError::BatchTooLarge
+        );
+
+        let mut accounts = Vec::with_capacity(ctx.remaining_accounts.len());
+        for (i, info) in ctx.remaining_accounts.iter().enumerate() {
+            require!(
+                !ctx.remaining_accounts[..i].iter().any(|other| other.key == info.key),
+                ValidationError::DuplicateAccount
+            );
+
+            let request: Account<ValidationRequest> = Account::try_from(info)?;
+            require!(request.agent_id == agent_id, ValidationError::AgentMismatch);
+            accounts.push((info.key(), info.try_borrow_data()?.to_vec()));
+        }
+
+        Ok(state::state_commitment(agent_id, accounts))
+    }
+
+    /// Close a validation request to recover rent (optional)
+    ///
+    /// Only the agent owner or program authority can close validations.
+    /// The agent owner must pass the agent's Identity Registry account, which
+    /// is read like in request_validation; the authority needs no agent account.
+    /// Rent is returned to the specified receiver.
+    ///
+    /// The request's `ValidationResponseEntry` accounts are closed with it, so
+    /// a later request reusing the nonce can be answered again. Pass all
+    /// `response_count` entries as remaining_accounts in index order; their
+    /// rent goes back to the validator who paid for them. Requests with many
+    /// responses may need an address lookup table to fit the transaction.
+    pub fn close_validation<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseValidation<'info>>,
+    ) -> Result<()> {
+        let authority = ctx.accounts.authority.key();
+
+        if authority != ctx.accounts.config.authority {
+            let agent_account = ctx
+                .accounts
+                .agent_account
+                .as_ref()
+                .ok_or(ValidationError::UnauthorizedClose)?;
+            let owner = agent_owner_from_data(
+                &agent_account.try_borrow_data()?,
+                ctx.accounts.validation_request.agent_id,
+            )?;
+            require_keys_eq!(owner, authority, ValidationError::UnauthorizedClose);
+        }
+
+        let request = &ctx.accounts.validation_request;
+        require!(
+            ctx.remaining_accounts.len() == request.response_count as usize,
+            ValidationError::ResponseEntriesMismatch
+        );
+
+        for (index, info) in ctx.remaining_accounts.iter().enumerate() {
+            let entry: Account<ValidationResponseEntry> = Account::try_from(info)?;
+            require!(
+                entry.agent_id == request.agent_id
+                    && entry.validator_address == request.validator_address
+                    && entry.nonce == request.nonce
+                    && entry.response_index as usize == index,
+                ValidationError::ResponseEntriesMismatch
+            );
+            entry.close(ctx.accounts.validator.to_account_info())?;
+        }
+
+        // The request itself is closed by Anchor's `close` constraint
+        msg!("Validation request closed, rent recovered");
+        Ok(())
+    }
+
+    /// Cancel a validation request that hasn't been answered yet
+    ///
+    /// Only the original requester can cancel, and only while the request is
+    /// pending. The request is closed and its rent returned to the requester;
+    /// no status is stored, the `ValidationCancelled` event is the only record.
+    pub fn cancel_validation(ctx: Context<CancelValidation>) -> Result<()> {
+        let validation_request = &ctx.accounts.validation_request;
+
+        require!(validation_request.is_pending(), ValidationError::ValidationNotPending);
+
+        emit!(ValidationCancelled {
+            agent_id: validation_request.agent_id,
+            validator_address: validation_request.validator_address,
+            nonce: validation_request.nonce,
+            requester: validation_request.requester,
+        });
+
+        msg!(
+            "Validation request for agent #{} cancelled by {}",
+            validation_request.agent_id,
+            validation_request.requester
+        );
+
+        Ok(())
+    }
+
+    /// Mark pending validation requests past their deadline as expired
+    ///
+    /// Janitorial sweep for the registry authority. Takes `ValidationRequest`
+    /// accounts (writable) via `remaining_accounts`, up to
+    /// `ValidationRequest::MAX_EXPIRE_BATCH`; requests still answerable or
+    /// already answered are skipped instead of failing the batch. Expired
+    /// requests past `ValidationRequest::EXPIRY_GRACE_PERIOD` are closed and
+    /// their rent sent to `rent_receiver`. An expired request was never
+    /// answered, so it has no response entries to close with it.
+    pub fn expire_validations_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ExpireValidationsBatch<'info>>,
+    ) -> Result<()> {
+        require!(
+            ctx.remaining_accounts.len() <= ValidationRequest::MAX_EXPIRE_BATCH,
+            ValidationError::BatchTooLarge
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let mut expired = 0u32;
+        let mut closed = 0u32;
+
+        for (i, info) in ctx.remaining_accounts.iter().enumerate() {
+            require!(
+                !ctx.remaining_accounts[..i].iter().any(|other| other.key == info.key),
+                ValidationError::DuplicateAccount
+            );
+
+            let mut request: Account<ValidationRequest> = Account::try_from(info)?;
+            let marked = request.mark_expired(now);
+            let close = request.is_past_expiry_grace(now);
+            if !marked && !close {
+                continue;
+            }
+
+            emit!(ValidationExpired {
+                agent_id: request.agent_id,
+                validator_address: request.validator_address,
+                nonce: request.nonce,
+                deadline: request.deadline,
+                closed: close,
+            });
+
+            if marked {
+                expired += 1;
+            }
+            if close {
+                request.close(ctx.accounts.rent_receiver.to_account_info())?;
+                closed += 1;
+            } else {
+                request.exit(&crate::ID)?;
+            }
+        }
+
+        msg!("Expired {} validation requests, closed {}", expired, closed);
+        Ok(())
+    }
+
+    /// Attach a note (rebuttal, evidence) to a validation request
+    ///
+    /// Open to anyone, like `append_response` on feedback: the agent, the
+    /// validator or a third party. Each note gets its own PDA, indexed in
+    /// append order per request.
+    ///
+    /// Args:
+    /// - agent_id, validator_address, nonce: Request PDA seeds
+    /// - note_uri: Note content link (max 200 bytes)
+    /// - note_hash: SHA-256 of the note content
+    pub fn append_validation_note(
+        ctx: Context<AppendValidationNote>,
+        agent_id: u64,
+        validator_address: Pubkey,
+        nonce: u32,
+        note_uri: String,
+        note_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(
+            note_uri.len() <= ValidationNote::MAX_URI_LENGTH,
+            ValidationError::NoteUriTooLong
+        );
+
+        let note_index = &mut ctx.accounts.note_index;
+        note_index.agent_id = agent_id;
+        note_index.validator_address = validator_address;
+        note_index.nonce = nonce;
+        note_index.bump = ctx.bumps.note_index;
+        let index = note_index.take_index()?;
+
+        let note = &mut ctx.accounts.note;
+        note.agent_id = agent_id;
+        note.validator_address = validator_address;
+        note.nonce = nonce;
+        note.note_index = index;
+        note.author = ctx.accounts.author.key();
+        note.note_uri = note_uri.clone();
+        note.note_hash = note_hash;
+        note.created_at = Clock::get()?.unix_timestamp;
+        note.bump = ctx.bumps.note;
+
+        emit!(ValidationNoteAppended {
+            agent_id,
+            validator_address,
+            nonce,
+            note_index: index,
+            author: note.author,
+            note_uri,
+            note_hash,
+        });
+
+        msg!("Note #{} appended to validation of agent #{}", index, agent_id);
+
+        Ok(())
+    }
+
+    /// Read a validation request (view, `views` feature)
+    ///
+    /// Stable, versioned return data for CPI callers; see `views::ValidationView`.
+    ///
+    /// Args:
+    /// - agent_id, validator_address, nonce: Request PDA seeds
+    #[cfg(feature = "views")]
+    pub fn get_validation(
+        ctx: Context<GetValidation>,
+        _agent_id: u64,
+        _validator_address: Pubkey,
+        _nonce: u32,
+    ) -> Result<ValidationView> {
+        Ok(ValidationView::from(&*ctx.accounts.validation_request))
+    }
+
+    /// Read the registry configuration (view, `views` feature)
+    #[cfg(feature = "views")]
+    pub fn get_config(ctx: Context<GetConfig>) -> Result<ValidationConfigView> {
+        Ok(ValidationConfigView::from(&*ctx.accounts.config))
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Read the owner of `agent_id` through the Identity Registry's `get_owner` CPI
+///
+/// Only the `AgentAccount` discriminator is checked locally, so a wrong
+/// account type still fails with `AgentNotFound`; the rest of the layout is
+/// left to the Identity Registry, which also rejects a mismatched `agent_id`.
+fn identity_owner_of<'info>(
+    identity_registry_program: &AccountInfo<'info>,
+    agent_account: &AccountInfo<'info>,
+    agent_id: u64,
+) -> Result<Pubkey> {
+    require!(
+        is_agent_account(&agent_account.try_borrow_data()?),
+        ValidationError::AgentNotFound
+    );
+
+    let mut data = GET_OWNER_DISCRIMINATOR.to_vec();
+    data.extend_from_slice(&agent_id.to_le_bytes());
+    let ix = Instruction {
+        program_id: identity_registry_program.key(),
+        accounts: vec![AccountMeta::new_readonly(agent_account.key(), false)],
+        data,
+    };
+    invoke(&ix, &[agent_account.clone(), identity_registry_program.clone()])?;
+
+    let (program_id, owner) = get_return_data().ok_or(ValidationError::AgentNotFound)?;
+    require_keys_eq!(
+        program_id,
+        identity_registry_program.key(),
+        ValidationError::AgentNotFound
+    );
+    Pubkey::try_from(owner.as_slice()).map_err(|_| ValidationError::AgentNotFound.into())
+}
+
+/// Give Reputation Registry feedback as the validator (`respond_and_attest`)
+///
+/// `accounts` is the Reputation Registry program followed by the
+/// `give_feedback` accounts other than client, payer and system program.
+/// Their writable flags are forwarded as passed; the Reputation Registry
+/// checks every account itself.
+fn give_feedback_cpi<'info>(
+    accounts: &[AccountInfo<'info>],
+    validator: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    data: Vec<u8>,
+) -> Result<()> {
+    let (program, feedback_accounts) = accounts
+        .split_first()
+        .ok_or(ValidationError::InvalidReputationRegistry)?;
+    let (config, rest) = feedback_accounts
+        .split_first()
+        .ok_or(ValidationError::InvalidReputationRegistry)?;
+
+    // give_feedback order: config, client, payer, then the rest
+    let mut metas = vec![
+        AccountMeta::new_readonly(config.key(), false),
+        AccountMeta::new(validator.key(), true),
+        AccountMeta::new(validator.key(), true),
+    ];
+    metas.extend(rest.iter().map(|info| {
+        if info.is_writable {
+            AccountMeta::new(info.key(), false)
+        } else {
+            AccountMeta::new_readonly(info.key(), false)
+        }
+    }));
+    metas.push(AccountMeta::new_readonly(system_program.key(), false));
+
+    let mut infos = vec![config.clone(), validator.clone()];
+    infos.extend_from_slice(rest);
+    infos.push(system_program.clone());
+    infos.push(program.clone());
+
+    let ix = Instruction {
+        program_id: program.key(),
+        accounts: metas,
+        data,
+    };
+    invoke(&ix, &infos)?;
+
+    Ok(())
+}
+
+/// Point the (agent, request_hash) index at a new request (`dedup_by_hash`)
+///
+/// If the index already records a request, it must be passed as `previous`
+/// and must no longer be pending (responded to, cancelled or closed).
+fn claim_request_hash(
+    index: &mut Account<RequestHashIndex>,
+    previous: Option<&UncheckedAccount>,
+    agent_id: u64,
+    request_hash: [u8; 32],
+    validation_request: Pubkey,
+    bump: u8,
+) -> Result<()> {
+    if index.is_claimed() {
+        let previous = previous.ok_or(ValidationError::DuplicateRequest)?;
+        require_keys_eq!(
+            previous.key(),
+            index.validation_request,
+            ValidationError::DuplicateRequest
+        );
+
+        // Closed requests are no longer pending
+        if previous.owner == &crate::ID && !previous.data_is_empty() {
+            let previous =
+                ValidationRequest::try_deserialize(&mut &previous.try_borrow_data()?[..])?;
+            require!(!previous.is_pending(), ValidationError::DuplicateRequest);
+        }
+    }
+
+    index.agent_id = agent_id;
+    index.request_hash = request_hash;
+    index.validation_request = validation_request;
+    index.bump = bump;
+    Ok(())
+}
+
+// ============================================================================
+// Instruction Contexts
+// ============================================================================
+
+#[derive(Accounts)]
 pub struct Initialize<'info> {
     #[account(
         init,
@@ -235,7 +1328,20 @@ pub struct Initialize<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(agent_id: u64, validator_address: Pubkey, nonce: u32)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = authority.key() == config.authority @ ValidationError::UnauthorizedAuthority
+    )]
+    pub config: Account<'info, ValidationConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(agent_id: u64, validator_address: Pubkey, nonce: u32, request_uri: String, request_hash: [u8; 32])]
 pub struct RequestValidation<'info> {
     #[account(mut, seeds = [b"config"], bump = config.bump)]
     pub config: Account<'info, ValidationConfig>,
@@ -248,7 +1354,7 @@ pub struct RequestValidation<'info> {
     pub payer: Signer<'info>,
 
     /// Agent account from Identity Registry (for ownership verification)
-    /// CHECK: Verified via program ownership and manual deserialization
+    /// CHECK: Verified via program ownership and the Identity Registry's `get_owner` CPI
     #[account(
         constraint = agent_account.owner == &config.identity_registry @ ValidationError::AgentNotFound
     )]
@@ -269,8 +1375,165 @@ pub struct RequestValidation<'info> {
     )]
     pub validation_request: Account<'info, ValidationRequest>,
 
-    /// Identity Registry program (for CPI)
-    /// CHECK: Program ID verified via seeds::program constraint above
+    /// Agent validation rollup
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + AgentValidationMetadata::SIZE,
+        seeds = [b"agent_validation", agent_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agent_validation: Account<'info, AgentValidationMetadata>,
+
+    /// Registration of the named validator, required when `require_registered_validators` is on
+    #[account(seeds = [b"validator", validator_address.as_ref()], bump = validator_account.bump)]
+    pub validator_account: Option<Account<'info, ValidatorAccount>>,
+
+    /// Latest request for this (agent, request_hash), required when `dedup_by_hash` is on
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + RequestHashIndex::SIZE,
+        seeds = [b"request_hash", agent_id.to_le_bytes().as_ref(), request_hash.as_ref()],
+        bump
+    )]
+    pub request_hash_index: Option<Account<'info, RequestHashIndex>>,
+
+    /// Request currently recorded in `request_hash_index`, to prove it's no longer pending
+    /// CHECK: Key matched against request_hash_index, deserialized manually (may be closed)
+    pub previous_request: Option<UncheckedAccount<'info>>,
+
+    /// Identity Registry program (owner lookup via `get_owner` CPI)
+    /// CHECK: Address checked against the configured Identity Registry
+    #[account(
+        executable,
+        address = config.identity_registry @ ValidationError::InvalidIdentityRegistry
+    )]
+    pub identity_registry_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(agent_id: u64, validator_address: Pubkey, request_uri: String, request_hash: [u8; 32])]
+pub struct RequestValidationAuto<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ValidationConfig>,
+
+    /// Agent owner (must match agent_account.owner)
+    pub requester: Signer<'info>,
+
+    /// Payer for the new accounts (can be different from requester)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Agent account from Identity Registry (for ownership verification)
+    /// CHECK: Verified via program ownership and the Identity Registry's `get_owner` CPI
+    #[account(
+        constraint = agent_account.owner == &config.identity_registry @ ValidationError::AgentNotFound
+    )]
+    pub agent_account: UncheckedAccount<'info>,
+
+    /// Next nonce for this (agent, validator) pair
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + ValidatorNonceCounter::SIZE,
+        seeds = [b"val_nonce", agent_id.to_le_bytes().as_ref(), validator_address.as_ref()],
+        bump
+    )]
+    pub nonce_counter: Account<'info, ValidatorNonceCounter>,
+
+    /// Validation request PDA at the counter's current nonce
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ValidationRequest::SIZE,
+        seeds = [
+            b"validation",
+            agent_id.to_le_bytes().as_ref(),
+            validator_address.as_ref(),
+            nonce_counter.next_nonce.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub validation_request: Account<'info, ValidationRequest>,
+
+    /// Agent validation rollup
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + AgentValidationMetadata::SIZE,
+        seeds = [b"agent_validation", agent_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agent_validation: Account<'info, AgentValidationMetadata>,
+
+    /// Registration of the named validator, required when `require_registered_validators` is on
+    #[account(seeds = [b"validator", validator_address.as_ref()], bump = validator_account.bump)]
+    pub validator_account: Option<Account<'info, ValidatorAccount>>,
+
+    /// Latest request for this (agent, request_hash), required when `dedup_by_hash` is on
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + RequestHashIndex::SIZE,
+        seeds = [b"request_hash", agent_id.to_le_bytes().as_ref(), request_hash.as_ref()],
+        bump
+    )]
+    pub request_hash_index: Option<Account<'info, RequestHashIndex>>,
+
+    /// Request currently recorded in `request_hash_index`, to prove it's no longer pending
+    /// CHECK: Key matched against request_hash_index, deserialized manually (may be closed)
+    pub previous_request: Option<UncheckedAccount<'info>>,
+
+    /// Identity Registry program (owner lookup via `get_owner` CPI)
+    /// CHECK: Address checked against the configured Identity Registry
+    #[account(
+        executable,
+        address = config.identity_registry @ ValidationError::InvalidIdentityRegistry
+    )]
+    pub identity_registry_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(agent_id: u64)]
+pub struct RequestValidationMulti<'info> {
+    #[account(mut, seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ValidationConfig>,
+
+    /// Agent owner (must match agent_account.owner)
+    pub requester: Signer<'info>,
+
+    /// Payer for the validation request accounts (can be different from requester)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Agent account from Identity Registry (for ownership verification)
+    /// CHECK: Verified via program ownership and the Identity Registry's `get_owner` CPI
+    #[account(
+        constraint = agent_account.owner == &config.identity_registry @ ValidationError::AgentNotFound
+    )]
+    pub agent_account: UncheckedAccount<'info>,
+
+    /// Agent validation rollup
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + AgentValidationMetadata::SIZE,
+        seeds = [b"agent_validation", agent_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agent_validation: Account<'info, AgentValidationMetadata>,
+
+    /// Identity Registry program (owner lookup via `get_owner` CPI)
+    /// CHECK: Address checked against the configured Identity Registry
+    #[account(
+        executable,
+        address = config.identity_registry @ ValidationError::InvalidIdentityRegistry
+    )]
     pub identity_registry_program: UncheckedAccount<'info>,
 
     pub system_program: Program<'info, System>,
@@ -281,7 +1544,8 @@ pub struct RespondToValidation<'info> {
     #[account(mut, seeds = [b"config"], bump = config.bump)]
     pub config: Account<'info, ValidationConfig>,
 
-    /// Validator (must match validation_request.validator_address)
+    /// Validator (must match validation_request.validator_address), receives pool rewards
+    #[account(mut)]
     pub validator: Signer<'info>,
 
     /// Validation request to respond to
@@ -297,10 +1561,103 @@ pub struct RespondToValidation<'info> {
         constraint = validation_request.validator_address == validator.key() @ ValidationError::UnauthorizedValidator
     )]
     pub validation_request: Account<'info, ValidationRequest>,
+
+    /// History entry for this response (index = responses recorded so far)
+    #[account(
+        init,
+        payer = validator,
+        space = 8 + ValidationResponseEntry::SIZE,
+        seeds = [
+            b"validation_response",
+            validation_request.agent_id.to_le_bytes().as_ref(),
+            validation_request.validator_address.as_ref(),
+            validation_request.nonce.to_le_bytes().as_ref(),
+            validation_request.response_count.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub validation_response: Account<'info, ValidationResponseEntry>,
+
+    /// Agent validation rollup
+    #[account(
+        init_if_needed,
+        payer = validator,
+        space = 8 + AgentValidationMetadata::SIZE,
+        seeds = [b"agent_validation", validation_request.agent_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub agent_validation: Account<'info, AgentValidationMetadata>,
+
+    /// Shared reward pool (optional, pays `config.per_response_reward`)
+    #[account(mut, seeds = [b"reward_pool"], bump = reward_pool.bump)]
+    pub reward_pool: Option<Account<'info, RewardPool>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterValidator<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = authority.key() == config.authority @ ValidationError::UnauthorizedAuthority
+    )]
+    pub config: Account<'info, ValidationConfig>,
+
+    /// Registry authority (pays for the validator account)
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// Validator being registered
+    /// CHECK: Any key can be registered; only used as a PDA seed
+    pub validator: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ValidatorAccount::SIZE,
+        seeds = [b"validator", validator.key().as_ref()],
+        bump
+    )]
+    pub validator_account: Account<'info, ValidatorAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundRewardPool<'info> {
+    #[account(
+        init_if_needed,
+        payer = funder,
+        space = 8 + RewardPool::SIZE,
+        seeds = [b"reward_pool"],
+        bump
+    )]
+    pub reward_pool: Account<'info, RewardPool>,
+
+    #[account(mut)]
+    pub funder: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetPendingValidations<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ValidationConfig>,
+}
+
+#[derive(Accounts)]
+pub struct StateCommitment<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ValidationConfig>,
 }
 
 #[derive(Accounts)]
 pub struct CloseValidation<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ValidationConfig>,
+
     /// Agent owner or program authority
     pub authority: Signer<'info>,
 
@@ -322,8 +1679,115 @@ pub struct CloseValidation<'info> {
     #[account(mut)]
     pub rent_receiver: SystemAccount<'info>,
 
+    /// Validator of the request, refunded the rent of its response entries
+    /// CHECK: Address checked against the request; only receives lamports
+    #[account(mut, address = validation_request.validator_address @ ValidationError::UnauthorizedValidator)]
+    pub validator: UncheckedAccount<'info>,
+
+    /// Agent account from Identity Registry (required unless the signer is the
+    /// registry authority)
+    /// CHECK: Verified via program ownership and manual deserialization
+    #[account(
+        constraint = agent_account.owner == &config.identity_registry @ ValidationError::AgentNotFound
+    )]
+    pub agent_account: Option<UncheckedAccount<'info>>,
+
     /// Identity Registry program (for ownership verification via CPI if needed)
     /// CHECK: Optional, can be used for additional checks
     pub identity_registry_program: Option<UncheckedAccount<'info>>,
 }
 
+#[derive(Accounts)]
+pub struct ExpireValidationsBatch<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = authority.key() == config.authority @ ValidationError::UnauthorizedAuthority
+    )]
+    pub config: Account<'info, ValidationConfig>,
+
+    pub authority: Signer<'info>,
+
+    /// Receiver of the rent of requests closed past the grace period
+    #[account(mut)]
+    pub rent_receiver: SystemAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(agent_id: u64, validator_address: Pubkey, nonce: u32)]
+pub struct AppendValidationNote<'info> {
+    /// Note author (can be anyone)
+    pub author: Signer<'info>,
+
+    /// Payer for the note accounts (can differ from the author for sponsorship)
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// Validation request being annotated
+    #[account(
+        seeds = [
+            b"validation",
+            agent_id.to_le_bytes().as_ref(),
+            validator_address.as_ref(),
+            nonce.to_le_bytes().as_ref()
+        ],
+        bump = validation_request.bump
+    )]
+    pub validation_request: Account<'info, ValidationRequest>,
+
+    /// Note counter for the request (created with the first note)
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + ValidationNoteIndex::SIZE,
+        seeds = [
+            b"validation_note_index",
+            agent_id.to_le_bytes().as_ref(),
+            validator_address.as_ref(),
+            nonce.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub note_index: Account<'info, ValidationNoteIndex>,
+
+    /// Note account (index = notes appended so far)
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + ValidationNote::SIZE,
+        seeds = [
+            b"validation_note",
+            agent_id.to_le_bytes().as_ref(),
+            validator_address.as_ref(),
+            nonce.to_le_bytes().as_ref(),
+            note_index.next_index.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub note: Account<'info, ValidationNote>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelValidation<'info> {
+    /// Original requester (receives the rent)
+    #[account(mut)]
+    pub requester: Signer<'info>,
+
+    /// Pending validation request to cancel
+    #[account(
+        mut,
+        close = requester,
+        seeds = [
+            b"validation",
+            validation_request.agent_id.to_le_bytes().as_ref(),
+            validation_request.validator_address.as_ref(),
+            validation_request.nonce.to_le_bytes().as_ref()
+        ],
+        bump = validation_request.bump,
+        constraint = validation_request.requester == requester.key() @ ValidationError::UnauthorizedCancel
+    )]
+    pub validation_request: Account<'info, ValidationRequest>,
+}
+
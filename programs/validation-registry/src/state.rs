@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::error::ValidationError;
+
 /// Global validation registry configuration
 #[account]
 pub struct ValidationConfig {
@@ -17,11 +19,75 @@ pub struct ValidationConfig {
 
     /// PDA bump seed
     pub bump: u8,
+
+    /// Reject responses with an all-zero tag (default: false)
+    pub require_response_tag: bool,
+
+    /// Reject a request whose hash matches a pending request for the same agent (default: false)
+    pub dedup_by_hash: bool,
+
+    /// Maximum responses (initial + updates) a validator can record per request
+    pub max_responses_per_request: u16,
+
+    /// Lamports paid from the reward pool for each first response (0 = disabled)
+    pub per_response_reward: u64,
+
+    /// Only validators with a `ValidatorAccount` can be named in requests (default: false)
+    pub require_registered_validators: bool,
+
+    /// Minimum response counted as passing (0-100, fixed at initialize, default: 50)
+    pub passing_threshold: u8,
+
+    /// Reputation Registry program that `respond_and_attest` gives feedback in
+    /// (None = attestation disabled, the default)
+    pub reputation_registry: Option<Pubkey>,
+
+    /// Maximum request and response URI length accepted
+    /// (1..=MAX_URI_LENGTH, defaults to DEFAULT_URI_LENGTH)
+    pub max_uri_length: u16,
 }
 
 impl ValidationConfig {
-    /// Account size: 32 + 32 + 8 + 8 + 1 = 81 bytes
-    pub const SIZE: usize = 32 + 32 + 8 + 8 + 1;
+    /// Account size: 32 + 32 + 8 + 8 + 1 + 1 + 1 + 2 + 8 + 1 + 1 + 33 + 2 = 130 bytes
+    pub const SIZE: usize = 32 + 32 + 8 + 8 + 1 + 1 + 1 + 2 + 8 + 1 + 1 + 33 + 2;
+
+    /// Default `max_uri_length` (ERC-8004 spec)
+    pub const DEFAULT_URI_LENGTH: u16 = 200;
+
+    /// Highest `max_uri_length` a deployment may configure
+    /// Request and response URIs aren't stored, so only transaction size bounds this
+    pub const MAX_URI_LENGTH: u16 = 512;
+
+    /// Whether `max_uri_length` is a limit this registry supports
+    pub fn is_valid_max_uri_length(max_uri_length: u16) -> bool {
+        max_uri_length > 0 && max_uri_length <= Self::MAX_URI_LENGTH
+    }
+
+    /// Whether a request or response URI of `len` bytes is accepted
+    pub fn accepts_uri_length(&self, len: usize) -> bool {
+        len <= self.max_uri_length as usize
+    }
+
+    /// Default cap on responses per request (bounds progressive-validation growth)
+    pub const DEFAULT_MAX_RESPONSES_PER_REQUEST: u16 = 32;
+
+    /// Passing threshold when `initialize` isn't given one
+    pub const DEFAULT_PASSING_THRESHOLD: u8 = 50;
+
+    /// Whether `threshold` is on the 0-100 response scale
+    pub fn is_valid_passing_threshold(threshold: u8) -> bool {
+        threshold <= 100
+    }
+
+    /// Whether a response counts as passing in this registry
+    pub fn is_passing(&self, response: u8) -> bool {
+        response >= self.passing_threshold
+    }
+
+    /// Check whether a response tag satisfies the registry's tag requirement
+    pub fn accepts_response_tag(&self, tag: &[u8; 32]) -> bool {
+        !self.require_response_tag || tag.iter().any(|b| *b != 0)
+    }
 }
 
 /// Individual validation request (optimized for cost - minimal state)
@@ -53,27 +119,592 @@ pub struct ValidationRequest {
     /// Timestamp of last response (0 if no response yet)
     pub responded_at: i64,
 
+    /// `responded_at` of the response the latest one replaced
+    /// (0 for the first response or if no response yet)
+    pub supersedes_responded_at: i64,
+
+    /// Number of responses recorded (initial response + updates)
+    pub response_count: u16,
+
+    /// Unix timestamp after which the validator can no longer respond
+    /// (0 = no expiry)
+    pub deadline: i64,
+
+    /// Agent owner who created the request (may cancel it while pending)
+    pub requester: Pubkey,
+
+    /// Lifecycle status (`ValidationStatus` as u8), so a score-0 response is
+    /// distinguishable from no response
+    pub status: u8,
+
     /// PDA bump seed
     pub bump: u8,
 }
 
 impl ValidationRequest {
-    /// Account size: 8 + 32 + 4 + 32 + 32 + 1 + 8 + 8 + 1 = 126 bytes
-    /// This is 5x smaller than storing URIs on-chain (~590 bytes)
-    /// Cost savings: ~$0.67 → ~$0.14 per validation
-    pub const SIZE: usize = 8 + 32 + 4 + 32 + 32 + 1 + 8 + 8 + 1;
+    /// Account size: 8 + 32 + 4 + 32 + 32 + 1 + 8 + 8 + 8 + 2 + 8 + 32 + 1 + 1 = 177 bytes
+    /// This is 4x smaller than storing URIs on-chain (~590 bytes)
+    /// Cost savings: ~$0.67 → ~$0.15 per validation
+    pub const SIZE: usize = 8 + 32 + 4 + 32 + 32 + 1 + 8 + 8 + 8 + 2 + 8 + 32 + 1 + 1;
 
-    /// Maximum URI length per ERC-8004 spec (validated but not stored on-chain)
-    pub const MAX_URI_LENGTH: usize = 200;
+    /// Maximum request accounts per view call
+    /// 16 * 44 bytes (PendingValidation) + 4 fits in the 1024-byte return data limit
+    pub const MAX_VIEW_BATCH: usize = 16;
+
+    /// Maximum request accounts per state commitment
+    ///
+    /// Leaves room for the fee payer, the program and the config account under
+    /// the 64-account transaction lock limit. A legacy transaction only fits
+    /// about 30 account keys, so larger batches must be sent as a v0
+    /// transaction with an address lookup table.
+    pub const MAX_COMMITMENT_BATCH: usize = 60;
+
+    /// Maximum validators per `request_validation_multi` call
+    /// Bounded by compute: each request costs a PDA derivation and an account creation
+    pub const MAX_MULTI_VALIDATORS: usize = 8;
+
+    /// Maximum request accounts per `expire_validations_batch` call
+    /// Bounded by compute: each request is deserialized, then written back or closed
+    pub const MAX_EXPIRE_BATCH: usize = 20;
+
+    /// Time after the deadline before `expire_validations_batch` closes an
+    /// expired request (30 days), so indexers can still read it meanwhile
+    pub const EXPIRY_GRACE_PERIOD: i64 = 30 * 24 * 60 * 60;
 
     /// Check if validation has been responded to
     pub fn has_response(&self) -> bool {
-        self.responded_at > 0
+        self.status == ValidationStatus::Responded as u8
     }
 
     /// Check if response is pending
     pub fn is_pending(&self) -> bool {
-        self.responded_at == 0
+        self.status == ValidationStatus::Pending as u8
+    }
+
+    /// Status as of `now`: a pending request past its deadline reads as expired
+    pub fn effective_status(&self, now: i64) -> ValidationStatus {
+        match ValidationStatus::from_u8(self.status) {
+            Some(ValidationStatus::Pending) if self.is_expired(now) => ValidationStatus::Expired,
+            Some(status) => status,
+            None => ValidationStatus::Pending,
+        }
+    }
+
+    /// Check whether the response deadline has passed at `now`
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.deadline != 0 && now > self.deadline
+    }
+
+    /// Store `Expired` on a pending request past its deadline at `now`,
+    /// returning whether the status changed
+    pub fn mark_expired(&mut self, now: i64) -> bool {
+        if !self.is_pending() || !self.is_expired(now) {
+            return false;
+        }
+        self.status = ValidationStatus::Expired as u8;
+        true
+    }
+
+    /// Check whether an expired request is past the grace period at `now`
+    pub fn is_past_expiry_grace(&self, now: i64) -> bool {
+        self.status == ValidationStatus::Expired as u8
+            && now > self.deadline.saturating_add(Self::EXPIRY_GRACE_PERIOD)
+    }
+
+    /// Check whether another response fits under the per-request limit
+    pub fn can_record_response(&self, max_responses: u16) -> bool {
+        self.response_count < max_responses
+    }
+}
+
+/// Lifecycle of a validation request
+///
+/// `Expired` is stored by `expire_validations_batch`; until a sweep reaches
+/// it, a pending request past its deadline keeps `Pending` and is reported as
+/// expired by `ValidationRequest::effective_status`.
+/// Cancellation has no status: `cancel_validation` closes the account, so it
+/// is only signalled by the `ValidationCancelled` event.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ValidationStatus {
+    /// Awaiting the validator's first response
+    Pending = 0,
+    /// At least one response recorded
+    Responded = 1,
+    /// Deadline passed without a response
+    Expired = 3,
+}
+
+impl ValidationStatus {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Pending),
+            1 => Some(Self::Responded),
+            3 => Some(Self::Expired),
+            _ => None,
+        }
+    }
+}
+
+/// One recorded response to a validation request, never modified once written
+/// Seeds: [b"validation_response", agent_id, validator_address, nonce, response_index]
+///
+/// The request keeps the latest response as its summary; these entries keep
+/// the whole progressive-validation trajectory. URIs stay in events only.
+#[account]
+pub struct ValidationResponseEntry {
+    /// Agent ID from Identity Registry
+    pub agent_id: u64,
+
+    /// Validator who responded
+    pub validator_address: Pubkey,
+
+    /// Nonce of the validation request
+    pub nonce: u32,
+
+    /// Position in the request's response history (0 = initial response)
+    pub response_index: u16,
+
+    /// Response value (0-100)
+    pub response: u8,
+
+    /// Response hash (SHA-256 of response content)
+    pub response_hash: [u8; 32],
+
+    /// Response tag
+    pub tag: [u8; 32],
+
+    /// Timestamp of this response
+    pub responded_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ValidationResponseEntry {
+    /// Account size: 8 + 32 + 4 + 2 + 1 + 32 + 32 + 8 + 1 = 120 bytes
+    pub const SIZE: usize = 8 + 32 + 4 + 2 + 1 + 32 + 32 + 8 + 1;
+}
+
+/// Cached rollup of an agent's validation outcomes
+/// Seeds: [b"agent_validation", agent_id]
+///
+/// Responses count once per request: an update replaces the request's
+/// previous response in `sum_responses` and `passing_count`.
+#[account]
+pub struct AgentValidationMetadata {
+    /// Agent ID from Identity Registry
+    pub agent_id: u64,
+
+    /// Validation requests created for this agent
+    pub total_requests: u64,
+
+    /// Requests that received at least one response
+    pub total_responses: u64,
+
+    /// Sum of the latest response of each responded request
+    pub sum_responses: u64,
+
+    /// Responded requests whose latest response is passing
+    pub passing_count: u64,
+
+    /// Timestamp of the last request or response
+    pub last_updated: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl AgentValidationMetadata {
+    /// Account size: 8 + 8 + 8 + 8 + 8 + 8 + 1 = 49 bytes
+    pub const SIZE: usize = 8 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    /// Record a new request (initializes the account on first use)
+    pub fn record_request(&mut self, agent_id: u64, bump: u8, now: i64) -> Result<()> {
+        if self.agent_id == 0 {
+            self.agent_id = agent_id;
+            self.bump = bump;
+        }
+        self.total_requests = self
+            .total_requests
+            .checked_add(1)
+            .ok_or(ValidationError::Overflow)?;
+        self.last_updated = now;
+        Ok(())
+    }
+
+    /// Record a response; `previous` is the request's earlier response, if any
+    /// (pass/fail by the registry's `passing_threshold`)
+    pub fn record_response(
+        &mut self,
+        config: &ValidationConfig,
+        agent_id: u64,
+        bump: u8,
+        previous: Option<u8>,
+        response: u8,
+        now: i64,
+    ) -> Result<()> {
+        if self.agent_id == 0 {
+            self.agent_id = agent_id;
+            self.bump = bump;
+        }
+
+        match previous {
+            Some(previous) => {
+                self.sum_responses = self
+                    .sum_responses
+                    .checked_sub(previous as u64)
+                    .ok_or(ValidationError::Overflow)?;
+                if config.is_passing(previous) {
+                    self.passing_count = self
+                        .passing_count
+                        .checked_sub(1)
+                        .ok_or(ValidationError::Overflow)?;
+                }
+            }
+            None => {
+                self.total_responses = self
+                    .total_responses
+                    .checked_add(1)
+                    .ok_or(ValidationError::Overflow)?;
+            }
+        }
+
+        self.sum_responses = self
+            .sum_responses
+            .checked_add(response as u64)
+            .ok_or(ValidationError::Overflow)?;
+        if config.is_passing(response) {
+            self.passing_count = self
+                .passing_count
+                .checked_add(1)
+                .ok_or(ValidationError::Overflow)?;
+        }
+        self.last_updated = now;
+        Ok(())
+    }
+
+    /// Average latest response (0-100, rounded half up; 0 with no responses)
+    pub fn average_response(&self) -> u8 {
+        if self.total_responses == 0 {
+            return 0;
+        }
+        ((self.sum_responses + self.total_responses / 2) / self.total_responses) as u8
+    }
+}
+
+/// Validator admitted by the registry authority
+/// Seeds: [b"validator", validator]
+///
+/// Only consulted when `require_registered_validators` is set.
+#[account]
+pub struct ValidatorAccount {
+    /// Registered validator
+    pub validator: Pubkey,
+
+    /// Validator metadata (IPFS/Arweave link, max 200 bytes)
+    pub metadata_uri: String,
+
+    /// Registration timestamp
+    pub registered_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ValidatorAccount {
+    /// Account size: 32 + 4 + 200 + 8 + 1 = 245 bytes
+    pub const SIZE: usize = 32 + 4 + 200 + 8 + 1;
+
+    /// Maximum metadata URI length
+    pub const MAX_URI_LENGTH: usize = 200;
+}
+
+/// Latest request per (agent, request_hash), used when `dedup_by_hash` is on
+/// Seeds: [b"request_hash", agent_id, request_hash]
+#[account]
+pub struct RequestHashIndex {
+    /// Agent ID from Identity Registry
+    pub agent_id: u64,
+
+    /// Request hash this index is keyed by
+    pub request_hash: [u8; 32],
+
+    /// Most recent validation request created with this hash
+    pub validation_request: Pubkey,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl RequestHashIndex {
+    /// Account size: 8 + 32 + 32 + 1 = 73 bytes
+    pub const SIZE: usize = 8 + 32 + 32 + 1;
+
+    /// Check whether a request has been recorded for this hash yet
+    pub fn is_claimed(&self) -> bool {
+        self.validation_request != Pubkey::default()
+    }
+}
+
+/// Next nonce per (agent, validator), used by `request_validation_auto`
+/// Seeds: [b"val_nonce", agent_id, validator_address]
+#[account]
+pub struct ValidatorNonceCounter {
+    /// Agent ID from Identity Registry
+    pub agent_id: u64,
+
+    /// Validator the nonces are issued for
+    pub validator_address: Pubkey,
+
+    /// Nonce the next auto request will use
+    pub next_nonce: u32,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ValidatorNonceCounter {
+    /// Account size: 8 + 32 + 4 + 1 = 45 bytes
+    pub const SIZE: usize = 8 + 32 + 4 + 1;
+
+    /// Hand out `next_nonce` and advance the counter
+    pub fn take_nonce(&mut self) -> Result<u32> {
+        let nonce = self.next_nonce;
+        self.next_nonce = nonce.checked_add(1).ok_or(ValidationError::Overflow)?;
+        Ok(nonce)
+    }
+}
+
+/// Next note index per validation request, used by `append_validation_note`
+/// Seeds: [b"validation_note_index", agent_id, validator_address, nonce]
+#[account]
+pub struct ValidationNoteIndex {
+    /// Agent ID from Identity Registry
+    pub agent_id: u64,
+
+    /// Validator of the annotated request
+    pub validator_address: Pubkey,
+
+    /// Nonce of the annotated request
+    pub nonce: u32,
+
+    /// Index the next note will use
+    pub next_index: u32,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ValidationNoteIndex {
+    /// Account size: 8 + 32 + 4 + 4 + 1 = 49 bytes
+    pub const SIZE: usize = 8 + 32 + 4 + 4 + 1;
+
+    /// Hand out `next_index` and advance the counter
+    pub fn take_index(&mut self) -> Result<u32> {
+        let index = self.next_index;
+        self.next_index = index.checked_add(1).ok_or(ValidationError::Overflow)?;
+        Ok(index)
+    }
+}
+
+/// Rebuttal or evidence attached to a validation request, by anyone
+/// Seeds: [b"validation_note", agent_id, validator_address, nonce, note_index]
+#[account]
+pub struct ValidationNote {
+    /// Agent ID from Identity Registry
+    pub agent_id: u64,
+
+    /// Validator of the annotated request
+    pub validator_address: Pubkey,
+
+    /// Nonce of the annotated request
+    pub nonce: u32,
+
+    /// Sequential note index for this request
+    pub note_index: u32,
+
+    /// Who appended the note (agent, validator, anyone)
+    pub author: Pubkey,
+
+    /// Note URI (IPFS/Arweave link, max 200 bytes)
+    pub note_uri: String,
+
+    /// Note hash (SHA-256 of note content)
+    pub note_hash: [u8; 32],
+
+    /// Creation timestamp
+    pub created_at: i64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl ValidationNote {
+    /// Account size: 8 + 32 + 4 + 4 + 32 + 4 + 200 + 32 + 8 + 1 = 325 bytes
+    pub const SIZE: usize = 8 + 32 + 4 + 4 + 32 + 4 + 200 + 32 + 8 + 1;
+
+    /// Maximum note URI length
+    pub const MAX_URI_LENGTH: usize = 200;
+}
+
+/// Shared validator reward pool, funded by anyone
+/// Seeds: [b"reward_pool"]
+///
+/// Rewards are paid out of the account's own lamports; `pool_balance` tracks
+/// the funded amount so the rent-exempt reserve is never drawn.
+#[account]
+pub struct RewardPool {
+    /// Lamports available for rewards
+    pub pool_balance: u64,
+
+    /// Lifetime lamports funded
+    pub total_funded: u64,
+
+    /// Lifetime lamports paid to validators
+    pub total_paid: u64,
+
+    /// PDA bump seed
+    pub bump: u8,
+}
+
+impl RewardPool {
+    /// Account size: 8 + 8 + 8 + 1 = 25 bytes
+    pub const SIZE: usize = 8 + 8 + 8 + 1;
+
+    /// Reward to pay for a response, or None when rewards are disabled or the
+    /// pool can't cover the full amount (no partial payouts)
+    pub fn reward_due(&self, per_response_reward: u64) -> Option<u64> {
+        (per_response_reward > 0 && self.pool_balance >= per_response_reward)
+            .then_some(per_response_reward)
+    }
+}
+
+/// Anchor discriminator of the Identity Registry's `AgentAccount`
+/// (sha256("account:AgentAccount")[..8])
+pub const AGENT_ACCOUNT_DISCRIMINATOR: [u8; 8] = [241, 119, 69, 140, 233, 9, 112, 50];
+
+/// Whether raw Identity Registry account data starts with the `AgentAccount`
+/// discriminator, so its fields can be read by offset
+pub fn is_agent_account(data: &[u8]) -> bool {
+    data.len() >= 8 && data[..8] == AGENT_ACCOUNT_DISCRIMINATOR
+}
+
+/// Read the owner of `agent_id` from raw Identity Registry `AgentAccount` data
+///
+/// Layout after the 8-byte discriminator: agent_id (8 bytes), owner (32 bytes)
+pub fn agent_owner_from_data(data: &[u8], agent_id: u64) -> Result<Pubkey> {
+    require!(data.len() >= 8 + 8 + 32, ValidationError::AgentNotFound);
+    require!(is_agent_account(data), ValidationError::AgentNotFound);
+
+    let stored_agent_id = u64::from_le_bytes(
+        data[8..16]
+            .try_into()
+            .map_err(|_| ValidationError::AgentNotFound)?
+    );
+    require!(stored_agent_id == agent_id, ValidationError::AgentNotFound);
+
+    Pubkey::try_from(&data[16..48]).map_err(|_| ValidationError::AgentNotFound.into())
+}
+
+/// Anchor discriminator of the Identity Registry's `get_owner` instruction
+/// (sha256("global:get_owner")[..8])
+pub const GET_OWNER_DISCRIMINATOR: [u8; 8] = [122, 232, 247, 85, 211, 245, 62, 91];
+
+/// Anchor discriminator of the Reputation Registry's `give_feedback` instruction
+/// (sha256("global:give_feedback")[..8])
+pub const GIVE_FEEDBACK_DISCRIMINATOR: [u8; 8] = [145, 136, 123, 3, 215, 165, 98, 41];
+
+/// Reputation Registry `FeedbackAuth`, forwarded as-is by `respond_and_attest`
+///
+/// Field order must match the Reputation Registry's struct: it is part of the
+/// `give_feedback` instruction data.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct FeedbackAuth {
+    pub agent_id: u64,
+    pub client_address: Pubkey,
+    pub index_limit: u64,
+    pub expiry: i64,
+    pub chain_id: String,
+    pub identity_registry: Pubkey,
+    pub signer_address: Pubkey,
+    pub signature: [u8; 64],
+}
+
+/// `give_feedback` accounts `respond_and_attest` expects in `remaining_accounts`,
+/// after the Reputation Registry program itself:
+/// config, agent_mint, agent_account, client_index, feedback_account,
+/// agent_reputation, tag1_reputation, tag2_reputation, score_histogram,
+/// client_block, treasury, identity_registry_program
+/// (the validator signs as both client and payer)
+pub const ATTEST_FEEDBACK_ACCOUNTS: usize = 12;
+
+/// Instruction data of a Reputation Registry `give_feedback` attesting a
+/// validation: `tag` as tag1, no tag2, the response report as the feedback file
+pub fn give_feedback_data(
+    agent_id: u64,
+    score: u8,
+    tag: [u8; 32],
+    file_uri: &str,
+    file_hash: [u8; 32],
+    feedback_index: u64,
+    feedback_auth: &FeedbackAuth,
+) -> Result<Vec<u8>> {
+    let mut data = GIVE_FEEDBACK_DISCRIMINATOR.to_vec();
+    agent_id.serialize(&mut data)?;
+    score.serialize(&mut data)?;
+    tag.serialize(&mut data)?;
+    [0u8; 32].serialize(&mut data)?;
+    file_uri.serialize(&mut data)?;
+    file_hash.serialize(&mut data)?;
+    feedback_index.serialize(&mut data)?;
+    feedback_auth.serialize(&mut data)?;
+    0u64.serialize(&mut data)?; // file_size: unknown
+    Ok(data)
+}
+
+/// Domain prefix of validation state commitments
+pub const STATE_COMMITMENT_DOMAIN: &[u8] = b"erc8004:validation:state:v1";
+
+/// Commit to an agent's validation request accounts as a single SHA-256 hash
+///
+/// See `erc8004_common::state_commitment` for the canonical form, with
+/// `STATE_COMMITMENT_DOMAIN` as the domain.
+pub fn state_commitment(agent_id: u64, accounts: Vec<(Pubkey, Vec<u8>)>) -> [u8; 32] {
+    erc8004_common::state_commitment(STATE_COMMITMENT_DOMAIN, agent_id, accounts)
+}
+
+/// Pending validation summary returned by `get_pending_validations`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct PendingValidation {
+    /// Validator expected to respond
+    pub validator_address: Pubkey,
+
+    /// Request nonce
+    pub nonce: u32,
+
+    /// Timestamp of request creation
+    pub created_at: i64,
+}
+
+impl PendingValidation {
+    /// Canonical enumeration order: oldest first, ties broken by
+    /// (validator_address, nonce)
+    ///
+    /// Requests created in the same slot share `created_at`; the tiebreak keys
+    /// are the request PDA seeds, so no two requests of an agent compare equal
+    /// and the order never depends on how accounts were passed in.
+    ///
+    /// `get_pending_validations` is the only list view ordered by time. The
+    /// others need no tiebreak: `get_all_metadata` returns storage order,
+    /// `get_reputation_batch` the order of the accounts asked for, the
+    /// leaderboard ranks with agent_id as its last tiebreak, and the state
+    /// commitments sort by account key.
+    pub fn canonical_cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.created_at
+            .cmp(&other.created_at)
+            .then_with(|| self.validator_address.cmp(&other.validator_address))
+            .then_with(|| self.nonce.cmp(&other.nonce))
     }
 }
 
@@ -81,18 +712,504 @@ impl ValidationRequest {
 mod tests {
     use super::*;
 
+    fn test_config() -> ValidationConfig {
+        ValidationConfig {
+            authority: Pubkey::default(),
+            identity_registry: Pubkey::default(),
+            total_requests: 0,
+            total_responses: 0,
+            bump: 0,
+            require_response_tag: false,
+            dedup_by_hash: false,
+            max_responses_per_request: ValidationConfig::DEFAULT_MAX_RESPONSES_PER_REQUEST,
+            per_response_reward: 0,
+            require_registered_validators: false,
+            passing_threshold: ValidationConfig::DEFAULT_PASSING_THRESHOLD,
+            reputation_registry: None,
+            max_uri_length: ValidationConfig::DEFAULT_URI_LENGTH,
+        }
+    }
+
+    #[test]
+    fn test_agent_account_discriminator() {
+        use anchor_lang::solana_program::hash::hash;
+
+        assert_eq!(
+            hash(b"account:AgentAccount").to_bytes()[..8],
+            AGENT_ACCOUNT_DISCRIMINATOR
+        );
+
+        let mut data = AGENT_ACCOUNT_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&[0; 40]);
+        assert!(is_agent_account(&data));
+
+        // Same layout, other account type (e.g. RegistryConfig)
+        data[..8].copy_from_slice(&hash(b"account:RegistryConfig").to_bytes()[..8]);
+        assert!(!is_agent_account(&data));
+        assert!(!is_agent_account(&[]));
+    }
+
+    #[test]
+    fn test_get_owner_discriminator() {
+        use anchor_lang::solana_program::hash::hash;
+
+        assert_eq!(
+            hash(b"global:get_owner").to_bytes()[..8],
+            GET_OWNER_DISCRIMINATOR
+        );
+    }
+
+    #[test]
+    fn test_agent_owner_from_data() {
+        let owner = Pubkey::new_unique();
+        let mut data = AGENT_ACCOUNT_DISCRIMINATOR.to_vec();
+        data.extend_from_slice(&7u64.to_le_bytes());
+        data.extend_from_slice(owner.as_ref());
+
+        assert_eq!(agent_owner_from_data(&data, 7).unwrap(), owner);
+        assert!(agent_owner_from_data(&data, 8).is_err());
+        assert!(agent_owner_from_data(&data[..40], 7).is_err());
+    }
+
     #[test]
     fn test_validation_config_size() {
-        assert_eq!(ValidationConfig::SIZE, 81);
+        assert_eq!(ValidationConfig::SIZE, 130);
+    }
+
+    #[test]
+    fn test_zero_tag_accepted_when_not_required() {
+        let config = test_config();
+        assert!(config.accepts_response_tag(&[0; 32]));
+    }
+
+    #[test]
+    fn test_zero_tag_rejected_when_required() {
+        let mut config = test_config();
+        config.require_response_tag = true;
+
+        assert!(!config.accepts_response_tag(&[0; 32]));
+
+        let mut tag = [0u8; 32];
+        tag[..11].copy_from_slice(b"oasf-v0.8.0");
+        assert!(config.accepts_response_tag(&tag));
     }
 
     #[test]
     fn test_validation_request_size() {
-        assert_eq!(ValidationRequest::SIZE, 126);
+        assert_eq!(ValidationRequest::SIZE, 177);
+    }
+
+    #[test]
+    fn test_pending_view_fits_return_data() {
+        let pending = vec![
+            PendingValidation {
+                validator_address: Pubkey::new_unique(),
+                nonce: u32::MAX,
+                created_at: i64::MAX,
+            };
+            ValidationRequest::MAX_VIEW_BATCH
+        ];
+        let encoded = pending.try_to_vec().unwrap();
+        assert!(encoded.len() <= 1024);
+    }
+
+    #[test]
+    fn test_validation_response_entry_size() {
+        assert_eq!(ValidationResponseEntry::SIZE, 120);
+    }
+
+    #[test]
+    fn test_agent_validation_rollup() {
+        assert_eq!(AgentValidationMetadata::SIZE, 49);
+
+        let mut meta = AgentValidationMetadata {
+            agent_id: 0,
+            total_requests: 0,
+            total_responses: 0,
+            sum_responses: 0,
+            passing_count: 0,
+            last_updated: 0,
+            bump: 0,
+        };
+        assert_eq!(meta.average_response(), 0);
+
+        meta.record_request(7, 254, 10).unwrap();
+        meta.record_request(7, 254, 11).unwrap();
+        assert_eq!(meta.agent_id, 7);
+        assert_eq!(meta.bump, 254);
+        assert_eq!(meta.total_requests, 2);
+
+        let config = test_config();
+        meta.record_response(&config, 7, 254, None, 90, 12).unwrap();
+        meta.record_response(&config, 7, 254, None, 30, 13).unwrap();
+        assert_eq!(meta.total_responses, 2);
+        assert_eq!(meta.sum_responses, 120);
+        assert_eq!(meta.passing_count, 1);
+        assert_eq!(meta.average_response(), 60);
+
+        // An update replaces the earlier response instead of adding one
+        meta.record_response(&config, 7, 254, Some(30), 50, 14).unwrap();
+        assert_eq!(meta.total_responses, 2);
+        assert_eq!(meta.sum_responses, 140);
+        assert_eq!(meta.passing_count, 2);
+        assert_eq!(meta.average_response(), 70);
+        assert_eq!(meta.last_updated, 14);
+
+        meta.record_response(&config, 7, 254, Some(90), 49, 15).unwrap();
+        assert_eq!(meta.passing_count, 1);
+        assert_eq!(meta.average_response(), 50); // 99 / 2 rounds up
+    }
+
+    #[test]
+    fn test_give_feedback_data_layout() {
+        use anchor_lang::solana_program::hash::hash;
+
+        assert_eq!(
+            hash(b"global:give_feedback").to_bytes()[..8],
+            GIVE_FEEDBACK_DISCRIMINATOR
+        );
+
+        let auth = FeedbackAuth {
+            agent_id: 7,
+            client_address: Pubkey::new_unique(),
+            index_limit: 1,
+            expiry: 100,
+            chain_id: "solana-localnet".to_string(),
+            identity_registry: Pubkey::new_unique(),
+            signer_address: Pubkey::new_unique(),
+            signature: [9; 64],
+        };
+        let data = give_feedback_data(7, 90, [1; 32], "ipfs://report", [2; 32], 3, &auth).unwrap();
+
+        assert_eq!(data[..8], GIVE_FEEDBACK_DISCRIMINATOR);
+        assert_eq!(data[8..16], 7u64.to_le_bytes());
+        assert_eq!(data[16], 90);
+        assert_eq!(data[17..49], [1; 32]);
+        assert_eq!(data[49..81], [0; 32]);
+        // 4-byte length prefix + "ipfs://report" (13 bytes), hash, feedback index
+        let auth_start = 81 + 4 + 13 + 32 + 8;
+        assert_eq!(data[auth_start - 8..auth_start], 3u64.to_le_bytes());
+        assert_eq!(
+            FeedbackAuth::try_from_slice(&data[auth_start..data.len() - 8]).unwrap(),
+            auth
+        );
+        assert_eq!(data[data.len() - 8..], 0u64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_strict_passing_threshold() {
+        assert!(ValidationConfig::is_valid_passing_threshold(0));
+        assert!(ValidationConfig::is_valid_passing_threshold(100));
+        assert!(!ValidationConfig::is_valid_passing_threshold(101));
+
+        let config = ValidationConfig {
+            passing_threshold: 80,
+            ..test_config()
+        };
+        assert!(!config.is_passing(70));
+        assert!(config.is_passing(80));
+
+        let mut meta = AgentValidationMetadata {
+            agent_id: 0,
+            total_requests: 0,
+            total_responses: 0,
+            sum_responses: 0,
+            passing_count: 0,
+            last_updated: 0,
+            bump: 0,
+        };
+        meta.record_response(&config, 7, 254, None, 70, 1).unwrap();
+        assert_eq!(meta.passing_count, 0);
+
+        // Raised past the threshold by an update
+        meta.record_response(&config, 7, 254, Some(70), 85, 2).unwrap();
+        assert_eq!(meta.passing_count, 1);
+    }
+
+    #[test]
+    fn test_validator_account_size() {
+        assert_eq!(ValidatorAccount::SIZE, 245);
+    }
+
+    #[test]
+    fn test_request_hash_index() {
+        assert_eq!(RequestHashIndex::SIZE, 73);
+
+        let mut index = RequestHashIndex {
+            agent_id: 1,
+            request_hash: [1; 32],
+            validation_request: Pubkey::default(),
+            bump: 0,
+        };
+        assert!(!index.is_claimed());
+
+        index.validation_request = Pubkey::new_unique();
+        assert!(index.is_claimed());
+    }
+
+    #[test]
+    fn test_validator_nonce_counter() {
+        assert_eq!(ValidatorNonceCounter::SIZE, 45);
+
+        let mut counter = ValidatorNonceCounter {
+            agent_id: 1,
+            validator_address: Pubkey::new_unique(),
+            next_nonce: 0,
+            bump: 0,
+        };
+        assert_eq!(counter.take_nonce().unwrap(), 0);
+        assert_eq!(counter.take_nonce().unwrap(), 1);
+        assert_eq!(counter.next_nonce, 2);
+
+        counter.next_nonce = u32::MAX;
+        assert!(counter.take_nonce().is_err());
+    }
+
+    #[test]
+    fn test_validation_note_sizes() {
+        assert_eq!(ValidationNoteIndex::SIZE, 49);
+        assert_eq!(ValidationNote::SIZE, 325);
+
+        let mut index = ValidationNoteIndex {
+            agent_id: 1,
+            validator_address: Pubkey::new_unique(),
+            nonce: 0,
+            next_index: 0,
+            bump: 0,
+        };
+        assert_eq!(index.take_index().unwrap(), 0);
+        assert_eq!(index.take_index().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_response_limit() {
+        let config = test_config();
+        let mut request = ValidationRequest {
+            agent_id: 1,
+            validator_address: Pubkey::new_unique(),
+            nonce: 0,
+            request_hash: [0; 32],
+            response_hash: [0; 32],
+            response: 0,
+            created_at: 0,
+            responded_at: 0,
+            supersedes_responded_at: 0,
+            response_count: 0,
+            deadline: 0,
+            requester: Pubkey::new_unique(),
+            status: ValidationStatus::Pending as u8,
+            bump: 0,
+        };
+
+        assert!(request.can_record_response(config.max_responses_per_request));
+
+        request.response_count = config.max_responses_per_request - 1;
+        assert!(request.can_record_response(config.max_responses_per_request));
+
+        request.response_count = config.max_responses_per_request;
+        assert!(!request.can_record_response(config.max_responses_per_request));
+    }
+
+    #[test]
+    fn test_deadline_expiry() {
+        let mut request = ValidationRequest {
+            agent_id: 1,
+            validator_address: Pubkey::new_unique(),
+            nonce: 0,
+            request_hash: [0; 32],
+            response_hash: [0; 32],
+            response: 0,
+            created_at: 0,
+            responded_at: 0,
+            supersedes_responded_at: 0,
+            response_count: 0,
+            deadline: 0,
+            requester: Pubkey::new_unique(),
+            status: ValidationStatus::Pending as u8,
+            bump: 0,
+        };
+
+        // 0 = no expiry
+        assert!(!request.is_expired(i64::MAX));
+
+        // Still answerable at the deadline itself
+        request.deadline = 1_000;
+        assert!(!request.is_expired(999));
+        assert!(!request.is_expired(1_000));
+        assert!(request.is_expired(1_001));
+
+        assert_eq!(request.effective_status(1_000), ValidationStatus::Pending);
+        assert_eq!(request.effective_status(1_001), ValidationStatus::Expired);
+
+        // Responded requests don't expire
+        request.status = ValidationStatus::Responded as u8;
+        assert_eq!(request.effective_status(1_001), ValidationStatus::Responded);
+    }
+
+    #[test]
+    fn test_mark_expired() {
+        let mut request = ValidationRequest {
+            agent_id: 1,
+            validator_address: Pubkey::new_unique(),
+            nonce: 0,
+            request_hash: [0; 32],
+            response_hash: [0; 32],
+            response: 0,
+            created_at: 0,
+            responded_at: 0,
+            supersedes_responded_at: 0,
+            response_count: 0,
+            deadline: 1_000,
+            requester: Pubkey::new_unique(),
+            status: ValidationStatus::Pending as u8,
+            bump: 0,
+        };
+
+        // Not yet past the deadline: skipped
+        assert!(!request.mark_expired(1_000));
+        assert!(request.is_pending());
+
+        assert!(request.mark_expired(1_001));
+        assert_eq!(request.status, ValidationStatus::Expired as u8);
+        assert_eq!(request.effective_status(1_001), ValidationStatus::Expired);
+        // Marked once only
+        assert!(!request.mark_expired(1_002));
+
+        let grace_end = 1_000 + ValidationRequest::EXPIRY_GRACE_PERIOD;
+        assert!(!request.is_past_expiry_grace(grace_end));
+        assert!(request.is_past_expiry_grace(grace_end + 1));
+
+        // Answered requests never expire
+        request.status = ValidationStatus::Responded as u8;
+        assert!(!request.mark_expired(i64::MAX));
+        assert!(!request.is_past_expiry_grace(i64::MAX));
+    }
+
+    #[test]
+    fn test_score_zero_response_is_not_pending() {
+        let mut request = ValidationRequest {
+            agent_id: 1,
+            validator_address: Pubkey::new_unique(),
+            nonce: 0,
+            request_hash: [0; 32],
+            response_hash: [0; 32],
+            response: 0,
+            created_at: 0,
+            responded_at: 0,
+            supersedes_responded_at: 0,
+            response_count: 0,
+            deadline: 0,
+            requester: Pubkey::new_unique(),
+            status: ValidationStatus::Pending as u8,
+            bump: 0,
+        };
+        assert!(request.is_pending());
+        assert!(!request.has_response());
+
+        // A genuine failing score of 0 leaves `response` unchanged
+        request.status = ValidationStatus::Responded as u8;
+        assert_eq!(request.response, 0);
+        assert!(!request.is_pending());
+        assert!(request.has_response());
+
+        // Expired is neither pending nor responded
+        request.status = ValidationStatus::Expired as u8;
+        assert!(!request.is_pending());
+        assert!(!request.has_response());
     }
 
     #[test]
     fn test_max_uri_length() {
-        assert_eq!(ValidationRequest::MAX_URI_LENGTH, 200);
+        assert_eq!(ValidationConfig::DEFAULT_URI_LENGTH, 200);
+        assert_eq!(test_config().max_uri_length, 200);
+
+        assert!(!ValidationConfig::is_valid_max_uri_length(0));
+        assert!(ValidationConfig::is_valid_max_uri_length(300));
+        assert!(ValidationConfig::is_valid_max_uri_length(512));
+        assert!(!ValidationConfig::is_valid_max_uri_length(513));
+
+        let config = ValidationConfig {
+            max_uri_length: 300,
+            ..test_config()
+        };
+        assert!(config.accepts_uri_length(250));
+        assert!(!config.accepts_uri_length(301));
+    }
+
+    fn test_pool(pool_balance: u64) -> RewardPool {
+        RewardPool {
+            pool_balance,
+            total_funded: pool_balance,
+            total_paid: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn test_reward_pool_size() {
+        assert_eq!(RewardPool::SIZE, 25);
+    }
+
+    #[test]
+    fn test_reward_paid_from_funded_pool() {
+        assert_eq!(test_pool(1_000).reward_due(400), Some(400));
+        assert_eq!(test_pool(400).reward_due(400), Some(400));
+    }
+
+    #[test]
+    fn test_no_reward_from_depleted_pool_or_when_disabled() {
+        assert_eq!(test_pool(399).reward_due(400), None);
+        assert_eq!(test_pool(0).reward_due(400), None);
+        assert_eq!(test_pool(1_000).reward_due(0), None);
+    }
+
+    #[test]
+    fn test_pending_order_is_deterministic_for_same_slot_requests() {
+        let validator_a = Pubkey::new_from_array([1; 32]);
+        let validator_b = Pubkey::new_from_array([2; 32]);
+        let pending = |validator_address, nonce, created_at| PendingValidation {
+            validator_address,
+            nonce,
+            created_at,
+        };
+
+        // Three requests in the same slot plus an older one, passed in two orders
+        let mut first = vec![
+            pending(validator_b, 0, 100),
+            pending(validator_a, 1, 100),
+            pending(validator_a, 0, 100),
+            pending(validator_b, 5, 90),
+        ];
+        let mut second: Vec<_> = first.iter().rev().cloned().collect();
+
+        first.sort_by(PendingValidation::canonical_cmp);
+        second.sort_by(PendingValidation::canonical_cmp);
+
+        assert_eq!(first, second);
+        assert_eq!(
+            first,
+            vec![
+                pending(validator_b, 5, 90),
+                pending(validator_a, 0, 100),
+                pending(validator_a, 1, 100),
+                pending(validator_b, 0, 100),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_state_commitment_uses_registry_domain() {
+        let accounts = vec![(Pubkey::new_from_array([9; 32]), vec![42u8; 10])];
+
+        let commitment = state_commitment(5, accounts.clone());
+        assert_eq!(
+            commitment,
+            erc8004_common::state_commitment(STATE_COMMITMENT_DOMAIN, 5, accounts.clone())
+        );
+        // The Reputation Registry commits to the same accounts differently
+        assert_ne!(
+            commitment,
+            erc8004_common::state_commitment(b"erc8004:reputation:state:v1", 5, accounts)
+        );
     }
 }
@@ -0,0 +1,140 @@
+//! Read-only view surface (`views` feature)
+//!
+//! Stable, versioned return-data encodings for CPI callers and simulating
+//! clients. Each view is Borsh-encoded via `set_return_data` by Anchor and
+//! starts with `version`; fields are only ever appended, so a decoder for
+//! version N can read any later version's prefix.
+
+use anchor_lang::prelude::*;
+
+use crate::state::{ValidationConfig, ValidationRequest};
+
+/// Encoding version of every view in this module
+pub const VIEWS_VERSION: u8 = 1;
+
+/// `get_validation` return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct ValidationView {
+    pub version: u8,
+    pub agent_id: u64,
+    pub validator_address: Pubkey,
+    pub nonce: u32,
+    pub request_hash: [u8; 32],
+    pub response_hash: [u8; 32],
+    pub response: u8,
+    pub is_pending: bool,
+    pub created_at: i64,
+    pub responded_at: i64,
+    pub supersedes_responded_at: i64,
+    pub response_count: u16,
+}
+
+impl From<&ValidationRequest> for ValidationView {
+    fn from(request: &ValidationRequest) -> Self {
+        Self {
+            version: VIEWS_VERSION,
+            agent_id: request.agent_id,
+            validator_address: request.validator_address,
+            nonce: request.nonce,
+            request_hash: request.request_hash,
+            response_hash: request.response_hash,
+            response: request.response,
+            is_pending: request.is_pending(),
+            created_at: request.created_at,
+            responded_at: request.responded_at,
+            supersedes_responded_at: request.supersedes_responded_at,
+            response_count: request.response_count,
+        }
+    }
+}
+
+/// `get_config` return data
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct ValidationConfigView {
+    pub version: u8,
+    pub authority: Pubkey,
+    pub identity_registry: Pubkey,
+    pub total_requests: u64,
+    pub total_responses: u64,
+    pub require_response_tag: bool,
+    pub dedup_by_hash: bool,
+    pub max_responses_per_request: u16,
+    pub per_response_reward: u64,
+    pub passing_threshold: u8,
+    pub reputation_registry: Option<Pubkey>,
+    pub max_uri_length: u16,
+}
+
+impl From<&ValidationConfig> for ValidationConfigView {
+    fn from(config: &ValidationConfig) -> Self {
+        Self {
+            version: VIEWS_VERSION,
+            authority: config.authority,
+            identity_registry: config.identity_registry,
+            total_requests: config.total_requests,
+            total_responses: config.total_responses,
+            require_response_tag: config.require_response_tag,
+            dedup_by_hash: config.dedup_by_hash,
+            max_responses_per_request: config.max_responses_per_request,
+            per_response_reward: config.per_response_reward,
+            passing_threshold: config.passing_threshold,
+            reputation_registry: config.reputation_registry,
+            max_uri_length: config.max_uri_length,
+        }
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(agent_id: u64, validator_address: Pubkey, nonce: u32)]
+pub struct GetValidation<'info> {
+    #[account(
+        seeds = [
+            b"validation",
+            agent_id.to_le_bytes().as_ref(),
+            validator_address.as_ref(),
+            nonce.to_le_bytes().as_ref()
+        ],
+        bump = validation_request.bump
+    )]
+    pub validation_request: Account<'info, ValidationRequest>,
+}
+
+#[derive(Accounts)]
+pub struct GetConfig<'info> {
+    #[account(seeds = [b"config"], bump = config.bump)]
+    pub config: Account<'info, ValidationConfig>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_views_are_versioned_and_fixed_size() {
+        let request = ValidationRequest {
+            agent_id: 1,
+            validator_address: Pubkey::new_unique(),
+            nonce: 2,
+            request_hash: [1; 32],
+            response_hash: [0; 32],
+            response: 0,
+            created_at: 10,
+            responded_at: 0,
+            supersedes_responded_at: 0,
+            response_count: 0,
+            deadline: 0,
+            requester: Pubkey::new_unique(),
+            status: 0,
+            bump: 255,
+        };
+
+        let view = ValidationView::from(&request);
+        assert!(view.is_pending);
+
+        let encoded = view.try_to_vec().unwrap();
+        assert_eq!(encoded[0], VIEWS_VERSION);
+        // 1 + 8 + 32 + 4 + 32 + 32 + 1 + 1 + 8 + 8 + 8 + 2
+        assert_eq!(encoded.len(), 137);
+        assert_eq!(ValidationView::try_from_slice(&encoded).unwrap(), view);
+    }
+}
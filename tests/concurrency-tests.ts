@@ -57,6 +57,18 @@ describe("ERC-8004 Registries - Concurrency Tests (LOT 3)", () => {
     );
   }
 
+  // Helper: Get the zero-tag reputation PDA (all feedback here is untagged)
+  function getUntaggedReputationPda(agentId: number): PublicKey {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("tag_reputation"),
+        new anchor.BN(agentId).toArrayLike(Buffer, "le", 8),
+        Buffer.alloc(32),
+      ],
+      reputationProgram.programId
+    )[0];
+  }
+
   // Helper: Create feedbackAuth
   function createFeedbackAuth(clientAddress: PublicKey, indexLimit: number = 50) {
     return {
@@ -480,6 +492,7 @@ describe("ERC-8004 Registries - Concurrency Tests (LOT 3)", () => {
               client: client.publicKey,
               feedbackAccount: feedbackPda,
               agentReputation: reputationPda,
+              tag1Reputation: getUntaggedReputationPda(agentId),
             })
             .signers([client])
             .rpc();
@@ -584,6 +597,7 @@ describe("ERC-8004 Registries - Concurrency Tests (LOT 3)", () => {
           client: client.publicKey,
           feedbackAccount: feedbackToRevoke,
           agentReputation: reputationPda,
+          tag1Reputation: getUntaggedReputationPda(agentId),
         })
         .signers([client])
         .rpc();
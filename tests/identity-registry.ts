@@ -8,17 +8,23 @@ import {
   SYSVAR_RENT_PUBKEY,
   ComputeBudgetProgram,
   Transaction,
-  TransactionInstruction
+  TransactionInstruction,
+  Ed25519Program,
 } from "@solana/web3.js";
 import { assert } from "chai";
 import {
   getAssociatedTokenAddressSync,
   TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
   ASSOCIATED_TOKEN_PROGRAM_ID,
   getAccount,
   transfer,
+  createMint,
+  mintTo,
+  getOrCreateAssociatedTokenAccount,
 } from "@solana/spl-token";
 import { IdentityRegistry } from "../target/types/identity_registry";
+import { MockGovernance } from "../target/types/mock_governance";
 
 // Metaplex Token Metadata Program ID
 const TOKEN_METADATA_PROGRAM_ID = new PublicKey("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
@@ -69,6 +75,22 @@ describe("Identity Registry (ERC-8004 Spec Compliant)", () => {
     );
   }
 
+  function getOwnerAgentsPda(owner: PublicKey): PublicKey {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("owner_agents"), owner.toBuffer()],
+      program.programId
+    )[0];
+  }
+
+  // Helper to create a mint violating the agent NFT invariants (supply = 1, decimals = 0)
+  async function createMalformedMint(decimals: number, supply: number): Promise<PublicKey> {
+    const payer = (provider.wallet as anchor.Wallet).payer;
+    const mint = await createMint(provider.connection, payer, payer.publicKey, null, decimals);
+    const ata = await getOrCreateAssociatedTokenAccount(provider.connection, payer, mint, payer.publicKey);
+    await mintTo(provider.connection, payer, mint, ata.address, payer, supply);
+    return mint;
+  }
+
   // Helper to send transaction with compute budget
   async function sendWithComputeBudget(
     ix: TransactionInstruction,
@@ -125,6 +147,7 @@ describe("Identity Registry (ERC-8004 Spec Compliant)", () => {
       assert.equal(config.nextAgentId.toNumber(), 0, "Agent ID should start at 0");
       assert.equal(config.totalAgents.toNumber(), 0);
       assert.equal(config.collectionMint.toBase58(), collectionMint.publicKey.toBase58());
+      assert.equal(config.maxValueLength, 256, "Default max value length");
       assert.equal(config.bump, configBump);
 
       // Verify collection token account was created and holds 1 NFT
@@ -240,6 +263,48 @@ describe("Identity Registry (ERC-8004 Spec Compliant)", () => {
       assert.equal(tokenAcct.mint.toBase58(), agentMint.publicKey.toBase58());
     });
 
+    it("Creates an agent_id index resolving to the agent mint", async () => {
+      const configBefore = await program.account.registryConfig.fetch(configPda);
+      const agentId = configBefore.nextAgentId;
+
+      const ix = await program.methods
+        .register("https://example.com/indexed.json")
+        .accounts({
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          agentMetadata,
+          agentMasterEdition,
+          agentTokenAccount,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .instruction();
+
+      await sendWithComputeBudget(ix, [agentMint]);
+
+      // id -> mint -> agent account, knowing only the numeric ID
+      const [indexPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("agent_id"), agentId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      );
+      const index = await program.account.agentIdIndex.fetch(indexPda);
+      assert.equal(index.agentId.toNumber(), agentId.toNumber());
+      assert.equal(index.agentMint.toBase58(), agentMint.publicKey.toBase58());
+
+      const agent = await program.account.agentAccount.fetch(getAgentPda(index.agentMint)[0]);
+      assert.equal(agent.agentId.toNumber(), agentId.toNumber());
+    });
+
     it("Registers agent with empty tokenURI (ERC-8004 spec)", async () => {
       await program.methods
         .register("")
@@ -355,8 +420,141 @@ describe("Identity Registry (ERC-8004 Spec Compliant)", () => {
       }
     });
 
+    it("Accepts a 250-byte tokenURI under a raised max_uri_length", async () => {
+      const longUri = "https://gateway.example.com/agent.json?sig=" + "x".repeat(206);
+      assert.equal(longUri.length, 250);
+
+      await program.methods
+        .setMaxUriLength(300)
+        .accounts({ config: configPda, authority: provider.wallet.publicKey })
+        .rpc();
+
+      try {
+        await program.methods
+          .register(longUri)
+          .accounts({
+            config: configPda,
+            authority: provider.wallet.publicKey,
+            agentAccount: agentPda,
+            agentMint: agentMint.publicKey,
+            agentMetadata,
+            agentMasterEdition,
+            agentTokenAccount,
+            collectionMint: collectionMint.publicKey,
+            collectionMetadata,
+            collectionMasterEdition,
+            owner: provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            rent: SYSVAR_RENT_PUBKEY,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .signers([agentMint])
+          .rpc();
+
+        // The full URI lives on the AgentAccount (Metaplex caps NFT URIs at 200 bytes)
+        const agent = await program.account.agentAccount.fetch(agentPda);
+        assert.equal(agent.tokenUri, longUri);
+
+        // Only the 50 bytes over the 200-byte reserve are allocated
+        const AGENT_ACCOUNT_BASE_SPACE = 8 + 3457;
+        const info = await provider.connection.getAccountInfo(agentPda);
+        assert.equal(info!.data.length, AGENT_ACCOUNT_BASE_SPACE + 50);
+
+        const config = await program.account.registryConfig.fetch(configPda);
+        assert.equal(config.maxUriLength, 300);
+
+        let rejected = false;
+        try {
+          await program.methods
+            .setMaxUriLength(513)
+            .accounts({ config: configPda, authority: provider.wallet.publicKey })
+            .rpc();
+        } catch (error) {
+          rejected = true;
+          assert.include(error.message, "InvalidMaxUriLength");
+        }
+        assert.isTrue(rejected, "Limit above the account layout must be rejected");
+      } finally {
+        // Restore the default for the remaining tests
+        await program.methods
+          .setMaxUriLength(200)
+          .accounts({ config: configPda, authority: provider.wallet.publicKey })
+          .rpc();
+      }
+    });
+
+    it("Fails with a collection mint other than the registry's", async () => {
+      const payer = (provider.wallet as anchor.Wallet).payer;
+      const wrongCollectionMint = await createMint(provider.connection, payer, payer.publicKey, null, 0);
+
+      try {
+        await program.methods
+          .register("https://example.com/agent.json")
+          .accounts({
+            config: configPda,
+            authority: provider.wallet.publicKey,
+            agentAccount: agentPda,
+            agentMint: agentMint.publicKey,
+            agentMetadata,
+            agentMasterEdition,
+            agentTokenAccount,
+            collectionMint: wrongCollectionMint,
+            collectionMetadata,
+            collectionMasterEdition,
+            owner: provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            rent: SYSVAR_RENT_PUBKEY,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .signers([agentMint])
+          .rpc();
+
+        assert.fail("Should have failed with InvalidCollectionMint error");
+      } catch (error) {
+        assert.include(error.message, "InvalidCollectionMint");
+      }
+    });
+
+    it("Rejects a tokenURI without a supported scheme", async () => {
+      try {
+        await program.methods
+          .register("javascript:alert(1)")
+          .accounts({
+            config: configPda,
+            authority: provider.wallet.publicKey,
+            agentAccount: agentPda,
+            agentMint: agentMint.publicKey,
+            agentMetadata,
+            agentMasterEdition,
+            agentTokenAccount,
+            collectionMint: collectionMint.publicKey,
+            collectionMetadata,
+            collectionMasterEdition,
+            owner: provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            rent: SYSVAR_RENT_PUBKEY,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .signers([agentMint])
+          .rpc();
+
+        assert.fail("Should have failed with InvalidUriScheme error");
+      } catch (error) {
+        assert.include(error.message, "InvalidUriScheme");
+      }
+    });
+
     it("Accepts tokenURI with exactly 200 bytes", async () => {
-      const exactUri = "x".repeat(200);
+      const exactUri = "https://" + "x".repeat(192); // 8 + 192 = 200 bytes
 
       const ix = await program.methods
         .register(exactUri)
@@ -386,6 +584,62 @@ describe("Identity Registry (ERC-8004 Spec Compliant)", () => {
       const agent = await program.account.agentAccount.fetch(agentPda);
       assert.equal(agent.tokenUri.length, 200);
     });
+
+    const registerNamedIx = (name: string) =>
+      program.methods
+        .registerNamed("https://example.com/named.json", name)
+        .accounts({
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          agentMetadata,
+          agentMasterEdition,
+          agentTokenAccount,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .instruction();
+
+    // Metaplex Metadata: key (1) + update_authority (32) + mint (32), then the
+    // u32-prefixed name
+    async function fetchNftName(): Promise<string> {
+      const info = await provider.connection.getAccountInfo(agentMetadata);
+      const len = info.data.readUInt32LE(65);
+      return info.data.subarray(69, 69 + len).toString().replace(/\0+$/, "");
+    }
+
+    it("Registers with a custom NFT name", async () => {
+      await sendWithComputeBudget(await registerNamedIx("Trading Bot Alpha"), [agentMint]);
+
+      assert.equal(await fetchNftName(), "Trading Bot Alpha");
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.nftName, "Trading Bot Alpha");
+    });
+
+    it("Falls back to Agent #id for an empty name", async () => {
+      const config = await program.account.registryConfig.fetch(configPda);
+      await sendWithComputeBudget(await registerNamedIx(""), [agentMint]);
+
+      assert.equal(await fetchNftName(), `Agent #${config.nextAgentId.toNumber()}`);
+    });
+
+    it("Rejects a name longer than 32 bytes", async () => {
+      try {
+        await sendWithComputeBudget(await registerNamedIx("n".repeat(33)), [agentMint]);
+        assert.fail("Should have failed with NameTooLong error");
+      } catch (error) {
+        assert.include(error.message, "NameTooLong");
+      }
+    });
   });
 
   describe("Register Empty (ERC-8004: register())", () => {
@@ -559,6 +813,46 @@ describe("Identity Registry (ERC-8004 Spec Compliant)", () => {
       }
     });
 
+    it("Fails with duplicate metadata keys", async () => {
+      const agentMint = Keypair.generate();
+      const [agentPda] = getAgentPda(agentMint.publicKey);
+
+      const metadata = [
+        { key: "x", value: Buffer.from([1]) },
+        { key: "x", value: Buffer.from([2]) },
+      ];
+
+      try {
+        await program.methods
+          .registerWithMetadata("https://example.com", metadata)
+          .accounts({
+            config: configPda,
+            authority: provider.wallet.publicKey,
+            agentAccount: agentPda,
+            agentMint: agentMint.publicKey,
+            agentMetadata: getMetadataPda(agentMint.publicKey),
+            agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
+            agentTokenAccount: getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey),
+            collectionMint: collectionMint.publicKey,
+            collectionMetadata,
+            collectionMasterEdition,
+            owner: provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            rent: SYSVAR_RENT_PUBKEY,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .signers([agentMint])
+          .rpc();
+
+        assert.fail("Should have failed with DuplicateMetadataKey error");
+      } catch (error) {
+        assert.include(error.message, "DuplicateMetadataKey");
+      }
+    });
+
     it("Accepts exactly 10 metadata entries", async () => {
       const agentMint = Keypair.generate();
       const agentMetadata = getMetadataPda(agentMint.publicKey);
@@ -685,33 +979,37 @@ describe("Identity Registry (ERC-8004 Spec Compliant)", () => {
     });
   });
 
-  describe("Get Metadata (ERC-8004: getMetadata(agentId, key))", () => {
-    let agentMint: Keypair;
-    let agentPda: PublicKey;
+  describe("Collection Authority Delegate", () => {
+    const delegate = Keypair.generate();
 
-    before(async () => {
-      // Register an agent with metadata
-      agentMint = Keypair.generate();
-      const agentMetadata = getMetadataPda(agentMint.publicKey);
-      const agentMasterEdition = getMasterEditionPda(agentMint.publicKey);
-      const agentTokenAccount = getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey);
-      [agentPda] = getAgentPda(agentMint.publicKey);
+    function getCollectionAuthorityRecordPda(authority: PublicKey): PublicKey {
+      return PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("metadata"),
+          TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+          collectionMint.publicKey.toBuffer(),
+          Buffer.from("collection_authority"),
+          authority.toBuffer(),
+        ],
+        TOKEN_METADATA_PROGRAM_ID
+      )[0];
+    }
 
-      const metadata = [
-        { key: "name", value: Buffer.from("Test Agent") },
-        { key: "type", value: Buffer.from("assistant") },
-      ];
+    async function registerVerifiedBy(authority: Keypair): Promise<PublicKey> {
+      const agentMint = Keypair.generate();
+      const [agentPda] = getAgentPda(agentMint.publicKey);
 
       const ix = await program.methods
-        .registerWithMetadata("https://test.com", metadata)
+        .register("https://delegated.example")
         .accounts({
           config: configPda,
-          authority: provider.wallet.publicKey,
+          collectionAuthorityRecord: getCollectionAuthorityRecordPda(authority.publicKey),
+          authority: authority.publicKey,
           agentAccount: agentPda,
           agentMint: agentMint.publicKey,
-          agentMetadata,
-          agentMasterEdition,
-          agentTokenAccount,
+          agentMetadata: getMetadataPda(agentMint.publicKey),
+          agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
+          agentTokenAccount: getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey),
           collectionMint: collectionMint.publicKey,
           collectionMetadata,
           collectionMasterEdition,
@@ -725,54 +1023,102 @@ describe("Identity Registry (ERC-8004 Spec Compliant)", () => {
         })
         .instruction();
 
-      await sendWithComputeBudget(ix, [agentMint]);
-    });
+      await sendWithComputeBudget(ix, [agentMint, authority]);
+      return agentPda;
+    }
 
-    it("Returns metadata value for existing key", async () => {
-      const result = await program.methods
-        .getMetadata("name")
+    it("Approved delegate verifies a new agent into the collection", async () => {
+      await program.methods
+        .approveCollectionAuthority(delegate.publicKey)
         .accounts({
-          agentAccount: agentPda,
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          collectionAuthorityRecord: getCollectionAuthorityRecordPda(delegate.publicKey),
+          delegateAccount: delegate.publicKey,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
         })
-        .view();
+        .rpc();
 
-      assert.equal(Buffer.from(result).toString(), "Test Agent");
+      const agentPda = await registerVerifiedBy(delegate);
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.tokenUri, "https://delegated.example");
     });
 
-    it("Returns empty bytes for non-existent key", async () => {
-      const result = await program.methods
-        .getMetadata("nonexistent")
+    it("Revoked delegate can no longer verify agents", async () => {
+      await program.methods
+        .revokeCollectionAuthority(delegate.publicKey)
         .accounts({
-          agentAccount: agentPda,
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          collectionAuthorityRecord: getCollectionAuthorityRecordPda(delegate.publicKey),
+          delegateAccount: delegate.publicKey,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
         })
-        .view();
+        .rpc();
 
-      assert.equal(result.length, 0, "Should return empty array");
+      // Metaplex rejects the closed collection authority record
+      let rejected = false;
+      try {
+        await registerVerifiedBy(delegate);
+      } catch (error) {
+        rejected = true;
+      }
+      assert.isTrue(rejected, "Revoked delegate should not verify agents");
     });
-  });
 
-  describe("Set Metadata (ERC-8004: setMetadata(agentId, key, value))", () => {
-    let agentMint: Keypair;
-    let agentPda: PublicKey;
+    it("Fails when a non-authority approves a delegate", async () => {
+      const otherUser = Keypair.generate();
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(otherUser.publicKey, 1000000000)
+      );
 
-    beforeEach(async () => {
-      // Register a fresh agent for each test
-      agentMint = Keypair.generate();
-      const agentMetadata = getMetadataPda(agentMint.publicKey);
-      const agentMasterEdition = getMasterEditionPda(agentMint.publicKey);
-      const agentTokenAccount = getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey);
-      [agentPda] = getAgentPda(agentMint.publicKey);
+      try {
+        await program.methods
+          .approveCollectionAuthority(otherUser.publicKey)
+          .accounts({
+            config: configPda,
+            authority: otherUser.publicKey,
+            collectionAuthorityRecord: getCollectionAuthorityRecordPda(otherUser.publicKey),
+            delegateAccount: otherUser.publicKey,
+            collectionMint: collectionMint.publicKey,
+            collectionMetadata,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([otherUser])
+          .rpc();
 
-      const registerIx = await program.methods
-        .register("https://example.com")
+        assert.fail("Should have failed with Unauthorized error");
+      } catch (error) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+  });
+
+  describe("Pause Registry (Incident Switch)", () => {
+    const setPaused = (paused: boolean) =>
+      program.methods
+        .setPaused(paused)
+        .accounts({ config: configPda, authority: provider.wallet.publicKey })
+        .rpc();
+
+    const registerIx = (agentMint: Keypair) =>
+      program.methods
+        .register("https://example.com/paused.json")
         .accounts({
           config: configPda,
           authority: provider.wallet.publicKey,
-          agentAccount: agentPda,
+          agentAccount: getAgentPda(agentMint.publicKey)[0],
           agentMint: agentMint.publicKey,
-          agentMetadata,
-          agentMasterEdition,
-          agentTokenAccount,
+          agentMetadata: getMetadataPda(agentMint.publicKey),
+          agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
+          agentTokenAccount: getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey),
           collectionMint: collectionMint.publicKey,
           collectionMetadata,
           collectionMasterEdition,
@@ -786,151 +1132,251 @@ describe("Identity Registry (ERC-8004 Spec Compliant)", () => {
         })
         .instruction();
 
-      await sendWithComputeBudget(registerIx, [agentMint]);
-    });
-
-    it("Sets new metadata entry", async () => {
-      await program.methods
-        .setMetadata("name", Buffer.from("Alice"))
-        .accounts({
-          agentAccount: agentPda,
-          owner: provider.wallet.publicKey,
-        })
-        .rpc();
-
-      const agent = await program.account.agentAccount.fetch(agentPda);
-      assert.equal(agent.metadata.length, 1);
-      assert.equal(agent.metadata[0].key, "name");
-      assert.equal(Buffer.from(agent.metadata[0].value).toString(), "Alice");
-    });
-
-    it("Updates existing metadata entry", async () => {
-      // Set initial value
-      await program.methods
-        .setMetadata("status", Buffer.from("active"))
-        .accounts({
-          agentAccount: agentPda,
-          owner: provider.wallet.publicKey,
-        })
-        .rpc();
-
-      // Update same key
-      await program.methods
-        .setMetadata("status", Buffer.from("inactive"))
-        .accounts({
-          agentAccount: agentPda,
-          owner: provider.wallet.publicKey,
-        })
-        .rpc();
-
-      const agent = await program.account.agentAccount.fetch(agentPda);
-      assert.equal(agent.metadata.length, 1, "Should still have 1 entry");
-      assert.equal(Buffer.from(agent.metadata[0].value).toString(), "inactive");
-    });
+    it("Blocks register but allows set_metadata while paused", async () => {
+      // Existing agent, registered before the pause
+      const existingMint = Keypair.generate();
+      const [existingPda] = getAgentPda(existingMint.publicKey);
+      await sendWithComputeBudget(await registerIx(existingMint), [existingMint]);
 
-    it("Adds multiple metadata entries", async () => {
-      const entries = [
-        { key: "name", value: "Alice" },
-        { key: "type", value: "assistant" },
-        { key: "version", value: "1.0" },
-      ];
+      await setPaused(true);
 
-      for (const entry of entries) {
+      try {
+        const agentMint = Keypair.generate();
+        await sendWithComputeBudget(await registerIx(agentMint), [agentMint]);
+        assert.fail("Should have failed with RegistryPaused error");
+      } catch (error) {
+        assert.include(error.message, "RegistryPaused");
+      } finally {
+        // Existing agents stay mutable
         await program.methods
-          .setMetadata(entry.key, Buffer.from(entry.value))
+          .setMetadata("status", Buffer.from("incident"))
           .accounts({
-            agentAccount: agentPda,
+            agentAccount: existingPda,
             owner: provider.wallet.publicKey,
           })
           .rpc();
+
+        await setPaused(false);
       }
 
-      const agent = await program.account.agentAccount.fetch(agentPda);
-      assert.equal(agent.metadata.length, 3);
+      const agent = await program.account.agentAccount.fetch(existingPda);
+      assert.equal(Buffer.from(agent.metadata[0].value).toString(), "incident");
+
+      const config = await program.account.registryConfig.fetch(configPda);
+      assert.isFalse(config.paused);
     });
 
-    it("Enforces 10 metadata entry limit", async () => {
-      // Add 10 entries
-      for (let i = 0; i < 10; i++) {
+    it("Fails when called by non-authority", async () => {
+      const attacker = Keypair.generate();
+
+      try {
         await program.methods
-          .setMetadata(`key${i}`, Buffer.from(`value${i}`))
-          .accounts({
-            agentAccount: agentPda,
-            owner: provider.wallet.publicKey,
-          })
+          .setPaused(true)
+          .accounts({ config: configPda, authority: attacker.publicKey })
+          .signers([attacker])
           .rpc();
+        assert.fail("Should have failed with Unauthorized error");
+      } catch (error) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+  });
+
+  describe("Seller Fee (Agent NFT Royalties)", () => {
+    const setSellerFee = (bps: number) =>
+      program.methods
+        .setDefaultSellerFeeBps(bps)
+        .accounts({ config: configPda, authority: provider.wallet.publicKey })
+        .rpc();
+
+    // Metaplex Metadata: key (1) + update_authority (32) + mint (32), then
+    // name, symbol and uri as u32-prefixed strings, then seller_fee_basis_points (u16)
+    async function fetchSellerFee(agentMint: PublicKey): Promise<number> {
+      const info = await provider.connection.getAccountInfo(getMetadataPda(agentMint));
+      let offset = 1 + 32 + 32;
+      for (let i = 0; i < 3; i++) {
+        offset += 4 + info.data.readUInt32LE(offset);
       }
+      return info.data.readUInt16LE(offset);
+    }
 
-      // Try to add 11th entry
+    it("Mints agents with the configured royalty", async () => {
+      await setSellerFee(500);
+
+      const agentMint = Keypair.generate();
       try {
-        await program.methods
-          .setMetadata("key11", Buffer.from("value11"))
+        const registerIx = await program.methods
+          .register("https://example.com/royalty.json")
           .accounts({
-            agentAccount: agentPda,
+            config: configPda,
+            authority: provider.wallet.publicKey,
+            agentAccount: getAgentPda(agentMint.publicKey)[0],
+            agentMint: agentMint.publicKey,
+            agentMetadata: getMetadataPda(agentMint.publicKey),
+            agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
+            agentTokenAccount: getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey),
+            collectionMint: collectionMint.publicKey,
+            collectionMetadata,
+            collectionMasterEdition,
             owner: provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            rent: SYSVAR_RENT_PUBKEY,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
           })
-          .rpc();
+          .instruction();
+        await sendWithComputeBudget(registerIx, [agentMint]);
+      } finally {
+        // Restore the default for the remaining tests
+        await setSellerFee(0);
+      }
 
-        assert.fail("Should have failed with MetadataLimitReached error");
+      assert.equal(await fetchSellerFee(agentMint.publicKey), 500);
+
+      // URI updates keep the royalty the NFT was minted with
+      await program.methods
+        .setAgentUri("https://example.com/royalty-v2.json")
+        .accounts({
+          agentAccount: getAgentPda(agentMint.publicKey)[0],
+          agentMetadata: getMetadataPda(agentMint.publicKey),
+          agentMint: agentMint.publicKey,
+          owner: provider.wallet.publicKey,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .rpc();
+      assert.equal(await fetchSellerFee(agentMint.publicKey), 500);
+    });
+
+    it("Rejects a fee above 10000 bps", async () => {
+      try {
+        await setSellerFee(10_001);
+        assert.fail("Should have failed with InvalidSellerFee error");
       } catch (error) {
-        assert.include(error.message, "MetadataLimitReached");
+        assert.include(error.message, "InvalidSellerFee");
       }
     });
+  });
 
-    it("Fails with key > 32 bytes", async () => {
-      const longKey = "x".repeat(33);
+  describe("Collection Verification Toggle (Migration)", () => {
+    const setVerifyCollection = (verify: boolean) =>
+      program.methods
+        .setVerifyCollection(verify)
+        .accounts({ config: configPda, authority: provider.wallet.publicKey })
+        .rpc();
+
+    // Metaplex Metadata: key, update_authority, mint, name/symbol/uri, seller fee,
+    // creators, primary_sale_happened, is_mutable, edition_nonce, token_standard,
+    // then collection: Option<{ verified: bool, key: Pubkey }>
+    async function fetchCollection(agentMint: PublicKey): Promise<{ verified: boolean; key: PublicKey }> {
+      const data = (await provider.connection.getAccountInfo(getMetadataPda(agentMint))).data;
+      let offset = 1 + 32 + 32;
+      for (let i = 0; i < 3; i++) {
+        offset += 4 + data.readUInt32LE(offset);
+      }
+      offset += 2;
+      offset += data[offset] === 1 ? 1 + 4 + data.readUInt32LE(offset + 1) * 34 : 1;
+      offset += 2;
+      offset += data[offset] === 1 ? 2 : 1;
+      offset += data[offset] === 1 ? 2 : 1;
+      assert.equal(data[offset], 1, "collection should be set");
+      return {
+        verified: data[offset + 1] === 1,
+        key: new PublicKey(data.subarray(offset + 2, offset + 34)),
+      };
+    }
 
+    it("Mints agents unverified while off, then verifies them retroactively", async () => {
+      await setVerifyCollection(false);
+
+      const agentMint = Keypair.generate();
       try {
-        await program.methods
-          .setMetadata(longKey, Buffer.from("value"))
+        const registerIx = await program.methods
+          .register("https://example.com/unverified.json")
           .accounts({
-            agentAccount: agentPda,
+            config: configPda,
+            authority: provider.wallet.publicKey,
+            agentAccount: getAgentPda(agentMint.publicKey)[0],
+            agentMint: agentMint.publicKey,
+            agentMetadata: getMetadataPda(agentMint.publicKey),
+            agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
+            agentTokenAccount: getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey),
+            collectionMint: collectionMint.publicKey,
+            collectionMetadata,
+            collectionMasterEdition,
             owner: provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+            tokenProgram: TOKEN_PROGRAM_ID,
+            associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+            rent: SYSVAR_RENT_PUBKEY,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
           })
-          .rpc();
-
-        assert.fail("Should have failed with KeyTooLong error");
-      } catch (error) {
-        assert.include(error.message, "KeyTooLong");
+          .instruction();
+        await sendWithComputeBudget(registerIx, [agentMint]);
+      } finally {
+        // Restore the default for the remaining tests
+        await setVerifyCollection(true);
       }
-    });
 
-    it("Fails with value > 256 bytes", async () => {
-      const longValue = Buffer.alloc(257, "x");
+      let collection = await fetchCollection(agentMint.publicKey);
+      assert.isFalse(collection.verified);
+      assert.equal(collection.key.toBase58(), collectionMint.publicKey.toBase58());
 
+      // Only the authority (or an approved delegate) can verify
+      const attacker = Keypair.generate();
       try {
         await program.methods
-          .setMetadata("data", longValue)
+          .verifyAgentCollection(agentMint.publicKey)
           .accounts({
-            agentAccount: agentPda,
-            owner: provider.wallet.publicKey,
+            config: configPda,
+            collectionAuthorityRecord: null,
+            authority: attacker.publicKey,
+            agentAccount: getAgentPda(agentMint.publicKey)[0],
+            agentMetadata: getMetadataPda(agentMint.publicKey),
+            collectionMint: collectionMint.publicKey,
+            collectionMetadata,
+            collectionMasterEdition,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
           })
+          .signers([attacker])
           .rpc();
-
-        assert.fail("Should have failed with ValueTooLong error");
+        assert.fail("Should have failed with Unauthorized error");
       } catch (error) {
-        assert.include(error.message, "ValueTooLong");
+        assert.include(error.message, "Unauthorized");
       }
-    });
 
-    it("Fails when non-owner tries to set metadata", async () => {
-      const otherUser = Keypair.generate();
+      await program.methods
+        .verifyAgentCollection(agentMint.publicKey)
+        .accounts({
+          config: configPda,
+          collectionAuthorityRecord: null,
+          authority: provider.wallet.publicKey,
+          agentAccount: getAgentPda(agentMint.publicKey)[0],
+          agentMetadata: getMetadataPda(agentMint.publicKey),
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        })
+        .rpc();
 
-      // Airdrop to other user
-      await provider.connection.confirmTransaction(
-        await provider.connection.requestAirdrop(otherUser.publicKey, 1000000000)
-      );
+      collection = await fetchCollection(agentMint.publicKey);
+      assert.isTrue(collection.verified);
+    });
+
+    it("Fails when called by non-authority", async () => {
+      const attacker = Keypair.generate();
 
       try {
         await program.methods
-          .setMetadata("name", Buffer.from("Hacker"))
-          .accounts({
-            agentAccount: agentPda,
-            owner: otherUser.publicKey,
-          })
-          .signers([otherUser])
+          .setVerifyCollection(false)
+          .accounts({ config: configPda, authority: attacker.publicKey })
+          .signers([attacker])
           .rpc();
-
         assert.fail("Should have failed with Unauthorized error");
       } catch (error) {
         assert.include(error.message, "Unauthorized");
@@ -938,27 +1384,2124 @@ describe("Identity Registry (ERC-8004 Spec Compliant)", () => {
     });
   });
 
-  describe("Set Agent URI (ERC-8004: setAgentUri(agentId, newUri))", () => {
-    let agentMint: Keypair;
-    let agentPda: PublicKey;
+  describe("Transfer Authority (Registry Authority Handover)", () => {
+    const newAuthority = Keypair.generate();
 
-    beforeEach(async () => {
-      agentMint = Keypair.generate();
-      const agentMetadata = getMetadataPda(agentMint.publicKey);
-      const agentMasterEdition = getMasterEditionPda(agentMint.publicKey);
-      const agentTokenAccount = getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey);
-      [agentPda] = getAgentPda(agentMint.publicKey);
+    const transferAuthority = (to: PublicKey, signer: Keypair) =>
+      program.methods
+        .transferAuthority(to)
+        .accounts({ config: configPda, authority: signer.publicKey })
+        .signers([signer])
+        .rpc();
 
-      const registerIx = await program.methods
-        .register("https://original.com")
+    const updateCollectionAuthority = (signer: Keypair) =>
+      program.methods
+        .updateCollectionAuthority()
         .accounts({
           config: configPda,
-          authority: provider.wallet.publicKey,
-          agentAccount: agentPda,
+          collectionAuthority: signer.publicKey,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([signer])
+        .rpc();
+
+    const registerWith = async (authority: Keypair) => {
+      const agentMint = Keypair.generate();
+      const ix = await program.methods
+        .register("https://example.com/handover.json")
+        .accounts({
+          config: configPda,
+          authority: authority.publicKey,
+          agentAccount: getAgentPda(agentMint.publicKey)[0],
           agentMint: agentMint.publicKey,
-          agentMetadata,
-          agentMasterEdition,
-          agentTokenAccount,
+          agentMetadata: getMetadataPda(agentMint.publicKey),
+          agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
+          agentTokenAccount: getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey),
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .instruction();
+      await sendWithComputeBudget(ix, [agentMint, authority]);
+    };
+
+    before(async () => {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(newAuthority.publicKey, 1000000000)
+      );
+    });
+
+    it("New authority verifies registrations, the old one no longer can", async () => {
+      const oldAuthority = (provider.wallet as anchor.Wallet).payer;
+
+      await transferAuthority(newAuthority.publicKey, oldAuthority);
+      await updateCollectionAuthority(oldAuthority);
+
+      const config = await program.account.registryConfig.fetch(configPda);
+      assert.equal(config.authority.toBase58(), newAuthority.publicKey.toBase58());
+
+      await registerWith(newAuthority);
+
+      try {
+        await registerWith(oldAuthority);
+        assert.fail("Old authority should no longer verify registrations");
+      } catch (error) {
+        assert.notInclude(error.message, "Old authority should no longer");
+      }
+
+      // Hand the registry back so later tests keep working
+      await transferAuthority(oldAuthority.publicKey, newAuthority);
+      await updateCollectionAuthority(newAuthority);
+
+      await registerWith(oldAuthority);
+    });
+
+    it("Fails when called by non-authority", async () => {
+      const attacker = Keypair.generate();
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(attacker.publicKey, 1000000000)
+      );
+
+      try {
+        await transferAuthority(attacker.publicKey, attacker);
+        assert.fail("Should have failed with Unauthorized error");
+      } catch (error) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+  });
+
+  describe("Set PDA Authority (Governance Handover)", () => {
+    // The handover is irreversible for the test wallet: the mock governance
+    // program hands the registry back at the end of the CPI test
+    const governance = anchor.workspace.MockGovernance as Program<MockGovernance>;
+    const [governancePda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("governance")],
+      governance.programId
+    );
+
+    const handover = (newAuthority: PublicKey, signer?: Keypair) =>
+      program.methods
+        .setPdaAuthority(newAuthority)
+        .accounts({
+          config: configPda,
+          authority: signer ? signer.publicKey : provider.wallet.publicKey,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers(signer ? [signer] : [])
+        .rpc();
+
+    it("Rejects a regular (on-curve) wallet as the new authority", async () => {
+      try {
+        await handover(Keypair.generate().publicKey);
+        assert.fail("Should have failed with InvalidPdaAuthority error");
+      } catch (error) {
+        assert.include(error.message, "InvalidPdaAuthority");
+      }
+    });
+
+    it("Fails when called by non-authority", async () => {
+      const attacker = Keypair.generate();
+      const [governancePda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("governance")],
+        SystemProgram.programId
+      );
+
+      try {
+        await handover(governancePda, attacker);
+        assert.fail("Should have failed with Unauthorized error");
+      } catch (error) {
+        assert.include(error.message, "Unauthorized");
+      }
+
+      const config = await program.account.registryConfig.fetch(configPda);
+      assert.equal(config.authority.toBase58(), provider.wallet.publicKey.toBase58());
+    });
+
+    it("Lets the governance PDA sign set_paused via CPI", async () => {
+      await handover(governancePda);
+
+      try {
+        let config = await program.account.registryConfig.fetch(configPda);
+        assert.ok(config.authority.equals(governancePda));
+
+        // The former authority wallet is locked out
+        try {
+          await program.methods
+            .setPaused(true)
+            .accounts({ config: configPda, authority: provider.wallet.publicKey })
+            .rpc();
+          assert.fail("Should have failed with Unauthorized error");
+        } catch (error) {
+          assert.include(error.message, "Unauthorized");
+        }
+
+        for (const paused of [true, false]) {
+          await governance.methods
+            .setPaused(paused)
+            .accounts({
+              config: configPda,
+              governance: governancePda,
+              identityProgram: program.programId,
+            })
+            .rpc();
+
+          config = await program.account.registryConfig.fetch(configPda);
+          assert.equal(config.paused, paused);
+        }
+      } finally {
+        // Give the registry (config and collection) back for the remaining tests
+        await governance.methods
+          .handBack(provider.wallet.publicKey)
+          .accounts({
+            config: configPda,
+            governance: governancePda,
+            collectionMint: collectionMint.publicKey,
+            collectionMetadata,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            identityProgram: program.programId,
+          })
+          .rpc();
+      }
+
+      const config = await program.account.registryConfig.fetch(configPda);
+      assert.ok(config.authority.equals(provider.wallet.publicKey));
+    });
+  });
+
+  describe("Get Metadata (ERC-8004: getMetadata(agentId, key))", () => {
+    let agentMint: Keypair;
+    let agentPda: PublicKey;
+
+    before(async () => {
+      // Register an agent with metadata
+      agentMint = Keypair.generate();
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+      const agentMasterEdition = getMasterEditionPda(agentMint.publicKey);
+      const agentTokenAccount = getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey);
+      [agentPda] = getAgentPda(agentMint.publicKey);
+
+      const metadata = [
+        { key: "name", value: Buffer.from("Test Agent") },
+        { key: "type", value: Buffer.from("assistant") },
+      ];
+
+      const ix = await program.methods
+        .registerWithMetadata("https://test.com", metadata)
+        .accounts({
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          agentMetadata,
+          agentMasterEdition,
+          agentTokenAccount,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .instruction();
+
+      await sendWithComputeBudget(ix, [agentMint]);
+    });
+
+    it("Returns metadata value for existing key", async () => {
+      const result = await program.methods
+        .getMetadata("name")
+        .accounts({
+          agentAccount: agentPda,
+        })
+        .view();
+
+      assert.equal(Buffer.from(result).toString(), "Test Agent");
+    });
+
+    it("Returns empty bytes for non-existent key", async () => {
+      const result = await program.methods
+        .getMetadata("nonexistent")
+        .accounts({
+          agentAccount: agentPda,
+        })
+        .view();
+
+      assert.equal(result.length, 0, "Should return empty array");
+    });
+
+    it("Returns all metadata entries in one call", async () => {
+      const entries = await program.methods
+        .getAllMetadata()
+        .accounts({
+          agentAccount: agentPda,
+        })
+        .view();
+
+      assert.deepEqual(
+        entries.map((e) => e.key),
+        ["name", "type"]
+      );
+      assert.equal(Buffer.from(entries[1].value).toString(), "assistant");
+    });
+  });
+
+  describe("Set Metadata (ERC-8004: setMetadata(agentId, key, value))", () => {
+    let agentMint: Keypair;
+    let agentPda: PublicKey;
+
+    beforeEach(async () => {
+      // Register a fresh agent for each test
+      agentMint = Keypair.generate();
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+      const agentMasterEdition = getMasterEditionPda(agentMint.publicKey);
+      const agentTokenAccount = getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey);
+      [agentPda] = getAgentPda(agentMint.publicKey);
+
+      const registerIx = await program.methods
+        .register("https://example.com")
+        .accounts({
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          agentMetadata,
+          agentMasterEdition,
+          agentTokenAccount,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .instruction();
+
+      await sendWithComputeBudget(registerIx, [agentMint]);
+    });
+
+    it("Sets new metadata entry", async () => {
+      await program.methods
+        .setMetadata("name", Buffer.from("Alice"))
+        .accounts({
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.metadata.length, 1);
+      assert.equal(agent.metadata[0].key, "name");
+      assert.equal(Buffer.from(agent.metadata[0].value).toString(), "Alice");
+    });
+
+    it("Bumps updated_at past created_at on mutation", async () => {
+      let agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.updatedAt.toNumber(), agent.createdAt.toNumber());
+
+      // Let the cluster clock advance at least one second
+      await new Promise((resolve) => setTimeout(resolve, 1500));
+
+      await program.methods
+        .setMetadata("name", Buffer.from("Alice"))
+        .accounts({
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      agent = await program.account.agentAccount.fetch(agentPda);
+      assert.isAbove(agent.updatedAt.toNumber(), agent.createdAt.toNumber());
+    });
+
+    it("Updates existing metadata entry", async () => {
+      // Set initial value
+      await program.methods
+        .setMetadata("status", Buffer.from("active"))
+        .accounts({
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      // Update same key
+      await program.methods
+        .setMetadata("status", Buffer.from("inactive"))
+        .accounts({
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.metadata.length, 1, "Should still have 1 entry");
+      assert.equal(Buffer.from(agent.metadata[0].value).toString(), "inactive");
+    });
+
+    it("Adds multiple metadata entries", async () => {
+      const entries = [
+        { key: "name", value: "Alice" },
+        { key: "type", value: "assistant" },
+        { key: "version", value: "1.0" },
+      ];
+
+      for (const entry of entries) {
+        await program.methods
+          .setMetadata(entry.key, Buffer.from(entry.value))
+          .accounts({
+            agentAccount: agentPda,
+            owner: provider.wallet.publicKey,
+          })
+          .rpc();
+      }
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.metadata.length, 3);
+    });
+
+    it("Enforces 10 metadata entry limit", async () => {
+      // Add 10 entries
+      for (let i = 0; i < 10; i++) {
+        await program.methods
+          .setMetadata(`key${i}`, Buffer.from(`value${i}`))
+          .accounts({
+            agentAccount: agentPda,
+            owner: provider.wallet.publicKey,
+          })
+          .rpc();
+      }
+
+      // Try to add 11th entry
+      try {
+        await program.methods
+          .setMetadata("key11", Buffer.from("value11"))
+          .accounts({
+            agentAccount: agentPda,
+            owner: provider.wallet.publicKey,
+          })
+          .rpc();
+
+        assert.fail("Should have failed with MetadataLimitReached error");
+      } catch (error) {
+        assert.include(error.message, "MetadataLimitReached");
+      }
+    });
+
+    it("Removes an entry and frees a slot under the cap", async () => {
+      for (let i = 0; i < 10; i++) {
+        await program.methods
+          .setMetadata(`key${i}`, Buffer.from(`value${i}`))
+          .accounts({
+            agentAccount: agentPda,
+            owner: provider.wallet.publicKey,
+          })
+          .rpc();
+      }
+
+      await program.methods
+        .removeMetadata("key3")
+        .accounts({
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      let agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.metadata.length, 9);
+      assert.notInclude(agent.metadata.map((e) => e.key), "key3");
+
+      // A new key fits again
+      await program.methods
+        .setMetadata("key10", Buffer.from("value10"))
+        .accounts({
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.metadata.length, 10);
+    });
+
+    it("Fails to remove a missing key", async () => {
+      try {
+        await program.methods
+          .removeMetadata("missing")
+          .accounts({
+            agentAccount: agentPda,
+            owner: provider.wallet.publicKey,
+          })
+          .rpc();
+
+        assert.fail("Should have failed with MetadataNotFound error");
+      } catch (error) {
+        assert.include(error.message, "MetadataNotFound");
+      }
+    });
+
+    it("Fails to remove an entry as a stranger", async () => {
+      await program.methods
+        .setMetadata("name", Buffer.from("Alice"))
+        .accounts({
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      const stranger = Keypair.generate();
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(stranger.publicKey, 1000000000)
+      );
+
+      try {
+        await program.methods
+          .removeMetadata("name")
+          .accounts({
+            agentAccount: agentPda,
+            owner: stranger.publicKey,
+          })
+          .signers([stranger])
+          .rpc();
+
+        assert.fail("Should have failed with Unauthorized error");
+      } catch (error) {
+        assert.include(error.message, "Unauthorized");
+      }
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.include(agent.metadata.map((e) => e.key), "name");
+    });
+
+    it("Metadata delegate can remove an entry", async () => {
+      await program.methods
+        .setMetadata("name", Buffer.from("Alice"))
+        .accounts({
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      const delegate = Keypair.generate();
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(delegate.publicKey, 1000000000)
+      );
+      await program.methods
+        .setMetadataDelegate(delegate.publicKey)
+        .accounts({
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      await program.methods
+        .removeMetadata("name")
+        .accounts({
+          agentAccount: agentPda,
+          owner: delegate.publicKey,
+        })
+        .signers([delegate])
+        .rpc();
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.notInclude(agent.metadata.map((e) => e.key), "name");
+    });
+
+    it("Fails with key > 32 bytes", async () => {
+      const longKey = "x".repeat(33);
+
+      try {
+        await program.methods
+          .setMetadata(longKey, Buffer.from("value"))
+          .accounts({
+            agentAccount: agentPda,
+            owner: provider.wallet.publicKey,
+          })
+          .rpc();
+
+        assert.fail("Should have failed with KeyTooLong error");
+      } catch (error) {
+        assert.include(error.message, "KeyTooLong");
+      }
+    });
+
+    it("Fails with value > 256 bytes", async () => {
+      const longValue = Buffer.alloc(257, "x");
+
+      try {
+        await program.methods
+          .setMetadata("data", longValue)
+          .accounts({
+            agentAccount: agentPda,
+            owner: provider.wallet.publicKey,
+          })
+          .rpc();
+
+        assert.fail("Should have failed with ValueTooLong error");
+      } catch (error) {
+        assert.include(error.message, "ValueTooLong");
+      }
+    });
+
+    it("Accepts values up to a raised max_value_length", async () => {
+      await program.methods
+        .setMaxValueLength(512)
+        .accounts({ config: configPda, authority: provider.wallet.publicKey })
+        .rpc();
+
+      try {
+        // Slots reserve 256 value bytes: a 512-byte value borrows the slack
+        // of the other (shorter) entries
+        await program.methods
+          .setMetadata("data", Buffer.alloc(512, "x"))
+          .accounts({
+            agentAccount: agentPda,
+            owner: provider.wallet.publicKey,
+          })
+          .rpc();
+
+        const agent = await program.account.agentAccount.fetch(agentPda);
+        assert.equal(agent.metadata[0].value.length, 512);
+
+        let rejected = false;
+        try {
+          await program.methods
+            .setMaxValueLength(513)
+            .accounts({ config: configPda, authority: provider.wallet.publicKey })
+            .rpc();
+        } catch (error) {
+          rejected = true;
+          assert.include(error.message, "InvalidMaxValueLength");
+        }
+        assert.isTrue(rejected, "Limit above the account layout must be rejected");
+      } finally {
+        // Restore the default for the remaining tests
+        await program.methods
+          .setMaxValueLength(256)
+          .accounts({ config: configPda, authority: provider.wallet.publicKey })
+          .rpc();
+      }
+    });
+
+    it("Fails when non-owner tries to set metadata", async () => {
+      const otherUser = Keypair.generate();
+
+      // Airdrop to other user
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(otherUser.publicKey, 1000000000)
+      );
+
+      try {
+        await program.methods
+          .setMetadata("name", Buffer.from("Hacker"))
+          .accounts({
+            agentAccount: agentPda,
+            owner: otherUser.publicKey,
+          })
+          .signers([otherUser])
+          .rpc();
+
+        assert.fail("Should have failed with Unauthorized error");
+      } catch (error) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+  });
+
+  describe("Set Agent URI (ERC-8004: setAgentUri(agentId, newUri))", () => {
+    let agentMint: Keypair;
+    let agentPda: PublicKey;
+
+    beforeEach(async () => {
+      agentMint = Keypair.generate();
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+      const agentMasterEdition = getMasterEditionPda(agentMint.publicKey);
+      const agentTokenAccount = getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey);
+      [agentPda] = getAgentPda(agentMint.publicKey);
+
+      const registerIx = await program.methods
+        .register("https://original.com")
+        .accounts({
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          agentMetadata,
+          agentMasterEdition,
+          agentTokenAccount,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([agentMint])
+        .instruction();
+
+      const computeBudgetIx = ComputeBudgetProgram.setComputeUnitLimit({
+        units: 400_000,
+      });
+
+      const tx = new Transaction().add(computeBudgetIx, registerIx);
+      await provider.sendAndConfirm(tx, [agentMint]);
+    });
+
+    it("Updates agent URI", async () => {
+      const newUri = "https://updated.com";
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+
+      await program.methods
+        .setAgentUri(newUri)
+        .accounts({
+          agentAccount: agentPda,
+          agentMetadata,
+          agentMint: agentMint.publicKey,
+          owner: provider.wallet.publicKey,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .rpc();
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.tokenUri, newUri);
+    });
+
+    it("Updates agent URI to empty string (ERC-8004 spec)", async () => {
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+
+      await program.methods
+        .setAgentUri("")
+        .accounts({
+          agentAccount: agentPda,
+          agentMetadata,
+          agentMint: agentMint.publicKey,
+          owner: provider.wallet.publicKey,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .rpc();
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.tokenUri, "");
+    });
+
+    it("Updates agent URI multiple times", async () => {
+      const uris = ["https://v1.com", "https://v2.com", "https://v3.com"];
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+
+      for (const uri of uris) {
+        await program.methods
+          .setAgentUri(uri)
+          .accounts({
+            agentAccount: agentPda,
+            agentMetadata,
+            agentMint: agentMint.publicKey,
+            owner: provider.wallet.publicKey,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .rpc();
+
+        const agent = await program.account.agentAccount.fetch(agentPda);
+        assert.equal(agent.tokenUri, uri);
+      }
+    });
+
+    it("Resizes the agent account to the URI it stores", async () => {
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+      const AGENT_ACCOUNT_BASE_SPACE = 8 + 3457;
+      const setUri = (uri: string) =>
+        program.methods
+          .setAgentUri(uri)
+          .accounts({
+            agentAccount: agentPda,
+            agentMetadata,
+            agentMint: agentMint.publicKey,
+            owner: provider.wallet.publicKey,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .rpc();
+
+      await program.methods
+        .setMaxUriLength(512)
+        .accounts({ config: configPda, authority: provider.wallet.publicKey })
+        .rpc();
+      try {
+        await setUri("https://gateway.example.com/agent.json?sig=" + "x".repeat(457));
+        let info = await provider.connection.getAccountInfo(agentPda);
+        assert.equal(info!.data.length, AGENT_ACCOUNT_BASE_SPACE + 300);
+
+        // Back under the reserve: the excess is released
+        await setUri("https://short.example.com");
+        info = await provider.connection.getAccountInfo(agentPda);
+        assert.equal(info!.data.length, AGENT_ACCOUNT_BASE_SPACE);
+      } finally {
+        await program.methods
+          .setMaxUriLength(200)
+          .accounts({ config: configPda, authority: provider.wallet.publicKey })
+          .rpc();
+      }
+    });
+
+    it("Fails with URI > 200 bytes", async () => {
+      const longUri = "x".repeat(201);
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+
+      try {
+        await program.methods
+          .setAgentUri(longUri)
+          .accounts({
+            agentAccount: agentPda,
+            agentMetadata,
+            agentMint: agentMint.publicKey,
+            owner: provider.wallet.publicKey,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .rpc();
+
+        assert.fail("Should have failed with UriTooLong error");
+      } catch (error) {
+        assert.include(error.message, "UriTooLong");
+      }
+    });
+
+    it("Accepts each supported scheme and rejects others", async () => {
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+      const setUri = (uri: string) =>
+        program.methods
+          .setAgentUri(uri)
+          .accounts({
+            agentAccount: agentPda,
+            agentMetadata,
+            agentMint: agentMint.publicKey,
+            owner: provider.wallet.publicKey,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .rpc();
+
+      for (const uri of ["ipfs://QmAgent", "ar://agentTx", "https://example.com/a.json", "http://example.com/a.json"]) {
+        await setUri(uri);
+        const agent = await program.account.agentAccount.fetch(agentPda);
+        assert.equal(agent.tokenUri, uri);
+      }
+
+      try {
+        await setUri("ftp://example.com/a.json");
+        assert.fail("Should have failed with InvalidUriScheme error");
+      } catch (error) {
+        assert.include(error.message, "InvalidUriScheme");
+      }
+    });
+
+    it("Fails when non-owner tries to set URI", async () => {
+      const otherUser = Keypair.generate();
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(otherUser.publicKey, 1000000000)
+      );
+
+      try {
+        await program.methods
+          .setAgentUri("https://hacker.com")
+          .accounts({
+            agentAccount: agentPda,
+            agentMetadata,
+            agentMint: agentMint.publicKey,
+            owner: otherUser.publicKey,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .signers([otherUser])
+          .rpc();
+
+        assert.fail("Should have failed with Unauthorized error");
+      } catch (error) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+  });
+
+  describe("Metadata Delegate (Ops Key)", () => {
+    let agentMint: Keypair;
+    let agentPda: PublicKey;
+    let delegate: Keypair;
+
+    const setDelegate = (key: PublicKey | null, signer?: Keypair) =>
+      program.methods
+        .setMetadataDelegate(key)
+        .accounts({
+          agentAccount: agentPda,
+          owner: signer ? signer.publicKey : provider.wallet.publicKey,
+        })
+        .signers(signer ? [signer] : [])
+        .rpc();
+
+    const delegateSetsMetadata = (key: string) =>
+      program.methods
+        .setMetadata(key, Buffer.from("by-delegate"))
+        .accounts({ agentAccount: agentPda, owner: delegate.publicKey })
+        .signers([delegate])
+        .rpc();
+
+    beforeEach(async () => {
+      agentMint = Keypair.generate();
+      [agentPda] = getAgentPda(agentMint.publicKey);
+
+      const registerIx = await program.methods
+        .register("https://example.com/delegate.json")
+        .accounts({
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          agentMetadata: getMetadataPda(agentMint.publicKey),
+          agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
+          agentTokenAccount: getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey),
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .instruction();
+      await sendWithComputeBudget(registerIx, [agentMint]);
+
+      delegate = Keypair.generate();
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(delegate.publicKey, 1000000000)
+      );
+      await setDelegate(delegate.publicKey);
+    });
+
+    it("Delegate can set metadata but not the URI", async () => {
+      await delegateSetsMetadata("ops");
+
+      // The URI is mirrored into the NFT metadata, which only the owner can sign
+      try {
+        await program.methods
+          .setAgentUri("https://example.com/by-delegate.json")
+          .accounts({
+            agentAccount: agentPda,
+            agentMetadata: getMetadataPda(agentMint.publicKey),
+            agentMint: agentMint.publicKey,
+            owner: delegate.publicKey,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .signers([delegate])
+          .rpc();
+        assert.fail("Should have failed with Unauthorized error");
+      } catch (error) {
+        assert.include(error.message, "Unauthorized");
+      }
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.metadataDelegate.toBase58(), delegate.publicKey.toBase58());
+      assert.equal(agent.tokenUri, "https://example.com/delegate.json");
+      const entry = agent.metadata.find((e) => e.key === "ops");
+      assert.equal(Buffer.from(entry.value).toString(), "by-delegate");
+      // Owner is unchanged
+      assert.equal(agent.owner.toBase58(), provider.wallet.publicKey.toBase58());
+    });
+
+    it("Delegate cannot transfer the agent", async () => {
+      const toTokenAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        (provider.wallet as anchor.Wallet).payer,
+        agentMint.publicKey,
+        delegate.publicKey
+      );
+
+      try {
+        await program.methods
+          .transferAgent(false)
+          .accounts({
+            agentAccount: agentPda,
+            fromTokenAccount: getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey),
+            toTokenAccount: toTokenAccount.address,
+            owner: delegate.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .signers([delegate])
+          .rpc();
+
+        assert.fail("Should have failed with Unauthorized error");
+      } catch (error) {
+        assert.include(error.message, "Unauthorized");
+      }
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.owner.toBase58(), provider.wallet.publicKey.toBase58());
+    });
+
+    it("Delegate cannot change the delegate", async () => {
+      try {
+        await setDelegate(Keypair.generate().publicKey, delegate);
+        assert.fail("Should have failed with Unauthorized error");
+      } catch (error) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+
+    it("Revoked delegate loses access", async () => {
+      await setDelegate(null);
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.isNull(agent.metadataDelegate);
+
+      try {
+        await delegateSetsMetadata("revoked");
+        assert.fail("Should have failed with Unauthorized error");
+      } catch (error) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+  });
+
+  describe("Set Category (Agent Directory)", () => {
+    let agentMint: Keypair;
+    let agentPda: PublicKey;
+
+    beforeEach(async () => {
+      agentMint = Keypair.generate();
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+      const agentMasterEdition = getMasterEditionPda(agentMint.publicKey);
+      const agentTokenAccount = getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey);
+      [agentPda] = getAgentPda(agentMint.publicKey);
+
+      const registerIx = await program.methods
+        .register("https://original.com")
+        .accounts({
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          agentMetadata,
+          agentMasterEdition,
+          agentTokenAccount,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([agentMint])
+        .instruction();
+
+      const computeBudgetIx = ComputeBudgetProgram.setComputeUnitLimit({
+        units: 400_000,
+      });
+
+      const tx = new Transaction().add(computeBudgetIx, registerIx);
+      await provider.sendAndConfirm(tx, [agentMint]);
+    });
+
+    it("Defaults to uncategorized and sets a category", async () => {
+      let agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.category, 0);
+
+      await program.methods
+        .setCategory(1) // Trading
+        .accounts({
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.category, 1);
+    });
+
+    it("Is filterable by category with memcmp at a fixed offset", async () => {
+      await program.methods
+        .setCategory(1042) // deployment-specific
+        .accounts({
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      const categoryBytes = Buffer.alloc(2);
+      categoryBytes.writeUInt16LE(1042);
+      const matches = await program.account.agentAccount.all([
+        { memcmp: { offset: 8 + 8 + 32 + 32, bytes: anchor.utils.bytes.bs58.encode(categoryBytes) } },
+      ]);
+
+      assert.isTrue(matches.some((m) => m.publicKey.equals(agentPda)));
+    });
+
+    it("Fails with a reserved category", async () => {
+      try {
+        await program.methods
+          .setCategory(500)
+          .accounts({
+            agentAccount: agentPda,
+            owner: provider.wallet.publicKey,
+          })
+          .rpc();
+
+        assert.fail("Should have failed with InvalidCategory error");
+      } catch (error) {
+        assert.include(error.message, "InvalidCategory");
+      }
+    });
+
+    it("Fails when non-owner tries to set category", async () => {
+      const otherUser = Keypair.generate();
+
+      try {
+        await program.methods
+          .setCategory(2)
+          .accounts({
+            agentAccount: agentPda,
+            owner: otherUser.publicKey,
+          })
+          .signers([otherUser])
+          .rpc();
+
+        assert.fail("Should have failed with Unauthorized error");
+      } catch (error) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+  });
+
+  describe("Operator Type (Autonomous / Human-Operated)", () => {
+    let agentMint: Keypair;
+    let agentPda: PublicKey;
+
+    beforeEach(async () => {
+      agentMint = Keypair.generate();
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+      const agentMasterEdition = getMasterEditionPda(agentMint.publicKey);
+      const agentTokenAccount = getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey);
+      [agentPda] = getAgentPda(agentMint.publicKey);
+
+      const registerIx = await program.methods
+        .register("https://original.com")
+        .accounts({
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          agentMetadata,
+          agentMasterEdition,
+          agentTokenAccount,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([agentMint])
+        .instruction();
+
+      const computeBudgetIx = ComputeBudgetProgram.setComputeUnitLimit({
+        units: 400_000,
+      });
+
+      const tx = new Transaction().add(computeBudgetIx, registerIx);
+      await provider.sendAndConfirm(tx, [agentMint]);
+    });
+
+    function getOperatorAttestationPda(mint: PublicKey): PublicKey {
+      return PublicKey.findProgramAddressSync(
+        [Buffer.from("operator_attestation"), mint.toBuffer()],
+        program.programId
+      )[0];
+    }
+
+    it("Defaults to autonomous and sets a declared operator type", async () => {
+      let agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.operatorType, 0);
+
+      await program.methods
+        .setOperatorType(1) // HumanOperated
+        .accounts({
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.operatorType, 1);
+    });
+
+    it("Fails with an unknown operator type", async () => {
+      try {
+        await program.methods
+          .setOperatorType(3)
+          .accounts({
+            agentAccount: agentPda,
+            owner: provider.wallet.publicKey,
+          })
+          .rpc();
+
+        assert.fail("Should have failed with InvalidOperatorType error");
+      } catch (error) {
+        assert.include(error.message, "InvalidOperatorType");
+      }
+    });
+
+    it("Registry authority attests the declared operator type", async () => {
+      await program.methods
+        .setOperatorType(2) // Hybrid
+        .accounts({
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      const operatorAttestation = getOperatorAttestationPda(agentMint.publicKey);
+      await program.methods
+        .attestOperatorType()
+        .accounts({
+          config: configPda,
+          agentAccount: agentPda,
+          operatorAttestation,
+          authority: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const attestation = await program.account.operatorAttestation.fetch(operatorAttestation);
+      assert.equal(attestation.operatorType, 2);
+      assert.equal(attestation.attester.toBase58(), provider.wallet.publicKey.toBase58());
+    });
+
+    it("Fails when a non-authority attests", async () => {
+      const otherUser = Keypair.generate();
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(otherUser.publicKey, 1000000000)
+      );
+
+      try {
+        await program.methods
+          .attestOperatorType()
+          .accounts({
+            config: configPda,
+            agentAccount: agentPda,
+            operatorAttestation: getOperatorAttestationPda(agentMint.publicKey),
+            authority: otherUser.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([otherUser])
+          .rpc();
+
+        assert.fail("Should have failed with Unauthorized error");
+      } catch (error) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+  });
+
+  describe("Deprecate Agent (Successor Pointer)", () => {
+    async function registerFreshAgent(): Promise<PublicKey> {
+      const agentMint = Keypair.generate();
+      const [agentPda] = getAgentPda(agentMint.publicKey);
+
+      const registerIx = await program.methods
+        .register("https://versioned.com")
+        .accounts({
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          agentMetadata: getMetadataPda(agentMint.publicKey),
+          agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
+          agentTokenAccount: getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey),
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .instruction();
+
+      await sendWithComputeBudget(registerIx, [agentMint]);
+      return agentPda;
+    }
+
+    it("Deprecates an agent with a pointer to its successor", async () => {
+      const oldAgentPda = await registerFreshAgent();
+      const newAgentPda = await registerFreshAgent();
+      const newAgent = await program.account.agentAccount.fetch(newAgentPda);
+
+      let oldAgent = await program.account.agentAccount.fetch(oldAgentPda);
+      assert.equal(oldAgent.status, 0, "Agents start active");
+      assert.isNull(oldAgent.successorAgent);
+
+      await program.methods
+        .deprecateAgent(newAgent.agentId)
+        .accounts({
+          agentAccount: oldAgentPda,
+          successorAccount: newAgentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      oldAgent = await program.account.agentAccount.fetch(oldAgentPda);
+      assert.equal(oldAgent.status, 1, "Deprecated");
+      assert.equal(oldAgent.successorAgent.toString(), newAgent.agentId.toString());
+    });
+
+    it("Rejects a successor that does not match the given id", async () => {
+      const oldAgentPda = await registerFreshAgent();
+      const otherAgentPda = await registerFreshAgent();
+      const otherAgent = await program.account.agentAccount.fetch(otherAgentPda);
+
+      let rejected = false;
+      try {
+        await program.methods
+          .deprecateAgent(otherAgent.agentId.addn(1000))
+          .accounts({
+            agentAccount: oldAgentPda,
+            successorAccount: otherAgentPda,
+            owner: provider.wallet.publicKey,
+          })
+          .rpc();
+      } catch (error) {
+        rejected = true;
+        assert.include(error.message, "InvalidSuccessor");
+      }
+      assert.isTrue(rejected, "Successor must exist and match successor_id");
+    });
+
+    it("Allows deprecation without a successor", async () => {
+      const agentPda = await registerFreshAgent();
+
+      await program.methods
+        .deprecateAgent(null)
+        .accounts({
+          agentAccount: agentPda,
+          successorAccount: null,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.status, 1);
+      assert.isNull(agent.successorAgent);
+    });
+  });
+
+  describe("Sync Owner (NFT Transfer Support)", () => {
+    let agentMint: Keypair;
+    let agentPda: PublicKey;
+    let originalOwnerTokenAccount: PublicKey;
+    let newOwner: Keypair;
+    let newOwnerTokenAccount: PublicKey;
+
+    beforeEach(async () => {
+      agentMint = Keypair.generate();
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+      const agentMasterEdition = getMasterEditionPda(agentMint.publicKey);
+      originalOwnerTokenAccount = getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey);
+      [agentPda] = getAgentPda(agentMint.publicKey);
+
+      const registerIx = await program.methods
+        .register("https://example.com")
+        .accounts({
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          agentMetadata,
+          agentMasterEdition,
+          agentTokenAccount: originalOwnerTokenAccount,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .instruction();
+
+      await sendWithComputeBudget(registerIx, [agentMint]);
+
+      // Prepare new owner
+      newOwner = Keypair.generate();
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(newOwner.publicKey, 1000000000)
+      );
+      newOwnerTokenAccount = getAssociatedTokenAddressSync(agentMint.publicKey, newOwner.publicKey);
+    });
+
+    it("Syncs owner after SPL Token transfer", async () => {
+      // Create new owner's token account first
+      const { AssociatedTokenProgramInstruction, createAssociatedTokenAccountInstruction } = await import("@solana/spl-token");
+
+      const createAtaTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          provider.wallet.publicKey,
+          newOwnerTokenAccount,
+          newOwner.publicKey,
+          agentMint.publicKey
+        )
+      );
+      await provider.sendAndConfirm(createAtaTx);
+
+      // Transfer NFT via SPL Token
+      await transfer(
+        provider.connection,
+        provider.wallet.payer,
+        originalOwnerTokenAccount,
+        newOwnerTokenAccount,
+        provider.wallet.publicKey,
+        1
+      );
+
+      // Sync owner in AgentAccount (old owner must sign to transfer update_authority)
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+
+      await program.methods
+        .syncOwner(false)
+        .accounts({
+          agentAccount: agentPda,
+          tokenAccount: newOwnerTokenAccount,
+          agentMetadata,
+          agentMint: agentMint.publicKey,
+          oldOwnerSigner: provider.wallet.publicKey,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .rpc();
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.owner.toBase58(), newOwner.publicKey.toBase58());
+    });
+
+    it("Fails to sync with a substituted mint", async () => {
+      const badMint = await createMalformedMint(0, 2);
+
+      try {
+        await program.methods
+          .syncOwner(false)
+          .accounts({
+            agentAccount: agentPda,
+            tokenAccount: originalOwnerTokenAccount,
+            agentMetadata: getMetadataPda(badMint),
+            agentMint: badMint,
+            oldOwnerSigner: provider.wallet.publicKey,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .rpc();
+
+        assert.fail("Should have failed with InvalidTokenAccount error");
+      } catch (error) {
+        assert.include(error.message, "InvalidTokenAccount");
+      }
+    });
+
+    it("Allows new owner to update metadata after transfer", async () => {
+      // Create ATA and transfer
+      const { createAssociatedTokenAccountInstruction } = await import("@solana/spl-token");
+
+      const createAtaTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          provider.wallet.publicKey,
+          newOwnerTokenAccount,
+          newOwner.publicKey,
+          agentMint.publicKey
+        )
+      );
+      await provider.sendAndConfirm(createAtaTx);
+
+      await transfer(
+        provider.connection,
+        provider.wallet.payer,
+        originalOwnerTokenAccount,
+        newOwnerTokenAccount,
+        provider.wallet.publicKey,
+        1
+      );
+
+      // Sync owner (old owner must sign to transfer update_authority)
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+
+      await program.methods
+        .syncOwner(false)
+        .accounts({
+          agentAccount: agentPda,
+          tokenAccount: newOwnerTokenAccount,
+          agentMetadata,
+          agentMint: agentMint.publicKey,
+          oldOwnerSigner: provider.wallet.publicKey,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .rpc();
+
+      // New owner sets metadata
+      await program.methods
+        .setMetadata("newKey", Buffer.from("newValue"))
+        .accounts({
+          agentAccount: agentPda,
+          owner: newOwner.publicKey,
+        })
+        .signers([newOwner])
+        .rpc();
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.metadata.length, 1);
+      assert.equal(agent.metadata[0].key, "newKey");
+    });
+
+    it("Prevents old owner from updating metadata after transfer", async () => {
+      // Create ATA and transfer
+      const { createAssociatedTokenAccountInstruction } = await import("@solana/spl-token");
+
+      const createAtaTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          provider.wallet.publicKey,
+          newOwnerTokenAccount,
+          newOwner.publicKey,
+          agentMint.publicKey
+        )
+      );
+      await provider.sendAndConfirm(createAtaTx);
+
+      await transfer(
+        provider.connection,
+        provider.wallet.payer,
+        originalOwnerTokenAccount,
+        newOwnerTokenAccount,
+        provider.wallet.publicKey,
+        1
+      );
+
+      // Sync owner (old owner must sign to transfer update_authority)
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+
+      await program.methods
+        .syncOwner(false)
+        .accounts({
+          agentAccount: agentPda,
+          tokenAccount: newOwnerTokenAccount,
+          agentMetadata,
+          agentMint: agentMint.publicKey,
+          oldOwnerSigner: provider.wallet.publicKey,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .rpc();
+
+      // Old owner tries to set metadata
+      try {
+        await program.methods
+          .setMetadata("hack", Buffer.from("value"))
+          .accounts({
+            agentAccount: agentPda,
+            owner: provider.wallet.publicKey,
+          })
+          .rpc();
+
+        assert.fail("Should have failed with Unauthorized error");
+      } catch (error) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+
+    it("Fails to sync with invalid token account (amount = 0)", async () => {
+      // Create empty token account
+      const { createAssociatedTokenAccountInstruction } = await import("@solana/spl-token");
+
+      const emptyAccount = getAssociatedTokenAddressSync(agentMint.publicKey, newOwner.publicKey);
+      const createAtaTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          provider.wallet.publicKey,
+          emptyAccount,
+          newOwner.publicKey,
+          agentMint.publicKey
+        )
+      );
+      await provider.sendAndConfirm(createAtaTx);
+
+      try {
+        const agentMetadata = getMetadataPda(agentMint.publicKey);
+
+        await program.methods
+          .syncOwner(false)
+          .accounts({
+            agentAccount: agentPda,
+            tokenAccount: emptyAccount,
+            agentMetadata,
+            agentMint: agentMint.publicKey,
+            oldOwnerSigner: provider.wallet.publicKey,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .rpc();
+
+        assert.fail("Should have failed with InvalidTokenAccount error");
+      } catch (error) {
+        assert.include(error.message, "InvalidTokenAccount");
+      }
+    });
+
+    it("Strict set_metadata rejects a cached owner whose ATA was closed", async () => {
+      const { createAssociatedTokenAccountInstruction, closeAccount } = await import("@solana/spl-token");
+
+      const createAtaTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          provider.wallet.publicKey,
+          newOwnerTokenAccount,
+          newOwner.publicKey,
+          agentMint.publicKey
+        )
+      );
+      await provider.sendAndConfirm(createAtaTx);
+
+      // Plain SPL transfer without sync_owner, then close the emptied ATA
+      await transfer(
+        provider.connection,
+        provider.wallet.payer,
+        originalOwnerTokenAccount,
+        newOwnerTokenAccount,
+        provider.wallet.publicKey,
+        1
+      );
+      await closeAccount(
+        provider.connection,
+        provider.wallet.payer,
+        originalOwnerTokenAccount,
+        provider.wallet.publicKey,
+        provider.wallet.publicKey
+      );
+
+      // Recreate the cached owner's ATA (still empty)
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+      await program.methods
+        .reopenAgentTokenAccount()
+        .accounts({
+          agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          ownerTokenAccount: originalOwnerTokenAccount,
+          holderTokenAccount: null,
+          oldOwnerAgents: null,
+          newOwnerAgents: null,
+          agentMetadata,
+          owner: provider.wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .rpc();
+
+      try {
+        await program.methods
+          .setMetadata("stale", Buffer.from("value"))
+          .accounts({
+            agentAccount: agentPda,
+            owner: provider.wallet.publicKey,
+            ownerTokenAccount: originalOwnerTokenAccount,
+          })
+          .rpc();
+
+        assert.fail("Should have failed with StaleOwner error");
+      } catch (error) {
+        assert.include(error.message, "StaleOwner");
+      }
+    });
+
+    it("Reopens the owner ATA and re-syncs to the actual holder", async () => {
+      const { createAssociatedTokenAccountInstruction, closeAccount } = await import("@solana/spl-token");
+
+      const createAtaTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          provider.wallet.publicKey,
+          newOwnerTokenAccount,
+          newOwner.publicKey,
+          agentMint.publicKey
+        )
+      );
+      await provider.sendAndConfirm(createAtaTx);
+
+      await transfer(
+        provider.connection,
+        provider.wallet.payer,
+        originalOwnerTokenAccount,
+        newOwnerTokenAccount,
+        provider.wallet.publicKey,
+        1
+      );
+      await closeAccount(
+        provider.connection,
+        provider.wallet.payer,
+        originalOwnerTokenAccount,
+        provider.wallet.publicKey,
+        provider.wallet.publicKey
+      );
+
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+      const before = await program.account.agentAccount.fetch(agentPda);
+
+      // Let the cluster clock advance at least one second
+      await new Promise((resolve) => setTimeout(resolve, 1500));
+
+      await program.methods
+        .reopenAgentTokenAccount()
+        .accounts({
+          agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          ownerTokenAccount: originalOwnerTokenAccount,
+          holderTokenAccount: newOwnerTokenAccount,
+          oldOwnerAgents: getOwnerAgentsPda(provider.wallet.publicKey),
+          newOwnerAgents: getOwnerAgentsPda(newOwner.publicKey),
+          agentMetadata,
+          owner: provider.wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .rpc();
+
+      const ata = await getAccount(provider.connection, originalOwnerTokenAccount);
+      assert.equal(Number(ata.amount), 0);
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.owner.toBase58(), newOwner.publicKey.toBase58());
+      assert.isAbove(agent.updatedAt.toNumber(), before.updatedAt.toNumber());
+
+      // The agent moved to the holder's list
+      const holderList = await program.account.ownerAgentList.fetch(getOwnerAgentsPda(newOwner.publicKey));
+      assert.deepEqual(holderList.agentIds.map((id) => id.toNumber()), [agent.agentId.toNumber()]);
+      const oldList = await program.account.ownerAgentList.fetch(
+        getOwnerAgentsPda(provider.wallet.publicKey)
+      );
+      assert.notInclude(oldList.agentIds.map((id) => id.toNumber()), agent.agentId.toNumber());
+
+      // Strict mode passes for the re-synced owner
+      await program.methods
+        .setMetadata("resynced", Buffer.from("yes"))
+        .accounts({
+          agentAccount: agentPda,
+          owner: newOwner.publicKey,
+          ownerTokenAccount: newOwnerTokenAccount,
+        })
+        .signers([newOwner])
+        .rpc();
+    });
+  });
+
+  describe("Sync Owners Batch (Airdrop / Migration)", () => {
+    async function registerAgent(): Promise<{ mint: Keypair; pda: PublicKey; ata: PublicKey }> {
+      const mint = Keypair.generate();
+      const ata = getAssociatedTokenAddressSync(mint.publicKey, provider.wallet.publicKey);
+      const [pda] = getAgentPda(mint.publicKey);
+
+      const registerIx = await program.methods
+        .register("https://example.com/batch")
+        .accounts({
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          agentAccount: pda,
+          agentMint: mint.publicKey,
+          agentMetadata: getMetadataPda(mint.publicKey),
+          agentMasterEdition: getMasterEditionPda(mint.publicKey),
+          agentTokenAccount: ata,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .instruction();
+
+      await sendWithComputeBudget(registerIx, [mint]);
+      return { mint, pda, ata };
+    }
+
+    it("Syncs several airdropped agents at once and skips non-holders", async () => {
+      const { getOrCreateAssociatedTokenAccount } = await import("@solana/spl-token");
+
+      const agents = [await registerAgent(), await registerAgent(), await registerAgent()];
+      const recipients = agents.map(() => Keypair.generate());
+      const holderAccounts: PublicKey[] = [];
+
+      // Airdrop the first two agents, keep the third with the distributor
+      for (let i = 0; i < agents.length; i++) {
+        const recipientAta = await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          provider.wallet.payer,
+          agents[i].mint.publicKey,
+          recipients[i].publicKey
+        );
+        if (i < 2) {
+          await transfer(
+            provider.connection,
+            provider.wallet.payer,
+            agents[i].ata,
+            recipientAta.address,
+            provider.wallet.publicKey,
+            1
+          );
+        }
+        holderAccounts.push(recipientAta.address);
+      }
+
+      const remainingAccounts = agents.flatMap((agent, i) => [
+        { pubkey: agent.pda, isWritable: true, isSigner: false },
+        { pubkey: holderAccounts[i], isWritable: false, isSigner: false },
+        { pubkey: agent.mint.publicKey, isWritable: false, isSigner: false },
+        { pubkey: getMetadataPda(agent.mint.publicKey), isWritable: true, isSigner: false },
+        { pubkey: getOwnerAgentsPda(recipients[i].publicKey), isWritable: true, isSigner: false },
+      ]);
+
+      const ix = await program.methods
+        .syncOwnersBatch()
+        .accounts({
+          oldOwnerSigner: provider.wallet.publicKey,
+          oldOwnerAgents: getOwnerAgentsPda(provider.wallet.publicKey),
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .remainingAccounts(remainingAccounts)
+        .instruction();
+
+      // Let the cluster clock advance at least one second
+      await new Promise((resolve) => setTimeout(resolve, 1500));
+
+      await sendWithComputeBudget(ix, [], 1_000_000);
+
+      const distributorList = await program.account.ownerAgentList.fetch(
+        getOwnerAgentsPda(provider.wallet.publicKey)
+      );
+      const distributorIds = distributorList.agentIds.map((id) => id.toNumber());
+      for (let i = 0; i < 2; i++) {
+        const agent = await program.account.agentAccount.fetch(agents[i].pda);
+        assert.equal(agent.owner.toBase58(), recipients[i].publicKey.toBase58());
+        assert.isAbove(agent.updatedAt.toNumber(), agent.createdAt.toNumber());
+
+        // "My agents" follows the airdrop
+        const recipientList = await program.account.ownerAgentList.fetch(
+          getOwnerAgentsPda(recipients[i].publicKey)
+        );
+        assert.deepEqual(recipientList.agentIds.map((id) => id.toNumber()), [agent.agentId.toNumber()]);
+        assert.notInclude(distributorIds, agent.agentId.toNumber());
+      }
+
+      // Third agent's holder account is empty: skipped, owner unchanged
+      const skipped = await program.account.agentAccount.fetch(agents[2].pda);
+      assert.equal(skipped.owner.toBase58(), provider.wallet.publicKey.toBase58());
+      assert.equal(skipped.updatedAt.toNumber(), skipped.createdAt.toNumber());
+      assert.include(distributorIds, skipped.agentId.toNumber());
+    });
+  });
+
+  describe("Owner Of (ERC-721: ownerOf)", () => {
+    let agentMint: Keypair;
+    let agentPda: PublicKey;
+
+    beforeEach(async () => {
+      // Register an agent
+      agentMint = Keypair.generate();
+      [agentPda] = getAgentPda(agentMint.publicKey);
+
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+      const agentMasterEdition = getMasterEditionPda(agentMint.publicKey);
+      const agentTokenAccount = getAssociatedTokenAddressSync(
+        agentMint.publicKey,
+        provider.wallet.publicKey
+      );
+
+      await program.methods
+        .register("https://example.com/agent.json")
+        .accounts({
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          agentMetadata,
+          agentMasterEdition,
+          agentTokenAccount,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([agentMint])
+        .rpc();
+    });
+
+    it("Returns current owner of agent", async () => {
+      const owner = await program.methods
+        .ownerOf()
+        .accounts({
+          agentAccount: agentPda,
+        })
+        .view();
+
+      assert.equal(owner.toBase58(), provider.wallet.publicKey.toBase58());
+    });
+
+    it("Returns updated owner after transfer", async () => {
+      // Create new owner
+      const newOwner = Keypair.generate();
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(newOwner.publicKey, 1000000000)
+      );
+
+      const originalOwnerTokenAccount = getAssociatedTokenAddressSync(
+        agentMint.publicKey,
+        provider.wallet.publicKey
+      );
+      const newOwnerTokenAccount = getAssociatedTokenAddressSync(
+        agentMint.publicKey,
+        newOwner.publicKey
+      );
+
+      // Create new owner's token account
+      const { createAssociatedTokenAccountInstruction } = await import("@solana/spl-token");
+      const createAtaTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          provider.wallet.publicKey,
+          newOwnerTokenAccount,
+          newOwner.publicKey,
+          agentMint.publicKey
+        )
+      );
+      await provider.sendAndConfirm(createAtaTx);
+
+      // Transfer NFT
+      await transfer(
+        provider.connection,
+        provider.wallet.payer,
+        originalOwnerTokenAccount,
+        newOwnerTokenAccount,
+        provider.wallet.publicKey,
+        1
+      );
+
+      // Sync owner
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+      await program.methods
+        .syncOwner(false)
+        .accounts({
+          agentAccount: agentPda,
+          tokenAccount: newOwnerTokenAccount,
+          agentMetadata,
+          agentMint: agentMint.publicKey,
+          oldOwnerSigner: provider.wallet.publicKey,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .rpc();
+
+      // Check owner updated
+      const owner = await program.methods
+        .ownerOf()
+        .accounts({
+          agentAccount: agentPda,
+        })
+        .view();
+
+      assert.equal(owner.toBase58(), newOwner.publicKey.toBase58());
+    });
+  });
+
+  describe("Sync Agent Counts (Cached Feedback / Validation Counts)", () => {
+    const REPUTATION_REGISTRY_ID = new PublicKey("9WcFLL3Fsqs96JxuewEt9iqRwULtCZEsPT717hPbsQAa");
+    const VALIDATION_REGISTRY_ID = anchor.workspace.ValidationRegistry.programId as PublicKey;
+    let agentMint: Keypair;
+    let agentPda: PublicKey;
+
+    before(async () => {
+      // The config defaults to the devnet Validation Registry
+      await program.methods
+        .setValidationRegistry(VALIDATION_REGISTRY_ID)
+        .accounts({ config: configPda, authority: provider.wallet.publicKey })
+        .rpc();
+
+      const config = await program.account.registryConfig.fetch(configPda);
+      assert.ok(config.validationRegistry.equals(VALIDATION_REGISTRY_ID));
+    });
+
+    beforeEach(async () => {
+      agentMint = Keypair.generate();
+      [agentPda] = getAgentPda(agentMint.publicKey);
+
+      await program.methods
+        .register("https://example.com/agent.json")
+        .accounts({
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          agentMetadata: getMetadataPda(agentMint.publicKey),
+          agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
+          agentTokenAccount: getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey),
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([agentMint])
+        .rpc();
+    });
+
+    function getAgentReputationPda(agentId: anchor.BN): PublicKey {
+      return PublicKey.findProgramAddressSync(
+        [Buffer.from("agent_reputation"), agentId.toArrayLike(Buffer, "le", 8)],
+        REPUTATION_REGISTRY_ID
+      )[0];
+    }
+
+    function getAgentValidationPda(agentId: anchor.BN): PublicKey {
+      return PublicKey.findProgramAddressSync(
+        [Buffer.from("agent_validation"), agentId.toArrayLike(Buffer, "le", 8)],
+        VALIDATION_REGISTRY_ID
+      )[0];
+    }
+
+    it("Syncs zero counts for an agent without feedback or validations", async () => {
+      const agent = await program.account.agentAccount.fetch(agentPda);
+
+      await program.methods
+        .syncAgentCounts()
+        .accounts({
+          agentAccount: agentPda,
+          agentReputation: getAgentReputationPda(agent.agentId),
+          agentValidation: getAgentValidationPda(agent.agentId),
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      const synced = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(synced.cachedFeedbackCount.toNumber(), 0);
+      assert.equal(synced.cachedValidationCount.toNumber(), 0);
+    });
+
+    it("Rejects a validation count source that is not the agent's rollup PDA", async () => {
+      const agent = await program.account.agentAccount.fetch(agentPda);
+
+      try {
+        await program.methods
+          .syncAgentCounts()
+          .accounts({
+            agentAccount: agentPda,
+            agentReputation: getAgentReputationPda(agent.agentId),
+            agentValidation: agentPda,
+            owner: provider.wallet.publicKey,
+          })
+          .rpc();
+        assert.fail("Should have failed with ConstraintSeeds error");
+      } catch (error) {
+        assert.include(error.message, "ConstraintSeeds");
+      }
+    });
+  });
+
+  describe("Views (get_agent / get_config)", () => {
+    it("Returns versioned agent and config data", async () => {
+      const agentMint = Keypair.generate();
+      const [agentPda] = getAgentPda(agentMint.publicKey);
+
+      await program.methods
+        .register("https://example.com/view.json")
+        .accounts({
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          agentMetadata: getMetadataPda(agentMint.publicKey),
+          agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
+          agentTokenAccount: getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey),
           collectionMint: collectionMint.publicKey,
           collectionMetadata,
           collectionMasterEdition,
@@ -971,158 +3514,1203 @@ describe("Identity Registry (ERC-8004 Spec Compliant)", () => {
           sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
         })
         .signers([agentMint])
+        .rpc();
+
+      // .view() simulates the instruction and decodes its return data
+      const agent = await program.methods
+        .getAgent()
+        .accounts({ agentAccount: agentPda })
+        .view();
+
+      assert.equal(agent.version, 1);
+      assert.equal(agent.owner.toBase58(), provider.wallet.publicKey.toBase58());
+      assert.equal(agent.agentMint.toBase58(), agentMint.publicKey.toBase58());
+      assert.equal(agent.tokenUri, "https://example.com/view.json");
+      assert.equal(agent.metadataCount, 0);
+
+      const config = await program.methods
+        .getConfig()
+        .accounts({ config: configPda })
+        .view();
+      const stored = await program.account.registryConfig.fetch(configPda);
+
+      assert.equal(config.version, 1);
+      assert.equal(config.totalAgents.toNumber(), stored.totalAgents.toNumber());
+      assert.equal(config.collectionMint.toBase58(), collectionMint.publicKey.toBase58());
+    });
+  });
+
+  describe("Registry Stats (Daily Registrations)", () => {
+    const [registryStatsPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("registry_stats")],
+      program.programId
+    );
+
+    const dailyRegistrations = async (dayEpoch: number): Promise<number> =>
+      (
+        await program.methods
+          .getDailyRegistrations(new anchor.BN(dayEpoch))
+          .accounts({ registryStats: registryStatsPda })
+          .view()
+      ).toNumber();
+
+    it("Counts registrations per UTC day", async () => {
+      // Any earlier test registration has created the stats account
+      const before = await program.account.registryStats.fetch(registryStatsPda);
+      const dayBefore = Math.floor(before.lastRegistrationTs.toNumber() / 86400);
+      const countBefore = await dailyRegistrations(dayBefore);
+      assert.isAtLeast(countBefore, 1);
+
+      const agentMint = Keypair.generate();
+      const registerIx = await program.methods
+        .register("https://example.com/stats.json")
+        .accounts({
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          agentAccount: getAgentPda(agentMint.publicKey)[0],
+          agentMint: agentMint.publicKey,
+          agentMetadata: getMetadataPda(agentMint.publicKey),
+          agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
+          agentTokenAccount: getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey),
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .instruction();
+      await sendWithComputeBudget(registerIx, [agentMint]);
+
+      const after = await program.account.registryStats.fetch(registryStatsPda);
+      const agent = await program.account.agentAccount.fetch(getAgentPda(agentMint.publicKey)[0]);
+      assert.equal(after.lastRegistrationTs.toNumber(), agent.createdAt.toNumber());
+
+      const day = Math.floor(after.lastRegistrationTs.toNumber() / 86400);
+      // Same day unless the registration crossed UTC midnight
+      assert.equal(await dailyRegistrations(day), day === dayBefore ? countBefore + 1 : 1);
+
+      // Days that fell out of the 30-day ring report nothing
+      assert.equal(await dailyRegistrations(day - 30), 0);
+    });
+  });
+
+  describe("Get Collection (Collection NFT Details)", () => {
+    it("Returns the collection mint, name and URI", async () => {
+      const collection = await program.methods
+        .getCollection()
+        .accounts({
+          config: configPda,
+          collectionMetadata,
+        })
+        .view();
+
+      assert.equal(collection.collectionMint.toBase58(), collectionMint.publicKey.toBase58());
+      assert.equal(collection.name, "ERC-8004 Agent Registry");
+      assert.equal(collection.uri, "https://erc8004.org/collection.json");
+    });
+
+    it("Rejects metadata that is not the registry collection's", async () => {
+      const otherMint = Keypair.generate();
+
+      try {
+        await program.methods
+          .getCollection()
+          .accounts({
+            config: configPda,
+            collectionMetadata: getMetadataPda(otherMint.publicKey),
+          })
+          .view();
+        assert.fail("Should have failed with InvalidCollectionMetadata error");
+      } catch (error) {
+        assert.include(error.message, "InvalidCollectionMetadata");
+      }
+    });
+
+    it("Authority updates the collection URI", async () => {
+      const updateCollection = (name: string | null, uri: string | null, signer?: Keypair) =>
+        program.methods
+          .updateCollectionMetadata(name, uri)
+          .accounts({
+            config: configPda,
+            authority: signer ? signer.publicKey : provider.wallet.publicKey,
+            collectionMint: collectionMint.publicKey,
+            collectionMetadata,
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+            systemProgram: SystemProgram.programId,
+            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          })
+          .signers(signer ? [signer] : [])
+          .rpc();
+      const readCollection = () =>
+        program.methods
+          .getCollection()
+          .accounts({ config: configPda, collectionMetadata })
+          .view();
+
+      await updateCollection(null, "ipfs://QmOperatorBranding");
+      try {
+        const collection = await readCollection();
+        assert.equal(collection.uri, "ipfs://QmOperatorBranding");
+        // Name left untouched
+        assert.equal(collection.name, "ERC-8004 Agent Registry");
+
+        try {
+          await updateCollection(null, "https://" + "a".repeat(193));
+          assert.fail("Should have failed with UriTooLong error");
+        } catch (error) {
+          assert.include(error.message, "UriTooLong");
+        }
+
+        const attacker = Keypair.generate();
+        try {
+          await updateCollection("Hijacked", null, attacker);
+          assert.fail("Should have failed with Unauthorized error");
+        } catch (error) {
+          assert.include(error.message, "Unauthorized");
+        }
+      } finally {
+        // Restore the original URI for the remaining tests
+        await updateCollection(null, "https://erc8004.org/collection.json");
+      }
+
+      const restored = await readCollection();
+      assert.equal(restored.uri, "https://erc8004.org/collection.json");
+    });
+  });
+
+  describe("Metadata Extensions (Beyond 10 entries)", () => {
+    let agentMint: Keypair;
+    let agentPda: PublicKey;
+
+    // Helper to derive metadata extension PDA
+    function getMetadataExtensionPda(agentMint: PublicKey, extensionIndex: number): [PublicKey, number] {
+      return PublicKey.findProgramAddressSync(
+        [Buffer.from("metadata_ext"), agentMint.toBuffer(), Buffer.from([extensionIndex])],
+        program.programId
+      );
+    }
+
+    beforeEach(async () => {
+      // Register an agent
+      agentMint = Keypair.generate();
+      [agentPda] = getAgentPda(agentMint.publicKey);
+
+      const agentMetadata = getMetadataPda(agentMint.publicKey);
+      const agentMasterEdition = getMasterEditionPda(agentMint.publicKey);
+      const agentTokenAccount = getAssociatedTokenAddressSync(
+        agentMint.publicKey,
+        provider.wallet.publicKey
+      );
+
+      const registerIx = await program.methods
+        .register("https://example.com/agent.json")
+        .accounts({
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          agentMetadata,
+          agentMasterEdition,
+          agentTokenAccount,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
         .instruction();
 
-      const computeBudgetIx = ComputeBudgetProgram.setComputeUnitLimit({
-        units: 400_000,
+      await sendWithComputeBudget(registerIx, [agentMint]);
+
+      // Fill up the base 10 metadata slots
+      for (let i = 0; i < 10; i++) {
+        await program.methods
+          .setMetadata(`key${i}`, Buffer.from(`value${i}`))
+          .accounts({
+            agentAccount: agentPda,
+            owner: provider.wallet.publicKey,
+          })
+          .rpc();
+      }
+    });
+
+    it("Creates metadata extension PDA", async () => {
+      const [metadataExtensionPda] = getMetadataExtensionPda(agentMint.publicKey, 0);
+
+      await program.methods
+        .createMetadataExtension(0)
+        .accounts({
+          metadataExtension: metadataExtensionPda,
+          agentMint: agentMint.publicKey,
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const extension = await program.account.metadataExtension.fetch(metadataExtensionPda);
+      assert.equal(extension.agentMint.toBase58(), agentMint.publicKey.toBase58());
+      assert.equal(extension.extensionIndex, 0);
+      assert.equal(extension.metadata.length, 0);
+    });
+
+    it("Sets metadata in extension PDA", async () => {
+      const [metadataExtensionPda] = getMetadataExtensionPda(agentMint.publicKey, 0);
+
+      // Create extension
+      await program.methods
+        .createMetadataExtension(0)
+        .accounts({
+          metadataExtension: metadataExtensionPda,
+          agentMint: agentMint.publicKey,
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      // Set metadata in extension
+      await program.methods
+        .setMetadataExtended(0, "extKey1", Buffer.from("extValue1"))
+        .accounts({
+          metadataExtension: metadataExtensionPda,
+          agentMint: agentMint.publicKey,
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      const extension = await program.account.metadataExtension.fetch(metadataExtensionPda);
+      assert.equal(extension.metadata.length, 1);
+      assert.equal(extension.metadata[0].key, "extKey1");
+      assert.equal(Buffer.from(extension.metadata[0].value).toString(), "extValue1");
+    });
+
+    it("Gets metadata from extension PDA", async () => {
+      const [metadataExtensionPda] = getMetadataExtensionPda(agentMint.publicKey, 0);
+
+      // Create extension
+      await program.methods
+        .createMetadataExtension(0)
+        .accounts({
+          metadataExtension: metadataExtensionPda,
+          agentMint: agentMint.publicKey,
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      // Set metadata
+      await program.methods
+        .setMetadataExtended(0, "testKey", Buffer.from("testValue"))
+        .accounts({
+          metadataExtension: metadataExtensionPda,
+          agentMint: agentMint.publicKey,
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      // Get metadata
+      const value = await program.methods
+        .getMetadataExtended(0, "testKey")
+        .accounts({
+          metadataExtension: metadataExtensionPda,
+          agentMint: agentMint.publicKey,
+        })
+        .view();
+
+      assert.equal(Buffer.from(value).toString(), "testValue");
+
+      const entries = await program.methods
+        .getAllMetadataExtended(0)
+        .accounts({
+          metadataExtension: metadataExtensionPda,
+          agentMint: agentMint.publicKey,
+        })
+        .view();
+
+      assert.equal(entries.length, 1);
+      assert.equal(entries[0].key, "testKey");
+    });
+
+    it("Returns empty bytes for non-existent key in extension", async () => {
+      const [metadataExtensionPda] = getMetadataExtensionPda(agentMint.publicKey, 0);
+
+      // Create extension
+      await program.methods
+        .createMetadataExtension(0)
+        .accounts({
+          metadataExtension: metadataExtensionPda,
+          agentMint: agentMint.publicKey,
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      // Get non-existent key
+      const value = await program.methods
+        .getMetadataExtended(0, "nonExistent")
+        .accounts({
+          metadataExtension: metadataExtensionPda,
+          agentMint: agentMint.publicKey,
+        })
+        .view();
+
+      assert.equal(value.length, 0);
+    });
+
+    it("Enforces 10 metadata entry limit per extension", async () => {
+      const [metadataExtensionPda] = getMetadataExtensionPda(agentMint.publicKey, 0);
+
+      // Create extension
+      await program.methods
+        .createMetadataExtension(0)
+        .accounts({
+          metadataExtension: metadataExtensionPda,
+          agentMint: agentMint.publicKey,
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      // Add 10 entries
+      for (let i = 0; i < 10; i++) {
+        await program.methods
+          .setMetadataExtended(0, `extKey${i}`, Buffer.from(`extValue${i}`))
+          .accounts({
+            metadataExtension: metadataExtensionPda,
+            agentMint: agentMint.publicKey,
+            agentAccount: agentPda,
+            owner: provider.wallet.publicKey,
+          })
+          .rpc();
+      }
+
+      // Try to add 11th entry
+      try {
+        await program.methods
+          .setMetadataExtended(0, "extKey11", Buffer.from("extValue11"))
+          .accounts({
+            metadataExtension: metadataExtensionPda,
+            agentMint: agentMint.publicKey,
+            agentAccount: agentPda,
+            owner: provider.wallet.publicKey,
+          })
+          .rpc();
+
+        assert.fail("Should have failed with MetadataLimitReached error");
+      } catch (error) {
+        assert.include(error.message, "MetadataLimitReached");
+      }
+    });
+
+    it("Removes an entry from an extension PDA", async () => {
+      const [metadataExtensionPda] = getMetadataExtensionPda(agentMint.publicKey, 0);
+
+      await program.methods
+        .createMetadataExtension(0)
+        .accounts({
+          metadataExtension: metadataExtensionPda,
+          agentMint: agentMint.publicKey,
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      await program.methods
+        .setMetadataExtended(0, "extKey", Buffer.from("extValue"))
+        .accounts({
+          metadataExtension: metadataExtensionPda,
+          agentMint: agentMint.publicKey,
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      const signature = await program.methods
+        .removeMetadataExtended(0, "extKey")
+        .accounts({
+          metadataExtension: metadataExtensionPda,
+          agentMint: agentMint.publicKey,
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const extension = await program.account.metadataExtension.fetch(metadataExtensionPda);
+      assert.equal(extension.metadata.length, 0);
+
+      // The event says which extension the entry left
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+      const removed = [...parser.parseLogs(tx.meta.logMessages)].find(
+        (e) => e.name === "metadataRemoved"
+      );
+      assert.isDefined(removed, "MetadataRemoved should be emitted");
+      assert.equal(removed.data.key, "extKey");
+      assert.equal(removed.data.extensionIndex, 0);
+
+      try {
+        await program.methods
+          .removeMetadataExtended(0, "extKey")
+          .accounts({
+            metadataExtension: metadataExtensionPda,
+            agentMint: agentMint.publicKey,
+            agentAccount: agentPda,
+            owner: provider.wallet.publicKey,
+          })
+          .rpc();
+
+        assert.fail("Should have failed with MetadataNotFound error");
+      } catch (error) {
+        assert.include(error.message, "MetadataNotFound");
+      }
+    });
+
+    it("Allows multiple extension PDAs (unlimited metadata)", async () => {
+      // Create extension 0
+      const [ext0Pda] = getMetadataExtensionPda(agentMint.publicKey, 0);
+      await program.methods
+        .createMetadataExtension(0)
+        .accounts({
+          metadataExtension: ext0Pda,
+          agentMint: agentMint.publicKey,
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      // Create extension 1
+      const [ext1Pda] = getMetadataExtensionPda(agentMint.publicKey, 1);
+      await program.methods
+        .createMetadataExtension(1)
+        .accounts({
+          metadataExtension: ext1Pda,
+          agentMint: agentMint.publicKey,
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      // Add metadata to both
+      await program.methods
+        .setMetadataExtended(0, "ext0Key", Buffer.from("ext0Value"))
+        .accounts({
+          metadataExtension: ext0Pda,
+          agentMint: agentMint.publicKey,
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      await program.methods
+        .setMetadataExtended(1, "ext1Key", Buffer.from("ext1Value"))
+        .accounts({
+          metadataExtension: ext1Pda,
+          agentMint: agentMint.publicKey,
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+        })
+        .rpc();
+
+      // Verify both extensions
+      const ext0 = await program.account.metadataExtension.fetch(ext0Pda);
+      const ext1 = await program.account.metadataExtension.fetch(ext1Pda);
+
+      assert.equal(ext0.metadata[0].key, "ext0Key");
+      assert.equal(ext1.metadata[0].key, "ext1Key");
+    });
+
+    it("Tracks extension_count and rejects indices at the per-agent cap", async () => {
+      const createExtension = (index: number) =>
+        program.methods
+          .createMetadataExtension(index)
+          .accounts({
+            metadataExtension: getMetadataExtensionPda(agentMint.publicKey, index)[0],
+            agentMint: agentMint.publicKey,
+            agentAccount: agentPda,
+            owner: provider.wallet.publicKey,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+
+      // Highest valid index (cap is 16)
+      await createExtension(15);
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.extensionCount, 1);
+
+      try {
+        await createExtension(16);
+        assert.fail("Should have failed with InvalidExtensionIndex error");
+      } catch (error) {
+        assert.include(error.message, "InvalidExtensionIndex");
+      }
+    });
+
+    it("Emits MetadataExtensionCreated and stores the agent_id", async () => {
+      const [metadataExtensionPda] = getMetadataExtensionPda(agentMint.publicKey, 3);
+
+      const signature = await program.methods
+        .createMetadataExtension(3)
+        .accounts({
+          metadataExtension: metadataExtensionPda,
+          agentMint: agentMint.publicKey,
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
       });
+      const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+      const events = [...parser.parseLogs(tx.meta.logMessages)];
+      const created = events.find((e) => e.name === "metadataExtensionCreated");
+
+      assert.isDefined(created, "MetadataExtensionCreated should be emitted");
+      assert.equal(created.data.extensionIndex, 3);
+      assert.equal(created.data.agentMint.toBase58(), agentMint.publicKey.toBase58());
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      const extension = await program.account.metadataExtension.fetch(metadataExtensionPda);
+      assert.equal(extension.agentId.toNumber(), agent.agentId.toNumber());
+    });
+
+    it("Closes an extension, returns its rent and allows re-creating the index", async () => {
+      const extensionAccounts = (index: number) => ({
+        metadataExtension: getMetadataExtensionPda(agentMint.publicKey, index)[0],
+        agentMint: agentMint.publicKey,
+        agentAccount: agentPda,
+        owner: provider.wallet.publicKey,
+        systemProgram: SystemProgram.programId,
+      });
+
+      for (const index of [0, 1]) {
+        await program.methods
+          .createMetadataExtension(index)
+          .accounts(extensionAccounts(index))
+          .rpc();
+      }
+      await program.methods
+        .setMetadataExtended(0, "kept", Buffer.from("still here"))
+        .accounts(extensionAccounts(0))
+        .rpc();
+
+      const [ext1Pda] = getMetadataExtensionPda(agentMint.publicKey, 1);
+      const rent = await provider.connection.getBalance(ext1Pda);
+      const balanceBefore = await provider.connection.getBalance(provider.wallet.publicKey);
+
+      await program.methods
+        .closeMetadataExtension(1)
+        .accounts(extensionAccounts(1))
+        .rpc();
+
+      assert.isNull(await provider.connection.getAccountInfo(ext1Pda));
+      const balanceAfter = await provider.connection.getBalance(provider.wallet.publicKey);
+      // Rent returned, minus the transaction fee
+      assert.isAbove(balanceAfter - balanceBefore, rent - 10_000);
+
+      // Extension 0 is untouched
+      const ext0 = await program.account.metadataExtension.fetch(
+        getMetadataExtensionPda(agentMint.publicKey, 0)[0]
+      );
+      assert.equal(Buffer.from(ext0.metadata[0].value).toString(), "still here");
+
+      let agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.extensionCount, 1);
+
+      // Same index can be created again
+      await program.methods
+        .createMetadataExtension(1)
+        .accounts(extensionAccounts(1))
+        .rpc();
+      agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.extensionCount, 2);
+    });
+  });
+
+  describe("Resize Agent Metadata (Grow Base Account)", () => {
+    let agentPda: PublicKey;
+
+    before(async () => {
+      const agentMint = Keypair.generate();
+      [agentPda] = getAgentPda(agentMint.publicKey);
+
+      const registerIx = await program.methods
+        .register("https://resized.com")
+        .accounts({
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          agentMetadata: getMetadataPda(agentMint.publicKey),
+          agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
+          agentTokenAccount: getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey),
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .instruction();
+
+      await sendWithComputeBudget(registerIx, [agentMint]);
+
+      for (let i = 0; i < 10; i++) {
+        await program.methods
+          .setMetadata(`key${i}`, Buffer.from(`value${i}`))
+          .accounts({ agentAccount: agentPda, owner: provider.wallet.publicKey })
+          .rpc();
+      }
+    });
+
+    it("Grows the base account to 20 entries and sets entry 11", async () => {
+      const before = await provider.connection.getAccountInfo(agentPda);
+
+      try {
+        await program.methods
+          .setMetadata("key10", Buffer.from("value10"))
+          .accounts({ agentAccount: agentPda, owner: provider.wallet.publicKey })
+          .rpc();
+        assert.fail("Should have failed with MetadataLimitReached error");
+      } catch (error) {
+        assert.include(error.message, "MetadataLimitReached");
+      }
+
+      await program.methods
+        .resizeAgentMetadata(20)
+        .accounts({
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const after = await provider.connection.getAccountInfo(agentPda);
+      assert.equal(after.data.length, before.data.length + 10 * 296);
+
+      await program.methods
+        .setMetadata("key10", Buffer.from("value10"))
+        .accounts({ agentAccount: agentPda, owner: provider.wallet.publicKey })
+        .rpc();
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.metadata.length, 11);
+      assert.equal(agent.metadata[10].key, "key10");
+      assert.equal(Buffer.from(agent.metadata[10].value).toString(), "value10");
+    });
+
+    it("Refuses to shrink below the existing entries or outside the bounds", async () => {
+      for (const [capacity, expected] of [
+        [10, "MetadataCapacityTooSmall"],
+        [9, "InvalidMetadataCapacity"],
+        [29, "InvalidMetadataCapacity"],
+      ] as [number, string][]) {
+        try {
+          await program.methods
+            .resizeAgentMetadata(capacity)
+            .accounts({
+              agentAccount: agentPda,
+              owner: provider.wallet.publicKey,
+              systemProgram: SystemProgram.programId,
+            })
+            .rpc();
+          assert.fail(`Should have failed with ${expected} error`);
+        } catch (error) {
+          assert.include(error.message, expected);
+        }
+      }
+
+      // Shrinking to a capacity that still fits every entry is fine
+      await program.methods
+        .resizeAgentMetadata(11)
+        .accounts({
+          agentAccount: agentPda,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
 
-      const tx = new Transaction().add(computeBudgetIx, registerIx);
-      await provider.sendAndConfirm(tx, [agentMint]);
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.metadata.length, 11);
     });
 
-    it("Updates agent URI", async () => {
-      const newUri = "https://updated.com";
-      const agentMetadata = getMetadataPda(agentMint.publicKey);
+    it("Measures the metadata key scan at the 28-entry cap", async () => {
+      const MAX_RESIZED_METADATA_ENTRIES = 28;
+      // Max-length keys sharing a prefix: the worst case for key comparison
+      const key = (i: number) => `agent.profile.field.${String(i).padStart(12, "0")}`;
+      const setMetadata = (k: string, value: string) =>
+        program.methods
+          .setMetadata(k, Buffer.from(value))
+          .accounts({ agentAccount: agentPda, owner: provider.wallet.publicKey });
 
       await program.methods
-        .setAgentUri(newUri)
+        .resizeAgentMetadata(MAX_RESIZED_METADATA_ENTRIES)
         .accounts({
           agentAccount: agentPda,
-          agentMetadata,
-          agentMint: agentMint.publicKey,
           owner: provider.wallet.publicKey,
-          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
-          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
         })
         .rpc();
+      for (let i = 11; i < MAX_RESIZED_METADATA_ENTRIES; i++) {
+        await setMetadata(key(i), "value").rpc();
+      }
 
       const agent = await program.account.agentAccount.fetch(agentPda);
-      assert.equal(agent.tokenUri, newUri);
+      assert.equal(agent.metadata.length, MAX_RESIZED_METADATA_ENTRIES);
+
+      // Same-size update of the first vs the last entry: the difference is the
+      // scan over the 27 entries before the last one
+      const unitsConsumed = async (k: string) =>
+        (await provider.simulate(await setMetadata(k, "other").transaction())).unitsConsumed;
+      const first = await unitsConsumed(agent.metadata[0].key);
+      const last = await unitsConsumed(key(MAX_RESIZED_METADATA_ENTRIES - 1));
+      console.log(`      set_metadata CU at 28 entries: first key ${first}, last key ${last}`);
+
+      assert.isBelow(last - first, 5_000, "Key scan should stay a small share of set_metadata");
+      assert.isBelow(last, 200_000);
     });
+  });
 
-    it("Updates agent URI to empty string (ERC-8004 spec)", async () => {
+  describe("Import Metadata (Owner-Signed Manifest)", () => {
+    let agentMint: Keypair;
+    let agentPda: PublicKey;
+
+    beforeEach(async () => {
+      agentMint = Keypair.generate();
       const agentMetadata = getMetadataPda(agentMint.publicKey);
+      const agentMasterEdition = getMasterEditionPda(agentMint.publicKey);
+      const agentTokenAccount = getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey);
+      [agentPda] = getAgentPda(agentMint.publicKey);
 
-      await program.methods
-        .setAgentUri("")
+      const registerIx = await program.methods
+        .register("https://original.com")
         .accounts({
+          config: configPda,
+          authority: provider.wallet.publicKey,
           agentAccount: agentPda,
-          agentMetadata,
           agentMint: agentMint.publicKey,
+          agentMetadata,
+          agentMasterEdition,
+          agentTokenAccount,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
           owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([agentMint])
+        .instruction();
+
+      const computeBudgetIx = ComputeBudgetProgram.setComputeUnitLimit({
+        units: 400_000,
+      });
+
+      const tx = new Transaction().add(computeBudgetIx, registerIx);
+      await provider.sendAndConfirm(tx, [agentMint]);
+    });
+
+    const owner = (provider.wallet as anchor.Wallet).payer;
+    const relayer = Keypair.generate();
+
+    function getMetadataExtensionPda(agentMint: PublicKey, extensionIndex: number): PublicKey {
+      return PublicKey.findProgramAddressSync(
+        [Buffer.from("metadata_ext"), agentMint.toBuffer(), Buffer.from([extensionIndex])],
+        program.programId
+      )[0];
+    }
+
+    // IMPORT_METADATA_DOMAIN || agent_mint || nonce (u64 LE) || borsh(Vec<MetadataEntry>)
+    function manifestMessage(
+      agentMint: PublicKey,
+      nonce: anchor.BN,
+      manifest: { key: string; value: Buffer }[]
+    ): Buffer {
+      const u32 = (n: number) => {
+        const b = Buffer.alloc(4);
+        b.writeUInt32LE(n);
+        return b;
+      };
+      const parts = [
+        Buffer.from("erc8004:import_metadata:v2"),
+        agentMint.toBuffer(),
+        nonce.toArrayLike(Buffer, "le", 8),
+        u32(manifest.length),
+      ];
+      for (const entry of manifest) {
+        const key = Buffer.from(entry.key);
+        parts.push(u32(key.length), key, u32(entry.value.length), entry.value);
+      }
+      return Buffer.concat(parts);
+    }
+
+    async function signManifest(
+      manifest: { key: string; value: Buffer }[],
+      signer: Keypair,
+      nonce?: anchor.BN
+    ): Promise<{ nonce: anchor.BN; ed25519Ix: TransactionInstruction; signature: number[] }> {
+      nonce ??= (await program.account.agentAccount.fetch(agentPda)).importNonce;
+      const message = manifestMessage(agentMint.publicKey, nonce, manifest);
+      const ed25519Ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: signer.secretKey,
+        message,
+      });
+      const signature = Array.from(ed25519Ix.data.subarray(16 + 32, 16 + 32 + 64));
+      return { nonce, ed25519Ix, signature };
+    }
+
+    async function importMetadata(
+      manifest: { key: string; value: Buffer }[],
+      signer: Keypair,
+      extensions: PublicKey[] = [],
+      signed?: { nonce: anchor.BN; ed25519Ix: TransactionInstruction; signature: number[] }
+    ): Promise<string> {
+      const { nonce, ed25519Ix, signature } = signed ?? (await signManifest(manifest, signer));
+
+      const importIx = await program.methods
+        .importMetadata(manifest, nonce, signature)
+        .accounts({
+          agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          payer: relayer.publicKey,
           systemProgram: SystemProgram.programId,
           sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
         })
-        .rpc();
+        .remainingAccounts(extensions.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false })))
+        .instruction();
+
+      const computeBudgetIx = ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 });
+      const tx = new Transaction().add(computeBudgetIx, ed25519Ix, importIx);
+      tx.feePayer = relayer.publicKey;
+      return await provider.sendAndConfirm(tx, [relayer]);
+    }
+
+    before(async () => {
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(relayer.publicKey, 1_000_000_000)
+      );
+    });
+
+    it("Imports an owner-signed manifest submitted by a relayer", async () => {
+      const manifest = [
+        { key: "model", value: Buffer.from("gpt-4") },
+        { key: "version", value: Buffer.from("1.0") },
+        { key: "endpoint", value: Buffer.from("https://agent.example") },
+      ];
+
+      await importMetadata(manifest, owner);
 
       const agent = await program.account.agentAccount.fetch(agentPda);
-      assert.equal(agent.tokenUri, "");
+      assert.equal(agent.metadata.length, 3);
+      assert.equal(Buffer.from(agent.metadata[2].value).toString(), "https://agent.example");
+      assert.equal(agent.importNonce.toNumber(), 1);
     });
 
-    it("Updates agent URI multiple times", async () => {
-      const uris = ["https://v1.com", "https://v2.com", "https://v3.com"];
-      const agentMetadata = getMetadataPda(agentMint.publicKey);
+    it("Fails to replay an already imported manifest", async () => {
+      const manifest = [{ key: "model", value: Buffer.from("gpt-4") }];
+      const signed = await signManifest(manifest, owner);
+      await importMetadata(manifest, owner, [], signed);
 
-      for (const uri of uris) {
-        await program.methods
-          .setAgentUri(uri)
-          .accounts({
-            agentAccount: agentPda,
-            agentMetadata,
-            agentMint: agentMint.publicKey,
-            owner: provider.wallet.publicKey,
-            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
-            systemProgram: SystemProgram.programId,
-            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
-          })
-          .rpc();
+      // Owner changes the value afterwards
+      await importMetadata([{ key: "model", value: Buffer.from("claude") }], owner);
 
-        const agent = await program.account.agentAccount.fetch(agentPda);
-        assert.equal(agent.tokenUri, uri);
+      try {
+        await importMetadata(manifest, owner, [], signed);
+        assert.fail("Should have failed with InvalidImportNonce error");
+      } catch (error) {
+        assert.include(error.message, "InvalidImportNonce");
       }
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(Buffer.from(agent.metadata[0].value).toString(), "claude");
     });
 
-    it("Fails with URI > 200 bytes", async () => {
-      const longUri = "x".repeat(201);
-      const agentMetadata = getMetadataPda(agentMint.publicKey);
+    it("Spills into a newly created extension once the base account is full", async () => {
+      const manifest = Array.from({ length: 12 }, (_, i) => ({
+        key: `k${i}`,
+        value: Buffer.from(`v${i}`),
+      }));
+      const extension = getMetadataExtensionPda(agentMint.publicKey, 0);
 
-      try {
-        await program.methods
-          .setAgentUri(longUri)
-          .accounts({
-            agentAccount: agentPda,
-            agentMetadata,
-            agentMint: agentMint.publicKey,
-            owner: provider.wallet.publicKey,
-            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
-            systemProgram: SystemProgram.programId,
-            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+      await importMetadata(manifest, owner, [extension]);
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.metadata.length, 10);
+
+      const ext = await program.account.metadataExtension.fetch(extension);
+      assert.equal(ext.extensionIndex, 0);
+      assert.deepEqual(ext.metadata.map((e) => e.key), ["k10", "k11"]);
+    });
+
+    it("Creates an extension whose address was pre-funded", async () => {
+      const manifest = Array.from({ length: 11 }, (_, i) => ({
+        key: `k${i}`,
+        value: Buffer.from(`v${i}`),
+      }));
+      const extension = getMetadataExtensionPda(agentMint.publicKey, 0);
+
+      // Anyone can send lamports to the PDA ahead of time
+      await provider.sendAndConfirm(
+        new Transaction().add(
+          SystemProgram.transfer({
+            fromPubkey: provider.wallet.publicKey,
+            toPubkey: extension,
+            lamports: 1_000_000,
           })
-          .rpc();
+        )
+      );
 
-        assert.fail("Should have failed with UriTooLong error");
+      await importMetadata(manifest, owner, [extension]);
+
+      const ext = await program.account.metadataExtension.fetch(extension);
+      assert.deepEqual(ext.metadata.map((e) => e.key), ["k10"]);
+    });
+
+    it("Fails when the manifest is not signed by the owner", async () => {
+      try {
+        await importMetadata([{ key: "model", value: Buffer.from("evil") }], Keypair.generate());
+        assert.fail("Should have failed with InvalidSignature error");
       } catch (error) {
-        assert.include(error.message, "UriTooLong");
+        assert.include(error.message, "InvalidSignature");
       }
     });
 
-    it("Fails when non-owner tries to set URI", async () => {
-      const otherUser = Keypair.generate();
+    it("Fails atomically with duplicate keys in the manifest", async () => {
+      try {
+        await importMetadata(
+          [
+            { key: "model", value: Buffer.from("a") },
+            { key: "model", value: Buffer.from("b") },
+          ],
+          owner
+        );
+        assert.fail("Should have failed with DuplicateMetadataKey error");
+      } catch (error) {
+        assert.include(error.message, "DuplicateMetadataKey");
+      }
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.metadata.length, 0);
+    });
+  });
+
+  describe("Transfer Agent (Combined Transfer + Sync)", () => {
+    let agentMint: Keypair;
+    let agentPda: PublicKey;
+    let newOwner: Keypair;
+
+    beforeEach(async () => {
+      // Register an agent
+      agentMint = Keypair.generate();
+      [agentPda] = getAgentPda(agentMint.publicKey);
+
       const agentMetadata = getMetadataPda(agentMint.publicKey);
+      const agentMasterEdition = getMasterEditionPda(agentMint.publicKey);
+      const agentTokenAccount = getAssociatedTokenAddressSync(
+        agentMint.publicKey,
+        provider.wallet.publicKey
+      );
+
+      await program.methods
+        .register("https://example.com/agent.json")
+        .accounts({
+          config: configPda,
+          authority: provider.wallet.publicKey,
+          agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          agentMetadata,
+          agentMasterEdition,
+          agentTokenAccount,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
+          owner: provider.wallet.publicKey,
+          systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        })
+        .signers([agentMint])
+        .rpc();
 
+      // Prepare new owner
+      newOwner = Keypair.generate();
       await provider.connection.confirmTransaction(
-        await provider.connection.requestAirdrop(otherUser.publicKey, 1000000000)
+        await provider.connection.requestAirdrop(newOwner.publicKey, 1000000000)
+      );
+    });
+
+    it("Transfers agent and syncs owner in one transaction", async () => {
+      const fromTokenAccount = getAssociatedTokenAddressSync(
+        agentMint.publicKey,
+        provider.wallet.publicKey
+      );
+      const toTokenAccount = getAssociatedTokenAddressSync(
+        agentMint.publicKey,
+        newOwner.publicKey
+      );
+
+      // Create destination token account
+      const { createAssociatedTokenAccountInstruction } = await import("@solana/spl-token");
+      const createAtaTx = new anchor.web3.Transaction().add(
+        createAssociatedTokenAccountInstruction(
+          provider.wallet.publicKey,
+          toTokenAccount,
+          newOwner.publicKey,
+          agentMint.publicKey
+        )
+      );
+      await provider.sendAndConfirm(createAtaTx);
+
+      // Transfer agent (combines SPL transfer + sync_owner)
+      await program.methods
+        .transferAgent(false)
+        .accounts({
+          agentAccount: agentPda,
+          fromTokenAccount,
+          toTokenAccount,
+          owner: provider.wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      // Verify ownership changed
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.owner.toBase58(), newOwner.publicKey.toBase58());
+
+      // Verify NFT transferred
+      const tokenAccountInfo = await getAccount(provider.connection, toTokenAccount);
+      assert.equal(tokenAccountInfo.amount.toString(), "1");
+    });
+
+    it("Fails to transfer to self", async () => {
+      const tokenAccount = getAssociatedTokenAddressSync(
+        agentMint.publicKey,
+        provider.wallet.publicKey
       );
 
       try {
         await program.methods
-          .setAgentUri("https://hacker.com")
+          .transferAgent(false)
           .accounts({
             agentAccount: agentPda,
-            agentMetadata,
-            agentMint: agentMint.publicKey,
-            owner: otherUser.publicKey,
-            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
-            systemProgram: SystemProgram.programId,
-            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            fromTokenAccount: tokenAccount,
+            toTokenAccount: tokenAccount, // Same account
+            owner: provider.wallet.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
           })
-          .signers([otherUser])
           .rpc();
 
-        assert.fail("Should have failed with Unauthorized error");
+        assert.fail("Should have failed with TransferToSelf error");
       } catch (error) {
-        assert.include(error.message, "Unauthorized");
+        assert.include(error.message, "TransferToSelf");
       }
     });
+
+    for (const [decimals, supply] of [
+      [0, 2],
+      [1, 1],
+    ] as const) {
+      it(`Fails to transfer with a substituted mint (decimals ${decimals}, supply ${supply})`, async () => {
+        const badMint = await createMalformedMint(decimals, supply);
+        const fromTokenAccount = getAssociatedTokenAddressSync(
+          agentMint.publicKey,
+          provider.wallet.publicKey
+        );
+        const toTokenAccount = await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          (provider.wallet as anchor.Wallet).payer,
+          agentMint.publicKey,
+          newOwner.publicKey
+        );
+
+        try {
+          await program.methods
+            .transferAgent(false)
+            .accounts({
+              agentAccount: agentPda,
+              fromTokenAccount,
+              toTokenAccount: toTokenAccount.address,
+              agentMint: badMint,
+              agentMetadata: getMetadataPda(badMint),
+              owner: provider.wallet.publicKey,
+              tokenProgram: TOKEN_PROGRAM_ID,
+              tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+              systemProgram: SystemProgram.programId,
+              sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+            })
+            .rpc();
+
+          assert.fail("Should have failed with InvalidTokenAccount error");
+        } catch (error) {
+          assert.include(error.message, "InvalidTokenAccount");
+        }
+      });
+    }
   });
 
-  describe("Sync Owner (NFT Transfer Support)", () => {
+  describe("Ownership History", () => {
     let agentMint: Keypair;
     let agentPda: PublicKey;
-    let originalOwnerTokenAccount: PublicKey;
     let newOwner: Keypair;
-    let newOwnerTokenAccount: PublicKey;
 
-    beforeEach(async () => {
+    function getOwnershipHistoryPda(mint: PublicKey, seq: number): PublicKey {
+      const seqBytes = Buffer.alloc(4);
+      seqBytes.writeUInt32LE(seq);
+      return PublicKey.findProgramAddressSync(
+        [Buffer.from("owner_history"), mint.toBuffer(), seqBytes],
+        program.programId
+      )[0];
+    }
+
+    before(async () => {
       agentMint = Keypair.generate();
-      const agentMetadata = getMetadataPda(agentMint.publicKey);
-      const agentMasterEdition = getMasterEditionPda(agentMint.publicKey);
-      originalOwnerTokenAccount = getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey);
       [agentPda] = getAgentPda(agentMint.publicKey);
 
       const registerIx = await program.methods
-        .register("https://example.com")
+        .register("https://example.com/provenance.json")
         .accounts({
           config: configPda,
           authority: provider.wallet.publicKey,
           agentAccount: agentPda,
           agentMint: agentMint.publicKey,
-          agentMetadata,
-          agentMasterEdition,
-          agentTokenAccount: originalOwnerTokenAccount,
+          agentMetadata: getMetadataPda(agentMint.publicKey),
+          agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
+          agentTokenAccount: getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey),
           collectionMint: collectionMint.publicKey,
           collectionMetadata,
           collectionMasterEdition,
@@ -1135,238 +4723,264 @@ describe("Identity Registry (ERC-8004 Spec Compliant)", () => {
           sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
         })
         .instruction();
-
       await sendWithComputeBudget(registerIx, [agentMint]);
 
-      // Prepare new owner
       newOwner = Keypair.generate();
       await provider.connection.confirmTransaction(
         await provider.connection.requestAirdrop(newOwner.publicKey, 1000000000)
       );
-      newOwnerTokenAccount = getAssociatedTokenAddressSync(agentMint.publicKey, newOwner.publicKey);
     });
 
-    it("Syncs owner after SPL Token transfer", async () => {
-      // Create new owner's token account first
-      const { AssociatedTokenProgramInstruction, createAssociatedTokenAccountInstruction } = await import("@solana/spl-token");
-
-      const createAtaTx = new anchor.web3.Transaction().add(
-        createAssociatedTokenAccountInstruction(
-          provider.wallet.publicKey,
-          newOwnerTokenAccount,
-          newOwner.publicKey,
-          agentMint.publicKey
-        )
-      );
-      await provider.sendAndConfirm(createAtaTx);
-
-      // Transfer NFT via SPL Token
-      await transfer(
-        provider.connection,
-        provider.wallet.payer,
-        originalOwnerTokenAccount,
-        newOwnerTokenAccount,
-        provider.wallet.publicKey,
-        1
+    it("Records each opted-in transfer in order", async () => {
+      const walletTokenAccount = getAssociatedTokenAddressSync(
+        agentMint.publicKey,
+        provider.wallet.publicKey
       );
+      const newOwnerTokenAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          (provider.wallet as anchor.Wallet).payer,
+          agentMint.publicKey,
+          newOwner.publicKey
+        )
+      ).address;
 
-      // Sync owner in AgentAccount (old owner must sign to transfer update_authority)
-      const agentMetadata = getMetadataPda(agentMint.publicKey);
+      // Wallet -> newOwner
+      await program.methods
+        .transferAgent(true)
+        .accounts({
+          agentAccount: agentPda,
+          fromTokenAccount: walletTokenAccount,
+          toTokenAccount: newOwnerTokenAccount,
+          ownershipHistory: getOwnershipHistoryPda(agentMint.publicKey, 0),
+          owner: provider.wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
 
+      // newOwner -> wallet
       await program.methods
-        .syncOwner()
+        .transferAgent(true)
         .accounts({
           agentAccount: agentPda,
-          tokenAccount: newOwnerTokenAccount,
-          agentMetadata,
-          agentMint: agentMint.publicKey,
-          oldOwnerSigner: provider.wallet.publicKey,
-          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
-          systemProgram: SystemProgram.programId,
-          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          fromTokenAccount: newOwnerTokenAccount,
+          toTokenAccount: walletTokenAccount,
+          ownershipHistory: getOwnershipHistoryPda(agentMint.publicKey, 1),
+          owner: newOwner.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
         })
+        .signers([newOwner])
         .rpc();
 
       const agent = await program.account.agentAccount.fetch(agentPda);
-      assert.equal(agent.owner.toBase58(), newOwner.publicKey.toBase58());
-    });
+      assert.equal(agent.historyLen, 2);
 
-    it("Allows new owner to update metadata after transfer", async () => {
-      // Create ATA and transfer
-      const { createAssociatedTokenAccountInstruction } = await import("@solana/spl-token");
+      const first = await program.account.ownershipHistory.fetch(
+        getOwnershipHistoryPda(agentMint.publicKey, 0)
+      );
+      const second = await program.account.ownershipHistory.fetch(
+        getOwnershipHistoryPda(agentMint.publicKey, 1)
+      );
+      assert.equal(first.seq, 0);
+      assert.equal(first.agentId.toNumber(), agent.agentId.toNumber());
+      assert.equal(first.from.toBase58(), provider.wallet.publicKey.toBase58());
+      assert.equal(first.to.toBase58(), newOwner.publicKey.toBase58());
+      assert.equal(second.seq, 1);
+      assert.equal(second.from.toBase58(), newOwner.publicKey.toBase58());
+      assert.equal(second.to.toBase58(), provider.wallet.publicKey.toBase58());
+      assert.isAtLeast(second.ts.toNumber(), first.ts.toNumber());
+    });
 
-      const createAtaTx = new anchor.web3.Transaction().add(
-        createAssociatedTokenAccountInstruction(
-          provider.wallet.publicKey,
-          newOwnerTokenAccount,
-          newOwner.publicKey,
-          agentMint.publicKey
-        )
+    it("Fails when record_history is set without a history account", async () => {
+      const walletTokenAccount = getAssociatedTokenAddressSync(
+        agentMint.publicKey,
+        provider.wallet.publicKey
+      );
+      const newOwnerTokenAccount = getAssociatedTokenAddressSync(
+        agentMint.publicKey,
+        newOwner.publicKey
       );
-      await provider.sendAndConfirm(createAtaTx);
 
-      await transfer(
-        provider.connection,
-        provider.wallet.payer,
-        originalOwnerTokenAccount,
-        newOwnerTokenAccount,
+      try {
+        await program.methods
+          .transferAgent(true)
+          .accounts({
+            agentAccount: agentPda,
+            fromTokenAccount: walletTokenAccount,
+            toTokenAccount: newOwnerTokenAccount,
+            ownershipHistory: null,
+            owner: provider.wallet.publicKey,
+            tokenProgram: TOKEN_PROGRAM_ID,
+          })
+          .rpc();
+
+        assert.fail("Should have failed with OwnershipHistoryMismatch error");
+      } catch (error) {
+        assert.include(error.message, "OwnershipHistoryMismatch");
+      }
+
+      // Untracked transfers leave the history untouched
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.historyLen, 2);
+    });
+  });
+
+  describe("Token-2022 Agents", () => {
+    let agentMint: Keypair;
+    let agentPda: PublicKey;
+
+    const registerAccounts = (mint: Keypair, tokenProgram: PublicKey) => ({
+      config: configPda,
+      authority: provider.wallet.publicKey,
+      agentAccount: getAgentPda(mint.publicKey)[0],
+      agentMint: mint.publicKey,
+      agentMetadata: getMetadataPda(mint.publicKey),
+      agentMasterEdition: getMasterEditionPda(mint.publicKey),
+      agentTokenAccount: getAssociatedTokenAddressSync(
+        mint.publicKey,
         provider.wallet.publicKey,
-        1
+        false,
+        tokenProgram
+      ),
+      collectionMint: collectionMint.publicKey,
+      collectionMetadata,
+      collectionMasterEdition,
+      owner: provider.wallet.publicKey,
+      systemProgram: SystemProgram.programId,
+      tokenProgram,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      rent: SYSVAR_RENT_PUBKEY,
+      tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+    });
+
+    it("Registers an agent with a Token-2022 mint", async () => {
+      agentMint = Keypair.generate();
+      [agentPda] = getAgentPda(agentMint.publicKey);
+
+      const ix = await program.methods
+        .registerToken2022("https://example.com/token2022.json")
+        .accounts(registerAccounts(agentMint, TOKEN_2022_PROGRAM_ID))
+        .instruction();
+      await sendWithComputeBudget(ix, [agentMint]);
+
+      const mintInfo = await provider.connection.getAccountInfo(agentMint.publicKey);
+      assert.equal(mintInfo.owner.toBase58(), TOKEN_2022_PROGRAM_ID.toBase58());
+
+      const tokenAccount = await getAccount(
+        provider.connection,
+        getAssociatedTokenAddressSync(
+          agentMint.publicKey,
+          provider.wallet.publicKey,
+          false,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        undefined,
+        TOKEN_2022_PROGRAM_ID
       );
+      assert.equal(tokenAccount.amount.toString(), "1");
 
-      // Sync owner (old owner must sign to transfer update_authority)
-      const agentMetadata = getMetadataPda(agentMint.publicKey);
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.owner.toBase58(), provider.wallet.publicKey.toBase58());
+    });
+
+    it("Updates the URI of a Token-2022 agent", async () => {
+      const newUri = "https://example.com/token2022-v2.json";
 
       await program.methods
-        .syncOwner()
+        .setAgentUri(newUri)
         .accounts({
           agentAccount: agentPda,
-          tokenAccount: newOwnerTokenAccount,
-          agentMetadata,
+          agentMetadata: getMetadataPda(agentMint.publicKey),
           agentMint: agentMint.publicKey,
-          oldOwnerSigner: provider.wallet.publicKey,
+          owner: provider.wallet.publicKey,
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
           sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
         })
         .rpc();
 
-      // New owner sets metadata
-      await program.methods
-        .setMetadata("newKey", Buffer.from("newValue"))
-        .accounts({
-          agentAccount: agentPda,
-          owner: newOwner.publicKey,
-        })
-        .signers([newOwner])
-        .rpc();
-
       const agent = await program.account.agentAccount.fetch(agentPda);
-      assert.equal(agent.metadata.length, 1);
-      assert.equal(agent.metadata[0].key, "newKey");
+      assert.equal(agent.tokenUri, newUri);
     });
 
-    it("Prevents old owner from updating metadata after transfer", async () => {
-      // Create ATA and transfer
-      const { createAssociatedTokenAccountInstruction } = await import("@solana/spl-token");
-
-      const createAtaTx = new anchor.web3.Transaction().add(
-        createAssociatedTokenAccountInstruction(
-          provider.wallet.publicKey,
-          newOwnerTokenAccount,
-          newOwner.publicKey,
-          agentMint.publicKey
-        )
-      );
-      await provider.sendAndConfirm(createAtaTx);
-
-      await transfer(
-        provider.connection,
-        provider.wallet.payer,
-        originalOwnerTokenAccount,
-        newOwnerTokenAccount,
+    it("Transfers a Token-2022 agent", async () => {
+      const newOwner = Keypair.generate();
+      const fromTokenAccount = getAssociatedTokenAddressSync(
+        agentMint.publicKey,
         provider.wallet.publicKey,
-        1
+        false,
+        TOKEN_2022_PROGRAM_ID
       );
-
-      // Sync owner (old owner must sign to transfer update_authority)
-      const agentMetadata = getMetadataPda(agentMint.publicKey);
+      const toTokenAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          (provider.wallet as anchor.Wallet).payer,
+          agentMint.publicKey,
+          newOwner.publicKey,
+          false,
+          undefined,
+          undefined,
+          TOKEN_2022_PROGRAM_ID
+        )
+      ).address;
 
       await program.methods
-        .syncOwner()
+        .transferAgent(false)
         .accounts({
           agentAccount: agentPda,
-          tokenAccount: newOwnerTokenAccount,
-          agentMetadata,
+          fromTokenAccount,
+          toTokenAccount,
           agentMint: agentMint.publicKey,
-          oldOwnerSigner: provider.wallet.publicKey,
-          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
-          systemProgram: SystemProgram.programId,
-          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+          owner: provider.wallet.publicKey,
+          tokenProgram: TOKEN_2022_PROGRAM_ID,
         })
         .rpc();
 
-      // Old owner tries to set metadata
-      try {
-        await program.methods
-          .setMetadata("hack", Buffer.from("value"))
-          .accounts({
-            agentAccount: agentPda,
-            owner: provider.wallet.publicKey,
-          })
-          .rpc();
-
-        assert.fail("Should have failed with Unauthorized error");
-      } catch (error) {
-        assert.include(error.message, "Unauthorized");
-      }
-    });
-
-    it("Fails to sync with invalid token account (amount = 0)", async () => {
-      // Create empty token account
-      const { createAssociatedTokenAccountInstruction } = await import("@solana/spl-token");
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.owner.toBase58(), newOwner.publicKey.toBase58());
 
-      const emptyAccount = getAssociatedTokenAddressSync(agentMint.publicKey, newOwner.publicKey);
-      const createAtaTx = new anchor.web3.Transaction().add(
-        createAssociatedTokenAccountInstruction(
-          provider.wallet.publicKey,
-          emptyAccount,
-          newOwner.publicKey,
-          agentMint.publicKey
-        )
+      const tokenAccount = await getAccount(
+        provider.connection,
+        toTokenAccount,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
       );
-      await provider.sendAndConfirm(createAtaTx);
+      assert.equal(tokenAccount.amount.toString(), "1");
+    });
 
+    it("Fails to register a Token-2022 mint through register", async () => {
+      const mint = Keypair.generate();
       try {
-        const agentMetadata = getMetadataPda(agentMint.publicKey);
-
-        await program.methods
-          .syncOwner()
-          .accounts({
-            agentAccount: agentPda,
-            tokenAccount: emptyAccount,
-            agentMetadata,
-            agentMint: agentMint.publicKey,
-            oldOwnerSigner: provider.wallet.publicKey,
-            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
-            systemProgram: SystemProgram.programId,
-            sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
-          })
-          .rpc();
+        const ix = await program.methods
+          .register("https://example.com/wrong-path.json")
+          .accounts(registerAccounts(mint, TOKEN_2022_PROGRAM_ID))
+          .instruction();
+        await sendWithComputeBudget(ix, [mint]);
 
-        assert.fail("Should have failed with InvalidTokenAccount error");
+        assert.fail("Should have failed with InvalidTokenProgram error");
       } catch (error) {
-        assert.include(error.message, "InvalidTokenAccount");
+        assert.include(error.message, "InvalidTokenProgram");
       }
-    });
-  });
-
-  describe("Owner Of (ERC-721: ownerOf)", () => {
-    let agentMint: Keypair;
-    let agentPda: PublicKey;
-
-    beforeEach(async () => {
-      // Register an agent
-      agentMint = Keypair.generate();
-      [agentPda] = getAgentPda(agentMint.publicKey);
-
-      const agentMetadata = getMetadataPda(agentMint.publicKey);
-      const agentMasterEdition = getMasterEditionPda(agentMint.publicKey);
-      const agentTokenAccount = getAssociatedTokenAddressSync(
-        agentMint.publicKey,
-        provider.wallet.publicKey
-      );
+    });
+  });
 
-      await program.methods
-        .register("https://example.com/agent.json")
+  describe("Owner Agent List", () => {
+    async function registerAgent(): Promise<{ mint: Keypair; pda: PublicKey; id: number }> {
+      const mint = Keypair.generate();
+      const [pda] = getAgentPda(mint.publicKey);
+
+      const registerIx = await program.methods
+        .register("https://example.com/listed")
         .accounts({
           config: configPda,
           authority: provider.wallet.publicKey,
-          agentAccount: agentPda,
-          agentMint: agentMint.publicKey,
-          agentMetadata,
-          agentMasterEdition,
-          agentTokenAccount,
+          agentAccount: pda,
+          agentMint: mint.publicKey,
+          agentMetadata: getMetadataPda(mint.publicKey),
+          agentMasterEdition: getMasterEditionPda(mint.publicKey),
+          agentTokenAccount: getAssociatedTokenAddressSync(mint.publicKey, provider.wallet.publicKey),
+          ownerAgents: getOwnerAgentsPda(provider.wallet.publicKey),
           collectionMint: collectionMint.publicKey,
           collectionMetadata,
           collectionMasterEdition,
@@ -1378,121 +4992,138 @@ describe("Identity Registry (ERC-8004 Spec Compliant)", () => {
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
           sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
         })
-        .signers([agentMint])
-        .rpc();
-    });
+        .instruction();
 
-    it("Returns current owner of agent", async () => {
-      const owner = await program.methods
-        .ownerOf()
-        .accounts({
-          agentAccount: agentPda,
-        })
-        .view();
+      await sendWithComputeBudget(registerIx, [mint]);
+      const agent = await program.account.agentAccount.fetch(pda);
+      return { mint, pda, id: agent.agentId.toNumber() };
+    }
 
-      assert.equal(owner.toBase58(), provider.wallet.publicKey.toBase58());
-    });
+    async function transferTo(
+      agent: { mint: Keypair; pda: PublicKey },
+      from: Keypair | null,
+      to: PublicKey
+    ) {
+      const fromOwner = from ? from.publicKey : provider.wallet.publicKey;
+      const toTokenAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        (provider.wallet as anchor.Wallet).payer,
+        agent.mint.publicKey,
+        to
+      );
 
-    it("Returns updated owner after transfer", async () => {
-      // Create new owner
-      const newOwner = Keypair.generate();
+      const builder = program.methods
+        .transferAgent(false)
+        .accounts({
+          agentAccount: agent.pda,
+          fromTokenAccount: getAssociatedTokenAddressSync(agent.mint.publicKey, fromOwner),
+          toTokenAccount: toTokenAccount.address,
+          agentMint: agent.mint.publicKey,
+          agentMetadata: getMetadataPda(agent.mint.publicKey),
+          oldOwnerAgents: getOwnerAgentsPda(fromOwner),
+          newOwnerAgents: getOwnerAgentsPda(to),
+          owner: fromOwner,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        });
+      await (from ? builder.signers([from]) : builder).rpc();
+    }
+
+    it("Tracks agents per owner across registration and transfers", async () => {
+      const alice = Keypair.generate();
+      const bob = Keypair.generate();
       await provider.connection.confirmTransaction(
-        await provider.connection.requestAirdrop(newOwner.publicKey, 1000000000)
+        await provider.connection.requestAirdrop(alice.publicKey, 1_000_000_000)
       );
 
-      const originalOwnerTokenAccount = getAssociatedTokenAddressSync(
-        agentMint.publicKey,
-        provider.wallet.publicKey
-      );
-      const newOwnerTokenAccount = getAssociatedTokenAddressSync(
-        agentMint.publicKey,
-        newOwner.publicKey
-      );
+      const first = await registerAgent();
+      const second = await registerAgent();
 
-      // Create new owner's token account
-      const { createAssociatedTokenAccountInstruction } = await import("@solana/spl-token");
-      const createAtaTx = new anchor.web3.Transaction().add(
-        createAssociatedTokenAccountInstruction(
-          provider.wallet.publicKey,
-          newOwnerTokenAccount,
-          newOwner.publicKey,
-          agentMint.publicKey
-        )
+      // Registration lists the agents under the registering wallet (unless its list is full)
+      const registrar = await program.account.ownerAgentList.fetch(
+        getOwnerAgentsPda(provider.wallet.publicKey)
       );
-      await provider.sendAndConfirm(createAtaTx);
+      assert.equal(registrar.owner.toBase58(), provider.wallet.publicKey.toBase58());
+      if (!registrar.truncated) {
+        const ids = registrar.agentIds.map((id) => id.toNumber());
+        assert.include(ids, first.id);
+        assert.include(ids, second.id);
+      }
 
-      // Transfer NFT
-      await transfer(
-        provider.connection,
-        provider.wallet.payer,
-        originalOwnerTokenAccount,
-        newOwnerTokenAccount,
-        provider.wallet.publicKey,
-        1
+      // Alice acquires both agents, then transfers one to Bob
+      await transferTo(first, null, alice.publicKey);
+      await transferTo(second, null, alice.publicKey);
+
+      let aliceList = await program.account.ownerAgentList.fetch(getOwnerAgentsPda(alice.publicKey));
+      assert.deepEqual(aliceList.agentIds.map((id) => id.toNumber()), [first.id, second.id]);
+
+      const registrarAfter = await program.account.ownerAgentList.fetch(
+        getOwnerAgentsPda(provider.wallet.publicKey)
       );
+      const registrarIds = registrarAfter.agentIds.map((id) => id.toNumber());
+      assert.notInclude(registrarIds, first.id);
+      assert.notInclude(registrarIds, second.id);
 
-      // Sync owner
-      const agentMetadata = getMetadataPda(agentMint.publicKey);
-      await program.methods
-        .syncOwner()
-        .accounts({
-          agentAccount: agentPda,
-          tokenAccount: newOwnerTokenAccount,
-          agentMetadata,
-          agentMint: agentMint.publicKey,
-          oldOwnerSigner: provider.wallet.publicKey,
-          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
-          systemProgram: SystemProgram.programId,
-          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
-        })
-        .rpc();
+      await transferTo(first, alice, bob.publicKey);
 
-      // Check owner updated
-      const owner = await program.methods
-        .ownerOf()
-        .accounts({
-          agentAccount: agentPda,
-        })
-        .view();
+      aliceList = await program.account.ownerAgentList.fetch(getOwnerAgentsPda(alice.publicKey));
+      assert.deepEqual(aliceList.agentIds.map((id) => id.toNumber()), [second.id]);
 
-      assert.equal(owner.toBase58(), newOwner.publicKey.toBase58());
+      const bobList = await program.account.ownerAgentList.fetch(getOwnerAgentsPda(bob.publicKey));
+      assert.equal(bobList.owner.toBase58(), bob.publicKey.toBase58());
+      assert.deepEqual(bobList.agentIds.map((id) => id.toNumber()), [first.id]);
     });
   });
 
-  describe("Metadata Extensions (Beyond 10 entries)", () => {
+  describe("Freeze / Thaw Agent", () => {
     let agentMint: Keypair;
     let agentPda: PublicKey;
+    let ownerTokenAccount: PublicKey;
+    let newOwnerTokenAccount: PublicKey;
+    let newOwner: Keypair;
 
-    // Helper to derive metadata extension PDA
-    function getMetadataExtensionPda(agentMint: PublicKey, extensionIndex: number): [PublicKey, number] {
-      return PublicKey.findProgramAddressSync(
-        [Buffer.from("metadata_ext"), agentMint.toBuffer(), Buffer.from([extensionIndex])],
-        program.programId
-      );
+    function freezeAccounts() {
+      return {
+        agentAccount: agentPda,
+        agentMint: agentMint.publicKey,
+        ownerTokenAccount,
+        agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
+        owner: provider.wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      };
+    }
+
+    async function transfer() {
+      await program.methods
+        .transferAgent(false)
+        .accounts({
+          agentAccount: agentPda,
+          fromTokenAccount: ownerTokenAccount,
+          toTokenAccount: newOwnerTokenAccount,
+          owner: provider.wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
     }
 
     beforeEach(async () => {
-      // Register an agent
       agentMint = Keypair.generate();
       [agentPda] = getAgentPda(agentMint.publicKey);
+      ownerTokenAccount = getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey);
 
-      const agentMetadata = getMetadataPda(agentMint.publicKey);
-      const agentMasterEdition = getMasterEditionPda(agentMint.publicKey);
-      const agentTokenAccount = getAssociatedTokenAddressSync(
-        agentMint.publicKey,
-        provider.wallet.publicKey
-      );
-
-      const registerIx = await program.methods
-        .register("https://example.com/agent.json")
+      await program.methods
+        .register("https://example.com/freeze.json")
         .accounts({
           config: configPda,
           authority: provider.wallet.publicKey,
           agentAccount: agentPda,
           agentMint: agentMint.publicKey,
-          agentMetadata,
-          agentMasterEdition,
-          agentTokenAccount,
+          agentMetadata: getMetadataPda(agentMint.publicKey),
+          agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
+          agentTokenAccount: ownerTokenAccount,
           collectionMint: collectionMint.publicKey,
           collectionMetadata,
           collectionMasterEdition,
@@ -1504,269 +5135,410 @@ describe("Identity Registry (ERC-8004 Spec Compliant)", () => {
           tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
           sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
         })
-        .instruction();
+        .signers([agentMint])
+        .rpc();
 
-      await sendWithComputeBudget(registerIx, [agentMint]);
+      newOwner = Keypair.generate();
+      newOwnerTokenAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          (provider.wallet as anchor.Wallet).payer,
+          agentMint.publicKey,
+          newOwner.publicKey
+        )
+      ).address;
+    });
 
-      // Fill up the base 10 metadata slots
-      for (let i = 0; i < 10; i++) {
-        await program.methods
-          .setMetadata(`key${i}`, Buffer.from(`value${i}`))
-          .accounts({
-            agentAccount: agentPda,
-            owner: provider.wallet.publicKey,
-          })
-          .rpc();
+    it("Blocks transfer_agent while frozen", async () => {
+      await program.methods.freezeAgent().accounts(freezeAccounts()).rpc();
+
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.isTrue(agent.frozen);
+      const tokenAccount = await getAccount(provider.connection, ownerTokenAccount);
+      assert.isTrue(tokenAccount.isFrozen);
+
+      try {
+        await transfer();
+        assert.fail("Should have failed with AgentFrozen error");
+      } catch (error) {
+        assert.include(error.message, "AgentFrozen");
+      }
+
+      try {
+        await program.methods.freezeAgent().accounts(freezeAccounts()).rpc();
+        assert.fail("Should have failed with AgentFrozen error");
+      } catch (error) {
+        assert.include(error.message, "AgentFrozen");
       }
     });
 
-    it("Creates metadata extension PDA", async () => {
-      const [metadataExtensionPda] = getMetadataExtensionPda(agentMint.publicKey, 0);
+    it("Restores transfer_agent after thaw", async () => {
+      await program.methods.freezeAgent().accounts(freezeAccounts()).rpc();
+      await program.methods.thawAgent().accounts(freezeAccounts()).rpc();
 
-      await program.methods
-        .createMetadataExtension(0)
-        .accounts({
-          metadataExtension: metadataExtensionPda,
-          agentMint: agentMint.publicKey,
-          agentAccount: agentPda,
-          owner: provider.wallet.publicKey,
-          systemProgram: SystemProgram.programId,
-        })
-        .rpc();
+      let agent = await program.account.agentAccount.fetch(agentPda);
+      assert.isFalse(agent.frozen);
+      const tokenAccount = await getAccount(provider.connection, ownerTokenAccount);
+      assert.isFalse(tokenAccount.isFrozen);
+      assert.isNull(tokenAccount.delegate);
 
-      const extension = await program.account.metadataExtension.fetch(metadataExtensionPda);
-      assert.equal(extension.agentMint.toBase58(), agentMint.publicKey.toBase58());
-      assert.equal(extension.extensionIndex, 0);
-      assert.equal(extension.metadata.length, 0);
+      await transfer();
+
+      agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.owner.toBase58(), newOwner.publicKey.toBase58());
     });
 
-    it("Sets metadata in extension PDA", async () => {
-      const [metadataExtensionPda] = getMetadataExtensionPda(agentMint.publicKey, 0);
+    it("Fails to thaw an agent that isn't frozen", async () => {
+      try {
+        await program.methods.thawAgent().accounts(freezeAccounts()).rpc();
+        assert.fail("Should have failed with AgentNotFrozen error");
+      } catch (error) {
+        assert.include(error.message, "AgentNotFrozen");
+      }
+    });
+  });
 
-      // Create extension
-      await program.methods
-        .createMetadataExtension(0)
-        .accounts({
-          metadataExtension: metadataExtensionPda,
-          agentMint: agentMint.publicKey,
-          agentAccount: agentPda,
-          owner: provider.wallet.publicKey,
-          systemProgram: SystemProgram.programId,
-        })
-        .rpc();
+  describe("Two-Step Transfer (Initiate / Accept / Cancel)", () => {
+    let agentMint: Keypair;
+    let agentPda: PublicKey;
+    let agentMetadata: PublicKey;
+    let ownerTokenAccount: PublicKey;
+    let newOwner: Keypair;
 
-      // Set metadata in extension
+    function transferAccounts(signer: PublicKey) {
+      return {
+        agentAccount: agentPda,
+        agentMint: agentMint.publicKey,
+        ownerTokenAccount,
+        agentMetadata,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
+        owner: signer,
+      };
+    }
+
+    async function acceptAs(signer: Keypair) {
       await program.methods
-        .setMetadataExtended(0, "extKey1", Buffer.from("extValue1"))
+        .acceptTransfer()
         .accounts({
-          metadataExtension: metadataExtensionPda,
-          agentMint: agentMint.publicKey,
           agentAccount: agentPda,
-          owner: provider.wallet.publicKey,
+          agentMint: agentMint.publicKey,
+          ownerTokenAccount,
+          newOwnerTokenAccount: getAssociatedTokenAddressSync(agentMint.publicKey, signer.publicKey),
+          agentMetadata,
+          newOwner: signer.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
         })
+        .signers([signer])
         .rpc();
+    }
 
-      const extension = await program.account.metadataExtension.fetch(metadataExtensionPda);
-      assert.equal(extension.metadata.length, 1);
-      assert.equal(extension.metadata[0].key, "extKey1");
-      assert.equal(Buffer.from(extension.metadata[0].value).toString(), "extValue1");
-    });
-
-    it("Gets metadata from extension PDA", async () => {
-      const [metadataExtensionPda] = getMetadataExtensionPda(agentMint.publicKey, 0);
+    beforeEach(async () => {
+      agentMint = Keypair.generate();
+      [agentPda] = getAgentPda(agentMint.publicKey);
+      agentMetadata = getMetadataPda(agentMint.publicKey);
+      ownerTokenAccount = getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey);
 
-      // Create extension
       await program.methods
-        .createMetadataExtension(0)
+        .register("https://example.com/two-step.json")
         .accounts({
-          metadataExtension: metadataExtensionPda,
-          agentMint: agentMint.publicKey,
+          config: configPda,
+          authority: provider.wallet.publicKey,
           agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          agentMetadata,
+          agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
+          agentTokenAccount: ownerTokenAccount,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
           owner: provider.wallet.publicKey,
           systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
         })
+        .signers([agentMint])
         .rpc();
 
-      // Set metadata
+      newOwner = Keypair.generate();
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(newOwner.publicKey, 1000000000)
+      );
+
       await program.methods
-        .setMetadataExtended(0, "testKey", Buffer.from("testValue"))
-        .accounts({
-          metadataExtension: metadataExtensionPda,
-          agentMint: agentMint.publicKey,
-          agentAccount: agentPda,
-          owner: provider.wallet.publicKey,
-        })
+        .initiateTransfer(newOwner.publicKey)
+        .accounts(transferAccounts(provider.wallet.publicKey))
         .rpc();
+    });
 
-      // Get metadata
-      const value = await program.methods
-        .getMetadataExtended(0, "testKey")
-        .accounts({
-          metadataExtension: metadataExtensionPda,
-          agentMint: agentMint.publicKey,
-        })
-        .view();
+    it("Records the pending owner without moving the NFT", async () => {
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.pendingOwner.toBase58(), newOwner.publicKey.toBase58());
+      assert.equal(agent.owner.toBase58(), provider.wallet.publicKey.toBase58());
 
-      assert.equal(Buffer.from(value).toString(), "testValue");
+      const tokenAccount = await getAccount(provider.connection, ownerTokenAccount);
+      assert.equal(tokenAccount.amount.toString(), "1");
+      assert.equal(tokenAccount.delegate.toBase58(), agentPda.toBase58());
     });
 
-    it("Returns empty bytes for non-existent key in extension", async () => {
-      const [metadataExtensionPda] = getMetadataExtensionPda(agentMint.publicKey, 0);
+    it("Pending owner accepts and receives the NFT", async () => {
+      await acceptAs(newOwner);
 
-      // Create extension
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.owner.toBase58(), newOwner.publicKey.toBase58());
+      assert.isNull(agent.pendingOwner);
+
+      const newOwnerTokenAccount = getAssociatedTokenAddressSync(agentMint.publicKey, newOwner.publicKey);
+      const tokenAccount = await getAccount(provider.connection, newOwnerTokenAccount);
+      assert.equal(tokenAccount.amount.toString(), "1");
+    });
+
+    it("Fails when someone other than the pending owner accepts", async () => {
+      const stranger = Keypair.generate();
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(stranger.publicKey, 1000000000)
+      );
+
+      try {
+        await acceptAs(stranger);
+        assert.fail("Should have failed with Unauthorized error");
+      } catch (error) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
+
+    it("Owner cancels and the pending owner can no longer accept", async () => {
       await program.methods
-        .createMetadataExtension(0)
-        .accounts({
-          metadataExtension: metadataExtensionPda,
-          agentMint: agentMint.publicKey,
-          agentAccount: agentPda,
-          owner: provider.wallet.publicKey,
-          systemProgram: SystemProgram.programId,
-        })
+        .cancelTransfer()
+        .accounts(transferAccounts(provider.wallet.publicKey))
         .rpc();
 
-      // Get non-existent key
-      const value = await program.methods
-        .getMetadataExtended(0, "nonExistent")
-        .accounts({
-          metadataExtension: metadataExtensionPda,
-          agentMint: agentMint.publicKey,
-        })
-        .view();
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.isNull(agent.pendingOwner);
 
-      assert.equal(value.length, 0);
+      const tokenAccount = await getAccount(provider.connection, ownerTokenAccount);
+      assert.isNull(tokenAccount.delegate);
+
+      try {
+        await acceptAs(newOwner);
+        assert.fail("Should have failed with NoPendingTransfer error");
+      } catch (error) {
+        assert.include(error.message, "NoPendingTransfer");
+      }
     });
 
-    it("Enforces 10 metadata entry limit per extension", async () => {
-      const [metadataExtensionPda] = getMetadataExtensionPda(agentMint.publicKey, 0);
+    it("sync_owner clears the pending transfer after a plain SPL transfer", async () => {
+      const holder = Keypair.generate();
+      const holderTokenAccount = (
+        await getOrCreateAssociatedTokenAccount(
+          provider.connection,
+          provider.wallet.payer,
+          agentMint.publicKey,
+          holder.publicKey
+        )
+      ).address;
+
+      await transfer(
+        provider.connection,
+        provider.wallet.payer,
+        ownerTokenAccount,
+        holderTokenAccount,
+        provider.wallet.publicKey,
+        1
+      );
 
-      // Create extension
       await program.methods
-        .createMetadataExtension(0)
+        .syncOwner(false)
         .accounts({
-          metadataExtension: metadataExtensionPda,
-          agentMint: agentMint.publicKey,
           agentAccount: agentPda,
-          owner: provider.wallet.publicKey,
+          tokenAccount: holderTokenAccount,
+          agentMetadata,
+          agentMint: agentMint.publicKey,
+          oldOwnerSigner: provider.wallet.publicKey,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
         })
         .rpc();
 
-      // Add 10 entries
-      for (let i = 0; i < 10; i++) {
-        await program.methods
-          .setMetadataExtended(0, `extKey${i}`, Buffer.from(`extValue${i}`))
-          .accounts({
-            metadataExtension: metadataExtensionPda,
-            agentMint: agentMint.publicKey,
-            agentAccount: agentPda,
-            owner: provider.wallet.publicKey,
-          })
-          .rpc();
-      }
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.owner.toBase58(), holder.publicKey.toBase58());
+      assert.isNull(agent.pendingOwner);
+    });
+  });
 
-      // Try to add 11th entry
-      try {
-        await program.methods
-          .setMetadataExtended(0, "extKey11", Buffer.from("extValue11"))
-          .accounts({
-            metadataExtension: metadataExtensionPda,
-            agentMint: agentMint.publicKey,
-            agentAccount: agentPda,
-            owner: provider.wallet.publicKey,
-          })
-          .rpc();
+  describe("Approve / Transfer From (ERC-721: approve / transferFrom)", () => {
+    let agentMint: Keypair;
+    let agentPda: PublicKey;
+    let agentMetadata: PublicKey;
+    let ownerTokenAccount: PublicKey;
+    let marketplace: Keypair;
+    let buyer: Keypair;
 
-        assert.fail("Should have failed with MetadataLimitReached error");
-      } catch (error) {
-        assert.include(error.message, "MetadataLimitReached");
-      }
-    });
+    async function transferFromAs(signer: Keypair) {
+      const toTokenAccount = await getOrCreateAssociatedTokenAccount(
+        provider.connection,
+        (provider.wallet as anchor.Wallet).payer,
+        agentMint.publicKey,
+        buyer.publicKey
+      );
 
-    it("Allows multiple extension PDAs (unlimited metadata)", async () => {
-      // Create extension 0
-      const [ext0Pda] = getMetadataExtensionPda(agentMint.publicKey, 0);
       await program.methods
-        .createMetadataExtension(0)
+        .transferAgentFrom()
         .accounts({
-          metadataExtension: ext0Pda,
-          agentMint: agentMint.publicKey,
           agentAccount: agentPda,
-          owner: provider.wallet.publicKey,
+          agentMint: agentMint.publicKey,
+          fromTokenAccount: ownerTokenAccount,
+          toTokenAccount: toTokenAccount.address,
+          agentMetadata,
+          delegate: signer.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
           systemProgram: SystemProgram.programId,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
         })
+        .signers([signer])
         .rpc();
+    }
+
+    beforeEach(async () => {
+      agentMint = Keypair.generate();
+      [agentPda] = getAgentPda(agentMint.publicKey);
+      agentMetadata = getMetadataPda(agentMint.publicKey);
+      ownerTokenAccount = getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey);
 
-      // Create extension 1
-      const [ext1Pda] = getMetadataExtensionPda(agentMint.publicKey, 1);
       await program.methods
-        .createMetadataExtension(1)
+        .register("https://example.com/approve.json")
         .accounts({
-          metadataExtension: ext1Pda,
-          agentMint: agentMint.publicKey,
+          config: configPda,
+          authority: provider.wallet.publicKey,
           agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          agentMetadata,
+          agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
+          agentTokenAccount: ownerTokenAccount,
+          collectionMint: collectionMint.publicKey,
+          collectionMetadata,
+          collectionMasterEdition,
           owner: provider.wallet.publicKey,
           systemProgram: SystemProgram.programId,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          rent: SYSVAR_RENT_PUBKEY,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
         })
+        .signers([agentMint])
         .rpc();
 
-      // Add metadata to both
+      marketplace = Keypair.generate();
+      buyer = Keypair.generate();
+      for (const kp of [marketplace, buyer]) {
+        await provider.connection.confirmTransaction(
+          await provider.connection.requestAirdrop(kp.publicKey, 1000000000)
+        );
+      }
+
       await program.methods
-        .setMetadataExtended(0, "ext0Key", Buffer.from("ext0Value"))
+        .approveTransfer(marketplace.publicKey)
         .accounts({
-          metadataExtension: ext0Pda,
-          agentMint: agentMint.publicKey,
           agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          ownerTokenAccount,
+          delegateAccount: marketplace.publicKey,
+          agentMetadata,
           owner: provider.wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
         })
         .rpc();
+    });
+
+    it("Approved delegate moves the NFT and the owner is synced", async () => {
+      let agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.approved.toBase58(), marketplace.publicKey.toBase58());
+      const approvedAccount = await getAccount(provider.connection, ownerTokenAccount);
+      assert.equal(approvedAccount.delegate.toBase58(), marketplace.publicKey.toBase58());
+
+      await transferFromAs(marketplace);
+
+      agent = await program.account.agentAccount.fetch(agentPda);
+      assert.equal(agent.owner.toBase58(), buyer.publicKey.toBase58());
+      assert.isNull(agent.approved);
+
+      const buyerTokenAccount = getAssociatedTokenAddressSync(agentMint.publicKey, buyer.publicKey);
+      const tokenAccount = await getAccount(provider.connection, buyerTokenAccount);
+      assert.equal(tokenAccount.amount.toString(), "1");
+    });
+
+    it("Fails when a key other than the approved one transfers", async () => {
+      try {
+        await transferFromAs(buyer);
+        assert.fail("Should have failed with Unauthorized error");
+      } catch (error) {
+        assert.include(error.message, "Unauthorized");
+      }
+    });
 
+    it("Revoked approval can no longer be used", async () => {
       await program.methods
-        .setMetadataExtended(1, "ext1Key", Buffer.from("ext1Value"))
+        .revokeTransferApproval()
         .accounts({
-          metadataExtension: ext1Pda,
-          agentMint: agentMint.publicKey,
           agentAccount: agentPda,
+          agentMint: agentMint.publicKey,
+          ownerTokenAccount,
+          agentMetadata,
           owner: provider.wallet.publicKey,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          sysvarInstructions: SYSVAR_INSTRUCTIONS_PUBKEY,
         })
         .rpc();
 
-      // Verify both extensions
-      const ext0 = await program.account.metadataExtension.fetch(ext0Pda);
-      const ext1 = await program.account.metadataExtension.fetch(ext1Pda);
+      const agent = await program.account.agentAccount.fetch(agentPda);
+      assert.isNull(agent.approved);
+      const tokenAccount = await getAccount(provider.connection, ownerTokenAccount);
+      assert.isNull(tokenAccount.delegate);
 
-      assert.equal(ext0.metadata[0].key, "ext0Key");
-      assert.equal(ext1.metadata[0].key, "ext1Key");
+      try {
+        await transferFromAs(marketplace);
+        assert.fail("Should have failed with Unauthorized error");
+      } catch (error) {
+        assert.include(error.message, "Unauthorized");
+      }
     });
   });
 
-  describe("Transfer Agent (Combined Transfer + Sync)", () => {
+  describe("Deregister (Burn Agent NFT)", () => {
     let agentMint: Keypair;
     let agentPda: PublicKey;
-    let newOwner: Keypair;
 
     beforeEach(async () => {
-      // Register an agent
       agentMint = Keypair.generate();
       [agentPda] = getAgentPda(agentMint.publicKey);
 
-      const agentMetadata = getMetadataPda(agentMint.publicKey);
-      const agentMasterEdition = getMasterEditionPda(agentMint.publicKey);
-      const agentTokenAccount = getAssociatedTokenAddressSync(
-        agentMint.publicKey,
-        provider.wallet.publicKey
-      );
-
       await program.methods
-        .register("https://example.com/agent.json")
+        .register("https://example.com/retired.json")
         .accounts({
           config: configPda,
           authority: provider.wallet.publicKey,
           agentAccount: agentPda,
           agentMint: agentMint.publicKey,
-          agentMetadata,
-          agentMasterEdition,
-          agentTokenAccount,
+          agentMetadata: getMetadataPda(agentMint.publicKey),
+          agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
+          agentTokenAccount: getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey),
           collectionMint: collectionMint.publicKey,
           collectionMetadata,
           collectionMasterEdition,
@@ -1780,79 +5552,84 @@ describe("Identity Registry (ERC-8004 Spec Compliant)", () => {
         })
         .signers([agentMint])
         .rpc();
-
-      // Prepare new owner
-      newOwner = Keypair.generate();
-      await provider.connection.confirmTransaction(
-        await provider.connection.requestAirdrop(newOwner.publicKey, 1000000000)
-      );
     });
 
-    it("Transfers agent and syncs owner in one transaction", async () => {
-      const fromTokenAccount = getAssociatedTokenAddressSync(
-        agentMint.publicKey,
-        provider.wallet.publicKey
-      );
-      const toTokenAccount = getAssociatedTokenAddressSync(
-        agentMint.publicKey,
-        newOwner.publicKey
-      );
+    function getAgentIdIndexPda(agentId: anchor.BN): PublicKey {
+      return PublicKey.findProgramAddressSync(
+        [Buffer.from("agent_id"), agentId.toArrayLike(Buffer, "le", 8)],
+        program.programId
+      )[0];
+    }
 
-      // Create destination token account
-      const { createAssociatedTokenAccountInstruction } = await import("@solana/spl-token");
-      const createAtaTx = new anchor.web3.Transaction().add(
-        createAssociatedTokenAccountInstruction(
-          provider.wallet.publicKey,
-          toTokenAccount,
-          newOwner.publicKey,
-          agentMint.publicKey
-        )
-      );
-      await provider.sendAndConfirm(createAtaTx);
+    it("Burns the NFT, closes the agent account and decrements total_agents", async () => {
+      const configBefore = await program.account.registryConfig.fetch(configPda);
+      const ownerTokenAccount = getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey);
+      const { agentId } = await program.account.agentAccount.fetch(agentPda);
+      const agentIdIndex = getAgentIdIndexPda(agentId);
 
-      // Transfer agent (combines SPL transfer + sync_owner)
       await program.methods
-        .transferAgent()
+        .deregister()
         .accounts({
+          config: configPda,
           agentAccount: agentPda,
-          fromTokenAccount,
-          toTokenAccount,
+          agentIdIndex,
+          agentMint: agentMint.publicKey,
+          ownerTokenAccount,
+          agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
           owner: provider.wallet.publicKey,
           tokenProgram: TOKEN_PROGRAM_ID,
         })
         .rpc();
 
-      // Verify ownership changed
-      const agent = await program.account.agentAccount.fetch(agentPda);
-      assert.equal(agent.owner.toBase58(), newOwner.publicKey.toBase58());
+      const configAfter = await program.account.registryConfig.fetch(configPda);
+      assert.equal(
+        configAfter.totalAgents.toNumber(),
+        configBefore.totalAgents.toNumber() - 1
+      );
+      // IDs are never reused
+      assert.equal(configAfter.nextAgentId.toNumber(), configBefore.nextAgentId.toNumber());
+
+      const agentInfo = await provider.connection.getAccountInfo(agentPda);
+      assert.isNull(agentInfo, "Agent account should be closed");
+      assert.isNull(
+        await provider.connection.getAccountInfo(agentIdIndex),
+        "Agent ID index should be closed"
+      );
 
-      // Verify NFT transferred
-      const tokenAccountInfo = await getAccount(provider.connection, toTokenAccount);
-      assert.equal(tokenAccountInfo.amount.toString(), "1");
+      const tokenBalance = await provider.connection.getTokenAccountBalance(ownerTokenAccount);
+      assert.equal(tokenBalance.value.amount, "0");
     });
 
-    it("Fails to transfer to self", async () => {
-      const tokenAccount = getAssociatedTokenAddressSync(
-        agentMint.publicKey,
-        provider.wallet.publicKey
+    it("Fails when caller is not the owner", async () => {
+      const attacker = Keypair.generate();
+      await provider.connection.confirmTransaction(
+        await provider.connection.requestAirdrop(attacker.publicKey, 1000000000)
       );
 
       try {
         await program.methods
-          .transferAgent()
+          .deregister()
           .accounts({
+            config: configPda,
             agentAccount: agentPda,
-            fromTokenAccount: tokenAccount,
-            toTokenAccount: tokenAccount, // Same account
-            owner: provider.wallet.publicKey,
+            agentIdIndex: getAgentIdIndexPda(
+              (await program.account.agentAccount.fetch(agentPda)).agentId
+            ),
+            agentMint: agentMint.publicKey,
+            ownerTokenAccount: getAssociatedTokenAddressSync(agentMint.publicKey, provider.wallet.publicKey),
+            agentMasterEdition: getMasterEditionPda(agentMint.publicKey),
+            owner: attacker.publicKey,
             tokenProgram: TOKEN_PROGRAM_ID,
           })
+          .signers([attacker])
           .rpc();
-
-        assert.fail("Should have failed with TransferToSelf error");
-      } catch (error) {
-        assert.include(error.message, "TransferToSelf");
+        assert.fail("Should have failed");
+      } catch (error: any) {
+        assert.include(error.message, "Unauthorized");
       }
+
+      const agentInfo = await provider.connection.getAccountInfo(agentPda);
+      assert.isNotNull(agentInfo);
     });
   });
 
@@ -1967,7 +5744,7 @@ describe("Identity Registry (ERC-8004 Spec Compliant)", () => {
       );
 
       await program.methods
-        .syncOwner()
+        .syncOwner(false)
         .accounts({
           agentAccount: agentPda,
           tokenAccount: toTokenAccount,
@@ -356,7 +356,7 @@ describe("Identity Registry (ERC-8004 Spec Compliant)", () => {
     });
 
     it("Accepts tokenURI with exactly 200 bytes", async () => {
-      const exactUri = "x".repeat(200);
+      const exactUri = "https://" + "x".repeat(192); // 8 + 192 = 200 bytes
 
       const ix = await program.methods
         .register(exactUri)
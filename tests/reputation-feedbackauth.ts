@@ -124,6 +124,18 @@ describe("Reputation Registry - FeedbackAuth Tests (LOT 1)", () => {
     );
   }
 
+  // Untagged feedback (tag1 all zeros) counts towards the zero-tag counter
+  function getUntaggedReputationPda(agentId: number): [PublicKey, number] {
+    return PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("tag_reputation"),
+        Buffer.from(new anchor.BN(agentId).toArray("le", 8)),
+        Buffer.alloc(32),
+      ],
+      reputationProgram.programId
+    );
+  }
+
   before(async () => {
     console.log("\n🔧 Setting up test environment...\n");
 
@@ -216,7 +228,8 @@ describe("Reputation Registry - FeedbackAuth Tests (LOT 1)", () => {
           fileUri,
           Array.from(fileHash),
           new anchor.BN(feedbackIndex),
-          feedbackAuth
+          feedbackAuth,
+          new anchor.BN(0)
         )
         .accounts({
           client: client1.publicKey,
@@ -268,7 +281,8 @@ describe("Reputation Registry - FeedbackAuth Tests (LOT 1)", () => {
             fileUri,
             Array.from(fileHash),
             new anchor.BN(feedbackIndex),
-            feedbackAuth
+            feedbackAuth,
+            new anchor.BN(0)
           )
           .accounts({
             client: client2.publicKey,
@@ -322,7 +336,8 @@ describe("Reputation Registry - FeedbackAuth Tests (LOT 1)", () => {
             fileUri,
             Array.from(fileHash),
             new anchor.BN(feedbackIndex),
-            feedbackAuth
+            feedbackAuth,
+            new anchor.BN(0)
           )
           .accounts({
             client: client1.publicKey, // client1 signing, but auth is for client2
@@ -374,7 +389,8 @@ describe("Reputation Registry - FeedbackAuth Tests (LOT 1)", () => {
           fileUri1,
           Array.from(fileHash),
           new anchor.BN(0),
-          feedbackAuth
+          feedbackAuth,
+          new anchor.BN(0)
         )
         .accounts({
           client: client2.publicKey,
@@ -405,7 +421,8 @@ describe("Reputation Registry - FeedbackAuth Tests (LOT 1)", () => {
             "ipfs://QmTest4b",
             Array.from(fileHash),
             new anchor.BN(1),
-            feedbackAuth
+            feedbackAuth,
+            new anchor.BN(0)
           )
           .accounts({
             client: client2.publicKey,
@@ -458,7 +475,8 @@ describe("Reputation Registry - FeedbackAuth Tests (LOT 1)", () => {
             fileUri,
             Array.from(fileHash),
             new anchor.BN(feedbackIndex),
-            feedbackAuth
+            feedbackAuth,
+            new anchor.BN(0)
           )
           .accounts({
             client: client1.publicKey,
@@ -481,6 +499,53 @@ describe("Reputation Registry - FeedbackAuth Tests (LOT 1)", () => {
       }
     });
 
+    it("❌ Agent owner cannot review their own agent", async () => {
+      const feedbackAuth = createFeedbackAuth(
+        agentId,
+        agentOwner.publicKey,
+        1,
+        3600,
+        agentOwner.publicKey
+      );
+
+      const [clientIndexPda] = getClientIndexPda(agentId, agentOwner.publicKey);
+      const [feedbackPda] = getFeedbackPda(agentId, agentOwner.publicKey, 0);
+      const [reputationPda] = getAgentReputationPda(agentId);
+
+      try {
+        await reputationProgram.methods
+          .giveFeedback(
+            new anchor.BN(agentId),
+            100,
+            Array.from(Buffer.alloc(32)),
+            Array.from(Buffer.alloc(32)),
+            "ipfs://QmSelfReview",
+            Array.from(Buffer.alloc(32)),
+            new anchor.BN(0),
+            feedbackAuth,
+            new anchor.BN(0)
+          )
+          .accounts({
+            client: agentOwner.publicKey,
+            payer: agentOwner.publicKey,
+            agentMint: agentMint,
+            agentAccount: agentPda,
+            clientIndex: clientIndexPda,
+            feedbackAccount: feedbackPda,
+            agentReputation: reputationPda,
+            identityRegistryProgram: identityProgram.programId,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([agentOwner])
+          .rpc();
+
+        assert.fail("Should have failed with SelfFeedbackNotAllowed");
+      } catch (err: any) {
+        assert.include(err.toString(), "SelfFeedbackNotAllowed");
+        console.log("✅ Correctly rejected self-review by the agent owner");
+      }
+    });
+
     it("✅ Test 6: Multiple clients with independent index limits", async () => {
       // This test demonstrates that different clients can have different limits
       // and their indices are tracked independently
@@ -510,7 +575,8 @@ describe("Reputation Registry - FeedbackAuth Tests (LOT 1)", () => {
           "ipfs://QmTest6",
           Array.from(Buffer.alloc(32)),
           new anchor.BN(1),
-          feedbackAuth1
+          feedbackAuth1,
+          new anchor.BN(0)
         )
         .accounts({
           client: client1.publicKey,
@@ -554,7 +620,8 @@ describe("Reputation Registry - FeedbackAuth Tests (LOT 1)", () => {
           "ipfs://QmTest7a",
           Array.from(Buffer.alloc(32)),
           new anchor.BN(2),
-          feedbackAuth
+          feedbackAuth,
+          new anchor.BN(0)
         )
         .accounts({
           client: client1.publicKey,
@@ -581,7 +648,8 @@ describe("Reputation Registry - FeedbackAuth Tests (LOT 1)", () => {
           "ipfs://QmTest7b",
           Array.from(Buffer.alloc(32)),
           new anchor.BN(3),
-          feedbackAuth
+          feedbackAuth,
+          new anchor.BN(0)
         )
         .accounts({
           client: client1.publicKey,
@@ -625,7 +693,8 @@ describe("Reputation Registry - FeedbackAuth Tests (LOT 1)", () => {
           "ipfs://QmTest8",
           Array.from(Buffer.alloc(32)),
           new anchor.BN(4),
-          feedbackAuth
+          feedbackAuth,
+          new anchor.BN(0)
         )
         .accounts({
           client: client1.publicKey,
@@ -654,7 +723,8 @@ describe("Reputation Registry - FeedbackAuth Tests (LOT 1)", () => {
             "ipfs://QmTest8bad",
             Array.from(Buffer.alloc(32)),
             new anchor.BN(6), // Skipping index 5
-            feedbackAuth
+            feedbackAuth,
+            new anchor.BN(0)
           )
           .accounts({
             client: client1.publicKey,
@@ -677,4 +747,1834 @@ describe("Reputation Registry - FeedbackAuth Tests (LOT 1)", () => {
       }
     });
   });
+
+  describe("Self-Paid vs Sponsored Feedback", () => {
+    // Build a give_feedback transaction for a fresh client at index 0
+    async function buildFeedbackTx(client: Keypair, payerKey: PublicKey) {
+      const feedbackAuth = createFeedbackAuth(agentId, client.publicKey, 1, 3600, agentOwner.publicKey);
+      const [clientIndexPda] = getClientIndexPda(agentId, client.publicKey);
+      const [feedbackPda] = getFeedbackPda(agentId, client.publicKey, 0);
+      const [reputationPda] = getAgentReputationPda(agentId);
+
+      const tx = await reputationProgram.methods
+        .giveFeedback(
+          new anchor.BN(agentId),
+          75,
+          Array.from(Buffer.alloc(32)),
+          Array.from(Buffer.alloc(32)),
+          "ipfs://QmSelfPaid",
+          Array.from(Buffer.alloc(32)),
+          new anchor.BN(0),
+          feedbackAuth,
+          new anchor.BN(0)
+        )
+        .accounts({
+          client: client.publicKey,
+          payer: payerKey,
+          agentMint: agentMint,
+          agentAccount: agentPda,
+          clientIndex: clientIndexPda,
+          feedbackAccount: feedbackPda,
+          agentReputation: reputationPda,
+          identityRegistryProgram: identityProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .transaction();
+
+      tx.feePayer = payerKey;
+      tx.recentBlockhash = (await provider.connection.getLatestBlockhash()).blockhash;
+      return { tx, feedbackPda };
+    }
+
+    it("✅ payer == client needs a single signature", async () => {
+      const client = Keypair.generate();
+      await airdrop(client.publicKey, 2);
+
+      const { tx, feedbackPda } = await buildFeedbackTx(client, client.publicKey);
+
+      // Same pubkey for both Signer accounts collapses into one account meta
+      assert.equal(tx.compileMessage().header.numRequiredSignatures, 1);
+
+      await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [client]);
+
+      const feedback = await reputationProgram.account.feedbackAccount.fetch(feedbackPda);
+      assert.ok(feedback.clientAddress.equals(client.publicKey));
+      console.log("✅ Self-paid feedback submitted with one signature");
+    });
+
+    it("✅ Sponsored feedback needs both client and payer signatures", async () => {
+      const client = Keypair.generate();
+
+      const { tx, feedbackPda } = await buildFeedbackTx(client, payer.publicKey);
+      assert.equal(tx.compileMessage().header.numRequiredSignatures, 2);
+
+      await anchor.web3.sendAndConfirmTransaction(provider.connection, tx, [client, payer]);
+
+      const feedback = await reputationProgram.account.feedbackAccount.fetch(feedbackPda);
+      assert.ok(feedback.clientAddress.equals(client.publicKey));
+      console.log("✅ Sponsored feedback submitted by unfunded client");
+    });
+  });
+
+  describe("Require Live Agent (append_response)", () => {
+    const [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      reputationProgram.programId
+    );
+
+    function getResponseIndexPda(client: PublicKey, feedbackIndex: number): PublicKey {
+      return PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("response_index"),
+          Buffer.from(new anchor.BN(agentId).toArray("le", 8)),
+          client.toBuffer(),
+          Buffer.from(new anchor.BN(feedbackIndex).toArray("le", 8)),
+        ],
+        reputationProgram.programId
+      )[0];
+    }
+
+    function getResponsePda(client: PublicKey, feedbackIndex: number, responseIndex: number): PublicKey {
+      return PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("response"),
+          Buffer.from(new anchor.BN(agentId).toArray("le", 8)),
+          client.toBuffer(),
+          Buffer.from(new anchor.BN(feedbackIndex).toArray("le", 8)),
+          Buffer.from(new anchor.BN(responseIndex).toArray("le", 8)),
+        ],
+        reputationProgram.programId
+      )[0];
+    }
+
+    // Respond to client1's first feedback, optionally proving the agent is live
+    async function appendResponse(responseIndex: number, withAgent: boolean) {
+      const [feedbackPda] = getFeedbackPda(agentId, client1.publicKey, 0);
+
+      return reputationProgram.methods
+        .appendResponse(
+          new anchor.BN(agentId),
+          client1.publicKey,
+          new anchor.BN(0),
+          `ipfs://QmLiveAgent${responseIndex}`,
+          Array.from(Buffer.alloc(32)),
+          new anchor.BN(0)
+        )
+        .accounts({
+          config: configPda,
+          responder: provider.wallet.publicKey,
+          payer: provider.wallet.publicKey,
+          feedbackAccount: feedbackPda,
+          responseIndex: getResponseIndexPda(client1.publicKey, 0),
+          responseAccount: getResponsePda(client1.publicKey, 0, responseIndex),
+          agentMint: withAgent ? agentMint : null,
+          agentAccount: withAgent ? agentPda : null,
+          identityRegistryProgram: withAgent ? identityProgram.programId : null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    async function setRequireLiveAgent(value: boolean) {
+      await reputationProgram.methods
+        .setRequireLiveAgent(value)
+        .accounts({ config: configPda, authority: provider.wallet.publicKey })
+        .rpc();
+    }
+
+    it("✅ Default mode: responses need no agent account", async () => {
+      const config = await reputationProgram.account.reputationConfig.fetch(configPda);
+      assert.isFalse(config.requireLiveAgent);
+
+      const indexAccount = await reputationProgram.account.responseIndexAccount
+        .fetchNullable(getResponseIndexPda(client1.publicKey, 0));
+      const next = indexAccount ? indexAccount.nextIndex.toNumber() : 0;
+
+      await appendResponse(next, false);
+      console.log("✅ Response appended without agent proof");
+    });
+
+    it("✅ Strict mode: responses must prove the agent is live", async () => {
+      await setRequireLiveAgent(true);
+
+      try {
+        const indexAccount = await reputationProgram.account.responseIndexAccount
+          .fetch(getResponseIndexPda(client1.publicKey, 0));
+        const next = indexAccount.nextIndex.toNumber();
+
+        let rejected = false;
+        try {
+          await appendResponse(next, false);
+        } catch (err: any) {
+          rejected = true;
+          assert.include(err.toString(), "AgentNotFound");
+        }
+        assert.isTrue(rejected, "Response without agent account must fail in strict mode");
+
+        await appendResponse(next, true);
+        console.log("✅ Response appended with live agent proof");
+      } finally {
+        await setRequireLiveAgent(false);
+      }
+    });
+  });
+
+  describe("Identity Registry Binding", () => {
+    const [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      reputationProgram.programId
+    );
+
+    it("✅ Config records the Identity Registry program", async () => {
+      const config = await reputationProgram.account.reputationConfig.fetch(configPda);
+      assert.equal(config.identityRegistry.toBase58(), identityProgram.programId.toBase58());
+    });
+
+    it("❌ Agent account under another program is rejected", async () => {
+      const client = Keypair.generate();
+      await airdrop(client.publicKey, 2);
+
+      // Same PDA scheme, but derived under a program that isn't the Identity Registry
+      const spoofProgram = reputationProgram.programId;
+      const [spoofAgentPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("agent"), agentMint.toBuffer()],
+        spoofProgram
+      );
+
+      try {
+        await reputationProgram.methods
+          .giveFeedback(
+            new anchor.BN(agentId),
+            50,
+            Array.from(Buffer.alloc(32)),
+            Array.from(Buffer.alloc(32)),
+            "ipfs://QmSpoof",
+            Array.from(Buffer.alloc(32)),
+            new anchor.BN(0),
+            createFeedbackAuth(agentId, client.publicKey, 1, 3600, agentOwner.publicKey),
+            new anchor.BN(0)
+          )
+          .accounts({
+            client: client.publicKey,
+            payer: client.publicKey,
+            agentMint: agentMint,
+            agentAccount: spoofAgentPda,
+            clientIndex: getClientIndexPda(agentId, client.publicKey)[0],
+            feedbackAccount: getFeedbackPda(agentId, client.publicKey, 0)[0],
+            agentReputation: getAgentReputationPda(agentId)[0],
+            identityRegistryProgram: spoofProgram,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([client])
+          .rpc();
+        assert.fail("Should have rejected the spoofed Identity Registry");
+      } catch (err: any) {
+        assert.match(err.toString(), /AgentNotFound|InvalidIdentityRegistry/);
+      }
+    });
+  });
+
+  describe("Feedback Edit Window", () => {
+    const [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      reputationProgram.programId
+    );
+
+    async function setEditWindow(seconds: number) {
+      await reputationProgram.methods
+        .setEditWindow(new anchor.BN(seconds))
+        .accounts({ config: configPda, authority: provider.wallet.publicKey })
+        .rpc();
+    }
+
+    // Fresh self-paid client with a single feedback at index 0
+    async function giveFreshFeedback(): Promise<Keypair> {
+      const client = Keypair.generate();
+      await airdrop(client.publicKey, 2);
+
+      await reputationProgram.methods
+        .giveFeedback(
+          new anchor.BN(agentId),
+          60,
+          Array.from(Buffer.alloc(32)),
+          Array.from(Buffer.alloc(32)),
+          "ipfs://QmEditWindow",
+          Array.from(Buffer.alloc(32)),
+          new anchor.BN(0),
+          createFeedbackAuth(agentId, client.publicKey, 1, 3600, agentOwner.publicKey),
+          new anchor.BN(0)
+        )
+        .accounts({
+          client: client.publicKey,
+          payer: client.publicKey,
+          agentMint: agentMint,
+          agentAccount: agentPda,
+          clientIndex: getClientIndexPda(agentId, client.publicKey)[0],
+          feedbackAccount: getFeedbackPda(agentId, client.publicKey, 0)[0],
+          agentReputation: getAgentReputationPda(agentId)[0],
+          identityRegistryProgram: identityProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([client])
+        .rpc();
+
+      return client;
+    }
+
+    async function revoke(client: Keypair) {
+      await reputationProgram.methods
+        .revokeFeedback(new anchor.BN(agentId), new anchor.BN(0))
+        .accounts({
+          config: configPda,
+          client: client.publicKey,
+          feedbackAccount: getFeedbackPda(agentId, client.publicKey, 0)[0],
+          agentReputation: getAgentReputationPda(agentId)[0],
+          tag1Reputation: getUntaggedReputationPda(agentId)[0],
+        })
+        .signers([client])
+        .rpc();
+    }
+
+    it("✅ Revoke succeeds within the edit window", async () => {
+      await setEditWindow(3600);
+      try {
+        const client = await giveFreshFeedback();
+        await revoke(client);
+
+        const feedback = await reputationProgram.account.feedbackAccount
+          .fetch(getFeedbackPda(agentId, client.publicKey, 0)[0]);
+        assert.isTrue(feedback.isRevoked);
+      } finally {
+        await setEditWindow(0);
+      }
+    });
+
+    it("❌ Revoke fails once the edit window has closed", async () => {
+      await setEditWindow(1);
+      try {
+        const client = await giveFreshFeedback();
+        await new Promise((resolve) => setTimeout(resolve, 3000));
+
+        try {
+          await revoke(client);
+          assert.fail("Should have failed with EditWindowClosed");
+        } catch (err: any) {
+          assert.include(err.toString(), "EditWindowClosed");
+        }
+      } finally {
+        await setEditWindow(0);
+      }
+    });
+
+    it("❌ Negative edit window is rejected", async () => {
+      try {
+        await setEditWindow(-1);
+        assert.fail("Should have failed with InvalidEditWindow");
+      } catch (err: any) {
+        assert.include(err.toString(), "InvalidEditWindow");
+      }
+    });
+  });
+
+  describe("Update Feedback (In-Place Edit)", () => {
+    const [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      reputationProgram.programId
+    );
+
+    it("✅ Updating 40 → 80 moves the average by the score difference", async () => {
+      const [reputationPda] = getAgentReputationPda(agentId);
+      const client = Keypair.generate();
+      await airdrop(client.publicKey, 2);
+      const [feedbackPda] = getFeedbackPda(agentId, client.publicKey, 0);
+
+      await reputationProgram.methods
+        .giveFeedback(
+          new anchor.BN(agentId),
+          40,
+          Array.from(Buffer.alloc(32)),
+          Array.from(Buffer.alloc(32)),
+          "ipfs://QmUpdateBefore",
+          Array.from(Buffer.alloc(32)),
+          new anchor.BN(0),
+          createFeedbackAuth(agentId, client.publicKey, 1, 3600, agentOwner.publicKey),
+          new anchor.BN(0)
+        )
+        .accounts({
+          client: client.publicKey,
+          payer: client.publicKey,
+          agentMint: agentMint,
+          agentAccount: agentPda,
+          clientIndex: getClientIndexPda(agentId, client.publicKey)[0],
+          feedbackAccount: feedbackPda,
+          agentReputation: reputationPda,
+          identityRegistryProgram: identityProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([client])
+        .rpc();
+
+      const before = await reputationProgram.account.agentReputationMetadata.fetch(reputationPda);
+
+      await reputationProgram.methods
+        .updateFeedback(
+          new anchor.BN(agentId),
+          new anchor.BN(0),
+          80,
+          "ipfs://QmUpdateAfter",
+          Array.from(Buffer.alloc(32, 1))
+        )
+        .accounts({
+          config: configPda,
+          client: client.publicKey,
+          feedbackAccount: feedbackPda,
+          agentReputation: reputationPda,
+        })
+        .signers([client])
+        .rpc();
+
+      const feedback = await reputationProgram.account.feedbackAccount.fetch(feedbackPda);
+      assert.equal(feedback.score, 80);
+      assert.equal(feedback.fileUri, "ipfs://QmUpdateAfter");
+
+      const after = await reputationProgram.account.agentReputationMetadata.fetch(reputationPda);
+      assert.equal(after.totalFeedbacks.toNumber(), before.totalFeedbacks.toNumber());
+      assert.equal(after.totalScoreSum.toNumber(), before.totalScoreSum.toNumber() + 40);
+
+      const count = after.totalFeedbacks.toNumber();
+      const expected = Math.floor((after.totalScoreSum.toNumber() * 2 + count) / (count * 2));
+      assert.equal(after.averageScore, expected);
+      assert.equal(after.averageScoreBps, Math.floor((after.totalScoreSum.toNumber() * 100) / count));
+    });
+
+    it("❌ Another client cannot update the feedback", async () => {
+      const [feedbackPda] = getFeedbackPda(agentId, client1.publicKey, 0);
+
+      try {
+        await reputationProgram.methods
+          .updateFeedback(
+            new anchor.BN(agentId),
+            new anchor.BN(0),
+            10,
+            "ipfs://QmHijack",
+            Array.from(Buffer.alloc(32))
+          )
+          .accounts({
+            config: configPda,
+            client: unauthorized.publicKey,
+            feedbackAccount: feedbackPda,
+            agentReputation: getAgentReputationPda(agentId)[0],
+          })
+          .signers([unauthorized])
+          .rpc();
+        assert.fail("Should have failed: feedback PDA is derived from the signer");
+      } catch (err: any) {
+        assert.include(err.toString(), "ConstraintSeeds");
+      }
+    });
+  });
+
+  describe("Close Feedback / Response (Rent Recovery)", () => {
+    const [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      reputationProgram.programId
+    );
+
+    function getResponseIndexPda(client: PublicKey, feedbackIndex: number): PublicKey {
+      return PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("response_index"),
+          Buffer.from(new anchor.BN(agentId).toArray("le", 8)),
+          client.toBuffer(),
+          Buffer.from(new anchor.BN(feedbackIndex).toArray("le", 8)),
+        ],
+        reputationProgram.programId
+      )[0];
+    }
+
+    function getResponsePda(client: PublicKey, feedbackIndex: number, responseIndex: number): PublicKey {
+      return PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("response"),
+          Buffer.from(new anchor.BN(agentId).toArray("le", 8)),
+          client.toBuffer(),
+          Buffer.from(new anchor.BN(feedbackIndex).toArray("le", 8)),
+          Buffer.from(new anchor.BN(responseIndex).toArray("le", 8)),
+        ],
+        reputationProgram.programId
+      )[0];
+    }
+
+    async function closeFeedback(client: Keypair) {
+      await reputationProgram.methods
+        .closeFeedback(new anchor.BN(agentId), new anchor.BN(0))
+        .accounts({
+          client: client.publicKey,
+          feedbackAccount: getFeedbackPda(agentId, client.publicKey, 0)[0],
+          agentReputation: getAgentReputationPda(agentId)[0],
+        })
+        .signers([client])
+        .rpc();
+    }
+
+    async function closeResponse(responder: Keypair | null, responseIndex: number) {
+      const builder = reputationProgram.methods
+        .closeResponse(
+          new anchor.BN(agentId),
+          client1.publicKey,
+          new anchor.BN(0),
+          new anchor.BN(responseIndex)
+        )
+        .accounts({
+          responder: responder ? responder.publicKey : provider.wallet.publicKey,
+          responseAccount: getResponsePda(client1.publicKey, 0, responseIndex),
+        });
+      await (responder ? builder.signers([responder]) : builder).rpc();
+    }
+
+    it("✅ Revoked feedback closes and returns rent to the author", async () => {
+      const [reputationPda] = getAgentReputationPda(agentId);
+      const client = Keypair.generate();
+      await airdrop(client.publicKey, 2);
+      const [feedbackPda] = getFeedbackPda(agentId, client.publicKey, 0);
+
+      await reputationProgram.methods
+        .giveFeedback(
+          new anchor.BN(agentId),
+          70,
+          Array.from(Buffer.alloc(32)),
+          Array.from(Buffer.alloc(32)),
+          "ipfs://QmCloseMe",
+          Array.from(Buffer.alloc(32)),
+          new anchor.BN(0),
+          createFeedbackAuth(agentId, client.publicKey, 1, 3600, agentOwner.publicKey),
+          new anchor.BN(0)
+        )
+        .accounts({
+          client: client.publicKey,
+          payer: client.publicKey,
+          agentMint: agentMint,
+          agentAccount: agentPda,
+          clientIndex: getClientIndexPda(agentId, client.publicKey)[0],
+          feedbackAccount: feedbackPda,
+          agentReputation: reputationPda,
+          identityRegistryProgram: identityProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([client])
+        .rpc();
+
+      // Guard: live feedback can't be closed
+      try {
+        await closeFeedback(client);
+        assert.fail("Should have failed with FeedbackNotRevoked");
+      } catch (err: any) {
+        assert.include(err.toString(), "FeedbackNotRevoked");
+      }
+
+      await reputationProgram.methods
+        .revokeFeedback(new anchor.BN(agentId), new anchor.BN(0))
+        .accounts({
+          config: configPda,
+          client: client.publicKey,
+          feedbackAccount: feedbackPda,
+          agentReputation: reputationPda,
+          tag1Reputation: getUntaggedReputationPda(agentId)[0],
+        })
+        .signers([client])
+        .rpc();
+
+      const before = await reputationProgram.account.agentReputationMetadata.fetch(reputationPda);
+      const balanceBefore = await provider.connection.getBalance(client.publicKey);
+
+      await closeFeedback(client);
+
+      assert.isNull(await reputationProgram.account.feedbackAccount.fetchNullable(feedbackPda));
+      assert.isAbove(await provider.connection.getBalance(client.publicKey), balanceBefore);
+
+      const after = await reputationProgram.account.agentReputationMetadata.fetch(reputationPda);
+      assert.equal(after.feedbackAccountCount.toNumber(), before.feedbackAccountCount.toNumber() - 1);
+      assert.equal(after.totalFeedbacks.toNumber(), before.totalFeedbacks.toNumber());
+      assert.equal(after.totalScoreSum.toNumber(), before.totalScoreSum.toNumber());
+    });
+
+    it("✅ Responder closes their response; others cannot", async () => {
+      const indexAccount = await reputationProgram.account.responseIndexAccount
+        .fetchNullable(getResponseIndexPda(client1.publicKey, 0));
+      const next = indexAccount ? indexAccount.nextIndex.toNumber() : 0;
+      const responsePda = getResponsePda(client1.publicKey, 0, next);
+
+      await reputationProgram.methods
+        .appendResponse(
+          new anchor.BN(agentId),
+          client1.publicKey,
+          new anchor.BN(0),
+          "ipfs://QmCloseResponse",
+          Array.from(Buffer.alloc(32)),
+          new anchor.BN(0)
+        )
+        .accounts({
+          config: configPda,
+          responder: provider.wallet.publicKey,
+          payer: provider.wallet.publicKey,
+          feedbackAccount: getFeedbackPda(agentId, client1.publicKey, 0)[0],
+          responseIndex: getResponseIndexPda(client1.publicKey, 0),
+          responseAccount: responsePda,
+          agentMint: null,
+          agentAccount: null,
+          identityRegistryProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      try {
+        await closeResponse(unauthorized, next);
+        assert.fail("Should have failed with Unauthorized");
+      } catch (err: any) {
+        assert.include(err.toString(), "Unauthorized");
+      }
+
+      await closeResponse(null, next);
+      assert.isNull(await reputationProgram.account.responseAccount.fetchNullable(responsePda));
+    });
+
+    it("✅ Responder revokes their response; it stays on-chain", async () => {
+      const indexAccount = await reputationProgram.account.responseIndexAccount
+        .fetchNullable(getResponseIndexPda(client1.publicKey, 0));
+      const next = indexAccount ? indexAccount.nextIndex.toNumber() : 0;
+      const responsePda = getResponsePda(client1.publicKey, 0, next);
+
+      await reputationProgram.methods
+        .appendResponse(
+          new anchor.BN(agentId),
+          client1.publicKey,
+          new anchor.BN(0),
+          "ipfs://QmRevokeResponse",
+          Array.from(Buffer.alloc(32)),
+          new anchor.BN(0)
+        )
+        .accounts({
+          config: configPda,
+          responder: provider.wallet.publicKey,
+          payer: provider.wallet.publicKey,
+          feedbackAccount: getFeedbackPda(agentId, client1.publicKey, 0)[0],
+          responseIndex: getResponseIndexPda(client1.publicKey, 0),
+          responseAccount: responsePda,
+          agentMint: null,
+          agentAccount: null,
+          identityRegistryProgram: null,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      async function revokeResponse(responder: Keypair | null) {
+        const builder = reputationProgram.methods
+          .revokeResponse(
+            new anchor.BN(agentId),
+            client1.publicKey,
+            new anchor.BN(0),
+            new anchor.BN(next)
+          )
+          .accounts({
+            responder: responder ? responder.publicKey : provider.wallet.publicKey,
+            responseAccount: responsePda,
+          });
+        await (responder ? builder.signers([responder]) : builder).rpc();
+      }
+
+      try {
+        await revokeResponse(unauthorized);
+        assert.fail("Should have failed with Unauthorized");
+      } catch (err: any) {
+        assert.include(err.toString(), "Unauthorized");
+      }
+
+      await revokeResponse(null);
+      const response = await reputationProgram.account.responseAccount.fetch(responsePda);
+      assert.isTrue(response.isRevoked);
+      assert.equal(response.responseUri, "ipfs://QmRevokeResponse");
+
+      try {
+        await revokeResponse(null);
+        assert.fail("Should have failed with ResponseAlreadyRevoked");
+      } catch (err: any) {
+        assert.include(err.toString(), "ResponseAlreadyRevoked");
+      }
+    });
+
+    it("❌ Missing feedback/response reports FeedbackNotFound/ResponseNotFound", async () => {
+      // client1 never left a feedback at index 99
+      const missingIndex = 99;
+
+      try {
+        await reputationProgram.methods
+          .appendResponse(
+            new anchor.BN(agentId),
+            client1.publicKey,
+            new anchor.BN(missingIndex),
+            "ipfs://QmNoFeedback",
+            Array.from(Buffer.alloc(32)),
+            new anchor.BN(0)
+          )
+          .accounts({
+            config: configPda,
+            responder: provider.wallet.publicKey,
+            payer: provider.wallet.publicKey,
+            feedbackAccount: getFeedbackPda(agentId, client1.publicKey, missingIndex)[0],
+            responseIndex: getResponseIndexPda(client1.publicKey, missingIndex),
+            responseAccount: getResponsePda(client1.publicKey, missingIndex, 0),
+            agentMint: null,
+            agentAccount: null,
+            identityRegistryProgram: null,
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc();
+        assert.fail("Should have failed with FeedbackNotFound");
+      } catch (err: any) {
+        assert.include(err.toString(), "FeedbackNotFound");
+      }
+
+      try {
+        await reputationProgram.methods
+          .revokeFeedback(new anchor.BN(agentId), new anchor.BN(missingIndex))
+          .accounts({
+            config: configPda,
+            client: client1.publicKey,
+            feedbackAccount: getFeedbackPda(agentId, client1.publicKey, missingIndex)[0],
+            agentReputation: getAgentReputationPda(agentId)[0],
+            tag1Reputation: getUntaggedReputationPda(agentId)[0],
+          })
+          .signers([client1])
+          .rpc();
+        assert.fail("Should have failed with FeedbackNotFound");
+      } catch (err: any) {
+        assert.include(err.toString(), "FeedbackNotFound");
+      }
+
+      try {
+        await reputationProgram.methods
+          .revokeResponse(
+            new anchor.BN(agentId),
+            client1.publicKey,
+            new anchor.BN(0),
+            new anchor.BN(999)
+          )
+          .accounts({
+            responder: provider.wallet.publicKey,
+            responseAccount: getResponsePda(client1.publicKey, 0, 999),
+          })
+          .rpc();
+        assert.fail("Should have failed with ResponseNotFound");
+      } catch (err: any) {
+        assert.include(err.toString(), "ResponseNotFound");
+      }
+    });
+  });
+
+  describe("Per-Tag Reputation", () => {
+    function tagBytes(name: string): number[] {
+      const tag = Buffer.alloc(32);
+      tag.write(name);
+      return Array.from(tag);
+    }
+
+    function getTagReputationPda(tag: number[]): PublicKey {
+      return PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("tag_reputation"),
+          Buffer.from(new anchor.BN(agentId).toArray("le", 8)),
+          Buffer.from(tag),
+        ],
+        reputationProgram.programId
+      )[0];
+    }
+
+    async function giveTaggedFeedback(score: number, tag1: number[], tag2: number[]) {
+      const client = Keypair.generate();
+      await airdrop(client.publicKey, 2);
+      const hasTag2 = tag2.some((b) => b !== 0) && Buffer.compare(Buffer.from(tag1), Buffer.from(tag2)) !== 0;
+
+      await reputationProgram.methods
+        .giveFeedback(
+          new anchor.BN(agentId),
+          score,
+          tag1,
+          tag2,
+          "ipfs://QmTagged",
+          Array.from(Buffer.alloc(32)),
+          new anchor.BN(0),
+          createFeedbackAuth(agentId, client.publicKey, 1, 3600, agentOwner.publicKey),
+          new anchor.BN(0)
+        )
+        .accounts({
+          client: client.publicKey,
+          payer: client.publicKey,
+          agentMint: agentMint,
+          agentAccount: agentPda,
+          clientIndex: getClientIndexPda(agentId, client.publicKey)[0],
+          feedbackAccount: getFeedbackPda(agentId, client.publicKey, 0)[0],
+          agentReputation: getAgentReputationPda(agentId)[0],
+          tag1Reputation: getTagReputationPda(tag1),
+          tag2Reputation: hasTag2 ? getTagReputationPda(tag2) : null,
+          identityRegistryProgram: identityProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([client])
+        .rpc();
+    }
+
+    it("✅ Feedback under two tags keeps separate counters", async () => {
+      const suffix = Date.now().toString(36);
+      const translation = tagBytes(`translation-${suffix}`);
+      const coding = tagBytes(`coding-${suffix}`);
+      const none = Array.from(Buffer.alloc(32));
+
+      await giveTaggedFeedback(95, translation, none);
+      await giveTaggedFeedback(85, translation, none);
+      await giveTaggedFeedback(20, coding, translation);
+
+      const translationRep = await reputationProgram.account.tagReputation
+        .fetch(getTagReputationPda(translation));
+      assert.equal(translationRep.count.toNumber(), 3);
+      assert.equal(translationRep.scoreSum.toNumber(), 95 + 85 + 20);
+
+      const codingRep = await reputationProgram.account.tagReputation
+        .fetch(getTagReputationPda(coding));
+      assert.equal(codingRep.agentId.toNumber(), agentId);
+      assert.deepEqual(Array.from(codingRep.tag), coding);
+      assert.equal(codingRep.count.toNumber(), 1);
+      assert.equal(codingRep.scoreSum.toNumber(), 20);
+    });
+
+    it("❌ tag2 set without its tag reputation account is rejected", async () => {
+      const suffix = Date.now().toString(36);
+      try {
+        const client = Keypair.generate();
+        await airdrop(client.publicKey, 2);
+        await reputationProgram.methods
+          .giveFeedback(
+            new anchor.BN(agentId),
+            50,
+            tagBytes(`a-${suffix}`),
+            tagBytes(`b-${suffix}`),
+            "ipfs://QmTagged",
+            Array.from(Buffer.alloc(32)),
+            new anchor.BN(0),
+            createFeedbackAuth(agentId, client.publicKey, 1, 3600, agentOwner.publicKey),
+            new anchor.BN(0)
+          )
+          .accounts({
+            client: client.publicKey,
+            payer: client.publicKey,
+            agentMint: agentMint,
+            agentAccount: agentPda,
+            clientIndex: getClientIndexPda(agentId, client.publicKey)[0],
+            feedbackAccount: getFeedbackPda(agentId, client.publicKey, 0)[0],
+            agentReputation: getAgentReputationPda(agentId)[0],
+            tag1Reputation: getTagReputationPda(tagBytes(`a-${suffix}`)),
+            tag2Reputation: null,
+            identityRegistryProgram: identityProgram.programId,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([client])
+          .rpc();
+        assert.fail("Should have failed with MissingTagReputation");
+      } catch (err: any) {
+        assert.include(err.toString(), "MissingTagReputation");
+      }
+    });
+
+    it("✅ New tags past max_tracked_tags count in the aggregate only", async () => {
+      const [configPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("config")],
+        reputationProgram.programId
+      );
+      const setMaxTrackedTags = (max: number) =>
+        reputationProgram.methods
+          .setMaxTrackedTags(max)
+          .accounts({ config: configPda, authority: provider.wallet.publicKey })
+          .rpc();
+
+      const before = await reputationProgram.account.agentReputationMetadata
+        .fetch(getAgentReputationPda(agentId)[0]);
+      await setMaxTrackedTags(before.trackedTagCount);
+      try {
+        const untracked = tagBytes(`untracked-${Date.now().toString(36)}`);
+        await giveTaggedFeedback(70, untracked, Array.from(Buffer.alloc(32)));
+
+        const info = await provider.connection.getAccountInfo(getTagReputationPda(untracked));
+        assert.isNull(info, "No TagReputation account past the cap");
+
+        const after = await reputationProgram.account.agentReputationMetadata
+          .fetch(getAgentReputationPda(agentId)[0]);
+        assert.equal(after.totalFeedbacks.toNumber(), before.totalFeedbacks.toNumber() + 1);
+        assert.equal(after.trackedTagCount, before.trackedTagCount);
+      } finally {
+        await setMaxTrackedTags(64);
+      }
+    });
+
+    async function giveFeedbackTags(client: Keypair, tags: number[][]) {
+      const [tag1, tag2, ...extraTags] = tags;
+      await reputationProgram.methods
+        .giveFeedbackTags(
+          new anchor.BN(agentId),
+          70,
+          tag1,
+          tag2,
+          "ipfs://QmMultiTagged",
+          Array.from(Buffer.alloc(32)),
+          new anchor.BN(0),
+          createFeedbackAuth(agentId, client.publicKey, 1, 3600, agentOwner.publicKey),
+          new anchor.BN(0),
+          extraTags
+        )
+        .accounts({
+          client: client.publicKey,
+          payer: client.publicKey,
+          agentMint: agentMint,
+          agentAccount: agentPda,
+          clientIndex: getClientIndexPda(agentId, client.publicKey)[0],
+          feedbackAccount: getFeedbackPda(agentId, client.publicKey, 0)[0],
+          agentReputation: getAgentReputationPda(agentId)[0],
+          tag1Reputation: getTagReputationPda(tag1),
+          tag2Reputation: getTagReputationPda(tag2),
+          identityRegistryProgram: identityProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([client])
+        .rpc();
+    }
+
+    it("✅ Five tags round-trip through the feedback account", async () => {
+      const suffix = Date.now().toString(36);
+      const tags = ["domain", "language", "difficulty", "model", "outcome"].map((name) =>
+        tagBytes(`${name}-${suffix}`)
+      );
+      const client = Keypair.generate();
+      await airdrop(client.publicKey, 2);
+
+      await giveFeedbackTags(client, tags);
+
+      const feedback = await reputationProgram.account.feedbackAccount
+        .fetch(getFeedbackPda(agentId, client.publicKey, 0)[0]);
+      assert.deepEqual(Array.from(feedback.tag1), tags[0]);
+      assert.deepEqual(Array.from(feedback.tag2), tags[1]);
+      assert.deepEqual(
+        feedback.extraTags.map((tag: number[]) => Array.from(tag)),
+        tags.slice(2)
+      );
+
+      // Sized for the three extra tags stored, not the six-tag ceiling
+      const FEEDBACK_BASE_SIZE = 382;
+      const info = await provider.connection.getAccountInfo(getFeedbackPda(agentId, client.publicKey, 0)[0]);
+      assert.equal(info!.data.length, FEEDBACK_BASE_SIZE + 3 * 32);
+    });
+
+    it("❌ More than 8 tags are rejected", async () => {
+      const suffix = Date.now().toString(36);
+      const tags = Array.from({ length: 9 }, (_, i) => tagBytes(`t${i}-${suffix}`));
+      const client = Keypair.generate();
+      await airdrop(client.publicKey, 2);
+      try {
+        await giveFeedbackTags(client, tags);
+        assert.fail("Should have failed with TooManyTags");
+      } catch (err: any) {
+        assert.include(err.toString(), "TooManyTags");
+      }
+    });
+  });
+
+  describe("Dispute Feedback (Agent Owner Flag)", () => {
+    const [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      reputationProgram.programId
+    );
+
+    async function dispute(owner: Keypair, client: PublicKey, feedbackIndex: number) {
+      await reputationProgram.methods
+        .disputeFeedback(new anchor.BN(agentId), client, new anchor.BN(feedbackIndex))
+        .accounts({
+          config: configPda,
+          owner: owner.publicKey,
+          agentMint: agentMint,
+          agentAccount: agentPda,
+          feedbackAccount: getFeedbackPda(agentId, client, feedbackIndex)[0],
+          identityRegistryProgram: identityProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+    }
+
+    it("❌ Only the agent owner can dispute", async () => {
+      try {
+        await dispute(unauthorized, client1.publicKey, 0);
+        assert.fail("Should have failed with Unauthorized");
+      } catch (err: any) {
+        assert.include(err.toString(), "Unauthorized");
+      }
+    });
+
+    it("✅ Owner disputes; the score stays counted", async () => {
+      const [reputationPda] = getAgentReputationPda(agentId);
+      const [feedbackPda] = getFeedbackPda(agentId, client1.publicKey, 0);
+      const before = await reputationProgram.account.agentReputationMetadata.fetch(reputationPda);
+
+      await dispute(agentOwner, client1.publicKey, 0);
+
+      const feedback = await reputationProgram.account.feedbackAccount.fetch(feedbackPda);
+      assert.isTrue(feedback.isDisputed);
+
+      const after = await reputationProgram.account.agentReputationMetadata.fetch(reputationPda);
+      assert.equal(after.totalFeedbacks.toNumber(), before.totalFeedbacks.toNumber());
+      assert.equal(after.totalScoreSum.toNumber(), before.totalScoreSum.toNumber());
+      assert.equal(after.averageScore, before.averageScore);
+
+      try {
+        await dispute(agentOwner, client1.publicKey, 0);
+        assert.fail("Should have failed with AlreadyDisputed");
+      } catch (err: any) {
+        assert.include(err.toString(), "AlreadyDisputed");
+      }
+    });
+  });
+
+  describe("Client Blocklist (Agent Owner)", () => {
+    const [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      reputationProgram.programId
+    );
+
+    function ownerAccounts(owner: Keypair) {
+      return {
+        config: configPda,
+        owner: owner.publicKey,
+        agentMint: agentMint,
+        agentAccount: agentPda,
+        identityRegistryProgram: identityProgram.programId,
+      };
+    }
+
+    async function giveFeedbackFrom(client: Keypair) {
+      await reputationProgram.methods
+        .giveFeedback(
+          new anchor.BN(agentId),
+          10,
+          Array.from(Buffer.alloc(32)),
+          Array.from(Buffer.alloc(32)),
+          "ipfs://QmBlocklist",
+          Array.from(Buffer.alloc(32)),
+          new anchor.BN(0),
+          createFeedbackAuth(agentId, client.publicKey, 1, 3600, agentOwner.publicKey),
+          new anchor.BN(0)
+        )
+        .accounts({
+          client: client.publicKey,
+          payer: client.publicKey,
+          agentMint: agentMint,
+          agentAccount: agentPda,
+          clientIndex: getClientIndexPda(agentId, client.publicKey)[0],
+          feedbackAccount: getFeedbackPda(agentId, client.publicKey, 0)[0],
+          agentReputation: getAgentReputationPda(agentId)[0],
+          identityRegistryProgram: identityProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([client])
+        .rpc();
+    }
+
+    it("✅ Block prevents new feedback, unblock restores it", async () => {
+      const harasser = Keypair.generate();
+      await airdrop(harasser.publicKey, 2);
+
+      // Only the agent owner can block
+      try {
+        await reputationProgram.methods
+          .blockClient(new anchor.BN(agentId), harasser.publicKey)
+          .accounts(ownerAccounts(unauthorized))
+          .signers([unauthorized])
+          .rpc();
+        assert.fail("Should have failed with Unauthorized");
+      } catch (err: any) {
+        assert.include(err.toString(), "Unauthorized");
+      }
+
+      await reputationProgram.methods
+        .blockClient(new anchor.BN(agentId), harasser.publicKey)
+        .accounts(ownerAccounts(agentOwner))
+        .signers([agentOwner])
+        .rpc();
+
+      try {
+        await giveFeedbackFrom(harasser);
+        assert.fail("Should have failed with ClientBlocked");
+      } catch (err: any) {
+        assert.include(err.toString(), "ClientBlocked");
+      }
+
+      await reputationProgram.methods
+        .unblockClient(new anchor.BN(agentId), harasser.publicKey)
+        .accounts(ownerAccounts(agentOwner))
+        .signers([agentOwner])
+        .rpc();
+
+      await giveFeedbackFrom(harasser);
+      const feedback = await reputationProgram.account.feedbackAccount.fetch(
+        getFeedbackPda(agentId, harasser.publicKey, 0)[0]
+      );
+      assert.equal(feedback.score, 10);
+    });
+  });
+
+  describe("Feedback Fee (Anti-Sybil)", () => {
+    const [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      reputationProgram.programId
+    );
+
+    async function setFeedbackFee(lamports: number, treasury: PublicKey) {
+      await reputationProgram.methods
+        .setFeedbackFee(new anchor.BN(lamports), treasury)
+        .accounts({ config: configPda, authority: provider.wallet.publicKey })
+        .rpc();
+    }
+
+    async function giveFeedbackFrom(client: Keypair, treasury: PublicKey | null) {
+      await reputationProgram.methods
+        .giveFeedback(
+          new anchor.BN(agentId),
+          70,
+          Array.from(Buffer.alloc(32)),
+          Array.from(Buffer.alloc(32)),
+          "ipfs://QmFeedbackFee",
+          Array.from(Buffer.alloc(32)),
+          new anchor.BN(0),
+          createFeedbackAuth(agentId, client.publicKey, 1, 3600, agentOwner.publicKey),
+          new anchor.BN(0)
+        )
+        .accounts({
+          client: client.publicKey,
+          payer: client.publicKey,
+          agentMint: agentMint,
+          agentAccount: agentPda,
+          clientIndex: getClientIndexPda(agentId, client.publicKey)[0],
+          feedbackAccount: getFeedbackPda(agentId, client.publicKey, 0)[0],
+          agentReputation: getAgentReputationPda(agentId)[0],
+          treasury,
+          identityRegistryProgram: identityProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([client])
+        .rpc();
+    }
+
+    it("✅ Fee is transferred to the treasury, zero fee stays free", async () => {
+      const fee = 5_000_000;
+      const treasury = Keypair.generate();
+      await airdrop(treasury.publicKey, 1);
+
+      await setFeedbackFee(fee, treasury.publicKey);
+      try {
+        const client = Keypair.generate();
+        await airdrop(client.publicKey, 2);
+
+        try {
+          await giveFeedbackFrom(client, null);
+          assert.fail("Should have failed with MissingTreasury");
+        } catch (err: any) {
+          assert.include(err.toString(), "MissingTreasury");
+        }
+
+        try {
+          await giveFeedbackFrom(client, unauthorized.publicKey);
+          assert.fail("Should have failed with InvalidTreasury");
+        } catch (err: any) {
+          assert.include(err.toString(), "InvalidTreasury");
+        }
+
+        const before = await provider.connection.getBalance(treasury.publicKey);
+        await giveFeedbackFrom(client, treasury.publicKey);
+        const after = await provider.connection.getBalance(treasury.publicKey);
+        assert.equal(after - before, fee);
+      } finally {
+        await setFeedbackFee(0, PublicKey.default);
+      }
+
+      // Free again, no treasury needed
+      const freeClient = Keypair.generate();
+      await airdrop(freeClient.publicKey, 2);
+      await giveFeedbackFrom(freeClient, null);
+      const feedback = await reputationProgram.account.feedbackAccount.fetch(
+        getFeedbackPda(agentId, freeClient.publicKey, 0)[0]
+      );
+      assert.equal(feedback.score, 70);
+    });
+
+    it("❌ Rejects a fee below the rent-exempt minimum", async () => {
+      // A smaller transfer to a fresh treasury would fail every give_feedback
+      const minimum = await provider.connection.getMinimumBalanceForRentExemption(0);
+      try {
+        await setFeedbackFee(minimum - 1, Keypair.generate().publicKey);
+        assert.fail("Should have failed with FeedbackFeeBelowRent");
+      } catch (err: any) {
+        assert.include(err.toString(), "FeedbackFeeBelowRent");
+      }
+
+      const config = await reputationProgram.account.reputationConfig.fetch(configPda);
+      assert.equal(config.feedbackFeeLamports.toNumber(), 0);
+    });
+  });
+
+  describe("Min / Max Score (High-Water Marks)", () => {
+    it("✅ Running min/max bracket every live score and the average", async () => {
+      const [reputationPda] = getAgentReputationPda(agentId);
+      const client = Keypair.generate();
+      await airdrop(client.publicKey, 2);
+
+      await reputationProgram.methods
+        .giveFeedback(
+          new anchor.BN(agentId),
+          60,
+          Array.from(Buffer.alloc(32)),
+          Array.from(Buffer.alloc(32)),
+          "ipfs://QmMinMax",
+          Array.from(Buffer.alloc(32)),
+          new anchor.BN(0),
+          createFeedbackAuth(agentId, client.publicKey, 1, 3600, agentOwner.publicKey),
+          new anchor.BN(0)
+        )
+        .accounts({
+          client: client.publicKey,
+          payer: client.publicKey,
+          agentMint: agentMint,
+          agentAccount: agentPda,
+          clientIndex: getClientIndexPda(agentId, client.publicKey)[0],
+          feedbackAccount: getFeedbackPda(agentId, client.publicKey, 0)[0],
+          agentReputation: reputationPda,
+          identityRegistryProgram: identityProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([client])
+        .rpc();
+
+      const reputation = await reputationProgram.account.agentReputationMetadata.fetch(reputationPda);
+      assert.isAtMost(reputation.minScore, 60);
+      assert.isAtLeast(reputation.maxScore, 60);
+      assert.isAtMost(reputation.minScore, reputation.averageScore);
+      assert.isAtLeast(reputation.maxScore, reputation.averageScore);
+    });
+  });
+
+  describe("Empty Reputation Reset", () => {
+    // Dedicated agent so its only feedback can be revoked
+    const emptyAgentId = agentId + 100;
+    let emptyAgentMint: PublicKey;
+    let emptyAgentPda: PublicKey;
+
+    before(async () => {
+      const { nft } = await metaplex.nfts().create({
+        uri: "https://example.com/empty-agent.json",
+        name: "Empty Agent",
+        sellerFeeBasisPoints: 0,
+        updateAuthority: agentOwner,
+      });
+      emptyAgentMint = nft.address;
+      [emptyAgentPda] = getAgentPda(emptyAgentMint);
+
+      await identityProgram.methods
+        .registerAgent(new anchor.BN(emptyAgentId), "Empty Agent", "https://example.com")
+        .accounts({
+          owner: agentOwner.publicKey,
+          agentMint: emptyAgentMint,
+          agentAccount: emptyAgentPda,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([agentOwner])
+        .rpc();
+    });
+
+    it("✅ Revoking the only feedback zeroes every aggregate", async () => {
+      const [reputationPda] = getAgentReputationPda(emptyAgentId);
+      const client = Keypair.generate();
+      await airdrop(client.publicKey, 2);
+
+      await reputationProgram.methods
+        .giveFeedback(
+          new anchor.BN(emptyAgentId),
+          85,
+          Array.from(Buffer.alloc(32)),
+          Array.from(Buffer.alloc(32)),
+          "ipfs://QmOnlyFeedback",
+          Array.from(Buffer.alloc(32)),
+          new anchor.BN(0),
+          createFeedbackAuth(emptyAgentId, client.publicKey, 1, 3600, agentOwner.publicKey),
+          new anchor.BN(0)
+        )
+        .accounts({
+          client: client.publicKey,
+          payer: client.publicKey,
+          agentMint: emptyAgentMint,
+          agentAccount: emptyAgentPda,
+          clientIndex: getClientIndexPda(emptyAgentId, client.publicKey)[0],
+          feedbackAccount: getFeedbackPda(emptyAgentId, client.publicKey, 0)[0],
+          agentReputation: reputationPda,
+          identityRegistryProgram: identityProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([client])
+        .rpc();
+
+      let reputation = await reputationProgram.account.agentReputationMetadata.fetch(reputationPda);
+      assert.equal(reputation.averageScore, 85);
+      assert.equal(reputation.minScore, 85);
+
+      await reputationProgram.methods
+        .revokeFeedback(new anchor.BN(emptyAgentId), new anchor.BN(0))
+        .accounts({
+          client: client.publicKey,
+          feedbackAccount: getFeedbackPda(emptyAgentId, client.publicKey, 0)[0],
+          agentReputation: reputationPda,
+          tag1Reputation: getUntaggedReputationPda(emptyAgentId)[0],
+        })
+        .signers([client])
+        .rpc();
+
+      reputation = await reputationProgram.account.agentReputationMetadata.fetch(reputationPda);
+      assert.equal(reputation.totalFeedbacks.toNumber(), 0);
+      assert.equal(reputation.totalScoreSum.toNumber(), 0);
+      assert.equal(reputation.averageScore, 0);
+      assert.equal(reputation.averageScoreBps, 0);
+      assert.equal(reputation.confidence, 0);
+      assert.equal(reputation.minScore, 0);
+      assert.equal(reputation.maxScore, 0);
+      assert.equal(reputation.trimmedScoreSum.toNumber(), 0);
+      assert.equal(reputation.decayWeightedScoreSum.toNumber(), 0);
+    });
+  });
+
+  describe("Score Histogram (Rating Distribution)", () => {
+    async function getHistogram(): Promise<number[]> {
+      const view = await reputationProgram.methods
+        .getHistogram(new anchor.BN(agentId))
+        .view();
+      return view.buckets.map((count: anchor.BN) => count.toNumber());
+    }
+
+    async function giveScore(score: number): Promise<Keypair> {
+      const client = Keypair.generate();
+      await airdrop(client.publicKey, 2);
+
+      await reputationProgram.methods
+        .giveFeedback(
+          new anchor.BN(agentId),
+          score,
+          Array.from(Buffer.alloc(32)),
+          Array.from(Buffer.alloc(32)),
+          "ipfs://QmHistogram",
+          Array.from(Buffer.alloc(32)),
+          new anchor.BN(0),
+          createFeedbackAuth(agentId, client.publicKey, 1, 3600, agentOwner.publicKey),
+          new anchor.BN(0)
+        )
+        .accounts({
+          client: client.publicKey,
+          payer: client.publicKey,
+          agentMint: agentMint,
+          agentAccount: agentPda,
+          clientIndex: getClientIndexPda(agentId, client.publicKey)[0],
+          feedbackAccount: getFeedbackPda(agentId, client.publicKey, 0)[0],
+          agentReputation: getAgentReputationPda(agentId)[0],
+          identityRegistryProgram: identityProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([client])
+        .rpc();
+
+      return client;
+    }
+
+    it("✅ Scores land in their buckets and revocation removes them", async () => {
+      const before = await getHistogram();
+
+      const clients: Keypair[] = [];
+      for (const score of [5, 15, 19, 55, 90, 100]) {
+        clients.push(await giveScore(score));
+      }
+
+      let after = await getHistogram();
+      const delta = after.map((count, i) => count - before[i]);
+      // 100 counts in the last bucket with 90-99
+      assert.deepEqual(delta, [1, 2, 0, 0, 0, 1, 0, 0, 0, 2]);
+
+      // Revoke the 100
+      const perfect = clients[clients.length - 1];
+      await reputationProgram.methods
+        .revokeFeedback(new anchor.BN(agentId), new anchor.BN(0))
+        .accounts({
+          client: perfect.publicKey,
+          feedbackAccount: getFeedbackPda(agentId, perfect.publicKey, 0)[0],
+          agentReputation: getAgentReputationPda(agentId)[0],
+          tag1Reputation: getUntaggedReputationPda(agentId)[0],
+        })
+        .signers([perfect])
+        .rpc();
+
+      after = await getHistogram();
+      assert.equal(after[9] - before[9], 1);
+    });
+
+    it("❌ Revoke of counted feedback requires the histogram", async () => {
+      const client = await giveScore(42);
+      try {
+        await reputationProgram.methods
+          .revokeFeedback(new anchor.BN(agentId), new anchor.BN(0))
+          .accounts({
+            client: client.publicKey,
+            feedbackAccount: getFeedbackPda(agentId, client.publicKey, 0)[0],
+            agentReputation: getAgentReputationPda(agentId)[0],
+            tag1Reputation: getUntaggedReputationPda(agentId)[0],
+            scoreHistogram: null,
+          })
+          .signers([client])
+          .rpc();
+        assert.fail("Should have failed with MissingScoreHistogram");
+      } catch (err: any) {
+        assert.include(err.toString(), "MissingScoreHistogram");
+      }
+
+      const feedback = await reputationProgram.account.feedbackAccount.fetch(
+        getFeedbackPda(agentId, client.publicKey, 0)[0]
+      );
+      assert.isFalse(feedback.isRevoked);
+    });
+  });
+
+  describe("Per-Agent Reputation Policy", () => {
+    async function setPolicy(policy: number, owner: Keypair) {
+      await reputationProgram.methods
+        .setReputationPolicy(new anchor.BN(agentId), policy)
+        .accounts({
+          owner: owner.publicKey,
+          agentMint: agentMint,
+          agentAccount: agentPda,
+          agentReputation: getAgentReputationPda(agentId)[0],
+          identityRegistryProgram: identityProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([owner])
+        .rpc();
+    }
+
+    it("✅ Owner switches policy and the average follows the running sums", async () => {
+      const [reputationPda] = getAgentReputationPda(agentId);
+
+      try {
+        await setPolicy(1, agentOwner); // Trimmed
+        const reputation = await reputationProgram.account.agentReputationMetadata.fetch(reputationPda);
+        assert.equal(reputation.reputationPolicy, 1);
+
+        const expected = reputation.trimmedFeedbacks.toNumber() > 0
+          ? Math.floor((reputation.trimmedScoreSum.toNumber() * 2 + reputation.trimmedFeedbacks.toNumber())
+              / (reputation.trimmedFeedbacks.toNumber() * 2))
+          : Math.floor((reputation.totalScoreSum.toNumber() * 2 + reputation.totalFeedbacks.toNumber())
+              / (Math.max(reputation.totalFeedbacks.toNumber(), 1) * 2));
+        assert.equal(reputation.averageScore, expected);
+      } finally {
+        await setPolicy(0, agentOwner);
+      }
+    });
+
+    it("❌ Non-owner cannot set the policy", async () => {
+      try {
+        await setPolicy(1, unauthorized);
+        assert.fail("Should have failed with Unauthorized");
+      } catch (err: any) {
+        assert.include(err.toString(), "Unauthorized");
+      }
+    });
+
+    it("❌ Unknown policy is rejected", async () => {
+      try {
+        await setPolicy(3, agentOwner);
+        assert.fail("Should have failed with UnsupportedReputationPolicy");
+      } catch (err: any) {
+        assert.include(err.toString(), "UnsupportedReputationPolicy");
+      }
+    });
+  });
+
+  describe("Minimum Client Balance (Anti-Sybil)", () => {
+    const [configPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      reputationProgram.programId
+    );
+
+    async function setMinClientBalance(lamports: number) {
+      await reputationProgram.methods
+        .setMinClientBalance(new anchor.BN(lamports))
+        .accounts({ config: configPda, authority: provider.wallet.publicKey })
+        .rpc();
+    }
+
+    // Sponsored feedback so the client's own balance is not spent on rent
+    async function giveSponsoredFeedback(client: Keypair) {
+      await reputationProgram.methods
+        .giveFeedback(
+          new anchor.BN(agentId),
+          70,
+          Array.from(Buffer.alloc(32)),
+          Array.from(Buffer.alloc(32)),
+          "ipfs://QmMinBalance",
+          Array.from(Buffer.alloc(32)),
+          new anchor.BN(0),
+          createFeedbackAuth(agentId, client.publicKey, 1, 3600, agentOwner.publicKey),
+          new anchor.BN(0)
+        )
+        .accounts({
+          client: client.publicKey,
+          payer: payer.publicKey,
+          agentMint: agentMint,
+          agentAccount: agentPda,
+          clientIndex: getClientIndexPda(agentId, client.publicKey)[0],
+          feedbackAccount: getFeedbackPda(agentId, client.publicKey, 0)[0],
+          agentReputation: getAgentReputationPda(agentId)[0],
+          identityRegistryProgram: identityProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([client, payer])
+        .rpc();
+    }
+
+    it("❌ Client below the minimum balance is rejected", async () => {
+      await setMinClientBalance(anchor.web3.LAMPORTS_PER_SOL);
+      try {
+        const client = Keypair.generate(); // 0 lamports
+        try {
+          await giveSponsoredFeedback(client);
+          assert.fail("Should have failed with InsufficientClientBalance");
+        } catch (err: any) {
+          assert.include(err.toString(), "InsufficientClientBalance");
+        }
+      } finally {
+        await setMinClientBalance(0);
+      }
+    });
+
+    it("✅ Client above the minimum balance can give feedback", async () => {
+      await setMinClientBalance(anchor.web3.LAMPORTS_PER_SOL);
+      try {
+        const client = Keypair.generate();
+        await airdrop(client.publicKey, 2);
+        await giveSponsoredFeedback(client);
+
+        const feedback = await reputationProgram.account.feedbackAccount
+          .fetch(getFeedbackPda(agentId, client.publicKey, 0)[0]);
+        assert.equal(feedback.score, 70);
+
+        // Balance check only: nothing was taken from the client
+        const balance = await provider.connection.getBalance(client.publicKey);
+        assert.equal(balance, 2 * anchor.web3.LAMPORTS_PER_SOL);
+      } finally {
+        await setMinClientBalance(0);
+      }
+    });
+  });
+
+  describe("Views (get_feedback / get_reputation / get_config)", () => {
+    it("✅ Views return versioned data matching the accounts", async () => {
+      const [feedbackPda] = getFeedbackPda(agentId, client1.publicKey, 0);
+      const [reputationPda] = getAgentReputationPda(agentId);
+
+      // .view() simulates the instruction and decodes its return data
+      const feedback = await reputationProgram.methods
+        .getFeedback(new anchor.BN(agentId), client1.publicKey, new anchor.BN(0))
+        .view();
+      const storedFeedback = await reputationProgram.account.feedbackAccount.fetch(feedbackPda);
+
+      assert.equal(feedback.version, 1);
+      assert.equal(feedback.score, storedFeedback.score);
+      assert.equal(feedback.fileUri, storedFeedback.fileUri);
+      assert.ok(feedback.clientAddress.equals(client1.publicKey));
+
+      const reputation = await reputationProgram.methods
+        .getReputation(new anchor.BN(agentId))
+        .view();
+      const storedReputation = await reputationProgram.account.agentReputationMetadata.fetch(reputationPda);
+
+      assert.equal(reputation.version, 1);
+      assert.equal(reputation.totalFeedbacks.toNumber(), storedReputation.totalFeedbacks.toNumber());
+      assert.equal(reputation.averageScore, storedReputation.averageScore);
+
+      const config = await reputationProgram.methods.getConfig().view();
+      assert.equal(config.version, 1);
+      assert.ok(config.authority.equals(provider.wallet.publicKey));
+    });
+
+    it("✅ Trust score at full feedback weight equals the feedback average", async () => {
+      // The config defaults to the devnet Validation Registry
+      const validationRegistry = anchor.workspace.ValidationRegistry.programId as PublicKey;
+      const [configPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("config")],
+        reputationProgram.programId
+      );
+      await reputationProgram.methods
+        .setValidationRegistry(validationRegistry)
+        .accounts({ config: configPda, authority: provider.wallet.publicKey })
+        .rpc();
+
+      const [reputationPda] = getAgentReputationPda(agentId);
+      const [validationPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("agent_validation"), new anchor.BN(agentId).toArrayLike(Buffer, "le", 8)],
+        validationRegistry
+      );
+
+      const score = await reputationProgram.methods
+        .getTrustScore(new anchor.BN(agentId), 10000)
+        .accounts({ config: configPda, agentReputation: reputationPda, agentValidation: validationPda })
+        .view();
+      const reputation = await reputationProgram.account.agentReputationMetadata.fetch(reputationPda);
+      assert.equal(score, reputation.averageScore);
+
+      try {
+        await reputationProgram.methods
+          .getTrustScore(new anchor.BN(agentId), 10001)
+          .accounts({ config: configPda, agentReputation: reputationPda, agentValidation: validationPda })
+          .view();
+        assert.fail("Should have rejected a weight above 10000 bps");
+      } catch (err: any) {
+        assert.include(err.toString(), "InvalidTrustWeight");
+      }
+    });
+
+    it("✅ Reputation summary reflects two new feedbacks", async () => {
+      const [reputationPda] = getAgentReputationPda(agentId);
+      const before = await reputationProgram.account.agentReputationMetadata.fetch(reputationPda);
+
+      for (const score of [0, 100]) {
+        const client = Keypair.generate();
+        await airdrop(client.publicKey, 2);
+
+        await reputationProgram.methods
+          .giveFeedback(
+            new anchor.BN(agentId),
+            score,
+            Array.from(Buffer.alloc(32)),
+            Array.from(Buffer.alloc(32)),
+            "ipfs://QmSummary",
+            Array.from(Buffer.alloc(32)),
+            new anchor.BN(0),
+            createFeedbackAuth(agentId, client.publicKey, 1, 3600, agentOwner.publicKey),
+            new anchor.BN(0)
+          )
+          .accounts({
+            client: client.publicKey,
+            payer: client.publicKey,
+            agentMint: agentMint,
+            agentAccount: agentPda,
+            clientIndex: getClientIndexPda(agentId, client.publicKey)[0],
+            feedbackAccount: getFeedbackPda(agentId, client.publicKey, 0)[0],
+            agentReputation: reputationPda,
+            identityRegistryProgram: identityProgram.programId,
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([client])
+          .rpc();
+      }
+
+      const summary = await reputationProgram.methods
+        .getReputation(new anchor.BN(agentId))
+        .view();
+      const stored = await reputationProgram.account.agentReputationMetadata.fetch(reputationPda);
+
+      assert.equal(summary.agentId.toNumber(), agentId);
+      assert.equal(summary.totalFeedbacks.toNumber(), before.totalFeedbacks.toNumber() + 2);
+      assert.equal(summary.averageScore, stored.averageScore);
+      assert.equal(summary.minScore, 0);
+      assert.equal(summary.maxScore, 100);
+      assert.equal(summary.lastUpdated.toNumber(), stored.lastUpdated.toNumber());
+    });
+
+    it("✅ Batch read returns summaries in order and flags missing accounts", async () => {
+      const [reputationPda] = getAgentReputationPda(agentId);
+      const [missingPda] = getAgentReputationPda(999_999_999); // never received feedback
+      const [configPda] = PublicKey.findProgramAddressSync(
+        [Buffer.from("config")],
+        reputationProgram.programId
+      ); // not a reputation account
+
+      const summaries = await reputationProgram.methods
+        .getReputationBatch()
+        .remainingAccounts(
+          [reputationPda, missingPda, configPda].map((pubkey) => ({
+            pubkey,
+            isSigner: false,
+            isWritable: false,
+          }))
+        )
+        .view();
+      const stored = await reputationProgram.account.agentReputationMetadata.fetch(reputationPda);
+
+      assert.equal(summaries.length, 3);
+      assert.equal(summaries[0].version, 1);
+      assert.isTrue(summaries[0].found);
+      assert.equal(summaries[0].agentId.toNumber(), agentId);
+      assert.equal(summaries[0].totalFeedbacks.toNumber(), stored.totalFeedbacks.toNumber());
+      assert.equal(summaries[0].averageScore, stored.averageScore);
+      assert.isFalse(summaries[1].found);
+      assert.equal(summaries[1].totalFeedbacks.toNumber(), 0);
+      assert.isFalse(summaries[2].found);
+    });
+
+    it("✅ Decayed reputation drops as time passes without activity", async () => {
+      const stored = await reputationProgram.account.agentReputationMetadata
+        .fetch(getAgentReputationPda(agentId)[0]);
+      assert.isAbove(stored.averageScore, 0);
+
+      // A long half-life barely moves the score
+      const fresh = await reputationProgram.methods
+        .getDecayedReputation(new anchor.BN(agentId), new anchor.BN(365 * 86400))
+        .view();
+      assert.isAtMost(fresh, stored.averageScore);
+      assert.isAtLeast(fresh, stored.averageScore - 1);
+
+      // With a 1s half-life, a few seconds of inactivity decays it heavily
+      await new Promise((resolve) => setTimeout(resolve, 3000));
+      const decayed = await reputationProgram.methods
+        .getDecayedReputation(new anchor.BN(agentId), new anchor.BN(1))
+        .view();
+      assert.isBelow(decayed, stored.averageScore);
+
+      try {
+        await reputationProgram.methods
+          .getDecayedReputation(new anchor.BN(agentId), new anchor.BN(0))
+          .view();
+        assert.fail("Should have failed with InvalidHalfLife");
+      } catch (err: any) {
+        assert.include(err.toString(), "InvalidHalfLife");
+      }
+    });
+  });
+
+  describe("Client Index (agent_id 0)", () => {
+    it("✅ First-ever feedback to agent 0 is accepted at index 0", async function () {
+      // agent_id 0 is the first agent the Identity Registry ever registered
+      const [agentZero] = await identityProgram.account.agentAccount.all([
+        {
+          memcmp: {
+            offset: 8,
+            bytes: anchor.utils.bytes.bs58.encode(new anchor.BN(0).toArrayLike(Buffer, "le", 8)),
+          },
+        },
+      ]);
+      if (!agentZero) {
+        this.skip();
+      }
+
+      const client = Keypair.generate();
+      await airdrop(client.publicKey, 2);
+      const [clientIndexPda] = getClientIndexPda(0, client.publicKey);
+      const [feedbackPda] = getFeedbackPda(0, client.publicKey, 0);
+
+      await reputationProgram.methods
+        .giveFeedback(
+          new anchor.BN(0),
+          70,
+          Array.from(Buffer.alloc(32)),
+          Array.from(Buffer.alloc(32)),
+          "ipfs://QmAgentZero",
+          Array.from(Buffer.alloc(32)),
+          new anchor.BN(0),
+          createFeedbackAuth(0, client.publicKey, 2, 3600, agentZero.account.owner),
+          new anchor.BN(0)
+        )
+        .accounts({
+          client: client.publicKey,
+          payer: client.publicKey,
+          agentMint: agentZero.account.agentMint,
+          agentAccount: agentZero.publicKey,
+          clientIndex: clientIndexPda,
+          feedbackAccount: feedbackPda,
+          agentReputation: getAgentReputationPda(0)[0],
+          identityRegistryProgram: identityProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([client])
+        .rpc();
+
+      const clientIndex = await reputationProgram.account.clientIndexAccount.fetch(clientIndexPda);
+      assert.isTrue(clientIndex.isInitialized);
+      assert.equal(clientIndex.agentId.toNumber(), 0);
+      assert.equal(clientIndex.lastIndex.toNumber(), 1);
+
+      const feedback = await reputationProgram.account.feedbackAccount.fetch(feedbackPda);
+      assert.equal(feedback.feedbackIndex.toNumber(), 0);
+    });
+
+    it("✅ Anyone can recompute agent 0's reputation from its feedback accounts", async function () {
+      const [reputationPda] = getAgentReputationPda(0);
+      const stored = await reputationProgram.account.agentReputationMetadata
+        .fetchNullable(reputationPda);
+      if (!stored) {
+        this.skip();
+      }
+
+      const feedbacks = await reputationProgram.account.feedbackAccount.all([
+        {
+          memcmp: {
+            offset: 8,
+            bytes: anchor.utils.bytes.bs58.encode(new anchor.BN(0).toArrayLike(Buffer, "le", 8)),
+          },
+        },
+      ]);
+      assert.equal(feedbacks.length, stored.feedbackAccountCount.toNumber());
+      // The whole set goes in one transaction: agents with more than
+      // MAX_CONSISTENCY_BATCH (60) feedback accounts can't be recomputed
+      // (BatchTooLarge), see AgentReputationMetadata::is_recomputable
+      assert.isAtMost(feedbacks.length, 60);
+      const accounts = feedbacks.map((feedback) => ({
+        pubkey: feedback.publicKey,
+        isSigner: false,
+        isWritable: false,
+      }));
+
+      const anyone = Keypair.generate();
+      await airdrop(anyone.publicKey, 1);
+
+      // A partial set would under-count: rejected
+      try {
+        await reputationProgram.methods
+          .recomputeReputation(new anchor.BN(0))
+          .accounts({ agentReputation: reputationPda, caller: anyone.publicKey })
+          .remainingAccounts(accounts.slice(1))
+          .signers([anyone])
+          .rpc();
+        assert.fail("Should have rejected an incomplete feedback set");
+      } catch (err: any) {
+        assert.include(err.toString(), "IncompleteFeedbackSet");
+      }
+
+      await reputationProgram.methods
+        .recomputeReputation(new anchor.BN(0))
+        .accounts({ agentReputation: reputationPda, caller: anyone.publicKey })
+        .remainingAccounts(accounts)
+        .signers([anyone])
+        .rpc();
+
+      // The cache can't be corrupted through the program (unit tests cover
+      // repairing a drifted one); recomputing a healthy cache leaves it exact
+      const live = feedbacks.filter((feedback) => !feedback.account.isRevoked);
+      const repaired = await reputationProgram.account.agentReputationMetadata.fetch(reputationPda);
+      assert.equal(repaired.totalFeedbacks.toNumber(), live.length);
+      assert.equal(repaired.totalFeedbacks.toNumber(), stored.totalFeedbacks.toNumber());
+      assert.equal(repaired.totalScoreSum.toNumber(), stored.totalScoreSum.toNumber());
+      assert.equal(repaired.averageScore, stored.averageScore);
+
+      const consistency = await reputationProgram.methods
+        .verifyReputationConsistency(new anchor.BN(0))
+        .accounts({ agentReputation: reputationPda })
+        .remainingAccounts(accounts)
+        .view();
+      assert.isTrue(consistency.isConsistent);
+    });
+  });
 });
@@ -6,7 +6,9 @@ import {
   Keypair,
   PublicKey,
   SystemProgram,
-  LAMPORTS_PER_SOL
+  LAMPORTS_PER_SOL,
+  Transaction,
+  TransactionInstruction,
 } from "@solana/web3.js";
 import {
   TOKEN_PROGRAM_ID,
@@ -46,6 +48,76 @@ export function getValidationRequestPda(
   );
 }
 
+// Helper function: Get validation response history entry PDA
+export function getValidationResponsePda(
+  programId: PublicKey,
+  agentId: number,
+  validatorAddress: PublicKey,
+  nonce: number,
+  responseIndex: number
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("validation_response"),
+      new BN(agentId).toArrayLike(Buffer, "le", 8),
+      validatorAddress.toBuffer(),
+      new BN(nonce).toArrayLike(Buffer, "le", 4),
+      new BN(responseIndex).toArrayLike(Buffer, "le", 2),
+    ],
+    programId
+  );
+}
+
+// Helper function: Get validation note PDA
+export function getValidationNotePda(
+  programId: PublicKey,
+  agentId: number,
+  validatorAddress: PublicKey,
+  nonce: number,
+  noteIndex: number
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("validation_note"),
+      new BN(agentId).toArrayLike(Buffer, "le", 8),
+      validatorAddress.toBuffer(),
+      new BN(nonce).toArrayLike(Buffer, "le", 4),
+      new BN(noteIndex).toArrayLike(Buffer, "le", 4),
+    ],
+    programId
+  );
+}
+
+// Helper function: Get agent validation rollup PDA
+export function getAgentValidationPda(
+  programId: PublicKey,
+  agentId: number
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("agent_validation"), new BN(agentId).toArrayLike(Buffer, "le", 8)],
+    programId
+  );
+}
+
+// Helper function: Get registered validator PDA
+export function getValidatorAccountPda(
+  programId: PublicKey,
+  validator: PublicKey
+): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("validator"), validator.toBuffer()],
+    programId
+  );
+}
+
+// Helper function: Get validator reward pool PDA
+export function getRewardPoolPda(programId: PublicKey): [PublicKey, number] {
+  return PublicKey.findProgramAddressSync(
+    [Buffer.from("reward_pool")],
+    programId
+  );
+}
+
 // Helper function: Get agent account PDA (from Identity Registry)
 export function getAgentAccountPda(
   identityProgramId: PublicKey,
@@ -70,6 +142,9 @@ export function computeHash(content: string): Buffer {
   return crypto.createHash("sha256").update(content).digest();
 }
 
+// Passing threshold the test registry is initialized with (strict: default is 50)
+export const TEST_PASSING_THRESHOLD = 80;
+
 // Helper function: Initialize Validation Registry
 export async function initializeValidationRegistry(
   validationProgram: Program<ValidationRegistry>,
@@ -87,7 +162,7 @@ export async function initializeValidationRegistry(
   }
 
   await validationProgram.methods
-    .initialize(identityProgram.programId)
+    .initialize(identityProgram.programId, TEST_PASSING_THRESHOLD)
     .accounts({
       config: validationConfig,
       authority: provider.wallet.publicKey,
@@ -157,11 +232,14 @@ export async function initializeIdentityRegistry(
     .rpc();
 }
 
-// Helper function: Register an agent in Identity Registry
-export async function registerAgent(
+// Helper function: Build a register_empty instruction for Identity Registry
+// The agent ID is predicted from config.next_agent_id (registration fails
+// atomically if another agent takes it first)
+export async function buildRegisterAgentIx(
   identityProgram: Program<IdentityRegistry>,
   provider: anchor.AnchorProvider
 ): Promise<{
+  ix: TransactionInstruction;
   id: number;
   owner: PublicKey;
   mint: Keypair;
@@ -224,7 +302,7 @@ export async function registerAgent(
   );
 
   // Use provider.wallet as both authority and owner (avoids Metaplex authority mismatch)
-  await identityProgram.methods
+  const ix = await identityProgram.methods
     .registerEmpty()
     .accounts({
       config: configPda,
@@ -245,10 +323,10 @@ export async function registerAgent(
       tokenMetadataProgram: MPL_TOKEN_METADATA_PROGRAM_ID,
       sysvarInstructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
     })
-    .signers([agentMint])
-    .rpc();
+    .instruction();
 
   return {
+    ix,
     id: agentId,
     owner,
     mint: agentMint,
@@ -256,6 +334,23 @@ export async function registerAgent(
   };
 }
 
+// Helper function: Register an agent in Identity Registry
+export async function registerAgent(
+  identityProgram: Program<IdentityRegistry>,
+  provider: anchor.AnchorProvider
+): Promise<{
+  id: number;
+  owner: PublicKey;
+  mint: Keypair;
+  account: PublicKey;
+}> {
+  const { ix, ...agent } = await buildRegisterAgentIx(identityProgram, provider);
+
+  await provider.sendAndConfirm(new Transaction().add(ix), [agent.mint]);
+
+  return agent;
+}
+
 // Helper function: Request validation
 export async function requestValidation(
   validationProgram: Program<ValidationRegistry>,
@@ -269,6 +364,8 @@ export async function requestValidation(
     nonce: number;
     requestUri: string;
     requestHash: Buffer;
+    deadline?: number;
+    validatorAccount?: PublicKey;
   }
 ): Promise<PublicKey> {
   const [validationRequest] = getValidationRequestPda(
@@ -284,7 +381,8 @@ export async function requestValidation(
       config.validatorAddress,
       config.nonce,
       config.requestUri,
-      Array.from(config.requestHash)
+      Array.from(config.requestHash),
+      new BN(config.deadline ?? 0)
     )
     .accounts({
       config: config.validationConfig,
@@ -292,6 +390,7 @@ export async function requestValidation(
       payer: config.agentOwner,
       agentAccount: config.agentAccount,
       validationRequest,
+      validatorAccount: config.validatorAccount ?? null,
       identityRegistryProgram: identityProgram.programId,
       systemProgram: SystemProgram.programId,
     })
@@ -311,6 +410,8 @@ export async function respondToValidation(
     responseUri: string;
     responseHash: Buffer;
     tag: Buffer;
+    rewardPool?: PublicKey;
+    expectedRequestHash?: Buffer;
   }
 ): Promise<void> {
   await validationProgram.methods
@@ -318,12 +419,14 @@ export async function respondToValidation(
       config.response,
       config.responseUri,
       Array.from(config.responseHash),
-      Array.from(config.tag)
+      Array.from(config.tag),
+      Array.from(config.expectedRequestHash ?? Buffer.alloc(32))
     )
     .accounts({
       config: config.validationConfig,
       validator: config.validator.publicKey,
       validationRequest: config.validationRequest,
+      rewardPool: config.rewardPool ?? null,
     })
     .signers([config.validator])
     .rpc();
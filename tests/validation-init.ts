@@ -4,7 +4,11 @@ import { ValidationRegistry } from "../target/types/validation_registry";
 import { IdentityRegistry } from "../target/types/identity_registry";
 import { SystemProgram } from "@solana/web3.js";
 import { assert } from "chai";
-import { getValidationConfigPda, initializeIdentityRegistry } from "./validation-helpers";
+import {
+  getValidationConfigPda,
+  initializeIdentityRegistry,
+  TEST_PASSING_THRESHOLD,
+} from "./validation-helpers";
 
 describe("Validation Registry - Initialization", () => {
   const provider = anchor.AnchorProvider.env();
@@ -21,9 +25,25 @@ describe("Validation Registry - Initialization", () => {
     await initializeIdentityRegistry(identityProgram, provider);
   });
 
+  it("❌ Fail: passing threshold above 100", async () => {
+    try {
+      await validationProgram.methods
+        .initialize(identityProgram.programId, 101)
+        .accounts({
+          config: validationConfig,
+          authority: authority.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Should have failed with InvalidPassingThreshold");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidPassingThreshold");
+    }
+  });
+
   it("✅ Initialize Validation Registry with Identity Registry reference", async () => {
     await validationProgram.methods
-      .initialize(identityProgram.programId)
+      .initialize(identityProgram.programId, TEST_PASSING_THRESHOLD)
       .accounts({
         config: validationConfig,
         authority: authority.publicKey,
@@ -45,6 +65,7 @@ describe("Validation Registry - Initialization", () => {
     );
     assert.equal(config.totalRequests.toNumber(), 0);
     assert.equal(config.totalResponses.toNumber(), 0);
+    assert.equal(config.passingThreshold, TEST_PASSING_THRESHOLD);
 
     console.log("✅ Validation Registry initialized");
     console.log("   Identity Registry:", config.identityRegistry.toBase58());
@@ -53,7 +74,7 @@ describe("Validation Registry - Initialization", () => {
   it("❌ Fail to reinitialize", async () => {
     try {
       await validationProgram.methods
-        .initialize(identityProgram.programId)
+        .initialize(identityProgram.programId, null)
         .accounts({
           config: validationConfig,
           authority: authority.publicKey,
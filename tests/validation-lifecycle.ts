@@ -7,6 +7,7 @@ import { assert } from "chai";
 import {
   getValidationConfigPda,
   getValidationRequestPda,
+  getValidationResponsePda,
   registerAgent,
   computeHash,
   requestValidation,
@@ -287,15 +288,26 @@ describe("Validation Registry - E2E Lifecycle", () => {
     // Get balance before closing
     const balanceBefore = await provider.connection.getBalance(agent1.owner);
 
-    // Close validation account
+    // Close validation account with its response history entry
+    const [responseEntry] = getValidationResponsePda(
+      validationProgram.programId,
+      agent1.id,
+      validator1.publicKey,
+      nonce,
+      0
+    );
     await validationProgram.methods
       .closeValidation()
       .accounts({
+        config: validationConfig,
         authority: agent1.owner,
         validationRequest,
         rentReceiver: agent1.owner,
+        validator: validator1.publicKey,
+        agentAccount: agent1.account,
         identityRegistryProgram: null,
       })
+      .remainingAccounts([{ pubkey: responseEntry, isWritable: true, isSigner: false }])
       .rpc();
 
     // Verify rent was recovered
@@ -310,7 +322,155 @@ describe("Validation Registry - E2E Lifecycle", () => {
       assert.include(err.toString(), "Account does not exist");
     }
 
+    assert.isNull(await provider.connection.getAccountInfo(responseEntry));
+
     console.log(`✅ Validation closed, rent recovered: ${(balanceAfter - balanceBefore) / anchor.web3.LAMPORTS_PER_SOL} SOL`);
+
+    // The nonce can be requested and answered again
+    await requestValidation(validationProgram, identityProgram, {
+      validationConfig,
+      agentId: agent1.id,
+      agentAccount: agent1.account,
+      agentOwner: agent1.owner,
+      validatorAddress: validator1.publicKey,
+      nonce,
+      requestUri,
+      requestHash: computeHash(requestUri),
+    });
+
+    await respondToValidation(validationProgram, {
+      validationConfig,
+      validationRequest,
+      validator: validator1,
+      response: 70,
+      responseUri: "ipfs://QmCloseResp2",
+      responseHash: computeHash("ipfs://QmCloseResp2"),
+      tag: Buffer.alloc(32),
+    });
+
+    const entry = await validationProgram.account.validationResponseEntry.fetch(responseEntry);
+    assert.equal(entry.response, 70);
+  });
+
+  it("❌ Close rejected without the request's response entries", async () => {
+    const nonce = 13;
+    const requestUri = "ipfs://QmCloseMissingEntries";
+
+    const validationRequest = await requestValidation(validationProgram, identityProgram, {
+      validationConfig,
+      agentId: agent1.id,
+      agentAccount: agent1.account,
+      agentOwner: agent1.owner,
+      validatorAddress: validator1.publicKey,
+      nonce,
+      requestUri,
+      requestHash: computeHash(requestUri),
+    });
+
+    await respondToValidation(validationProgram, {
+      validationConfig,
+      validationRequest,
+      validator: validator1,
+      response: 90,
+      responseUri: "ipfs://QmCloseMissingEntriesResp",
+      responseHash: computeHash("ipfs://QmCloseMissingEntriesResp"),
+      tag: Buffer.alloc(32),
+    });
+
+    try {
+      await validationProgram.methods
+        .closeValidation()
+        .accounts({
+          config: validationConfig,
+          authority: agent1.owner,
+          validationRequest,
+          rentReceiver: agent1.owner,
+          validator: validator1.publicKey,
+          agentAccount: agent1.account,
+          identityRegistryProgram: null,
+        })
+        .rpc();
+      assert.fail("Should have failed with ResponseEntriesMismatch");
+    } catch (err) {
+      assert.include(err.toString(), "ResponseEntriesMismatch");
+    }
+  });
+
+  it("❌ Close rejected for a signer that is neither owner nor authority", async () => {
+    const nonce = 11;
+    const requestUri = "ipfs://QmCloseUnauthorized";
+    const stranger = Keypair.generate();
+
+    const validationRequest = await requestValidation(validationProgram, identityProgram, {
+      validationConfig,
+      agentId: agent1.id,
+      agentAccount: agent1.account,
+      agentOwner: agent1.owner,
+      validatorAddress: validator2.publicKey,
+      nonce,
+      requestUri,
+      requestHash: computeHash(requestUri),
+    });
+
+    try {
+      await validationProgram.methods
+        .closeValidation()
+        .accounts({
+          config: validationConfig,
+          authority: stranger.publicKey,
+          validationRequest,
+          rentReceiver: stranger.publicKey,
+          validator: validator2.publicKey,
+          agentAccount: agent1.account,
+          identityRegistryProgram: null,
+        })
+        .signers([stranger])
+        .rpc();
+      assert.fail("Should have failed with UnauthorizedClose");
+    } catch (err) {
+      assert.include(err.toString(), "UnauthorizedClose");
+    }
+
+    // Request is untouched
+    const request = await validationProgram.account.validationRequest.fetch(validationRequest);
+    assert.equal(request.nonce, nonce);
+  });
+
+  it("✅ Requester cancels a pending request; others cannot", async () => {
+    const nonce = 12;
+    const requestUri = "ipfs://QmCancelMe";
+
+    const validationRequest = await requestValidation(validationProgram, identityProgram, {
+      validationConfig,
+      agentId: agent1.id,
+      agentAccount: agent1.account,
+      agentOwner: agent1.owner,
+      validatorAddress: validator2.publicKey,
+      nonce,
+      requestUri,
+      requestHash: computeHash(requestUri),
+    });
+
+    let request = await validationProgram.account.validationRequest.fetch(validationRequest);
+    assert.equal(request.requester.toBase58(), agent1.owner.toBase58());
+
+    try {
+      await validationProgram.methods
+        .cancelValidation()
+        .accounts({ requester: validator2.publicKey, validationRequest })
+        .signers([validator2])
+        .rpc();
+      assert.fail("Should have failed with UnauthorizedCancel");
+    } catch (err) {
+      assert.include(err.toString(), "UnauthorizedCancel");
+    }
+
+    await validationProgram.methods
+      .cancelValidation()
+      .accounts({ requester: agent1.owner, validationRequest })
+      .rpc();
+
+    assert.isNull(await validationProgram.account.validationRequest.fetchNullable(validationRequest));
   });
 
   it("✅ Query validations using getProgramAccounts", async () => {
@@ -341,4 +501,182 @@ describe("Validation Registry - E2E Lifecycle", () => {
 
     console.log(`✅ Found ${agent1Validations.length} validations for agent #${agent1.id}`);
   });
+
+  it("✅ Get pending validations for an agent dashboard", async () => {
+    const pendingNonce = 30;
+    const answeredNonce = 31;
+    const requests: PublicKey[] = [];
+
+    for (const nonce of [pendingNonce, answeredNonce]) {
+      requests.push(
+        await requestValidation(validationProgram, identityProgram, {
+          validationConfig,
+          agentId: agent2.id,
+          agentAccount: agent2.account,
+          agentOwner: agent2.owner,
+          validatorAddress: validator2.publicKey,
+          nonce,
+          requestUri: `ipfs://QmPending${nonce}`,
+          requestHash: computeHash(`ipfs://QmPending${nonce}`),
+        })
+      );
+    }
+
+    await respondToValidation(validationProgram, {
+      validationConfig,
+      validationRequest: requests[1],
+      validator: validator2,
+      response: 90,
+      responseUri: "ipfs://QmAnswered",
+      responseHash: computeHash("ipfs://QmAnswered"),
+      tag: Buffer.alloc(32),
+    });
+
+    const pending = await validationProgram.methods
+      .getPendingValidations(new anchor.BN(agent2.id))
+      .remainingAccounts(
+        requests.map((pubkey) => ({ pubkey, isWritable: false, isSigner: false }))
+      )
+      .view();
+
+    assert.equal(pending.length, 1);
+    assert.equal(pending[0].validatorAddress.toBase58(), validator2.publicKey.toBase58());
+    assert.equal(pending[0].nonce, pendingNonce);
+    assert.isAbove(pending[0].createdAt.toNumber(), 0);
+
+    console.log("✅ Only the unanswered request is reported as pending");
+  });
+
+  it("✅ Same-slot pending requests come back in canonical order", async () => {
+    // Three requests in one transaction share the same created_at
+    const specs = [
+      { validator: validator3.publicKey, nonce: 40 },
+      { validator: validator1.publicKey, nonce: 41 },
+      { validator: validator1.publicKey, nonce: 40 },
+    ];
+
+    const tx = new anchor.web3.Transaction();
+    const requests: PublicKey[] = [];
+    for (const { validator, nonce } of specs) {
+      const [validationRequest] = getValidationRequestPda(
+        validationProgram.programId,
+        agent2.id,
+        validator,
+        nonce
+      );
+      requests.push(validationRequest);
+
+      const requestUri = `ipfs://QmSameSlot${nonce}`;
+      tx.add(
+        await validationProgram.methods
+          .requestValidation(
+            new anchor.BN(agent2.id),
+            validator,
+            nonce,
+            requestUri,
+            Array.from(computeHash(requestUri)),
+            new anchor.BN(0)
+          )
+          .accounts({
+            config: validationConfig,
+            requester: agent2.owner,
+            payer: agent2.owner,
+            agentAccount: agent2.account,
+            validationRequest,
+            identityRegistryProgram: identityProgram.programId,
+            systemProgram: SystemProgram.programId,
+          })
+          .instruction()
+      );
+    }
+    await provider.sendAndConfirm(tx);
+
+    const view = (accounts: PublicKey[]) =>
+      validationProgram.methods
+        .getPendingValidations(new anchor.BN(agent2.id))
+        .remainingAccounts(
+          accounts.map((pubkey) => ({ pubkey, isWritable: false, isSigner: false }))
+        )
+        .view();
+
+    const forward = await view(requests);
+    const backward = await view([...requests].reverse());
+
+    const key = (p: any) => `${p.createdAt.toString()}:${p.validatorAddress.toBase58()}:${p.nonce}`;
+    assert.deepEqual(forward.map(key), backward.map(key));
+
+    // Equal timestamps, ordered by validator address then nonce
+    assert.equal(new Set(forward.map((p: any) => p.createdAt.toString())).size, 1);
+    const sorted = [...forward].sort((a: any, b: any) =>
+      Buffer.compare(a.validatorAddress.toBuffer(), b.validatorAddress.toBuffer()) || a.nonce - b.nonce
+    );
+    assert.deepEqual(forward.map(key), sorted.map(key));
+
+    console.log("✅ Pending order independent of account order");
+  });
+
+  it("✅ State commitment is stable across account ordering", async () => {
+    const agent1Requests = (await validationProgram.account.validationRequest.all())
+      .filter((acc) => acc.account.agentId.toNumber() === agent1.id)
+      .slice(0, 8)
+      .map((acc) => acc.publicKey);
+    assert.isAtLeast(agent1Requests.length, 2);
+
+    const commit = (accounts: PublicKey[]) =>
+      validationProgram.methods
+        .stateCommitment(new anchor.BN(agent1.id))
+        .remainingAccounts(
+          accounts.map((pubkey) => ({ pubkey, isWritable: false, isSigner: false }))
+        )
+        .view();
+
+    const forward = await commit(agent1Requests);
+    const backward = await commit([...agent1Requests].reverse());
+    assert.deepEqual(forward, backward);
+
+    // Dropping an account changes the commitment
+    const partial = await commit(agent1Requests.slice(1));
+    assert.notDeepEqual(forward, partial);
+
+    console.log("✅ Commitment independent of account order");
+  });
+
+  it("✅ Views: get_validation and get_config return versioned data", async () => {
+    const nonce = 50;
+    const requestUri = "ipfs://QmViewRequest";
+
+    await requestValidation(validationProgram, identityProgram, {
+      validationConfig,
+      agentId: agent1.id,
+      agentAccount: agent1.account,
+      agentOwner: agent1.owner,
+      validatorAddress: validator1.publicKey,
+      nonce,
+      requestUri,
+      requestHash: computeHash(requestUri),
+    });
+
+    // .view() runs the instruction through simulateTransaction and decodes the return data
+    const view = await validationProgram.methods
+      .getValidation(new anchor.BN(agent1.id), validator1.publicKey, nonce)
+      .view();
+
+    assert.equal(view.version, 1);
+    assert.equal(view.agentId.toNumber(), agent1.id);
+    assert.equal(view.nonce, nonce);
+    assert.isTrue(view.isPending);
+    assert.deepEqual(view.requestHash, Array.from(computeHash(requestUri)));
+
+    const config = await validationProgram.methods
+      .getConfig()
+      .accounts({ config: validationConfig })
+      .view();
+    const stored = await validationProgram.account.validationConfig.fetch(validationConfig);
+
+    assert.equal(config.version, 1);
+    assert.equal(config.totalRequests.toNumber(), stored.totalRequests.toNumber());
+    assert.equal(config.identityRegistry.toBase58(), stored.identityRegistry.toBase58());
+
+    console.log("✅ Views decoded from simulated return data");
+  });
 });
@@ -2,12 +2,21 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program, BN } from "@coral-xyz/anchor";
 import { ValidationRegistry } from "../target/types/validation_registry";
 import { IdentityRegistry } from "../target/types/identity_registry";
-import { Keypair, PublicKey, SystemProgram, LAMPORTS_PER_SOL } from "@solana/web3.js";
+import {
+  Keypair,
+  PublicKey,
+  SystemProgram,
+  LAMPORTS_PER_SOL,
+  Transaction,
+  ComputeBudgetProgram,
+} from "@solana/web3.js";
 import { assert } from "chai";
 import {
   getValidationConfigPda,
   getValidationRequestPda,
+  getValidatorAccountPda,
   registerAgent,
+  buildRegisterAgentIx,
   computeHash,
   requestValidation,
   initializeIdentityRegistry,
@@ -171,7 +180,8 @@ describe("Validation Registry - Request Validation", () => {
           validator1.publicKey,
           nonce,
           requestUri,
-          Array.from(requestHash)
+          Array.from(requestHash),
+          new BN(0)
         )
         .accounts({
           config: validationConfig,
@@ -213,6 +223,112 @@ describe("Validation Registry - Request Validation", () => {
     }
   });
 
+  it("❌ Fail: Identity Registry account of another type (not AgentAccount)", async () => {
+    const nonce = 4;
+    const requestUri = "ipfs://QmWrongType";
+
+    // Owned by the Identity Registry, but a RegistryConfig rather than an AgentAccount
+    const [identityConfig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      identityProgram.programId
+    );
+
+    try {
+      await requestValidation(validationProgram, identityProgram, {
+        validationConfig,
+        agentId: agent1.id,
+        agentAccount: identityConfig,
+        agentOwner: agent1.owner,
+        validatorAddress: validator1.publicKey,
+        nonce,
+        requestUri,
+        requestHash: computeHash(requestUri),
+      });
+      assert.fail("Should have failed with AgentNotFound");
+    } catch (err) {
+      assert.include(err.toString(), "AgentNotFound");
+      console.log("✅ Wrong account type correctly rejected");
+    }
+  });
+
+  it("✅ Owner resolved through Identity Registry get_owner CPI", async () => {
+    const nonce = 5;
+    const requestUri = "ipfs://QmOwnerViaCpi";
+
+    // The view the validation program calls into
+    const owner = await identityProgram.methods
+      .getOwner(new BN(agent1.id))
+      .accounts({ agentAccount: agent1.account })
+      .view();
+    assert.isTrue(owner.equals(agent1.owner));
+
+    // Agent account holding another agent_id is rejected by the Identity Registry
+    try {
+      await requestValidation(validationProgram, identityProgram, {
+        validationConfig,
+        agentId: agent2.id,
+        agentAccount: agent1.account,
+        agentOwner: agent1.owner,
+        validatorAddress: validator1.publicKey,
+        nonce,
+        requestUri,
+        requestHash: computeHash(requestUri),
+      });
+      assert.fail("Should have failed with AgentIdMismatch");
+    } catch (err) {
+      assert.include(err.toString(), "AgentIdMismatch");
+    }
+
+    // Only the configured Identity Registry may answer the owner lookup
+    const [validationRequest] = getValidationRequestPda(
+      validationProgram.programId,
+      agent1.id,
+      validator1.publicKey,
+      nonce
+    );
+    try {
+      await validationProgram.methods
+        .requestValidation(
+          new BN(agent1.id),
+          validator1.publicKey,
+          nonce,
+          requestUri,
+          Array.from(computeHash(requestUri)),
+          new BN(0)
+        )
+        .accounts({
+          config: validationConfig,
+          requester: agent1.owner,
+          payer: agent1.owner,
+          agentAccount: agent1.account,
+          validationRequest,
+          validatorAccount: null,
+          identityRegistryProgram: SystemProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      assert.fail("Should have failed with InvalidIdentityRegistry");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidIdentityRegistry");
+    }
+
+    // Owner is authorized without the program reading AgentAccount by offset
+    await requestValidation(validationProgram, identityProgram, {
+      validationConfig,
+      agentId: agent1.id,
+      agentAccount: agent1.account,
+      agentOwner: agent1.owner,
+      validatorAddress: validator1.publicKey,
+      nonce,
+      requestUri,
+      requestHash: computeHash(requestUri),
+    });
+    const request = await validationProgram.account.validationRequest.fetch(validationRequest);
+    assert.isTrue(request.requester.equals(agent1.owner));
+
+    console.log("✅ Owner authorized via get_owner CPI");
+  });
+
   it("✅ Multiple validations same agent (different validators)", async () => {
     const nonce = 0;
     const requestUri1 = "ipfs://QmTestValidator1";
@@ -314,4 +430,313 @@ describe("Validation Registry - Request Validation", () => {
 
     console.log(`✅ Counter incremented: ${totalBefore} → ${totalAfter}`);
   });
+
+  it("❌ Fail: duplicate request hash while pending (dedup_by_hash)", async () => {
+    const requestUri = "ipfs://QmSameContent";
+    const requestHash = computeHash(requestUri);
+    const [requestHashIndex] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("request_hash"),
+        new BN(agent2.id).toArrayLike(Buffer, "le", 8),
+        requestHash,
+      ],
+      validationProgram.programId
+    );
+
+    const request = (nonce: number, previousRequest: PublicKey | null) =>
+      validationProgram.methods
+        .requestValidation(new BN(agent2.id), validator1.publicKey, nonce, requestUri, Array.from(requestHash), new BN(0))
+        .accounts({
+          config: validationConfig,
+          requester: agent2.owner,
+          payer: agent2.owner,
+          agentAccount: agent2.account,
+          validationRequest: getValidationRequestPda(
+            validationProgram.programId,
+            agent2.id,
+            validator1.publicKey,
+            nonce
+          )[0],
+          requestHashIndex,
+          previousRequest,
+          identityRegistryProgram: identityProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    await validationProgram.methods
+      .setDedupByHash(true)
+      .accounts({
+        config: validationConfig,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    try {
+      await request(40, null);
+
+      const index = await validationProgram.account.requestHashIndex.fetch(requestHashIndex);
+      const [firstRequest] = getValidationRequestPda(
+        validationProgram.programId,
+        agent2.id,
+        validator1.publicKey,
+        40
+      );
+      assert.equal(index.validationRequest.toBase58(), firstRequest.toBase58());
+
+      try {
+        await request(41, firstRequest);
+        assert.fail("Should have failed with DuplicateRequest");
+      } catch (err) {
+        assert.include(err.toString(), "DuplicateRequest");
+      }
+    } finally {
+      await validationProgram.methods
+        .setDedupByHash(false)
+        .accounts({
+          config: validationConfig,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+    }
+
+    console.log("✅ Identical pending request hash rejected when dedup is enabled");
+  });
+
+  it("✅ Registered validators required only when enabled", async () => {
+    const curated = Keypair.generate();
+    const unlisted = Keypair.generate();
+    const [curatedAccount] = getValidatorAccountPda(validationProgram.programId, curated.publicKey);
+    const requestUri = "ipfs://QmCuratedRequest";
+
+    const request = (validator: PublicKey, validatorAccount?: PublicKey) =>
+      requestValidation(validationProgram, identityProgram, {
+        validationConfig,
+        agentId: agent2.id,
+        agentAccount: agent2.account,
+        agentOwner: agent2.owner,
+        validatorAddress: validator,
+        nonce: 0,
+        requestUri,
+        requestHash: computeHash(requestUri),
+        validatorAccount,
+      });
+
+    const setRequired = (required: boolean) =>
+      validationProgram.methods
+        .setRequireRegisteredValidators(required)
+        .accounts({
+          config: validationConfig,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+
+    await validationProgram.methods
+      .registerValidator("ipfs://QmValidatorProfile")
+      .accounts({
+        config: validationConfig,
+        authority: provider.wallet.publicKey,
+        validator: curated.publicKey,
+        validatorAccount: curatedAccount,
+      })
+      .rpc();
+
+    const registered = await validationProgram.account.validatorAccount.fetch(curatedAccount);
+    assert.equal(registered.validator.toBase58(), curated.publicKey.toBase58());
+    assert.equal(registered.metadataUri, "ipfs://QmValidatorProfile");
+
+    await setRequired(true);
+    try {
+      try {
+        await request(unlisted.publicKey);
+        assert.fail("Should have failed with ValidatorNotRegistered");
+      } catch (err) {
+        assert.include(err.toString(), "ValidatorNotRegistered");
+      }
+
+      await request(curated.publicKey, curatedAccount);
+    } finally {
+      await setRequired(false);
+    }
+
+    // Disabled: any validator can be named
+    await request(unlisted.publicKey);
+  });
+
+  it("✅ Request validation from three validators in one call", async () => {
+    const validators = [Keypair.generate(), Keypair.generate(), Keypair.generate()].map(
+      (kp) => kp.publicKey
+    );
+    const baseNonce = 50;
+    const requestUri = "ipfs://QmMultiValidator";
+    const requestHash = computeHash(requestUri);
+    const requestPdas = validators.map(
+      (validator, i) =>
+        getValidationRequestPda(validationProgram.programId, agent1.id, validator, baseNonce + i)[0]
+    );
+
+    const requestMulti = (keys: PublicKey[], pdas: PublicKey[]) =>
+      validationProgram.methods
+        .requestValidationMulti(
+          new BN(agent1.id),
+          keys,
+          baseNonce,
+          requestUri,
+          Array.from(requestHash),
+          new BN(0)
+        )
+        .accounts({
+          config: validationConfig,
+          requester: agent1.owner,
+          payer: agent1.owner,
+          agentAccount: agent1.account,
+          identityRegistryProgram: identityProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .remainingAccounts(
+          pdas.map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }))
+        )
+        .rpc();
+
+    // A pre-funded request address must not block the batch
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: provider.wallet.publicKey,
+          toPubkey: requestPdas[1],
+          lamports: 1_000,
+        })
+      )
+    );
+
+    await requestMulti(validators, requestPdas);
+
+    for (const [i, pda] of requestPdas.entries()) {
+      const request = await validationProgram.account.validationRequest.fetch(pda);
+      assert.equal(request.agentId.toNumber(), agent1.id);
+      assert.equal(request.validatorAddress.toBase58(), validators[i].toBase58());
+      assert.equal(request.nonce, baseNonce + i);
+      assert.deepEqual(Array.from(request.requestHash), Array.from(requestHash));
+      assert.isTrue(request.requester.equals(agent1.owner));
+    }
+
+    // Cap: at most 8 validators per call
+    const tooMany = Array.from({ length: 9 }, () => Keypair.generate().publicKey);
+    try {
+      await requestMulti(
+        tooMany,
+        tooMany.map(
+          (validator, i) =>
+            getValidationRequestPda(validationProgram.programId, agent1.id, validator, baseNonce + i)[0]
+        )
+      );
+      assert.fail("Should have failed with TooManyValidators");
+    } catch (err) {
+      assert.include(err.toString(), "TooManyValidators");
+    }
+  });
+
+  it("✅ Auto nonce: two requests get nonces 0 then 1", async () => {
+    const validator = Keypair.generate().publicKey;
+    const [nonceCounter] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("val_nonce"),
+        new BN(agent1.id).toArrayLike(Buffer, "le", 8),
+        validator.toBuffer(),
+      ],
+      validationProgram.programId
+    );
+
+    for (const expectedNonce of [0, 1]) {
+      const requestUri = `ipfs://QmAutoNonce${expectedNonce}`;
+      const [validationRequest] = getValidationRequestPda(
+        validationProgram.programId,
+        agent1.id,
+        validator,
+        expectedNonce
+      );
+
+      await validationProgram.methods
+        .requestValidationAuto(
+          new BN(agent1.id),
+          validator,
+          requestUri,
+          Array.from(computeHash(requestUri))
+        )
+        .accounts({
+          config: validationConfig,
+          requester: agent1.owner,
+          payer: agent1.owner,
+          agentAccount: agent1.account,
+          nonceCounter,
+          validationRequest,
+          validatorAccount: null,
+          requestHashIndex: null,
+          previousRequest: null,
+          identityRegistryProgram: identityProgram.programId,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+      const request = await validationProgram.account.validationRequest.fetch(validationRequest);
+      assert.equal(request.nonce, expectedNonce);
+      assert.equal(request.validatorAddress.toBase58(), validator.toBase58());
+      assert.isTrue(request.requester.equals(agent1.owner));
+    }
+
+    const counter = await validationProgram.account.validatorNonceCounter.fetch(nonceCounter);
+    assert.equal(counter.nextNonce, 2);
+  });
+
+  it("✅ Register agent and request validation in one transaction", async () => {
+    const nonce = 0;
+    const requestUri = "ipfs://QmOnboardingValidation";
+    const requestHash = computeHash(requestUri);
+
+    // Agent ID is predicted from config.next_agent_id; if another registration
+    // lands first the whole transaction fails atomically
+    const agent = await buildRegisterAgentIx(identityProgram, provider);
+    const [validationRequest] = getValidationRequestPda(
+      validationProgram.programId,
+      agent.id,
+      validator1.publicKey,
+      nonce
+    );
+
+    const requestIx = await validationProgram.methods
+      .requestValidation(
+        new BN(agent.id),
+        validator1.publicKey,
+        nonce,
+        requestUri,
+        Array.from(requestHash),
+        new BN(0)
+      )
+      .accounts({
+        config: validationConfig,
+        requester: agent.owner,
+        payer: agent.owner,
+        agentAccount: agent.account,
+        validationRequest,
+        identityRegistryProgram: identityProgram.programId,
+        systemProgram: SystemProgram.programId,
+      })
+      .instruction();
+
+    const tx = new Transaction().add(
+      ComputeBudgetProgram.setComputeUnitLimit({ units: 600_000 }),
+      agent.ix,
+      requestIx
+    );
+    await provider.sendAndConfirm(tx, [agent.mint]);
+
+    const agentAccount = await identityProgram.account.agentAccount.fetch(agent.account);
+    assert.equal(agentAccount.agentId.toNumber(), agent.id);
+
+    const request = await validationProgram.account.validationRequest.fetch(validationRequest);
+    assert.equal(request.agentId.toNumber(), agent.id);
+    assert.isTrue(request.respondedAt.toNumber() === 0);
+
+    console.log(`✅ Agent #${agent.id} registered and validation requested atomically`);
+  });
 });
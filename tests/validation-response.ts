@@ -2,17 +2,23 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
 import { ValidationRegistry } from "../target/types/validation_registry";
 import { IdentityRegistry } from "../target/types/identity_registry";
+import { ReputationRegistry } from "../target/types/reputation_registry";
 import { Keypair, PublicKey, LAMPORTS_PER_SOL } from "@solana/web3.js";
 import { assert } from "chai";
 import {
   getValidationConfigPda,
   getValidationRequestPda,
+  getValidationResponsePda,
+  getValidationNotePda,
+  getAgentValidationPda,
+  getRewardPoolPda,
   registerAgent,
   computeHash,
   requestValidation,
   respondToValidation,
   initializeIdentityRegistry,
   initializeValidationRegistry,
+  TEST_PASSING_THRESHOLD,
 } from "./validation-helpers";
 
 describe("Validation Registry - Respond to Validation", () => {
@@ -21,6 +27,7 @@ describe("Validation Registry - Respond to Validation", () => {
 
   const validationProgram = anchor.workspace.ValidationRegistry as Program<ValidationRegistry>;
   const identityProgram = anchor.workspace.IdentityRegistry as Program<IdentityRegistry>;
+  const reputationProgram = anchor.workspace.ReputationRegistry as Program<ReputationRegistry>;
 
   const [validationConfig] = getValidationConfigPda(validationProgram.programId);
 
@@ -119,6 +126,10 @@ describe("Validation Registry - Respond to Validation", () => {
     const responseHash = computeHash(responseUri);
     const tag = Buffer.alloc(32);
 
+    // Pending: status 0
+    const pending = await validationProgram.account.validationRequest.fetch(validationRequest);
+    assert.equal(pending.status, 0);
+
     await respondToValidation(validationProgram, {
       validationConfig,
       validationRequest,
@@ -134,6 +145,8 @@ describe("Validation Registry - Respond to Validation", () => {
     );
 
     assert.equal(request.response, 0);
+    // A genuine score of 0 is distinguishable from pending
+    assert.equal(request.status, 1);
 
     console.log("✅ Response=0 (failed) recorded");
   });
@@ -382,6 +395,8 @@ describe("Validation Registry - Respond to Validation", () => {
       validationRequest
     );
     assert.equal(request.response, 70);
+    assert.equal(request.supersedesRespondedAt.toNumber(), 0);
+    const firstRespondedAt = request.respondedAt.toNumber();
 
     // Update to 95 (agent improved)
     await validationProgram.methods
@@ -389,12 +404,14 @@ describe("Validation Registry - Respond to Validation", () => {
         95,
         "ipfs://QmResponse95",
         Array.from(computeHash("ipfs://QmResponse95")),
+        Array.from(Buffer.alloc(32)),
         Array.from(Buffer.alloc(32))
       )
       .accounts({
         config: validationConfig,
         validator: validator1.publicKey,
         validationRequest,
+        rewardPool: null,
       })
       .signers([validator1])
       .rpc();
@@ -403,7 +420,709 @@ describe("Validation Registry - Respond to Validation", () => {
       validationRequest
     );
     assert.equal(request.response, 95);
+    // The update points back at the response it replaced
+    assert.equal(request.supersedesRespondedAt.toNumber(), firstRespondedAt);
 
     console.log("✅ Progressive validation works (70 → 95)");
   });
+
+  it("✅ Every response is kept as a history entry", async () => {
+    const nonce = 8;
+    const requestUri = "ipfs://QmResponseHistory";
+
+    const validationRequest = await requestValidation(
+      validationProgram,
+      identityProgram,
+      {
+        validationConfig,
+        agentId: agent1.id,
+        agentAccount: agent1.account,
+        agentOwner: agent1.owner,
+        validatorAddress: validator1.publicKey,
+        nonce,
+        requestUri,
+        requestHash: computeHash(requestUri),
+      }
+    );
+
+    const scores = [40, 65, 90];
+    for (const score of scores) {
+      await respondToValidation(validationProgram, {
+        validationConfig,
+        validationRequest,
+        validator: validator1,
+        response: score,
+        responseUri: `ipfs://QmHistory${score}`,
+        responseHash: computeHash(`ipfs://QmHistory${score}`),
+        tag: Buffer.alloc(32),
+      });
+    }
+
+    const request = await validationProgram.account.validationRequest.fetch(
+      validationRequest
+    );
+    assert.equal(request.responseCount, 3);
+    assert.equal(request.response, 90);
+
+    for (let i = 0; i < scores.length; i++) {
+      const [entryPda] = getValidationResponsePda(
+        validationProgram.programId,
+        agent1.id,
+        validator1.publicKey,
+        nonce,
+        i
+      );
+      const entry = await validationProgram.account.validationResponseEntry.fetch(entryPda);
+      assert.equal(entry.responseIndex, i);
+      assert.equal(entry.response, scores[i]);
+      assert.deepEqual(
+        Buffer.from(entry.responseHash),
+        computeHash(`ipfs://QmHistory${scores[i]}`)
+      );
+    }
+  });
+
+  it("✅ Anyone can append notes to a validation", async () => {
+    const nonce = 40;
+    const requestUri = "ipfs://QmNotedRequest";
+
+    await requestValidation(validationProgram, identityProgram, {
+      validationConfig,
+      agentId: agent1.id,
+      agentAccount: agent1.account,
+      agentOwner: agent1.owner,
+      validatorAddress: validator1.publicKey,
+      nonce,
+      requestUri,
+      requestHash: computeHash(requestUri),
+    });
+
+    // Agent owner rebuts, then the validator adds evidence
+    const authors: [Keypair | null, string][] = [
+      [null, "ipfs://QmAgentRebuttal"],
+      [validator1, "ipfs://QmValidatorEvidence"],
+    ];
+    for (const [author, noteUri] of authors) {
+      const authorKey = author ? author.publicKey : provider.wallet.publicKey;
+      const builder = validationProgram.methods
+        .appendValidationNote(
+          new anchor.BN(agent1.id),
+          validator1.publicKey,
+          nonce,
+          noteUri,
+          Array.from(computeHash(noteUri))
+        )
+        .accounts({ author: authorKey, payer: authorKey });
+      await (author ? builder.signers([author]) : builder).rpc();
+    }
+
+    const notePdas = [0, 1].map(
+      (i) => getValidationNotePda(validationProgram.programId, agent1.id, validator1.publicKey, nonce, i)[0]
+    );
+    assert.notEqual(notePdas[0].toBase58(), notePdas[1].toBase58());
+
+    for (let i = 0; i < authors.length; i++) {
+      const note = await validationProgram.account.validationNote.fetch(notePdas[i]);
+      const [author, noteUri] = authors[i];
+      assert.equal(note.noteIndex, i);
+      assert.equal(note.noteUri, noteUri);
+      assert.equal(
+        note.author.toBase58(),
+        (author ? author.publicKey : provider.wallet.publicKey).toBase58()
+      );
+    }
+
+    try {
+      await validationProgram.methods
+        .appendValidationNote(
+          new anchor.BN(agent1.id),
+          validator1.publicKey,
+          nonce,
+          "ipfs://" + "a".repeat(200),
+          Array.from(Buffer.alloc(32))
+        )
+        .accounts({ author: provider.wallet.publicKey, payer: provider.wallet.publicKey })
+        .rpc();
+      assert.fail("Should have failed with NoteUriTooLong");
+    } catch (err: any) {
+      assert.include(err.toString(), "NoteUriTooLong");
+    }
+  });
+
+  it("✅ Validator responds before the deadline", async () => {
+    const nonce = 9;
+    const requestUri = "ipfs://QmRequestDeadlineOpen";
+
+    const validationRequest = await requestValidation(
+      validationProgram,
+      identityProgram,
+      {
+        validationConfig,
+        agentId: agent1.id,
+        agentAccount: agent1.account,
+        agentOwner: agent1.owner,
+        validatorAddress: validator1.publicKey,
+        nonce,
+        requestUri,
+        requestHash: computeHash(requestUri),
+        deadline: Math.floor(Date.now() / 1000) + 3600,
+      }
+    );
+
+    await respondToValidation(validationProgram, {
+      validationConfig,
+      validationRequest,
+      validator: validator1,
+      response: 80,
+      responseUri: "ipfs://QmOnTime",
+      responseHash: computeHash("ipfs://QmOnTime"),
+      tag: Buffer.alloc(32),
+    });
+
+    const request = await validationProgram.account.validationRequest.fetch(validationRequest);
+    assert.equal(request.response, 80);
+  });
+
+  it("❌ Fail: response after the deadline", async () => {
+    const nonce = 10;
+    const requestUri = "ipfs://QmRequestDeadlinePassed";
+
+    const validationRequest = await requestValidation(
+      validationProgram,
+      identityProgram,
+      {
+        validationConfig,
+        agentId: agent1.id,
+        agentAccount: agent1.account,
+        agentOwner: agent1.owner,
+        validatorAddress: validator1.publicKey,
+        nonce,
+        requestUri,
+        requestHash: computeHash(requestUri),
+        deadline: Math.floor(Date.now() / 1000) + 1,
+      }
+    );
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    try {
+      await respondToValidation(validationProgram, {
+        validationConfig,
+        validationRequest,
+        validator: validator1,
+        response: 80,
+        responseUri: "ipfs://QmTooLate",
+        responseHash: computeHash("ipfs://QmTooLate"),
+        tag: Buffer.alloc(32),
+      });
+      assert.fail("Should have failed with ValidationExpired");
+    } catch (err) {
+      assert.include(err.toString(), "ValidationExpired");
+    }
+  });
+
+  it("✅ Expiry sweep stores Expired and skips requests still open", async () => {
+    const request = async (nonce: number, deadline: number) => {
+      const requestUri = `ipfs://QmExpirySweep${nonce}`;
+      return requestValidation(validationProgram, identityProgram, {
+        validationConfig,
+        agentId: agent1.id,
+        agentAccount: agent1.account,
+        agentOwner: agent1.owner,
+        validatorAddress: validator1.publicKey,
+        nonce,
+        requestUri,
+        requestHash: computeHash(requestUri),
+        deadline,
+      });
+    };
+
+    const now = Math.floor(Date.now() / 1000);
+    const stale = await request(41, now + 1);
+    const open = await request(42, now + 3600);
+
+    await new Promise((resolve) => setTimeout(resolve, 3000));
+
+    await validationProgram.methods
+      .expireValidationsBatch()
+      .accounts({
+        config: validationConfig,
+        authority: provider.wallet.publicKey,
+        rentReceiver: provider.wallet.publicKey,
+      })
+      .remainingAccounts(
+        [stale, open].map((pubkey) => ({ pubkey, isSigner: false, isWritable: true }))
+      )
+      .rpc();
+
+    const staleRequest = await validationProgram.account.validationRequest.fetch(stale);
+    assert.equal(staleRequest.status, 3, "Expired is stored");
+    const openRequest = await validationProgram.account.validationRequest.fetch(open);
+    assert.equal(openRequest.status, 0, "Request before its deadline is skipped");
+  });
+
+  it("❌ Fail: expected request hash doesn't match the request", async () => {
+    const nonce = 11;
+    const requestUri = "ipfs://QmRequestHashBinding";
+    const requestHash = computeHash(requestUri);
+
+    const validationRequest = await requestValidation(
+      validationProgram,
+      identityProgram,
+      {
+        validationConfig,
+        agentId: agent1.id,
+        agentAccount: agent1.account,
+        agentOwner: agent1.owner,
+        validatorAddress: validator1.publicKey,
+        nonce,
+        requestUri,
+        requestHash,
+      }
+    );
+
+    try {
+      await respondToValidation(validationProgram, {
+        validationConfig,
+        validationRequest,
+        validator: validator1,
+        response: 100,
+        responseUri: "ipfs://QmWrongRequest",
+        responseHash: computeHash("ipfs://QmWrongRequest"),
+        tag: Buffer.alloc(32),
+        expectedRequestHash: computeHash("ipfs://QmSomeOtherRequest"),
+      });
+      assert.fail("Should have failed with RequestHashMismatch");
+    } catch (err) {
+      assert.include(err.toString(), "RequestHashMismatch");
+    }
+
+    // The matching hash is accepted
+    await respondToValidation(validationProgram, {
+      validationConfig,
+      validationRequest,
+      validator: validator1,
+      response: 100,
+      responseUri: "ipfs://QmRightRequest",
+      responseHash: computeHash("ipfs://QmRightRequest"),
+      tag: Buffer.alloc(32),
+      expectedRequestHash: requestHash,
+    });
+  });
+
+  it("✅ Agent validation rollup tracks requests and responses", async () => {
+    // Fresh agent so the rollup starts empty
+    const agent = await registerAgent(identityProgram, provider);
+    const [agentValidation] = getAgentValidationPda(validationProgram.programId, agent.id);
+
+    const requests = [];
+    for (const nonce of [0, 1]) {
+      const requestUri = `ipfs://QmRollup${nonce}`;
+      requests.push(
+        await requestValidation(validationProgram, identityProgram, {
+          validationConfig,
+          agentId: agent.id,
+          agentAccount: agent.account,
+          agentOwner: agent.owner,
+          validatorAddress: validator1.publicKey,
+          nonce,
+          requestUri,
+          requestHash: computeHash(requestUri),
+        })
+      );
+    }
+
+    let rollup = await validationProgram.account.agentValidationMetadata.fetch(agentValidation);
+    assert.equal(rollup.agentId.toNumber(), agent.id);
+    assert.equal(rollup.totalRequests.toNumber(), 2);
+    assert.equal(rollup.totalResponses.toNumber(), 0);
+
+    for (const [i, response] of [90, 30].entries()) {
+      await respondToValidation(validationProgram, {
+        validationConfig,
+        validationRequest: requests[i],
+        validator: validator1,
+        response,
+        responseUri: `ipfs://QmRollupResponse${i}`,
+        responseHash: computeHash(`ipfs://QmRollupResponse${i}`),
+        tag: Buffer.alloc(32),
+      });
+    }
+
+    rollup = await validationProgram.account.agentValidationMetadata.fetch(agentValidation);
+    assert.equal(rollup.totalRequests.toNumber(), 2);
+    assert.equal(rollup.totalResponses.toNumber(), 2);
+    assert.equal(rollup.sumResponses.toNumber(), 120);
+    assert.equal(rollup.passingCount.toNumber(), 1);
+    assert.isAbove(rollup.lastUpdated.toNumber(), 0);
+  });
+
+  it("✅ Passing is judged against the registry's threshold", async () => {
+    const config = await validationProgram.account.validationConfig.fetch(validationConfig);
+    assert.equal(config.passingThreshold, TEST_PASSING_THRESHOLD);
+
+    // Fresh agent so the rollup starts empty
+    const agent = await registerAgent(identityProgram, provider);
+    const [agentValidation] = getAgentValidationPda(validationProgram.programId, agent.id);
+
+    const requestUri = "ipfs://QmStrictThreshold";
+    const validationRequest = await requestValidation(validationProgram, identityProgram, {
+      validationConfig,
+      agentId: agent.id,
+      agentAccount: agent.account,
+      agentOwner: agent.owner,
+      validatorAddress: validator1.publicKey,
+      nonce: 0,
+      requestUri,
+      requestHash: computeHash(requestUri),
+    });
+
+    // 70 would pass under the default 50, not under 80
+    await respondToValidation(validationProgram, {
+      validationConfig,
+      validationRequest,
+      validator: validator1,
+      response: 70,
+      responseUri: "ipfs://QmStrictResponse",
+      responseHash: computeHash("ipfs://QmStrictResponse"),
+      tag: Buffer.alloc(32),
+    });
+
+    const rollup = await validationProgram.account.agentValidationMetadata.fetch(agentValidation);
+    assert.equal(rollup.totalResponses.toNumber(), 1);
+    assert.equal(rollup.passingCount.toNumber(), 0);
+  });
+
+  it("❌ Fail: zero tag when response tag is required", async () => {
+    const nonce = 20;
+    const requestUri = "ipfs://QmRequireTag";
+
+    const validationRequest = await requestValidation(
+      validationProgram,
+      identityProgram,
+      {
+        validationConfig,
+        agentId: agent1.id,
+        agentAccount: agent1.account,
+        agentOwner: agent1.owner,
+        validatorAddress: validator1.publicKey,
+        nonce,
+        requestUri,
+        requestHash: computeHash(requestUri),
+      }
+    );
+
+    await validationProgram.methods
+      .setRequireResponseTag(true)
+      .accounts({
+        config: validationConfig,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    try {
+      await respondToValidation(validationProgram, {
+        validationConfig,
+        validationRequest,
+        validator: validator1,
+        response: 80,
+        responseUri: "ipfs://QmNoTag",
+        responseHash: computeHash("ipfs://QmNoTag"),
+        tag: Buffer.alloc(32),
+      });
+      assert.fail("Should have failed with MissingResponseTag");
+    } catch (err) {
+      assert.include(err.toString(), "MissingResponseTag");
+    } finally {
+      await validationProgram.methods
+        .setRequireResponseTag(false)
+        .accounts({
+          config: validationConfig,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+    }
+
+    // Flag off: the same zero-tag response is accepted
+    await respondToValidation(validationProgram, {
+      validationConfig,
+      validationRequest,
+      validator: validator1,
+      response: 80,
+      responseUri: "ipfs://QmNoTag",
+      responseHash: computeHash("ipfs://QmNoTag"),
+      tag: Buffer.alloc(32),
+    });
+
+    const request = await validationProgram.account.validationRequest.fetch(
+      validationRequest
+    );
+    assert.equal(request.response, 80);
+
+    console.log("✅ Zero tag rejected when required, accepted when not");
+  });
+
+  it("❌ Fail: updates beyond max_responses_per_request", async () => {
+    const nonce = 21;
+    const requestUri = "ipfs://QmResponseLimit";
+
+    const validationRequest = await requestValidation(
+      validationProgram,
+      identityProgram,
+      {
+        validationConfig,
+        agentId: agent1.id,
+        agentAccount: agent1.account,
+        agentOwner: agent1.owner,
+        validatorAddress: validator1.publicKey,
+        nonce,
+        requestUri,
+        requestHash: computeHash(requestUri),
+      }
+    );
+
+    const configBefore = await validationProgram.account.validationConfig.fetch(
+      validationConfig
+    );
+
+    await validationProgram.methods
+      .setMaxResponsesPerRequest(2)
+      .accounts({
+        config: validationConfig,
+        authority: provider.wallet.publicKey,
+      })
+      .rpc();
+
+    try {
+      for (const response of [60, 75]) {
+        await respondToValidation(validationProgram, {
+          validationConfig,
+          validationRequest,
+          validator: validator1,
+          response,
+          responseUri: `ipfs://QmResponse${response}`,
+          responseHash: computeHash(`ipfs://QmResponse${response}`),
+          tag: Buffer.alloc(32),
+        });
+      }
+
+      try {
+        await respondToValidation(validationProgram, {
+          validationConfig,
+          validationRequest,
+          validator: validator1,
+          response: 90,
+          responseUri: "ipfs://QmResponse90",
+          responseHash: computeHash("ipfs://QmResponse90"),
+          tag: Buffer.alloc(32),
+        });
+        assert.fail("Should have failed with ResponseHistoryLimitReached");
+      } catch (err) {
+        assert.include(err.toString(), "ResponseHistoryLimitReached");
+      }
+    } finally {
+      await validationProgram.methods
+        .setMaxResponsesPerRequest(configBefore.maxResponsesPerRequest)
+        .accounts({
+          config: validationConfig,
+          authority: provider.wallet.publicKey,
+        })
+        .rpc();
+    }
+
+    // Latest cached response is still readable
+    const request = await validationProgram.account.validationRequest.fetch(
+      validationRequest
+    );
+    assert.equal(request.response, 75);
+    assert.equal(request.responseCount, 2);
+
+    console.log("✅ Response limit enforced, latest response preserved");
+  });
+
+  it("✅ Reward pool pays validators until depleted", async () => {
+    const [rewardPool] = getRewardPoolPda(validationProgram.programId);
+    const reward = 0.1 * LAMPORTS_PER_SOL;
+
+    await validationProgram.methods
+      .setPerResponseReward(new anchor.BN(reward))
+      .accounts({ config: validationConfig, authority: provider.wallet.publicKey })
+      .rpc();
+
+    // Fund exactly one reward
+    await validationProgram.methods
+      .fundRewardPool(new anchor.BN(reward))
+      .accounts({ rewardPool, funder: provider.wallet.publicKey })
+      .rpc();
+
+    const poolBefore = await validationProgram.account.rewardPool.fetch(rewardPool);
+
+    const respond = async (nonce: number) => {
+      const requestUri = `ipfs://QmRewarded${nonce}`;
+      const validationRequest = await requestValidation(validationProgram, identityProgram, {
+        validationConfig,
+        agentId: agent1.id,
+        agentAccount: agent1.account,
+        agentOwner: agent1.owner,
+        validatorAddress: validator2.publicKey,
+        nonce,
+        requestUri,
+        requestHash: computeHash(requestUri),
+      });
+
+      const balanceBefore = await provider.connection.getBalance(validator2.publicKey);
+      await respondToValidation(validationProgram, {
+        validationConfig,
+        validationRequest,
+        validator: validator2,
+        response: 80,
+        responseUri: `ipfs://QmRewardedResponse${nonce}`,
+        responseHash: computeHash(`ipfs://QmRewardedResponse${nonce}`),
+        tag: Buffer.alloc(32),
+        rewardPool,
+      });
+      // Validator pays the transaction fee (5000 lamports, single signature)
+      return (await provider.connection.getBalance(validator2.publicKey)) - balanceBefore + 5000;
+    };
+
+    try {
+      // Funded: the pool covers the reward
+      assert.equal(await respond(30), reward);
+      let pool = await validationProgram.account.rewardPool.fetch(rewardPool);
+      assert.equal(pool.poolBalance.toNumber(), poolBefore.poolBalance.toNumber() - reward);
+      assert.equal(pool.totalPaid.toNumber(), poolBefore.totalPaid.toNumber() + reward);
+
+      // Depleted: the response still succeeds, without a payout
+      if (pool.poolBalance.toNumber() < reward) {
+        assert.equal(await respond(31), 0);
+        pool = await validationProgram.account.rewardPool.fetch(rewardPool);
+        assert.equal(pool.totalPaid.toNumber(), poolBefore.totalPaid.toNumber() + reward);
+      }
+    } finally {
+      await validationProgram.methods
+        .setPerResponseReward(new anchor.BN(0))
+        .accounts({ config: validationConfig, authority: provider.wallet.publicKey })
+        .rpc();
+    }
+
+    console.log("✅ Reward paid from funded pool, skipped once depleted");
+  });
+
+  it("✅ Passing response is attested as reputation feedback", async () => {
+    const reputationPda = (...seeds: Buffer[]) =>
+      PublicKey.findProgramAddressSync(seeds, reputationProgram.programId)[0];
+    const [reputationConfig] = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      reputationProgram.programId
+    );
+
+    try {
+      await reputationProgram.account.reputationConfig.fetch(reputationConfig);
+    } catch {
+      await reputationProgram.methods
+        .initialize(identityProgram.programId)
+        .accounts({ config: reputationConfig, authority: provider.wallet.publicKey })
+        .rpc();
+    }
+
+    await validationProgram.methods
+      .setReputationRegistry(reputationProgram.programId)
+      .accounts({ config: validationConfig, authority: provider.wallet.publicKey })
+      .rpc();
+
+    try {
+      // Fresh agent so its reputation only holds the attested feedback
+      const agent = await registerAgent(identityProgram, provider);
+      const agentIdBytes = new anchor.BN(agent.id).toArrayLike(Buffer, "le", 8);
+      const validator = validator2.publicKey;
+
+      const requestUri = "ipfs://QmAttestedRequest";
+      const validationRequest = await requestValidation(validationProgram, identityProgram, {
+        validationConfig,
+        agentId: agent.id,
+        agentAccount: agent.account,
+        agentOwner: agent.owner,
+        validatorAddress: validator,
+        nonce: 50,
+        requestUri,
+        requestHash: computeHash(requestUri),
+      });
+
+      const tag = Buffer.alloc(32);
+      tag.write("validated");
+      const feedbackAuth = {
+        agentId: new anchor.BN(agent.id),
+        clientAddress: validator,
+        indexLimit: new anchor.BN(1),
+        expiry: new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+        chainId: "solana-localnet",
+        identityRegistry: identityProgram.programId,
+        signerAddress: agent.owner,
+        signature: Array.from(Buffer.alloc(64)), // Ed25519 verification is TODO upstream
+      };
+      const feedbackAccount = reputationPda(
+        Buffer.from("feedback"),
+        agentIdBytes,
+        validator.toBuffer(),
+        new anchor.BN(0).toArrayLike(Buffer, "le", 8)
+      );
+      const agentReputation = reputationPda(Buffer.from("agent_reputation"), agentIdBytes);
+
+      const writable = (pubkey: PublicKey) => ({ pubkey, isSigner: false, isWritable: true });
+      const readonly = (pubkey: PublicKey) => ({ pubkey, isSigner: false, isWritable: false });
+
+      await validationProgram.methods
+        .respondAndAttest(
+          90,
+          "ipfs://QmAttestedResponse",
+          Array.from(computeHash("ipfs://QmAttestedResponse")),
+          Array.from(tag),
+          Array.from(Buffer.alloc(32)),
+          new anchor.BN(0),
+          feedbackAuth
+        )
+        .accounts({
+          config: validationConfig,
+          validator,
+          validationRequest,
+          rewardPool: null,
+        })
+        .remainingAccounts([
+          readonly(reputationProgram.programId),
+          readonly(reputationConfig),
+          readonly(agent.mint.publicKey),
+          readonly(agent.account),
+          writable(reputationPda(Buffer.from("client_index"), agentIdBytes, validator.toBuffer())),
+          writable(feedbackAccount),
+          writable(agentReputation),
+          writable(reputationPda(Buffer.from("tag_reputation"), agentIdBytes, tag)),
+          readonly(reputationProgram.programId), // tag2_reputation: none
+          writable(reputationPda(Buffer.from("histogram"), agentIdBytes)),
+          readonly(reputationPda(Buffer.from("client_block"), agentIdBytes, validator.toBuffer())),
+          readonly(reputationProgram.programId), // treasury: none
+          readonly(identityProgram.programId),
+        ])
+        .signers([validator2])
+        .rpc();
+
+      const request = await validationProgram.account.validationRequest.fetch(validationRequest);
+      assert.equal(request.response, 90);
+
+      const feedback = await reputationProgram.account.feedbackAccount.fetch(feedbackAccount);
+      assert.equal(feedback.clientAddress.toBase58(), validator.toBase58());
+      assert.equal(feedback.score, 90);
+      assert.deepEqual(feedback.tag1, Array.from(tag));
+
+      const reputation = await reputationProgram.account.agentReputationMetadata.fetch(agentReputation);
+      assert.equal(reputation.totalFeedbacks.toNumber(), 1);
+      assert.equal(reputation.averageScore, 90);
+    } finally {
+      await validationProgram.methods
+        .setReputationRegistry(null)
+        .accounts({ config: validationConfig, authority: provider.wallet.publicKey })
+        .rpc();
+    }
+
+    console.log("✅ Passing validation recorded and attested as feedback");
+  });
 });